@@ -0,0 +1,85 @@
+use syn::{Attribute, Error, Lit, LitStr, Meta, NestedMeta, Result};
+
+/// Options of a field, given with `#[cdrs(...)]` attributes.
+#[derive(Default)]
+pub struct FieldAttributes {
+    /// Name of the column or UDT field the field is read from, if different.
+    pub rename: Option<String>,
+    /// Whether missing and null values are replaced with `Default::default()`.
+    pub default: bool,
+}
+
+impl FieldAttributes {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut attributes = FieldAttributes::default();
+
+        for nested in cdrs_attributes(attrs)? {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(ref name_value))
+                    if name_value.path.is_ident("rename") =>
+                {
+                    attributes.rename = Some(string_value(&name_value.lit)?.value());
+                }
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("default") => {
+                    attributes.default = true;
+                }
+                nested => return Err(Error::new_spanned(nested, "unknown cdrs attribute")),
+            }
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// Options of an enum, given with `#[cdrs(...)]` attributes.
+#[derive(Default)]
+pub struct EnumAttributes {
+    /// CQL type variants are represented as.
+    pub repr: Option<LitStr>,
+    /// Case names of variants are converted into, unless they're renamed.
+    pub rename_all: Option<LitStr>,
+}
+
+impl EnumAttributes {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut attributes = EnumAttributes::default();
+
+        for nested in cdrs_attributes(attrs)? {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(ref name_value))
+                    if name_value.path.is_ident("repr") =>
+                {
+                    attributes.repr = Some(string_value(&name_value.lit)?.clone());
+                }
+                NestedMeta::Meta(Meta::NameValue(ref name_value))
+                    if name_value.path.is_ident("rename_all") =>
+                {
+                    attributes.rename_all = Some(string_value(&name_value.lit)?.clone());
+                }
+                nested => return Err(Error::new_spanned(nested, "unknown cdrs attribute")),
+            }
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// Returns options given with all `#[cdrs(...)]` attributes.
+fn cdrs_attributes(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+    let mut options = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("cdrs")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => options.extend(list.nested),
+            meta => return Err(Error::new_spanned(meta, "expected `#[cdrs(...)]`")),
+        }
+    }
+
+    Ok(options)
+}
+
+pub fn string_value(lit: &Lit) -> Result<&LitStr> {
+    match lit {
+        Lit::Str(value) => Ok(value),
+        lit => Err(Error::new_spanned(lit, "expected a string")),
+    }
+}
@@ -0,0 +1,282 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{Attribute, Data, DeriveInput, Error, Ident, LitStr, Meta, Result, Token, Visibility};
+
+use crate::attributes::string_value;
+use crate::cql::{check, Column, CqlType, Native, Schema};
+use crate::try_from_row;
+
+/// Environment variable with the path of the schema dump, if it's not given with `#[schema]`.
+const SCHEMA_VAR: &str = "CDRS_SCHEMA";
+
+/// `#[schema = "path"] vis struct Name = "query";`
+pub struct CheckedQuery {
+    attrs: Vec<Attribute>,
+    schema: Option<LitStr>,
+    vis: Visibility,
+    name: Ident,
+    query: LitStr,
+}
+
+impl Parse for CheckedQuery {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attrs = vec![];
+        let mut schema = None;
+        for attr in input.call(Attribute::parse_outer)? {
+            if !attr.path.is_ident("schema") {
+                attrs.push(attr);
+                continue;
+            }
+
+            match attr.parse_meta()? {
+                Meta::NameValue(name_value) => {
+                    schema = Some(string_value(&name_value.lit)?.clone());
+                }
+                meta => return Err(Error::new_spanned(meta, "expected `#[schema = \"...\"]`")),
+            }
+        }
+
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let query = input.parse()?;
+        input.parse::<Option<Token![;]>>()?;
+
+        Ok(CheckedQuery {
+            attrs,
+            schema,
+            vis,
+            name,
+            query,
+        })
+    }
+}
+
+pub fn expand(input: CheckedQuery) -> Result<TokenStream> {
+    let (path, span) = match input.schema {
+        Some(ref schema) => (schema.value(), schema.span()),
+        None => match env::var(SCHEMA_VAR) {
+            Ok(path) => (path, input.query.span()),
+            Err(_) => {
+                return Err(Error::new(
+                    input.query.span(),
+                    format!(
+                        "no schema to check the query against, set {} or add `#[schema = \"...\"]`",
+                        SCHEMA_VAR
+                    ),
+                ))
+            }
+        },
+    };
+
+    // relative paths are relative to the crate being compiled
+    let path = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(path);
+    let cql = fs::read_to_string(&path).map_err(|err| {
+        Error::new(
+            span,
+            format!("cannot read schema {}: {}", path.display(), err),
+        )
+    })?;
+    let schema = Schema::parse(&cql)
+        .map_err(|err| Error::new(span, format!("invalid schema {}: {}", path.display(), err)))?;
+
+    let CheckedQuery {
+        attrs,
+        vis,
+        name,
+        query,
+        ..
+    } = input;
+    let statement = check(&schema, &query.value()).map_err(|err| Error::new(query.span(), err))?;
+
+    let fields = statement
+        .binds
+        .iter()
+        .map(|bind| field_ident(&bind.name, &query))
+        .collect::<Result<Vec<_>>>()?;
+    let types = statement.binds.iter().map(|bind| {
+        let ty = bind_type(&bind.ty);
+        if bind.nullable {
+            quote! { ::std::option::Option<#ty> }
+        } else {
+            ty
+        }
+    });
+
+    let rows = match statement.columns {
+        Some(ref columns) => expand_row(&vis, &name, columns, &query)?,
+        None => quote! {},
+    };
+    let path = path.to_string_lossy();
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(Debug, Clone)]
+        #vis struct #name {
+            #(pub #fields: #types,)*
+        }
+
+        impl #name {
+            /// The checked query, which values of the struct are bound to.
+            pub const QUERY: &'static str = #query;
+        }
+
+        impl ::std::convert::From<#name> for ::cdrs_tokio::query::QueryValues {
+            fn from(values: #name) -> Self {
+                ::cdrs_tokio::query::QueryValues::SimpleValues(::std::vec![
+                    #(::cdrs_tokio::types::value::Value::from(values.#fields),)*
+                ])
+            }
+        }
+
+        #rows
+
+        // rebuilds the query when the schema changes
+        const _: &str = ::std::include_str!(#path);
+    })
+}
+
+/// Generates the struct rows returned by a `SELECT` query are converted into. Columns which
+/// aren't part of the primary key are optional, since they can be null.
+fn expand_row(
+    vis: &Visibility,
+    name: &Ident,
+    columns: &[Column],
+    query: &LitStr,
+) -> Result<TokenStream> {
+    let row_name = format_ident!("{}Row", name);
+    let doc = format!("Row returned by [`{}`].", name);
+
+    let mut fields = vec![];
+    for column in columns {
+        let ident = field_ident(&column.name, query)?;
+        let rename = if ident.to_string().trim_start_matches("r#") != column.name {
+            let name = &column.name;
+            quote! { #[cdrs(rename = #name)] }
+        } else {
+            quote! {}
+        };
+
+        let ty = rust_type(&column.ty, false)
+            .unwrap_or_else(|| quote! { ::cdrs_tokio::types::cql_value::CqlValue });
+        let ty = if column.primary_key {
+            ty
+        } else {
+            quote! { ::std::option::Option<#ty> }
+        };
+
+        fields.push(quote! { #rename pub #ident: #ty });
+    }
+
+    let mut row: DeriveInput = syn::parse2(quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        #vis struct #row_name {
+            #(#fields,)*
+        }
+    })?;
+    let try_from_row = try_from_row::expand(row.clone())?;
+
+    // `#[cdrs]` attributes are only known to derives
+    if let Data::Struct(ref mut data) = row.data {
+        for field in data.fields.iter_mut() {
+            field.attrs.clear();
+        }
+    }
+
+    Ok(quote! {
+        #row
+
+        #try_from_row
+    })
+}
+
+/// Returns the identifier of a field named after a column, which is lowercased, or a raw
+/// identifier for keywords.
+fn field_ident(name: &str, query: &LitStr) -> Result<Ident> {
+    let name = name.to_lowercase();
+    if name.is_empty()
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || matches!(name.as_str(), "_" | "crate" | "self" | "super")
+    {
+        return Err(Error::new_spanned(
+            query,
+            format!("`{}` isn't a valid name of a field", name),
+        ));
+    }
+
+    Ok(syn::parse_str(&name).unwrap_or_else(|_| Ident::new_raw(&name, Span::call_site())))
+}
+
+/// Returns the type values of given CQL type are bound as. Types without a Rust type are bound
+/// as encoded `Bytes`, e.g. built with `UdtBuilder`.
+fn bind_type(ty: &CqlType) -> TokenStream {
+    rust_type(ty, true).unwrap_or_else(|| quote! { ::cdrs_tokio::types::value::Bytes })
+}
+
+/// Returns the Rust type values of given CQL type are read or bound as, or `None` for UDTs,
+/// tuples, durations and custom types, and collections of them. Vectors are read as arrays and
+/// bound as `CqlVector`.
+fn rust_type(ty: &CqlType, bound: bool) -> Option<TokenStream> {
+    Some(match ty {
+        CqlType::Native(native) => native_type(*native),
+        CqlType::List(element) | CqlType::Set(element) => {
+            let element = rust_type(element, bound)?;
+            quote! { ::std::vec::Vec<#element> }
+        }
+        CqlType::Map(key, value) => {
+            // maps of floating point keys can't be `HashMap`s
+            if matches!(
+                **key,
+                CqlType::Native(Native::Float | Native::Double | Native::Decimal)
+            ) {
+                return None;
+            }
+
+            let key = rust_type(key, bound)?;
+            let value = rust_type(value, bound)?;
+            quote! { ::std::collections::HashMap<#key, #value> }
+        }
+        CqlType::Vector(element, dimension) => {
+            let element = match **element {
+                CqlType::Native(native @ Native::Float)
+                | CqlType::Native(native @ Native::Double)
+                | CqlType::Native(native @ Native::Int)
+                | CqlType::Native(native @ Native::Bigint) => native_type(native),
+                _ => return None,
+            };
+            if bound {
+                quote! { ::cdrs_tokio::types::vector::CqlVector<#element> }
+            } else {
+                let dimension = Literal::usize_unsuffixed(*dimension);
+                quote! { [#element; #dimension] }
+            }
+        }
+        CqlType::Opaque(_) => return None,
+    })
+}
+
+fn native_type(native: Native) -> TokenStream {
+    match native {
+        Native::Ascii | Native::Text => quote! { ::std::string::String },
+        Native::Bigint | Native::Counter | Native::Time | Native::Timestamp => quote! { i64 },
+        Native::Blob => quote! { ::cdrs_tokio::types::blob::Blob },
+        Native::Boolean => quote! { bool },
+        Native::Date | Native::Int => quote! { i32 },
+        Native::Decimal => quote! { ::cdrs_tokio::types::decimal::Decimal },
+        Native::Double => quote! { f64 },
+        Native::Float => quote! { f32 },
+        Native::Inet => quote! { ::std::net::IpAddr },
+        Native::Smallint => quote! { i16 },
+        Native::Timeuuid | Native::Uuid => quote! { ::cdrs_tokio::uuid::Uuid },
+        Native::Tinyint => quote! { i8 },
+        Native::Varint => quote! { i128 },
+    }
+}
@@ -0,0 +1,1194 @@
+//! Minimal CQL parser used by `checked_query!`. It reads tables of schema dumps and checks
+//! queries against them, finding the type of each bind marker.
+
+use std::fmt;
+
+type Result<T> = std::result::Result<T, String>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// Unquoted identifier or keyword, as written.
+    Word(String),
+    /// Quoted identifier, which is case sensitive.
+    Quoted(String),
+    /// String literal.
+    Str,
+    /// Number, UUID or blob literal.
+    Number(String),
+    Punct(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Quoted(name) => write!(f, "`\"{}\"`", name),
+            Token::Str => write!(f, "string"),
+            Token::Number(number) => write!(f, "`{}`", number),
+            Token::Punct(punct) => write!(f, "`{}`", punct),
+        }
+    }
+}
+
+const PUNCTS: &[&str] = &[
+    "<=", ">=", "!=", "+=", "-=", "(", ")", ",", ";", "?", "*", ".", "=", "<", ">", "+", "-", "[",
+    "]", "{", "}", ":",
+];
+
+fn tokenize(cql: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = cql.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let rest: String = chars[i..].iter().take(36).collect();
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if rest.starts_with("--") || rest.starts_with("//") {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if rest.starts_with("/*") {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if rest.starts_with("$$") {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '$' && chars.get(i + 1) == Some(&'$')) {
+                i += 1;
+            }
+            i += 2;
+            tokens.push(Token::Str);
+        } else if c == '\'' || c == '"' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some(&quote) if quote == c && chars.get(i + 1) == Some(&c) => {
+                        value.push(c);
+                        i += 2;
+                    }
+                    Some(&quote) if quote == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&other) => {
+                        value.push(other);
+                        i += 1;
+                    }
+                    None => return Err("unterminated quotes".to_string()),
+                }
+            }
+
+            tokens.push(if c == '"' {
+                Token::Quoted(value)
+            } else {
+                Token::Str
+            });
+        } else if is_uuid(&rest) {
+            tokens.push(Token::Number(rest));
+            i += 36;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() {
+                let exponent_sign = matches!(chars[i], '+' | '-')
+                    && matches!(chars[i - 1], 'e' | 'E')
+                    && chars.get(i + 1).is_some_and(char::is_ascii_digit);
+                if chars[i].is_ascii_alphanumeric() || chars[i] == '.' || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|punct| rest.starts_with(*punct))
+                .ok_or_else(|| format!("unexpected character `{}`", c))?;
+            tokens.push(Token::Punct(punct));
+            i += punct.len();
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Native CQL types.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Native {
+    Ascii,
+    Bigint,
+    Blob,
+    Boolean,
+    Counter,
+    Date,
+    Decimal,
+    Double,
+    Float,
+    Inet,
+    Int,
+    Smallint,
+    Text,
+    Time,
+    Timestamp,
+    Timeuuid,
+    Tinyint,
+    Uuid,
+    Varint,
+}
+
+impl Native {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "ascii" => Native::Ascii,
+            "bigint" => Native::Bigint,
+            "blob" => Native::Blob,
+            "boolean" => Native::Boolean,
+            "counter" => Native::Counter,
+            "date" => Native::Date,
+            "decimal" => Native::Decimal,
+            "double" => Native::Double,
+            "float" => Native::Float,
+            "inet" => Native::Inet,
+            "int" => Native::Int,
+            "smallint" => Native::Smallint,
+            "text" | "varchar" => Native::Text,
+            "time" => Native::Time,
+            "timestamp" => Native::Timestamp,
+            "timeuuid" => Native::Timeuuid,
+            "tinyint" => Native::Tinyint,
+            "uuid" => Native::Uuid,
+            "varint" => Native::Varint,
+            _ => return None,
+        })
+    }
+}
+
+/// Type of a column. Frozen types are the same as their unfrozen types.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CqlType {
+    Native(Native),
+    List(Box<CqlType>),
+    Set(Box<CqlType>),
+    Map(Box<CqlType>, Box<CqlType>),
+    Vector(Box<CqlType>, usize),
+    /// UDTs, tuples, durations and custom types, given by name.
+    Opaque(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Column {
+    pub name: String,
+    pub ty: CqlType,
+    /// Whether the column is a partition key or clustering column, so it's never null.
+    pub primary_key: bool,
+}
+
+#[derive(Debug)]
+pub struct Table {
+    pub keyspace: Option<String>,
+    pub name: String,
+    pub columns: Vec<Column>,
+    /// Names of the partition key columns.
+    pub partition_key: Vec<String>,
+}
+
+impl Table {
+    fn column(&self, name: &str) -> Result<&Column> {
+        self.columns
+            .iter()
+            .find(|column| column.name == name)
+            .ok_or_else(|| format!("unknown column `{}` of table `{}`", name, self.name))
+    }
+}
+
+/// Tables and materialized views of a schema dump, e.g. the output of `DESCRIBE KEYSPACE`.
+#[derive(Debug, Default)]
+pub struct Schema {
+    tables: Vec<Table>,
+}
+
+impl Schema {
+    /// Reads `CREATE TABLE` and `CREATE MATERIALIZED VIEW` statements, ignoring other ones.
+    /// Tables without a keyspace belong to the keyspace of the last `USE` statement, if any.
+    pub fn parse(cql: &str) -> Result<Self> {
+        let mut schema = Schema::default();
+        let mut keyspace = None;
+
+        let tokens = tokenize(cql)?;
+        for statement in tokens.split(|token| *token == Token::Punct(";")) {
+            let mut parser = Parser::new(statement.to_vec());
+            if parser.eat_keyword("USE") {
+                keyspace = Some(parser.name()?);
+            } else if parser.eat_keyword("CREATE") {
+                let table = if parser.eat_keyword("TABLE") || parser.eat_keyword("COLUMNFAMILY") {
+                    parser.create_table(keyspace.as_ref())?
+                } else if parser.eat_keyword("MATERIALIZED") {
+                    parser.expect_keyword("VIEW")?;
+                    parser.create_view(keyspace.as_ref(), &schema)?
+                } else {
+                    continue;
+                };
+                schema.tables.push(table);
+            }
+        }
+
+        Ok(schema)
+    }
+
+    fn table(&self, keyspace: Option<&str>, name: &str) -> Result<&Table> {
+        let mut tables = self.tables.iter().filter(|table| {
+            table.name == name && (keyspace.is_none() || table.keyspace.as_deref() == keyspace)
+        });
+
+        match (tables.next(), tables.next()) {
+            (Some(table), None) => Ok(table),
+            (Some(_), Some(_)) => Err(format!(
+                "table `{}` is in several keyspaces, qualify it with one",
+                name
+            )),
+            (None, _) => match keyspace {
+                Some(keyspace) => Err(format!("unknown table `{}.{}`", keyspace, name)),
+                None => Err(format!("unknown table `{}`", name)),
+            },
+        }
+    }
+}
+
+/// Bind marker of a query, named after the column or option it's bound to.
+#[derive(Debug)]
+pub struct Bind {
+    pub name: String,
+    pub ty: CqlType,
+    /// Whether null can be bound, e.g. for columns which aren't part of the primary key.
+    pub nullable: bool,
+}
+
+/// Query checked against a schema.
+#[derive(Debug)]
+pub struct Statement {
+    pub binds: Vec<Bind>,
+    /// Columns returned by `SELECT` statements.
+    pub columns: Option<Vec<Column>>,
+}
+
+/// Checks a `SELECT`, `INSERT`, `UPDATE` or `DELETE` statement against a schema, returning the
+/// types of its bind markers and selected columns.
+pub fn check(schema: &Schema, query: &str) -> Result<Statement> {
+    let mut checker = Checker {
+        schema,
+        parser: Parser::new(tokenize(query)?),
+        binds: vec![],
+    };
+
+    let columns = if checker.parser.eat_keyword("SELECT") {
+        Some(checker.select()?)
+    } else if checker.parser.eat_keyword("INSERT") {
+        checker.insert()?;
+        None
+    } else if checker.parser.eat_keyword("UPDATE") {
+        checker.update()?;
+        None
+    } else if checker.parser.eat_keyword("DELETE") {
+        checker.delete()?;
+        None
+    } else {
+        return Err("only SELECT, INSERT, UPDATE and DELETE statements can be checked".to_string());
+    };
+
+    checker.parser.eat_punct(";");
+    if let Some(token) = checker.parser.peek() {
+        return Err(format!("unexpected {}", token));
+    }
+
+    Ok(Statement {
+        binds: checker.binds,
+        columns,
+    })
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn peek_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.peek_punct(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T> {
+        match self.peek() {
+            Some(token) => Err(format!("expected {}, found {}", expected, token)),
+            None => Err(format!(
+                "expected {}, found the end of the statement",
+                expected
+            )),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", keyword))
+        }
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<()> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", punct))
+        }
+    }
+
+    /// Reads a name, which is lowercased unless it's quoted.
+    fn name(&mut self) -> Result<String> {
+        let name = match self.peek() {
+            Some(Token::Word(word)) => word.to_lowercase(),
+            Some(Token::Quoted(name)) => name.clone(),
+            _ => return self.unexpected("a name"),
+        };
+        self.pos += 1;
+        Ok(name)
+    }
+
+    /// Reads a name of a table, which may be qualified with a keyspace.
+    fn table_name(&mut self) -> Result<(Option<String>, String)> {
+        let name = self.name()?;
+        if self.eat_punct(".") {
+            Ok((Some(name), self.name()?))
+        } else {
+            Ok((None, name))
+        }
+    }
+
+    fn if_not_exists(&mut self) -> Result<()> {
+        if self.eat_keyword("IF") {
+            self.expect_keyword("NOT")?;
+            self.expect_keyword("EXISTS")?;
+        }
+        Ok(())
+    }
+
+    fn names(&mut self) -> Result<Vec<String>> {
+        let mut names = vec![self.name()?];
+        while self.eat_punct(",") {
+            names.push(self.name()?);
+        }
+        Ok(names)
+    }
+
+    fn cql_type(&mut self) -> Result<CqlType> {
+        if self.peek() == Some(&Token::Str) {
+            self.pos += 1;
+            return Ok(CqlType::Opaque("custom".to_string()));
+        }
+
+        let name = self.name()?;
+        let ty = match name.as_str() {
+            "frozen" => {
+                self.expect_punct("<")?;
+                self.cql_type()?
+            }
+            "list" => {
+                self.expect_punct("<")?;
+                CqlType::List(Box::new(self.cql_type()?))
+            }
+            "set" => {
+                self.expect_punct("<")?;
+                CqlType::Set(Box::new(self.cql_type()?))
+            }
+            "map" => {
+                self.expect_punct("<")?;
+                let key = self.cql_type()?;
+                self.expect_punct(",")?;
+                CqlType::Map(Box::new(key), Box::new(self.cql_type()?))
+            }
+            "tuple" => {
+                self.expect_punct("<")?;
+                self.cql_type()?;
+                while self.eat_punct(",") {
+                    self.cql_type()?;
+                }
+                CqlType::Opaque(name)
+            }
+            "vector" => {
+                self.expect_punct("<")?;
+                let element = self.cql_type()?;
+                self.expect_punct(",")?;
+                let dimension = match self.peek() {
+                    Some(Token::Number(number)) => number.parse().ok(),
+                    _ => None,
+                };
+                let dimension = match dimension {
+                    Some(dimension) => dimension,
+                    None => return self.unexpected("a dimension"),
+                };
+                self.pos += 1;
+                CqlType::Vector(Box::new(element), dimension)
+            }
+            _ => {
+                if self.eat_punct(".") {
+                    self.name()?;
+                }
+                return Ok(Native::from_name(&name)
+                    .map(CqlType::Native)
+                    .unwrap_or(CqlType::Opaque(name)));
+            }
+        };
+
+        self.expect_punct(">")?;
+        Ok(ty)
+    }
+
+    /// Reads names of a `PRIMARY KEY (...)` clause, which may have a composite partition key,
+    /// returning the names of all primary key columns and of the partition key columns.
+    fn primary_key(&mut self) -> Result<(Vec<String>, Vec<String>)> {
+        self.expect_punct("(")?;
+        let mut names = vec![];
+        let mut partition_key = None;
+        loop {
+            if self.eat_punct("(") {
+                names.extend(self.names()?);
+                self.expect_punct(")")?;
+            } else {
+                names.push(self.name()?);
+            }
+            partition_key.get_or_insert_with(|| names.clone());
+
+            if !self.eat_punct(",") {
+                break;
+            }
+        }
+        self.expect_punct(")")?;
+        Ok((names, partition_key.unwrap_or_default()))
+    }
+
+    fn create_table(&mut self, keyspace: Option<&String>) -> Result<Table> {
+        self.if_not_exists()?;
+        let (table_keyspace, name) = self.table_name()?;
+
+        let mut columns = vec![];
+        let mut primary_key = (vec![], vec![]);
+        self.expect_punct("(")?;
+        loop {
+            if self.eat_keyword("PRIMARY") {
+                self.expect_keyword("KEY")?;
+                primary_key = self.primary_key()?;
+            } else {
+                let name = self.name()?;
+                let ty = self.cql_type()?;
+                self.eat_keyword("STATIC");
+                if self.eat_keyword("PRIMARY") {
+                    self.expect_keyword("KEY")?;
+                    primary_key = (vec![name.clone()], vec![name.clone()]);
+                }
+                columns.push(Column {
+                    name,
+                    ty,
+                    primary_key: false,
+                });
+            }
+
+            if !self.eat_punct(",") {
+                break;
+            }
+        }
+        self.expect_punct(")")?;
+
+        let mut table = Table {
+            keyspace: table_keyspace.or_else(|| keyspace.cloned()),
+            name,
+            columns,
+            partition_key: vec![],
+        };
+        set_primary_key(&mut table, &primary_key)?;
+        Ok(table)
+    }
+
+    fn create_view(&mut self, keyspace: Option<&String>, schema: &Schema) -> Result<Table> {
+        self.if_not_exists()?;
+        let (view_keyspace, name) = self.table_name()?;
+        let view_keyspace = view_keyspace.or_else(|| keyspace.cloned());
+
+        self.expect_keyword("AS")?;
+        self.expect_keyword("SELECT")?;
+        let selected = if self.eat_punct("*") {
+            None
+        } else {
+            Some(self.names()?)
+        };
+        self.expect_keyword("FROM")?;
+        let (base_keyspace, base_name) = self.table_name()?;
+        let base = schema.table(
+            base_keyspace.as_deref().or(view_keyspace.as_deref()),
+            &base_name,
+        )?;
+
+        let columns = match selected {
+            Some(selected) => selected
+                .iter()
+                .map(|name| base.column(name).cloned())
+                .collect::<Result<_>>()?,
+            None => base.columns.clone(),
+        };
+
+        while self.peek().is_some() && !self.peek_keyword("PRIMARY") {
+            self.pos += 1;
+        }
+        self.expect_keyword("PRIMARY")?;
+        self.expect_keyword("KEY")?;
+        let primary_key = self.primary_key()?;
+
+        let mut view = Table {
+            keyspace: view_keyspace,
+            name,
+            columns,
+            partition_key: vec![],
+        };
+        set_primary_key(&mut view, &primary_key)?;
+        Ok(view)
+    }
+}
+
+fn set_primary_key(table: &mut Table, primary_key: &(Vec<String>, Vec<String>)) -> Result<()> {
+    let (primary_key, partition_key) = primary_key;
+    if primary_key.is_empty() {
+        return Err(format!("table `{}` has no primary key", table.name));
+    }
+
+    for name in primary_key {
+        table.column(name)?;
+    }
+    for column in &mut table.columns {
+        column.primary_key = primary_key.contains(&column.name);
+    }
+    table.partition_key = partition_key.clone();
+    Ok(())
+}
+
+/// Checks that relations of a `WHERE` clause restrict all partition key columns with `=` or
+/// `IN`, as required by `UPDATE` and `DELETE` statements.
+fn check_partition_key(table: &Table, restricted: &[String]) -> Result<()> {
+    match table
+        .partition_key
+        .iter()
+        .find(|name| !restricted.contains(name))
+    {
+        Some(missing) => Err(format!(
+            "missing relation of partition key column `{}`",
+            missing
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Type of elements of a list or set, values of a map or elements of a vector.
+fn element_type(column: &Column) -> Result<CqlType> {
+    match column.ty {
+        CqlType::List(ref element)
+        | CqlType::Set(ref element)
+        | CqlType::Vector(ref element, _) => Ok((**element).clone()),
+        CqlType::Map(_, ref value) => Ok((**value).clone()),
+        _ => Err(format!("column `{}` isn't a collection", column.name)),
+    }
+}
+
+/// Type of indexes of a list or keys of a map, which elements are accessed with `column[key]`.
+fn key_type(column: &Column) -> Result<CqlType> {
+    match column.ty {
+        CqlType::List(_) => Ok(CqlType::Native(Native::Int)),
+        CqlType::Map(ref key, _) => Ok((**key).clone()),
+        _ => Err(format!("column `{}` isn't a list or map", column.name)),
+    }
+}
+
+/// Name of the bind marker of indexes of a list or keys of a map.
+fn key_name(column: &Column) -> String {
+    match column.ty {
+        CqlType::List(_) => format!("{}_index", column.name),
+        _ => format!("{}_key", column.name),
+    }
+}
+
+struct Checker<'a> {
+    schema: &'a Schema,
+    parser: Parser,
+    binds: Vec<Bind>,
+}
+
+impl<'a> Checker<'a> {
+    fn table(&mut self) -> Result<&'a Table> {
+        let (keyspace, name) = self.parser.table_name()?;
+        self.schema.table(keyspace.as_deref(), &name)
+    }
+
+    /// Adds a bind marker, suffixing its name with a number if it's already used.
+    fn bind(&mut self, name: &str, ty: CqlType, nullable: bool) {
+        let mut unique_name = name.to_string();
+        let mut n = 1;
+        while self.binds.iter().any(|bind| bind.name == unique_name) {
+            n += 1;
+            unique_name = format!("{}_{}", name, n);
+        }
+
+        self.binds.push(Bind {
+            name: unique_name,
+            ty,
+            nullable,
+        });
+    }
+
+    /// Reads a bind marker of given type or a literal.
+    fn term(&mut self, name: &str, ty: CqlType, nullable: bool) -> Result<()> {
+        if self.parser.eat_punct("?") {
+            self.bind(name, ty, nullable);
+            Ok(())
+        } else if self.parser.peek_punct(":") {
+            Err("named bind markers aren't supported, use `?` instead".to_string())
+        } else {
+            self.literal()
+        }
+    }
+
+    /// Skips a literal, a collection literal or a function call, which can't have bind markers.
+    fn literal(&mut self) -> Result<()> {
+        let token = match self.parser.peek() {
+            Some(token) => token.clone(),
+            None => return self.parser.unexpected("a value"),
+        };
+        self.parser.pos += 1;
+
+        match token {
+            Token::Str | Token::Number(_) => Ok(()),
+            Token::Punct("-") => self.literal(),
+            Token::Word(_) if self.parser.eat_punct("(") => self.skip_group(")"),
+            Token::Word(_) => Ok(()),
+            Token::Punct("(") => self.skip_group(")"),
+            Token::Punct("[") => self.skip_group("]"),
+            Token::Punct("{") => self.skip_group("}"),
+            _ => {
+                self.parser.pos -= 1;
+                self.parser.unexpected("a value")
+            }
+        }
+    }
+
+    fn skip_group(&mut self, close: &str) -> Result<()> {
+        loop {
+            match self.parser.peek() {
+                Some(Token::Punct(p)) if *p == close => {
+                    self.parser.pos += 1;
+                    return Ok(());
+                }
+                Some(Token::Punct("?")) => {
+                    return Err(
+                        "bind markers in collection literals and function calls aren't supported"
+                            .to_string(),
+                    )
+                }
+                Some(_) => self.literal()?,
+                None => return self.parser.unexpected(&format!("`{}`", close)),
+            }
+            self.parser.eat_punct(",");
+            self.parser.eat_punct(":");
+        }
+    }
+
+    /// Reads a `LIMIT` or `PER PARTITION LIMIT` value.
+    fn limit(&mut self, name: &str) -> Result<()> {
+        self.term(name, CqlType::Native(Native::Int), false)
+    }
+
+    fn using(&mut self) -> Result<()> {
+        loop {
+            if self.parser.eat_keyword("TTL") {
+                self.term("ttl", CqlType::Native(Native::Int), false)?;
+            } else if self.parser.eat_keyword("TIMESTAMP") {
+                self.term("timestamp", CqlType::Native(Native::Bigint), false)?;
+            } else {
+                return self.parser.unexpected("`TTL` or `TIMESTAMP`");
+            }
+
+            if !self.parser.eat_keyword("AND") {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads relations of a `WHERE` clause, or conditions of an `IF` clause, which can compare
+    /// elements of collections and nulls. Returns names of columns restricted with `=` or `IN`.
+    fn relations(&mut self, table: &Table, conditions: bool) -> Result<Vec<String>> {
+        let mut restricted = vec![];
+        loop {
+            if self.parser.peek_punct("(") {
+                return Err("multi-column relations aren't supported".to_string());
+            }
+
+            let name = self.parser.name()?;
+            if self.parser.peek_punct("(") {
+                return Err(format!("relations on `{}(...)` aren't supported", name));
+            }
+            let column = table.column(&name)?;
+
+            let (name, ty) = if conditions && self.parser.eat_punct("[") {
+                self.term(&key_name(column), key_type(column)?, false)?;
+                self.parser.expect_punct("]")?;
+                (column.name.as_str(), element_type(column)?)
+            } else {
+                (column.name.as_str(), column.ty.clone())
+            };
+            let nullable = conditions && !column.primary_key;
+
+            if self.parser.eat_keyword("IN") {
+                restricted.push(column.name.clone());
+                if self.parser.eat_punct("(") {
+                    if !self.parser.eat_punct(")") {
+                        loop {
+                            self.term(name, ty.clone(), nullable)?;
+                            if !self.parser.eat_punct(",") {
+                                break;
+                            }
+                        }
+                        self.parser.expect_punct(")")?;
+                    }
+                } else {
+                    self.term(name, CqlType::List(Box::new(ty)), false)?;
+                }
+            } else if !conditions && self.parser.eat_keyword("CONTAINS") {
+                if self.parser.eat_keyword("KEY") {
+                    self.term(&key_name(column), key_type(column)?, false)?;
+                } else {
+                    self.term(name, element_type(column)?, false)?;
+                }
+            } else if !conditions && self.parser.eat_keyword("LIKE") {
+                self.term(name, CqlType::Native(Native::Text), false)?;
+            } else {
+                let operator = ["=", "<", ">", "<=", ">=", "!="]
+                    .iter()
+                    .find(|operator| self.parser.eat_punct(operator));
+                match operator {
+                    Some(&"=") => restricted.push(column.name.clone()),
+                    Some(_) => {}
+                    None => return self.parser.unexpected("an operator"),
+                }
+                self.term(name, ty, nullable)?;
+            }
+
+            if !self.parser.eat_keyword("AND") {
+                return Ok(restricted);
+            }
+        }
+    }
+
+    fn conditions(&mut self, table: &Table) -> Result<()> {
+        if !self.parser.eat_keyword("EXISTS") {
+            self.relations(table, true)?;
+        }
+        Ok(())
+    }
+
+    fn select(&mut self) -> Result<Vec<Column>> {
+        self.parser.eat_keyword("DISTINCT");
+        if self.parser.peek_keyword("JSON") {
+            return Err("SELECT JSON isn't supported".to_string());
+        }
+
+        let selected = if self.parser.eat_punct("*") {
+            None
+        } else {
+            let mut names = vec![];
+            loop {
+                let name = self.parser.name()?;
+                if self.parser.peek_punct("(") {
+                    return Err(format!("selecting `{}(...)` isn't supported", name));
+                }
+                if self.parser.peek_keyword("AS") {
+                    return Err("aliases aren't supported".to_string());
+                }
+                names.push(name);
+
+                if !self.parser.eat_punct(",") {
+                    break;
+                }
+            }
+            Some(names)
+        };
+
+        self.parser.expect_keyword("FROM")?;
+        let table = self.table()?;
+        let columns = match selected {
+            Some(names) => names
+                .iter()
+                .map(|name| table.column(name).cloned())
+                .collect::<Result<_>>()?,
+            None => table.columns.clone(),
+        };
+
+        if self.parser.eat_keyword("WHERE") {
+            self.relations(table, false)?;
+        }
+        if self.parser.eat_keyword("GROUP") {
+            self.parser.expect_keyword("BY")?;
+            for name in self.parser.names()? {
+                table.column(&name)?;
+            }
+        }
+        if self.parser.eat_keyword("ORDER") {
+            self.parser.expect_keyword("BY")?;
+            loop {
+                table.column(&self.parser.name()?)?;
+                if !self.parser.eat_keyword("ASC") {
+                    self.parser.eat_keyword("DESC");
+                }
+                if !self.parser.eat_punct(",") {
+                    break;
+                }
+            }
+        }
+        if self.parser.eat_keyword("PER") {
+            self.parser.expect_keyword("PARTITION")?;
+            self.parser.expect_keyword("LIMIT")?;
+            self.limit("per_partition_limit")?;
+        }
+        if self.parser.eat_keyword("LIMIT") {
+            self.limit("limit")?;
+        }
+        if self.parser.eat_keyword("ALLOW") {
+            self.parser.expect_keyword("FILTERING")?;
+        }
+
+        Ok(columns)
+    }
+
+    fn insert(&mut self) -> Result<()> {
+        self.parser.expect_keyword("INTO")?;
+        let table = self.table()?;
+
+        if self.parser.eat_keyword("JSON") {
+            self.term("json", CqlType::Native(Native::Text), false)?;
+            if self.parser.eat_keyword("DEFAULT") && !self.parser.eat_keyword("NULL") {
+                self.parser.expect_keyword("UNSET")?;
+            }
+        } else {
+            self.parser.expect_punct("(")?;
+            let names = self.parser.names()?;
+            self.parser.expect_punct(")")?;
+
+            let columns = names
+                .iter()
+                .map(|name| table.column(name))
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(missing) = table
+                .columns
+                .iter()
+                .find(|column| column.primary_key && !names.contains(&column.name))
+            {
+                return Err(format!("missing primary key column `{}`", missing.name));
+            }
+
+            self.parser.expect_keyword("VALUES")?;
+            self.parser.expect_punct("(")?;
+            for (i, column) in columns.iter().enumerate() {
+                if i > 0 {
+                    self.parser.expect_punct(",")?;
+                }
+                self.term(&column.name, column.ty.clone(), !column.primary_key)?;
+            }
+            if self.parser.peek_punct(",") {
+                return Err(format!("more values than the {} columns", columns.len()));
+            }
+            self.parser.expect_punct(")")?;
+        }
+
+        self.parser.if_not_exists()?;
+        if self.parser.eat_keyword("USING") {
+            self.using()?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self) -> Result<()> {
+        let table = self.table()?;
+        if self.parser.eat_keyword("USING") {
+            self.using()?;
+        }
+
+        self.parser.expect_keyword("SET")?;
+        loop {
+            let column = table.column(&self.parser.name()?)?;
+            if column.primary_key {
+                return Err(format!(
+                    "primary key column `{}` can't be updated",
+                    column.name
+                ));
+            }
+            self.assignment(column)?;
+
+            if !self.parser.eat_punct(",") {
+                break;
+            }
+        }
+
+        self.parser.expect_keyword("WHERE")?;
+        let restricted = self.relations(table, false)?;
+        check_partition_key(table, &restricted)?;
+        if self.parser.eat_keyword("IF") {
+            self.conditions(table)?;
+        }
+        Ok(())
+    }
+
+    fn assignment(&mut self, column: &Column) -> Result<()> {
+        let name = column.name.as_str();
+
+        if self.parser.eat_punct("[") {
+            self.term(&key_name(column), key_type(column)?, false)?;
+            self.parser.expect_punct("]")?;
+            self.parser.expect_punct("=")?;
+            return self.term(name, element_type(column)?, true);
+        }
+
+        if self.parser.eat_punct("+=") {
+            return self.term(name, added_type(column)?, false);
+        }
+        if self.parser.eat_punct("-=") {
+            return self.term(name, removed_type(column)?, false);
+        }
+
+        self.parser.expect_punct("=")?;
+        let same_column = match self.parser.peek() {
+            Some(Token::Word(word)) => word.to_lowercase() == name,
+            Some(Token::Quoted(quoted)) => quoted == name,
+            _ => false,
+        };
+        let next = self.parser.tokens.get(self.parser.pos + 1);
+        if same_column && next == Some(&Token::Punct("+")) {
+            self.parser.pos += 2;
+            self.term(name, added_type(column)?, false)
+        } else if same_column && next == Some(&Token::Punct("-")) {
+            self.parser.pos += 2;
+            self.term(name, removed_type(column)?, false)
+        } else {
+            self.term(name, column.ty.clone(), true)?;
+            // prepending to a list, e.g. `c = ? + c`
+            if self.parser.eat_punct("+")
+                && (self.parser.name()? != name || !matches!(column.ty, CqlType::List(_)))
+            {
+                return Err(format!("only list `{}` can be prepended to", name));
+            }
+            Ok(())
+        }
+    }
+
+    fn delete(&mut self) -> Result<()> {
+        // deleted columns are checked once the table is known
+        let start = self.parser.pos;
+        while self.parser.peek().is_some() && !self.parser.peek_keyword("FROM") {
+            self.parser.pos += 1;
+        }
+        self.parser.expect_keyword("FROM")?;
+        let table = self.table()?;
+        let end = self.parser.pos;
+
+        self.parser.pos = start;
+        if !self.parser.peek_keyword("FROM") {
+            loop {
+                let column = table.column(&self.parser.name()?)?;
+                if column.primary_key {
+                    return Err(format!(
+                        "primary key column `{}` can't be deleted",
+                        column.name
+                    ));
+                }
+                if self.parser.eat_punct("[") {
+                    self.term(&key_name(column), key_type(column)?, false)?;
+                    self.parser.expect_punct("]")?;
+                }
+
+                if !self.parser.eat_punct(",") {
+                    break;
+                }
+            }
+        }
+        self.parser.expect_keyword("FROM")?;
+        self.parser.pos = end;
+
+        if self.parser.eat_keyword("USING") {
+            self.using()?;
+        }
+        self.parser.expect_keyword("WHERE")?;
+        let restricted = self.relations(table, false)?;
+        check_partition_key(table, &restricted)?;
+        if self.parser.eat_keyword("IF") {
+            self.conditions(table)?;
+        }
+        Ok(())
+    }
+}
+
+/// Type of values added with `c = c + ?`, i.e. counter increments and collections.
+fn added_type(column: &Column) -> Result<CqlType> {
+    match column.ty {
+        CqlType::Native(Native::Counter) => Ok(CqlType::Native(Native::Bigint)),
+        CqlType::List(_) | CqlType::Set(_) | CqlType::Map(..) => Ok(column.ty.clone()),
+        _ => Err(format!(
+            "column `{}` isn't a counter or collection",
+            column.name
+        )),
+    }
+}
+
+/// Type of values removed with `c = c - ?`, where keys of maps are removed as sets.
+fn removed_type(column: &Column) -> Result<CqlType> {
+    match column.ty {
+        CqlType::Map(ref key, _) => Ok(CqlType::Set(key.clone())),
+        _ => added_type(column),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "
+        USE ks;
+        CREATE TABLE users (
+            id int,
+            day date,
+            name text,
+            tags list<text>,
+            PRIMARY KEY ((id), day)
+        ) WITH comment = 'a; b';
+        CREATE TABLE other.users (id int PRIMARY KEY);
+    ";
+
+    fn error(query: &str) -> String {
+        check(&Schema::parse(SCHEMA).unwrap(), query).unwrap_err()
+    }
+
+    #[test]
+    fn finds_types_of_bind_markers() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let statement = check(
+            &schema,
+            "UPDATE ks.users SET tags[?] = ? WHERE id = ? AND day IN ? IF name = ?;",
+        )
+        .unwrap();
+
+        let binds: Vec<_> = statement
+            .binds
+            .iter()
+            .map(|bind| (bind.name.as_str(), &bind.ty, bind.nullable))
+            .collect();
+        assert_eq!(
+            binds,
+            vec![
+                ("tags_index", &CqlType::Native(Native::Int), false),
+                ("tags", &CqlType::Native(Native::Text), true),
+                ("id", &CqlType::Native(Native::Int), false),
+                (
+                    "day",
+                    &CqlType::List(Box::new(CqlType::Native(Native::Date))),
+                    false
+                ),
+                ("name", &CqlType::Native(Native::Text), true),
+            ]
+        );
+        assert!(statement.columns.is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        assert_eq!(
+            error("SELECT nmae FROM ks.users"),
+            "unknown column `nmae` of table `users`"
+        );
+        assert_eq!(error("SELECT * FROM ks.user"), "unknown table `ks.user`");
+        assert_eq!(
+            error("SELECT * FROM users"),
+            "table `users` is in several keyspaces, qualify it with one"
+        );
+        assert_eq!(
+            error("INSERT INTO ks.users (id, name) VALUES (?, ?)"),
+            "missing primary key column `day`"
+        );
+        assert_eq!(
+            error("UPDATE ks.users SET id = ? WHERE day = ?"),
+            "primary key column `id` can't be updated"
+        );
+        assert_eq!(
+            error("UPDATE ks.users SET name = name + ? WHERE id = ?"),
+            "column `name` isn't a counter or collection"
+        );
+        assert_eq!(
+            error("UPDATE ks.users SET name = ? WHERE day = ?"),
+            "missing relation of partition key column `id`"
+        );
+        assert_eq!(
+            error("DELETE FROM ks.users WHERE id > ?"),
+            "missing relation of partition key column `id`"
+        );
+        assert_eq!(
+            error("SELECT * FROM ks.users WHERE id = :id"),
+            "named bind markers aren't supported, use `?` instead"
+        );
+        assert_eq!(
+            error("SELECT * FROM ks.users WHERE id = ? LIMT 1"),
+            "unexpected `LIMT`"
+        );
+        assert_eq!(
+            error("TRUNCATE ks.users"),
+            "only SELECT, INSERT, UPDATE and DELETE statements can be checked"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use std::convert::TryFrom;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Expr, Fields, Lit, LitStr, Result, UnOp};
+
+use crate::attributes::{EnumAttributes, FieldAttributes};
+
+/// CQL type variants of an enum are represented as.
+enum Repr {
+    Text,
+    Int(IntRepr),
+}
+
+#[derive(Clone, Copy)]
+enum IntRepr {
+    Tinyint,
+    Smallint,
+    Int,
+    Bigint,
+}
+
+impl Repr {
+    fn parse(repr: Option<&LitStr>) -> Result<Self> {
+        let repr = match repr {
+            Some(repr) => repr,
+            None => return Ok(Repr::Text),
+        };
+
+        match repr.value().as_str() {
+            "text" => Ok(Repr::Text),
+            "tinyint" => Ok(Repr::Int(IntRepr::Tinyint)),
+            "smallint" => Ok(Repr::Int(IntRepr::Smallint)),
+            "int" => Ok(Repr::Int(IntRepr::Int)),
+            "bigint" => Ok(Repr::Int(IntRepr::Bigint)),
+            _ => Err(Error::new_spanned(
+                repr,
+                "expected one of `text`, `tinyint`, `smallint`, `int` or `bigint`",
+            )),
+        }
+    }
+
+    fn rust_type(&self) -> TokenStream {
+        match self {
+            Repr::Text => quote! { ::std::string::String },
+            Repr::Int(IntRepr::Tinyint) => quote! { i8 },
+            Repr::Int(IntRepr::Smallint) => quote! { i16 },
+            Repr::Int(IntRepr::Int) => quote! { i32 },
+            Repr::Int(IntRepr::Bigint) => quote! { i64 },
+        }
+    }
+}
+
+impl IntRepr {
+    /// Returns a literal of given value, typed as the Rust type of the representation.
+    fn literal(self, value: i64) -> Option<Literal> {
+        match self {
+            IntRepr::Tinyint => i8::try_from(value).ok().map(Literal::i8_suffixed),
+            IntRepr::Smallint => i16::try_from(value).ok().map(Literal::i16_suffixed),
+            IntRepr::Int => i32::try_from(value).ok().map(Literal::i32_suffixed),
+            IntRepr::Bigint => Some(Literal::i64_suffixed(value)),
+        }
+    }
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let variants = match input.data {
+        Data::Enum(ref data) => &data.variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "CqlEnum can only be derived for enums",
+            ))
+        }
+    };
+
+    let attributes = EnumAttributes::parse(&input.attrs)?;
+    let repr = Repr::parse(attributes.repr.as_ref())?;
+    if let (Repr::Int(_), Some(rename_all)) = (&repr, &attributes.rename_all) {
+        return Err(Error::new_spanned(
+            rename_all,
+            "variants represented as integers are not renamed",
+        ));
+    }
+
+    let mut idents = vec![];
+    let mut values = vec![];
+    let mut valid_values = vec![];
+    let mut next_discriminant = 0i64;
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "CqlEnum can only be derived for enums with unit variants",
+            ));
+        }
+
+        let variant_attributes = FieldAttributes::parse(&variant.attrs)?;
+        if variant_attributes.default {
+            return Err(Error::new_spanned(
+                variant,
+                "variants can't have default values",
+            ));
+        }
+
+        let value = match repr {
+            Repr::Text => {
+                let name = match variant_attributes.rename {
+                    Some(name) => name,
+                    None => rename(&variant.ident.to_string(), attributes.rename_all.as_ref())?,
+                };
+                valid_values.push(format!("{:?}", name));
+                Literal::string(&name)
+            }
+            Repr::Int(int_repr) => {
+                if variant_attributes.rename.is_some() {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "variants represented as integers are not renamed, but given \
+                         discriminants",
+                    ));
+                }
+
+                let discriminant = match variant.discriminant {
+                    Some((_, ref expr)) => discriminant(expr)?,
+                    None => next_discriminant,
+                };
+                next_discriminant = discriminant.wrapping_add(1);
+                valid_values.push(discriminant.to_string());
+                int_repr.literal(discriminant).ok_or_else(|| {
+                    Error::new_spanned(variant, "discriminant is out of range of the CQL type")
+                })?
+            }
+        };
+
+        idents.push(&variant.ident);
+        values.push(value);
+    }
+
+    let name = &input.ident;
+    let type_name = name.to_string();
+    let valid_values = valid_values.join(", ");
+    let rust_type = repr.rust_type();
+    let matched = match repr {
+        Repr::Text => quote! { value.as_str() },
+        Repr::Int(_) => quote! { value },
+    };
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::cdrs_tokio::types::FromCqlValue for #name #type_generics
+            #where_clause
+        {
+            fn from_cql_value(
+                col_type: &::cdrs_tokio::frame::frame_result::ColTypeOption,
+                bytes: &::cdrs_tokio::types::CBytes,
+            ) -> ::cdrs_tokio::error::Result<::std::option::Option<Self>> {
+                if bytes.is_null() {
+                    return ::std::result::Result::Ok(::std::option::Option::None);
+                }
+
+                let value: #rust_type =
+                    ::cdrs_tokio::types::FromElement::from_element(col_type, bytes)?;
+                match #matched {
+                    #(#values => ::std::result::Result::Ok(
+                        ::std::option::Option::Some(#name::#idents)
+                    ),)*
+                    _ => ::std::result::Result::Err(::cdrs_tokio::error::Error::General(
+                        ::std::format!(
+                            "Invalid value {:?} of {} (valid values: {})",
+                            value,
+                            #type_name,
+                            #valid_values
+                        ),
+                    )),
+                }
+            }
+        }
+
+        impl #impl_generics ::cdrs_tokio::types::ToCqlValue for #name #type_generics
+            #where_clause
+        {
+            fn to_cql_value(&self) -> ::cdrs_tokio::types::value::Bytes {
+                match *self {
+                    #(#name::#idents => ::std::convert::Into::into(#values),)*
+                }
+            }
+        }
+    })
+}
+
+/// Reads an integer discriminant, which may be negative.
+fn discriminant(expr: &Expr) -> Result<i64> {
+    match expr {
+        Expr::Lit(lit) => match lit.lit {
+            Lit::Int(ref value) => value.base10_parse(),
+            _ => Err(Error::new_spanned(expr, "expected an integer discriminant")),
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            discriminant(&unary.expr).map(|value| -value)
+        }
+        _ => Err(Error::new_spanned(expr, "expected an integer discriminant")),
+    }
+}
+
+/// Converts a name of a variant, which is in `PascalCase`, into given case.
+fn rename(name: &str, case: Option<&LitStr>) -> Result<String> {
+    let case = match case {
+        Some(case) => case,
+        None => return Ok(name.to_string()),
+    };
+
+    let snake_case = || {
+        let mut snake_case = String::new();
+        for (i, c) in name.chars().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                snake_case.push('_');
+            }
+            snake_case.extend(c.to_lowercase());
+        }
+        snake_case
+    };
+
+    match case.value().as_str() {
+        "lowercase" => Ok(name.to_lowercase()),
+        "UPPERCASE" => Ok(name.to_uppercase()),
+        "snake_case" => Ok(snake_case()),
+        "SCREAMING_SNAKE_CASE" => Ok(snake_case().to_uppercase()),
+        _ => Err(Error::new_spanned(
+            case,
+            "expected one of `lowercase`, `UPPERCASE`, `snake_case` or `SCREAMING_SNAKE_CASE`",
+        )),
+    }
+}
@@ -0,0 +1,85 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Data, DeriveInput, Error, Field, Fields, GenericArgument, PathArguments, Result, Type};
+
+use crate::attributes::FieldAttributes;
+
+/// Returns named fields of a struct, which the derive of given trait requires.
+pub fn named_fields<'a>(
+    input: &'a DeriveInput,
+    trait_name: &str,
+) -> Result<&'a Punctuated<Field, Comma>> {
+    match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => Ok(&fields.named),
+            _ => Err(Error::new_spanned(
+                &input.ident,
+                format!(
+                    "{} can only be derived for structs with named fields",
+                    trait_name
+                ),
+            )),
+        },
+        _ => Err(Error::new_spanned(
+            &input.ident,
+            format!("{} can only be derived for structs", trait_name),
+        )),
+    }
+}
+
+/// Generates field initializers reading each field by name from `source`, which is a `Row` or a
+/// `UDT`. `contains` is the method checking if `source` has a value of a given name.
+pub fn read_fields(
+    fields: &Punctuated<Field, Comma>,
+    source: &TokenStream,
+    contains: &TokenStream,
+) -> Result<Vec<TokenStream>> {
+    let mut field_values = vec![];
+    for field in fields {
+        let attributes = FieldAttributes::parse(&field.attrs)?;
+        let ident = field.ident.as_ref().unwrap();
+        let name = attributes
+            .rename
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+
+        let value = if attributes.default {
+            quote! {
+                if #source.#contains(#name) {
+                    ::cdrs_tokio::types::IntoRustByName::get_by_name(&#source, #name)?
+                        .unwrap_or_default()
+                } else {
+                    ::std::default::Default::default()
+                }
+            }
+        } else if is_option(&field.ty) {
+            quote! { ::cdrs_tokio::types::IntoRustByName::get_by_name(&#source, #name)? }
+        } else {
+            quote! { ::cdrs_tokio::types::IntoRustByName::get_r_by_name(&#source, #name)? }
+        };
+
+        field_values.push(quote! { #ident: #value });
+    }
+
+    Ok(field_values)
+}
+
+/// Checks if a type is an `Option`, whose values are `None` for nulls.
+pub fn is_option(ty: &Type) -> bool {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last(),
+        _ => None,
+    };
+
+    match segment {
+        Some(segment) if segment.ident == "Option" => match segment.arguments {
+            PathArguments::AngleBracketed(ref arguments) => {
+                arguments.args.len() == 1
+                    && matches!(arguments.args.first(), Some(GenericArgument::Type(_)))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
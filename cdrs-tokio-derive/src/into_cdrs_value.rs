@@ -0,0 +1,36 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Result};
+
+use crate::attributes::FieldAttributes;
+use crate::fields::named_fields;
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = named_fields(&input, "IntoCDRSValue")?;
+
+    let mut field_idents = vec![];
+    for field in fields {
+        // field names don't matter for serialization, but attributes are still validated
+        FieldAttributes::parse(&field.attrs)?;
+        field_idents.push(field.ident.as_ref().unwrap());
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::std::convert::From<#name #type_generics>
+            for ::cdrs_tokio::types::value::Bytes #where_clause
+        {
+            fn from(value: #name #type_generics) -> Self {
+                let mut bytes = ::std::vec::Vec::new();
+                #(
+                    bytes.extend(::cdrs_tokio::frame::IntoBytes::into_cbytes(
+                        &::cdrs_tokio::types::value::Value::from(value.#field_idents),
+                    ));
+                )*
+                ::cdrs_tokio::types::value::Bytes::new(bytes)
+            }
+        }
+    })
+}
@@ -0,0 +1,108 @@
+//! Derive macros and `checked_query!` for **cdrs-tokio**, re-exported by it with the `derive`
+//! feature.
+
+extern crate proc_macro;
+
+mod attributes;
+mod checked_query;
+mod cql;
+mod cql_enum;
+mod fields;
+mod into_cdrs_value;
+mod try_from_row;
+mod try_from_udt;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derives `TryFromRow` for structs with named fields, reading each field from the column of
+/// the same name. Fields are configured with `#[cdrs(...)]` attributes:
+///
+/// - `rename = "column"` reads the field from a column with a different name,
+/// - `default` uses `Default::default()` if the column is missing or null.
+///
+/// Fields of `Option` types are `None` for null columns, while null columns of other fields
+/// are errors.
+#[proc_macro_derive(TryFromRow, attributes(cdrs))]
+pub fn derive_try_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    try_from_row::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `TryFromUDT` for structs with named fields, reading each field from the UDT field of
+/// the same name. Fields are configured with the same `#[cdrs(...)]` attributes as for
+/// `TryFromRow`.
+///
+/// Types implementing `TryFromUDT` can be read from rows and UDTs by name, also in lists, sets
+/// and maps, so UDTs can be nested in each other.
+#[proc_macro_derive(TryFromUDT, attributes(cdrs))]
+pub fn derive_try_from_udt(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    try_from_udt::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `Into<Bytes>` for structs with named fields, serializing them as UDT values, so they
+/// can be bound to queries, also in lists, sets and maps. UDT values are serialized by position,
+/// so fields have to be declared in the order of the UDT's fields. Fields of `Option` types are
+/// null for `None`.
+#[proc_macro_derive(IntoCDRSValue, attributes(cdrs))]
+pub fn derive_into_cdrs_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_cdrs_value::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `FromCqlValue` and `ToCqlValue` for enums with unit variants, so they're read from
+/// and bound as `text` or integer columns. The enum is configured with `#[cdrs(...)]`
+/// attributes:
+///
+/// - `repr = "text"` (the default) represents variants by their names,
+/// - `repr = "tinyint"`, `"smallint"`, `"int"` or `"bigint"` represents variants by their
+///   discriminants,
+/// - `rename_all = "lowercase"`, `"UPPERCASE"`, `"snake_case"` or `"SCREAMING_SNAKE_CASE"`
+///   converts names of variants into given case.
+///
+/// Variants represented as text can be renamed with `#[cdrs(rename = "name")]`. Reading a value
+/// which isn't any of the variants fails with an error listing the valid values.
+#[proc_macro_derive(CqlEnum, attributes(cdrs))]
+pub fn derive_cql_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    cql_enum::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Checks a query against a schema dump at build time, and generates a struct of its bind
+/// markers:
+///
+/// ```ignore
+/// checked_query! {
+///     #[schema = "schema.cql"]
+///     pub struct SelectUser = "SELECT id, name FROM ks.users WHERE id = ?";
+/// }
+/// ```
+///
+/// The schema is read from the `CREATE TABLE` and `CREATE MATERIALIZED VIEW` statements of the
+/// file given with `#[schema = "..."]`, or the `CDRS_SCHEMA` environment variable, relative to
+/// the crate's manifest, e.g. the output of `cqlsh -e "DESCRIBE KEYSPACE ks"`. `SELECT`,
+/// `INSERT`, `UPDATE` and `DELETE` statements are checked for unknown tables and columns,
+/// missing primary key columns of inserts, missing partition key relations of updates and
+/// deletes, and bind markers used with columns of wrong types.
+///
+/// The struct has a field for each bind marker, named after its column, or `ttl`, `timestamp`,
+/// `limit` and `per_partition_limit`, and converts into `QueryValues`, while `QUERY` is the
+/// query itself. Values of columns which aren't part of the primary key are `Option`s, so they
+/// can be null. For `SELECT` queries, a `TryFromRow` struct of the selected columns is generated
+/// as well, named with a `Row` suffix.
+#[proc_macro]
+pub fn checked_query(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as checked_query::CheckedQuery);
+    checked_query::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
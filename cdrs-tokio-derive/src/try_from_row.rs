@@ -0,0 +1,25 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Result};
+
+use crate::fields::{named_fields, read_fields};
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = named_fields(&input, "TryFromRow")?;
+    let field_values = read_fields(fields, &quote! { row }, &quote! { contains_column })?;
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::cdrs_tokio::frame::TryFromRow for #name #type_generics #where_clause {
+            fn try_from_row(
+                row: ::cdrs_tokio::types::rows::Row,
+            ) -> ::cdrs_tokio::error::Result<Self> {
+                ::std::result::Result::Ok(#name {
+                    #(#field_values,)*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use std::collections::HashMap;
+
+use cdrs_tokio::frame::TryFromRow;
+use cdrs_tokio::query::{checked_query, QueryValues};
+use cdrs_tokio::types::cql_value::CqlValue;
+use cdrs_tokio::types::value::Value;
+use cdrs_tokio::types::vector::CqlVector;
+use cdrs_tokio::uuid::Uuid;
+
+use common::{row, simple_type, udt_type};
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    /// Reads users of given ids.
+    struct SelectUsers = "SELECT id, \"createdAt\", name, type, address FROM users \
+                          WHERE id IN ? AND \"createdAt\" > ? LIMIT ?";
+}
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    pub struct InsertUser = "INSERT INTO ks.users (id, \"createdAt\", name, embedding) \
+                             VALUES (?, ?, ?, ?) IF NOT EXISTS USING TTL ?";
+}
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    struct UpdateUser = "UPDATE ks.users USING TIMESTAMP ? \
+                         SET tags = ? + tags, emails = emails - ?, scores[?] = ?, type = 'admin' \
+                         WHERE id = ? AND \"createdAt\" = ? IF name = ?";
+}
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    struct DeleteScore = "DELETE scores[?] FROM ks.users WHERE id = ? AND \"createdAt\" IN (?, ?)";
+}
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    struct CountVisit = "UPDATE visits SET count = count + ? WHERE page = ?";
+}
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    struct SelectByName = "SELECT * FROM users_by_name WHERE name = ?";
+}
+
+checked_query! {
+    #[schema = "tests/schema.cql"]
+    struct SelectCollections = "SELECT scores, tags FROM users WHERE id = ? AND tags CONTAINS ? \
+                                ALLOW FILTERING";
+}
+
+#[test]
+fn binds_values_of_checked_queries() {
+    let select = SelectUsers {
+        id: vec![1, 2],
+        createdat: 1000,
+        limit: 10,
+    };
+    assert!(SelectUsers::QUERY.starts_with("SELECT id, \"createdAt\""));
+    assert_eq!(
+        QueryValues::from(select),
+        QueryValues::SimpleValues(vec![
+            Value::from(vec![1, 2]),
+            Value::from(1000i64),
+            Value::from(10)
+        ])
+    );
+
+    let insert = InsertUser {
+        id: 1,
+        createdat: 1000,
+        name: None,
+        embedding: Some(CqlVector(vec![0.5, 1.0, 2.0])),
+        ttl: 60,
+    };
+    assert_eq!(
+        QueryValues::from(insert),
+        QueryValues::SimpleValues(vec![
+            Value::from(1),
+            Value::from(1000i64),
+            Value::from(None::<String>),
+            Value::from(CqlVector(vec![0.5f32, 1.0, 2.0])),
+            Value::from(60)
+        ])
+    );
+
+    let update = UpdateUser {
+        timestamp: 5,
+        tags: Some(vec!["a".to_string()]),
+        emails: vec!["b".to_string()],
+        scores_key: "c".to_string(),
+        scores: Some(1),
+        id: 1,
+        createdat: 1000,
+        name: Some("d".to_string()),
+    };
+    assert_eq!(
+        QueryValues::from(update),
+        QueryValues::SimpleValues(vec![
+            Value::from(5i64),
+            Value::from(vec!["a"]),
+            Value::from(vec!["b"]),
+            Value::from("c"),
+            Value::from(1),
+            Value::from(1),
+            Value::from(1000i64),
+            Value::from("d")
+        ])
+    );
+
+    let delete = DeleteScore {
+        scores_key: "a".to_string(),
+        id: 1,
+        createdat: 1000,
+        createdat_2: 2000,
+    };
+    assert_eq!(
+        QueryValues::from(delete),
+        QueryValues::SimpleValues(vec![
+            Value::from("a"),
+            Value::from(1),
+            Value::from(1000i64),
+            Value::from(2000i64)
+        ])
+    );
+
+    let page = Uuid::nil();
+    let count = CountVisit { count: 1, page };
+    assert_eq!(
+        QueryValues::from(count),
+        QueryValues::SimpleValues(vec![Value::from(1i64), Value::from(page)])
+    );
+
+    let by_name = SelectByName {
+        name: "a".to_string(),
+    };
+    assert_eq!(
+        QueryValues::from(by_name),
+        QueryValues::SimpleValues(vec![Value::from("a")])
+    );
+}
+
+#[test]
+fn reads_rows_of_checked_queries() {
+    let mut address = vec![];
+    for field in &["Main St", "Springfield"] {
+        address.extend_from_slice(&(field.len() as i32).to_be_bytes());
+        address.extend_from_slice(field.as_bytes());
+    }
+
+    let user = SelectUsersRow::try_from_row(row(vec![
+        ("id", simple_type(0x0009), Some(1i32.to_be_bytes().to_vec())),
+        (
+            "createdAt",
+            simple_type(0x000B),
+            Some(1000i64.to_be_bytes().to_vec()),
+        ),
+        ("name", simple_type(0x000D), None),
+        ("type", simple_type(0x000D), Some(b"admin".to_vec())),
+        (
+            "address",
+            udt_type(
+                "address",
+                vec![
+                    ("street", simple_type(0x000D)),
+                    ("city", simple_type(0x000D)),
+                ],
+            ),
+            Some(address),
+        ),
+    ]))
+    .unwrap();
+
+    assert_eq!(user.id, 1);
+    assert_eq!(user.createdat, 1000);
+    assert_eq!(user.name, None);
+    assert_eq!(user.r#type, Some("admin".to_string()));
+    assert_eq!(
+        user.address,
+        Some(CqlValue::Udt(vec![
+            ("street".to_string(), CqlValue::Text("Main St".to_string())),
+            (
+                "city".to_string(),
+                CqlValue::Text("Springfield".to_string())
+            ),
+        ]))
+    );
+
+    let by_name = SelectByNameRow::try_from_row(row(vec![
+        ("id", simple_type(0x0009), Some(2i32.to_be_bytes().to_vec())),
+        (
+            "createdAt",
+            simple_type(0x000B),
+            Some(0i64.to_be_bytes().to_vec()),
+        ),
+        ("name", simple_type(0x000D), Some(b"a".to_vec())),
+    ]))
+    .unwrap();
+    assert_eq!(
+        (by_name.id, by_name.createdat, by_name.name),
+        (2, 0, "a".to_string())
+    );
+
+    // collections are read as `Vec`s and `HashMap`s
+    let _: fn(SelectCollectionsRow) -> (Option<HashMap<String, i32>>, Option<Vec<String>>) =
+        |row| (row.scores, row.tags);
+}
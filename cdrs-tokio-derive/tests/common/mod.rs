@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use cdrs_tokio::frame::{Frame, Opcode, Version};
+use cdrs_tokio::types::rows::Row;
+
+pub fn string(value: &str) -> Vec<u8> {
+    let mut bytes = (value.len() as i16).to_be_bytes().to_vec();
+    bytes.extend_from_slice(value.as_bytes());
+    bytes
+}
+
+/// Encodes the option of a type without parameters, given by its id.
+pub fn simple_type(id: u16) -> Vec<u8> {
+    id.to_be_bytes().to_vec()
+}
+
+/// Encodes the option of a UDT type of given fields.
+pub fn udt_type(name: &str, fields: Vec<(&str, Vec<u8>)>) -> Vec<u8> {
+    let mut bytes = simple_type(0x0030);
+    bytes.extend(string("ks"));
+    bytes.extend(string(name));
+    bytes.extend_from_slice(&(fields.len() as i16).to_be_bytes());
+    for (name, field_type) in fields {
+        bytes.extend(string(name));
+        bytes.extend(field_type);
+    }
+    bytes
+}
+
+/// Builds a row of given columns, given by name, type option and value.
+pub fn row(columns: Vec<(&str, Vec<u8>, Option<Vec<u8>>)>) -> Row {
+    let mut body = vec![0, 0, 0, 2];
+    body.extend_from_slice(&1i32.to_be_bytes());
+    body.extend_from_slice(&(columns.len() as i32).to_be_bytes());
+    body.extend(string("ks"));
+    body.extend(string("users"));
+    for (name, col_type, _) in &columns {
+        body.extend(string(name));
+        body.extend_from_slice(col_type);
+    }
+
+    body.extend_from_slice(&1i32.to_be_bytes());
+    for (_, _, value) in columns {
+        match value {
+            Some(value) => {
+                body.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                body.extend(value);
+            }
+            None => body.extend_from_slice(&(-1i32).to_be_bytes()),
+        }
+    }
+
+    let frame = Frame::new(
+        Version::Response,
+        vec![],
+        Opcode::Result,
+        body,
+        None,
+        vec![],
+    );
+    frame.get_body().unwrap().into_rows().unwrap().remove(0)
+}
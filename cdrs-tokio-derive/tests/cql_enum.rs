@@ -0,0 +1,120 @@
+mod common;
+
+use cdrs_tokio::error::Error;
+use cdrs_tokio::frame::{CqlEnum, TryFromRow};
+use cdrs_tokio::types::value::Value;
+
+use common::{row, simple_type};
+
+#[derive(Clone, Copy, Debug, PartialEq, CqlEnum)]
+#[cdrs(rename_all = "snake_case")]
+enum Status {
+    Active,
+    OnHold,
+    #[cdrs(rename = "closed")]
+    Archived,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, CqlEnum)]
+#[cdrs(repr = "smallint")]
+enum Priority {
+    Low = -1,
+    Normal,
+    High = 10,
+}
+
+#[derive(Debug, PartialEq, TryFromRow)]
+struct Ticket {
+    status: Status,
+    priority: Priority,
+    previous_status: Option<Status>,
+    history: Vec<Status>,
+}
+
+fn list_of_text() -> Vec<u8> {
+    let mut col_type = simple_type(0x0020);
+    col_type.extend(simple_type(0x000D));
+    col_type
+}
+
+fn text_list(values: &[&str]) -> Vec<u8> {
+    let mut bytes = (values.len() as i32).to_be_bytes().to_vec();
+    for value in values {
+        bytes.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn reads_enums() {
+    let ticket = row(vec![
+        ("status", simple_type(0x000D), Some(b"on_hold".to_vec())),
+        (
+            "priority",
+            simple_type(0x0013),
+            Some(10i16.to_be_bytes().to_vec()),
+        ),
+        ("previous_status", simple_type(0x000D), None),
+        (
+            "history",
+            list_of_text(),
+            Some(text_list(&["active", "closed"])),
+        ),
+    ]);
+
+    assert_eq!(
+        Ticket::try_from_row(ticket).unwrap(),
+        Ticket {
+            status: Status::OnHold,
+            priority: Priority::High,
+            previous_status: None,
+            history: vec![Status::Active, Status::Archived],
+        }
+    );
+}
+
+fn general_error(err: Error) -> String {
+    match err {
+        Error::General(message) => message,
+        err => panic!("unexpected error {}", err),
+    }
+}
+
+#[test]
+fn lists_valid_values_of_unknown_values() {
+    let ticket = row(vec![
+        ("status", simple_type(0x000D), Some(b"Archived".to_vec())),
+        (
+            "priority",
+            simple_type(0x0013),
+            Some(1i16.to_be_bytes().to_vec()),
+        ),
+        ("previous_status", simple_type(0x000D), None),
+        ("history", list_of_text(), Some(text_list(&[]))),
+    ]);
+    let err = general_error(Ticket::try_from_row(ticket).unwrap_err());
+    assert!(err.contains(r#"valid values: "active", "on_hold", "closed""#));
+
+    let ticket = row(vec![
+        ("status", simple_type(0x000D), Some(b"active".to_vec())),
+        (
+            "priority",
+            simple_type(0x0013),
+            Some(1i16.to_be_bytes().to_vec()),
+        ),
+        ("previous_status", simple_type(0x000D), None),
+        ("history", list_of_text(), Some(text_list(&[]))),
+    ]);
+    let err = general_error(Ticket::try_from_row(ticket).unwrap_err());
+    assert!(err.contains("Invalid value 1 of Priority (valid values: -1, 0, 10)"));
+}
+
+#[test]
+fn binds_enums() {
+    assert_eq!(Value::from(Status::OnHold), Value::from("on_hold"));
+    assert_eq!(Value::from(Status::Archived), Value::from("closed"));
+    assert_eq!(Value::from(Priority::Low), Value::from(-1i16));
+    assert_eq!(Value::from(Priority::Normal), Value::from(0i16));
+    assert_eq!(Value::from(vec![Priority::High]), Value::from(vec![10i16]));
+}
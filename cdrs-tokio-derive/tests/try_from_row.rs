@@ -0,0 +1,54 @@
+mod common;
+
+use cdrs_tokio::frame::TryFromRow;
+
+use common::{row, simple_type};
+
+#[derive(Debug, PartialEq, TryFromRow)]
+struct User {
+    id: i32,
+    #[cdrs(rename = "user_name")]
+    name: String,
+    email: Option<String>,
+    #[cdrs(default)]
+    age: i32,
+    #[cdrs(default)]
+    score: i64,
+}
+
+#[test]
+fn reads_fields_by_column_name() {
+    let user = User::try_from_row(row(vec![
+        ("id", simple_type(0x0009), Some(7i32.to_be_bytes().to_vec())),
+        ("user_name", simple_type(0x000D), Some(b"alice".to_vec())),
+        ("email", simple_type(0x000D), None),
+        ("age", simple_type(0x0009), None),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: "alice".to_string(),
+            email: None,
+            age: 0,
+            score: 0,
+        }
+    );
+}
+
+#[test]
+fn fails_on_null_required_columns() {
+    let result = User::try_from_row(row(vec![
+        ("id", simple_type(0x0009), None),
+        ("user_name", simple_type(0x000D), Some(b"alice".to_vec())),
+        (
+            "email",
+            simple_type(0x000D),
+            Some(b"alice@example.com".to_vec()),
+        ),
+    ]));
+
+    assert!(result.is_err());
+}
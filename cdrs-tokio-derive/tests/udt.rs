@@ -0,0 +1,136 @@
+mod common;
+
+use std::collections::HashMap;
+
+use cdrs_tokio::frame::{IntoCDRSValue, TryFromRow, TryFromUDT};
+use cdrs_tokio::types::value::Value;
+
+use common::{row, simple_type, udt_type};
+
+#[derive(Clone, Debug, PartialEq, IntoCDRSValue, TryFromUDT)]
+struct Address {
+    street: String,
+    number: Option<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq, IntoCDRSValue, TryFromUDT)]
+struct Person {
+    #[cdrs(rename = "full_name")]
+    name: String,
+    address: Address,
+    previous_addresses: Vec<Address>,
+}
+
+#[derive(Debug, PartialEq, TryFromRow)]
+struct Customer {
+    id: i32,
+    person: Person,
+    contacts: Vec<Person>,
+    addresses: HashMap<String, Address>,
+    billing_address: Option<Address>,
+}
+
+fn address_type() -> Vec<u8> {
+    udt_type(
+        "address",
+        vec![
+            ("street", simple_type(0x000D)),
+            ("number", simple_type(0x0009)),
+        ],
+    )
+}
+
+fn person_type() -> Vec<u8> {
+    let mut addresses = simple_type(0x0020);
+    addresses.extend(address_type());
+
+    udt_type(
+        "person",
+        vec![
+            ("full_name", simple_type(0x000D)),
+            ("address", address_type()),
+            ("previous_addresses", addresses),
+        ],
+    )
+}
+
+fn body<T: Into<Value>>(value: T) -> Option<Vec<u8>> {
+    Some(value.into().body)
+}
+
+#[test]
+fn converts_nested_udts_in_both_directions() {
+    let home = Address {
+        street: "Main Street".to_string(),
+        number: Some(1),
+    };
+    let office = Address {
+        street: "Side Street".to_string(),
+        number: None,
+    };
+    let person = Person {
+        name: "Alice".to_string(),
+        address: home.clone(),
+        previous_addresses: vec![office.clone(), home.clone()],
+    };
+    let contact = Person {
+        name: "Bob".to_string(),
+        address: office.clone(),
+        previous_addresses: vec![],
+    };
+
+    let mut addresses = HashMap::new();
+    addresses.insert("home".to_string(), home);
+    addresses.insert("office".to_string(), office);
+
+    let mut persons_type = simple_type(0x0020);
+    persons_type.extend(person_type());
+    let mut addresses_type = simple_type(0x0021);
+    addresses_type.extend(simple_type(0x000D));
+    addresses_type.extend(address_type());
+
+    let customer = Customer::try_from_row(row(vec![
+        ("id", simple_type(0x0009), body(7)),
+        ("person", person_type(), body(person.clone())),
+        (
+            "contacts",
+            persons_type,
+            body(vec![person.clone(), contact.clone()]),
+        ),
+        ("addresses", addresses_type, body(addresses.clone())),
+        ("billing_address", address_type(), None),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        customer,
+        Customer {
+            id: 7,
+            person: person.clone(),
+            contacts: vec![person, contact],
+            addresses,
+            billing_address: None,
+        }
+    );
+}
+
+#[derive(Debug, TryFromUDT)]
+struct RequiredAddress {
+    #[allow(dead_code)]
+    street: String,
+    #[allow(dead_code)]
+    number: i32,
+}
+
+#[test]
+fn fails_on_null_required_fields() {
+    let address = Address {
+        street: "Main Street".to_string(),
+        number: None,
+    };
+    let row = row(vec![("address", address_type(), body(address))]);
+
+    let result: Result<RequiredAddress, _> =
+        cdrs_tokio::types::IntoRustByName::get_r_by_name(&row, "address");
+    assert!(result.is_err());
+}
@@ -78,8 +78,8 @@ async fn batch_few_queries(session: &mut CurrentSession, query: &str) {
     let row_1 = RowStruct { key: 1001 as i32 };
     let row_2 = RowStruct { key: 2001 as i32 };
 
-    let batch = BatchQueryBuilder::new()
-        .add_query_prepared(prepared_query, row_1.into_query_values())
+    let batch = BatchBuilder::new()
+        .add_prepared(&prepared_query, row_1.into_query_values())
         .add_query(query, row_2.into_query_values())
         .finalize()
         .expect("batch builder");
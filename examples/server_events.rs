@@ -1,9 +1,6 @@
-use std::iter::Iterator;
-
 use cdrs_tokio::authenticators::NoneAuthenticator;
 use cdrs_tokio::cluster::session::new as new_session;
 use cdrs_tokio::cluster::{ClusterTcpConfig, NodeTcpConfigBuilder};
-use cdrs_tokio::compression::Compression;
 use cdrs_tokio::frame::events::{ChangeType, ServerEvent, SimpleServerEvent, Target};
 use cdrs_tokio::load_balancing::RoundRobin;
 
@@ -16,7 +13,7 @@ async fn main() {
     let lb = RoundRobin::new();
     let no_compression = new_session(&cluster_config, lb).await.expect("session should be created");
 
-    let (listener, stream) = no_compression
+    let (listener, mut stream) = no_compression
         .listen(
             "127.0.0.1:9042",
             NoneAuthenticator {},
@@ -25,24 +22,24 @@ async fn main() {
         .await
         .expect("listen error");
 
-    tokio::spawn(listener.start(&Compression::None));
+    tokio::spawn(listener.start());
+
+    println!("Start listen for server events");
 
-    let new_tables = stream
+    while let Some(event) = stream.next().await {
         // inspects all events in a stream
-        .inspect(|event| println!("inspect event {:?}", event))
-        // filter by event's type: schema changes
-        .filter(|event| event == &SimpleServerEvent::SchemaChange)
+        println!("inspect event {:?}", event);
+
         // filter by event's specific information: new table was added
-        .filter(|event| match event {
-            &ServerEvent::SchemaChange(ref event) => {
+        let is_new_table = match event {
+            ServerEvent::SchemaChange(ref event) => {
                 event.change_type == ChangeType::Created && event.target == Target::Table
             }
             _ => false,
-        });
-
-    println!("Start listen for server events");
+        };
 
-    for change in new_tables {
-        println!("server event {:?}", change);
+        if is_new_table {
+            println!("server event {:?}", event);
+        }
     }
 }
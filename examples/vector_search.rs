@@ -0,0 +1,92 @@
+use cdrs_tokio::authenticators::StaticPasswordAuthenticator;
+use cdrs_tokio::cluster::session::{new as new_session, Session};
+use cdrs_tokio::cluster::{ClusterTcpConfig, NodeTcpConfigBuilder, TcpConnectionPool};
+use cdrs_tokio::load_balancing::RoundRobin;
+use cdrs_tokio::query::*;
+use cdrs_tokio::query_values;
+
+use cdrs_tokio::types::prelude::*;
+use cdrs_tokio::types::IntoRustByName;
+
+type CurrentSession = Session<RoundRobin<TcpConnectionPool<StaticPasswordAuthenticator>>>;
+
+#[tokio::main]
+async fn main() {
+    let user = "user";
+    let password = "password";
+    let auth = StaticPasswordAuthenticator::new(&user, &password);
+    let node = NodeTcpConfigBuilder::new("localhost:9042", auth).build();
+    let cluster_config = ClusterTcpConfig(vec![node]);
+    let mut session: CurrentSession = new_session(&cluster_config, RoundRobin::new())
+        .await
+        .expect("session should be created");
+
+    create_keyspace(&mut session).await;
+    create_table(&mut session).await;
+    insert_embeddings(&mut session).await;
+    select_nearest(&mut session).await;
+}
+
+async fn create_keyspace(session: &mut CurrentSession) {
+    let create_ks: &'static str = "CREATE KEYSPACE IF NOT EXISTS test_ks WITH REPLICATION = { \
+                                   'class' : 'SimpleStrategy', 'replication_factor' : 1 };";
+    session
+        .query(create_ks)
+        .await
+        .expect("Keyspace creation error");
+}
+
+async fn create_table(session: &mut CurrentSession) {
+    let create_table_cql = "CREATE TABLE IF NOT EXISTS test_ks.products \
+                            (id int PRIMARY KEY, name text, embedding vector<float, 3>);";
+    session
+        .query(create_table_cql)
+        .await
+        .expect("Table creation error");
+
+    // ANN queries require a storage attached index on the vector column
+    let create_index_cql = "CREATE CUSTOM INDEX IF NOT EXISTS products_embedding \
+                            ON test_ks.products (embedding) USING 'StorageAttachedIndex';";
+    session
+        .query(create_index_cql)
+        .await
+        .expect("Index creation error");
+}
+
+async fn insert_embeddings(session: &mut CurrentSession) {
+    let insert_cql = "INSERT INTO test_ks.products (id, name, embedding) VALUES (?, ?, ?)";
+    let products = vec![
+        (1, "coffee", vec![0.9f32, 0.1, 0.0]),
+        (2, "tea", vec![0.8, 0.2, 0.1]),
+        (3, "bread", vec![0.1, 0.9, 0.3]),
+        (4, "butter", vec![0.0, 0.7, 0.8]),
+        (5, "cocoa", vec![0.85, 0.15, 0.05]),
+    ];
+
+    for (id, name, embedding) in products {
+        session
+            .query_with_values(insert_cql, query_values!(id, name, CqlVector(embedding)))
+            .await
+            .expect("insert");
+    }
+}
+
+async fn select_nearest(session: &mut CurrentSession) {
+    let select_cql = "SELECT id, name, embedding FROM test_ks.products \
+                      ORDER BY embedding ANN OF ? LIMIT 3";
+    let rows = session
+        .query_with_values(select_cql, query_values!(CqlVector(vec![1.0f32, 0.0, 0.0])))
+        .await
+        .expect("query")
+        .get_body()
+        .expect("get body")
+        .into_rows()
+        .expect("into rows");
+
+    for row in rows {
+        let id: i32 = row.get_r_by_name("id").expect("id");
+        let name: String = row.get_r_by_name("name").expect("name");
+        let embedding: [f32; 3] = row.get_r_by_name("embedding").expect("embedding");
+        println!("{} {}: {:?}", id, name, embedding);
+    }
+}
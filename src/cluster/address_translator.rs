@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use crate::cluster::topology::{peer_ip, query_rows};
+use crate::error;
+use crate::transport::CDRSTransport;
+
+const PEERS_QUERY: &str = "SELECT peer, rpc_address FROM system.peers";
+
+/// Maps node addresses learned from the cluster to addresses reachable by the client.
+///
+/// Nodes advertise their broadcast addresses in `system.peers`, which might not be reachable
+/// from behind NAT, in Kubernetes or when ports are forwarded. A translator is invoked for every
+/// such address before connecting to it.
+pub trait AddressTranslator: Send + Sync {
+    /// Returns the address to connect to instead of given advertised one.
+    fn translate(&self, addr: SocketAddr) -> SocketAddr;
+}
+
+/// Translator which returns addresses unchanged. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityTranslator;
+
+impl AddressTranslator for IdentityTranslator {
+    fn translate(&self, addr: SocketAddr) -> SocketAddr {
+        addr
+    }
+}
+
+/// Translator using a fixed mapping of advertised addresses. Addresses without a mapping are
+/// returned unchanged.
+#[derive(Clone, Debug, Default)]
+pub struct StaticAddressTranslator {
+    mapping: HashMap<SocketAddr, SocketAddr>,
+}
+
+impl StaticAddressTranslator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Makes `advertised` address translate to `reachable` one.
+    pub fn with_mapping(mut self, advertised: SocketAddr, reachable: SocketAddr) -> Self {
+        self.mapping.insert(advertised, reachable);
+        self
+    }
+}
+
+impl From<HashMap<SocketAddr, SocketAddr>> for StaticAddressTranslator {
+    fn from(mapping: HashMap<SocketAddr, SocketAddr>) -> Self {
+        StaticAddressTranslator { mapping }
+    }
+}
+
+impl AddressTranslator for StaticAddressTranslator {
+    fn translate(&self, addr: SocketAddr) -> SocketAddr {
+        self.mapping.get(&addr).cloned().unwrap_or(addr)
+    }
+}
+
+/// Queries `system.peers` over given connection and returns translated addresses of other
+/// nodes in the cluster. Peers are assumed to listen on the same port as the node the
+/// connection is open to.
+pub async fn fetch_peer_addrs<T: CDRSTransport>(
+    transport: &T,
+    translator: &dyn AddressTranslator,
+) -> error::Result<Vec<SocketAddr>> {
+    let port = transport.addr().port();
+    query_rows(transport, PEERS_QUERY)
+        .await?
+        .iter()
+        .map(|row| Ok(translator.translate(SocketAddr::new(peer_ip(row)?, port))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_mapped_addresses() {
+        let advertised: SocketAddr = "10.0.0.1:9042".parse().unwrap();
+        let reachable: SocketAddr = "203.0.113.1:19042".parse().unwrap();
+        let other: SocketAddr = "10.0.0.2:9042".parse().unwrap();
+
+        let translator = StaticAddressTranslator::new().with_mapping(advertised, reachable);
+        assert_eq!(translator.translate(advertised), reachable);
+        assert_eq!(translator.translate(other), other);
+        assert_eq!(IdentityTranslator.translate(advertised), advertised);
+    }
+}
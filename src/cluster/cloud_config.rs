@@ -0,0 +1,382 @@
+use rustls::internal::pemfile;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{BufReader, Read, Seek};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time::timeout;
+use tokio_rustls::TlsConnector;
+use zip::ZipArchive;
+
+use crate::authenticators::{Authenticator, StaticPasswordAuthenticator};
+use crate::cluster::hostname_verification::{HostnameVerification, HostnameVerifier};
+use crate::cluster::{ClusterRustlsConfig, NodeRustlsConfigBuilder};
+use crate::error;
+
+const CONFIG_FILE: &str = "config.json";
+const CA_CERT_FILE: &str = "ca.crt";
+const CERT_FILE: &str = "cert";
+const KEY_FILE: &str = "key";
+const METADATA_PATH: &str = "/metadata";
+const METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuration of a DataStax Astra database, read from a secure connect bundle.
+///
+/// Node addresses are not part of the bundle - they are fetched from the metadata service with
+/// `fetch_nodes`. All nodes are reached through an SNI proxy, which routes connections by node
+/// host id sent as SNI.
+#[derive(Clone)]
+pub struct CloudConfig {
+  metadata_host: String,
+  metadata_port: u16,
+  tls_config: Arc<rustls::ClientConfig>,
+  username: Option<String>,
+  password: Option<String>,
+  keyspace: Option<String>,
+  local_dc: Option<String>,
+}
+
+/// Contact information returned by the metadata service.
+#[derive(Debug, PartialEq)]
+struct ContactInfo {
+  sni_proxy_address: String,
+  contact_points: Vec<String>,
+  local_dc: Option<String>,
+}
+
+impl CloudConfig {
+  /// Reads the configuration from a secure connect bundle, i.e. the zip file containing
+  /// `config.json`, `ca.crt`, `cert` and `key` files.
+  pub fn from_bundle<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+    Self::from_bundle_reader(fs::File::open(path)?)
+  }
+
+  /// Reads the configuration from a secure connect bundle zip file which is already open or
+  /// loaded into memory.
+  pub fn from_bundle_reader<R: Read + Seek>(reader: R) -> error::Result<Self> {
+    let mut archive = ZipArchive::new(reader)
+      .map_err(|err| error::Error::General(format!("Invalid bundle: {}", err)))?;
+    let mut read = |name: &str| -> error::Result<Vec<u8>> {
+      let mut file = archive.by_name(name).map_err(|err| {
+        error::Error::General(format!("Cannot read {} from bundle: {}", name, err))
+      })?;
+      let mut contents = vec![];
+      file.read_to_end(&mut contents)?;
+      Ok(contents)
+    };
+
+    Self::from_bundle_files(
+      &read(CONFIG_FILE)?,
+      &read(CA_CERT_FILE)?,
+      &read(CERT_FILE)?,
+      &read(KEY_FILE)?,
+    )
+  }
+
+  /// Reads the configuration from an extracted secure connect bundle directory.
+  pub fn from_bundle_dir<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+    let path = path.as_ref();
+
+    Self::from_bundle_files(
+      &fs::read(path.join(CONFIG_FILE))?,
+      &fs::read(path.join(CA_CERT_FILE))?,
+      &fs::read(path.join(CERT_FILE))?,
+      &fs::read(path.join(KEY_FILE))?,
+    )
+  }
+
+  fn from_bundle_files(
+    config: &[u8],
+    ca_cert: &[u8],
+    cert: &[u8],
+    key: &[u8],
+  ) -> error::Result<Self> {
+    let config: Value = serde_json::from_slice(config)
+      .map_err(|err| error::Error::General(format!("Invalid bundle config: {}", err)))?;
+
+    let metadata_host = config["host"]
+      .as_str()
+      .ok_or_else(|| error::Error::General("Missing host in bundle config".into()))?
+      .to_string();
+    let metadata_port = config["port"]
+      .as_u64()
+      .ok_or_else(|| error::Error::General("Missing port in bundle config".into()))?;
+    let metadata_port = u16::try_from(metadata_port).map_err(|_| {
+      error::Error::General(format!("Invalid port {} in bundle config", metadata_port))
+    })?;
+
+    let mut tls_config = rustls::ClientConfig::new();
+    tls_config
+      .root_store
+      .add_pem_file(&mut BufReader::new(ca_cert))
+      .map_err(|_| error::Error::General("Invalid bundle CA certificate".into()))?;
+
+    let cert_chain = pemfile::certs(&mut BufReader::new(cert))
+      .map_err(|_| error::Error::General("Invalid bundle client certificate".into()))?;
+    let key = read_private_key(key)?;
+    tls_config
+      .set_single_client_cert(cert_chain, key)
+      .map_err(|err| error::Error::General(format!("Invalid bundle client certificate: {}", err)))?;
+
+    let optional_string = |key: &str| config[key].as_str().map(|value| value.to_string());
+
+    Ok(CloudConfig {
+      metadata_host,
+      metadata_port,
+      tls_config: Arc::new(tls_config),
+      username: optional_string("username"),
+      password: optional_string("password"),
+      keyspace: optional_string("keyspace"),
+      local_dc: optional_string("localDC"),
+    })
+  }
+
+  /// Returns the default keyspace, if present in the bundle.
+  pub fn keyspace(&self) -> Option<&str> {
+    self.keyspace.as_deref()
+  }
+
+  /// Returns the local data center, if present in the bundle.
+  pub fn local_dc(&self) -> Option<&str> {
+    self.local_dc.as_deref()
+  }
+
+  /// Returns an authenticator with credentials from the bundle, if present. Newer bundles
+  /// don't contain credentials, so they need to be provided separately.
+  pub fn authenticator(&self) -> Option<StaticPasswordAuthenticator> {
+    match (&self.username, &self.password) {
+      (Some(username), Some(password)) => Some(StaticPasswordAuthenticator::new(
+        username.as_str(),
+        password.as_str(),
+      )),
+      _ => None,
+    }
+  }
+
+  /// Fetches current nodes from the metadata service and creates TLS configs for them. Fails
+  /// with `Error::Timeout` if the service doesn't respond within 10 seconds.
+  pub async fn fetch_nodes<A: Authenticator + Sized>(
+    &self,
+    authenticator: A,
+  ) -> error::Result<ClusterRustlsConfig<A>> {
+    let metadata = timeout(METADATA_TIMEOUT, self.fetch_metadata())
+      .await
+      .map_err(|_| {
+        error::Error::Timeout(format!(
+          "Metadata service {} didn't respond in time",
+          self.metadata_host
+        ))
+      })??;
+    let contact_info = parse_contact_info(&metadata)?;
+
+    let proxy_addr: SocketAddr = lookup_host(contact_info.sni_proxy_address.as_str())
+      .await?
+      .next()
+      .ok_or_else(|| error::Error::General("Cannot resolve SNI proxy address".into()))?;
+
+    let server_name = dns_name(&self.metadata_host)?;
+    let mut node_tls_config = (*self.tls_config).clone();
+    node_tls_config
+      .dangerous()
+      .set_certificate_verifier(Arc::new(HostnameVerifier::with_server_name(
+        HostnameVerification::Wildcard,
+        server_name,
+      )));
+    let node_tls_config = Arc::new(node_tls_config);
+
+    let nodes = contact_info
+      .contact_points
+      .iter()
+      .map(|host_id| {
+        Ok(
+          NodeRustlsConfigBuilder::new(
+            proxy_addr,
+            dns_name(host_id)?,
+            authenticator.clone(),
+            node_tls_config.clone(),
+          )
+          .build(),
+        )
+      })
+      .collect::<error::Result<Vec<_>>>()?;
+
+    Ok(ClusterRustlsConfig(nodes))
+  }
+
+  async fn fetch_metadata(&self) -> error::Result<Value> {
+    let addr = lookup_host((self.metadata_host.as_str(), self.metadata_port))
+      .await?
+      .next()
+      .ok_or_else(|| error::Error::General("Cannot resolve metadata service address".into()))?;
+
+    let stream = TcpStream::connect(addr).await?;
+    let server_name = dns_name(&self.metadata_host)?;
+    let mut stream = TlsConnector::from(self.tls_config.clone())
+      .connect(server_name.as_ref(), stream)
+      .await?;
+
+    // HTTP/1.0 makes the server close the connection after a plain, non-chunked response
+    let request = format!(
+      "GET {} HTTP/1.0\r\nHost: {}:{}\r\nAccept: application/json\r\n\r\n",
+      METADATA_PATH, self.metadata_host, self.metadata_port
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+
+    serde_json::from_slice(http_response_body(&response)?)
+      .map_err(|err| error::Error::General(format!("Invalid metadata response: {}", err)))
+  }
+}
+
+fn read_private_key(key: &[u8]) -> error::Result<rustls::PrivateKey> {
+  let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(key))
+    .map_err(|_| error::Error::General("Invalid bundle client key".into()))?;
+  if keys.is_empty() {
+    keys = pemfile::rsa_private_keys(&mut BufReader::new(key))
+      .map_err(|_| error::Error::General("Invalid bundle client key".into()))?;
+  }
+
+  keys
+    .pop()
+    .ok_or_else(|| error::Error::General("No private key found in bundle".into()))
+}
+
+fn dns_name(name: &str) -> error::Result<webpki::DNSName> {
+  webpki::DNSNameRef::try_from_ascii_str(name)
+    .map(|name| name.to_owned())
+    .map_err(|_| error::Error::General(format!("Invalid DNS name: {}", name)))
+}
+
+fn http_response_body(response: &[u8]) -> error::Result<&[u8]> {
+  let header_end = response
+    .windows(4)
+    .position(|window| window == b"\r\n\r\n")
+    .ok_or_else(|| error::Error::General("Invalid metadata response".into()))?;
+
+  let headers = String::from_utf8_lossy(&response[..header_end]);
+  let status_line = headers.lines().next().unwrap_or_default();
+  if status_line.split_whitespace().nth(1) != Some("200") {
+    return Err(error::Error::General(format!(
+      "Metadata service returned an error: {}",
+      status_line
+    )));
+  }
+
+  Ok(&response[header_end + 4..])
+}
+
+fn parse_contact_info(metadata: &Value) -> error::Result<ContactInfo> {
+  let contact_info = &metadata["contact_info"];
+
+  let sni_proxy_address = contact_info["sni_proxy_address"]
+    .as_str()
+    .ok_or_else(|| error::Error::General("Missing SNI proxy address in metadata".into()))?
+    .to_string();
+
+  let contact_points = contact_info["contact_points"]
+    .as_array()
+    .ok_or_else(|| error::Error::General("Missing contact points in metadata".into()))?
+    .iter()
+    .filter_map(|contact_point| contact_point.as_str())
+    .map(|contact_point| contact_point.to_string())
+    .collect();
+
+  Ok(ContactInfo {
+    sni_proxy_address,
+    contact_points,
+    local_dc: contact_info["local_dc"].as_str().map(|dc| dc.to_string()),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::{Cursor, Write};
+  use zip::write::FileOptions;
+  use zip::ZipWriter;
+
+  #[test]
+  fn parses_contact_info() {
+    let metadata: Value = serde_json::from_str(
+      r#"{
+        "version": 1,
+        "region": "eu-central-1",
+        "contact_info": {
+          "type": "sni_proxy",
+          "local_dc": "dc1",
+          "contact_points": ["b3a6d4a4-1fb2-4d2b-a2c7-7f2ef3c5c9a1", "8a2f4c1e-3b6d-4f7a-9c0e-1d2b3a4c5d6e"],
+          "sni_proxy_address": "proxy.example.com:29042"
+        }
+      }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      parse_contact_info(&metadata).unwrap(),
+      ContactInfo {
+        sni_proxy_address: "proxy.example.com:29042".into(),
+        contact_points: vec![
+          "b3a6d4a4-1fb2-4d2b-a2c7-7f2ef3c5c9a1".into(),
+          "8a2f4c1e-3b6d-4f7a-9c0e-1d2b3a4c5d6e".into()
+        ],
+        local_dc: Some("dc1".into()),
+      }
+    );
+  }
+
+  #[test]
+  fn rejects_metadata_without_proxy() {
+    let metadata: Value = serde_json::from_str(r#"{"contact_info": {"contact_points": []}}"#).unwrap();
+    assert!(parse_contact_info(&metadata).is_err());
+  }
+
+  fn bundle(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    for (name, contents) in files {
+      writer.start_file(*name, FileOptions::default()).unwrap();
+      writer.write_all(contents.as_bytes()).unwrap();
+    }
+
+    let mut bundle = writer.finish().unwrap();
+    bundle.set_position(0);
+    bundle
+  }
+
+  #[test]
+  fn reads_zipped_bundles() {
+    let error = |files: &[(&str, &str)]| match CloudConfig::from_bundle_reader(bundle(files)) {
+      Err(error) => error.to_string(),
+      Ok(_) => panic!("bundle should be invalid"),
+    };
+    let files = |config| {
+      vec![
+        (CONFIG_FILE, config),
+        (CA_CERT_FILE, "ca"),
+        (CERT_FILE, "cert"),
+        (KEY_FILE, "key"),
+      ]
+    };
+
+    assert!(error(&files(r#"{"host": "db.example.com", "port": 29080}"#))
+      .contains("No private key found in bundle"));
+    assert!(error(&files(r#"{"host": "db.example.com", "port": 70000}"#))
+      .contains("Invalid port 70000"));
+    assert!(error(&[(CONFIG_FILE, "{}")]).contains("Cannot read ca.crt"));
+  }
+
+  #[test]
+  fn extracts_http_body() {
+    let response = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{}";
+    assert_eq!(http_response_body(response).unwrap(), b"{}");
+
+    let response = b"HTTP/1.1 404 Not Found\r\n\r\n";
+    assert!(http_response_body(response).is_err());
+  }
+}
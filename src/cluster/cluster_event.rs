@@ -0,0 +1,106 @@
+use futures_core::Stream;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use crate::frame::events::{
+    SchemaChange, ServerEvent, StatusChangeType, TopologyChange, TopologyChangeType,
+};
+
+/// Change in the cluster reported by nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClusterEvent {
+    /// Node joined the cluster.
+    NodeAdded(SocketAddr),
+    /// Node left the cluster.
+    NodeRemoved(SocketAddr),
+    /// Node became reachable.
+    NodeUp(SocketAddr),
+    /// Node became unreachable.
+    NodeDown(SocketAddr),
+    /// Keyspace, table, type, function or aggregate got created, updated or dropped.
+    SchemaChanged(SchemaChange),
+}
+
+impl From<ServerEvent> for ClusterEvent {
+    fn from(event: ServerEvent) -> Self {
+        match event {
+            ServerEvent::TopologyChange(TopologyChange { change_type, addr }) => match change_type {
+                TopologyChangeType::NewNode => ClusterEvent::NodeAdded(addr.addr),
+                TopologyChangeType::RemovedNode => ClusterEvent::NodeRemoved(addr.addr),
+            },
+            ServerEvent::StatusChange(change) => match change.change_type {
+                StatusChangeType::Up => ClusterEvent::NodeUp(change.addr.addr),
+                StatusChangeType::Down => ClusterEvent::NodeDown(change.addr.addr),
+            },
+            ServerEvent::SchemaChange(change) => ClusterEvent::SchemaChanged(change),
+        }
+    }
+}
+
+/// Stream of cluster events, ending when the session is dropped.
+pub struct ClusterEventStream {
+    events: UnboundedReceiver<ClusterEvent>,
+}
+
+impl ClusterEventStream {
+    pub(crate) fn new(mut server_events: broadcast::Receiver<ServerEvent>) -> Self {
+        let (sender, events) = unbounded_channel();
+
+        tokio::spawn(async move {
+            loop {
+                match server_events.recv().await {
+                    Ok(event) => {
+                        if sender.send(event.into()).is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(count)) => {
+                        warn!("Cluster event stream missed {} events", count);
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+
+        ClusterEventStream { events }
+    }
+}
+
+impl Stream for ClusterEventStream {
+    type Item = ClusterEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::Cluster;
+    use crate::frame::events::StatusChange;
+    use crate::types::CInet;
+    use std::future::poll_fn;
+
+    #[tokio::test]
+    async fn streams_cluster_events() {
+        let cluster = Cluster::default();
+        let mut stream = ClusterEventStream::new(cluster.subscribe());
+
+        let addr: SocketAddr = "127.0.0.1:9042".parse().unwrap();
+        cluster.publish(ServerEvent::StatusChange(StatusChange {
+            change_type: StatusChangeType::Down,
+            addr: CInet { addr },
+        }));
+
+        let event = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert_eq!(event, Some(ClusterEvent::NodeDown(addr)));
+
+        drop(cluster);
+        let event = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert_eq!(event, None);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::load_balancing::NodeDistance;
+
+/// Snapshot of nodes known to a session, as returned by `Session::cluster_state()`, for
+/// diagnosing routing issues. Serializable with the `serde` feature.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClusterState {
+    pub nodes: Vec<NodeState>,
+}
+
+impl ClusterState {
+    /// Checks if all nodes which are up report the same schema version.
+    pub fn schema_in_agreement(&self) -> bool {
+        let versions: HashSet<Uuid> = self
+            .nodes
+            .iter()
+            .filter(|node| node.status == NodeStatus::Up)
+            .filter_map(|node| node.schema_version)
+            .collect();
+        versions.len() <= 1
+    }
+}
+
+/// Whether a session can send requests to a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NodeStatus {
+    Up,
+    /// The node is marked as down and reconnected to in the background.
+    Down,
+    /// The session has no pool for the node, e.g. because it's rejected by the host filter
+    /// or ignored by the load balancing strategy.
+    NotConnected,
+}
+
+/// State of a single node.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NodeState {
+    pub addr: SocketAddr,
+    pub datacenter: Option<String>,
+    pub rack: Option<String>,
+    pub host_id: Option<Uuid>,
+    pub release_version: Option<String>,
+    /// Schema version reported by the node during the last metadata refresh.
+    pub schema_version: Option<Uuid>,
+    pub status: NodeStatus,
+    pub distance: NodeDistance,
+    /// Open connections of the node's pool, including idle ones.
+    pub connections: u32,
+    pub idle_connections: u32,
+    /// Requests abandoned before their responses arrived.
+    pub orphaned_requests: usize,
+    /// Average latency of requests sent to the node, if any were measured.
+    pub average_latency: Option<Duration>,
+    /// Number of tokens owned by the node on the ring.
+    pub tokens: usize,
+    /// Fraction of the token ring for which the node is the primary replica. Unknown for
+    /// `ByteOrderedPartitioner`, which has no notion of range size.
+    pub ownership: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(status: NodeStatus, schema_version: u128) -> NodeState {
+        NodeState {
+            addr: "127.0.0.1:9042".parse().unwrap(),
+            datacenter: None,
+            rack: None,
+            host_id: None,
+            release_version: None,
+            schema_version: Some(Uuid::from_u128(schema_version)),
+            status,
+            distance: NodeDistance::Local,
+            connections: 0,
+            idle_connections: 0,
+            orphaned_requests: 0,
+            average_latency: None,
+            tokens: 0,
+            ownership: None,
+        }
+    }
+
+    #[test]
+    fn checks_schema_agreement_of_up_nodes() {
+        let state = ClusterState {
+            nodes: vec![node(NodeStatus::Up, 1), node(NodeStatus::Down, 2)],
+        };
+        assert!(state.schema_in_agreement());
+
+        let state = ClusterState {
+            nodes: vec![node(NodeStatus::Up, 1), node(NodeStatus::Up, 2)],
+        };
+        assert!(!state.schema_in_agreement());
+    }
+}
@@ -1,8 +1,15 @@
 use core::time::Duration;
-use std::sync::Arc;
+use rustls::internal::pemfile;
+use std::io::BufReader;
 use std::net;
+use std::sync::Arc;
 
 use crate::authenticators::Authenticator;
+use crate::cluster::hostname_verification::{HostnameVerification, HostnameVerifier};
+use crate::error;
+use crate::proxy::ProxyConfig;
+use crate::retry::{ExponentialReconnectionPolicy, ReconnectionPolicy};
+use crate::transport::TransportConfig;
 
 /// Cluster configuration that holds per node SSL configs
 pub struct ClusterRustlsConfig<A: Authenticator + Sized>(pub Vec<NodeRustlsConfig<A>>);
@@ -18,6 +25,11 @@ pub struct NodeRustlsConfig<A> {
     pub max_lifetime: Option<Duration>,
     pub idle_timeout: Option<Duration>,
     pub connection_timeout: Duration,
+    pub test_on_check_out: bool,
+    pub warm_up: bool,
+    pub warm_up_statements: Vec<String>,
+    pub transport_config: TransportConfig,
+    pub reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
     pub config: Arc<rustls::ClientConfig>,
 }
 
@@ -31,6 +43,11 @@ pub struct NodeRustlsConfigBuilder<A> {
     max_lifetime: Option<Duration>,
     idle_timeout: Option<Duration>,
     connection_timeout: Option<Duration>,
+    test_on_check_out: bool,
+    warm_up: bool,
+    warm_up_statements: Vec<String>,
+    transport_config: TransportConfig,
+    reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
     config: Arc<rustls::ClientConfig>,
 }
 
@@ -51,6 +68,11 @@ impl<A: Authenticator + Sized> NodeRustlsConfigBuilder<A> {
             max_lifetime: None,
             idle_timeout: None,
             connection_timeout: None,
+            test_on_check_out: false,
+            warm_up: false,
+            warm_up_statements: vec![],
+            transport_config: Default::default(),
+            reconnection_policy: Arc::new(ExponentialReconnectionPolicy::default()),
             config,
         }
     }
@@ -65,6 +87,8 @@ impl<A: Authenticator + Sized> NodeRustlsConfigBuilder<A> {
     /// Sets the minimum idle connection count maintained by the pool.
     /// If set, the pool will try to maintain at least this many idle
     /// connections at all times, while respecting the value of `max_size`.
+    /// Since connections are multiplexed and returned to the pool right after a request is
+    /// queued, this is effectively the core number of connections opened to a node.
     /// Defaults to None (equivalent to the value of `max_size`).
     pub fn min_idle(mut self, min_idle: Option<u32>) -> Self {
         self.min_idle = min_idle;
@@ -88,13 +112,202 @@ impl<A: Authenticator + Sized> NodeRustlsConfigBuilder<A> {
         self
     }
 
-    /// Sets the connection timeout used by the pool.
+    /// Sets the connection timeout used by the pool. It limits the time spent waiting
+    /// for a connection to be borrowed from the pool.
     /// Defaults to 30 seconds.
     pub fn connection_timeout(mut self, connection_timeout: Duration) -> Self {
         self.connection_timeout = Some(connection_timeout);
         self
     }
 
+    /// Sets whether connections should be validated with an OPTIONS request each time they
+    /// are borrowed from the pool. Broken connections are detected and replaced regardless.
+    /// Defaults to false.
+    pub fn test_on_check_out(mut self, test_on_check_out: bool) -> Self {
+        self.test_on_check_out = test_on_check_out;
+        self
+    }
+
+    /// Sets whether all connections of the pool should be established before the session is
+    /// created, so first requests don't wait for new connections. `min_idle` connections are
+    /// established if set, `max_size` otherwise.
+    /// Defaults to false.
+    pub fn warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Sets statements prepared on the node once warm-up establishes its connections. Prepared
+    /// statements are shared by all connections of a node, and they're added to the session
+    /// cache used by `ExecExecutor::execute_cached()`, so their first executions don't wait for
+    /// preparation. No keyspace is selected during warm-up, so the statements should use fully
+    /// qualified table names. Has no effect unless `warm_up` is enabled.
+    /// Defaults to no statements.
+    pub fn warm_up_statements(mut self, statements: Vec<String>) -> Self {
+        self.warm_up_statements = statements;
+        self
+    }
+
+    /// Sets the interval of inactivity after which a connection sends an OPTIONS request to
+    /// check if it is still alive. Connections which don't respond in time are considered broken
+    /// and replaced by the pool.
+    /// Defaults to None (heartbeats disabled).
+    pub fn heartbeat_interval(mut self, heartbeat_interval: Option<Duration>) -> Self {
+        self.transport_config.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Sets the time to wait for a heartbeat response.
+    /// Defaults to 30 seconds.
+    pub fn heartbeat_timeout(mut self, heartbeat_timeout: Duration) -> Self {
+        self.transport_config.heartbeat_timeout = heartbeat_timeout;
+        self
+    }
+
+    /// Sets the time limit for establishing a single connection, including TLS handshake.
+    /// Defaults to 5 seconds.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.transport_config.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the time limit for receiving a response to a request. Requests which exceed it fail
+    /// with `Error::Timeout`.
+    /// Defaults to 12 seconds.
+    pub fn request_timeout(mut self, request_timeout: Option<Duration>) -> Self {
+        self.transport_config.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the maximum number of requests in flight on a single connection. When all
+    /// connections of a node are busy, the pool opens additional ones up to `max_size`, after
+    /// which requests wait for a free slot within their request timeout.
+    /// Defaults to 1024. None means no limit other than the number of stream ids.
+    pub fn max_in_flight_requests(mut self, max_in_flight_requests: Option<usize>) -> Self {
+        self.transport_config.max_in_flight_requests = max_in_flight_requests;
+        self
+    }
+
+    /// Sets `TCP_NODELAY` on connection sockets, which disables Nagle's algorithm.
+    /// Defaults to true.
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.transport_config.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Sets `SO_KEEPALIVE` on connection sockets.
+    /// Defaults to false.
+    pub fn tcp_keepalive(mut self, tcp_keepalive: bool) -> Self {
+        self.transport_config.tcp_keepalive = tcp_keepalive;
+        self
+    }
+
+    /// Sets the size of socket send buffers (`SO_SNDBUF`).
+    /// Defaults to None (system default).
+    pub fn send_buffer_size(mut self, send_buffer_size: Option<u32>) -> Self {
+        self.transport_config.send_buffer_size = send_buffer_size;
+        self
+    }
+
+    /// Sets the size of socket receive buffers (`SO_RCVBUF`).
+    /// Defaults to None (system default).
+    pub fn recv_buffer_size(mut self, recv_buffer_size: Option<u32>) -> Self {
+        self.transport_config.recv_buffer_size = recv_buffer_size;
+        self
+    }
+
+    /// Sets the local address which connection sockets are bound to.
+    /// Defaults to None (chosen by the system).
+    pub fn local_addr(mut self, local_addr: Option<net::SocketAddr>) -> Self {
+        self.transport_config.local_addr = local_addr;
+        self
+    }
+
+    /// Sets the SOCKS5 or HTTP CONNECT proxy which connections are tunneled through.
+    /// Defaults to None (direct connections).
+    pub fn proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.transport_config.proxy = proxy;
+        self
+    }
+
+    /// Sets the policy which decides how often reconnection to a node is attempted when it goes
+    /// down.
+    /// Defaults to `ExponentialReconnectionPolicy` with delays from 1 second to 1 minute.
+    pub fn reconnection_policy(
+        mut self,
+        reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    ) -> Self {
+        self.reconnection_policy = reconnection_policy;
+        self
+    }
+
+    /// Sets the client certificate chain and private key presented during TLS handshake, which
+    /// is required by clusters with `require_client_auth: true`. The certificate is set on a copy
+    /// of the rustls client config, so other nodes sharing the config aren't affected.
+    pub fn client_cert(
+        mut self,
+        cert_chain: Vec<rustls::Certificate>,
+        key: rustls::PrivateKey,
+    ) -> error::Result<Self> {
+        let mut config = (*self.config).clone();
+        config
+            .set_single_client_cert(cert_chain, key)
+            .map_err(|err| error::Error::General(format!("Invalid client certificate: {}", err)))?;
+
+        self.config = Arc::new(config);
+        Ok(self)
+    }
+
+    /// Sets the client certificate chain and private key from PEM encoded data. The key can be
+    /// either PKCS#8 or RSA. PKCS#12 bundles need to be converted to PEM first, e.g. with
+    /// `openssl pkcs12 -in client.p12 -nodes`.
+    pub fn client_cert_pem(self, cert_chain: &[u8], key: &[u8]) -> error::Result<Self> {
+        let cert_chain = pemfile::certs(&mut BufReader::new(cert_chain))
+            .map_err(|_| error::Error::General("Invalid client certificate PEM".into()))?;
+        if cert_chain.is_empty() {
+            return Err(error::Error::General(
+                "No certificates found in client certificate PEM".into(),
+            ));
+        }
+
+        let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(key))
+            .map_err(|_| error::Error::General("Invalid client key PEM".into()))?;
+        if keys.is_empty() {
+            keys = pemfile::rsa_private_keys(&mut BufReader::new(key))
+                .map_err(|_| error::Error::General("Invalid client key PEM".into()))?;
+        }
+
+        let key = keys
+            .pop()
+            .ok_or_else(|| error::Error::General("No private key found in client key PEM".into()))?;
+
+        self.client_cert(cert_chain, key)
+    }
+
+    /// Sets how the server name in node certificates is checked against the node DNS name.
+    /// The setting is applied to a copy of the rustls client config.
+    /// Defaults to `HostnameVerification::Wildcard`.
+    pub fn hostname_verification(mut self, verification: HostnameVerification) -> Self {
+        let mut config = (*self.config).clone();
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(HostnameVerifier::new(verification)));
+
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// Sets whether the node DNS name is sent as SNI during TLS handshake. The DNS name is
+    /// given per node in `NodeRustlsConfigBuilder::new`.
+    /// Defaults to true.
+    pub fn enable_sni(mut self, enable_sni: bool) -> Self {
+        let mut config = (*self.config).clone();
+        config.enable_sni = enable_sni;
+
+        self.config = Arc::new(config);
+        self
+    }
+
     /// Sets new authenticator.
     pub fn authenticator(mut self, authenticator: A) -> Self {
         self.authenticator = authenticator;
@@ -116,6 +329,11 @@ impl<A: Authenticator + Sized> NodeRustlsConfigBuilder<A> {
             connection_timeout: self
                 .connection_timeout
                 .unwrap_or(Self::DEFAULT_CONNECTION_TIMEOUT),
+            test_on_check_out: self.test_on_check_out,
+            warm_up: self.warm_up,
+            warm_up_statements: self.warm_up_statements,
+            transport_config: self.transport_config,
+            reconnection_policy: self.reconnection_policy,
         }
     }
 }
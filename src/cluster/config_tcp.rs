@@ -1,6 +1,11 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::authenticators::Authenticator;
+use crate::proxy::ProxyConfig;
+use crate::retry::{ExponentialReconnectionPolicy, ReconnectionPolicy};
+use crate::transport::TransportConfig;
 
 /// Cluster configuration that holds per node TCP configs
 pub struct ClusterTcpConfig<'a, A: Authenticator + Sized>(pub Vec<NodeTcpConfig<'a, A>>);
@@ -15,6 +20,33 @@ pub struct NodeTcpConfig<'a, A> {
     pub max_lifetime: Option<Duration>,
     pub idle_timeout: Option<Duration>,
     pub connection_timeout: Duration,
+    pub test_on_check_out: bool,
+    pub warm_up: bool,
+    pub warm_up_statements: Vec<String>,
+    pub transport_config: TransportConfig,
+    pub reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    pub dns_refresh_interval: Option<Duration>,
+}
+
+impl<'a, A: Clone> NodeTcpConfig<'a, A> {
+    /// Returns a copy of the config with a different node address.
+    pub(crate) fn with_addr<'b>(&self, addr: &'b str) -> NodeTcpConfig<'b, A> {
+        NodeTcpConfig {
+            addr,
+            authenticator: self.authenticator.clone(),
+            max_size: self.max_size,
+            min_idle: self.min_idle,
+            max_lifetime: self.max_lifetime,
+            idle_timeout: self.idle_timeout,
+            connection_timeout: self.connection_timeout,
+            test_on_check_out: self.test_on_check_out,
+            warm_up: self.warm_up,
+            warm_up_statements: self.warm_up_statements.clone(),
+            transport_config: self.transport_config.clone(),
+            reconnection_policy: self.reconnection_policy.clone(),
+            dns_refresh_interval: self.dns_refresh_interval,
+        }
+    }
 }
 
 /// Builder structure that helps to configure TCP connection for node.
@@ -26,6 +58,12 @@ pub struct NodeTcpConfigBuilder<'a, A> {
     max_lifetime: Option<Duration>,
     idle_timeout: Option<Duration>,
     connection_timeout: Option<Duration>,
+    test_on_check_out: bool,
+    warm_up: bool,
+    warm_up_statements: Vec<String>,
+    transport_config: TransportConfig,
+    reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    dns_refresh_interval: Option<Duration>,
 }
 
 impl<'a, A: Authenticator + Sized> NodeTcpConfigBuilder<'a, A> {
@@ -44,6 +82,12 @@ impl<'a, A: Authenticator + Sized> NodeTcpConfigBuilder<'a, A> {
             max_lifetime: None,
             idle_timeout: None,
             connection_timeout: None,
+            test_on_check_out: false,
+            warm_up: false,
+            warm_up_statements: vec![],
+            transport_config: Default::default(),
+            reconnection_policy: Arc::new(ExponentialReconnectionPolicy::default()),
+            dns_refresh_interval: None,
         }
     }
 
@@ -57,6 +101,8 @@ impl<'a, A: Authenticator + Sized> NodeTcpConfigBuilder<'a, A> {
     /// Sets the minimum idle connection count maintained by the pool.
     /// If set, the pool will try to maintain at least this many idle
     /// connections at all times, while respecting the value of `max_size`.
+    /// Since connections are multiplexed and returned to the pool right after a request is
+    /// queued, this is effectively the core number of connections opened to a node.
     /// Defaults to None (equivalent to the value of `max_size`).
     pub fn min_idle(mut self, min_idle: Option<u32>) -> Self {
         self.min_idle = min_idle;
@@ -80,13 +126,154 @@ impl<'a, A: Authenticator + Sized> NodeTcpConfigBuilder<'a, A> {
         self
     }
 
-    /// Sets the connection timeout used by the pool.
+    /// Sets the connection timeout used by the pool. It limits the time spent waiting
+    /// for a connection to be borrowed from the pool.
     /// Defaults to 30 seconds.
     pub fn connection_timeout(mut self, connection_timeout: Duration) -> Self {
         self.connection_timeout = Some(connection_timeout);
         self
     }
 
+    /// Sets whether connections should be validated with an OPTIONS request each time they
+    /// are borrowed from the pool. Broken connections are detected and replaced regardless.
+    /// Defaults to false.
+    pub fn test_on_check_out(mut self, test_on_check_out: bool) -> Self {
+        self.test_on_check_out = test_on_check_out;
+        self
+    }
+
+    /// Sets whether all connections of the pool should be established before the session is
+    /// created, so first requests don't wait for new connections. `min_idle` connections are
+    /// established if set, `max_size` otherwise.
+    /// Defaults to false.
+    pub fn warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Sets statements prepared on the node once warm-up establishes its connections. Prepared
+    /// statements are shared by all connections of a node, and they're added to the session
+    /// cache used by `ExecExecutor::execute_cached()`, so their first executions don't wait for
+    /// preparation. No keyspace is selected during warm-up, so the statements should use fully
+    /// qualified table names. Has no effect unless `warm_up` is enabled.
+    /// Defaults to no statements.
+    pub fn warm_up_statements(mut self, statements: Vec<String>) -> Self {
+        self.warm_up_statements = statements;
+        self
+    }
+
+    /// Sets the interval of inactivity after which a connection sends an OPTIONS request to
+    /// check if it is still alive. Connections which don't respond in time are considered broken
+    /// and replaced by the pool.
+    /// Defaults to None (heartbeats disabled).
+    pub fn heartbeat_interval(mut self, heartbeat_interval: Option<Duration>) -> Self {
+        self.transport_config.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Sets the time to wait for a heartbeat response.
+    /// Defaults to 30 seconds.
+    pub fn heartbeat_timeout(mut self, heartbeat_timeout: Duration) -> Self {
+        self.transport_config.heartbeat_timeout = heartbeat_timeout;
+        self
+    }
+
+    /// Sets the time limit for establishing a single connection, including TLS handshake.
+    /// Defaults to 5 seconds.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.transport_config.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the time limit for receiving a response to a request. Requests which exceed it fail
+    /// with `Error::Timeout`.
+    /// Defaults to 12 seconds.
+    pub fn request_timeout(mut self, request_timeout: Option<Duration>) -> Self {
+        self.transport_config.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the maximum number of requests in flight on a single connection. When all
+    /// connections of a node are busy, the pool opens additional ones up to `max_size`, after
+    /// which requests wait for a free slot within their request timeout.
+    /// Defaults to 1024. None means no limit other than the number of stream ids.
+    pub fn max_in_flight_requests(mut self, max_in_flight_requests: Option<usize>) -> Self {
+        self.transport_config.max_in_flight_requests = max_in_flight_requests;
+        self
+    }
+
+    /// Sets `TCP_NODELAY` on connection sockets, which disables Nagle's algorithm.
+    /// Defaults to true.
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.transport_config.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Sets `SO_KEEPALIVE` on connection sockets.
+    /// Defaults to false.
+    pub fn tcp_keepalive(mut self, tcp_keepalive: bool) -> Self {
+        self.transport_config.tcp_keepalive = tcp_keepalive;
+        self
+    }
+
+    /// Sets the size of socket send buffers (`SO_SNDBUF`).
+    /// Defaults to None (system default).
+    pub fn send_buffer_size(mut self, send_buffer_size: Option<u32>) -> Self {
+        self.transport_config.send_buffer_size = send_buffer_size;
+        self
+    }
+
+    /// Sets the size of socket receive buffers (`SO_RCVBUF`).
+    /// Defaults to None (system default).
+    pub fn recv_buffer_size(mut self, recv_buffer_size: Option<u32>) -> Self {
+        self.transport_config.recv_buffer_size = recv_buffer_size;
+        self
+    }
+
+    /// Sets the local address which connection sockets are bound to.
+    /// Defaults to None (chosen by the system).
+    pub fn local_addr(mut self, local_addr: Option<SocketAddr>) -> Self {
+        self.transport_config.local_addr = local_addr;
+        self
+    }
+
+    /// Sets the SOCKS5 or HTTP CONNECT proxy which connections are tunneled through.
+    /// Defaults to None (direct connections).
+    pub fn proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.transport_config.proxy = proxy;
+        self
+    }
+
+    /// Sets the delay between staggered connection attempts when node hostname resolves to
+    /// multiple addresses, e.g. both IPv4 and IPv6 ones (Happy Eyeballs). The first established
+    /// connection is used, which avoids long timeouts on unreachable addresses.
+    /// Defaults to 250 milliseconds. None connects only to the first resolved address.
+    pub fn happy_eyeballs_delay(mut self, happy_eyeballs_delay: Option<Duration>) -> Self {
+        self.transport_config.happy_eyeballs_delay = happy_eyeballs_delay;
+        self
+    }
+
+    /// Sets the policy which decides how often reconnection to a node is attempted when it goes
+    /// down.
+    /// Defaults to `ExponentialReconnectionPolicy` with delays from 1 second to 1 minute.
+    pub fn reconnection_policy(
+        mut self,
+        reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    ) -> Self {
+        self.reconnection_policy = reconnection_policy;
+        self
+    }
+
+    /// Sets the interval of re-resolving node hostname. If set, each address the hostname
+    /// resolves to gets its own pool and the hostname is periodically resolved again - pools are
+    /// created for new addresses and removed for addresses which are gone. This keeps sessions
+    /// working when node IPs behind a DNS name change, e.g. in Kubernetes.
+    /// Defaults to None (hostname resolved only when connecting).
+    pub fn dns_refresh_interval(mut self, dns_refresh_interval: Option<Duration>) -> Self {
+        self.dns_refresh_interval = dns_refresh_interval;
+        self
+    }
+
     /// Sets new authenticator.
     pub fn authenticator(mut self, authenticator: A) -> Self {
         self.authenticator = authenticator;
@@ -106,6 +293,12 @@ impl<'a, A: Authenticator + Sized> NodeTcpConfigBuilder<'a, A> {
             connection_timeout: self
                 .connection_timeout
                 .unwrap_or(Self::DEFAULT_CONNECTION_TIMEOUT),
+            test_on_check_out: self.test_on_check_out,
+            warm_up: self.warm_up,
+            warm_up_statements: self.warm_up_statements,
+            transport_config: self.transport_config,
+            reconnection_policy: self.reconnection_policy,
+            dns_refresh_interval: self.dns_refresh_interval,
         }
     }
 }
@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::authenticators::Authenticator;
+use crate::retry::{ExponentialReconnectionPolicy, ReconnectionPolicy};
+use crate::transport::TransportConfig;
+
+/// Cluster configuration that holds per node Unix socket configs
+pub struct ClusterUnixConfig<A: Authenticator + Sized>(pub Vec<NodeUnixConfig<A>>);
+
+/// Single node Unix socket connection config.
+#[derive(Clone)]
+pub struct NodeUnixConfig<A> {
+    pub path: PathBuf,
+    pub authenticator: A,
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    pub max_lifetime: Option<Duration>,
+    pub idle_timeout: Option<Duration>,
+    pub connection_timeout: Duration,
+    pub test_on_check_out: bool,
+    pub warm_up: bool,
+    pub warm_up_statements: Vec<String>,
+    pub transport_config: TransportConfig,
+    pub reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+}
+
+/// Builder structure that helps to configure Unix socket connection for node.
+pub struct NodeUnixConfigBuilder<A> {
+    path: PathBuf,
+    authenticator: A,
+    max_size: Option<u32>,
+    min_idle: Option<u32>,
+    max_lifetime: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    connection_timeout: Option<Duration>,
+    test_on_check_out: bool,
+    warm_up: bool,
+    warm_up_statements: Vec<String>,
+    transport_config: TransportConfig,
+    reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+}
+
+impl<A: Authenticator + Sized> NodeUnixConfigBuilder<A> {
+    const DEFAULT_MAX_SIZE: u32 = 10;
+    const DEFAULT_CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// `NodeUnixConfigBuilder` constructor function. It receives
+    /// * socket path
+    /// * authenticator
+    pub fn new<P: Into<PathBuf>>(path: P, authenticator: A) -> Self {
+        NodeUnixConfigBuilder {
+            path: path.into(),
+            authenticator,
+            max_size: None,
+            min_idle: None,
+            max_lifetime: None,
+            idle_timeout: None,
+            connection_timeout: None,
+            test_on_check_out: false,
+            warm_up: false,
+            warm_up_statements: vec![],
+            transport_config: Default::default(),
+            reconnection_policy: Arc::new(ExponentialReconnectionPolicy::default()),
+        }
+    }
+
+    /// Sets the maximum number of connections managed by the pool.
+    /// Defaults to 10.
+    pub fn max_size(mut self, size: u32) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Sets the minimum idle connection count maintained by the pool.
+    /// If set, the pool will try to maintain at least this many idle
+    /// connections at all times, while respecting the value of `max_size`.
+    /// Since connections are multiplexed and returned to the pool right after a request is
+    /// queued, this is effectively the core number of connections opened to a node.
+    /// Defaults to None (equivalent to the value of `max_size`).
+    pub fn min_idle(mut self, min_idle: Option<u32>) -> Self {
+        self.min_idle = min_idle;
+        self
+    }
+
+    /// Sets the maximum lifetime of connections in the pool.
+    /// If set, connections will be closed after existing for at most 30 seconds beyond this duration.
+    /// If a connection reaches its maximum lifetime while checked out it will be closed when it is returned to the pool.
+    /// Defaults to 30 minutes.
+    pub fn max_lifetime(mut self, max_lifetime: Option<Duration>) -> Self {
+        self.max_lifetime = max_lifetime;
+        self
+    }
+
+    /// Sets the idle timeout used by the pool.
+    /// If set, connections will be closed after sitting idle for at most 30 seconds beyond this duration.
+    /// Defaults to 10 minutes.
+    pub fn idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets the connection timeout used by the pool. It limits the time spent waiting
+    /// for a connection to be borrowed from the pool.
+    /// Defaults to 30 seconds.
+    pub fn connection_timeout(mut self, connection_timeout: Duration) -> Self {
+        self.connection_timeout = Some(connection_timeout);
+        self
+    }
+
+    /// Sets whether connections should be validated with an OPTIONS request each time they
+    /// are borrowed from the pool. Broken connections are detected and replaced regardless.
+    /// Defaults to false.
+    pub fn test_on_check_out(mut self, test_on_check_out: bool) -> Self {
+        self.test_on_check_out = test_on_check_out;
+        self
+    }
+
+    /// Sets whether all connections of the pool should be established before the session is
+    /// created, so first requests don't wait for new connections. `min_idle` connections are
+    /// established if set, `max_size` otherwise.
+    /// Defaults to false.
+    pub fn warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Sets statements prepared on the node once warm-up establishes its connections. Prepared
+    /// statements are shared by all connections of a node, and they're added to the session
+    /// cache used by `ExecExecutor::execute_cached()`, so their first executions don't wait for
+    /// preparation. No keyspace is selected during warm-up, so the statements should use fully
+    /// qualified table names. Has no effect unless `warm_up` is enabled.
+    /// Defaults to no statements.
+    pub fn warm_up_statements(mut self, statements: Vec<String>) -> Self {
+        self.warm_up_statements = statements;
+        self
+    }
+
+    /// Sets the interval of inactivity after which a connection sends an OPTIONS request to
+    /// check if it is still alive. Connections which don't respond in time are considered broken
+    /// and replaced by the pool.
+    /// Defaults to None (heartbeats disabled).
+    pub fn heartbeat_interval(mut self, heartbeat_interval: Option<Duration>) -> Self {
+        self.transport_config.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Sets the time to wait for a heartbeat response.
+    /// Defaults to 30 seconds.
+    pub fn heartbeat_timeout(mut self, heartbeat_timeout: Duration) -> Self {
+        self.transport_config.heartbeat_timeout = heartbeat_timeout;
+        self
+    }
+
+    /// Sets the time limit for establishing a single connection.
+    /// Defaults to 5 seconds.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.transport_config.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the time limit for receiving a response to a request. Requests which exceed it fail
+    /// with `Error::Timeout`.
+    /// Defaults to 12 seconds.
+    pub fn request_timeout(mut self, request_timeout: Option<Duration>) -> Self {
+        self.transport_config.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the maximum number of requests in flight on a single connection. When all
+    /// connections of a node are busy, the pool opens additional ones up to `max_size`, after
+    /// which requests wait for a free slot within their request timeout.
+    /// Defaults to 1024. None means no limit other than the number of stream ids.
+    pub fn max_in_flight_requests(mut self, max_in_flight_requests: Option<usize>) -> Self {
+        self.transport_config.max_in_flight_requests = max_in_flight_requests;
+        self
+    }
+
+    /// Sets the policy which decides how often reconnection to a node is attempted when it goes
+    /// down.
+    /// Defaults to `ExponentialReconnectionPolicy` with delays from 1 second to 1 minute.
+    pub fn reconnection_policy(
+        mut self,
+        reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    ) -> Self {
+        self.reconnection_policy = reconnection_policy;
+        self
+    }
+
+    /// Sets new authenticator.
+    pub fn authenticator(mut self, authenticator: A) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
+    /// Finalizes building process and returns `NodeUnixConfig`
+    pub fn build(self) -> NodeUnixConfig<A> {
+        NodeUnixConfig {
+            path: self.path,
+            authenticator: self.authenticator,
+
+            max_size: self.max_size.unwrap_or(Self::DEFAULT_MAX_SIZE),
+            min_idle: self.min_idle,
+            max_lifetime: self.max_lifetime,
+            idle_timeout: self.idle_timeout,
+            connection_timeout: self
+                .connection_timeout
+                .unwrap_or(Self::DEFAULT_CONNECTION_TIMEOUT),
+            test_on_check_out: self.test_on_check_out,
+            warm_up: self.warm_up,
+            warm_up_statements: self.warm_up_statements,
+            transport_config: self.transport_config,
+            reconnection_policy: self.reconnection_policy,
+        }
+    }
+}
@@ -0,0 +1,581 @@
+use fnv::FnvHashMap;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::sync::{oneshot, Mutex};
+use tokio::time::{sleep_until, Instant};
+
+use crate::authenticators::Authenticator;
+use crate::cluster::topology::fetch_nodes;
+use crate::cluster::{
+  fetch_metadata, fetch_partitioner, new_lazy_tcp_pool, startup, AddressTranslator, Cluster,
+  HostFilter, Node, NodeTcpConfig, RequestTracker, TcpConnectionPool, TokenMap,
+};
+use crate::compression::Compression;
+use crate::error;
+use crate::frame::events::{ServerEvent, SimpleServerEvent, StatusChange, StatusChangeType};
+use crate::frame::Frame;
+use crate::load_balancing::{LoadBalancingStrategy, NodeDistance};
+use crate::query::{prepare_on_node, PreparedCache};
+use crate::transport::{CDRSTransport, TransportTcp};
+
+/// Request for an immediate refresh, answered with its result.
+pub(crate) type RefreshRequest = oneshot::Sender<error::Result<()>>;
+
+/// Reason for waking up the control connection task.
+enum Wakeup {
+  Event(ServerEvent),
+  Refresh,
+  RefreshRequested(RefreshRequest),
+  Closed,
+}
+
+/// Connection used for discovering cluster nodes, kept open to one of them. Pools of discovered
+/// nodes share the configuration of the first contact point. The connection is registered for
+/// server events, which are published to cluster subscribers. Nodes reported down stop being
+/// routed to until they're reconnected to or reported up. Nodes rejected by the host filter are
+/// left out, apart from the token ring. Pools are sized according to node distances decided by
+/// the load balancing strategy - remote nodes get a single connection and ignored ones none.
+///
+/// Topology and schema changes are debounced - a refresh happens once the debounce window after
+/// the first of them passes, so bursts of events cause a single refresh.
+pub(crate) struct ControlConnection<A: Authenticator + 'static> {
+  node_config: NodeTcpConfig<'static, A>,
+  contact_points: Vec<String>,
+  compression: Compression,
+  address_translator: Arc<dyn AddressTranslator>,
+  host_filter: Arc<dyn HostFilter>,
+  refresh_interval: Duration,
+  refresh_debounce: Duration,
+  cluster: Arc<Cluster>,
+  transport: Option<TransportTcp>,
+  event_frames: Option<UnboundedReceiver<Frame>>,
+  pools: FnvHashMap<SocketAddr, Arc<TcpConnectionPool<A>>>,
+  distances: FnvHashMap<SocketAddr, NodeDistance>,
+  prepared_cache: Arc<PreparedCache>,
+}
+
+impl<A: Authenticator + 'static> ControlConnection<A> {
+  pub(crate) fn new(
+    node_config: &NodeTcpConfig<'_, A>,
+    contact_points: Vec<String>,
+    compression: Compression,
+    address_translator: Arc<dyn AddressTranslator>,
+    host_filter: Arc<dyn HostFilter>,
+    refresh_interval: Duration,
+    cluster: Arc<Cluster>,
+  ) -> Self {
+    ControlConnection {
+      node_config: node_config.with_addr(""),
+      contact_points,
+      compression,
+      address_translator,
+      host_filter,
+      refresh_interval,
+      refresh_debounce: Duration::from_secs(0),
+      cluster,
+      transport: None,
+      event_frames: None,
+      pools: Default::default(),
+      distances: Default::default(),
+      prepared_cache: Default::default(),
+    }
+  }
+
+  /// Sets how long topology and schema changes are collected before refreshing. Default is no
+  /// debouncing.
+  pub(crate) fn with_refresh_debounce(mut self, refresh_debounce: Duration) -> Self {
+    self.refresh_debounce = refresh_debounce;
+    self
+  }
+
+  /// Sets the cache of statements prepared by the session, which are prepared on nodes
+  /// joining the cluster. Default is an empty cache.
+  pub(crate) fn with_prepared_cache(mut self, prepared_cache: Arc<PreparedCache>) -> Self {
+    self.prepared_cache = prepared_cache;
+    self
+  }
+
+  /// Fetches current cluster nodes and schema, and stores them in the cluster. Only nodes
+  /// accepted by the host filter are stored.
+  pub(crate) async fn refresh(&mut self) -> error::Result<Vec<Arc<Node>>> {
+    let transport = self.connect().await?;
+
+    let nodes = match fetch_nodes(&transport, &*self.address_translator).await {
+      Ok(nodes) => nodes,
+      Err(err) => {
+        // the node might be in a bad state, so try another one next time
+        self.transport = None;
+        self.event_frames = None;
+        return Err(err);
+      }
+    };
+
+    // nodes are refreshed even if the schema can't be read, e.g. on versions without
+    // system_schema
+    let metadata = match fetch_metadata(&transport).await {
+      Ok(metadata) => {
+        self.cluster.set_metadata(metadata);
+        self.cluster.metadata()
+      }
+      Err(err) => {
+        warn!("Unable to refresh schema metadata: {}", err);
+        self.cluster.metadata()
+      }
+    };
+
+    // without a supported partitioner tokens can't be computed, so nothing is routed
+    let partitioner = match fetch_partitioner(&transport).await {
+      Ok(Some(partitioner)) => {
+        self.cluster.set_partitioner(partitioner.clone());
+        Some(partitioner)
+      }
+      Ok(None) => None,
+      Err(err) => {
+        warn!("Unable to detect partitioner: {}", err);
+        Some(self.cluster.partitioner())
+      }
+    };
+
+    let nodes: Vec<Arc<Node>> = nodes.into_iter().map(Arc::new).collect();
+    let token_map = match partitioner {
+      Some(partitioner) => TokenMap::new(
+        &nodes,
+        metadata.replication_strategies(),
+        &*partitioner,
+      ),
+      None => TokenMap::default(),
+    };
+    self.cluster.set_token_map(token_map);
+
+    // the ring needs all nodes for replicas to be placed right, but rejected ones are never
+    // connected to
+    let nodes: Vec<Arc<Node>> = nodes
+      .into_iter()
+      .filter(|node| self.host_filter.accept(node))
+      .collect();
+    self.cluster.set_nodes(nodes.clone());
+
+    Ok(nodes)
+  }
+
+  /// Creates a pool for a discovered node, which connects in the background.
+  pub(crate) fn add_pool(
+    &mut self,
+    addr: SocketAddr,
+    distance: NodeDistance,
+  ) -> error::Result<Arc<TcpConnectionPool<A>>> {
+    let addr_string = addr.to_string();
+    let mut node_config = self.node_config.with_addr(&addr_string);
+    if distance == NodeDistance::Remote {
+      node_config.max_size = 1;
+      node_config.min_idle = None;
+    }
+
+    let pool = Arc::new(new_lazy_tcp_pool(node_config, self.compression)?);
+    self.pools.insert(addr, pool.clone());
+    self.distances.insert(addr, distance);
+
+    Ok(pool)
+  }
+
+  /// Dispatches server events and refreshes nodes until the session is dropped or shut down.
+  /// Nodes are refreshed periodically, on topology and schema changes, on request and when the
+  /// control connection needs to be reopened.
+  pub(crate) async fn run<LB>(
+    mut self,
+    load_balancing: Weak<Mutex<LB>>,
+    requests: Arc<RequestTracker>,
+    mut refresh_requests: UnboundedReceiver<RefreshRequest>,
+  ) where
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Send,
+  {
+    let mut next_refresh = Instant::now() + self.refresh_interval;
+
+    loop {
+      let wakeup = self.next_event(next_refresh, &mut refresh_requests).await;
+
+      let load_balancing = match load_balancing.upgrade() {
+        Some(load_balancing) => load_balancing,
+        None => return,
+      };
+
+      if requests.is_shut_down() {
+        return;
+      }
+
+      let refresh_request = match wakeup {
+        Wakeup::Event(event) => {
+          let event = self.translate(event);
+          if let ServerEvent::StatusChange(ref change) = event {
+            self.update_status(change);
+          }
+
+          // replicas depend on both the ring and keyspace replication settings, and schema
+          // metadata on any schema change
+          let needs_refresh = matches!(
+            event,
+            ServerEvent::TopologyChange(_) | ServerEvent::SchemaChange(_)
+          );
+          self.cluster.publish(event);
+
+          if needs_refresh {
+            next_refresh = next_refresh.min(Instant::now() + self.refresh_debounce);
+          }
+
+          continue;
+        }
+        Wakeup::Refresh => None,
+        Wakeup::RefreshRequested(refresh_request) => Some(refresh_request),
+        Wakeup::Closed => return,
+      };
+
+      next_refresh = Instant::now() + self.refresh_interval;
+
+      let previous = self.cluster.nodes();
+      let result = match self.refresh().await {
+        Ok(nodes) => {
+          self.reconcile(&mut *load_balancing.lock().await, &previous, &nodes);
+          Ok(())
+        }
+        Err(err) => {
+          warn!("Unable to refresh cluster nodes: {}", err);
+          Err(err)
+        }
+      };
+
+      if let Some(refresh_request) = refresh_request {
+        // the requester might have given up waiting
+        let _ = refresh_request.send(result);
+      }
+    }
+  }
+
+  /// Waits for the next server event or refresh request. Returns `Wakeup::Refresh` when it's
+  /// time to refresh nodes or the control connection got closed.
+  async fn next_event(
+    &mut self,
+    next_refresh: Instant,
+    refresh_requests: &mut UnboundedReceiver<RefreshRequest>,
+  ) -> Wakeup {
+    loop {
+      let event_frames = match self.event_frames {
+        Some(ref mut event_frames) => event_frames,
+        None => {
+          return tokio::select! {
+            _ = sleep_until(next_refresh) => Wakeup::Refresh,
+            refresh_request = refresh_requests.recv() => requested_refresh(refresh_request),
+          };
+        }
+      };
+
+      let frame = tokio::select! {
+        _ = sleep_until(next_refresh) => return Wakeup::Refresh,
+        refresh_request = refresh_requests.recv() => return requested_refresh(refresh_request),
+        frame = event_frames.recv() => frame,
+      };
+
+      let frame = match frame {
+        Some(frame) => frame,
+        None => {
+          // refreshing reopens the connection and registers for events again
+          warn!("Control connection closed - reconnecting");
+          self.event_frames = None;
+          return Wakeup::Refresh;
+        }
+      };
+
+      match frame.get_body().map(|body| body.into_server_event()) {
+        Ok(Some(event)) => return Wakeup::Event(event.event),
+        Ok(None) => warn!("Unexpected frame received as server event"),
+        Err(err) => warn!("Unable to parse server event: {}", err),
+      }
+    }
+  }
+
+  /// Translates node addresses of an event, so they match addresses of discovered nodes.
+  fn translate(&self, mut event: ServerEvent) -> ServerEvent {
+    match event {
+      ServerEvent::TopologyChange(ref mut change) => {
+        change.addr.addr = self.address_translator.translate(change.addr.addr)
+      }
+      ServerEvent::StatusChange(ref mut change) => {
+        change.addr.addr = self.address_translator.translate(change.addr.addr)
+      }
+      ServerEvent::SchemaChange(_) => {}
+    }
+
+    event
+  }
+
+  fn update_status(&self, change: &StatusChange) {
+    let pool = match self.pools.get(&change.addr.addr) {
+      Some(pool) => pool,
+      None => return,
+    };
+
+    match change.change_type {
+      StatusChangeType::Down => pool.mark_down(),
+      StatusChangeType::Up => pool.mark_up(),
+    }
+  }
+
+  fn reconcile<LB>(&mut self, load_balancing: &mut LB, previous: &[Arc<Node>], nodes: &[Arc<Node>])
+  where
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>>,
+  {
+    load_balancing.update_nodes(nodes);
+
+    let previous: HashSet<SocketAddr> = previous.iter().map(|node| node.addr).collect();
+    let current: HashSet<SocketAddr> = nodes.iter().map(|node| node.addr).collect();
+    let distances: FnvHashMap<SocketAddr, NodeDistance> = nodes
+      .iter()
+      .map(|node| (node.addr, load_balancing.distance(node)))
+      .filter(|(_, distance)| *distance != NodeDistance::Ignored)
+      .collect();
+
+    // pools of nodes which left the cluster or changed distance are replaced
+    let stale: Vec<SocketAddr> = self
+      .distances
+      .iter()
+      .filter(|(addr, distance)| distances.get(addr) != Some(distance))
+      .map(|(addr, _)| *addr)
+      .collect();
+    for addr in stale {
+      if current.contains(&addr) {
+        info!("Distance of node {} changed - removing its pool", addr);
+      } else {
+        info!("Node {} left the cluster - removing it", addr);
+      }
+
+      load_balancing.remove_node(|pool| pool.get_addr() == addr);
+      self.pools.remove(&addr);
+      self.distances.remove(&addr);
+    }
+
+    for (addr, distance) in distances {
+      if self.pools.contains_key(&addr) {
+        continue;
+      }
+
+      match self.add_pool(addr, distance) {
+        Ok(pool) => {
+          if previous.contains(&addr) {
+            info!("Node {} is now {:?} - adding its pool", addr, distance);
+          } else {
+            info!("Node {} joined the cluster - adding it", addr);
+          }
+          self.prepare_cached(pool.clone());
+          load_balancing.add_node(pool);
+        }
+        Err(err) => warn!("Unable to create pool for {}: {}", addr, err),
+      }
+    }
+  }
+
+  /// Prepares cached statements on a newly added node in the background, so executions routed
+  /// to it don't need to prepare them first.
+  fn prepare_cached(&self, pool: Arc<TcpConnectionPool<A>>) {
+    let queries = self.prepared_cache.queries();
+    if queries.is_empty() {
+      return;
+    }
+
+    tokio::spawn(async move {
+      for (_, query) in queries {
+        prepare_on_node(&pool, &query).await;
+      }
+    });
+  }
+
+  /// Returns the current control connection, or opens a new one to a known node or a contact
+  /// point.
+  async fn connect(&mut self) -> error::Result<TransportTcp> {
+    if let Some(ref transport) = self.transport {
+      if !transport.is_broken() {
+        return Ok(transport.clone());
+      }
+    }
+
+    let candidates: Vec<String> = self
+      .cluster
+      .nodes()
+      .iter()
+      .map(|node| node.addr.to_string())
+      .chain(self.contact_points.iter().cloned())
+      .collect();
+
+    let mut errors = Vec::with_capacity(candidates.len());
+    for addr in candidates {
+      match self.open(&addr).await {
+        Ok((transport, event_frames)) => {
+          self.transport = Some(transport.clone());
+          self.event_frames = Some(event_frames);
+          return Ok(transport);
+        }
+        Err(err) => errors.push(format!("{}: {}", addr, err)),
+      }
+    }
+
+    Err(error::Error::General(format!(
+      "Unable to open control connection [{}]",
+      errors.join("; ")
+    )))
+  }
+
+  async fn open(&self, addr: &str) -> error::Result<(TransportTcp, UnboundedReceiver<Frame>)> {
+    let (event_frames_sender, event_frames) = unbounded_channel();
+    let transport = TransportTcp::new(
+      addr,
+      Compression::None,
+      Some(event_frames_sender),
+      &self.node_config.transport_config,
+    )
+    .await?;
+    startup(&transport, &self.node_config.authenticator).await?;
+
+    let register = Frame::new_req_register(vec![
+      SimpleServerEvent::TopologyChange,
+      SimpleServerEvent::StatusChange,
+      SimpleServerEvent::SchemaChange,
+    ]);
+    transport.write_frame(&register).await?;
+
+    Ok((transport, event_frames))
+  }
+}
+
+/// Refresh requests stop when the session is dropped.
+fn requested_refresh(refresh_request: Option<RefreshRequest>) -> Wakeup {
+  match refresh_request {
+    Some(refresh_request) => Wakeup::RefreshRequested(refresh_request),
+    None => Wakeup::Closed,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::authenticators::NoneAuthenticator;
+  use crate::cluster::{AcceptAllFilter, IdentityTranslator, NodeTcpConfigBuilder};
+  use crate::load_balancing::{DcAware, RoundRobin};
+  use crate::types::CInet;
+
+  fn node(addr: &str) -> Arc<Node> {
+    Arc::new(Node {
+      addr: addr.parse().unwrap(),
+      datacenter: None,
+      rack: None,
+      host_id: None,
+      tokens: vec![],
+      schema_version: None,
+      release_version: None,
+    })
+  }
+
+  fn new_control_connection() -> ControlConnection<NoneAuthenticator> {
+    let node_config = NodeTcpConfigBuilder::new("127.0.0.1:9042", NoneAuthenticator).build();
+    ControlConnection::new(
+      &node_config,
+      vec![],
+      Compression::None,
+      Arc::new(IdentityTranslator),
+      Arc::new(AcceptAllFilter),
+      Duration::from_secs(60),
+      Default::default(),
+    )
+  }
+
+  #[tokio::test]
+  async fn reconciles_discovered_nodes() {
+    let mut control_connection = new_control_connection();
+
+    let first = vec![node("127.0.0.1:9042"), node("127.0.0.2:9042")];
+    let second = vec![node("127.0.0.2:9042"), node("127.0.0.3:9042")];
+
+    let pools: Vec<_> = first
+      .iter()
+      .map(|node| {
+        control_connection
+          .add_pool(node.addr, NodeDistance::Local)
+          .unwrap()
+      })
+      .collect();
+    let mut load_balancing = RoundRobin::from(pools);
+
+    control_connection.reconcile(&mut load_balancing, &first, &second);
+
+    let addrs: HashSet<SocketAddr> = (0..4)
+      .map(|_| load_balancing.next().unwrap().get_addr())
+      .collect();
+    assert_eq!(addrs, second.iter().map(|node| node.addr).collect());
+  }
+
+  #[tokio::test]
+  async fn skips_ignored_nodes() {
+    let mut control_connection = new_control_connection();
+
+    let dc_node = |addr: &str, datacenter: &str| {
+      Arc::new(Node {
+        datacenter: Some(datacenter.to_string()),
+        ..(*node(addr)).clone()
+      })
+    };
+    let nodes = vec![
+      dc_node("127.0.0.1:9042", "dc1"),
+      dc_node("127.0.0.2:9042", "dc2"),
+    ];
+    let mut load_balancing = DcAware::new("dc1");
+
+    control_connection.reconcile(&mut load_balancing, &[], &nodes);
+
+    assert_eq!(control_connection.pools.len(), 1);
+    assert_eq!(
+      load_balancing.query_plan()[0].get_addr(),
+      "127.0.0.1:9042".parse().unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn updates_status_of_nodes() {
+    let mut control_connection = new_control_connection();
+    let addr: SocketAddr = "127.0.0.1:9042".parse().unwrap();
+    let pool = control_connection
+      .add_pool(addr, NodeDistance::Local)
+      .unwrap();
+
+    let status_change = |change_type| StatusChange {
+      change_type,
+      addr: CInet { addr },
+    };
+
+    control_connection.update_status(&status_change(StatusChangeType::Down));
+    assert!(pool.is_down());
+
+    control_connection.update_status(&status_change(StatusChangeType::Up));
+    assert!(!pool.is_down());
+  }
+
+  #[tokio::test]
+  async fn answers_refresh_requests() {
+    let control_connection = new_control_connection();
+    let load_balancing: Arc<Mutex<RoundRobin<TcpConnectionPool<NoneAuthenticator>>>> =
+      Arc::new(Mutex::new(RoundRobin::new()));
+    let (refresh_sender, refresh_requests) = unbounded_channel();
+    let task = tokio::spawn(control_connection.run(
+      Arc::downgrade(&load_balancing),
+      Default::default(),
+      refresh_requests,
+    ));
+
+    // there are no contact points to connect to
+    let (result_sender, result) = oneshot::channel();
+    refresh_sender.send(result_sender).unwrap();
+    assert!(result.await.unwrap().is_err());
+
+    drop(refresh_sender);
+    task.await.unwrap();
+  }
+}
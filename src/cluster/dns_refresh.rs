@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::net::lookup_host;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::authenticators::Authenticator;
+use crate::cluster::{new_lazy_tcp_pool, NodeTcpConfig, RequestTracker, TcpConnectionPool};
+use crate::compression::Compression;
+use crate::error;
+use crate::load_balancing::LoadBalancingStrategy;
+
+/// Periodically resolves node hostname and reconciles pools of a session with resolved
+/// addresses.
+pub(crate) struct DnsRefresh<A> {
+  hostname: String,
+  node_config: NodeTcpConfig<'static, A>,
+  interval: Duration,
+  compression: Compression,
+  addrs: HashSet<SocketAddr>,
+}
+
+impl<A: Authenticator + 'static> DnsRefresh<A> {
+  /// Resolves node hostname and creates a pool for each address.
+  pub(crate) async fn new(
+    node_config: &NodeTcpConfig<'_, A>,
+    interval: Duration,
+    compression: Compression,
+  ) -> error::Result<(Self, Vec<Arc<TcpConnectionPool<A>>>)> {
+    let mut refresh = DnsRefresh {
+      hostname: node_config.addr.to_string(),
+      node_config: node_config.with_addr(""),
+      interval,
+      compression,
+      addrs: HashSet::new(),
+    };
+
+    let mut pools = vec![];
+    for addr in lookup_host(node_config.addr).await? {
+      if refresh.addrs.insert(addr) {
+        pools.push(Arc::new(refresh.new_pool(addr)?));
+      }
+    }
+
+    if pools.is_empty() {
+      return Err(error::Error::General(format!(
+        "Unable to resolve {}",
+        node_config.addr
+      )));
+    }
+
+    Ok((refresh, pools))
+  }
+
+  /// Refreshes addresses until the session is dropped or shut down.
+  pub(crate) async fn run<LB>(
+    mut self,
+    load_balancing: Weak<Mutex<LB>>,
+    requests: Arc<RequestTracker>,
+  ) where
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Send,
+  {
+    loop {
+      sleep(self.interval).await;
+
+      let load_balancing = match load_balancing.upgrade() {
+        Some(load_balancing) => load_balancing,
+        None => return,
+      };
+
+      if requests.is_shut_down() {
+        return;
+      }
+
+      let resolved: HashSet<SocketAddr> = match lookup_host(self.hostname.as_str()).await {
+        Ok(addrs) => addrs.collect(),
+        Err(err) => {
+          warn!("Unable to resolve {}: {}", self.hostname, err);
+          continue;
+        }
+      };
+
+      // keep current nodes when the name temporarily resolves to nothing
+      if resolved.is_empty() {
+        continue;
+      }
+
+      self.reconcile(&mut *load_balancing.lock().await, resolved);
+    }
+  }
+
+  fn reconcile<LB>(&mut self, load_balancing: &mut LB, resolved: HashSet<SocketAddr>)
+  where
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>>,
+  {
+    for addr in self.addrs.difference(&resolved) {
+      info!("{} no longer resolves to {} - removing node", self.hostname, addr);
+      load_balancing.remove_node(|pool| pool.get_addr() == *addr);
+    }
+
+    let mut addrs: HashSet<SocketAddr> = self.addrs.intersection(&resolved).cloned().collect();
+    for addr in resolved.difference(&self.addrs) {
+      match self.new_pool(*addr) {
+        Ok(pool) => {
+          info!("{} resolves to new address {} - adding node", self.hostname, addr);
+          load_balancing.add_node(Arc::new(pool));
+          addrs.insert(*addr);
+        }
+        Err(err) => warn!("Unable to create pool for {}: {}", addr, err),
+      }
+    }
+
+    self.addrs = addrs;
+  }
+
+  fn new_pool(&self, addr: SocketAddr) -> error::Result<TcpConnectionPool<A>> {
+    let addr = addr.to_string();
+    new_lazy_tcp_pool(self.node_config.with_addr(&addr), self.compression)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::authenticators::NoneAuthenticator;
+  use crate::cluster::NodeTcpConfigBuilder;
+  use crate::load_balancing::RoundRobin;
+
+  fn node_addrs(load_balancing: &RoundRobin<TcpConnectionPool<NoneAuthenticator>>, count: usize) -> HashSet<SocketAddr> {
+    (0..count)
+      .map(|_| load_balancing.next().unwrap().get_addr())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn reconciles_resolved_addresses() {
+    let node_config = NodeTcpConfigBuilder::new("127.0.0.1:9042", NoneAuthenticator).build();
+    let (mut refresh, pools) = DnsRefresh::new(&node_config, Duration::from_secs(1), Compression::None)
+      .await
+      .unwrap();
+    assert_eq!(pools.len(), 1);
+
+    let mut load_balancing = RoundRobin::from(pools);
+
+    let first: SocketAddr = "127.0.0.1:9042".parse().unwrap();
+    let second: SocketAddr = "127.0.0.2:9042".parse().unwrap();
+    let third: SocketAddr = "127.0.0.3:9042".parse().unwrap();
+
+    refresh.reconcile(&mut load_balancing, vec![first, second].into_iter().collect());
+    assert_eq!(node_addrs(&load_balancing, 4), vec![first, second].into_iter().collect());
+
+    refresh.reconcile(&mut load_balancing, vec![third].into_iter().collect());
+    assert_eq!(node_addrs(&load_balancing, 4), vec![third].into_iter().collect());
+  }
+}
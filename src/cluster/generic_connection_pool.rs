@@ -1,24 +1,78 @@
+use std::fmt;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
 
 use bb8;
+use tokio::task::JoinSet;
+use tokio::time::sleep;
+
+use crate::cluster::{NodeLatency, ShardConnections, ShardConnector};
+use crate::error;
+use crate::query::RequestPriority;
+use crate::retry::ReconnectionPolicy;
+use crate::transport::{CDRSTransport, InFlightSlots};
 
 /// Generic pool connection that is able to return an
 /// `bb8::Pool` as well as an IP address of a node.
-#[derive(Debug)]
 pub struct ConnectionPool<M: bb8::ManageConnection> {
   pool: Arc<bb8::Pool<M>>,
   addr: SocketAddr,
+  reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+  is_down: Arc<AtomicBool>,
+  max_size: u32,
+  warm_up_size: Option<u32>,
+  warm_up_statements: Vec<String>,
+  orphaned_requests: Arc<AtomicUsize>,
+  latency: NodeLatency,
+  shards: Option<Arc<ShardConnections<M::Connection>>>,
+  checkouts: Arc<InFlightSlots>,
 }
 
 impl<M: bb8::ManageConnection> ConnectionPool<M> {
-  pub fn new(pool: bb8::Pool<M>, addr: SocketAddr) -> Self {
+  pub fn new(
+    pool: bb8::Pool<M>,
+    addr: SocketAddr,
+    reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    max_size: u32,
+    warm_up_size: Option<u32>,
+    orphaned_requests: Arc<AtomicUsize>,
+  ) -> Self {
     ConnectionPool {
       pool: Arc::new(pool),
       addr,
+      reconnection_policy,
+      is_down: Arc::new(AtomicBool::new(false)),
+      max_size,
+      warm_up_size,
+      warm_up_statements: vec![],
+      orphaned_requests,
+      latency: Default::default(),
+      shards: None,
+      checkouts: Arc::new(InFlightSlots::new(max_size as usize)),
     }
   }
 
+  /// Enables opening connections to specific shards of Scylla nodes with given connector.
+  pub(crate) fn with_shard_connector(
+    mut self,
+    connector: Arc<dyn ShardConnector<M::Connection>>,
+  ) -> Self {
+    self.shards = Some(Arc::new(ShardConnections::new(connector)));
+    self
+  }
+
+  /// Sets statements prepared on the node once it's warmed up.
+  pub(crate) fn with_warm_up_statements(mut self, statements: Vec<String>) -> Self {
+    self.warm_up_statements = statements;
+    self
+  }
+
+  /// Returns statements prepared on the node once it's warmed up.
+  pub(crate) fn warm_up_statements(&self) -> &[String] {
+    &self.warm_up_statements
+  }
+
   /// Returns reference to underlying `bb8::Pool`.
   pub fn get_pool(&self) -> Arc<bb8::Pool<M>> {
     self.pool.clone()
@@ -28,4 +82,261 @@ impl<M: bb8::ManageConnection> ConnectionPool<M> {
   pub fn get_addr(&self) -> SocketAddr {
     self.addr
   }
+
+  /// Returns current number of open and idle connections.
+  pub fn state(&self) -> bb8::State {
+    self.pool.state()
+  }
+
+  /// Checks if the node is considered down, i.e. new connections could not be established
+  /// and reconnection attempts are scheduled according to the reconnection policy.
+  pub fn is_down(&self) -> bool {
+    self.is_down.load(Ordering::Relaxed)
+  }
+
+  /// Returns the number of requests to this node which were abandoned (timed out or cancelled)
+  /// before their responses arrived. A steadily growing number indicates a slow node.
+  pub fn orphaned_requests(&self) -> usize {
+    self.orphaned_requests.load(Ordering::Relaxed)
+  }
+
+  /// Returns latencies of requests sent to this node.
+  pub fn latency(&self) -> &NodeLatency {
+    &self.latency
+  }
+}
+
+impl<M: bb8::ManageConnection> fmt::Debug for ConnectionPool<M> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("ConnectionPool")
+      .field("pool", &self.pool)
+      .field("addr", &self.addr)
+      .field("is_down", &self.is_down())
+      .finish()
+  }
+}
+
+impl<T, M> ConnectionPool<M>
+where
+  T: CDRSTransport + 'static,
+  M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+{
+  /// Borrows a connection from the pool, waiting at most for the configured connection timeout.
+  /// Broken connections are dropped by the pool and replaced with new ones. Since connections
+  /// are multiplexed, the returned handle can be used concurrently with other borrowers.
+  /// Connections which reached their in-flight request limit are skipped if the pool has other
+  /// ones or can open a new one.
+  ///
+  /// If no connection can be established, the node is marked as down and reconnection happens
+  /// in the background according to the reconnection policy. Until then, this method fails
+  /// immediately.
+  pub async fn connection(&self) -> error::Result<T> {
+    self.connection_with_priority(Default::default()).await
+  }
+
+  /// Borrows a connection like `connection()`. When as many requests as the pool's maximum size
+  /// are waiting for the pool, e.g. while new connections are being opened, requests of higher
+  /// priority borrow connections first.
+  pub async fn connection_with_priority(&self, priority: RequestPriority) -> error::Result<T> {
+    if self.is_down() {
+      return Err(error::Error::General(format!("Node {} is down", self.addr)));
+    }
+
+    let result = self.borrow_connection(priority).await;
+    if result.is_err() {
+      self.mark_down();
+    }
+
+    result
+  }
+
+  /// Borrows a connection to the shard owning given token on Scylla nodes. If there is no
+  /// usable connection to that shard yet, one is opened in the background on the shard-aware
+  /// port and a connection to another shard is returned in the meantime. Other nodes behave
+  /// like `connection()`.
+  pub async fn connection_for_token(&self, token: i64) -> error::Result<T> {
+    self.connection_to(None, Some(token), Default::default()).await
+  }
+
+  /// Borrows a connection to given shard on Scylla nodes, e.g. one storing a tablet. Behaves
+  /// like `connection_for_token()` otherwise.
+  pub async fn connection_for_shard(&self, shard: u32) -> error::Result<T> {
+    self.connection_to(Some(shard), None, Default::default()).await
+  }
+
+  /// Borrows a connection with given priority to given shard or the shard owning given token,
+  /// if known.
+  pub(crate) async fn connection_to(
+    &self,
+    shard: Option<u32>,
+    token: Option<i64>,
+    priority: RequestPriority,
+  ) -> error::Result<T> {
+    let connection = self.connection_with_priority(priority).await?;
+
+    let shard = match (shard, token, connection.shard_info()) {
+      (Some(shard), _, _) => shard,
+      (None, Some(token), Some(shard_info)) => shard_info.shard_of(token),
+      _ => return Ok(connection),
+    };
+
+    Ok(self.shard_connection(connection, shard))
+  }
+
+  fn shard_connection(&self, connection: T, shard: u32) -> T {
+    let shard_info = match connection.shard_info() {
+      Some(shard_info) if shard < shard_info.nr_shards => shard_info,
+      _ => return connection,
+    };
+
+    if shard == shard_info.shard {
+      return connection;
+    }
+
+    let shard_connection = self
+      .shards
+      .as_ref()
+      .and_then(|shards| shards.connection(connection.addr(), &shard_info, shard));
+
+    shard_connection.unwrap_or(connection)
+  }
+
+  async fn borrow_connection(&self, priority: RequestPriority) -> error::Result<T> {
+    // waiting for the pool is limited to as many requests as it has connections, so the others
+    // wait for their turn in order of priority
+    let _checkout = self.checkouts.acquire(priority).await;
+
+    // a connection could have broken while idle in the pool (e.g. after a failed heartbeat) -
+    // returning it to the pool drops it, so try another one
+    let max_attempts = self.pool.state().connections.max(self.max_size) + 1;
+
+    // busy connections are held, so the pool hands out another one or opens a new one
+    let mut busy = vec![];
+
+    for _ in 0..max_attempts {
+      let connection = self.pool.get().await.map_err(|error| match error {
+        bb8::RunError::User(error) => error,
+        bb8::RunError::TimedOut => {
+          error::Error::General(format!("Timed out waiting for connection to {}", self.addr))
+        }
+      })?;
+
+      if connection.is_broken() {
+        continue;
+      }
+
+      if connection.has_capacity() {
+        return Ok(connection.clone());
+      }
+
+      busy.push(connection);
+
+      let state = self.pool.state();
+      if state.idle_connections == 0 && state.connections >= self.max_size {
+        break;
+      }
+    }
+
+    // all connections are busy - wait for a free slot on one of them
+    match busy.first() {
+      Some(connection) => Ok(T::clone(connection)),
+      None => Err(error::Error::General(format!(
+        "Unable to get a working connection to {}",
+        self.addr
+      ))),
+    }
+  }
+
+  /// Establishes connections until the pool holds as many as configured with `warm_up` node
+  /// config option. Does nothing if warm-up is disabled.
+  pub async fn warm_up(&self) -> error::Result<()> {
+    let size = match self.warm_up_size {
+      Some(size) => size,
+      None => return Ok(()),
+    };
+
+    // connections are held until all are established, so the pool has to open new ones
+    let mut connections = JoinSet::new();
+    for _ in 0..size {
+      let pool = self.pool.clone();
+      connections.spawn(async move { pool.get_owned().await });
+    }
+
+    let mut established = Vec::with_capacity(size as usize);
+    while let Some(result) = connections.join_next().await {
+      match result {
+        Ok(Ok(connection)) => established.push(connection),
+        Ok(Err(bb8::RunError::User(error))) => return Err(error),
+        Ok(Err(bb8::RunError::TimedOut)) => {
+          return Err(error::Error::General(format!(
+            "Timed out warming up connections to {}",
+            self.addr
+          )))
+        }
+        Err(error) => return Err(error::Error::General(error.to_string())),
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Marks the node as down, e.g. when the cluster reports it as such, and schedules
+  /// reconnection according to the reconnection policy.
+  pub(crate) fn mark_down(&self) {
+    if self.is_down.swap(true, Ordering::Relaxed) {
+      // reconnection is already in progress
+      return;
+    }
+
+    warn!("Node {} is down - scheduling reconnection", self.addr);
+
+    tokio::spawn(Self::reconnect(
+      Arc::downgrade(&self.pool),
+      self.addr,
+      self.reconnection_policy.clone(),
+      self.is_down.clone(),
+    ));
+  }
+
+  /// Brings the node back without waiting for scheduled reconnection, e.g. when the cluster
+  /// reports it as up. If it's still unreachable, it's marked as down again on the next failed
+  /// connection attempt.
+  pub(crate) fn mark_up(&self) {
+    if self.is_down.swap(false, Ordering::Relaxed) {
+      info!("Node {} is reported up", self.addr);
+    }
+  }
+
+  async fn reconnect(
+    pool: Weak<bb8::Pool<M>>,
+    addr: SocketAddr,
+    reconnection_policy: Arc<dyn ReconnectionPolicy + Send + Sync>,
+    is_down: Arc<AtomicBool>,
+  ) {
+    let mut schedule = reconnection_policy.new_node_schedule();
+    loop {
+      sleep(schedule.next_delay()).await;
+
+      // stop reconnecting when the pool is gone or the node was brought back in the meantime
+      let pool = match pool.upgrade() {
+        Some(pool) => pool,
+        None => return,
+      };
+
+      if !is_down.load(Ordering::Relaxed) {
+        return;
+      }
+
+      let is_up = match pool.get().await {
+        Ok(connection) => !connection.is_broken(),
+        Err(_) => false,
+      };
+
+      if is_up {
+        info!("Node {} is up again", addr);
+        is_down.store(false, Ordering::Relaxed);
+        return;
+      }
+    }
+  }
 }
@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use crate::cluster::Node;
+
+/// Decides which discovered nodes a session connects to. Nodes which are not accepted get no
+/// pools and are never routed to, e.g. nodes of an analytics data center.
+///
+/// Closures taking `&Node` and returning `bool` can be used as filters.
+pub trait HostFilter: Send + Sync {
+    /// Checks if the session should connect to given node.
+    fn accept(&self, node: &Node) -> bool;
+}
+
+impl<F: Fn(&Node) -> bool + Send + Sync> HostFilter for F {
+    fn accept(&self, node: &Node) -> bool {
+        self(node)
+    }
+}
+
+/// Filter accepting all nodes. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcceptAllFilter;
+
+impl HostFilter for AcceptAllFilter {
+    fn accept(&self, _node: &Node) -> bool {
+        true
+    }
+}
+
+/// Filter based on allowlists and denylists of data centers, addresses and subnets.
+///
+/// A node is accepted if it doesn't match any denylist and, for every kind of allowlist which is
+/// not empty, matches that allowlist. Addresses and subnets are matched against IP addresses of
+/// nodes, after address translation.
+#[derive(Clone, Debug, Default)]
+pub struct ListHostFilter {
+    allowed_datacenters: HashSet<String>,
+    denied_datacenters: HashSet<String>,
+    allowed_subnets: Vec<Subnet>,
+    denied_subnets: Vec<Subnet>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Subnet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Subnet {
+    fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(subnet), IpAddr::V4(addr)) => {
+                prefix_matches(&subnet.octets(), &addr.octets(), self.prefix_len)
+            }
+            (IpAddr::V6(subnet), IpAddr::V6(addr)) => {
+                prefix_matches(&subnet.octets(), &addr.octets(), self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(subnet: &[u8], addr: &[u8], prefix_len: u8) -> bool {
+    let prefix_len = (prefix_len as usize).min(subnet.len() * 8);
+    let full_bytes = prefix_len / 8;
+    if subnet[..full_bytes] != addr[..full_bytes] {
+        return false;
+    }
+
+    let remaining_bits = prefix_len % 8;
+    if remaining_bits == 0 {
+        return true;
+    }
+
+    let mask = 0xffu8 << (8 - remaining_bits);
+    subnet[full_bytes] & mask == addr[full_bytes] & mask
+}
+
+impl ListHostFilter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Accepts only nodes of allowed data centers, unless denied otherwise.
+    pub fn allow_datacenter(mut self, datacenter: &str) -> Self {
+        self.allowed_datacenters.insert(datacenter.to_string());
+        self
+    }
+
+    /// Rejects nodes of given data center.
+    pub fn deny_datacenter(mut self, datacenter: &str) -> Self {
+        self.denied_datacenters.insert(datacenter.to_string());
+        self
+    }
+
+    /// Accepts only nodes with allowed addresses, unless denied otherwise.
+    pub fn allow_addr(self, addr: IpAddr) -> Self {
+        self.allow_subnet(addr, 128)
+    }
+
+    /// Rejects node with given address.
+    pub fn deny_addr(self, addr: IpAddr) -> Self {
+        self.deny_subnet(addr, 128)
+    }
+
+    /// Accepts only nodes in allowed subnets, given by address and prefix length, unless
+    /// denied otherwise.
+    pub fn allow_subnet(mut self, addr: IpAddr, prefix_len: u8) -> Self {
+        self.allowed_subnets.push(Subnet { addr, prefix_len });
+        self
+    }
+
+    /// Rejects nodes in given subnet, given by address and prefix length.
+    pub fn deny_subnet(mut self, addr: IpAddr, prefix_len: u8) -> Self {
+        self.denied_subnets.push(Subnet { addr, prefix_len });
+        self
+    }
+}
+
+impl HostFilter for ListHostFilter {
+    fn accept(&self, node: &Node) -> bool {
+        let datacenter = node.datacenter.as_deref();
+        let ip = node.addr.ip();
+
+        let in_datacenters = |datacenters: &HashSet<String>| match datacenter {
+            Some(datacenter) => datacenters.contains(datacenter),
+            None => false,
+        };
+
+        let denied = in_datacenters(&self.denied_datacenters)
+            || self.denied_subnets.iter().any(|subnet| subnet.contains(ip));
+        if denied {
+            return false;
+        }
+
+        let datacenter_allowed =
+            self.allowed_datacenters.is_empty() || in_datacenters(&self.allowed_datacenters);
+        let subnet_allowed = self.allowed_subnets.is_empty()
+            || self
+                .allowed_subnets
+                .iter()
+                .any(|subnet| subnet.contains(ip));
+
+        datacenter_allowed && subnet_allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(addr: &str, datacenter: &str) -> Node {
+        Node {
+            addr: addr.parse().unwrap(),
+            datacenter: Some(datacenter.to_string()),
+            rack: None,
+            host_id: None,
+            tokens: vec![],
+            schema_version: None,
+            release_version: None,
+        }
+    }
+
+    #[test]
+    fn filters_by_datacenter() {
+        let filter = ListHostFilter::new().deny_datacenter("analytics");
+        assert!(filter.accept(&node("10.0.0.1:9042", "dc1")));
+        assert!(!filter.accept(&node("10.0.0.2:9042", "analytics")));
+
+        let filter = ListHostFilter::new().allow_datacenter("dc1");
+        assert!(filter.accept(&node("10.0.0.1:9042", "dc1")));
+        assert!(!filter.accept(&node("10.0.0.2:9042", "dc2")));
+    }
+
+    #[test]
+    fn filters_by_address() {
+        let filter = ListHostFilter::new()
+            .allow_subnet("10.0.0.0".parse().unwrap(), 20)
+            .deny_addr("10.0.0.2".parse().unwrap());
+
+        assert!(filter.accept(&node("10.0.15.1:9042", "dc1")));
+        assert!(!filter.accept(&node("10.0.16.1:9042", "dc1")));
+        assert!(!filter.accept(&node("10.0.0.2:9042", "dc1")));
+        assert!(!filter.accept(&node("[::1]:9042", "dc1")));
+    }
+
+    #[test]
+    fn uses_closures_as_filters() {
+        let filter = |node: &Node| node.addr.port() == 9042;
+        assert!(HostFilter::accept(&filter, &node("10.0.0.1:9042", "dc1")));
+        assert!(!HostFilter::accept(&filter, &node("10.0.0.1:9043", "dc1")));
+    }
+}
@@ -0,0 +1,245 @@
+use rustls::{
+    Certificate, RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError, WebPKIVerifier,
+};
+use std::time::SystemTime;
+
+/// Defines how the server name in node certificates is checked against node DNS name.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum HostnameVerification {
+    /// Node DNS name must match a name in the certificate, possibly through a wildcard
+    /// (e.g. `*.example.com`). This is the default.
+    #[default]
+    Wildcard,
+    /// Node DNS name must match a name in the certificate exactly - certificates with a wildcard
+    /// name covering the node are rejected.
+    Exact,
+    /// Server name is not checked. Certificate chain is still verified. Use only for test
+    /// clusters.
+    Disabled,
+}
+
+static SUPPORTED_SIG_ALGS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+/// First label of a name which no real node should have. If a certificate is valid for it, the
+/// certificate contains a wildcard name.
+const WILDCARD_PROBE_LABEL: &str = "cdrs-wildcard-probe";
+
+/// Certificate verifier implementing `HostnameVerification` modes other than the default.
+pub(crate) struct HostnameVerifier {
+    verification: HostnameVerification,
+    server_name: Option<webpki::DNSName>,
+}
+
+impl HostnameVerifier {
+    pub(crate) fn new(verification: HostnameVerification) -> Self {
+        HostnameVerifier {
+            verification,
+            server_name: None,
+        }
+    }
+
+    /// Creates a verifier which checks certificates against given name instead of the SNI name,
+    /// which is needed when SNI is used for routing by a proxy.
+    pub(crate) fn with_server_name(
+        verification: HostnameVerification,
+        server_name: webpki::DNSName,
+    ) -> Self {
+        HostnameVerifier {
+            verification,
+            server_name: Some(server_name),
+        }
+    }
+
+    fn verify_chain(
+        roots: &RootCertStore,
+        presented_certs: &[Certificate],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let (end_entity, chain) = presented_certs
+            .split_first()
+            .ok_or(TLSError::NoCertificatesPresented)?;
+
+        let cert = webpki::EndEntityCert::from(&end_entity.0).map_err(TLSError::WebPKIError)?;
+        let chain: Vec<&[u8]> = chain.iter().map(|cert| cert.0.as_ref()).collect();
+        let trust_roots: Vec<webpki::TrustAnchor> = roots
+            .roots
+            .iter()
+            .map(|root| root.to_trust_anchor())
+            .collect();
+        let now = webpki::Time::try_from(SystemTime::now())
+            .map_err(|_| TLSError::FailedToGetCurrentTime)?;
+
+        cert.verify_is_valid_tls_server_cert(
+            SUPPORTED_SIG_ALGS,
+            &webpki::TLSServerTrustAnchors(&trust_roots),
+            &chain,
+            now,
+        )
+        .map_err(TLSError::WebPKIError)
+        .map(|_| ServerCertVerified::assertion())
+    }
+
+    fn verify_no_wildcard(
+        presented_certs: &[Certificate],
+        dns_name: webpki::DNSNameRef,
+    ) -> Result<(), TLSError> {
+        let dns_name: &str = dns_name.into();
+        let parent = match dns_name.find('.') {
+            Some(index) => &dns_name[index..],
+            None => return Ok(()),
+        };
+
+        let probe = format!("{}{}", WILDCARD_PROBE_LABEL, parent);
+        let probe = match webpki::DNSNameRef::try_from_ascii_str(&probe) {
+            Ok(probe) => probe,
+            Err(_) => return Ok(()),
+        };
+
+        let cert = presented_certs
+            .first()
+            .ok_or(TLSError::NoCertificatesPresented)?;
+        let cert = webpki::EndEntityCert::from(&cert.0).map_err(TLSError::WebPKIError)?;
+
+        if cert.verify_is_valid_for_dns_name(probe).is_ok() {
+            return Err(TLSError::General(format!(
+                "Certificate contains a wildcard name covering {}",
+                dns_name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl ServerCertVerifier for HostnameVerifier {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[Certificate],
+        dns_name: webpki::DNSNameRef,
+        ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let dns_name = self
+            .server_name
+            .as_ref()
+            .map_or(dns_name, |server_name| server_name.as_ref());
+
+        match self.verification {
+            HostnameVerification::Wildcard => WebPKIVerifier::new().verify_server_cert(
+                roots,
+                presented_certs,
+                dns_name,
+                ocsp_response,
+            ),
+            HostnameVerification::Exact => {
+                let verified = WebPKIVerifier::new().verify_server_cert(
+                    roots,
+                    presented_certs,
+                    dns_name,
+                    ocsp_response,
+                )?;
+
+                Self::verify_no_wildcard(presented_certs, dns_name).map(|_| verified)
+            }
+            HostnameVerification::Disabled => Self::verify_chain(roots, presented_certs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../../fixtures/tls/ca.der");
+    const OTHER_CA: &[u8] = include_bytes!("../../fixtures/tls/other-ca.der");
+    // valid for node1.example.com
+    const EXACT: &[u8] = include_bytes!("../../fixtures/tls/exact.der");
+    // valid for *.example.com
+    const WILDCARD: &[u8] = include_bytes!("../../fixtures/tls/wildcard.der");
+
+    fn roots(ca: &[u8]) -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        roots.add(&Certificate(ca.to_vec())).unwrap();
+        roots
+    }
+
+    fn verify(
+        verifier: &HostnameVerifier,
+        ca: &[u8],
+        cert: &[u8],
+        dns_name: &str,
+    ) -> Result<ServerCertVerified, TLSError> {
+        verifier.verify_server_cert(
+            &roots(ca),
+            &[Certificate(cert.to_vec())],
+            webpki::DNSNameRef::try_from_ascii_str(dns_name).unwrap(),
+            &[],
+        )
+    }
+
+    #[test]
+    fn defaults_to_wildcard() {
+        assert_eq!(
+            HostnameVerification::default(),
+            HostnameVerification::Wildcard
+        );
+    }
+
+    #[test]
+    fn wildcard_accepts_matching_names() {
+        let verifier = HostnameVerifier::new(HostnameVerification::Wildcard);
+
+        assert!(verify(&verifier, CA, EXACT, "node1.example.com").is_ok());
+        assert!(verify(&verifier, CA, WILDCARD, "node1.example.com").is_ok());
+        assert!(verify(&verifier, CA, EXACT, "node2.example.com").is_err());
+        assert!(verify(&verifier, CA, WILDCARD, "node1.example.org").is_err());
+        assert!(verify(&verifier, OTHER_CA, EXACT, "node1.example.com").is_err());
+    }
+
+    #[test]
+    fn exact_rejects_wildcard_names() {
+        let verifier = HostnameVerifier::new(HostnameVerification::Exact);
+
+        assert!(verify(&verifier, CA, EXACT, "node1.example.com").is_ok());
+        assert!(matches!(
+            verify(&verifier, CA, WILDCARD, "node1.example.com"),
+            Err(TLSError::General(_))
+        ));
+        assert!(verify(&verifier, CA, EXACT, "node2.example.com").is_err());
+        assert!(verify(&verifier, OTHER_CA, EXACT, "node1.example.com").is_err());
+    }
+
+    #[test]
+    fn disabled_verifies_chain_only() {
+        let verifier = HostnameVerifier::new(HostnameVerification::Disabled);
+
+        assert!(verify(&verifier, CA, EXACT, "node1.example.com").is_ok());
+        assert!(verify(&verifier, CA, EXACT, "node2.example.com").is_ok());
+        assert!(verify(&verifier, CA, WILDCARD, "node1.example.org").is_ok());
+        assert!(verify(&verifier, OTHER_CA, EXACT, "node1.example.com").is_err());
+    }
+
+    #[test]
+    fn verifies_against_given_server_name() {
+        let server_name = webpki::DNSNameRef::try_from_ascii_str("node1.example.com")
+            .unwrap()
+            .to_owned();
+        let verifier = HostnameVerifier::with_server_name(HostnameVerification::Exact, server_name);
+
+        // the SNI name is ignored in favour of the given one
+        assert!(verify(&verifier, CA, EXACT, "proxy.example.org").is_ok());
+        assert!(verify(&verifier, CA, WILDCARD, "proxy.example.org").is_err());
+    }
+}
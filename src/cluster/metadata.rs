@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use crate::cluster::topology::query_rows;
+use crate::cluster::ReplicationStrategy;
+use crate::error;
+use crate::transport::CDRSTransport;
+use crate::types::list::List;
+use crate::types::map::Map;
+use crate::types::rows::Row;
+use crate::types::{AsRustType, ByName};
+
+const KEYSPACES_QUERY: &str =
+    "SELECT keyspace_name, replication, durable_writes FROM system_schema.keyspaces";
+const TABLES_QUERY: &str = "SELECT keyspace_name, table_name FROM system_schema.tables";
+const COLUMNS_QUERY: &str = "SELECT keyspace_name, table_name, column_name, kind, position, \
+                             type, clustering_order FROM system_schema.columns";
+const TYPES_QUERY: &str =
+    "SELECT keyspace_name, type_name, field_names, field_types FROM system_schema.types";
+
+/// Schema of the cluster, read from `system_schema` tables.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClusterMetadata {
+    pub keyspaces: HashMap<String, KeyspaceMetadata>,
+}
+
+impl ClusterMetadata {
+    /// Returns metadata of given keyspace, if it exists.
+    pub fn keyspace(&self, name: &str) -> Option<&KeyspaceMetadata> {
+        self.keyspaces.get(name)
+    }
+
+    /// Returns metadata of given table, if it exists.
+    pub fn table(&self, keyspace: &str, table: &str) -> Option<&TableMetadata> {
+        self.keyspace(keyspace)?.tables.get(table)
+    }
+
+    /// Returns replication strategies of all keyspaces.
+    pub fn replication_strategies(&self) -> HashMap<String, ReplicationStrategy> {
+        self.keyspaces
+            .iter()
+            .map(|(name, keyspace)| (name.clone(), keyspace.replication.clone()))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyspaceMetadata {
+    pub name: String,
+    pub replication: ReplicationStrategy,
+    pub durable_writes: bool,
+    pub tables: HashMap<String, TableMetadata>,
+    pub user_types: HashMap<String, UserTypeMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableMetadata {
+    pub keyspace: String,
+    pub name: String,
+    /// Partition key columns, in key order.
+    pub partition_key: Vec<String>,
+    /// Clustering columns, in key order.
+    pub clustering_key: Vec<String>,
+    pub columns: HashMap<String, ColumnMetadata>,
+}
+
+impl TableMetadata {
+    /// Returns metadata of given column, if it exists.
+    pub fn column(&self, name: &str) -> Option<&ColumnMetadata> {
+        self.columns.get(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnMetadata {
+    pub name: String,
+    /// CQL type of the column, e.g. `map<text, frozen<address>>`.
+    pub cql_type: String,
+    pub kind: ColumnKind,
+    /// Position within the partition or clustering key, -1 for other columns.
+    pub position: i32,
+    /// Clustering order, `asc` or `desc` for clustering columns and `none` for others.
+    pub clustering_order: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnKind {
+    PartitionKey,
+    Clustering,
+    Regular,
+    Static,
+}
+
+impl ColumnKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "partition_key" => Some(ColumnKind::PartitionKey),
+            "clustering" => Some(ColumnKind::Clustering),
+            "regular" => Some(ColumnKind::Regular),
+            "static" => Some(ColumnKind::Static),
+            _ => None,
+        }
+    }
+}
+
+/// User defined type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserTypeMetadata {
+    pub keyspace: String,
+    pub name: String,
+    /// Field names along with their CQL types, in definition order.
+    pub fields: Vec<(String, String)>,
+}
+
+/// Queries `system_schema` tables and returns the schema of the cluster.
+pub(crate) async fn fetch_metadata<T: CDRSTransport>(
+    transport: &T,
+) -> error::Result<ClusterMetadata> {
+    let mut keyspaces = HashMap::new();
+    for row in query_rows(transport, KEYSPACES_QUERY).await? {
+        let keyspace = keyspace_from_row(&row)?;
+        keyspaces.insert(keyspace.name.clone(), keyspace);
+    }
+
+    for row in query_rows(transport, TABLES_QUERY).await? {
+        let keyspace: String = row.r_by_name("keyspace_name")?;
+        let name: String = row.r_by_name("table_name")?;
+        if let Some(keyspace_metadata) = keyspaces.get_mut(&keyspace) {
+            keyspace_metadata.tables.insert(
+                name.clone(),
+                TableMetadata {
+                    keyspace,
+                    name,
+                    partition_key: vec![],
+                    clustering_key: vec![],
+                    columns: HashMap::new(),
+                },
+            );
+        }
+    }
+
+    let mut columns = vec![];
+    for row in query_rows(transport, COLUMNS_QUERY).await? {
+        let keyspace: String = row.r_by_name("keyspace_name")?;
+        let table: String = row.r_by_name("table_name")?;
+        columns.push((keyspace, table, column_from_row(&row)?));
+    }
+    add_columns(&mut keyspaces, columns);
+
+    for row in query_rows(transport, TYPES_QUERY).await? {
+        let user_type = user_type_from_row(&row)?;
+        if let Some(keyspace) = keyspaces.get_mut(&user_type.keyspace) {
+            keyspace
+                .user_types
+                .insert(user_type.name.clone(), user_type);
+        }
+    }
+
+    Ok(ClusterMetadata { keyspaces })
+}
+
+fn keyspace_from_row(row: &Row) -> error::Result<KeyspaceMetadata> {
+    let replication: Option<Map> = row.by_name("replication")?;
+    let replication: HashMap<String, String> = match replication {
+        Some(replication) => replication.as_r_type()?,
+        None => HashMap::new(),
+    };
+
+    Ok(KeyspaceMetadata {
+        name: row.r_by_name("keyspace_name")?,
+        replication: ReplicationStrategy::from_replication(&replication),
+        durable_writes: row.by_name("durable_writes")?.unwrap_or(true),
+        tables: HashMap::new(),
+        user_types: HashMap::new(),
+    })
+}
+
+fn column_from_row(row: &Row) -> error::Result<ColumnMetadata> {
+    let kind: String = row.r_by_name("kind")?;
+
+    Ok(ColumnMetadata {
+        name: row.r_by_name("column_name")?,
+        cql_type: row.r_by_name("type")?,
+        kind: ColumnKind::from_name(&kind)
+            .ok_or_else(|| error::Error::General(format!("Unknown column kind {}", kind)))?,
+        position: row.r_by_name("position")?,
+        clustering_order: row
+            .by_name("clustering_order")?
+            .unwrap_or_else(|| "none".to_string()),
+    })
+}
+
+fn user_type_from_row(row: &Row) -> error::Result<UserTypeMetadata> {
+    let field_names: Option<List> = row.by_name("field_names")?;
+    let field_names: Vec<String> = match field_names {
+        Some(field_names) => field_names.as_r_type()?,
+        None => vec![],
+    };
+    let field_types: Option<List> = row.by_name("field_types")?;
+    let field_types: Vec<String> = match field_types {
+        Some(field_types) => field_types.as_r_type()?,
+        None => vec![],
+    };
+
+    Ok(UserTypeMetadata {
+        keyspace: row.r_by_name("keyspace_name")?,
+        name: row.r_by_name("type_name")?,
+        fields: field_names.into_iter().zip(field_types).collect(),
+    })
+}
+
+/// Adds columns to their tables and fills in partition and clustering keys.
+fn add_columns(
+    keyspaces: &mut HashMap<String, KeyspaceMetadata>,
+    columns: Vec<(String, String, ColumnMetadata)>,
+) {
+    let mut key_columns: HashMap<(String, String), Vec<ColumnMetadata>> = HashMap::new();
+
+    for (keyspace, table, column) in columns {
+        let table_metadata = match keyspaces
+            .get_mut(&keyspace)
+            .and_then(|keyspace| keyspace.tables.get_mut(&table))
+        {
+            Some(table_metadata) => table_metadata,
+            None => continue,
+        };
+
+        if column.kind == ColumnKind::PartitionKey || column.kind == ColumnKind::Clustering {
+            key_columns
+                .entry((keyspace, table))
+                .or_default()
+                .push(column.clone());
+        }
+
+        table_metadata.columns.insert(column.name.clone(), column);
+    }
+
+    for ((keyspace, table), mut columns) in key_columns {
+        columns.sort_by_key(|column| column.position);
+
+        let table_metadata = keyspaces
+            .get_mut(&keyspace)
+            .and_then(|keyspace| keyspace.tables.get_mut(&table))
+            .expect("key columns belong to known tables");
+
+        for column in columns {
+            match column.kind {
+                ColumnKind::PartitionKey => table_metadata.partition_key.push(column.name),
+                _ => table_metadata.clustering_key.push(column.name),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, kind: ColumnKind, position: i32) -> ColumnMetadata {
+        ColumnMetadata {
+            name: name.to_string(),
+            cql_type: "int".to_string(),
+            kind,
+            position,
+            clustering_order: "none".to_string(),
+        }
+    }
+
+    #[test]
+    fn orders_key_columns() {
+        let table = TableMetadata {
+            keyspace: "ks".to_string(),
+            name: "t".to_string(),
+            partition_key: vec![],
+            clustering_key: vec![],
+            columns: HashMap::new(),
+        };
+        let mut keyspaces = HashMap::new();
+        keyspaces.insert(
+            "ks".to_string(),
+            KeyspaceMetadata {
+                name: "ks".to_string(),
+                replication: ReplicationStrategy::Other,
+                durable_writes: true,
+                tables: vec![("t".to_string(), table)].into_iter().collect(),
+                user_types: HashMap::new(),
+            },
+        );
+
+        let columns = vec![
+            column("value", ColumnKind::Regular, -1),
+            column("c2", ColumnKind::Clustering, 1),
+            column("pk2", ColumnKind::PartitionKey, 1),
+            column("c1", ColumnKind::Clustering, 0),
+            column("pk1", ColumnKind::PartitionKey, 0),
+        ];
+        let columns = columns
+            .into_iter()
+            .map(|column| ("ks".to_string(), "t".to_string(), column))
+            .chain(vec![(
+                "ks".to_string(),
+                "unknown".to_string(),
+                column("pk", ColumnKind::PartitionKey, 0),
+            )])
+            .collect();
+        add_columns(&mut keyspaces, columns);
+
+        let metadata = ClusterMetadata { keyspaces };
+        let table = metadata.table("ks", "t").unwrap();
+        assert_eq!(table.partition_key, vec!["pk1", "pk2"]);
+        assert_eq!(table.clustering_key, vec!["c1", "c2"]);
+        assert_eq!(table.columns.len(), 5);
+        assert_eq!(table.column("value").unwrap().kind, ColumnKind::Regular);
+        assert!(metadata.table("ks", "unknown").is_none());
+    }
+}
@@ -1,47 +1,237 @@
 use bb8;
 use async_trait::async_trait;
-use tokio::sync::Mutex;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
+mod address_translator;
+#[cfg(feature = "rust-tls")]
+mod cloud_config;
+mod cluster_event;
+mod cluster_state;
 #[cfg(feature = "rust-tls")]
 mod config_rustls;
 mod config_tcp;
+mod control_connection;
+#[cfg(unix)]
+mod config_unix;
+mod dns_refresh;
 mod generic_connection_pool;
+mod host_filter;
+mod metadata;
+#[cfg(feature = "rust-tls")]
+mod hostname_verification;
+mod node_latency;
 mod pager;
+mod partitioner;
+mod query_trace;
+mod request_tracker;
 pub mod session;
+mod sharding;
+mod tablets;
 #[cfg(feature = "rust-tls")]
 mod rustls_connection_pool;
 mod tcp_connection_pool;
+mod token_map;
+mod topology;
+#[cfg(unix)]
+mod unix_connection_pool;
 
+pub use crate::cluster::address_translator::{
+    fetch_peer_addrs, AddressTranslator, IdentityTranslator, StaticAddressTranslator,
+};
+#[cfg(feature = "rust-tls")]
+pub use crate::cluster::cloud_config::CloudConfig;
+pub use crate::cluster::cluster_event::{ClusterEvent, ClusterEventStream};
+pub use crate::cluster::cluster_state::{ClusterState, NodeState, NodeStatus};
 #[cfg(feature = "rust-tls")]
 pub use crate::cluster::config_rustls::{ClusterRustlsConfig, NodeRustlsConfig, NodeRustlsConfigBuilder};
 pub use crate::cluster::config_tcp::{ClusterTcpConfig, NodeTcpConfig, NodeTcpConfigBuilder};
+#[cfg(unix)]
+pub use crate::cluster::config_unix::{ClusterUnixConfig, NodeUnixConfig, NodeUnixConfigBuilder};
+pub use crate::cluster::host_filter::{AcceptAllFilter, HostFilter, ListHostFilter};
+pub use crate::cluster::metadata::{
+    ClusterMetadata, ColumnKind, ColumnMetadata, KeyspaceMetadata, TableMetadata,
+    UserTypeMetadata,
+};
+pub(crate) use crate::cluster::metadata::fetch_metadata;
+pub use crate::cluster::node_latency::{LatencyStats, NodeLatency};
 pub use crate::cluster::pager::{PagerState, QueryPager, SessionPager};
+pub use crate::cluster::partitioner::{
+    partitioner_by_name, routing_key, ByteOrderedPartitioner, Murmur3Partitioner, Partitioner,
+    RandomPartitioner, Token,
+};
+pub(crate) use crate::cluster::partitioner::fetch_partitioner;
+pub use crate::cluster::query_trace::{QueryTrace, TraceEvent};
+pub(crate) use crate::cluster::query_trace::fetch_query_trace;
+pub use crate::cluster::request_tracker::RequestGuard;
+pub(crate) use crate::cluster::request_tracker::RequestTracker;
+#[cfg(feature = "rust-tls")]
+pub use crate::cluster::hostname_verification::HostnameVerification;
 #[cfg(feature = "rust-tls")]
 pub use crate::cluster::rustls_connection_pool::{
     new_rustls_pool, RustlsConnectionPool, RustlsConnectionsManager,
 };
+#[cfg(feature = "rust-tls")]
+pub(crate) use crate::cluster::rustls_connection_pool::{new_lazy_rustls_pool, RustlsConfigSlot};
+pub use crate::cluster::sharding::ShardInfo;
+pub(crate) use crate::cluster::sharding::{
+    fetch_supported, supported_extensions, ShardConnections, ShardConnector,
+};
+pub use crate::cluster::tablets::{Tablet, TabletMap, TabletReplica};
+pub(crate) use crate::cluster::tablets::TABLETS_ROUTING_PAYLOAD;
 pub use crate::cluster::tcp_connection_pool::{
     new_tcp_pool, startup, TcpConnectionPool, TcpConnectionsManager,
 };
+pub(crate) use crate::cluster::tcp_connection_pool::new_lazy_tcp_pool;
+pub use crate::cluster::token_map::{ReplicationStrategy, TokenMap, TokenRange};
+#[cfg(unix)]
+pub use crate::cluster::unix_connection_pool::{
+    new_unix_pool, UnixConnectionPool, UnixConnectionsManager,
+};
+#[cfg(unix)]
+pub(crate) use crate::cluster::unix_connection_pool::new_lazy_unix_pool;
+pub use crate::cluster::topology::{Cluster, Node};
+pub(crate) use crate::cluster::topology::fetch_nodes;
+pub(crate) use control_connection::{ControlConnection, RefreshRequest};
+pub(crate) use dns_refresh::DnsRefresh;
 pub(crate) use generic_connection_pool::ConnectionPool;
 
 use crate::compression::Compression;
+use crate::consistency::Consistency;
 use crate::error;
-use crate::query::{BatchExecutor, ExecExecutor, PrepareExecutor, QueryExecutor};
+use crate::query::{
+    BatchExecutor, BatchWarningThresholds, ExecExecutor, LogWarningHandler, PrepareExecutor, PreparedCache,
+    QueryExecutor, ServerSideTimestampGenerator, TargetNode, TimestampGenerator, WarningHandler,
+};
+use crate::retry::{
+    DefaultRetryPolicy, NoSpeculativeExecutionPolicy, RetryPolicy, SpeculativeExecutionPolicy,
+};
 use crate::transport::CDRSTransport;
-use crate::frame::{Frame, StreamId};
 
 /// `GetConnection` trait provides a unified interface for Session to get a connection
 /// from a load balancer
 #[async_trait]
 pub trait GetConnection<
     T: CDRSTransport + Send + Sync + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error>,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error>,
 >
 {
     /// Returns connection from a load balancer.
     async fn get_connection(&self) -> Option<Arc<ConnectionPool<M>>>;
+
+    /// Returns partitioner used for computing tokens of partition keys.
+    fn partitioner(&self) -> Arc<dyn Partitioner> {
+        Arc::new(Murmur3Partitioner)
+    }
+
+    /// Returns connection to a node storing the partition with given token in given keyspace.
+    /// Falls back to `get_connection()` when replicas are unknown or unavailable.
+    async fn get_connection_for_token(
+        &self,
+        _keyspace: &str,
+        _token: &Token,
+    ) -> Option<Arc<ConnectionPool<M>>> {
+        self.get_connection().await
+    }
+
+    /// Returns connection to a shard of a node storing given tablet of given table, along
+    /// with the shard. Returns `None` when the tablet is unknown or its replicas are
+    /// unavailable, in which case requests are routed by token ring.
+    async fn get_connection_for_tablet(
+        &self,
+        _keyspace: &str,
+        _table: &str,
+        _token: i64,
+    ) -> Option<(Arc<ConnectionPool<M>>, u32)> {
+        None
+    }
+
+    /// Returns connection to the next node of the query plan which is up and wasn't tried yet,
+    /// for retrying a request. Returns `None` if there's no such node.
+    async fn get_next_connection(&self, _tried: &[SocketAddr]) -> Option<Arc<ConnectionPool<M>>> {
+        None
+    }
+
+    /// Returns connection to given node, if it's known and up, for requests pinned to it.
+    async fn get_connection_to(&self, _target_node: &TargetNode) -> Option<Arc<ConnectionPool<M>>> {
+        None
+    }
+
+    /// Returns connections to all nodes of the query plan which are up, e.g. for preparing
+    /// queries on each of them.
+    async fn get_all_connections(&self) -> Vec<Arc<ConnectionPool<M>>> {
+        self.get_connection().await.into_iter().collect()
+    }
+
+    /// Returns the consistency of queries, executions and batches which don't set one
+    /// explicitly.
+    fn default_consistency(&self) -> Consistency {
+        Consistency::default()
+    }
+
+    /// Returns the serial consistency of conditional updates which don't set one explicitly,
+    /// or `None` to leave it to the server, which uses `Serial`.
+    fn default_serial_consistency(&self) -> Option<Consistency> {
+        None
+    }
+
+    /// Returns the policy deciding whether failed requests are retried.
+    fn retry_policy(&self) -> Arc<dyn RetryPolicy + Send + Sync> {
+        Arc::new(DefaultRetryPolicy)
+    }
+
+    /// Returns the time limit of requests, including retries and speculative executions, used
+    /// for requests without their own deadline.
+    fn request_deadline(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns the generator of default timestamps of requests without explicit ones.
+    fn timestamp_generator(&self) -> Arc<dyn TimestampGenerator + Send + Sync> {
+        Arc::new(ServerSideTimestampGenerator)
+    }
+
+    /// Returns the policy deciding when idempotent requests are executed speculatively.
+    fn speculative_execution_policy(&self) -> Arc<dyn SpeculativeExecutionPolicy + Send + Sync> {
+        Arc::new(NoSpeculativeExecutionPolicy)
+    }
+
+    /// Returns the keyspace selected with a `USE` statement, in which unqualified tables of
+    /// queries are resolved.
+    fn keyspace(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the cache of statements prepared by `ExecExecutor::execute_cached()`, if there's
+    /// one.
+    fn prepared_cache(&self) -> Option<Arc<PreparedCache>> {
+        None
+    }
+
+    /// Returns the handler of warnings attached to responses.
+    fn warning_handler(&self) -> Arc<dyn WarningHandler + Send + Sync> {
+        Arc::new(LogWarningHandler)
+    }
+
+    /// Returns the thresholds of batches, over which the warning handler is called before
+    /// sending them.
+    fn batch_warning_thresholds(&self) -> BatchWarningThresholds {
+        BatchWarningThresholds::default()
+    }
+
+    /// Stores tablet routing information sent by a node.
+    fn add_tablet(&self, _keyspace: &str, _table: &str, _tablet: Tablet) {}
+
+    /// Registers a request which is about to be sent, so it can be waited for when shutting
+    /// down. Returns `None` if no new requests are accepted.
+    fn start_request(&self) -> Option<RequestGuard> {
+        Some(RequestGuard::default())
+    }
+
+    /// Called after a statement altering the schema succeeded, before its response is
+    /// returned.
+    async fn after_schema_change(&self) {}
 }
 
 /// `GetCompressor` trait provides a unified interface for Session to get a compressor
@@ -51,18 +241,12 @@ pub trait GetCompressor<'a> {
     fn get_compressor(&self) -> Compression;
 }
 
-/// `ResponseCache` caches responses to match them by their stream id to requests.
-#[async_trait]
-pub trait ResponseCache {
-    async fn match_or_cache_response(&self, stream_id: StreamId, frame: Frame) -> Option<Frame>;
-}
-
 /// `CDRSSession` trait wrap ups whole query functionality. Use it only if whole query
 /// machinery is needed and direct sub traits otherwise.
 pub trait CDRSSession<
     'a,
     T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error>,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error>,
 >:
     GetCompressor<'static>
     + GetConnection<T, M>
@@ -0,0 +1,65 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Weight of a new sample in the moving average.
+const SMOOTHING_FACTOR: f64 = 0.1;
+
+/// Request latency statistics of a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LatencyStats {
+    /// Exponentially weighted moving average of request latencies.
+    pub average: Duration,
+    /// Number of requests measured.
+    pub samples: u64,
+    /// When the last request was measured.
+    pub updated: Instant,
+}
+
+/// Keeps track of request latencies of a node.
+#[derive(Debug, Default)]
+pub struct NodeLatency {
+    stats: Mutex<Option<LatencyStats>>,
+}
+
+impl NodeLatency {
+    /// Records latency of a request.
+    pub fn record(&self, latency: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        *stats = Some(match *stats {
+            Some(stats) => LatencyStats {
+                average: stats.average.mul_f64(1.0 - SMOOTHING_FACTOR)
+                    + latency.mul_f64(SMOOTHING_FACTOR),
+                samples: stats.samples + 1,
+                updated: Instant::now(),
+            },
+            None => LatencyStats {
+                average: latency,
+                samples: 1,
+                updated: Instant::now(),
+            },
+        });
+    }
+
+    /// Returns current statistics, if any request was measured.
+    pub fn stats(&self) -> Option<LatencyStats> {
+        *self.stats.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_latencies() {
+        let latency = NodeLatency::default();
+        assert_eq!(latency.stats(), None);
+
+        latency.record(Duration::from_millis(10));
+        latency.record(Duration::from_millis(20));
+
+        let stats = latency.stats().unwrap();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.average.as_micros(), 11_000);
+    }
+}
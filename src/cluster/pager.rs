@@ -1,5 +1,4 @@
 use bb8;
-use tokio::sync::Mutex;
 use std::marker::PhantomData;
 
 use crate::cluster::CDRSSession;
@@ -13,7 +12,7 @@ use crate::types::CBytes;
 
 pub struct SessionPager<
     'a,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
     S: CDRSSession<'static, T, M> + 'a,
     T: CDRSTransport + Unpin + 'static,
 > {
@@ -26,7 +25,7 @@ pub struct SessionPager<
 impl<
         'a,
         'b: 'a,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
         S: CDRSSession<'static, T, M>,
         T: CDRSTransport + Unpin + 'static,
     > SessionPager<'a, M, S, T>
@@ -65,7 +64,11 @@ impl<
             pager_state: state,
             query,
             qv: qp.values,
-            consistency: qp.consistency,
+            consistency: if qp.explicit_consistency {
+                Some(qp.consistency)
+            } else {
+                None
+            },
         }
     }
 
@@ -73,12 +76,7 @@ impl<
     where
         Q: ToString,
     {
-        self.query_with_param(
-            query,
-            QueryParamsBuilder::new()
-                .consistency(Consistency::One)
-                .finalize(),
-        )
+        self.query_with_param(query, QueryParamsBuilder::new().finalize())
     }
 
     pub fn query_with_param<Q>(
@@ -117,22 +115,23 @@ pub struct QueryPager<'a, Q: ToString, P: 'a> {
     pager_state: PagerState,
     query: Q,
     qv: Option<QueryValues>,
-    consistency: Consistency,
+    consistency: Option<Consistency>,
 }
 
 impl<
         'a,
         Q: ToString,
         T: CDRSTransport + Unpin + 'static,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
         S: CDRSSession<'static, T, M> + Sync + Send,
     > QueryPager<'a, Q, SessionPager<'a, M, S, T>>
 {
     pub async fn next(&mut self) -> error::Result<Vec<Row>> {
-        let mut params = QueryParamsBuilder::new()
-            .consistency(self.consistency)
-            .page_size(self.pager.page_size);
+        let mut params = QueryParamsBuilder::new().page_size(self.pager.page_size);
 
+        if let Some(consistency) = self.consistency {
+            params = params.consistency(consistency);
+        }
         if let Some(qv) = &self.qv {
             params = params.values(qv.clone());
         }
@@ -180,7 +179,7 @@ pub struct ExecPager<'a, P: 'a> {
 impl<
         'a,
         T: CDRSTransport + Unpin + 'static,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
         S: CDRSSession<'static, T, M> + Sync + Send,
     > ExecPager<'a, SessionPager<'a, M, S, T>>
 {
@@ -0,0 +1,316 @@
+use md5::{Digest, Md5};
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use crate::cluster::topology::query_rows;
+use crate::error;
+use crate::transport::CDRSTransport;
+use crate::types::ByName;
+
+const PARTITIONER_QUERY: &str = "SELECT partitioner FROM system.local WHERE key = 'local'";
+
+/// Position of a partition on the token ring. Tokens of a cluster all come from the same
+/// partitioner, so only tokens of the same kind are compared.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Token {
+    /// Token of `Murmur3Partitioner`.
+    Murmur3(i64),
+    /// Token of `RandomPartitioner`, between 0 and 2^127.
+    Random(u128),
+    /// Token of `ByteOrderedPartitioner`, i.e. the routing key itself.
+    ByteOrdered(Vec<u8>),
+}
+
+/// Maps partition keys to tokens, the same way the cluster does.
+pub trait Partitioner: Send + Sync {
+    /// Computes the token of a routing key, i.e. a serialized partition key. Composite keys
+    /// are serialized as length-prefixed components, each followed by a zero byte.
+    fn hash(&self, routing_key: &[u8]) -> Token;
+
+    /// Parses a token as returned by nodes, e.g. in `system.local`.
+    fn parse_token(&self, token: &str) -> Option<Token>;
+}
+
+/// Cassandra's default partitioner, also used by Scylla.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Murmur3Partitioner;
+
+impl Partitioner for Murmur3Partitioner {
+    fn hash(&self, routing_key: &[u8]) -> Token {
+        Token::Murmur3(murmur3_token(routing_key))
+    }
+
+    fn parse_token(&self, token: &str) -> Option<Token> {
+        token.parse().ok().map(Token::Murmur3)
+    }
+}
+
+/// Legacy partitioner using MD5 hashes of keys as tokens.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomPartitioner;
+
+impl Partitioner for RandomPartitioner {
+    fn hash(&self, routing_key: &[u8]) -> Token {
+        // the digest is read as a signed big-endian integer, whose absolute value is the token
+        Token::Random(i128::from_be_bytes(Md5::digest(routing_key).into()).unsigned_abs())
+    }
+
+    fn parse_token(&self, token: &str) -> Option<Token> {
+        token.parse().ok().map(Token::Random)
+    }
+}
+
+/// Partitioner ordering partitions by raw key bytes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ByteOrderedPartitioner;
+
+impl Partitioner for ByteOrderedPartitioner {
+    fn hash(&self, routing_key: &[u8]) -> Token {
+        Token::ByteOrdered(routing_key.to_vec())
+    }
+
+    fn parse_token(&self, token: &str) -> Option<Token> {
+        // tokens are hex-encoded keys
+        token
+            .as_bytes()
+            .chunks(2)
+            .map(|digits| match digits {
+                [high, low] => Some((hex_digit(*high)? << 4) | hex_digit(*low)?),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .map(Token::ByteOrdered)
+    }
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// Serializes partition key values the way Cassandra does for computing tokens. Composite
+/// keys are serialized as length-prefixed components, each followed by a zero byte.
+pub fn routing_key(components: &[&[u8]]) -> Vec<u8> {
+    if let [component] = components {
+        return component.to_vec();
+    }
+
+    let mut routing_key = vec![];
+    for component in components {
+        routing_key.extend_from_slice(&(component.len() as u16).to_be_bytes());
+        routing_key.extend_from_slice(component);
+        routing_key.push(0);
+    }
+
+    routing_key
+}
+
+/// Returns the partitioner with given class name, as reported by nodes. Returns `None` for
+/// partitioners which are not supported.
+pub fn partitioner_by_name(name: &str) -> Option<Arc<dyn Partitioner>> {
+    let name = name.rsplit('.').next().unwrap_or(name);
+    match name {
+        "Murmur3Partitioner" => Some(Arc::new(Murmur3Partitioner)),
+        "RandomPartitioner" => Some(Arc::new(RandomPartitioner)),
+        "ByteOrderedPartitioner" => Some(Arc::new(ByteOrderedPartitioner)),
+        _ => None,
+    }
+}
+
+/// Queries the partitioner used by the cluster. Returns `None` if it's not supported.
+pub(crate) async fn fetch_partitioner<T: CDRSTransport>(
+    transport: &T,
+) -> error::Result<Option<Arc<dyn Partitioner>>> {
+    let rows = query_rows(transport, PARTITIONER_QUERY).await?;
+    let name: String = match rows.first() {
+        Some(row) => row.r_by_name("partitioner")?,
+        None => return Ok(None),
+    };
+
+    let partitioner = partitioner_by_name(&name);
+    if partitioner.is_none() {
+        warn!(
+            "Partitioner {} is not supported - requests won't be routed to replicas",
+            name
+        );
+    }
+
+    Ok(partitioner)
+}
+
+const C1: u64 = 0x87c3_7b91_1142_53d5;
+const C2: u64 = 0x4cf5_ad43_2745_937f;
+
+/// Computes the token the way Cassandra's `Murmur3Partitioner` does, i.e. the first half of x64
+/// 128-bit MurmurHash3 with tail bytes treated as signed.
+fn murmur3_token(routing_key: &[u8]) -> i64 {
+    let len = routing_key.len();
+    let mut h1: u64 = 0;
+    let mut h2: u64 = 0;
+
+    let mut blocks = routing_key.chunks_exact(16);
+    for block in &mut blocks {
+        let k1 = u64::from_le_bytes(block[..8].try_into().unwrap());
+        let k2 = u64::from_le_bytes(block[8..].try_into().unwrap());
+
+        h1 ^= mix_k1(k1);
+        h1 = h1
+            .rotate_left(27)
+            .wrapping_add(h2)
+            .wrapping_mul(5)
+            .wrapping_add(0x52dc_e729);
+
+        h2 ^= mix_k2(k2);
+        h2 = h2
+            .rotate_left(31)
+            .wrapping_add(h1)
+            .wrapping_mul(5)
+            .wrapping_add(0x3849_5ab5);
+    }
+
+    // Java bytes are signed, so tail bytes get sign-extended before being combined
+    let tail = blocks.remainder();
+    let byte = |index: usize| tail[index] as i8 as i64 as u64;
+
+    if tail.len() > 8 {
+        let k2 = (8..tail.len()).fold(0, |k2, index| k2 ^ byte(index) << ((index - 8) * 8));
+        h2 ^= mix_k2(k2);
+    }
+
+    if !tail.is_empty() {
+        let k1 = (0..tail.len().min(8)).fold(0, |k1, index| k1 ^ byte(index) << (index * 8));
+        h1 ^= mix_k1(k1);
+    }
+
+    h1 ^= len as u64;
+    h2 ^= len as u64;
+
+    h1 = h1.wrapping_add(h2);
+    h2 = h2.wrapping_add(h1);
+
+    h1 = fmix(h1);
+    h2 = fmix(h2);
+
+    // the minimum token is reserved for the ring start
+    match h1.wrapping_add(h2) as i64 {
+        i64::MIN => i64::MAX,
+        token => token,
+    }
+}
+
+fn mix_k1(k1: u64) -> u64 {
+    k1.wrapping_mul(C1).rotate_left(31).wrapping_mul(C2)
+}
+
+fn mix_k2(k2: u64) -> u64 {
+    k2.wrapping_mul(C2).rotate_left(33).wrapping_mul(C1)
+}
+
+fn fmix(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    k ^ k >> 33
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_murmur3_tokens() {
+        let partitioner = Murmur3Partitioner;
+
+        assert_eq!(
+            partitioner.hash(&1i32.to_be_bytes()),
+            Token::Murmur3(-4069959284402364209)
+        );
+        assert_eq!(
+            partitioner.hash(&2i32.to_be_bytes()),
+            Token::Murmur3(-3248873570005575792)
+        );
+        assert_eq!(
+            partitioner.hash(&3i32.to_be_bytes()),
+            Token::Murmur3(9010454139840013625)
+        );
+        assert_eq!(partitioner.hash(b""), Token::Murmur3(0));
+        assert_eq!(
+            partitioner.hash(b"test"),
+            Token::Murmur3(-6017608668500074083)
+        );
+        assert_eq!(
+            partitioner.hash(b"hello"),
+            Token::Murmur3(0xcbd8_a7b3_41bd_9b02_u64 as i64)
+        );
+        assert_eq!(
+            partitioner.hash(b"The quick brown fox jumps over the lazy dog."),
+            Token::Murmur3(0xcd99_481f_9ee9_02c9_u64 as i64)
+        );
+    }
+
+    #[test]
+    fn serializes_routing_keys() {
+        assert_eq!(routing_key(&[&[1, 2]]), vec![1, 2]);
+        assert_eq!(
+            routing_key(&[&[1, 2], &[3]]),
+            vec![0, 2, 1, 2, 0, 0, 1, 3, 0]
+        );
+    }
+
+    #[test]
+    fn parses_murmur3_tokens() {
+        let partitioner = Murmur3Partitioner;
+
+        assert_eq!(
+            partitioner.parse_token("-9223372036854775808"),
+            Some(Token::Murmur3(i64::MIN))
+        );
+        assert_eq!(partitioner.parse_token("42"), Some(Token::Murmur3(42)));
+        assert_eq!(partitioner.parse_token("not a token"), None);
+    }
+
+    #[test]
+    fn computes_random_tokens() {
+        let partitioner = RandomPartitioner;
+
+        // digests with the highest bit set are negated
+        assert_eq!(
+            partitioner.hash(b""),
+            Token::Random(0x2be2_7326_70ff_4dfb_167f_f667_1307_bd82)
+        );
+        assert_eq!(
+            partitioner.hash(b"abc"),
+            Token::Random(0x6ffe_af67_c32d_b04f_2969_c082_d71e_808e)
+        );
+        assert_eq!(
+            partitioner.parse_token("170141183460469231731687303715884105728"),
+            Some(Token::Random(1 << 127))
+        );
+    }
+
+    #[test]
+    fn computes_byte_ordered_tokens() {
+        let partitioner = ByteOrderedPartitioner;
+
+        assert_eq!(
+            partitioner.hash(b"key"),
+            Token::ByteOrdered(b"key".to_vec())
+        );
+        assert_eq!(
+            partitioner.parse_token("6b6579"),
+            Some(Token::ByteOrdered(b"key".to_vec()))
+        );
+        assert_eq!(partitioner.parse_token("6b657"), None);
+        assert_eq!(partitioner.parse_token("zz"), None);
+    }
+
+    #[test]
+    fn finds_partitioners_by_name() {
+        let partitioner =
+            partitioner_by_name("org.apache.cassandra.dht.RandomPartitioner").unwrap();
+        assert_eq!(partitioner.parse_token("1"), Some(Token::Random(1)));
+        assert!(
+            partitioner_by_name("org.apache.cassandra.dht.OrderPreservingPartitioner").is_none()
+        );
+    }
+}
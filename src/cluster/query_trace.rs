@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use time::PrimitiveDateTime;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use crate::cluster::topology::query_rows;
+use crate::error;
+use crate::transport::CDRSTransport;
+use crate::types::map::Map;
+use crate::types::rows::Row;
+use crate::types::{AsRustType, ByName};
+
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_INTERVAL: Duration = Duration::from_millis(3);
+
+/// Trace of a request sent with tracing enabled, read from `system_traces` tables by
+/// `Session::get_query_trace()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryTrace {
+    pub tracing_id: Uuid,
+    /// Node which coordinated the request.
+    pub coordinator: Option<IpAddr>,
+    pub client: Option<IpAddr>,
+    /// Kind of the request, e.g. `Execute CQL3 query`.
+    pub request: Option<String>,
+    /// Parameters of the request, e.g. its query and consistency.
+    pub parameters: HashMap<String, String>,
+    pub started_at: Option<PrimitiveDateTime>,
+    /// Time it took the coordinator to process the request.
+    pub duration: Duration,
+    /// Events recorded by all nodes involved in processing the request, in order.
+    pub events: Vec<TraceEvent>,
+}
+
+/// Single step of processing a traced request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEvent {
+    pub event_id: Uuid,
+    pub activity: Option<String>,
+    /// Node which recorded the event.
+    pub source: Option<IpAddr>,
+    /// Time elapsed since the source node started processing the request.
+    pub source_elapsed: Duration,
+    pub thread: Option<String>,
+}
+
+impl QueryTrace {
+    /// Reads a trace from its row of `system_traces.sessions` and rows of
+    /// `system_traces.events`. Returns `None` if the trace is still being written, i.e. the
+    /// duration of the request is not known yet.
+    fn from_rows(tracing_id: Uuid, session: &Row, events: &[Row]) -> error::Result<Option<Self>> {
+        let duration: Option<i32> = session.by_name("duration")?;
+        let duration = match duration {
+            Some(duration) => Duration::from_micros(duration as u64),
+            None => return Ok(None),
+        };
+
+        let parameters: Option<Map> = session.by_name("parameters")?;
+        let parameters = match parameters {
+            Some(parameters) => parameters.as_r_type()?,
+            None => HashMap::new(),
+        };
+
+        Ok(Some(QueryTrace {
+            tracing_id,
+            coordinator: session.by_name("coordinator")?,
+            client: session.by_name("client")?,
+            request: session.by_name("request")?,
+            parameters,
+            started_at: session.by_name("started_at")?,
+            duration,
+            events: events
+                .iter()
+                .map(TraceEvent::from_row)
+                .collect::<error::Result<_>>()?,
+        }))
+    }
+}
+
+impl TraceEvent {
+    fn from_row(row: &Row) -> error::Result<Self> {
+        let source_elapsed: Option<i32> = row.by_name("source_elapsed")?;
+
+        Ok(TraceEvent {
+            event_id: row.r_by_name("event_id")?,
+            activity: row.by_name("activity")?,
+            source: row.by_name("source")?,
+            source_elapsed: Duration::from_micros(source_elapsed.unwrap_or_default() as u64),
+            thread: row.by_name("thread")?,
+        })
+    }
+}
+
+/// Reads the trace with given id over given connection. Nodes write traces asynchronously, so
+/// reading is retried with growing intervals while the trace is incomplete.
+pub(crate) async fn fetch_query_trace<T: CDRSTransport>(
+    transport: &T,
+    tracing_id: Uuid,
+) -> error::Result<QueryTrace> {
+    let session_query = format!(
+        "SELECT client, coordinator, duration, parameters, request, started_at \
+         FROM system_traces.sessions WHERE session_id = {}",
+        tracing_id
+    );
+    let events_query = format!(
+        "SELECT event_id, activity, source, source_elapsed, thread \
+         FROM system_traces.events WHERE session_id = {}",
+        tracing_id
+    );
+
+    let mut interval = RETRY_INTERVAL;
+    for attempt in 1..=MAX_ATTEMPTS {
+        if attempt > 1 {
+            sleep(interval).await;
+            interval *= 2;
+        }
+
+        let session = match query_rows(transport, &session_query)
+            .await?
+            .into_iter()
+            .next()
+        {
+            Some(session) => session,
+            None => continue,
+        };
+        let events = query_rows(transport, &events_query).await?;
+
+        if let Some(trace) = QueryTrace::from_rows(tracing_id, &session, &events)? {
+            return Ok(trace);
+        }
+    }
+
+    Err(error::Error::General(format!(
+        "Trace {} is not complete after {} attempts",
+        tracing_id, MAX_ATTEMPTS
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{row, value};
+
+    fn session(duration: Option<i32>) -> Row {
+        let mut parameters = 1i32.to_be_bytes().to_vec();
+        parameters.extend(value(b"consistency_level"));
+        parameters.extend(value(b"ONE"));
+
+        row(vec![
+            ("client", vec![0, 0x10], Some(vec![127, 0, 0, 2])),
+            ("coordinator", vec![0, 0x10], Some(vec![127, 0, 0, 1])),
+            (
+                "duration",
+                vec![0, 0x09],
+                duration.map(|d| d.to_be_bytes().to_vec()),
+            ),
+            (
+                "parameters",
+                vec![0, 0x21, 0, 0x0D, 0, 0x0D],
+                Some(parameters),
+            ),
+            (
+                "request",
+                vec![0, 0x0D],
+                Some(b"Execute CQL3 query".to_vec()),
+            ),
+            ("started_at", vec![0, 0x0B], None),
+        ])
+    }
+
+    #[test]
+    fn reads_complete_traces() {
+        let tracing_id = Uuid::from_u128(1);
+        assert!(QueryTrace::from_rows(tracing_id, &session(None), &[])
+            .unwrap()
+            .is_none());
+
+        let event = row(vec![
+            (
+                "event_id",
+                vec![0, 0x0F],
+                Some(Uuid::from_u128(2).as_bytes().to_vec()),
+            ),
+            ("activity", vec![0, 0x0D], Some(b"Parsing query".to_vec())),
+            ("source", vec![0, 0x10], Some(vec![127, 0, 0, 1])),
+            (
+                "source_elapsed",
+                vec![0, 0x09],
+                Some(250i32.to_be_bytes().to_vec()),
+            ),
+            ("thread", vec![0, 0x0D], None),
+        ]);
+        let trace = QueryTrace::from_rows(tracing_id, &session(Some(1500)), &[event])
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(trace.coordinator, Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(trace.client, Some("127.0.0.2".parse().unwrap()));
+        assert_eq!(trace.duration, Duration::from_micros(1500));
+        assert_eq!(trace.parameters["consistency_level"], "ONE");
+        assert_eq!(trace.request.as_deref(), Some("Execute CQL3 query"));
+        assert!(trace.started_at.is_none());
+        assert_eq!(trace.events.len(), 1);
+        assert_eq!(trace.events[0].event_id, Uuid::from_u128(2));
+        assert_eq!(trace.events[0].activity.as_deref(), Some("Parsing query"));
+        assert_eq!(trace.events[0].source_elapsed, Duration::from_micros(250));
+        assert!(trace.events[0].thread.is_none());
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Keeps track of requests in flight, so a session can wait for them when shutting down.
+#[derive(Debug, Default)]
+pub(crate) struct RequestTracker {
+    in_flight: AtomicUsize,
+    is_shut_down: AtomicBool,
+    idle: Notify,
+}
+
+impl RequestTracker {
+    /// Registers a new request. Returns `None` when shut down.
+    pub(crate) fn start(self: &Arc<Self>) -> Option<RequestGuard> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+
+        // checked after registering, so shutdown can't miss a request which got through
+        if self.is_shut_down() {
+            self.finish();
+            return None;
+        }
+
+        Some(RequestGuard {
+            tracker: Some(self.clone()),
+        })
+    }
+
+    /// Stops accepting new requests.
+    pub(crate) fn shut_down(&self) {
+        self.is_shut_down.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_shut_down(&self) -> bool {
+        self.is_shut_down.load(Ordering::SeqCst)
+    }
+
+    /// Waits until there are no requests in flight.
+    pub(crate) async fn wait_idle(&self) {
+        loop {
+            let idle = self.idle.notified();
+            tokio::pin!(idle);
+            idle.as_mut().enable();
+
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+
+            idle.await;
+        }
+    }
+
+    fn finish(&self) {
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.idle.notify_waiters();
+        }
+    }
+}
+
+/// Marks a request in flight until dropped.
+#[derive(Debug, Default)]
+pub struct RequestGuard {
+    tracker: Option<Arc<RequestTracker>>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        if let Some(ref tracker) = self.tracker {
+            tracker.finish();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn waits_for_requests_in_flight() {
+        let tracker = Arc::new(RequestTracker::default());
+        let request = tracker.start().unwrap();
+
+        tracker.shut_down();
+        assert!(tracker.start().is_none());
+        assert!(timeout(Duration::from_millis(50), tracker.wait_idle())
+            .await
+            .is_err());
+
+        drop(request);
+        assert!(timeout(Duration::from_millis(50), tracker.wait_idle())
+            .await
+            .is_ok());
+    }
+}
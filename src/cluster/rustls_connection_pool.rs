@@ -1,18 +1,18 @@
 use async_trait::async_trait;
 use bb8::{Builder, ManageConnection, PooledConnection};
-use tokio::io::AsyncWriteExt;
-use tokio::sync::Mutex;
 
+use std::fmt;
 use std::net;
-use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, RwLock};
 
-use crate::cluster::{startup, NodeRustlsConfig};
+use crate::cluster::tcp_connection_pool::startup_with_extensions;
+use crate::cluster::{fetch_supported, supported_extensions, NodeRustlsConfig};
 use crate::authenticators::Authenticator;
 use crate::cluster::ConnectionPool;
 use crate::compression::Compression;
-use crate::frame::parser::parse_frame;
-use crate::frame::{Frame, IntoBytes};
-use crate::transport::TransportRustls;
+use crate::frame::Frame;
+use crate::transport::{CDRSTransport, TransportConfig, TransportRustls};
 use crate::error;
 
 pub type RustlsConnectionPool<A> = ConnectionPool<RustlsConnectionsManager<A>>;
@@ -20,67 +20,148 @@ pub type RustlsConnectionPool<A> = ConnectionPool<RustlsConnectionsManager<A>>;
 /// `bb8::Pool` of SSL-based CDRS connections.
 ///
 /// Used internally for SSL Session for holding connections to a specific Cassandra node.
-pub async fn new_rustls_pool<A: Authenticator + Send + Sync + 'static>(node_config: NodeRustlsConfig<A>) -> error::Result<RustlsConnectionPool<A>> {
+pub async fn new_rustls_pool<A: Authenticator + Send + Sync + 'static>(
+    node_config: NodeRustlsConfig<A>,
+    compression: Compression,
+) -> error::Result<RustlsConnectionPool<A>> {
+    let (pool, _) = new_lazy_rustls_pool(node_config, compression);
+    pool.connection().await?;
+    Ok(pool)
+}
+
+/// Creates a pool which establishes connections in the background, without waiting for the
+/// node to be reachable. Returned slot allows replacing rustls config used for new connections.
+pub(crate) fn new_lazy_rustls_pool<A: Authenticator + Send + Sync + 'static>(
+    node_config: NodeRustlsConfig<A>,
+    compression: Compression,
+) -> (RustlsConnectionPool<A>, RustlsConfigSlot) {
     let manager = RustlsConnectionsManager::new(
         node_config.addr,
         node_config.dns_name,
         node_config.config,
         node_config.authenticator,
+        compression,
+        node_config.transport_config,
     );
+    let config_slot = manager.config.clone();
 
+    let (warm_up_size, warm_up_statements) = if node_config.warm_up {
+        (
+            Some(node_config.min_idle.unwrap_or(node_config.max_size)),
+            node_config.warm_up_statements,
+        )
+    } else {
+        (None, vec![])
+    };
+
+    let orphaned_requests = manager.orphaned_requests.clone();
     let pool = Builder::new()
         .max_size(node_config.max_size)
         .min_idle(node_config.min_idle)
         .max_lifetime(node_config.max_lifetime)
         .idle_timeout(node_config.idle_timeout)
         .connection_timeout(node_config.connection_timeout)
-        .build(manager)
-        .await
-        .map_err(|err| error::Error::from(err.to_string()))?;
+        .test_on_check_out(node_config.test_on_check_out)
+        .build_unchecked(manager);
+
+    let pool = RustlsConnectionPool::new(
+        pool,
+        node_config.addr,
+        node_config.reconnection_policy,
+        node_config.max_size,
+        warm_up_size,
+        orphaned_requests,
+    )
+    .with_warm_up_statements(warm_up_statements);
+    (pool, config_slot)
+}
+
+/// Rustls client config shared between a connection manager and its session, which can be
+/// replaced while connections are in use.
+#[derive(Clone)]
+pub(crate) struct RustlsConfigSlot(Arc<RwLock<Arc<rustls::ClientConfig>>>);
+
+impl RustlsConfigSlot {
+    fn new(config: Arc<rustls::ClientConfig>) -> Self {
+        RustlsConfigSlot(Arc::new(RwLock::new(config)))
+    }
+
+    fn get(&self) -> Arc<rustls::ClientConfig> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub(crate) fn set(&self, config: Arc<rustls::ClientConfig>) {
+        *self.0.write().unwrap() = config;
+    }
+}
 
-    Ok(RustlsConnectionPool::new(pool, node_config.addr))
+impl fmt::Debug for RustlsConfigSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RustlsConfigSlot")
+    }
 }
 
 /// `bb8` connection manager.
 pub struct RustlsConnectionsManager<A> {
     addr: net::SocketAddr,
     dns_name: webpki::DNSName,
-    config: Arc<rustls::ClientConfig>,
+    config: RustlsConfigSlot,
     auth: A,
+    compression: Compression,
+    transport_config: TransportConfig,
+    orphaned_requests: Arc<AtomicUsize>,
 }
 
 impl<A> RustlsConnectionsManager<A> {
     #[inline]
-    pub fn new(addr: net::SocketAddr, dns_name: webpki::DNSName, config: Arc<rustls::ClientConfig>, auth: A) -> Self {
+    pub fn new(
+        addr: net::SocketAddr,
+        dns_name: webpki::DNSName,
+        config: Arc<rustls::ClientConfig>,
+        auth: A,
+        compression: Compression,
+        transport_config: TransportConfig,
+    ) -> Self {
         Self {
             addr,
             dns_name,
-            config,
+            config: RustlsConfigSlot::new(config),
             auth,
+            compression,
+            transport_config,
+            orphaned_requests: Default::default(),
         }
     }
 }
 
 #[async_trait]
 impl<A: Authenticator + 'static + Send + Sync> ManageConnection for RustlsConnectionsManager<A> {
-    type Connection = Mutex<TransportRustls>;
+    type Connection = TransportRustls;
     type Error = error::Error;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        let transport = Mutex::new(TransportRustls::new(self.addr, self.dns_name.clone(), self.config.clone()).await?);
-        startup(&transport, &self.auth).await?;
+        let transport = TransportRustls::connect(
+            self.addr,
+            self.dns_name.clone(),
+            self.config.get(),
+            self.compression,
+            None,
+            &self.transport_config,
+            self.orphaned_requests.clone(),
+        )
+        .await?;
+        let supported = fetch_supported(&transport).await?;
+        startup_with_extensions(&transport, &self.auth, &supported_extensions(&supported)).await?;
 
         Ok(transport)
     }
 
     async fn is_valid(&self, conn: &mut PooledConnection<'_, Self>) -> Result<(), Self::Error> {
-        let options_frame = Frame::new_req_options().into_cbytes();
-        conn.lock().await.write(options_frame.as_slice()).await?;
-
-        parse_frame(&conn, &Compression::None {}).await.map(|_| ())
+        let options_frame = Frame::new_req_options();
+        conn.write_frame(&options_frame).await.map(|_| ())
     }
 
-    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
     }
 }
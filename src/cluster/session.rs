@@ -1,36 +1,72 @@
 use async_trait::async_trait;
 use bb8;
-use fnv::FnvHashMap;
+use rand::{thread_rng, Rng};
+use std::collections::HashSet;
 use std::iter::Iterator;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::{io::AsyncWriteExt, sync::Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, timeout, Instant};
+use uuid::Uuid;
 
 #[cfg(feature = "unstable-dynamic-cluster")]
 use crate::cluster::NodeTcpConfig;
-use crate::cluster::{new_tcp_pool, startup, CDRSSession, ClusterTcpConfig, ConnectionPool, GetCompressor, GetConnection, TcpConnectionPool, ResponseCache};
+#[cfg(feature = "rust-tls")]
+use crate::cluster::{new_lazy_rustls_pool, CloudConfig, ClusterRustlsConfig, RustlsConfigSlot, RustlsConnectionPool};
+#[cfg(feature = "rust-tls")]
+use std::path::Path;
+#[cfg(unix)]
+use crate::cluster::{new_lazy_unix_pool, ClusterUnixConfig, UnixConnectionPool};
+use crate::cluster::{fetch_nodes, fetch_query_trace, new_lazy_tcp_pool, startup, AcceptAllFilter, AddressTranslator, Cluster, ClusterEventStream, ClusterState, NodeState, NodeStatus, ControlConnection, Murmur3Partitioner, RefreshRequest, Partitioner, QueryTrace, Token, DnsRefresh, HostFilter, IdentityTranslator, RequestGuard, RequestTracker, Tablet, CDRSSession, ClusterTcpConfig, ConnectionPool, GetCompressor, GetConnection, TcpConnectionPool};
+use crate::consistency::Consistency;
 use crate::error;
-use crate::load_balancing::LoadBalancingStrategy;
-use crate::transport::{CDRSTransport, TransportTcp};
+use crate::load_balancing::{LoadBalancingStrategy, NodeDistance};
+use crate::retry::{
+    DefaultRetryPolicy, NoSpeculativeExecutionPolicy, RetryPolicy, SpeculativeExecutionPolicy,
+};
+use crate::transport::{CDRSTransport, SessionKeyspace, TransportTcp};
 
 use crate::authenticators::Authenticator;
 use crate::cluster::SessionPager;
 use crate::compression::Compression;
 use crate::events::{new_listener, EventStream, EventStreamNonBlocking, Listener};
 use crate::frame::events::{ServerEvent, SimpleServerEvent, StatusChange, StatusChangeType};
-use crate::frame::parser::parse_frame;
-use crate::frame::{Frame, IntoBytes, StreamId};
-use crate::query::{BatchExecutor, ExecExecutor, PrepareExecutor, QueryExecutor};
+use crate::frame::Frame;
+use crate::query::{
+    prepare_node_query, BatchExecutor, BatchWarningThresholds, ExecExecutor, LogWarningHandler,
+    MonotonicTimestampGenerator, PrepareExecutor, PreparedCache, PreparedQuery, QueryExecutor,
+    TargetNode, TimestampGenerator, WarningHandler,
+};
 
 /// CDRS session that holds one pool of authorized connecitons per node.
 /// `compression` field contains data compressor that will be used
 /// for decompressing data received from Cassandra server.
 #[derive(Debug)]
 pub struct Session<LB> {
-    load_balancing: Mutex<LB>,
+    load_balancing: Arc<Mutex<LB>>,
     event_stream: Option<Mutex<EventStreamNonBlocking>>,
-    responses: Mutex<FnvHashMap<StreamId, Frame>>,
     #[allow(dead_code)]
     pub compression: Compression,
+    #[cfg(feature = "rust-tls")]
+    tls_configs: Vec<RustlsConfigSlot>,
+    requests: Arc<RequestTracker>,
+    close_nodes: fn(&mut LB),
+    cluster: Option<Arc<Cluster>>,
+    schema_agreement_timeout: Option<Duration>,
+    refresh_requests: Option<UnboundedSender<RefreshRequest>>,
+    retry_policy: Arc<dyn RetryPolicy + Send + Sync>,
+    speculative_execution_policy: Arc<dyn SpeculativeExecutionPolicy + Send + Sync>,
+    request_deadline: Option<Duration>,
+    timestamp_generator: Arc<dyn TimestampGenerator + Send + Sync>,
+    prepared_cache: Arc<PreparedCache>,
+    default_consistency: Consistency,
+    default_serial_consistency: Option<Consistency>,
+    keyspace: SessionKeyspace,
+    warning_handler: Arc<dyn WarningHandler + Send + Sync>,
+    batch_warning_thresholds: BatchWarningThresholds,
 }
 
 impl<'a, LB> GetCompressor<'a> for Session<LB> {
@@ -45,7 +81,7 @@ impl<'a, LB: Sized> Session<LB> {
     /// for performing paged queries.
     pub fn paged<
         T: CDRSTransport + Unpin + 'static,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error>,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error>,
     >(
         &'a mut self,
         page_size: i32,
@@ -55,16 +91,342 @@ impl<'a, LB: Sized> Session<LB> {
     {
         return SessionPager::new(self, page_size);
     }
+
+    /// Shuts the session down gracefully. New requests fail right away, while requests in
+    /// flight are given `deadline` time to complete. Afterwards node pools are dropped, which
+    /// closes their connections cleanly once the last requests using them finish. Returns
+    /// `Error::Timeout` if some requests were still in flight at the deadline.
+    pub async fn shutdown(&self, deadline: Duration) -> error::Result<()> {
+        self.requests.shut_down();
+
+        let result = timeout(deadline, self.requests.wait_idle())
+            .await
+            .map_err(|_| {
+                error::Error::Timeout("Requests still in flight at shutdown deadline".into())
+            });
+
+        (self.close_nodes)(&mut *self.load_balancing.lock().await);
+
+        result
+    }
+
+    /// Makes statements altering the schema wait until all nodes agree on the schema version,
+    /// for at most given time. Disabled by default.
+    pub fn set_schema_agreement_timeout(&mut self, timeout: Option<Duration>) {
+        self.schema_agreement_timeout = timeout;
+    }
+
+    /// Sets the policy deciding whether failed requests are retried. Default is
+    /// `DefaultRetryPolicy`.
+    pub fn set_retry_policy(&mut self, retry_policy: Arc<dyn RetryPolicy + Send + Sync>) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Sets the policy deciding when idempotent requests are executed speculatively. Default
+    /// is `NoSpeculativeExecutionPolicy`.
+    pub fn set_speculative_execution_policy(
+        &mut self,
+        speculative_execution_policy: Arc<dyn SpeculativeExecutionPolicy + Send + Sync>,
+    ) {
+        self.speculative_execution_policy = speculative_execution_policy;
+    }
+
+    /// Sets the time limit of requests, including retries and speculative executions, for
+    /// requests without their own deadline. Disabled by default.
+    pub fn set_request_deadline(&mut self, deadline: Option<Duration>) {
+        self.request_deadline = deadline;
+    }
+
+    /// Sets the generator of default timestamps of requests without explicit ones. Default is
+    /// `MonotonicTimestampGenerator`.
+    pub fn set_timestamp_generator(
+        &mut self,
+        timestamp_generator: Arc<dyn TimestampGenerator + Send + Sync>,
+    ) {
+        self.timestamp_generator = timestamp_generator;
+    }
+
+    /// Sets the handler of warnings attached to responses, e.g. about reading many tombstones.
+    /// Default is `LogWarningHandler`.
+    pub fn set_warning_handler(&mut self, warning_handler: Arc<dyn WarningHandler + Send + Sync>) {
+        self.warning_handler = warning_handler;
+    }
+
+    /// Sets the thresholds of batches, over which `WarningHandler::handle_batch_warning()` is
+    /// called before sending them. Disabled by default.
+    pub fn set_batch_warning_thresholds(&mut self, thresholds: BatchWarningThresholds) {
+        self.batch_warning_thresholds = thresholds;
+    }
+
+    /// Sets how many prepared statements are cached. Default is
+    /// `PreparedCache::DEFAULT_CAPACITY`.
+    pub fn set_prepared_cache_capacity(&mut self, capacity: usize) {
+        self.prepared_cache.set_capacity(capacity);
+    }
+
+    /// Sets the consistency of queries, executions and batches which don't set one explicitly.
+    /// Default is `Consistency::One`.
+    pub fn set_default_consistency(&mut self, consistency: Consistency) {
+        self.default_consistency = consistency;
+    }
+
+    /// Sets the serial consistency of conditional updates which don't set one explicitly,
+    /// which has to be `Serial` or `LocalSerial`. By default it's left to the server, which
+    /// uses `Serial`.
+    pub fn set_default_serial_consistency(
+        &mut self,
+        serial_consistency: Option<Consistency>,
+    ) -> error::Result<()> {
+        if let Some(serial_consistency) = serial_consistency {
+            serial_consistency.check_serial()?;
+        }
+
+        self.default_serial_consistency = serial_consistency;
+        Ok(())
+    }
+
+    /// Returns the keyspace selected with a `USE` statement, which all connections of the
+    /// session switch to, including ones established later.
+    pub fn keyspace(&self) -> Option<String> {
+        self.keyspace.get()
+    }
+
+    /// Refreshes cluster nodes and schema metadata right away, without waiting for the
+    /// periodic or event triggered refresh. Works only for sessions created with
+    /// `ClusterSessionBuilder`.
+    pub async fn refresh_metadata(&self) -> error::Result<()> {
+        let refresh_requests = self.refresh_requests.as_ref().ok_or_else(|| {
+            error::Error::General("Session doesn't discover cluster nodes".into())
+        })?;
+
+        let (result_sender, result) = oneshot::channel();
+        refresh_requests
+            .send(result_sender)
+            .map_err(|_| error::Error::General("Cluster nodes are no longer refreshed".into()))?;
+
+        result
+            .await
+            .map_err(|_| error::Error::General("Cluster nodes are no longer refreshed".into()))?
+    }
+
+    /// Returns nodes discovered in the cluster, if the session was created with
+    /// `ClusterSessionBuilder`.
+    pub fn cluster(&self) -> Option<Arc<Cluster>> {
+        self.cluster.clone()
+    }
+
+    /// Returns a stream of node and schema changes, if the session was created with
+    /// `ClusterSessionBuilder`. Only events received after the call are returned.
+    pub fn event_stream(&self) -> Option<ClusterEventStream> {
+        self.cluster
+            .as_ref()
+            .map(|cluster| ClusterEventStream::new(cluster.subscribe()))
+    }
+
+    /// Replaces rustls client config of all nodes, e.g. in order to use renewed client
+    /// certificates or CA bundles. New connections use the new config, while established
+    /// connections are kept. Options previously set with `NodeRustlsConfigBuilder` methods, like
+    /// client certificate or hostname verification, need to be present in the new config. Has no
+    /// effect on non-TLS sessions.
+    #[cfg(feature = "rust-tls")]
+    pub fn reload_tls(&self, config: Arc<rustls::ClientConfig>) {
+        for tls_config in &self.tls_configs {
+            tls_config.set(config.clone());
+        }
+    }
+}
+
+impl<LB> Session<LB> {
+    const SCHEMA_AGREEMENT_INTERVAL: Duration = Duration::from_millis(200);
+
+    /// Checks if all nodes agree on the schema version, as reported in `system.local` and
+    /// `system.peers`. Nodes marked as down are not taken into account.
+    pub async fn check_schema_agreement<T, M>(&self) -> error::Result<bool>
+    where
+        T: CDRSTransport + 'static,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+        LB: LoadBalancingStrategy<ConnectionPool<M>>,
+    {
+        let plan = self.load_balancing.lock().await.query_plan();
+        let node = plan
+            .iter()
+            .find(|node| !node.is_down())
+            .ok_or_else(|| error::Error::from("No node is up"))?;
+        let transport = node.connection().await?;
+
+        let down: HashSet<SocketAddr> = plan
+            .iter()
+            .filter(|node| node.is_down())
+            .map(|node| node.get_addr())
+            .collect();
+        let versions: HashSet<_> = fetch_nodes(&transport, &IdentityTranslator)
+            .await?
+            .into_iter()
+            .filter(|node| !down.contains(&node.addr))
+            .filter_map(|node| node.schema_version)
+            .collect();
+
+        Ok(versions.len() <= 1)
+    }
+
+    /// Polls schema versions of nodes until they agree. Returns `Error::Timeout` if they
+    /// still don't agree after given time.
+    pub async fn wait_for_schema_agreement<T, M>(&self, deadline: Duration) -> error::Result<()>
+    where
+        T: CDRSTransport + 'static,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+        LB: LoadBalancingStrategy<ConnectionPool<M>>,
+    {
+        let deadline = Instant::now() + deadline;
+        loop {
+            if self.check_schema_agreement().await? {
+                return Ok(());
+            }
+
+            if Instant::now() + Self::SCHEMA_AGREEMENT_INTERVAL > deadline {
+                return Err(error::Error::Timeout(
+                    "Nodes did not agree on the schema version in time".into(),
+                ));
+            }
+
+            sleep(Self::SCHEMA_AGREEMENT_INTERVAL).await;
+        }
+    }
+
+    /// Reads the trace of a request sent with tracing enabled, by the id returned in
+    /// `QueryResult::tracing_id`. Fails if the trace is still incomplete after a few attempts.
+    pub async fn get_query_trace<T, M>(&self, tracing_id: Uuid) -> error::Result<QueryTrace>
+    where
+        T: CDRSTransport + 'static,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+        LB: LoadBalancingStrategy<ConnectionPool<M>>,
+    {
+        let plan = self.load_balancing.lock().await.query_plan();
+        let node = plan
+            .iter()
+            .find(|node| !node.is_down())
+            .ok_or_else(|| error::Error::from("No node is up"))?;
+        let transport = node.connection().await?;
+
+        fetch_query_trace(&transport, tracing_id).await
+    }
+
+    /// Returns a snapshot of nodes known to the session, along with their pools and token
+    /// ownership, if the session was created with `ClusterSessionBuilder`.
+    pub async fn cluster_state<T, M>(&self) -> Option<ClusterState>
+    where
+        T: CDRSTransport + 'static,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+        LB: LoadBalancingStrategy<ConnectionPool<M>>,
+    {
+        let cluster = self.cluster.as_ref()?;
+        let token_map = cluster.token_map();
+        let token_counts = token_map.token_counts();
+        let ownership = token_map.ownership();
+
+        let load_balancing = self.load_balancing.lock().await;
+        let nodes = cluster
+            .nodes()
+            .iter()
+            .map(|node| {
+                let pool = load_balancing.find(|pool| pool.get_addr() == node.addr);
+                let state = pool.as_ref().map(|pool| pool.state());
+                let status = match pool {
+                    Some(ref pool) if pool.is_down() => NodeStatus::Down,
+                    Some(_) => NodeStatus::Up,
+                    None => NodeStatus::NotConnected,
+                };
+
+                NodeState {
+                    addr: node.addr,
+                    datacenter: node.datacenter.clone(),
+                    rack: node.rack.clone(),
+                    host_id: node.host_id,
+                    release_version: node.release_version.clone(),
+                    schema_version: node.schema_version,
+                    status,
+                    distance: load_balancing.distance(node),
+                    connections: state.as_ref().map_or(0, |state| state.connections),
+                    idle_connections: state.as_ref().map_or(0, |state| state.idle_connections),
+                    orphaned_requests: pool
+                        .as_ref()
+                        .map_or(0, |pool| pool.orphaned_requests()),
+                    average_latency: pool
+                        .as_ref()
+                        .and_then(|pool| pool.latency().stats())
+                        .map(|stats| stats.average),
+                    tokens: token_counts.get(&node.addr).cloned().unwrap_or(0),
+                    ownership: ownership.get(&node.addr).cloned(),
+                }
+            })
+            .collect();
+
+        Some(ClusterState { nodes })
+    }
+
+    /// Returns a pool of one of given replicas which is up, preferring the ones earlier in
+    /// the query plan.
+    async fn replica_pool<T, M>(&self, replicas: &[SocketAddr]) -> Option<Arc<ConnectionPool<M>>>
+    where
+        T: CDRSTransport + 'static,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+        LB: LoadBalancingStrategy<ConnectionPool<M>>,
+    {
+        if replicas.is_empty() {
+            return None;
+        }
+
+        // replicas are tried in query plan order first, so the strategy decides which of them
+        // are preferred
+        let load_balancing = self.load_balancing.lock().await;
+        let replica = load_balancing
+            .query_plan()
+            .into_iter()
+            .find(|pool| !pool.is_down() && replicas.contains(&pool.get_addr()));
+        if replica.is_some() {
+            return replica;
+        }
+
+        // plans of some strategies don't include all nodes, so other replicas they'd pick are
+        // tried too, starting with a random one
+        let start = thread_rng().gen_range(0, replicas.len());
+        for index in 0..replicas.len() {
+            let addr = replicas[(start + index) % replicas.len()];
+            let replica = load_balancing.find(|pool| pool.get_addr() == addr && !pool.is_down());
+            if replica.is_some() {
+                return replica;
+            }
+        }
+
+        None
+    }
+
+    fn start_dns_refreshes<A>(&self, dns_refreshes: Vec<DnsRefresh<A>>)
+    where
+        A: Authenticator + 'static + Sized,
+        LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Send + 'static,
+    {
+        for dns_refresh in dns_refreshes {
+            tokio::spawn(dns_refresh.run(
+                Arc::downgrade(&self.load_balancing),
+                self.requests.clone(),
+            ));
+        }
+    }
 }
 
 #[async_trait]
 impl<
         T: CDRSTransport + Send + Sync + 'static,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
         LB: LoadBalancingStrategy<ConnectionPool<M>> + Sized + Send + Sync,
     > GetConnection<T, M> for Session<LB>
 {
     async fn get_connection(&self) -> Option<Arc<ConnectionPool<M>>> {
+        if self.requests.is_shut_down() {
+            return None;
+        }
+
         if cfg!(feature = "unstable-dynamic-cluster") {
             if let Some(ref event_stream_mx) = self.event_stream {
                 if let Ok(ref mut event_stream) = event_stream_mx.try_lock() {
@@ -89,10 +451,183 @@ impl<
             }
         }
 
+        // requests to nodes marked as down fail right away, so further nodes of the query plan
+        // are preferred as long as any of them is up
+        let plan = self.load_balancing.lock().await.query_plan();
+        plan.iter()
+            .find(|node| !node.is_down())
+            .or_else(|| plan.first())
+            .cloned()
+    }
+
+    fn partitioner(&self) -> Arc<dyn Partitioner> {
+        match self.cluster {
+            Some(ref cluster) => cluster.partitioner(),
+            None => Arc::new(Murmur3Partitioner),
+        }
+    }
+
+    async fn get_connection_for_token(
+        &self,
+        keyspace: &str,
+        token: &Token,
+    ) -> Option<Arc<ConnectionPool<M>>> {
+        if self.requests.is_shut_down() {
+            return None;
+        }
+
+        let replicas = match self.cluster {
+            Some(ref cluster) => cluster.token_map().replicas(keyspace, token),
+            None => vec![],
+        };
+
+        let replicas: Vec<SocketAddr> = replicas.iter().map(|replica| replica.addr).collect();
+        match self.replica_pool(&replicas).await {
+            Some(replica) => Some(replica),
+            None => self.get_connection().await,
+        }
+    }
+
+    async fn get_connection_for_tablet(
+        &self,
+        keyspace: &str,
+        table: &str,
+        token: i64,
+    ) -> Option<(Arc<ConnectionPool<M>>, u32)> {
+        if self.requests.is_shut_down() {
+            return None;
+        }
+
+        let cluster = self.cluster.as_ref()?;
+        let tablet = cluster.tablet(keyspace, table, token)?;
+        let nodes = cluster.nodes();
+
+        let replicas: Vec<(SocketAddr, u32)> = tablet
+            .replicas
+            .iter()
+            .filter_map(|replica| {
+                nodes
+                    .iter()
+                    .find(|node| node.host_id == Some(replica.host_id))
+                    .map(|node| (node.addr, replica.shard))
+            })
+            .collect();
+        let addrs: Vec<SocketAddr> = replicas.iter().map(|(addr, _)| *addr).collect();
+
+        let pool = self.replica_pool(&addrs).await?;
+        let shard = replicas
+            .iter()
+            .find(|(addr, _)| *addr == pool.get_addr())
+            .map(|(_, shard)| *shard)?;
+
+        Some((pool, shard))
+    }
+
+    async fn get_next_connection(&self, tried: &[SocketAddr]) -> Option<Arc<ConnectionPool<M>>> {
+        if self.requests.is_shut_down() {
+            return None;
+        }
+
+        self.load_balancing
+            .lock()
+            .await
+            .query_plan()
+            .into_iter()
+            .find(|node| !node.is_down() && !tried.contains(&node.get_addr()))
+    }
+
+    async fn get_connection_to(&self, target_node: &TargetNode) -> Option<Arc<ConnectionPool<M>>> {
+        if self.requests.is_shut_down() {
+            return None;
+        }
+
+        let addr = match *target_node {
+            TargetNode::Addr(addr) => addr,
+            TargetNode::HostId(host_id) => self
+                .cluster
+                .as_ref()?
+                .nodes()
+                .iter()
+                .find(|node| node.host_id == Some(host_id))?
+                .addr,
+        };
+
+        self.load_balancing
+            .lock()
+            .await
+            .find(|pool| pool.get_addr() == addr && !pool.is_down())
+    }
+
+    async fn get_all_connections(&self) -> Vec<Arc<ConnectionPool<M>>> {
+        if self.requests.is_shut_down() {
+            return vec![];
+        }
+
         self.load_balancing
             .lock()
             .await
-            .next()
+            .query_plan()
+            .into_iter()
+            .filter(|node| !node.is_down())
+            .collect()
+    }
+
+    fn default_consistency(&self) -> Consistency {
+        self.default_consistency
+    }
+
+    fn default_serial_consistency(&self) -> Option<Consistency> {
+        self.default_serial_consistency
+    }
+
+    fn retry_policy(&self) -> Arc<dyn RetryPolicy + Send + Sync> {
+        self.retry_policy.clone()
+    }
+
+    fn speculative_execution_policy(&self) -> Arc<dyn SpeculativeExecutionPolicy + Send + Sync> {
+        self.speculative_execution_policy.clone()
+    }
+
+    fn request_deadline(&self) -> Option<Duration> {
+        self.request_deadline
+    }
+
+    fn timestamp_generator(&self) -> Arc<dyn TimestampGenerator + Send + Sync> {
+        self.timestamp_generator.clone()
+    }
+
+    fn keyspace(&self) -> Option<String> {
+        self.keyspace.get()
+    }
+
+    fn prepared_cache(&self) -> Option<Arc<PreparedCache>> {
+        Some(self.prepared_cache.clone())
+    }
+
+    fn warning_handler(&self) -> Arc<dyn WarningHandler + Send + Sync> {
+        self.warning_handler.clone()
+    }
+
+    fn batch_warning_thresholds(&self) -> BatchWarningThresholds {
+        self.batch_warning_thresholds
+    }
+
+    fn add_tablet(&self, keyspace: &str, table: &str, tablet: Tablet) {
+        if let Some(ref cluster) = self.cluster {
+            cluster.add_tablet(keyspace, table, tablet);
+        }
+    }
+
+    fn start_request(&self) -> Option<RequestGuard> {
+        self.requests.start()
+    }
+
+    async fn after_schema_change(&self) {
+        if let Some(deadline) = self.schema_agreement_timeout {
+            if let Err(err) = self.wait_for_schema_agreement(deadline).await {
+                warn!("Unable to reach schema agreement: {}", err);
+            }
+        }
     }
 }
 
@@ -100,7 +635,7 @@ impl<
 impl<
         'a,
         T: CDRSTransport + Unpin + 'static,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
         LB: LoadBalancingStrategy<ConnectionPool<M>> + Sized + Send + Sync,
     > QueryExecutor<T, M> for Session<LB>
 {
@@ -111,7 +646,7 @@ impl<
         'a,
         T: CDRSTransport + Unpin + 'static,
         LB: LoadBalancingStrategy<ConnectionPool<M>> + Sized + Send + Sync,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
     > PrepareExecutor<T, M> for Session<LB>
 {
 }
@@ -121,7 +656,7 @@ impl<
         'a,
         T: CDRSTransport + Unpin + 'static,
         LB: LoadBalancingStrategy<ConnectionPool<M>> + Sized + Send + Sync,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
     > ExecExecutor<T, M> for Session<LB>
 {
 }
@@ -131,7 +666,7 @@ impl<
         'a,
         T: CDRSTransport + Unpin + 'static,
         LB: LoadBalancingStrategy<ConnectionPool<M>> + Sized + Send + Sync,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
     > BatchExecutor<T, M> for Session<LB>
 {
 }
@@ -139,24 +674,108 @@ impl<
 impl<
         'a,
         T: CDRSTransport + Unpin + 'static,
-        M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+        M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
         LB: LoadBalancingStrategy<ConnectionPool<M>> + Sized + Send + Sync,
     > CDRSSession<'a, T, M> for Session<LB>
 {
 }
 
-#[async_trait]
-impl <LB> ResponseCache for Session<LB> where LB: Send {
-    async fn match_or_cache_response(&self, stream_id: i16, frame: Frame) -> Option<Frame> {
-        if frame.stream == stream_id {
-            return Some(frame);
+/// Tries to connect to contact points in random order until a connection succeeds. Remaining
+/// nodes connect in the background, while nodes which could not be reached are reconnected
+/// according to their reconnection policy. Fails with errors of all nodes if none is reachable.
+async fn connect_any<T, M>(nodes: &[Arc<ConnectionPool<M>>]) -> error::Result<()>
+where
+    T: CDRSTransport + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+{
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    thread_rng().shuffle(&mut order);
+
+    let mut errors = Vec::with_capacity(nodes.len());
+    for index in order {
+        match nodes[index].connection().await {
+            Ok(_) => return Ok(()),
+            Err(err) => {
+                warn!("Unable to connect to {}: {}", nodes[index].get_addr(), err);
+                errors.push(format!("{}: {}", nodes[index].get_addr(), err));
+            }
         }
+    }
 
-        let mut responses = self.responses.lock().await;
+    Err(error::Error::General(format!(
+        "Unable to connect to any contact point [{}]",
+        errors.join("; ")
+    )))
+}
+
+/// Creates pools for TCP nodes. Nodes with DNS refresh enabled get a pool for each address
+/// their hostname resolves to.
+async fn new_tcp_node_pools<A>(
+    node_configs: &ClusterTcpConfig<'_, A>,
+    compression: Compression,
+    keyspace: &SessionKeyspace,
+) -> error::Result<(Vec<Arc<TcpConnectionPool<A>>>, Vec<DnsRefresh<A>>)>
+where
+    A: Authenticator + 'static + Sized,
+{
+    let mut nodes: Vec<Arc<TcpConnectionPool<A>>> = Vec::with_capacity(node_configs.0.len());
+    let mut dns_refreshes = vec![];
+
+    for node_config in &node_configs.0 {
+        // connections to all nodes share the keyspace selected with `USE`
+        let mut node_config = node_config.clone();
+        node_config.transport_config.keyspace = keyspace.clone();
 
-        responses.insert(frame.stream, frame);
-        responses.remove(&stream_id)
+        match node_config.dns_refresh_interval {
+            Some(interval) => {
+                let (dns_refresh, node_connection_pools) =
+                    DnsRefresh::new(&node_config, interval, compression).await?;
+                nodes.extend(node_connection_pools);
+                dns_refreshes.push(dns_refresh);
+            }
+            None => {
+                let node_connection_pool = new_lazy_tcp_pool(node_config, compression)?;
+                nodes.push(Arc::new(node_connection_pool));
+            }
+        }
     }
+
+    Ok((nodes, dns_refreshes))
+}
+
+/// Warms up node pools concurrently, and prepares their warm-up statements, adding them to
+/// given cache. Nodes which fail to warm up are only reported, since they're handled like any
+/// other unreachable node.
+async fn warm_up<T, M>(nodes: &[Arc<ConnectionPool<M>>], prepared_cache: &Arc<PreparedCache>)
+where
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error>,
+{
+    let mut warm_ups = JoinSet::new();
+    for node in nodes {
+        let node = node.clone();
+        let prepared_cache = prepared_cache.clone();
+        warm_ups.spawn(async move {
+            if let Err(err) = node.warm_up().await {
+                warn!("Unable to warm up connections to {}: {}", node.get_addr(), err);
+                return;
+            }
+
+            for query in node.warm_up_statements() {
+                match prepare_node_query(&node, query).await {
+                    Ok(prepared) => {
+                        let mut prepared = PreparedQuery::from(prepared);
+                        prepared.query = Some(query.clone());
+                        // no keyspace is selected yet
+                        prepared_cache.insert(None, query, prepared);
+                    }
+                    Err(err) => warn!("Unable to prepare query on {}: {}", node.get_addr(), err),
+                }
+            }
+        });
+    }
+
+    while warm_ups.join_next().await.is_some() {}
 }
 
 async fn connect_static<A, LB>(
@@ -166,22 +785,95 @@ async fn connect_static<A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
-    let mut nodes: Vec<Arc<TcpConnectionPool<A>>> = Vec::with_capacity(node_configs.0.len());
+    let keyspace = SessionKeyspace::new();
+    let (nodes, dns_refreshes) = new_tcp_node_pools(node_configs, compression, &keyspace).await?;
+
+    connect_any(&nodes).await?;
+    let prepared_cache: Arc<PreparedCache> = Default::default();
+    warm_up(&nodes, &prepared_cache).await;
+
+    load_balancing.init(nodes);
+
+    let session = Session {
+        load_balancing: Arc::new(Mutex::new(load_balancing)),
+        requests: Default::default(),
+        close_nodes: |load_balancing| load_balancing.init(vec![]),
+        cluster: None,
+        event_stream: None,
+        schema_agreement_timeout: None,
+        refresh_requests: None,
+        retry_policy: Arc::new(DefaultRetryPolicy),
+        speculative_execution_policy: Arc::new(NoSpeculativeExecutionPolicy),
+        request_deadline: None,
+        timestamp_generator: Arc::new(MonotonicTimestampGenerator::new()),
+        prepared_cache,
+        default_consistency: Default::default(),
+        default_serial_consistency: None,
+        keyspace,
+        warning_handler: Arc::new(LogWarningHandler),
+        batch_warning_thresholds: Default::default(),
+        compression,
+        #[cfg(feature = "rust-tls")]
+        tls_configs: vec![],
+    };
+
+    session.start_dns_refreshes(dns_refreshes);
+
+    Ok(session)
+}
+
+#[cfg(feature = "rust-tls")]
+async fn connect_rustls_static<A, LB>(
+    node_configs: &ClusterRustlsConfig<A>,
+    mut load_balancing: LB,
+    compression: Compression,
+) -> error::Result<Session<LB>>
+where
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<RustlsConnectionPool<A>> + Sized,
+{
+    let mut nodes: Vec<Arc<RustlsConnectionPool<A>>> = Vec::with_capacity(node_configs.0.len());
+    let mut tls_configs = Vec::with_capacity(node_configs.0.len());
+    let keyspace = SessionKeyspace::new();
 
     for node_config in &node_configs.0 {
-        let node_connection_pool = new_tcp_pool(node_config.clone()).await?;
+        // connections to all nodes share the keyspace selected with `USE`
+        let mut node_config = node_config.clone();
+        node_config.transport_config.keyspace = keyspace.clone();
+
+        let (node_connection_pool, tls_config) = new_lazy_rustls_pool(node_config, compression);
         nodes.push(Arc::new(node_connection_pool));
+        tls_configs.push(tls_config);
     }
 
+    connect_any(&nodes).await?;
+    let prepared_cache: Arc<PreparedCache> = Default::default();
+    warm_up(&nodes, &prepared_cache).await;
+
     load_balancing.init(nodes);
 
     Ok(Session {
-        load_balancing: Mutex::new(load_balancing),
+        load_balancing: Arc::new(Mutex::new(load_balancing)),
+        requests: Default::default(),
+        close_nodes: |load_balancing| load_balancing.init(vec![]),
+        cluster: None,
         event_stream: None,
-        responses: Mutex::new(FnvHashMap::default()),
+        schema_agreement_timeout: None,
+        refresh_requests: None,
+        retry_policy: Arc::new(DefaultRetryPolicy),
+        speculative_execution_policy: Arc::new(NoSpeculativeExecutionPolicy),
+        request_deadline: None,
+        timestamp_generator: Arc::new(MonotonicTimestampGenerator::new()),
+        prepared_cache,
+        default_consistency: Default::default(),
+        default_serial_consistency: None,
+        keyspace,
+        warning_handler: Arc::new(LogWarningHandler),
+        batch_warning_thresholds: Default::default(),
         compression,
+        tls_configs,
     })
 }
 
@@ -194,37 +886,315 @@ async fn connect_dynamic<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
-    let mut nodes: Vec<Arc<TcpConnectionPool<A>>> = Vec::with_capacity(node_configs.0.len());
+    let keyspace = SessionKeyspace::new();
+    let (nodes, dns_refreshes) = new_tcp_node_pools(node_configs, compression, &keyspace).await?;
 
-    for node_config in &node_configs.0 {
-        let node_connection_pool = new_tcp_pool(node_config.clone()).await?;
-        nodes.push(Arc::new(node_connection_pool));
-    }
+    connect_any(&nodes).await?;
+    let prepared_cache: Arc<PreparedCache> = Default::default();
+    warm_up(&nodes, &prepared_cache).await;
 
     load_balancing.init(nodes);
 
     let mut session = Session {
-        load_balancing: Mutex::new(load_balancing),
+        load_balancing: Arc::new(Mutex::new(load_balancing)),
+        requests: Default::default(),
+        close_nodes: |load_balancing| load_balancing.init(vec![]),
+        cluster: None,
         event_stream: None,
-        responses: Mutex::new(FnvHashMap::default()),
+        schema_agreement_timeout: None,
+        refresh_requests: None,
+        retry_policy: Arc::new(DefaultRetryPolicy),
+        speculative_execution_policy: Arc::new(NoSpeculativeExecutionPolicy),
+        request_deadline: None,
+        timestamp_generator: Arc::new(MonotonicTimestampGenerator::new()),
+        prepared_cache,
+        default_consistency: Default::default(),
+        default_serial_consistency: None,
+        keyspace,
+        warning_handler: Arc::new(LogWarningHandler),
+        batch_warning_thresholds: Default::default(),
         compression,
+        #[cfg(feature = "rust-tls")]
+        tls_configs: vec![],
     };
 
+    session.start_dns_refreshes(dns_refreshes);
+
     let (listener, event_stream) = session.listen_non_blocking(
         event_src.addr,
         event_src.authenticator,
         vec![SimpleServerEvent::StatusChange],
     ).await?;
 
-    tokio::spawn(listener.start(&Compression::None));
+    tokio::spawn(listener.start());
 
     session.event_stream = Some(Mutex::new(event_stream));
 
     Ok(session)
 }
 
+/// Builder of TCP sessions which discover cluster nodes instead of connecting only to the
+/// configured ones. Configured nodes serve as contact points - a control connection is kept
+/// open to one of them (or to a discovered node later on), nodes are read from `system.local`
+/// and `system.peers`, and pools are created for all of them. Nodes are refreshed periodically,
+/// so pools are added for nodes joining the cluster and removed for nodes leaving it.
+///
+/// Discovered nodes use the configuration of the first contact point, with node address
+/// replaced.
+pub struct ClusterSessionBuilder<'a, A: Authenticator + Sized, LB> {
+    node_configs: &'a ClusterTcpConfig<'a, A>,
+    load_balancing: LB,
+    compression: Compression,
+    address_translator: Arc<dyn AddressTranslator>,
+    host_filter: Arc<dyn HostFilter>,
+    topology_refresh_interval: Duration,
+    refresh_debounce: Duration,
+    schema_agreement_timeout: Option<Duration>,
+    retry_policy: Arc<dyn RetryPolicy + Send + Sync>,
+    speculative_execution_policy: Arc<dyn SpeculativeExecutionPolicy + Send + Sync>,
+    request_deadline: Option<Duration>,
+    timestamp_generator: Arc<dyn TimestampGenerator + Send + Sync>,
+    prepared_cache_capacity: usize,
+    default_consistency: Consistency,
+    default_serial_consistency: Option<Consistency>,
+    warning_handler: Arc<dyn WarningHandler + Send + Sync>,
+    batch_warning_thresholds: BatchWarningThresholds,
+}
+
+impl<'a, A, LB> ClusterSessionBuilder<'a, A, LB>
+where
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
+{
+    const DEFAULT_TOPOLOGY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+    const DEFAULT_REFRESH_DEBOUNCE: Duration = Duration::from_secs(1);
+
+    /// Creates a builder using given contact points and load balancing strategy.
+    pub fn new(node_configs: &'a ClusterTcpConfig<'a, A>, load_balancing: LB) -> Self {
+        ClusterSessionBuilder {
+            node_configs,
+            load_balancing,
+            compression: Compression::None,
+            address_translator: Arc::new(IdentityTranslator),
+            host_filter: Arc::new(AcceptAllFilter),
+            topology_refresh_interval: Self::DEFAULT_TOPOLOGY_REFRESH_INTERVAL,
+            refresh_debounce: Self::DEFAULT_REFRESH_DEBOUNCE,
+            schema_agreement_timeout: None,
+            retry_policy: Arc::new(DefaultRetryPolicy),
+            speculative_execution_policy: Arc::new(NoSpeculativeExecutionPolicy),
+            request_deadline: None,
+            timestamp_generator: Arc::new(MonotonicTimestampGenerator::new()),
+            prepared_cache_capacity: PreparedCache::DEFAULT_CAPACITY,
+            default_consistency: Default::default(),
+            default_serial_consistency: None,
+            warning_handler: Arc::new(LogWarningHandler),
+            batch_warning_thresholds: Default::default(),
+        }
+    }
+
+    /// Sets compression. Default is no compression.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets translator of node addresses read from `system.peers`. Default leaves addresses
+    /// unchanged.
+    pub fn address_translator(mut self, address_translator: Arc<dyn AddressTranslator>) -> Self {
+        self.address_translator = address_translator;
+        self
+    }
+
+    /// Sets filter of discovered nodes - rejected nodes are never connected to. Default accepts
+    /// all nodes.
+    pub fn host_filter(mut self, host_filter: Arc<dyn HostFilter>) -> Self {
+        self.host_filter = host_filter;
+        self
+    }
+
+    /// Sets how often cluster nodes are refreshed. Default is 60 seconds.
+    pub fn topology_refresh_interval(mut self, interval: Duration) -> Self {
+        self.topology_refresh_interval = interval;
+        self
+    }
+
+    /// Sets how long topology and schema change events are collected before refreshing nodes
+    /// and schema metadata, so bursts of them cause a single refresh. Default is 1 second.
+    pub fn refresh_debounce(mut self, debounce: Duration) -> Self {
+        self.refresh_debounce = debounce;
+        self
+    }
+
+    /// Makes statements altering the schema wait until all nodes agree on the schema version,
+    /// for at most given time. Disabled by default.
+    pub fn schema_agreement_timeout(mut self, timeout: Duration) -> Self {
+        self.schema_agreement_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the policy deciding whether failed requests are retried. Default is
+    /// `DefaultRetryPolicy`.
+    pub fn retry_policy(mut self, retry_policy: Arc<dyn RetryPolicy + Send + Sync>) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the policy deciding when idempotent requests are executed speculatively. Default
+    /// is `NoSpeculativeExecutionPolicy`.
+    pub fn speculative_execution_policy(
+        mut self,
+        speculative_execution_policy: Arc<dyn SpeculativeExecutionPolicy + Send + Sync>,
+    ) -> Self {
+        self.speculative_execution_policy = speculative_execution_policy;
+        self
+    }
+
+    /// Sets the time limit of requests, including retries and speculative executions, for
+    /// requests without their own deadline. Disabled by default.
+    pub fn request_deadline(mut self, deadline: Duration) -> Self {
+        self.request_deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the generator of default timestamps of requests without explicit ones. Default is
+    /// `MonotonicTimestampGenerator`.
+    pub fn timestamp_generator(
+        mut self,
+        timestamp_generator: Arc<dyn TimestampGenerator + Send + Sync>,
+    ) -> Self {
+        self.timestamp_generator = timestamp_generator;
+        self
+    }
+
+    /// Sets the handler of warnings attached to responses, e.g. about reading many tombstones.
+    /// Default is `LogWarningHandler`.
+    pub fn warning_handler(
+        mut self,
+        warning_handler: Arc<dyn WarningHandler + Send + Sync>,
+    ) -> Self {
+        self.warning_handler = warning_handler;
+        self
+    }
+
+    /// Sets the thresholds of batches, over which `WarningHandler::handle_batch_warning()` is
+    /// called before sending them. Disabled by default.
+    pub fn batch_warning_thresholds(mut self, thresholds: BatchWarningThresholds) -> Self {
+        self.batch_warning_thresholds = thresholds;
+        self
+    }
+
+    /// Sets how many prepared statements are cached. Default is
+    /// `PreparedCache::DEFAULT_CAPACITY`.
+    pub fn prepared_cache_capacity(mut self, capacity: usize) -> Self {
+        self.prepared_cache_capacity = capacity;
+        self
+    }
+
+    /// Sets the consistency of queries, executions and batches which don't set one explicitly.
+    /// Default is `Consistency::One`.
+    pub fn default_consistency(mut self, consistency: Consistency) -> Self {
+        self.default_consistency = consistency;
+        self
+    }
+
+    /// Sets the serial consistency of conditional updates which don't set one explicitly,
+    /// which has to be `Serial` or `LocalSerial`. By default it's left to the server, which
+    /// uses `Serial`.
+    pub fn default_serial_consistency(mut self, serial_consistency: Consistency) -> Self {
+        self.default_serial_consistency = Some(serial_consistency);
+        self
+    }
+
+    /// Discovers cluster nodes and creates the session.
+    pub async fn build(self) -> error::Result<Session<LB>> {
+        if let Some(serial_consistency) = self.default_serial_consistency {
+            serial_consistency.check_serial()?;
+        }
+
+        // pools of all nodes are created from the first node config, so their connections
+        // share the keyspace selected with `USE`
+        let keyspace = SessionKeyspace::new();
+        let mut node_config = self
+            .node_configs
+            .0
+            .first()
+            .ok_or_else(|| error::Error::General("No contact points".into()))?
+            .clone();
+        node_config.transport_config.keyspace = keyspace.clone();
+        let contact_points = self
+            .node_configs
+            .0
+            .iter()
+            .map(|node_config| node_config.addr.to_string())
+            .collect();
+
+        let cluster = Arc::new(Cluster::default());
+        let prepared_cache = Arc::new(PreparedCache::new(self.prepared_cache_capacity));
+        let mut control_connection = ControlConnection::new(
+            &node_config,
+            contact_points,
+            self.compression,
+            self.address_translator,
+            self.host_filter,
+            self.topology_refresh_interval,
+            cluster.clone(),
+        )
+        .with_refresh_debounce(self.refresh_debounce)
+        .with_prepared_cache(prepared_cache.clone());
+
+        let discovered = control_connection.refresh().await?;
+        let mut load_balancing = self.load_balancing;
+        load_balancing.update_nodes(&discovered);
+
+        let mut nodes = vec![];
+        for node in &discovered {
+            let distance = load_balancing.distance(node);
+            if distance != NodeDistance::Ignored {
+                nodes.push(control_connection.add_pool(node.addr, distance)?);
+            }
+        }
+
+        connect_any(&nodes).await?;
+        warm_up(&nodes, &prepared_cache).await;
+
+        load_balancing.init(nodes);
+
+        let (refresh_sender, refresh_requests) = unbounded_channel();
+        let session = Session {
+            load_balancing: Arc::new(Mutex::new(load_balancing)),
+            requests: Default::default(),
+            close_nodes: |load_balancing| load_balancing.init(vec![]),
+            cluster: Some(cluster),
+            event_stream: None,
+            schema_agreement_timeout: self.schema_agreement_timeout,
+            refresh_requests: Some(refresh_sender),
+            retry_policy: self.retry_policy,
+            speculative_execution_policy: self.speculative_execution_policy,
+            request_deadline: self.request_deadline,
+            timestamp_generator: self.timestamp_generator,
+            prepared_cache,
+            default_consistency: self.default_consistency,
+            default_serial_consistency: self.default_serial_consistency,
+            keyspace,
+            warning_handler: self.warning_handler,
+            batch_warning_thresholds: self.batch_warning_thresholds,
+            compression: self.compression,
+            #[cfg(feature = "rust-tls")]
+            tls_configs: vec![],
+        };
+
+        tokio::spawn(control_connection.run(
+            Arc::downgrade(&session.load_balancing),
+            session.requests.clone(),
+            refresh_requests,
+        ));
+
+        Ok(session)
+    }
+}
+
 /// Creates new session that will perform queries without any compression. `Compression` type
 /// can be changed at any time.
 /// As a parameter it takes:
@@ -236,11 +1206,67 @@ pub async fn new<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
     connect_static(node_configs, load_balancing, Compression::None).await
 }
 
+/// Creates new session connected over Unix domain sockets that will perform queries without
+/// any compression. `Compression` type can be changed at any time.
+/// As a parameter it takes:
+/// * cluster config
+/// * load balancing strategy (cannot be changed during `Session` life time).
+#[cfg(unix)]
+pub async fn new_unix<A, LB>(
+    node_configs: &ClusterUnixConfig<A>,
+    mut load_balancing: LB,
+) -> error::Result<Session<LB>>
+where
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<UnixConnectionPool<A>> + Sized,
+{
+    let mut nodes: Vec<Arc<UnixConnectionPool<A>>> = Vec::with_capacity(node_configs.0.len());
+    let keyspace = SessionKeyspace::new();
+
+    for node_config in &node_configs.0 {
+        // connections to all nodes share the keyspace selected with `USE`
+        let mut node_config = node_config.clone();
+        node_config.transport_config.keyspace = keyspace.clone();
+
+        let node_connection_pool = new_lazy_unix_pool(node_config, Compression::None);
+        nodes.push(Arc::new(node_connection_pool));
+    }
+
+    connect_any(&nodes).await?;
+    let prepared_cache: Arc<PreparedCache> = Default::default();
+    warm_up(&nodes, &prepared_cache).await;
+
+    load_balancing.init(nodes);
+
+    Ok(Session {
+        load_balancing: Arc::new(Mutex::new(load_balancing)),
+        requests: Default::default(),
+        close_nodes: |load_balancing| load_balancing.init(vec![]),
+        cluster: None,
+        event_stream: None,
+        schema_agreement_timeout: None,
+        refresh_requests: None,
+        retry_policy: Arc::new(DefaultRetryPolicy),
+        speculative_execution_policy: Arc::new(NoSpeculativeExecutionPolicy),
+        request_deadline: None,
+        timestamp_generator: Arc::new(MonotonicTimestampGenerator::new()),
+        prepared_cache,
+        default_consistency: Default::default(),
+        default_serial_consistency: None,
+        keyspace,
+        warning_handler: Arc::new(LogWarningHandler),
+        batch_warning_thresholds: Default::default(),
+        compression: Compression::None,
+        #[cfg(feature = "rust-tls")]
+        tls_configs: vec![],
+    })
+}
+
 /// Creates new session that will perform queries without any compression. `Compression` type
 /// can be changed at any time. Once received topology change event, it will adjust an inner load
 /// balancer.
@@ -256,7 +1282,7 @@ pub async fn new_dynamic<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
     connect_dynamic(node_configs, load_balancing, Compression::None, event_src).await
 }
@@ -272,7 +1298,7 @@ pub async fn new_snappy<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
     connect_static(node_configs, load_balancing, Compression::Snappy).await
 }
@@ -292,7 +1318,7 @@ pub async fn new_snappy_dynamic<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
     connect_dynamic(node_configs, load_balancing, Compression::Snappy, event_src).await
 }
@@ -308,7 +1334,7 @@ pub async fn new_lz4<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
     connect_static(node_configs, load_balancing, Compression::Lz4).await
 }
@@ -328,11 +1354,91 @@ pub async fn new_lz4_dynamic<'a, A, LB>(
 ) -> error::Result<Session<LB>>
 where
     A: Authenticator + 'static + Sized,
-    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized,
+    LB: LoadBalancingStrategy<TcpConnectionPool<A>> + Sized + Send + 'static,
 {
     connect_dynamic(node_configs, load_balancing, Compression::Lz4, event_src).await
 }
 
+/// Creates new TLS session that will perform queries without any compression. `Compression` type
+/// can be changed at any time.
+/// As a parameter it takes:
+/// * cluster config
+/// * load balancing strategy (cannot be changed during `Session` life time).
+#[cfg(feature = "rust-tls")]
+pub async fn new_rustls<A, LB>(
+    node_configs: &ClusterRustlsConfig<A>,
+    load_balancing: LB,
+) -> error::Result<Session<LB>>
+where
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<RustlsConnectionPool<A>> + Sized,
+{
+    connect_rustls_static(node_configs, load_balancing, Compression::None).await
+}
+
+/// Creates new TLS session that will perform queries with Snappy compression. `Compression` type
+/// can be changed at any time.
+/// As a parameter it takes:
+/// * cluster config
+/// * load balancing strategy (cannot be changed during `Session` life time).
+#[cfg(feature = "rust-tls")]
+pub async fn new_rustls_snappy<A, LB>(
+    node_configs: &ClusterRustlsConfig<A>,
+    load_balancing: LB,
+) -> error::Result<Session<LB>>
+where
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<RustlsConnectionPool<A>> + Sized,
+{
+    connect_rustls_static(node_configs, load_balancing, Compression::Snappy).await
+}
+
+/// Creates new TLS session that will perform queries with LZ4 compression. `Compression` type
+/// can be changed at any time.
+/// As a parameter it takes:
+/// * cluster config
+/// * load balancing strategy (cannot be changed during `Session` life time).
+#[cfg(feature = "rust-tls")]
+pub async fn new_rustls_lz4<A, LB>(
+    node_configs: &ClusterRustlsConfig<A>,
+    load_balancing: LB,
+) -> error::Result<Session<LB>>
+where
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<RustlsConnectionPool<A>> + Sized,
+{
+    connect_rustls_static(node_configs, load_balancing, Compression::Lz4).await
+}
+
+/// Creates new session connected to a DataStax Astra database that will perform queries without
+/// any compression. Nodes are discovered with the metadata service described by given secure
+/// connect bundle. `Compression` type can be changed at any time.
+/// As a parameter it takes:
+/// * path to secure connect bundle zip file, or a directory it was extracted to
+/// * authenticator
+/// * load balancing strategy (cannot be changed during `Session` life time).
+#[cfg(feature = "rust-tls")]
+pub async fn new_cloud<P, A, LB>(
+    bundle_path: P,
+    authenticator: A,
+    load_balancing: LB,
+) -> error::Result<Session<LB>>
+where
+    P: AsRef<Path>,
+    A: Authenticator + 'static + Sized,
+    LB: LoadBalancingStrategy<RustlsConnectionPool<A>> + Sized,
+{
+    let bundle_path = bundle_path.as_ref();
+    let cloud_config = if bundle_path.is_dir() {
+        CloudConfig::from_bundle_dir(bundle_path)?
+    } else {
+        CloudConfig::from_bundle(bundle_path)?
+    };
+
+    let node_configs = cloud_config.fetch_nodes(authenticator).await?;
+    connect_rustls_static(&node_configs, load_balancing, Compression::None).await
+}
+
 impl<'a, L> Session<L> {
     /// Returns new event listener.
     pub async fn listen<A: Authenticator + 'static + Sized>(
@@ -340,17 +1446,23 @@ impl<'a, L> Session<L> {
         node: &str,
         authenticator: A,
         events: Vec<SimpleServerEvent>,
-    ) -> error::Result<(Listener<Mutex<TransportTcp>>, EventStream)> {
+    ) -> error::Result<(Listener<TransportTcp>, EventStream)> {
         let compression = self.get_compressor();
-        let transport = TransportTcp::new(&node).await.map(Mutex::new)?;
+        let (event_frames_sender, event_frames) = unbounded_channel();
+        let transport = TransportTcp::new(
+            &node,
+            compression,
+            Some(event_frames_sender),
+            &Default::default(),
+        )
+        .await?;
 
         startup(&transport, &authenticator).await?;
 
-        let query_frame = Frame::new_req_register(events).into_cbytes();
-        transport.lock().await.write(query_frame.as_slice()).await?;
-        parse_frame(&transport, &compression).await?;
+        let query_frame = Frame::new_req_register(events);
+        transport.write_frame(&query_frame).await?;
 
-        Ok(new_listener(transport))
+        Ok(new_listener(transport, event_frames))
     }
 
     pub async fn listen_non_blocking<A: Authenticator + 'static + Sized>(
@@ -358,10 +1470,175 @@ impl<'a, L> Session<L> {
         node: &str,
         authenticator: A,
         events: Vec<SimpleServerEvent>,
-    ) -> error::Result<(Listener<Mutex<TransportTcp>>, EventStreamNonBlocking)> {
+    ) -> error::Result<(Listener<TransportTcp>, EventStreamNonBlocking)> {
         self.listen(node, authenticator, events).await.map(|l| {
             let (listener, stream) = l;
             (listener, stream.into())
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticators::NoneAuthenticator;
+    #[cfg(feature = "rust-tls")]
+    use crate::cluster::NodeRustlsConfigBuilder;
+    use crate::cluster::NodeTcpConfigBuilder;
+    use crate::load_balancing::RoundRobin;
+    use crate::test_utils::serve_requests;
+    #[cfg(feature = "rust-tls")]
+    use rustls::{Certificate, ClientConfig, NoClientAuth, PrivateKey, ServerConfig};
+    use std::sync::Mutex as StdMutex;
+    use tokio::net::TcpListener;
+    #[cfg(feature = "rust-tls")]
+    use tokio_rustls::TlsAcceptor;
+
+    /// Runs a node answering requests with `serve_requests`. Returns its address and texts of
+    /// prepared queries.
+    async fn fake_node() -> (String, Arc<StdMutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let prepared: Arc<StdMutex<Vec<String>>> = Default::default();
+
+        let node_prepared = prepared.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_requests(stream, node_prepared.clone()));
+            }
+        });
+
+        (addr, prepared)
+    }
+
+    #[tokio::test]
+    async fn reports_errors_of_all_contact_points() {
+        let node = |addr| {
+            NodeTcpConfigBuilder::new(addr, NoneAuthenticator)
+                .connection_timeout(Duration::from_millis(100))
+                .build()
+        };
+        let cluster_config = ClusterTcpConfig(vec![node("127.0.0.1:1"), node("127.0.0.1:2")]);
+
+        let error = new(&cluster_config, RoundRobin::new()).await.unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("127.0.0.1:1"));
+        assert!(message.contains("127.0.0.1:2"));
+    }
+
+    #[tokio::test]
+    async fn prepares_statements_on_warmed_up_nodes() {
+        let (addr, prepared) = fake_node().await;
+        let query = "SELECT * FROM ks.t WHERE id = ?";
+        let cluster_config = ClusterTcpConfig(vec![NodeTcpConfigBuilder::new(
+            addr.as_str(),
+            NoneAuthenticator,
+        )
+        .max_size(3)
+        .warm_up(true)
+        .warm_up_statements(vec![query.to_string()])
+        .build()]);
+
+        let session = new(&cluster_config, RoundRobin::new()).await.unwrap();
+
+        let node = session.get_connection().await.unwrap();
+        assert_eq!(node.state().connections, 3);
+        // prepared statements are shared by connections of a node
+        assert_eq!(*prepared.lock().unwrap(), vec![query]);
+
+        let cached = session.prepared_cache.get(None, query).unwrap();
+        assert_eq!(cached.id.into_plain(), Some(vec![7]));
+        assert_eq!(cached.query.as_deref(), Some(query));
+    }
+
+    #[tokio::test]
+    async fn prepares_no_statements_without_warm_up() {
+        let (addr, prepared) = fake_node().await;
+        let cluster_config = ClusterTcpConfig(vec![NodeTcpConfigBuilder::new(
+            addr.as_str(),
+            NoneAuthenticator,
+        )
+        .warm_up_statements(vec!["SELECT * FROM ks.t".to_string()])
+        .build()]);
+
+        let session = new(&cluster_config, RoundRobin::new()).await.unwrap();
+
+        assert!(prepared.lock().unwrap().is_empty());
+        assert!(session
+            .prepared_cache
+            .get(None, "SELECT * FROM ks.t")
+            .is_none());
+    }
+
+    /// Runs a TLS node with a certificate for `node1.example.com`, signed by the test CA.
+    #[cfg(feature = "rust-tls")]
+    async fn tls_node() -> SocketAddr {
+        let mut config = ServerConfig::new(NoClientAuth::new());
+        config
+            .set_single_cert(
+                vec![Certificate(
+                    include_bytes!("../../fixtures/tls/exact.der").to_vec(),
+                )],
+                PrivateKey(include_bytes!("../../fixtures/tls/exact-key.der").to_vec()),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(stream) = acceptor.accept(stream).await {
+                        serve_requests(stream, Default::default()).await;
+                    }
+                });
+            }
+        });
+
+        addr
+    }
+
+    #[cfg(feature = "rust-tls")]
+    fn client_config(ca: &[u8]) -> Arc<ClientConfig> {
+        let mut config = ClientConfig::new();
+        config.root_store.add(&Certificate(ca.to_vec())).unwrap();
+        Arc::new(config)
+    }
+
+    #[cfg(feature = "rust-tls")]
+    #[tokio::test]
+    async fn opens_connections_with_reloaded_tls_config() {
+        let ca = include_bytes!("../../fixtures/tls/ca.der");
+        let other_ca = include_bytes!("../../fixtures/tls/other-ca.der");
+        let dns_name = webpki::DNSNameRef::try_from_ascii_str("node1.example.com")
+            .unwrap()
+            .to_owned();
+        let cluster_config = ClusterRustlsConfig(vec![NodeRustlsConfigBuilder::new(
+            tls_node().await,
+            dns_name,
+            NoneAuthenticator,
+            client_config(ca),
+        )
+        .build()]);
+
+        let session = new_rustls(&cluster_config, RoundRobin::new())
+            .await
+            .unwrap();
+        let node = session.get_connection().await.unwrap();
+        let connection = node.connection().await.unwrap();
+
+        // new connections don't trust the node certificate anymore
+        session.reload_tls(client_config(other_ca));
+        assert!(node.get_pool().dedicated_connection().await.is_err());
+
+        // while established ones keep working
+        let options = Frame::new_req_options();
+        assert!(connection.write_frame(&options).await.is_ok());
+
+        session.reload_tls(client_config(ca));
+        let connection = node.get_pool().dedicated_connection().await.unwrap();
+        assert!(connection.write_frame(&options).await.is_ok());
+    }
+}
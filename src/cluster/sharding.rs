@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use fnv::FnvHashMap;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::cluster::tablets::TABLETS_ROUTING_EXTENSION;
+use crate::error;
+use crate::frame::Frame;
+use crate::transport::CDRSTransport;
+
+const SHARD_OPTION: &str = "SCYLLA_SHARD";
+const NR_SHARDS_OPTION: &str = "SCYLLA_NR_SHARDS";
+const SHARDING_IGNORE_MSB_OPTION: &str = "SCYLLA_SHARDING_IGNORE_MSB";
+const SHARD_AWARE_PORT_OPTION: &str = "SCYLLA_SHARD_AWARE_PORT";
+
+/// Range of source ports used for connecting to a specific shard.
+const SOURCE_PORT_MIN: u16 = 49152;
+const SOURCE_PORT_MAX: u16 = 65535;
+
+/// Time to wait before connecting to a shard again after a failed attempt.
+const SHARD_RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Sharding information of a Scylla node connection, read from the SUPPORTED response.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShardInfo {
+    /// Shard handling the connection.
+    pub shard: u32,
+    /// Number of shards of the node.
+    pub nr_shards: u32,
+    /// Number of most significant token bits ignored when computing the shard.
+    pub msb_ignore: u8,
+    /// Port on which the node assigns connections to shards by source port, if enabled.
+    pub shard_aware_port: Option<u16>,
+}
+
+impl ShardInfo {
+    /// Reads sharding information from options of a SUPPORTED response. Returns `None` for
+    /// nodes which are not sharded, e.g. Cassandra ones.
+    pub fn from_supported(options: &HashMap<String, Vec<String>>) -> Option<Self> {
+        fn option<T: std::str::FromStr>(
+            options: &HashMap<String, Vec<String>>,
+            name: &str,
+        ) -> Option<T> {
+            options.get(name)?.first()?.parse().ok()
+        }
+
+        let nr_shards = option(options, NR_SHARDS_OPTION).filter(|nr_shards| *nr_shards > 0)?;
+
+        Some(ShardInfo {
+            shard: option(options, SHARD_OPTION)?,
+            nr_shards,
+            msb_ignore: option(options, SHARDING_IGNORE_MSB_OPTION).unwrap_or(0),
+            shard_aware_port: option(options, SHARD_AWARE_PORT_OPTION),
+        })
+    }
+
+    /// Returns the shard owning given token.
+    pub fn shard_of(&self, token: i64) -> u32 {
+        let biased_token = (token as u64).wrapping_add(1 << 63) << self.msb_ignore;
+        ((biased_token as u128 * self.nr_shards as u128) >> 64) as u32
+    }
+
+    /// Returns source ports which make the shard-aware port assign a connection to given
+    /// shard, starting at a random one.
+    pub(crate) fn source_ports(&self, shard: u32) -> impl Iterator<Item = u16> {
+        let nr_shards = self.nr_shards;
+        let first = SOURCE_PORT_MIN as u32 + nr_shards - SOURCE_PORT_MIN as u32 % nr_shards + shard;
+        let count = (SOURCE_PORT_MAX as u32 - first) / nr_shards + 1;
+        let start = rand::random::<u32>() % count;
+
+        (0..count).map(move |index| (first + (start + index) % count * nr_shards) as u16)
+    }
+}
+
+/// Asks the node for options it supports, which tell the shard handling the connection and
+/// protocol extensions of Scylla nodes.
+pub(crate) async fn fetch_supported<T: CDRSTransport>(
+    transport: &T,
+) -> error::Result<HashMap<String, Vec<String>>> {
+    let supported = transport
+        .write_frame(&Frame::new_req_options())
+        .await?
+        .get_body()?
+        .into_supported();
+
+    Ok(supported
+        .map(|supported| supported.data)
+        .unwrap_or_default())
+}
+
+/// Returns protocol extensions supported by both the node and the driver, which are enabled
+/// when starting up connections.
+pub(crate) fn supported_extensions(options: &HashMap<String, Vec<String>>) -> Vec<&'static str> {
+    [TABLETS_ROUTING_EXTENSION]
+        .iter()
+        .filter(|extension| options.contains_key(**extension))
+        .cloned()
+        .collect()
+}
+
+/// Opens connections to specific shards of a node.
+#[async_trait]
+pub(crate) trait ShardConnector<T>: Send + Sync {
+    async fn connect_to_shard(
+        &self,
+        addr: SocketAddr,
+        shard_info: &ShardInfo,
+        shard: u32,
+    ) -> error::Result<T>;
+}
+
+enum ShardConnection<T> {
+    Connecting,
+    Connected(T),
+    Failed(Instant),
+}
+
+/// Connections to node shards other than the ones pool connections landed on, opened in the
+/// background when requests for those shards show up.
+pub(crate) struct ShardConnections<T> {
+    connector: Arc<dyn ShardConnector<T>>,
+    connections: Mutex<FnvHashMap<u32, ShardConnection<T>>>,
+}
+
+impl<T> ShardConnections<T> {
+    pub(crate) fn new(connector: Arc<dyn ShardConnector<T>>) -> Self {
+        ShardConnections {
+            connector,
+            connections: Default::default(),
+        }
+    }
+}
+
+impl<T: CDRSTransport + 'static> ShardConnections<T> {
+    /// Returns a connection to given shard if there is a usable one. Otherwise starts
+    /// connecting to the shard in the background.
+    pub(crate) fn connection(
+        self: &Arc<Self>,
+        addr: SocketAddr,
+        shard_info: &ShardInfo,
+        shard: u32,
+    ) -> Option<T> {
+        let mut connections = self.connections.lock().unwrap();
+        match connections.get(&shard) {
+            Some(ShardConnection::Connected(connection)) if !connection.is_broken() => {
+                return if connection.has_capacity() {
+                    Some(connection.clone())
+                } else {
+                    None
+                };
+            }
+            Some(ShardConnection::Connecting) => return None,
+            Some(ShardConnection::Failed(since)) if since.elapsed() < SHARD_RECONNECT_DELAY => {
+                return None
+            }
+            _ => {}
+        }
+
+        // specific shards can be connected to only through the shard-aware port
+        shard_info.shard_aware_port?;
+
+        connections.insert(shard, ShardConnection::Connecting);
+
+        let shards = self.clone();
+        let shard_info = *shard_info;
+        tokio::spawn(async move {
+            let result = shards
+                .connector
+                .connect_to_shard(addr, &shard_info, shard)
+                .await;
+
+            let mut connections = shards.connections.lock().unwrap();
+            match result {
+                Ok(connection) => {
+                    connections.insert(shard, ShardConnection::Connected(connection));
+                }
+                Err(err) => {
+                    debug!("Unable to connect to shard {} of {}: {}", shard, addr, err);
+                    connections.insert(shard, ShardConnection::Failed(Instant::now()));
+                }
+            }
+        });
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sharded_node(nr_shards: u32, msb_ignore: u8) -> ShardInfo {
+        ShardInfo {
+            shard: 0,
+            nr_shards,
+            msb_ignore,
+            shard_aware_port: Some(19042),
+        }
+    }
+
+    #[test]
+    fn parses_supported_options() {
+        let options: HashMap<String, Vec<String>> = vec![
+            (SHARD_OPTION.to_string(), vec!["3".to_string()]),
+            (NR_SHARDS_OPTION.to_string(), vec!["12".to_string()]),
+            (SHARDING_IGNORE_MSB_OPTION.to_string(), vec!["12".to_string()]),
+            (SHARD_AWARE_PORT_OPTION.to_string(), vec!["19042".to_string()]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            ShardInfo::from_supported(&options),
+            Some(ShardInfo {
+                shard: 3,
+                nr_shards: 12,
+                msb_ignore: 12,
+                shard_aware_port: Some(19042),
+            })
+        );
+        assert_eq!(ShardInfo::from_supported(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn computes_shard_of_token() {
+        let shard_info = sharded_node(4, 0);
+        assert_eq!(shard_info.shard_of(i64::MIN), 0);
+        assert_eq!(shard_info.shard_of(-1), 1);
+        assert_eq!(shard_info.shard_of(0), 2);
+        assert_eq!(shard_info.shard_of(i64::MAX), 3);
+
+        let shard_info = sharded_node(12, 12);
+        assert_eq!(shard_info.shard_of(i64::MIN), 0);
+        assert_eq!(shard_info.shard_of(-9219783007514621794), 9);
+    }
+
+    #[test]
+    fn selects_source_ports_of_shard() {
+        let shard_info = sharded_node(12, 12);
+        let ports: Vec<u16> = shard_info.source_ports(5).collect();
+
+        assert!(!ports.is_empty());
+        assert!(ports.iter().all(|port| *port >= SOURCE_PORT_MIN && port % 12 == 5));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use uuid::Uuid;
+
+use crate::error;
+use crate::frame::FromCursor;
+use crate::types::data_serialization_types::{decode_bigint, decode_int};
+use crate::types::{CBytes, CInt};
+
+/// Protocol extension making Scylla nodes send tablets of requests which reached a node not
+/// storing them.
+pub(crate) const TABLETS_ROUTING_EXTENSION: &str = "TABLETS_ROUTING_V1";
+
+/// Custom payload key of tablet routing information.
+pub(crate) const TABLETS_ROUTING_PAYLOAD: &str = "tablets-routing-v1";
+
+/// Replica of a tablet - a shard of a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TabletReplica {
+    pub host_id: Uuid,
+    pub shard: u32,
+}
+
+/// Range of tokens of a table in a tablet-enabled Scylla keyspace, from `first_token`
+/// (exclusive) to `last_token` (inclusive), along with nodes storing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tablet {
+    pub first_token: i64,
+    pub last_token: i64,
+    pub replicas: Vec<TabletReplica>,
+}
+
+impl Tablet {
+    /// Reads a tablet from routing information sent by a node, serialized as
+    /// `tuple<bigint, bigint, list<tuple<uuid, int>>>`.
+    pub fn from_payload(payload: &[u8]) -> error::Result<Self> {
+        let mut cursor = Cursor::new(payload);
+        let first_token = decode_bigint(&element(&mut cursor)?)?;
+        let last_token = decode_bigint(&element(&mut cursor)?)?;
+
+        let replicas_bytes = element(&mut cursor)?;
+        let mut cursor = Cursor::new(replicas_bytes.as_slice());
+        let len = CInt::from_cursor(&mut cursor)?;
+        let mut replicas = Vec::with_capacity(len.max(0) as usize);
+        for _ in 0..len {
+            let replica_bytes = element(&mut cursor)?;
+            let mut cursor = Cursor::new(replica_bytes.as_slice());
+            let host_id = Uuid::from_slice(&element(&mut cursor)?)?;
+            let shard = decode_int(&element(&mut cursor)?)?;
+            replicas.push(TabletReplica {
+                host_id,
+                shard: shard as u32,
+            });
+        }
+
+        Ok(Tablet {
+            first_token,
+            last_token,
+            replicas,
+        })
+    }
+
+    /// Checks if the tablet contains given token.
+    pub fn contains(&self, token: i64) -> bool {
+        token > self.first_token && token <= self.last_token
+    }
+
+    fn overlaps(&self, other: &Tablet) -> bool {
+        self.first_token < other.last_token && other.first_token < self.last_token
+    }
+}
+
+/// Reads a non-null element of a tuple or a list.
+fn element(cursor: &mut Cursor<&[u8]>) -> error::Result<Vec<u8>> {
+    CBytes::from_cursor(cursor)?.into_plain().ok_or_else(|| {
+        error::Error::General("Unexpected null in tablet routing information".into())
+    })
+}
+
+/// Tablets of tables, learned from routing information sent along with responses to requests
+/// which reached nodes not storing their partitions.
+#[derive(Debug, Default)]
+pub struct TabletMap {
+    tables: HashMap<String, HashMap<String, Vec<Tablet>>>,
+}
+
+impl TabletMap {
+    /// Returns the tablet of given table containing given token, if known.
+    pub fn tablet(&self, keyspace: &str, table: &str, token: i64) -> Option<&Tablet> {
+        // tablets are sorted by tokens and don't overlap
+        let tablets = self.tables.get(keyspace)?.get(table)?;
+        let index = tablets.partition_point(|tablet| tablet.last_token < token);
+        tablets.get(index).filter(|tablet| tablet.contains(token))
+    }
+
+    /// Adds a tablet, replacing known ones it overlaps with, e.g. after tablets were split or
+    /// migrated.
+    pub(crate) fn add(&mut self, keyspace: &str, table: &str, tablet: Tablet) {
+        let tablets = self
+            .tables
+            .entry(keyspace.to_string())
+            .or_default()
+            .entry(table.to_string())
+            .or_default();
+
+        tablets.retain(|known| !known.overlaps(&tablet));
+        let index = tablets.partition_point(|known| known.last_token < tablet.last_token);
+        tablets.insert(index, tablet);
+    }
+
+    /// Removes tablets with replicas on nodes which are not known anymore.
+    pub(crate) fn retain_hosts(&mut self, host_ids: &HashSet<Uuid>) {
+        for tables in self.tables.values_mut() {
+            for tablets in tables.values_mut() {
+                tablets.retain(|tablet| {
+                    tablet
+                        .replicas
+                        .iter()
+                        .all(|replica| host_ids.contains(&replica.host_id))
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tablet(first_token: i64, last_token: i64) -> Tablet {
+        Tablet {
+            first_token,
+            last_token,
+            replicas: vec![TabletReplica {
+                host_id: Uuid::from_u128(1),
+                shard: 2,
+            }],
+        }
+    }
+
+    fn bytes(value: &[u8]) -> Vec<u8> {
+        let mut bytes = (value.len() as i32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    #[test]
+    fn parses_routing_payload() {
+        let replica = [
+            bytes(Uuid::from_u128(1).as_bytes()),
+            bytes(&2i32.to_be_bytes()),
+        ]
+        .concat();
+        let replicas = [1i32.to_be_bytes().to_vec(), bytes(&replica)].concat();
+        let payload = [
+            bytes(&(-100i64).to_be_bytes()),
+            bytes(&100i64.to_be_bytes()),
+            bytes(&replicas),
+        ]
+        .concat();
+
+        assert_eq!(Tablet::from_payload(&payload).unwrap(), tablet(-100, 100));
+        assert!(Tablet::from_payload(&payload[..10]).is_err());
+    }
+
+    #[test]
+    fn finds_tablets_of_tokens() {
+        let mut tablets = TabletMap::default();
+        tablets.add("ks", "t", tablet(0, 100));
+        tablets.add("ks", "t", tablet(-100, 0));
+
+        assert_eq!(tablets.tablet("ks", "t", 0), Some(&tablet(-100, 0)));
+        assert_eq!(tablets.tablet("ks", "t", 1), Some(&tablet(0, 100)));
+        assert_eq!(tablets.tablet("ks", "t", 101), None);
+        assert_eq!(tablets.tablet("ks", "other", 1), None);
+
+        // a merged tablet replaces the ones it covers
+        tablets.add("ks", "t", tablet(-50, 50));
+        assert_eq!(tablets.tablet("ks", "t", 1), Some(&tablet(-50, 50)));
+        assert_eq!(tablets.tablet("ks", "t", 60), None);
+
+        tablets.retain_hosts(&HashSet::new());
+        assert_eq!(tablets.tablet("ks", "t", 1), None);
+    }
+}
@@ -1,18 +1,19 @@
 use async_trait::async_trait;
 use bb8::{Builder, ManageConnection, PooledConnection};
 use std::io;
-use std::net::ToSocketAddrs;
-use tokio::io::AsyncWriteExt;
-use tokio::sync::Mutex;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
 
 use crate::authenticators::Authenticator;
-use crate::cluster::ConnectionPool;
+use crate::cluster::{
+    fetch_supported, supported_extensions, ConnectionPool, ShardConnector, ShardInfo,
+};
 use crate::cluster::NodeTcpConfig;
 use crate::compression::Compression;
 use crate::error;
-use crate::frame::parser::parse_frame;
-use crate::frame::{Frame, IntoBytes, Opcode};
-use crate::transport::{CDRSTransport, TransportTcp};
+use crate::frame::{Frame, Opcode};
+use crate::transport::{CDRSTransport, TransportConfig, TransportTcp};
 
 /// Shortcut for `bb8::Pool` type of TCP-based CDRS connections.
 pub type TcpConnectionPool<A> = ConnectionPool<TcpConnectionsManager<A>>;
@@ -22,80 +23,171 @@ pub type TcpConnectionPool<A> = ConnectionPool<TcpConnectionsManager<A>>;
 /// Used internally for TCP Session for holding connections to a specific Cassandra node.
 pub async fn new_tcp_pool<'a, A: Authenticator + Send + Sync + 'static>(
     node_config: NodeTcpConfig<'a, A>,
+    compression: Compression,
 ) -> error::Result<TcpConnectionPool<A>> {
-    let manager =
-        TcpConnectionsManager::new(node_config.addr.to_string(), node_config.authenticator);
-
-    let pool = Builder::new()
-        .max_size(node_config.max_size)
-        .min_idle(node_config.min_idle)
-        .max_lifetime(node_config.max_lifetime)
-        .idle_timeout(node_config.idle_timeout)
-        .connection_timeout(node_config.connection_timeout)
-        .build(manager)
-        .await
-        .map_err(|err| error::Error::from(err.to_string()))?;
+    let pool = new_lazy_tcp_pool(node_config, compression)?;
+    pool.connection().await?;
+    Ok(pool)
+}
 
+/// Creates a pool which establishes connections in the background, without waiting for the
+/// node to be reachable.
+pub(crate) fn new_lazy_tcp_pool<'a, A: Authenticator + Send + Sync + 'static>(
+    node_config: NodeTcpConfig<'a, A>,
+    compression: Compression,
+) -> error::Result<TcpConnectionPool<A>> {
     let addr = node_config
         .addr
         .to_socket_addrs()?
         .next()
         .ok_or_else(|| error::Error::from("Cannot parse address"))?;
 
-    Ok(TcpConnectionPool::new(pool, addr))
+    let manager = TcpConnectionsManager::new(
+        node_config.addr.to_string(),
+        node_config.authenticator,
+        compression,
+        node_config.transport_config,
+    );
+
+    let (warm_up_size, warm_up_statements) = if node_config.warm_up {
+        (
+            Some(node_config.min_idle.unwrap_or(node_config.max_size)),
+            node_config.warm_up_statements,
+        )
+    } else {
+        (None, vec![])
+    };
+
+    let orphaned_requests = manager.orphaned_requests.clone();
+    let shard_connector = Arc::new(manager.clone());
+    let pool = Builder::new()
+        .max_size(node_config.max_size)
+        .min_idle(node_config.min_idle)
+        .max_lifetime(node_config.max_lifetime)
+        .idle_timeout(node_config.idle_timeout)
+        .connection_timeout(node_config.connection_timeout)
+        .test_on_check_out(node_config.test_on_check_out)
+        .build_unchecked(manager);
+
+    Ok(TcpConnectionPool::new(
+        pool,
+        addr,
+        node_config.reconnection_policy,
+        node_config.max_size,
+        warm_up_size,
+        orphaned_requests,
+    )
+    .with_warm_up_statements(warm_up_statements)
+    .with_shard_connector(shard_connector))
 }
 
 /// `bb8` connection manager.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TcpConnectionsManager<A> {
     addr: String,
     auth: A,
+    compression: Compression,
+    transport_config: TransportConfig,
+    orphaned_requests: Arc<AtomicUsize>,
 }
 
 impl<A> TcpConnectionsManager<A> {
-    pub fn new<S: ToString>(addr: S, auth: A) -> Self {
+    pub fn new<S: ToString>(
+        addr: S,
+        auth: A,
+        compression: Compression,
+        transport_config: TransportConfig,
+    ) -> Self {
         TcpConnectionsManager {
             addr: addr.to_string(),
             auth,
+            compression,
+            transport_config,
+            orphaned_requests: Default::default(),
         }
     }
 }
 
 #[async_trait]
 impl<A: Authenticator + 'static + Send + Sync> ManageConnection for TcpConnectionsManager<A> {
-    type Connection = Mutex<TransportTcp>;
+    type Connection = TransportTcp;
     type Error = error::Error;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        let transport = Mutex::new(TransportTcp::new(&self.addr).await?);
-        startup(&transport, &self.auth).await?;
+        let transport = TransportTcp::connect(
+            &self.addr,
+            self.compression,
+            None,
+            &self.transport_config,
+            self.orphaned_requests.clone(),
+        )
+        .await?;
+        let supported = fetch_supported(&transport).await?;
+        let transport = transport.with_shard_info(ShardInfo::from_supported(&supported));
+        startup_with_extensions(&transport, &self.auth, &supported_extensions(&supported))
+            .await?;
 
         Ok(transport)
     }
 
     async fn is_valid(&self, conn: &mut PooledConnection<'_, Self>) -> Result<(), Self::Error> {
-        let options_frame = Frame::new_req_options().into_cbytes();
-        conn.lock().await.write(options_frame.as_slice()).await?;
+        let options_frame = Frame::new_req_options();
+        conn.write_frame(&options_frame).await.map(|_| ())
+    }
 
-        parse_frame(&conn, &Compression::None {}).await.map(|_| ())
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
     }
+}
+
+#[async_trait]
+impl<A: Authenticator + 'static + Send + Sync> ShardConnector<TransportTcp>
+    for TcpConnectionsManager<A>
+{
+    async fn connect_to_shard(
+        &self,
+        addr: SocketAddr,
+        shard_info: &ShardInfo,
+        shard: u32,
+    ) -> error::Result<TransportTcp> {
+        let transport = TransportTcp::connect_to_shard(
+            addr,
+            shard_info,
+            shard,
+            self.compression,
+            &self.transport_config,
+            self.orphaned_requests.clone(),
+        )
+        .await?;
+        let supported = fetch_supported(&transport).await?;
+        let transport = transport.with_shard_info(ShardInfo::from_supported(&supported));
+        startup_with_extensions(&transport, &self.auth, &supported_extensions(&supported))
+            .await?;
 
-    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
-        // cannot synchronously determine broken connection, so return false as per bb8 docs
-        false
+        Ok(transport)
     }
 }
 
 pub async fn startup<'b, T: CDRSTransport + Unpin + 'static, A: Authenticator + 'static + Sized>(
-    transport: &Mutex<T>,
+    transport: &T,
     session_authenticator: &'b A,
 ) -> error::Result<()> {
-    let ref mut compression = Compression::None;
-    let startup_frame = Frame::new_req_startup(compression.as_str()).into_cbytes();
+    startup_with_extensions(transport, session_authenticator, &[]).await
+}
 
-    transport.lock().await.write(startup_frame.as_slice()).await?;
+/// Starts up a connection, enabling given protocol extensions.
+pub(crate) async fn startup_with_extensions<
+    T: CDRSTransport + Unpin + 'static,
+    A: Authenticator + 'static + Sized,
+>(
+    transport: &T,
+    session_authenticator: &A,
+    extensions: &[&'static str],
+) -> error::Result<()> {
+    let ref mut compression = Compression::None;
+    let startup_frame = Frame::new_req_startup_with_extensions(compression.as_str(), extensions);
 
-    let start_response = parse_frame(transport, compression).await?;
+    let start_response = transport.write_frame(&startup_frame).await?;
 
     if start_response.opcode == Opcode::Ready {
         return Ok(());
@@ -140,12 +232,9 @@ pub async fn startup<'b, T: CDRSTransport + Unpin + 'static, A: Authenticator +
         }
 
         let auth_token_bytes = session_authenticator.get_auth_token();
-        transport.lock().await.write(
-            Frame::new_req_auth_response(auth_token_bytes)
-                .into_cbytes()
-                .as_slice(),
-        ).await?;
-        parse_frame(transport, compression).await?;
+        transport
+            .write_frame(&Frame::new_req_auth_response(auth_token_bytes))
+            .await?;
 
         return Ok(());
     }
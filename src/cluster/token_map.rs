@@ -0,0 +1,592 @@
+use fnv::FnvHashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::cluster::{Node, Partitioner, Token};
+use crate::types::blob::Blob;
+use crate::types::value::Value;
+
+/// How a keyspace places replicas of its partitions on the token ring.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplicationStrategy {
+    /// Replicas are placed on consecutive nodes of the ring.
+    SimpleStrategy { replication_factor: usize },
+    /// Replicas are placed on consecutive nodes of each data center, in distinct racks where
+    /// possible.
+    NetworkTopologyStrategy {
+        datacenter_replication_factor: HashMap<String, usize>,
+    },
+    /// Strategy which is not used for routing, e.g. `LocalStrategy`.
+    Other,
+}
+
+impl ReplicationStrategy {
+    /// Reads the strategy from `replication` column of `system_schema.keyspaces`.
+    pub fn from_replication(replication: &HashMap<String, String>) -> Self {
+        // transient replication is declared as "<all replicas>/<transient replicas>"
+        fn factor(value: &str) -> Option<usize> {
+            value.split('/').next()?.trim().parse().ok()
+        }
+
+        let class = replication.get("class").map(String::as_str).unwrap_or("");
+        if class.ends_with("SimpleStrategy") {
+            match replication.get("replication_factor").and_then(|value| factor(value)) {
+                Some(replication_factor) => {
+                    ReplicationStrategy::SimpleStrategy { replication_factor }
+                }
+                None => ReplicationStrategy::Other,
+            }
+        } else if class.ends_with("NetworkTopologyStrategy") {
+            ReplicationStrategy::NetworkTopologyStrategy {
+                datacenter_replication_factor: replication
+                    .iter()
+                    .filter(|(key, _)| key.as_str() != "class")
+                    .filter_map(|(datacenter, value)| Some((datacenter.clone(), factor(value)?)))
+                    .collect(),
+            }
+        } else {
+            ReplicationStrategy::Other
+        }
+    }
+}
+
+/// Range of tokens on the ring, from `start` (exclusive) to `end` (inclusive). Missing bounds
+/// stand for the start and the end of the ring, so ranges wrapping around the ring are split in
+/// two. Rows of a table can be scanned in parallel by querying each range separately.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TokenRange {
+    pub start: Option<Token>,
+    pub end: Option<Token>,
+}
+
+impl TokenRange {
+    /// Returns a CQL condition selecting rows of the range by their partition key columns,
+    /// e.g. `token(id) > ? AND token(id) <= ?`, with bind markers for values returned by
+    /// `values()`. Returns an empty string for a range covering the whole ring.
+    pub fn condition(&self, partition_key: &[&str]) -> String {
+        let token = format!("token({})", partition_key.join(", "));
+        let mut conditions = vec![];
+        if self.start.is_some() {
+            conditions.push(format!("{} > ?", token));
+        }
+        if self.end.is_some() {
+            conditions.push(format!("{} <= ?", token));
+        }
+
+        conditions.join(" AND ")
+    }
+
+    /// Returns values of range bounds, in the order of bind markers in `condition()`.
+    pub fn values(&self) -> Vec<Value> {
+        self.start
+            .iter()
+            .chain(self.end.iter())
+            .map(token_value)
+            .collect()
+    }
+}
+
+/// Serializes a token the way the `token()` function returns it for its partitioner.
+fn token_value(token: &Token) -> Value {
+    match *token {
+        Token::Murmur3(token) => Value::from(token),
+        Token::Random(token) => {
+            // varint - the shortest big-endian two's complement representation
+            let mut bytes = vec![0];
+            bytes.extend_from_slice(&token.to_be_bytes());
+            let start = bytes
+                .windows(2)
+                .position(|pair| pair[0] != 0 || pair[1] & 0x80 != 0)
+                .unwrap_or(bytes.len() - 1);
+            Value::from(Blob::from(bytes[start..].to_vec()))
+        }
+        Token::ByteOrdered(ref token) => Value::from(Blob::from(token.clone())),
+    }
+}
+
+/// Token ring of the cluster along with replication settings of keyspaces, used for finding
+/// nodes which store given partition.
+#[derive(Debug, Default)]
+pub struct TokenMap {
+    ring: Vec<(Token, Arc<Node>)>,
+    keyspaces: HashMap<String, ReplicationStrategy>,
+}
+
+impl TokenMap {
+    /// Builds the ring from tokens owned by given nodes. Tokens which can't be parsed by given
+    /// partitioner are skipped.
+    pub fn new(
+        nodes: &[Arc<Node>],
+        keyspaces: HashMap<String, ReplicationStrategy>,
+        partitioner: &dyn Partitioner,
+    ) -> Self {
+        let mut ring: Vec<(Token, Arc<Node>)> = nodes
+            .iter()
+            .flat_map(|node| {
+                node.tokens
+                    .iter()
+                    .filter_map(|token| partitioner.parse_token(token))
+                    .map(move |token| (token, node.clone()))
+            })
+            .collect();
+        ring.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        TokenMap { ring, keyspaces }
+    }
+
+    /// Returns replication strategy of given keyspace, if known.
+    pub fn replication_strategy(&self, keyspace: &str) -> Option<&ReplicationStrategy> {
+        self.keyspaces.get(keyspace)
+    }
+
+    /// Returns nodes storing the partition with given token in given keyspace, primary replica
+    /// first. Returns no nodes for unknown keyspaces or ones which don't place replicas on the
+    /// ring.
+    pub fn replicas(&self, keyspace: &str, token: &Token) -> Vec<Arc<Node>> {
+        if self.ring.is_empty() {
+            return vec![];
+        }
+
+        match self.keyspaces.get(keyspace) {
+            Some(ReplicationStrategy::SimpleStrategy { replication_factor }) => {
+                self.simple_replicas(token, *replication_factor)
+            }
+            Some(ReplicationStrategy::NetworkTopologyStrategy {
+                datacenter_replication_factor,
+            }) => self.network_topology_replicas(token, datacenter_replication_factor),
+            Some(ReplicationStrategy::Other) | None => vec![],
+        }
+    }
+
+    /// Returns ranges of tokens between consecutive tokens of the ring, each owned by the node
+    /// with its end token. Together, they cover the whole ring.
+    pub fn token_ranges(&self) -> Vec<TokenRange> {
+        let (first, last) = match (self.ring.first(), self.ring.last()) {
+            (Some((first, _)), Some((last, _))) => (first, last),
+            _ => return vec![],
+        };
+
+        let mut ranges = vec![TokenRange {
+            start: None,
+            end: Some(first.clone()),
+        }];
+        ranges.extend(self.ring.windows(2).map(|pair| TokenRange {
+            start: Some(pair[0].0.clone()),
+            end: Some(pair[1].0.clone()),
+        }));
+        ranges.push(TokenRange {
+            start: Some(last.clone()),
+            end: None,
+        });
+
+        ranges
+    }
+
+    /// Returns nodes storing partitions of given token range in given keyspace, primary replica
+    /// first.
+    pub fn range_replicas(&self, keyspace: &str, range: &TokenRange) -> Vec<Arc<Node>> {
+        // tokens past the last one belong to the owner of the first one
+        match range
+            .end
+            .as_ref()
+            .or_else(|| self.ring.first().map(|(token, _)| token))
+        {
+            Some(token) => self.replicas(keyspace, token),
+            None => vec![],
+        }
+    }
+
+    /// Returns the number of ring tokens owned by each node.
+    pub fn token_counts(&self) -> HashMap<SocketAddr, usize> {
+        let mut counts = HashMap::new();
+        for (_, node) in &self.ring {
+            *counts.entry(node.addr).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// Returns the fraction of the ring for which each node is the primary replica, i.e. the
+    /// summed sizes of ranges ending with its tokens. Returns nothing for `ByteOrdered` tokens,
+    /// whose ranges have no size.
+    pub fn ownership(&self) -> HashMap<SocketAddr, f64> {
+        let mut ownership = HashMap::new();
+        for (index, (token, node)) in self.ring.iter().enumerate() {
+            let previous = &self.ring[(index + self.ring.len() - 1) % self.ring.len()].0;
+            let share = match (previous, token) {
+                (Token::Murmur3(previous), Token::Murmur3(token)) => {
+                    range_share(*previous as u128, *token as u128, 1 << 64)
+                }
+                (Token::Random(previous), Token::Random(token)) => {
+                    range_share(*previous, *token, 1 << 127)
+                }
+                _ => return HashMap::new(),
+            };
+            *ownership.entry(node.addr).or_insert(0.0) += share;
+        }
+
+        ownership
+    }
+
+    /// Iterates over ring nodes starting with the owner of given token, i.e. the first node
+    /// whose token is not less than it, wrapping around.
+    fn walk(&self, token: &Token) -> impl Iterator<Item = &Arc<Node>> {
+        let start = self
+            .ring
+            .partition_point(|(node_token, _)| node_token < token);
+        self.ring[start..]
+            .iter()
+            .chain(self.ring[..start].iter())
+            .map(|(_, node)| node)
+    }
+
+    fn simple_replicas(&self, token: &Token, replication_factor: usize) -> Vec<Arc<Node>> {
+        let mut replicas: Vec<Arc<Node>> = Vec::with_capacity(replication_factor);
+        for node in self.walk(token) {
+            if replicas.len() == replication_factor {
+                break;
+            }
+
+            if !replicas.iter().any(|replica| replica.addr == node.addr) {
+                replicas.push(node.clone());
+            }
+        }
+
+        replicas
+    }
+
+    fn network_topology_replicas(
+        &self,
+        token: &Token,
+        datacenter_replication_factor: &HashMap<String, usize>,
+    ) -> Vec<Arc<Node>> {
+        struct DatacenterReplicas<'a> {
+            remaining: usize,
+            racks: HashSet<Option<&'a str>>,
+            seen_racks: HashSet<Option<&'a str>>,
+            // nodes in already used racks, taken once all racks have a replica
+            skipped: Vec<&'a Arc<Node>>,
+        }
+
+        let mut datacenters: FnvHashMap<&str, DatacenterReplicas> = FnvHashMap::default();
+        for (_, node) in &self.ring {
+            let datacenter = match node.datacenter.as_deref() {
+                Some(datacenter) => datacenter,
+                None => continue,
+            };
+
+            let replication_factor = match datacenter_replication_factor.get(datacenter) {
+                Some(replication_factor) if *replication_factor > 0 => *replication_factor,
+                _ => continue,
+            };
+
+            datacenters
+                .entry(datacenter)
+                .or_insert_with(|| DatacenterReplicas {
+                    remaining: replication_factor,
+                    racks: HashSet::new(),
+                    seen_racks: HashSet::new(),
+                    skipped: vec![],
+                })
+                .racks
+                .insert(node.rack.as_deref());
+        }
+
+        // a data center can't have more replicas than nodes
+        for (datacenter, replicas) in datacenters.iter_mut() {
+            let nodes: HashSet<SocketAddr> = self
+                .ring
+                .iter()
+                .filter(|(_, node)| node.datacenter.as_deref() == Some(*datacenter))
+                .map(|(_, node)| node.addr)
+                .collect();
+            replicas.remaining = replicas.remaining.min(nodes.len());
+        }
+
+        let mut replicas: Vec<Arc<Node>> = vec![];
+        let mut added: HashSet<SocketAddr> = HashSet::new();
+
+        for node in self.walk(token) {
+            if datacenters.values().all(|datacenter| datacenter.remaining == 0) {
+                break;
+            }
+
+            let datacenter = match node
+                .datacenter
+                .as_deref()
+                .and_then(|datacenter| datacenters.get_mut(datacenter))
+            {
+                Some(datacenter) => datacenter,
+                None => continue,
+            };
+
+            if datacenter.remaining == 0 || added.contains(&node.addr) {
+                continue;
+            }
+
+            let rack = node.rack.as_deref();
+            if datacenter.seen_racks.len() == datacenter.racks.len() {
+                added.insert(node.addr);
+                replicas.push(node.clone());
+                datacenter.remaining -= 1;
+            } else if datacenter.seen_racks.insert(rack) {
+                added.insert(node.addr);
+                replicas.push(node.clone());
+                datacenter.remaining -= 1;
+
+                // all racks have a replica now, so skipped nodes can be used in ring order
+                if datacenter.seen_racks.len() == datacenter.racks.len() {
+                    for skipped in datacenter.skipped.drain(..) {
+                        if datacenter.remaining == 0 {
+                            break;
+                        }
+
+                        if added.insert(skipped.addr) {
+                            replicas.push(skipped.clone());
+                            datacenter.remaining -= 1;
+                        }
+                    }
+                }
+            } else {
+                datacenter.skipped.push(node);
+            }
+        }
+
+        replicas
+    }
+}
+
+/// Returns the fraction of a ring of given size covered by range `(start, end]`. Tokens are
+/// compared modulo the ring size, so signed ones can be passed as wrapped unsigned values.
+fn range_share(start: u128, end: u128, ring_size: u128) -> f64 {
+    let size = end.wrapping_sub(start) % ring_size;
+
+    // a single token owns the whole ring
+    let size = if size == 0 { ring_size } else { size };
+    size as f64 / ring_size as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::Murmur3Partitioner;
+
+    fn node(addr: &str, datacenter: &str, rack: &str, tokens: &[i64]) -> Arc<Node> {
+        Arc::new(Node {
+            addr: addr.parse().unwrap(),
+            datacenter: Some(datacenter.into()),
+            rack: Some(rack.into()),
+            host_id: None,
+            tokens: tokens.iter().map(|token| token.to_string()).collect(),
+            schema_version: None,
+            release_version: None,
+        })
+    }
+
+    fn addrs(replicas: Vec<Arc<Node>>) -> Vec<String> {
+        replicas
+            .iter()
+            .map(|replica| replica.addr.ip().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parses_replication() {
+        let simple: HashMap<String, String> = vec![
+            ("class".to_string(), "org.apache.cassandra.locator.SimpleStrategy".to_string()),
+            ("replication_factor".to_string(), "3".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            ReplicationStrategy::from_replication(&simple),
+            ReplicationStrategy::SimpleStrategy {
+                replication_factor: 3
+            }
+        );
+
+        let network_topology: HashMap<String, String> = vec![
+            ("class".to_string(), "NetworkTopologyStrategy".to_string()),
+            ("dc1".to_string(), "3".to_string()),
+            ("dc2".to_string(), "2/1".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            ReplicationStrategy::from_replication(&network_topology),
+            ReplicationStrategy::NetworkTopologyStrategy {
+                datacenter_replication_factor: vec![("dc1".to_string(), 3), ("dc2".to_string(), 2)]
+                    .into_iter()
+                    .collect()
+            }
+        );
+
+        let local: HashMap<String, String> =
+            vec![("class".to_string(), "org.apache.cassandra.locator.LocalStrategy".to_string())]
+                .into_iter()
+                .collect();
+        assert_eq!(
+            ReplicationStrategy::from_replication(&local),
+            ReplicationStrategy::Other
+        );
+    }
+
+    #[test]
+    fn finds_simple_strategy_replicas() {
+        let nodes = vec![
+            node("127.0.0.1:9042", "dc1", "r1", &[-100, 100]),
+            node("127.0.0.2:9042", "dc1", "r1", &[-50, 150]),
+            node("127.0.0.3:9042", "dc1", "r1", &[0]),
+        ];
+        let keyspaces = vec![(
+            "ks".to_string(),
+            ReplicationStrategy::SimpleStrategy {
+                replication_factor: 2,
+            },
+        )]
+        .into_iter()
+        .collect();
+        let token_map = TokenMap::new(&nodes, keyspaces, &Murmur3Partitioner);
+
+        assert_eq!(
+            addrs(token_map.replicas("ks", &Token::Murmur3(-100))),
+            vec!["127.0.0.1", "127.0.0.2"]
+        );
+        assert_eq!(
+            addrs(token_map.replicas("ks", &Token::Murmur3(-10))),
+            vec!["127.0.0.3", "127.0.0.1"]
+        );
+        // tokens past the last one wrap around to the ring start
+        assert_eq!(
+            addrs(token_map.replicas("ks", &Token::Murmur3(200))),
+            vec!["127.0.0.1", "127.0.0.2"]
+        );
+        assert!(token_map.replicas("unknown", &Token::Murmur3(0)).is_empty());
+    }
+
+    #[test]
+    fn finds_network_topology_strategy_replicas() {
+        let nodes = vec![
+            node("127.0.0.1:9042", "dc1", "r1", &[0]),
+            node("127.0.0.2:9042", "dc1", "r1", &[10]),
+            node("127.0.0.3:9042", "dc2", "r1", &[20]),
+            node("127.0.0.4:9042", "dc1", "r2", &[30]),
+            node("127.0.0.5:9042", "dc2", "r1", &[40]),
+        ];
+        let keyspaces = vec![(
+            "ks".to_string(),
+            ReplicationStrategy::NetworkTopologyStrategy {
+                datacenter_replication_factor: vec![
+                    ("dc1".to_string(), 2),
+                    ("dc2".to_string(), 5),
+                ]
+                .into_iter()
+                .collect(),
+            },
+        )]
+        .into_iter()
+        .collect();
+        let token_map = TokenMap::new(&nodes, keyspaces, &Murmur3Partitioner);
+
+        // the second dc1 replica goes to another rack, and dc2 has only two nodes
+        assert_eq!(
+            addrs(token_map.replicas("ks", &Token::Murmur3(0))),
+            vec!["127.0.0.1", "127.0.0.3", "127.0.0.4", "127.0.0.5"]
+        );
+    }
+
+    #[test]
+    fn enumerates_token_ranges() {
+        let nodes = vec![
+            node("127.0.0.1:9042", "dc1", "r1", &[-100, 100]),
+            node("127.0.0.2:9042", "dc1", "r1", &[0]),
+        ];
+        let keyspaces = vec![(
+            "ks".to_string(),
+            ReplicationStrategy::SimpleStrategy {
+                replication_factor: 1,
+            },
+        )]
+        .into_iter()
+        .collect();
+        let token_map = TokenMap::new(&nodes, keyspaces, &Murmur3Partitioner);
+
+        let range = |start: Option<i64>, end: Option<i64>| TokenRange {
+            start: start.map(Token::Murmur3),
+            end: end.map(Token::Murmur3),
+        };
+        let ranges = token_map.token_ranges();
+        assert_eq!(
+            ranges,
+            vec![
+                range(None, Some(-100)),
+                range(Some(-100), Some(0)),
+                range(Some(0), Some(100)),
+                range(Some(100), None),
+            ]
+        );
+
+        let replicas: Vec<Vec<String>> = ranges
+            .iter()
+            .map(|range| addrs(token_map.range_replicas("ks", range)))
+            .collect();
+        assert_eq!(
+            replicas,
+            vec![
+                vec!["127.0.0.1"],
+                vec!["127.0.0.2"],
+                vec!["127.0.0.1"],
+                vec!["127.0.0.1"],
+            ]
+        );
+        assert!(TokenMap::default().token_ranges().is_empty());
+    }
+
+    #[test]
+    fn computes_ownership() {
+        let nodes = vec![
+            node("127.0.0.1:9042", "dc1", "r1", &[i64::MIN, 0]),
+            node("127.0.0.2:9042", "dc1", "r1", &[i64::MIN / 2]),
+        ];
+        let token_map = TokenMap::new(&nodes, HashMap::new(), &Murmur3Partitioner);
+
+        let ownership = token_map.ownership();
+        assert_eq!(ownership[&nodes[0].addr], 0.75);
+        assert_eq!(ownership[&nodes[1].addr], 0.25);
+        assert_eq!(token_map.token_counts()[&nodes[0].addr], 2);
+
+        let token_map = TokenMap::new(&nodes[1..], HashMap::new(), &Murmur3Partitioner);
+        assert_eq!(token_map.ownership()[&nodes[1].addr], 1.0);
+    }
+
+    #[test]
+    fn builds_range_conditions() {
+        let range = TokenRange {
+            start: Some(Token::Murmur3(-100)),
+            end: Some(Token::Murmur3(100)),
+        };
+        assert_eq!(
+            range.condition(&["a", "b"]),
+            "token(a, b) > ? AND token(a, b) <= ?"
+        );
+        assert_eq!(range.values().len(), 2);
+        assert_eq!(range.values()[0].body, (-100i64).to_be_bytes().to_vec());
+
+        let range = TokenRange {
+            start: None,
+            end: Some(Token::Random(0x80)),
+        };
+        assert_eq!(range.condition(&["id"]), "token(id) <= ?");
+        assert_eq!(range.values()[0].body, vec![0, 0x80]);
+
+        let range = TokenRange {
+            start: Some(Token::Random(1 << 127)),
+            end: None,
+        };
+        assert_eq!(range.condition(&["id"]), "token(id) > ?");
+        let mut max = vec![0, 0x80];
+        max.extend_from_slice(&[0; 15]);
+        assert_eq!(range.values()[0].body, max);
+    }
+}
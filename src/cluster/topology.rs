@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::cluster::{
+    routing_key, AddressTranslator, ClusterMetadata, Murmur3Partitioner, Partitioner, Tablet,
+    TabletMap, TokenMap,
+};
+use crate::consistency::Consistency;
+use crate::error;
+use crate::frame::events::ServerEvent;
+use crate::frame::Frame;
+use crate::transport::CDRSTransport;
+use crate::types::list::List;
+use crate::types::rows::Row;
+use crate::types::value::Value;
+use crate::types::{AsRustType, ByName};
+
+const LOCAL_QUERY: &str = "SELECT data_center, rack, host_id, tokens, schema_version, \
+                           release_version FROM system.local WHERE key = 'local'";
+const PEERS_QUERY: &str = "SELECT peer, rpc_address, data_center, rack, host_id, tokens, \
+                           schema_version, release_version FROM system.peers";
+
+/// Number of server events kept for subscribers which fall behind.
+const EVENT_BUFFER_SIZE: usize = 256;
+
+/// Node discovered in the cluster.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// Address the driver connects to, after address translation.
+    pub addr: SocketAddr,
+    pub datacenter: Option<String>,
+    pub rack: Option<String>,
+    pub host_id: Option<Uuid>,
+    /// Tokens owned by the node, as returned by the node.
+    pub tokens: Vec<String>,
+    pub schema_version: Option<Uuid>,
+    pub release_version: Option<String>,
+}
+
+impl Node {
+    fn from_row(row: &Row, addr: SocketAddr) -> error::Result<Self> {
+        let tokens: Option<List> = row.by_name("tokens")?;
+
+        Ok(Node {
+            addr,
+            datacenter: row.by_name("data_center")?,
+            rack: row.by_name("rack")?,
+            host_id: row.by_name("host_id")?,
+            tokens: match tokens {
+                Some(tokens) => tokens.as_r_type()?,
+                None => vec![],
+            },
+            schema_version: row.by_name("schema_version")?,
+            release_version: row.by_name("release_version")?,
+        })
+    }
+}
+
+/// Cluster nodes known to a session. Nodes are discovered through a control connection and
+/// kept current as the cluster changes.
+pub struct Cluster {
+    nodes: RwLock<Vec<Arc<Node>>>,
+    token_map: RwLock<Arc<TokenMap>>,
+    metadata: RwLock<Arc<ClusterMetadata>>,
+    partitioner: RwLock<Arc<dyn Partitioner>>,
+    tablets: RwLock<TabletMap>,
+    events: broadcast::Sender<ServerEvent>,
+}
+
+impl fmt::Debug for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cluster")
+            .field("nodes", &self.nodes)
+            .finish()
+    }
+}
+
+impl Default for Cluster {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+        Cluster {
+            nodes: Default::default(),
+            token_map: Default::default(),
+            metadata: Default::default(),
+            partitioner: RwLock::new(Arc::new(Murmur3Partitioner)),
+            tablets: Default::default(),
+            events,
+        }
+    }
+}
+
+impl Cluster {
+    /// Returns currently known nodes.
+    pub fn nodes(&self) -> Vec<Arc<Node>> {
+        self.nodes.read().unwrap().clone()
+    }
+
+    /// Returns current token ring, used for routing requests to replicas.
+    pub fn token_map(&self) -> Arc<TokenMap> {
+        self.token_map.read().unwrap().clone()
+    }
+
+    /// Returns current schema of the cluster, refreshed along with nodes and on schema changes.
+    pub fn metadata(&self) -> Arc<ClusterMetadata> {
+        self.metadata.read().unwrap().clone()
+    }
+
+    /// Returns partitioner used by the cluster. Murmur3 is assumed until the partitioner is
+    /// read from `system.local`.
+    pub fn partitioner(&self) -> Arc<dyn Partitioner> {
+        self.partitioner.read().unwrap().clone()
+    }
+
+    /// Returns nodes storing the partition with given partition key in given keyspace, primary
+    /// replica first. Values of composite partition keys are given in partition key order.
+    /// Returns no nodes for keyspaces which are unknown or don't place replicas on the ring.
+    pub fn replicas_for(&self, keyspace: &str, partition_key: &[Value]) -> Vec<Arc<Node>> {
+        let components: Vec<&[u8]> = partition_key
+            .iter()
+            .map(|value| value.body.as_slice())
+            .collect();
+        let token = self.partitioner().hash(&routing_key(&components));
+
+        self.token_map().replicas(keyspace, &token)
+    }
+
+    /// Returns the tablet of given table containing given token, if known. Tablets of
+    /// tablet-enabled Scylla keyspaces are learned from responses to requests sent to nodes
+    /// not storing them.
+    pub fn tablet(&self, keyspace: &str, table: &str, token: i64) -> Option<Tablet> {
+        self.tablets
+            .read()
+            .unwrap()
+            .tablet(keyspace, table, token)
+            .cloned()
+    }
+
+    /// Subscribes to topology, status and schema change events received by the control
+    /// connection, with node addresses translated. Subscribers which don't keep up miss the oldest events and get notified
+    /// about it with `RecvError::Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    pub(crate) fn set_nodes(&self, nodes: Vec<Arc<Node>>) {
+        // tablets of removed nodes would route requests nowhere
+        let host_ids: HashSet<Uuid> = nodes.iter().filter_map(|node| node.host_id).collect();
+        self.tablets.write().unwrap().retain_hosts(&host_ids);
+
+        *self.nodes.write().unwrap() = nodes;
+    }
+
+    pub(crate) fn add_tablet(&self, keyspace: &str, table: &str, tablet: Tablet) {
+        self.tablets.write().unwrap().add(keyspace, table, tablet);
+    }
+
+    pub(crate) fn set_token_map(&self, token_map: TokenMap) {
+        *self.token_map.write().unwrap() = Arc::new(token_map);
+    }
+
+    pub(crate) fn set_metadata(&self, metadata: ClusterMetadata) {
+        *self.metadata.write().unwrap() = Arc::new(metadata);
+    }
+
+    pub(crate) fn set_partitioner(&self, partitioner: Arc<dyn Partitioner>) {
+        *self.partitioner.write().unwrap() = partitioner;
+    }
+
+    pub(crate) fn publish(&self, event: ServerEvent) {
+        // no subscribers is not an error
+        let _ = self.events.send(event);
+    }
+}
+
+/// Runs a query which is not part of user workload, e.g. one for system tables, and returns
+/// its rows.
+pub(crate) async fn query_rows<T: CDRSTransport>(
+    transport: &T,
+    query: &str,
+) -> error::Result<Vec<Row>> {
+    let query = Frame::new_req_query(
+        query.into(),
+        Consistency::One,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        vec![],
+    );
+
+    transport
+        .write_frame(&query)
+        .await?
+        .get_body()?
+        .into_rows()
+        .ok_or_else(|| error::Error::General("Unexpected response to system query".into()))
+}
+
+/// Queries `system.local` and `system.peers` over given connection and returns all nodes of
+/// the cluster. Peers are assumed to listen on the same port as the node the connection is
+/// open to, and their addresses are passed through `translator`.
+pub(crate) async fn fetch_nodes<T: CDRSTransport>(
+    transport: &T,
+    translator: &dyn AddressTranslator,
+) -> error::Result<Vec<Node>> {
+    let mut nodes = vec![];
+    for row in query_rows(transport, LOCAL_QUERY).await? {
+        nodes.push(Node::from_row(&row, transport.addr())?);
+    }
+
+    let port = transport.addr().port();
+    for row in query_rows(transport, PEERS_QUERY).await? {
+        let addr = translator.translate(SocketAddr::new(peer_ip(&row)?, port));
+        nodes.push(Node::from_row(&row, addr)?);
+    }
+
+    Ok(nodes)
+}
+
+pub(crate) fn peer_ip(row: &Row) -> error::Result<IpAddr> {
+    // nodes listening on all interfaces advertise unspecified address, so the peer address is
+    // used instead
+    match row.by_name::<IpAddr>("rpc_address")? {
+        Some(rpc_address) if !rpc_address.is_unspecified() => Ok(rpc_address),
+        _ => row.r_by_name("peer"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::ReplicationStrategy;
+    use crate::frame::events::{StatusChange, StatusChangeType};
+    use crate::types::CInet;
+
+    #[test]
+    fn finds_replicas_of_partition_keys() {
+        let node = |addr: &str, token: i64| {
+            Arc::new(Node {
+                addr: addr.parse().unwrap(),
+                datacenter: None,
+                rack: None,
+                host_id: None,
+                tokens: vec![token.to_string()],
+                schema_version: None,
+                release_version: None,
+            })
+        };
+        let nodes = vec![
+            node("127.0.0.1:9042", -5_000_000_000_000_000_000),
+            node("127.0.0.2:9042", 0),
+            node("127.0.0.3:9042", 5_000_000_000_000_000_000),
+        ];
+        let keyspaces = vec![(
+            "ks".to_string(),
+            ReplicationStrategy::SimpleStrategy {
+                replication_factor: 1,
+            },
+        )]
+        .into_iter()
+        .collect();
+
+        let cluster = Cluster::default();
+        cluster.set_token_map(TokenMap::new(&nodes, keyspaces, &Murmur3Partitioner));
+
+        // tokens of 1 and 3 are -4069959284402364209 and 9010454139840013625
+        let replicas = cluster.replicas_for("ks", &[Value::from(1i32)]);
+        assert_eq!(replicas, vec![nodes[1].clone()]);
+        let replicas = cluster.replicas_for("ks", &[Value::from(3i32)]);
+        assert_eq!(replicas, vec![nodes[0].clone()]);
+        assert!(cluster
+            .replicas_for("unknown", &[Value::from(1i32)])
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn publishes_events_to_subscribers() {
+        let cluster = Cluster::default();
+        cluster.publish(ServerEvent::StatusChange(StatusChange {
+            change_type: StatusChangeType::Up,
+            addr: CInet {
+                addr: "127.0.0.1:9042".parse().unwrap(),
+            },
+        }));
+
+        let mut first = cluster.subscribe();
+        let mut second = cluster.subscribe();
+        cluster.publish(ServerEvent::StatusChange(StatusChange {
+            change_type: StatusChangeType::Down,
+            addr: CInet {
+                addr: "127.0.0.2:9042".parse().unwrap(),
+            },
+        }));
+
+        for subscriber in [&mut first, &mut second].iter_mut() {
+            match subscriber.recv().await.unwrap() {
+                ServerEvent::StatusChange(change) => {
+                    assert_eq!(change.change_type, StatusChangeType::Down);
+                    assert_eq!(change.addr.addr, "127.0.0.2:9042".parse().unwrap());
+                }
+                event => panic!("unexpected event {:?}", event),
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use bb8::{Builder, ManageConnection, PooledConnection};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+
+use crate::authenticators::Authenticator;
+use crate::cluster::tcp_connection_pool::startup_with_extensions;
+use crate::cluster::{fetch_supported, supported_extensions, ConnectionPool, NodeUnixConfig};
+use crate::compression::Compression;
+use crate::error;
+use crate::frame::Frame;
+use crate::transport::{CDRSTransport, TransportConfig, TransportUnix};
+
+/// Shortcut for `bb8::Pool` type of Unix socket-based CDRS connections.
+pub type UnixConnectionPool<A> = ConnectionPool<UnixConnectionsManager<A>>;
+
+/// `bb8::Pool` of Unix socket-based CDRS connections.
+///
+/// Used internally for Unix socket Session for holding connections to a specific Cassandra node.
+pub async fn new_unix_pool<A: Authenticator + Send + Sync + 'static>(
+    node_config: NodeUnixConfig<A>,
+    compression: Compression,
+) -> error::Result<UnixConnectionPool<A>> {
+    let pool = new_lazy_unix_pool(node_config, compression);
+    pool.connection().await?;
+    Ok(pool)
+}
+
+/// Creates a pool which establishes connections in the background, without waiting for the
+/// node to be reachable.
+pub(crate) fn new_lazy_unix_pool<A: Authenticator + Send + Sync + 'static>(
+    node_config: NodeUnixConfig<A>,
+    compression: Compression,
+) -> UnixConnectionPool<A> {
+    let addr = TransportUnix::socket_addr(&node_config.path);
+    let manager = UnixConnectionsManager::new(
+        node_config.path,
+        node_config.authenticator,
+        compression,
+        node_config.transport_config,
+    );
+
+    let (warm_up_size, warm_up_statements) = if node_config.warm_up {
+        (
+            Some(node_config.min_idle.unwrap_or(node_config.max_size)),
+            node_config.warm_up_statements,
+        )
+    } else {
+        (None, vec![])
+    };
+
+    let orphaned_requests = manager.orphaned_requests.clone();
+    let pool = Builder::new()
+        .max_size(node_config.max_size)
+        .min_idle(node_config.min_idle)
+        .max_lifetime(node_config.max_lifetime)
+        .idle_timeout(node_config.idle_timeout)
+        .connection_timeout(node_config.connection_timeout)
+        .test_on_check_out(node_config.test_on_check_out)
+        .build_unchecked(manager);
+
+    UnixConnectionPool::new(
+        pool,
+        addr,
+        node_config.reconnection_policy,
+        node_config.max_size,
+        warm_up_size,
+        orphaned_requests,
+    )
+    .with_warm_up_statements(warm_up_statements)
+}
+
+/// `bb8` connection manager.
+#[derive(Debug)]
+pub struct UnixConnectionsManager<A> {
+    path: PathBuf,
+    auth: A,
+    compression: Compression,
+    transport_config: TransportConfig,
+    orphaned_requests: Arc<AtomicUsize>,
+}
+
+impl<A> UnixConnectionsManager<A> {
+    pub fn new(
+        path: PathBuf,
+        auth: A,
+        compression: Compression,
+        transport_config: TransportConfig,
+    ) -> Self {
+        UnixConnectionsManager {
+            path,
+            auth,
+            compression,
+            transport_config,
+            orphaned_requests: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Authenticator + 'static + Send + Sync> ManageConnection for UnixConnectionsManager<A> {
+    type Connection = TransportUnix;
+    type Error = error::Error;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        let transport = TransportUnix::connect(
+            &self.path,
+            self.compression,
+            None,
+            &self.transport_config,
+            self.orphaned_requests.clone(),
+        )
+        .await?;
+        let supported = fetch_supported(&transport).await?;
+        startup_with_extensions(&transport, &self.auth, &supported_extensions(&supported)).await?;
+
+        Ok(transport)
+    }
+
+    async fn is_valid(&self, conn: &mut PooledConnection<'_, Self>) -> Result<(), Self::Error> {
+        let options_frame = Frame::new_req_options();
+        conn.write_frame(&options_frame).await.map(|_| ())
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
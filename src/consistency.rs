@@ -74,6 +74,26 @@ pub enum Consistency {
     Unknown,
 }
 
+impl Consistency {
+    /// Checks if the consistency is a serial one, i.e. `Serial` or `LocalSerial`, which are the
+    /// only ones allowed as serial consistency of conditional updates.
+    pub fn is_serial(self) -> bool {
+        matches!(self, Consistency::Serial | Consistency::LocalSerial)
+    }
+
+    /// Checks that the consistency can be used as serial consistency.
+    pub fn check_serial(self) -> error::Result<()> {
+        if self.is_serial() {
+            Ok(())
+        } else {
+            Err(error::Error::General(format!(
+                "Serial consistency should be Serial or LocalSerial, not {:?}",
+                self
+            )))
+        }
+    }
+}
+
 impl Default for Consistency {
     fn default() -> Consistency {
         Consistency::One
@@ -270,4 +290,12 @@ mod tests {
             Consistency::LocalOne
         );
     }
+
+    #[test]
+    fn checks_serial_consistencies() {
+        assert!(Consistency::Serial.check_serial().is_ok());
+        assert!(Consistency::LocalSerial.check_serial().is_ok());
+        assert!(Consistency::Quorum.check_serial().is_err());
+        assert!(!Consistency::One.is_serial());
+    }
 }
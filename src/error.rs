@@ -7,6 +7,7 @@ use std::string::FromUtf8Error;
 
 use crate::compression::CompressionError;
 use crate::frame::frame_error::CDRSError;
+use crate::frame::frame_result::ColTypeOption;
 use uuid::Error as UUIDError;
 
 pub type Result<T> = result::Result<T, Error>;
@@ -30,12 +31,43 @@ pub enum Error {
     Compression(CompressionError),
     /// Server error.
     Server(CDRSError),
+    /// Request timed out without receiving a response. The request might have been executed by
+    /// the server.
+    Timeout(String),
+    /// Connection broke while the request was in flight, e.g. because the server closed it or
+    /// sent data which could not be parsed. The connection is replaced, so the request can be
+    /// retried, but it might have been executed by the server.
+    ConnectionBroken(String),
+    /// Column, UDT field or tuple element with given name or index doesn't exist, as opposed to
+    /// existing with a null value.
+    MissingColumn(String),
 }
 
+#[deprecated(note = "Use column_not_found_err() for missing columns or null_value_err() for nulls")]
 pub fn column_is_empty_err<T: Display>(column_name: T) -> Error {
     Error::General(format!("Column or UDT property '{}' is empty", column_name))
 }
 
+pub fn column_not_found_err<T: Display>(column_name: T) -> Error {
+    Error::MissingColumn(column_name.to_string())
+}
+
+pub fn null_value_err<T: Display>(column_name: T) -> Error {
+    Error::General(format!(
+        "Value of column or UDT property '{}' is null",
+        column_name
+    ))
+}
+
+/// Error of converting a value of given type into a Rust type, which is converted from values of
+/// the valid types.
+pub(crate) fn invalid_conversion_err(col_type: &ColTypeOption, into: &str, valid: &str) -> Error {
+    Error::General(format!(
+        "Invalid conversion. Cannot convert {:?} into {} (valid types: {}).",
+        col_type.id, into, valid
+    ))
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -45,6 +77,11 @@ impl fmt::Display for Error {
             Error::FromUtf8(ref err) => write!(f, "FromUtf8Error error: {:?}", err),
             Error::UUIDParse(ref err) => write!(f, "UUIDParse error: {:?}", err),
             Error::General(ref err) => write!(f, "GeneralParsing error: {:?}", err),
+            Error::Timeout(ref err) => write!(f, "Timeout error: {}", err),
+            Error::ConnectionBroken(ref err) => write!(f, "Connection broken: {}", err),
+            Error::MissingColumn(ref name) => {
+                write!(f, "Column or UDT property '{}' doesn't exist", name)
+            }
         }
     }
 }
@@ -1,14 +1,12 @@
-use tokio::sync::Mutex;
 use std::iter::Iterator;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use crate::compression::Compression;
 use crate::error;
 use crate::frame::events::{
     SchemaChange as FrameSchemaChange, ServerEvent as FrameServerEvent,
     SimpleServerEvent as FrameSimpleServerEvent,
 };
-use crate::frame::parser::parse_frame;
+use crate::frame::Frame;
 use crate::transport::CDRSTransport;
 
 /// Full Server Event which includes all details about occured change.
@@ -24,15 +22,19 @@ pub type SchemaChange = FrameSchemaChange;
 /// Factory function which returns a `Listener` and related `EventStream.`
 ///
 /// `Listener` provides only one function `start` to start listening. It
-/// blocks a thread so should be moved into a separate one to no release
-/// main thread.
+/// returns a future which should be spawned as a separate task.
 ///
-/// `EventStream` is an iterator which returns new events once they come.
-/// It is similar to `Receiver::iter`.
-pub fn new_listener<X>(transport: X) -> (Listener<X>, EventStream) {
-    let (tx, rx) = channel();
+/// `EventStream` asynchronously returns new events once they come.
+///
+/// `event_frames` should receive event frames read by the `transport`.
+pub fn new_listener<X>(
+    transport: X,
+    event_frames: UnboundedReceiver<Frame>,
+) -> (Listener<X>, EventStream) {
+    let (tx, rx) = unbounded_channel();
     let listener = Listener {
         transport: transport,
+        event_frames,
         tx: tx,
     };
     let stream = EventStream { rx: rx };
@@ -40,20 +42,24 @@ pub fn new_listener<X>(transport: X) -> (Listener<X>, EventStream) {
 }
 
 /// `Listener` provides only one function `start` to start listening. It
-/// blocks a thread so should be moved into a separate one to no release
-/// main thread.
-
+/// returns a future which should be spawned as a separate task.
 pub struct Listener<X> {
+    // keeps the connection open while listening
+    #[allow(dead_code)]
     transport: X,
-    tx: Sender<ServerEvent>,
+    event_frames: UnboundedReceiver<Frame>,
+    tx: UnboundedSender<ServerEvent>,
 }
 
-impl<X: CDRSTransport + Unpin + 'static> Listener<Mutex<X>> {
-    /// It starts a process of listening to new events. Locks a frame.
-    pub async fn start(self, compressor: &Compression) -> error::Result<()> {
+impl<X: CDRSTransport + Unpin + 'static> Listener<X> {
+    /// It starts a process of listening to new events.
+    pub async fn start(mut self) -> error::Result<()> {
         loop {
-            let event_opt = parse_frame(&self.transport, compressor)
-                .await?
+            let event_opt = self
+                .event_frames
+                .recv()
+                .await
+                .ok_or_else(|| error::Error::General("Connection closed".into()))?
                 .get_body()?
                 .into_server_event();
 
@@ -71,17 +77,16 @@ impl<X: CDRSTransport + Unpin + 'static> Listener<Mutex<X>> {
     }
 }
 
-/// `EventStream` is an iterator which returns new events once they come.
-/// It is similar to `Receiver::iter`.
+/// `EventStream` asynchronously returns new events once they come.
+/// It is similar to `UnboundedReceiver::recv`.
 pub struct EventStream {
-    rx: Receiver<ServerEvent>,
+    rx: UnboundedReceiver<ServerEvent>,
 }
 
-impl Iterator for EventStream {
-    type Item = ServerEvent;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.rx.recv().ok()
+impl EventStream {
+    /// Waits for the next event. Returns `None` when the listener has stopped.
+    pub async fn next(&mut self) -> Option<ServerEvent> {
+        self.rx.recv().await
     }
 }
 
@@ -92,10 +97,10 @@ impl Into<EventStreamNonBlocking> for EventStream {
 }
 
 /// `EventStreamNonBlocking` is an iterator which returns new events once they come.
-/// It is similar to `Receiver::iter`. It's a non-blocking version of `EventStream`
+/// It is similar to `UnboundedReceiver::try_recv`. It's a non-blocking version of `EventStream`
 #[derive(Debug)]
 pub struct EventStreamNonBlocking {
-    rx: Receiver<ServerEvent>,
+    rx: UnboundedReceiver<ServerEvent>,
 }
 
 impl Iterator for EventStreamNonBlocking {
@@ -77,7 +77,7 @@ impl PartialEq<ServerEvent> for SimpleServerEvent {
 }
 
 /// Full server event that contains all details about a concreate change.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ServerEvent {
     /// Events related to change in the cluster topology
     TopologyChange(TopologyChange),
@@ -112,7 +112,7 @@ impl FromCursor for ServerEvent {
 }
 
 /// Events related to change in the cluster topology
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TopologyChange {
     pub change_type: TopologyChangeType,
     pub addr: CInet,
@@ -130,7 +130,7 @@ impl FromCursor for TopologyChange {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TopologyChangeType {
     NewNode,
     RemovedNode,
@@ -147,7 +147,7 @@ impl FromCursor for TopologyChangeType {
 }
 
 /// Events related to change of node status.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StatusChange {
     pub change_type: StatusChangeType,
     pub addr: CInet,
@@ -165,7 +165,7 @@ impl FromCursor for StatusChange {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StatusChangeType {
     Up,
     Down,
@@ -182,7 +182,7 @@ impl FromCursor for StatusChangeType {
 }
 
 /// Events related to schema change.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SchemaChange {
     pub change_type: ChangeType,
     pub target: Target,
@@ -205,7 +205,7 @@ impl FromCursor for SchemaChange {
 
 /// Represents type of changes.
 // TODO: rename to SchemaChangeType
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ChangeType {
     Created,
     Updated,
@@ -225,7 +225,7 @@ impl FromCursor for ChangeType {
 
 /// Refers to a target of changes were made.
 // TODO: rename to SchemaChangeTarget
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Target {
     Keyspace,
     Table,
@@ -248,7 +248,7 @@ impl FromCursor for Target {
 }
 
 /// Option that contains an information about changes were made.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ChangeSchemeOptions {
     /// Changes related to keyspaces. Contains keyspace name.
     Keyspace(String),
@@ -1,7 +1,11 @@
+use std::panic::Location;
+use std::time::Duration;
+
 use crate::consistency::Consistency;
 use crate::frame::*;
 use crate::query::QueryFlags;
 use crate::query::QueryValues;
+use crate::query::RequestPriority;
 use crate::types::*;
 
 /// `BodyResReady`
@@ -10,11 +14,31 @@ pub struct BodyReqBatch {
     pub batch_type: BatchType,
     pub queries: Vec<BatchQuery>,
     pub consistency: Consistency,
+    /// Whether the consistency was set explicitly, rather than left to the default consistency
+    /// of the session. Not sent to the server.
+    pub explicit_consistency: bool,
     /// **IMPORTANT NOTE:** with names flag does not work and should not be used.
     /// https://github.com/apache/cassandra/blob/trunk/doc/native_protocol_v4.spec#L413
     pub query_flags: Vec<QueryFlags>,
     pub serial_consistency: Option<Consistency>,
     pub timestamp: Option<i64>,
+    /// Whether the batch can be safely applied more than once. Not sent to the server.
+    pub is_idempotent: bool,
+    /// Time limit of the batch, including retries and speculative executions. Not sent to the
+    /// server.
+    pub deadline: Option<Duration>,
+    /// Priority of the batch when connections are saturated. Not sent to the server.
+    pub priority: RequestPriority,
+    /// Location of the code which built the batch, attributing batch warnings to it. Not sent
+    /// to the server.
+    pub location: Option<&'static Location<'static>>,
+    /// Keyspace of the partition the batch is routed to. Not sent to the server.
+    pub keyspace: Option<String>,
+    /// Table of the partition the batch is routed to, if known. Not sent to the server.
+    pub table: Option<String>,
+    /// Serialized partition key the batch is routed by, along with `keyspace`. Not sent to the
+    /// server.
+    pub routing_key: Option<Vec<u8>>,
 }
 
 impl IntoBytes for BodyReqBatch {
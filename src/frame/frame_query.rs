@@ -51,6 +51,7 @@ impl BodyReqQuery {
             query: CStringLong::new(query),
             query_params: QueryParams {
                 consistency,
+                explicit_consistency: true,
                 flags,
                 with_names,
                 values,
@@ -58,6 +59,13 @@ impl BodyReqQuery {
                 paging_state,
                 serial_consistency,
                 timestamp,
+                is_idempotent: false,
+                deadline: None,
+                priority: Default::default(),
+                keyspace: None,
+                routing_key: None,
+                token: None,
+                target_node: None,
             },
         }
     }
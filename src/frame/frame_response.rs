@@ -1,5 +1,7 @@
 use std::io::Cursor;
 
+use bytes::Bytes;
+
 use crate::error;
 use crate::frame::frame_auth_challenge::*;
 use crate::frame::frame_auth_success::BodyReqAuthSuccess;
@@ -36,7 +38,21 @@ pub enum ResponseBody {
 }
 
 impl ResponseBody {
+    /// Parses a response body, copying values of rows out of `bytes`.
     pub fn from(bytes: &[u8], response_type: &Opcode) -> error::Result<ResponseBody> {
+        ResponseBody::parse(bytes, None, response_type)
+    }
+
+    /// Parses a response body, with values of rows being slices of `buffer` instead of copies.
+    pub fn from_buffer(buffer: &Bytes, response_type: &Opcode) -> error::Result<ResponseBody> {
+        ResponseBody::parse(buffer, Some(buffer), response_type)
+    }
+
+    fn parse(
+        bytes: &[u8],
+        buffer: Option<&Bytes>,
+        response_type: &Opcode,
+    ) -> error::Result<ResponseBody> {
         let mut cursor: Cursor<&[u8]> = Cursor::new(bytes);
         Ok(match *response_type {
             // request frames
@@ -58,7 +74,9 @@ impl ResponseBody {
             Opcode::Supported => {
                 ResponseBody::Supported(BodyResSupported::from_cursor(&mut cursor)?)
             }
-            Opcode::Result => ResponseBody::Result(ResResultBody::from_cursor(&mut cursor)?),
+            Opcode::Result => {
+                ResponseBody::Result(ResResultBody::from_buffer(&mut cursor, buffer)?)
+            }
             Opcode::Event => ResponseBody::Event(BodyResEvent::from_cursor(&mut cursor)?),
             Opcode::AuthChallenge => {
                 ResponseBody::AuthChallenge(BodyResAuthChallenge::from_cursor(&mut cursor)?)
@@ -120,6 +138,15 @@ impl ResponseBody {
         }
     }
 
+    /// It unwraps body and returns BodyResSupported with options supported by the server.
+    /// If frame body is not of type `Supported` this method returns `None`.
+    pub fn into_supported(self) -> Option<BodyResSupported> {
+        match self {
+            ResponseBody::Supported(supported) => Some(supported),
+            _ => None,
+        }
+    }
+
     pub fn get_authenticator<'a>(&'a self) -> Option<&'a str> {
         match *self {
             ResponseBody::Authenticate(ref auth) => Some(auth.data.as_str()),
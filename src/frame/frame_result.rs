@@ -1,9 +1,14 @@
+use std::fmt;
 use std::io::Cursor;
 
+use bytes::Bytes;
+
 use crate::error;
 use crate::frame::events::SchemaChange;
 use crate::frame::{FromBytes, FromCursor, IntoBytes};
+use crate::types::data_serialization_types::DURATION_TYPE;
 use crate::types::rows::Row;
+use crate::types::vector::parse_vector_type;
 use crate::types::*;
 
 /// `ResultKind` is enum which represents types of result.
@@ -78,10 +83,13 @@ impl ResResultBody {
     fn parse_body_from_cursor(
         mut cursor: &mut Cursor<&[u8]>,
         result_kind: ResultKind,
+        buffer: Option<&Bytes>,
     ) -> error::Result<ResResultBody> {
         Ok(match result_kind {
             ResultKind::Void => ResResultBody::Void(BodyResResultVoid::from_cursor(&mut cursor)?),
-            ResultKind::Rows => ResResultBody::Rows(BodyResResultRows::from_cursor(&mut cursor)?),
+            ResultKind::Rows => {
+                ResResultBody::Rows(BodyResResultRows::parse_from_cursor(&mut cursor, buffer)?)
+            }
             ResultKind::SetKeyspace => {
                 ResResultBody::SetKeyspace(BodyResResultSetKeyspace::from_cursor(&mut cursor)?)
             }
@@ -94,6 +102,17 @@ impl ResResultBody {
         })
     }
 
+    /// It retrieves `ResResultBody` from `io::Cursor` reading `buffer`, with values of rows
+    /// being slices of `buffer` if it's given, and copies of them otherwise.
+    pub(crate) fn from_buffer(
+        mut cursor: &mut Cursor<&[u8]>,
+        buffer: Option<&Bytes>,
+    ) -> error::Result<ResResultBody> {
+        let result_kind = ResultKind::from_cursor(&mut cursor)?;
+
+        ResResultBody::parse_body_from_cursor(&mut cursor, result_kind, buffer)
+    }
+
     /// It converts body into `Vec<Row>` if body's type is `Row` and returns `None` otherwise.
     pub fn into_rows(self) -> Option<Vec<Row>> {
         match self {
@@ -130,10 +149,8 @@ impl ResResultBody {
 }
 
 impl FromCursor for ResResultBody {
-    fn from_cursor(mut cursor: &mut Cursor<&[u8]>) -> error::Result<ResResultBody> {
-        let result_kind = ResultKind::from_cursor(&mut cursor)?;
-
-        ResResultBody::parse_body_from_cursor(&mut cursor, result_kind)
+    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> error::Result<ResResultBody> {
+        ResResultBody::from_buffer(cursor, None)
     }
 }
 
@@ -190,29 +207,38 @@ pub struct BodyResResultRows {
 }
 
 impl BodyResResultRows {
-    /// It retrieves rows content having knowledge about number of rows and columns.
+    /// It retrieves rows content having knowledge about number of rows and columns. Values are
+    /// slices of `buffer`, which the cursor reads, if it's given, and copies otherwise.
     fn get_rows_content(
-        mut cursor: &mut Cursor<&[u8]>,
+        cursor: &mut Cursor<&[u8]>,
         rows_count: i32,
         columns_count: i32,
-    ) -> Vec<Vec<CBytes>> {
+        buffer: Option<&Bytes>,
+    ) -> error::Result<Vec<Vec<CBytes>>> {
         (0..rows_count)
             .map(|_| {
                 (0..columns_count)
-                    // XXX unwrap()
-                    .map(|_| CBytes::from_cursor(&mut cursor).unwrap() as CBytes)
+                    .map(|_| match buffer {
+                        Some(buffer) => CBytes::from_buffer(cursor, buffer),
+                        None => CBytes::from_cursor(cursor),
+                    })
                     .collect()
             })
             .collect()
     }
-}
 
-impl FromCursor for BodyResResultRows {
-    fn from_cursor(mut cursor: &mut Cursor<&[u8]>) -> error::Result<BodyResResultRows> {
+    fn parse_from_cursor(
+        mut cursor: &mut Cursor<&[u8]>,
+        buffer: Option<&Bytes>,
+    ) -> error::Result<BodyResResultRows> {
         let metadata = RowsMetadata::from_cursor(&mut cursor)?;
         let rows_count = CInt::from_cursor(&mut cursor)?;
-        let rows_content: Vec<Vec<CBytes>> =
-            BodyResResultRows::get_rows_content(&mut cursor, rows_count, metadata.columns_count);
+        let rows_content = BodyResResultRows::get_rows_content(
+            &mut cursor,
+            rows_count,
+            metadata.columns_count,
+            buffer,
+        )?;
 
         Ok(BodyResResultRows {
             metadata: metadata,
@@ -222,6 +248,12 @@ impl FromCursor for BodyResResultRows {
     }
 }
 
+impl FromCursor for BodyResResultRows {
+    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> error::Result<BodyResResultRows> {
+        BodyResResultRows::parse_from_cursor(cursor, None)
+    }
+}
+
 /// Rows metadata.
 #[derive(Debug, Clone)]
 pub struct RowsMetadata {
@@ -259,7 +291,12 @@ impl FromCursor for RowsMetadata {
             global_table_space = Some(vec![keyspace, tablename])
         }
 
-        let col_specs = ColSpec::parse_colspecs(&mut cursor, columns_count, has_global_table_space);
+        // responses to executions which skipped metadata have no column specs
+        let col_specs = if RowsMetadataFlag::has_no_metadata(flags) {
+            vec![]
+        } else {
+            ColSpec::parse_colspecs(&mut cursor, columns_count, has_global_table_space)
+        };
 
         Ok(RowsMetadata {
             flags: flags,
@@ -271,6 +308,43 @@ impl FromCursor for RowsMetadata {
     }
 }
 
+impl RowsMetadata {
+    /// Returns the keyspace of the table the columns belong to, if there are any columns.
+    pub fn keyspace(&self) -> Option<&str> {
+        match self.global_table_space {
+            Some(ref table_space) => table_space.first().map(CString::as_str),
+            None => self.col_specs.first().and_then(ColSpec::keyspace),
+        }
+    }
+
+    /// Returns the table the columns belong to, if there are any columns.
+    pub fn table(&self) -> Option<&str> {
+        match self.global_table_space {
+            Some(ref table_space) => table_space.get(1).map(CString::as_str),
+            None => self.col_specs.first().and_then(ColSpec::table),
+        }
+    }
+
+    /// Returns names of the columns, in the order of values of rows. Responses to executions
+    /// which skipped metadata have no columns.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.col_specs.iter().map(ColSpec::name).collect()
+    }
+
+    /// Returns the index of the column of given name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.col_specs.iter().position(|spec| spec.name() == name)
+    }
+
+    /// Returns the type of the column of given name.
+    pub fn column_type(&self, name: &str) -> Option<&ColTypeOption> {
+        self.col_specs
+            .iter()
+            .find(|spec| spec.name() == name)
+            .map(|spec| &spec.col_type)
+    }
+}
+
 const GLOBAL_TABLE_SPACE: i32 = 0x0001;
 const HAS_MORE_PAGES: i32 = 0x0002;
 const NO_METADATA: i32 = 0x0004;
@@ -353,6 +427,20 @@ pub struct ColSpec {
 }
 
 impl ColSpec {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the keyspace of the column, unless it's given for all columns by the metadata.
+    pub fn keyspace(&self) -> Option<&str> {
+        self.ksname.as_ref().map(CString::as_str)
+    }
+
+    /// Returns the table of the column, unless it's given for all columns by the metadata.
+    pub fn table(&self) -> Option<&str> {
+        self.tablename.as_ref().map(CString::as_str)
+    }
+
     /// parse_colspecs tables mutable cursor,
     /// number of columns (column_count) and flags that indicates
     /// if Global_tables_spec is specified. It returns column_count of ColSpecs.
@@ -512,6 +600,91 @@ impl FromCursor for ColTypeOption {
     }
 }
 
+/// Types are displayed as in CQL, e.g. `map<text, frozen<list<int>>>`. UDTs are displayed by
+/// their names, qualified with their keyspaces.
+impl fmt::Display for ColTypeOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.id, &self.value) {
+            (ColType::List, Some(ColTypeOptionValue::CList(element))) => {
+                write!(f, "list<{}>", Frozen(element))
+            }
+            (ColType::Set, Some(ColTypeOptionValue::CSet(element))) => {
+                write!(f, "set<{}>", Frozen(element))
+            }
+            (ColType::Map, Some(ColTypeOptionValue::CMap((key, value)))) => {
+                write!(f, "map<{}, {}>", Frozen(key), Frozen(value))
+            }
+            (ColType::Udt, Some(ColTypeOptionValue::UdtType(udt))) => {
+                write!(f, "{}.{}", udt.ks.as_str(), udt.udt_name.as_str())
+            }
+            (ColType::Tuple, Some(ColTypeOptionValue::TupleType(tuple))) => {
+                let types: Vec<String> = tuple
+                    .types
+                    .iter()
+                    .map(|col_type| Frozen(col_type).to_string())
+                    .collect();
+                write!(f, "tuple<{}>", types.join(", "))
+            }
+            (ColType::Custom, Some(ColTypeOptionValue::CString(class))) => {
+                match parse_vector_type(class.as_str()) {
+                    Some((element, _, dimension)) => {
+                        let element = ColTypeOption {
+                            id: element,
+                            value: None,
+                        };
+                        write!(f, "vector<{}, {}>", element, dimension)
+                    }
+                    None if class.as_str() == DURATION_TYPE => f.write_str("duration"),
+                    None => write!(f, "'{}'", class.as_str()),
+                }
+            }
+            (id, _) => f.write_str(match id {
+                ColType::Ascii => "ascii",
+                ColType::Bigint => "bigint",
+                ColType::Blob => "blob",
+                ColType::Boolean => "boolean",
+                ColType::Counter => "counter",
+                ColType::Decimal => "decimal",
+                ColType::Double => "double",
+                ColType::Float => "float",
+                ColType::Int => "int",
+                ColType::Timestamp => "timestamp",
+                ColType::Uuid => "uuid",
+                ColType::Varchar => "text",
+                ColType::Varint => "varint",
+                ColType::Timeuuid => "timeuuid",
+                ColType::Inet => "inet",
+                ColType::Date => "date",
+                ColType::Time => "time",
+                ColType::Smallint => "smallint",
+                ColType::Tinyint => "tinyint",
+                ColType::Null => "null",
+                // collections, UDTs, tuples and custom types without types of their elements
+                ColType::Custom => "custom",
+                ColType::List => "list",
+                ColType::Map => "map",
+                ColType::Set => "set",
+                ColType::Udt => "udt",
+                ColType::Tuple => "tuple",
+            }),
+        }
+    }
+}
+
+/// Displays a type nested in another one, which is frozen if it's a collection, UDT or tuple.
+struct Frozen<'a>(&'a ColTypeOption);
+
+impl fmt::Display for Frozen<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.id {
+            ColType::List | ColType::Map | ColType::Set | ColType::Udt | ColType::Tuple => {
+                write!(f, "frozen<{}>", self.0)
+            }
+            _ => self.0.fmt(f),
+        }
+    }
+}
+
 /// Enum that represents all possible types of `value` of `ColTypeOption`.
 #[derive(Debug, Clone)]
 pub enum ColTypeOptionValue {
@@ -614,6 +787,36 @@ pub struct PreparedMetadata {
     pub col_specs: Vec<ColSpec>,
 }
 
+impl PreparedMetadata {
+    /// Returns the keyspace of the table bound values belong to, if there are any.
+    pub fn keyspace(&self) -> Option<&str> {
+        match self.global_table_spec {
+            Some((ref keyspace, _)) => Some(keyspace.as_str()),
+            None => self.col_specs.first().and_then(ColSpec::keyspace),
+        }
+    }
+
+    /// Returns the table bound values belong to, if there are any.
+    pub fn table(&self) -> Option<&str> {
+        match self.global_table_spec {
+            Some((_, ref table)) => Some(table.as_str()),
+            None => self.col_specs.first().and_then(ColSpec::table),
+        }
+    }
+
+    /// Returns names of bound values, in the order they're bound in.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.col_specs.iter().map(ColSpec::name).collect()
+    }
+
+    /// Returns indexes of bound values which form the partition key, in partition key order.
+    /// They're known only for queries binding all columns of the partition key, with protocol
+    /// v4 or later.
+    pub fn partition_key_indexes(&self) -> Vec<usize> {
+        self.pk_indexes.iter().map(|&index| index as usize).collect()
+    }
+}
+
 impl FromCursor for PreparedMetadata {
     fn from_cursor(mut cursor: &mut Cursor<&[u8]>) -> error::Result<PreparedMetadata> {
         let flags = CInt::from_cursor(&mut cursor)?;
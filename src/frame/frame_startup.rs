@@ -22,6 +22,12 @@ impl<'a> BodyReqStartup<'a> {
         BodyReqStartup { map: map }
     }
 
+    /// Enables a protocol extension, e.g. one supported by Scylla nodes.
+    pub fn with_extension(mut self, name: &'static str) -> Self {
+        self.map.insert(name, "");
+        self
+    }
+
     // should be [u8; 2]
     // Number of key-value pairs
     fn num(&self) -> Vec<u8> {
@@ -60,6 +66,27 @@ impl Frame {
 
         Frame::new(version, vec![flag], opcode, body.into_cbytes(),  None, vec![])
     }
+
+    /// Creates new frame of type `startup`, enabling given protocol extensions.
+    pub fn new_req_startup_with_extensions(
+        compression: Option<&str>,
+        extensions: &[&'static str],
+    ) -> Frame {
+        let body = extensions
+            .iter()
+            .fold(BodyReqStartup::new(compression), |body, extension| {
+                body.with_extension(extension)
+            });
+
+        Frame::new(
+            Version::Request,
+            vec![Flag::Ignore],
+            Opcode::Startup,
+            body.into_cbytes(),
+            None,
+            vec![],
+        )
+    }
 }
 
 #[cfg(test)]
@@ -83,6 +110,13 @@ mod test {
         assert_eq!(body.map.len(), 1);
     }
 
+    #[test]
+    fn new_body_req_startup_extensions() {
+        let body = BodyReqStartup::new(None).with_extension("TABLETS_ROUTING_V1");
+        assert_eq!(body.map.get("TABLETS_ROUTING_V1"), Some(&""));
+        assert_eq!(body.map.len(), 2);
+    }
+
     #[test]
     fn new_req_startup() {
         let compression = Some("test_compression");
@@ -1,10 +1,16 @@
 //! `frame` module contains general Frame functionality.
-use std::sync::atomic::{AtomicI16, Ordering};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
 
 use crate::compression::Compression;
 use crate::frame::frame_response::ResponseBody;
+use crate::frame::frame_result::{ResResultBody, RowsMetadata, RowsMetadataFlag};
 pub use crate::frame::traits::*;
+#[cfg(feature = "derive")]
+pub use cdrs_tokio_derive::{CqlEnum, IntoCDRSValue, TryFromRow, TryFromUDT};
 use crate::types::to_n_bytes;
+use bytes::Bytes;
 use uuid::Uuid;
 
 /// Number of stream bytes in accordance to protocol.
@@ -35,11 +41,9 @@ pub mod traits;
 
 use crate::error;
 
-static STREAM_ID: AtomicI16 = AtomicI16::new(0);
-
 pub type StreamId = i16;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Frame {
     pub version: Version,
     pub flags: Vec<Flag>,
@@ -48,6 +52,11 @@ pub struct Frame {
     pub body: Vec<u8>,
     pub tracing_id: Option<Uuid>,
     pub warnings: Vec<String>,
+    /// Custom payload of a response, e.g. routing information sent by Scylla.
+    pub custom_payload: HashMap<String, Vec<u8>>,
+    /// Metadata of rows of a response to an execution which skipped it, cached when the query
+    /// was prepared. Used for decoding the rows.
+    pub result_metadata: Option<Arc<RowsMetadata>>,
 }
 
 impl Frame {
@@ -59,25 +68,46 @@ impl Frame {
         tracing_id: Option<Uuid>,
         warnings: Vec<String>,
     ) -> Self {
-        let mut stream = STREAM_ID.fetch_add(1, Ordering::SeqCst);
-        if stream < 0 {
-            STREAM_ID.store(0, Ordering::SeqCst);
-            stream = STREAM_ID.load(Ordering::SeqCst);
-        }
-
+        // actual stream id is assigned by a transport when the frame gets written
         Frame {
             version,
             flags,
             opcode,
-            stream,
+            stream: 0,
             body,
             tracing_id,
             warnings,
+            custom_payload: HashMap::new(),
+            result_metadata: None,
         }
     }
 
+    /// Parses the body of a response. The body is copied once, and values of rows are slices of
+    /// the copy.
     pub fn get_body(&self) -> error::Result<ResponseBody> {
-        ResponseBody::from(self.body.as_slice(), &self.opcode)
+        self.parse_body(&Bytes::copy_from_slice(&self.body))
+    }
+
+    /// Parses the body of a response without copying it, with values of rows being slices of
+    /// the body.
+    pub fn into_body(mut self) -> error::Result<ResponseBody> {
+        let body = Bytes::from(mem::take(&mut self.body));
+        self.parse_body(&body)
+    }
+
+    fn parse_body(&self, buffer: &Bytes) -> error::Result<ResponseBody> {
+        let mut body = ResponseBody::from_buffer(buffer, &self.opcode)?;
+
+        if let (Some(result_metadata), ResponseBody::Result(ResResultBody::Rows(rows))) =
+            (&self.result_metadata, &mut body)
+        {
+            if RowsMetadataFlag::has_no_metadata(rows.metadata.flags) {
+                rows.metadata.global_table_space = result_metadata.global_table_space.clone();
+                rows.metadata.col_specs = result_metadata.col_specs.clone();
+            }
+        }
+
+        Ok(body)
     }
 
     pub fn tracing_id(&self) -> &Option<Uuid> {
@@ -129,7 +159,7 @@ impl<'a> IntoBytes for Frame {
 }
 
 /// Frame's version
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Version {
     Request,
     Response,
@@ -218,7 +248,7 @@ impl From<Vec<u8>> for Version {
 
 /// Frame's flag
 // Is not implemented functionality. Only Igonore works for now
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Flag {
     Compression,
     Tracing,
@@ -307,7 +337,7 @@ impl From<u8> for Flag {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Opcode {
     Error,
     Startup,
@@ -383,6 +413,50 @@ impl From<u8> for Opcode {
 mod tests {
     use super::*;
     use crate::frame::traits::AsByte;
+    use crate::types::{IntoRustByIndex, IntoRustByName};
+
+    #[test]
+    fn test_frame_rows_share_body() {
+        let body = [
+            &[0, 0, 0, 2][..],
+            // global table spec of 2 columns
+            &[0, 0, 0, 1, 0, 0, 0, 2, 0, 2],
+            b"ks",
+            &[0, 1],
+            b"t",
+            &[0, 4],
+            b"name",
+            &[0, 0x0D, 0, 4],
+            b"data",
+            &[0, 0x03],
+            // 1 row
+            &[0, 0, 0, 1, 0, 0, 0, 3],
+            b"abc",
+            &[0, 0, 0, 2, 1, 2],
+        ]
+        .concat();
+        let frame = Frame::new(
+            Version::Response,
+            vec![],
+            Opcode::Result,
+            body,
+            None,
+            vec![],
+        );
+
+        let row = frame.into_body().unwrap().into_rows().unwrap().remove(0);
+        assert_eq!(row.get_str_by_name("name").unwrap(), Some("abc"));
+        assert!(row.get_str_by_name("data").is_err());
+
+        let data: Bytes = row.get_r_by_name("data").unwrap();
+        let again: Bytes = row.get_r_by_index(1).unwrap();
+        assert_eq!(data, Bytes::from_static(&[1, 2]));
+        assert_eq!(data.as_ptr(), again.as_ptr());
+
+        let owned: Bytes = row.into_owned().get_r_by_name("data").unwrap();
+        assert_eq!(owned, data);
+        assert_ne!(owned.as_ptr(), data.as_ptr());
+    }
 
     #[test]
     #[cfg(not(feature = "v3"))]
@@ -523,4 +597,51 @@ mod tests {
         assert_eq!(Opcode::from(0x0F), Opcode::AuthResponse);
         assert_eq!(Opcode::from(0x10), Opcode::AuthSuccess);
     }
+
+    #[test]
+    fn decodes_rows_with_cached_metadata() {
+        use crate::frame::frame_result::{ColSpec, ColType, ColTypeOption};
+        use crate::types::{CString, IntoRustByName};
+
+        let mut body = vec![0, 0, 0, 2];
+        body.extend_from_slice(&0x0004i32.to_be_bytes());
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.extend_from_slice(&4i32.to_be_bytes());
+        body.extend_from_slice(&7i32.to_be_bytes());
+
+        let mut frame = Frame::new(
+            Version::Response,
+            vec![],
+            Opcode::Result,
+            body,
+            None,
+            vec![],
+        );
+        frame.result_metadata = Some(Arc::new(RowsMetadata {
+            flags: 0,
+            columns_count: 1,
+            paging_state: None,
+            global_table_space: None,
+            col_specs: vec![ColSpec {
+                ksname: Some(CString::new("ks".into())),
+                tablename: Some(CString::new("t".into())),
+                name: CString::new("value".into()),
+                col_type: ColTypeOption {
+                    id: ColType::Int,
+                    value: None,
+                },
+            }],
+        }));
+
+        let rows = frame.get_body().unwrap().into_rows().unwrap();
+        assert_eq!(rows.len(), 1);
+        let value: i32 = rows[0].get_r_by_name("value").unwrap();
+        assert_eq!(value, 7);
+
+        frame.result_metadata = None;
+        let rows = frame.get_body().unwrap().into_rows().unwrap();
+        let value: error::Result<i32> = rows[0].get_r_by_name("value");
+        assert!(value.is_err());
+    }
 }
@@ -1,36 +1,30 @@
-use bb8;
+use std::collections::HashMap;
 use std::io::Cursor;
-use std::ops::Deref;
 use tokio::io::{AsyncRead, AsyncReadExt};
-use tokio::sync::Mutex;
 
 use super::*;
 use crate::compression::Compression;
 use crate::error;
 use crate::frame::frame_response::ResponseBody;
 use crate::frame::FromCursor;
-use crate::transport::CDRSTransport;
 use crate::types::data_serialization_types::decode_timeuuid;
-use crate::types::{from_bytes, CStringList, UUID_LEN, from_i16_bytes};
-
-pub async fn from_connection<M, T>(
-    conn: &bb8::PooledConnection<'_, M>,
-    compressor: &Compression,
-) -> error::Result<Frame>
-where
-    T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
-{
-    parse_frame(conn.deref(), compressor).await
+use crate::types::{from_bytes, CBytes, CIntShort, CString, CStringList, UUID_LEN, from_i16_bytes};
+
+/// Maximum length of a frame body. Longer frames are considered corrupt.
+const MAX_FRAME_BODY_LENGTH: usize = 256 * 1024 * 1024;
+
+/// Reads a frame and converts server errors into `Err`.
+pub async fn parse_frame<T>(cursor: &mut T, compressor: &Compression) -> error::Result<Frame> where T: AsyncRead + Unpin {
+    parse_raw_frame(cursor, compressor).await.and_then(convert_frame_into_result)
 }
 
-pub async fn parse_frame<T>(cursor_cell: &Mutex<T>, compressor: &Compression) -> error::Result<Frame> where T: AsyncRead + Unpin {
+/// Reads a frame as it is, including error frames.
+pub async fn parse_raw_frame<T>(cursor: &mut T, compressor: &Compression) -> error::Result<Frame> where T: AsyncRead + Unpin {
     let mut version_bytes = [0; Version::BYTE_LENGTH];
     let mut flag_bytes = [0; Flag::BYTE_LENGTH];
     let mut opcode_bytes = [0; Opcode::BYTE_LENGTH];
     let mut stream_bytes = [0; STREAM_LEN];
     let mut length_bytes = [0; LENGTH_LEN];
-    let mut cursor = cursor_cell.lock().await;
 
     // NOTE: order of reads matters
     cursor.read_exact(&mut version_bytes).await?;
@@ -39,11 +33,18 @@ pub async fn parse_frame<T>(cursor_cell: &Mutex<T>, compressor: &Compression) ->
     cursor.read_exact(&mut opcode_bytes).await?;
     cursor.read_exact(&mut length_bytes).await?;
 
-    let version = Version::from(version_bytes.to_vec());
+    // invalid header means the stream is out of sync, so nothing read from it can be trusted
+    let version = parse_version(version_bytes[0])?;
     let flags = Flag::get_collection(flag_bytes[0]);
     let stream = from_i16_bytes(&stream_bytes);
-    let opcode = Opcode::from(opcode_bytes[0]);
+    let opcode = parse_opcode(opcode_bytes[0])?;
     let length = from_bytes(&length_bytes) as usize;
+    if length > MAX_FRAME_BODY_LENGTH {
+        return Err(error::Error::General(format!(
+            "Protocol desync: frame body length {} exceeds maximum of {}",
+            length, MAX_FRAME_BODY_LENGTH
+        )));
+    }
 
     let mut body_bytes = Vec::with_capacity(length);
     unsafe {
@@ -58,7 +59,7 @@ pub async fn parse_frame<T>(cursor_cell: &Mutex<T>, compressor: &Compression) ->
         Compression::None.decode(body_bytes)?
     };
 
-    // Use cursor to get tracing id, warnings and actual body
+    // Use cursor to get tracing id, warnings, custom payload and actual body
     let mut body_cursor = Cursor::new(full_body.as_slice());
 
     let tracing_id = if flags.iter().any(|flag| flag == &Flag::Tracing) {
@@ -79,11 +80,17 @@ pub async fn parse_frame<T>(cursor_cell: &Mutex<T>, compressor: &Compression) ->
         vec![]
     };
 
+    let custom_payload = if flags.iter().any(|flag| flag == &Flag::CustomPayload) {
+        parse_custom_payload(&mut body_cursor)?
+    } else {
+        HashMap::new()
+    };
+
     let mut body = vec![];
 
     std::io::Read::read_to_end(&mut body_cursor, &mut body)?;
 
-    let frame = Frame {
+    Ok(Frame {
         version: version,
         flags: flags,
         opcode: opcode,
@@ -91,12 +98,47 @@ pub async fn parse_frame<T>(cursor_cell: &Mutex<T>, compressor: &Compression) ->
         body: body,
         tracing_id: tracing_id,
         warnings: warnings,
-    };
+        custom_payload,
+        result_metadata: None,
+    })
+}
 
-    convert_frame_into_result(frame)
+/// Reads a [bytes map].
+fn parse_custom_payload(cursor: &mut Cursor<&[u8]>) -> error::Result<HashMap<String, Vec<u8>>> {
+    let len = CIntShort::from_cursor(cursor)?;
+    let mut custom_payload = HashMap::new();
+    for _ in 0..len {
+        let key = CString::from_cursor(cursor)?.into_plain();
+        let value = CBytes::from_cursor(cursor)?.into_plain().unwrap_or_default();
+        custom_payload.insert(key, value);
+    }
+
+    Ok(custom_payload)
 }
 
-fn convert_frame_into_result(frame: Frame) -> error::Result<Frame> {
+fn parse_version(version: u8) -> error::Result<Version> {
+    if version == Version::request_version() || version == Version::response_version() {
+        Ok(Version::from(vec![version]))
+    } else {
+        Err(error::Error::General(format!(
+            "Protocol desync: unexpected frame version {:#04x}",
+            version
+        )))
+    }
+}
+
+fn parse_opcode(opcode: u8) -> error::Result<Opcode> {
+    match opcode {
+        0x00..=0x03 | 0x05..=0x10 => Ok(Opcode::from(opcode)),
+        _ => Err(error::Error::General(format!(
+            "Protocol desync: unknown opcode {:#04x}",
+            opcode
+        ))),
+    }
+}
+
+/// Converts an error frame into a server error.
+pub fn convert_frame_into_result(frame: Frame) -> error::Result<Frame> {
     match frame.opcode {
         Opcode::Error => frame.get_body().and_then(|err| match err {
             ResponseBody::Error(err) => Err(error::Error::Server(err)),
@@ -105,3 +147,62 @@ fn convert_frame_into_result(frame: Frame) -> error::Result<Frame> {
         _ => Ok(frame),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(opcode: u8, length: u32) -> Vec<u8> {
+        let mut header = vec![Version::response_version(), 0, 0, 1, opcode];
+        header.extend_from_slice(&length.to_be_bytes());
+        header
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_opcode() {
+        let frame = header(0x42, 0);
+        assert!(parse_raw_frame(&mut frame.as_slice(), &Compression::None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_corrupt_length() {
+        let frame = header(Opcode::Ready.as_byte(), u32::MAX);
+        assert!(parse_raw_frame(&mut frame.as_slice(), &Compression::None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn parses_valid_frame() {
+        let frame = header(Opcode::Ready.as_byte(), 0);
+        let frame = parse_raw_frame(&mut frame.as_slice(), &Compression::None)
+            .await
+            .unwrap();
+        assert_eq!(frame.opcode, Opcode::Ready);
+        assert_eq!(frame.stream, 1);
+    }
+
+    #[tokio::test]
+    async fn parses_custom_payload() {
+        let body = [
+            &[0, 1][..],
+            &[0, 3],
+            b"key",
+            &[0, 0, 0, 2],
+            &[7, 8],
+            &[0, 0, 0, 2],
+        ]
+        .concat();
+        let mut frame = header(Opcode::Result.as_byte(), body.len() as u32);
+        frame[1] = Flag::CustomPayload.as_byte();
+        frame.extend_from_slice(&body);
+
+        let frame = parse_raw_frame(&mut frame.as_slice(), &Compression::None)
+            .await
+            .unwrap();
+        assert_eq!(frame.custom_payload.get("key"), Some(&vec![7, 8]));
+        assert_eq!(frame.body, vec![0, 0, 0, 2]);
+    }
+}
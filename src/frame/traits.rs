@@ -51,3 +51,8 @@ pub trait TryFromRow: Sized {
 pub trait TryFromUDT: Sized {
     fn try_from_udt(udt: crate::types::udt::UDT) -> error::Result<Self>;
 }
+
+/// Conversion of CQL tuples into Rust types, implemented for Rust tuples of up to 12 elements.
+pub trait TryFromTuple: Sized {
+    fn try_from_tuple(tuple: crate::types::tuple::Tuple) -> error::Result<Self>;
+}
@@ -11,12 +11,12 @@ extern crate lz4_compress;
 extern crate bb8;
 extern crate rand;
 extern crate time;
-extern crate uuid;
 
 pub mod cluster;
 pub mod frame;
 pub mod load_balancing;
 pub mod query;
+pub mod retry;
 pub mod types;
 
 pub mod authenticators;
@@ -24,7 +24,15 @@ pub mod compression;
 pub mod consistency;
 pub mod error;
 pub mod events;
+pub mod proxy;
 pub mod transport;
 
+/// The `uuid` crate `uuid` and `timeuuid` values are read and bound as, which is used by code
+/// generated by `checked_query!`.
+pub use uuid;
+
+#[cfg(test)]
+mod test_utils;
+
 pub type Error = error::Error;
 pub type Result<T> = error::Result<T>;
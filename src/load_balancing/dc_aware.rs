@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::{LoadBalancingStrategy, NodeAddr, NodeDistance};
+use crate::cluster::Node;
+
+/// Round robin strategy preferring nodes in the local data center. Nodes in remote data
+/// centers are used only as a fallback, when all local nodes are unavailable, and only up to
+/// configured number of them per data center. No remote nodes are used by default.
+///
+/// Optionally, nodes in the local rack are preferred over other local nodes, e.g. to avoid
+/// cross availability zone traffic in cloud deployments. Since query plans are used for
+/// token-aware routing too, replicas in the local rack are preferred over other replicas.
+///
+/// Data centers and racks of nodes are known in sessions discovering cluster nodes. Nodes whose
+/// data center is unknown are considered local.
+#[derive(Debug)]
+pub struct DcAware<N> {
+    local_dc: String,
+    local_rack: Option<String>,
+    used_hosts_per_remote_dc: usize,
+    remote_dc_limits: HashMap<String, usize>,
+    cluster: Vec<Arc<N>>,
+    datacenters: HashMap<SocketAddr, String>,
+    racks: HashMap<SocketAddr, String>,
+    prev_idx: AtomicUsize,
+}
+
+impl<N> DcAware<N> {
+    pub fn new(local_dc: &str) -> Self {
+        DcAware {
+            local_dc: local_dc.to_string(),
+            local_rack: None,
+            used_hosts_per_remote_dc: 0,
+            remote_dc_limits: HashMap::new(),
+            cluster: vec![],
+            datacenters: HashMap::new(),
+            racks: HashMap::new(),
+            prev_idx: AtomicUsize::new(0),
+        }
+    }
+
+    /// Makes nodes in given rack of the local data center preferred over other local nodes.
+    pub fn with_local_rack(mut self, local_rack: &str) -> Self {
+        self.local_rack = Some(local_rack.to_string());
+        self
+    }
+
+    /// Sets how many nodes of each remote data center can be used when no local node is
+    /// available. Default is 0.
+    pub fn with_used_hosts_per_remote_dc(mut self, used_hosts_per_remote_dc: usize) -> Self {
+        self.used_hosts_per_remote_dc = used_hosts_per_remote_dc;
+        self
+    }
+
+    /// Sets how many nodes of given remote data center can be used when no local node is
+    /// available, overriding `with_used_hosts_per_remote_dc()`. A limit of 0 disables failover
+    /// to the data center.
+    pub fn with_remote_dc_limit(mut self, datacenter: &str, used_hosts: usize) -> Self {
+        self.remote_dc_limits
+            .insert(datacenter.to_string(), used_hosts);
+        self
+    }
+
+    /// Returns local data center name.
+    pub fn local_dc(&self) -> &str {
+        &self.local_dc
+    }
+
+    /// Returns local rack name, if set.
+    pub fn local_rack(&self) -> Option<&str> {
+        self.local_rack.as_deref()
+    }
+
+    fn remote_dc_limit(&self, datacenter: &str) -> usize {
+        self.remote_dc_limits
+            .get(datacenter)
+            .cloned()
+            .unwrap_or(self.used_hosts_per_remote_dc)
+    }
+}
+
+impl<N: NodeAddr> DcAware<N> {
+    fn datacenter(&self, node: &N) -> Option<&str> {
+        self.datacenters
+            .get(&node.node_addr())
+            .map(|datacenter| datacenter.as_str())
+    }
+
+    fn is_in_local_rack(&self, node: &N) -> bool {
+        match self.local_rack {
+            Some(ref local_rack) => self.racks.get(&node.node_addr()) == Some(local_rack),
+            None => false,
+        }
+    }
+
+    fn is_local(&self, node: &N) -> bool {
+        match self.datacenter(node) {
+            Some(datacenter) => datacenter == self.local_dc,
+            None => true,
+        }
+    }
+}
+
+/// Returns nodes rotated to start at given index.
+fn rotate<N>(nodes: &[Arc<N>], idx: usize) -> impl Iterator<Item = &Arc<N>> {
+    let idx = if nodes.is_empty() {
+        0
+    } else {
+        idx % nodes.len()
+    };
+    let (head, tail) = nodes.split_at(idx);
+    tail.iter().chain(head.iter())
+}
+
+impl<N> LoadBalancingStrategy<N> for DcAware<N>
+where
+    N: NodeAddr + Sync + Send,
+{
+    fn init(&mut self, cluster: Vec<Arc<N>>) {
+        self.cluster = cluster;
+    }
+
+    /// Returns next node from the local data center, or from a remote one if there are no
+    /// local nodes
+    fn next(&self) -> Option<Arc<N>> {
+        self.query_plan().into_iter().next()
+    }
+
+    /// Returns local nodes starting with the next one, local rack first, followed by allowed
+    /// remote nodes
+    fn query_plan(&self) -> Vec<Arc<N>> {
+        let idx = self
+            .prev_idx
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_add(1);
+
+        let mut local_rack = vec![];
+        let mut local = vec![];
+        let mut remote: BTreeMap<&str, Vec<Arc<N>>> = BTreeMap::new();
+        for node in &self.cluster {
+            match self.datacenter(node) {
+                Some(datacenter) if datacenter != self.local_dc => {
+                    remote.entry(datacenter).or_default().push(node.clone())
+                }
+                _ if self.is_in_local_rack(node) => local_rack.push(node.clone()),
+                _ => local.push(node.clone()),
+            }
+        }
+
+        let mut plan: Vec<Arc<N>> = rotate(&local_rack, idx)
+            .chain(rotate(&local, idx))
+            .cloned()
+            .collect();
+        for (datacenter, nodes) in remote {
+            plan.extend(
+                rotate(&nodes, idx)
+                    .take(self.remote_dc_limit(datacenter))
+                    .cloned(),
+            );
+        }
+
+        plan
+    }
+
+    fn remove_node<F>(&mut self, mut filter: F)
+    where
+        F: FnMut(&N) -> bool,
+    {
+        if let Some(i) = self.cluster.iter().position(|node| filter(node)) {
+            self.cluster.remove(i);
+        }
+    }
+
+    fn add_node(&mut self, node: Arc<N>) {
+        self.cluster.push(node);
+    }
+
+    /// Finds a matching node in the local data center, preferably in the local rack
+    fn find<F>(&self, mut filter: F) -> Option<Arc<N>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        let mut found = None;
+        for node in self.cluster.iter().filter(|node| self.is_local(node)) {
+            if filter(node) {
+                if self.is_in_local_rack(node) {
+                    return Some(node.clone());
+                }
+
+                found = found.or_else(|| Some(node.clone()));
+            }
+        }
+
+        found
+    }
+
+    fn update_nodes(&mut self, nodes: &[Arc<Node>]) {
+        self.datacenters = nodes
+            .iter()
+            .filter_map(|node| Some((node.addr, node.datacenter.clone()?)))
+            .collect();
+        self.racks = nodes
+            .iter()
+            .filter_map(|node| Some((node.addr, node.rack.clone()?)))
+            .collect();
+    }
+
+    /// Local data center nodes are local, remote ones are remote if failover to their data
+    /// center is allowed and ignored otherwise
+    fn distance(&self, node: &Node) -> NodeDistance {
+        match node.datacenter {
+            Some(ref datacenter) if *datacenter != self.local_dc => {
+                if self.remote_dc_limit(datacenter) > 0 {
+                    NodeDistance::Remote
+                } else {
+                    NodeDistance::Ignored
+                }
+            }
+            _ => NodeDistance::Local,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestNode(SocketAddr);
+
+    impl NodeAddr for TestNode {
+        fn node_addr(&self) -> SocketAddr {
+            self.0
+        }
+    }
+
+    fn node(addr: &str, datacenter: &str, rack: &str) -> Arc<Node> {
+        Arc::new(Node {
+            addr: addr.parse().unwrap(),
+            datacenter: Some(datacenter.to_string()),
+            rack: Some(rack.to_string()),
+            host_id: None,
+            tokens: vec![],
+            schema_version: None,
+            release_version: None,
+        })
+    }
+
+    fn load_balancer(load_balancer: DcAware<TestNode>) -> DcAware<TestNode> {
+        let nodes = vec![
+            node("127.0.0.1:9042", "dc1", "r1"),
+            node("127.0.0.2:9042", "dc1", "r1"),
+            node("127.0.0.3:9042", "dc2", "r1"),
+            node("127.0.0.4:9042", "dc2", "r1"),
+            node("127.0.0.5:9042", "dc3", "r1"),
+            node("127.0.0.6:9042", "dc1", "r2"),
+        ];
+
+        let mut load_balancer = load_balancer;
+        load_balancer.update_nodes(&nodes);
+        load_balancer.init(
+            nodes
+                .iter()
+                .map(|node| Arc::new(TestNode(node.addr)))
+                .collect(),
+        );
+        load_balancer
+    }
+
+    fn plan(load_balancer: &DcAware<TestNode>) -> Vec<String> {
+        load_balancer
+            .query_plan()
+            .iter()
+            .map(|node| node.0.ip().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn prefers_local_nodes() {
+        let load_balancer = load_balancer(DcAware::new("dc1"));
+
+        assert_eq!(
+            plan(&load_balancer),
+            vec!["127.0.0.2", "127.0.0.6", "127.0.0.1"]
+        );
+        assert_eq!(
+            plan(&load_balancer),
+            vec!["127.0.0.6", "127.0.0.1", "127.0.0.2"]
+        );
+        assert_eq!(
+            load_balancer.next().unwrap().0.ip().to_string(),
+            "127.0.0.1"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_remote_nodes() {
+        let load_balancer = load_balancer(
+            DcAware::new("dc1")
+                .with_used_hosts_per_remote_dc(1)
+                .with_remote_dc_limit("dc3", 0),
+        );
+
+        assert_eq!(
+            plan(&load_balancer),
+            vec!["127.0.0.2", "127.0.0.6", "127.0.0.1", "127.0.0.4"]
+        );
+        assert_eq!(
+            plan(&load_balancer),
+            vec!["127.0.0.6", "127.0.0.1", "127.0.0.2", "127.0.0.3"]
+        );
+    }
+
+    #[test]
+    fn prefers_local_rack() {
+        let load_balancer = load_balancer(DcAware::new("dc1").with_local_rack("r2"));
+
+        assert_eq!(
+            plan(&load_balancer),
+            vec!["127.0.0.6", "127.0.0.2", "127.0.0.1"]
+        );
+        assert_eq!(
+            plan(&load_balancer),
+            vec!["127.0.0.6", "127.0.0.1", "127.0.0.2"]
+        );
+
+        let replica = load_balancer.find(|node| node.0.port() == 9042).unwrap();
+        assert_eq!(replica.0.ip().to_string(), "127.0.0.6");
+    }
+
+    #[test]
+    fn classifies_node_distances() {
+        let load_balancer: DcAware<TestNode> = DcAware::new("dc1").with_remote_dc_limit("dc2", 1);
+
+        let distance =
+            |datacenter| load_balancer.distance(&node("127.0.0.1:9042", datacenter, "r1"));
+        assert_eq!(distance("dc1"), NodeDistance::Local);
+        assert_eq!(distance("dc2"), NodeDistance::Remote);
+        assert_eq!(distance("dc3"), NodeDistance::Ignored);
+    }
+
+    #[test]
+    fn finds_local_nodes() {
+        let load_balancer = load_balancer(DcAware::new("dc2"));
+
+        assert!(load_balancer
+            .find(|node| node.0.ip().to_string() == "127.0.0.1")
+            .is_none());
+        assert!(load_balancer
+            .find(|node| node.0.ip().to_string() == "127.0.0.3")
+            .is_some());
+    }
+}
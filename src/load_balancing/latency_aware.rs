@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{LoadBalancingStrategy, NodeDistance, NodeLatencyTracker};
+use crate::cluster::{LatencyStats, Node};
+
+/// Strategy wrapping another one, which moves nodes much slower than the fastest one to the
+/// end of query plans. A node is considered slow when its average latency exceeds the
+/// exclusion threshold multiplied by the best average latency, so a single degraded node
+/// doesn't inflate tail latency.
+///
+/// Slow nodes receive fewer requests, so their statistics get stale - once no request has
+/// been measured for the retry period, a node is used normally again, which probes whether it
+/// recovered.
+#[derive(Debug)]
+pub struct LatencyAware<LB> {
+    inner: LB,
+    exclusion_threshold: f64,
+    retry_period: Duration,
+    min_measured: u64,
+}
+
+impl<LB> LatencyAware<LB> {
+    pub fn new(inner: LB) -> Self {
+        LatencyAware {
+            inner,
+            exclusion_threshold: 2.0,
+            retry_period: Duration::from_secs(10),
+            min_measured: 50,
+        }
+    }
+
+    /// Sets how many times slower than the fastest node a node needs to be to be considered
+    /// slow. Default is 2.
+    pub fn with_exclusion_threshold(mut self, exclusion_threshold: f64) -> Self {
+        self.exclusion_threshold = exclusion_threshold;
+        self
+    }
+
+    /// Sets how long after its last measured request a slow node is used normally again.
+    /// Default is 10 seconds.
+    pub fn with_retry_period(mut self, retry_period: Duration) -> Self {
+        self.retry_period = retry_period;
+        self
+    }
+
+    /// Sets how many requests to a node need to be measured before its latency is taken into
+    /// account. Default is 50.
+    pub fn with_min_measured(mut self, min_measured: u64) -> Self {
+        self.min_measured = min_measured;
+        self
+    }
+
+    /// Returns the wrapped strategy.
+    pub fn inner(&self) -> &LB {
+        &self.inner
+    }
+
+    /// Returns statistics of a node if they're recent and based on enough requests.
+    fn usable_stats(&self, stats: Option<LatencyStats>) -> Option<LatencyStats> {
+        stats.filter(|stats| {
+            stats.samples >= self.min_measured && stats.updated.elapsed() < self.retry_period
+        })
+    }
+}
+
+impl<N, LB> LoadBalancingStrategy<N> for LatencyAware<LB>
+where
+    N: NodeLatencyTracker,
+    LB: LoadBalancingStrategy<N>,
+{
+    fn init(&mut self, cluster: Vec<Arc<N>>) {
+        self.inner.init(cluster);
+    }
+
+    /// Returns the first node of the query plan
+    fn next(&self) -> Option<Arc<N>> {
+        self.query_plan().into_iter().next()
+    }
+
+    /// Returns the query plan of the wrapped strategy with slow nodes moved to the end
+    fn query_plan(&self) -> Vec<Arc<N>> {
+        let plan = self.inner.query_plan();
+        let stats: Vec<Option<LatencyStats>> = plan
+            .iter()
+            .map(|node| self.usable_stats(node.latency().stats()))
+            .collect();
+
+        let best = match stats.iter().flatten().map(|stats| stats.average).min() {
+            Some(best) => best,
+            None => return plan,
+        };
+        let limit = best.mul_f64(self.exclusion_threshold);
+
+        let (fast, slow): (Vec<_>, Vec<_>) =
+            plan.into_iter()
+                .zip(stats)
+                .partition(|(_, stats)| match stats {
+                    Some(stats) => stats.average <= limit,
+                    None => true,
+                });
+
+        fast.into_iter().chain(slow).map(|(node, _)| node).collect()
+    }
+
+    fn remove_node<F>(&mut self, filter: F)
+    where
+        F: FnMut(&N) -> bool,
+    {
+        self.inner.remove_node(filter);
+    }
+
+    fn add_node(&mut self, node: Arc<N>) {
+        self.inner.add_node(node);
+    }
+
+    fn find<F>(&self, filter: F) -> Option<Arc<N>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        self.inner.find(filter)
+    }
+
+    fn update_nodes(&mut self, nodes: &[Arc<Node>]) {
+        self.inner.update_nodes(nodes);
+    }
+
+    fn distance(&self, node: &Node) -> NodeDistance {
+        self.inner.distance(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::NodeLatency;
+    use crate::load_balancing::RoundRobin;
+
+    struct TestNode(&'static str, NodeLatency);
+
+    impl NodeLatencyTracker for TestNode {
+        fn latency(&self) -> &NodeLatency {
+            &self.1
+        }
+    }
+
+    fn node(name: &'static str, latency_ms: u64, samples: u64) -> Arc<TestNode> {
+        let node = TestNode(name, NodeLatency::default());
+        for _ in 0..samples {
+            node.1.record(Duration::from_millis(latency_ms));
+        }
+
+        Arc::new(node)
+    }
+
+    fn plan(load_balancer: &LatencyAware<RoundRobin<TestNode>>) -> Vec<&'static str> {
+        load_balancer
+            .query_plan()
+            .iter()
+            .map(|node| node.0)
+            .collect()
+    }
+
+    #[test]
+    fn moves_slow_nodes_to_the_end() {
+        let nodes = vec![node("a", 10, 5), node("b", 50, 5), node("c", 15, 5)];
+        let load_balancer = LatencyAware::new(RoundRobin::from(nodes)).with_min_measured(5);
+
+        assert_eq!(plan(&load_balancer), vec!["c", "a", "b"]);
+        assert_eq!(plan(&load_balancer), vec!["c", "a", "b"]);
+        assert_eq!(plan(&load_balancer), vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn ignores_insufficient_and_stale_stats() {
+        let nodes = vec![node("a", 10, 5), node("b", 50, 1)];
+        let load_balancer = LatencyAware::new(RoundRobin::from(nodes)).with_min_measured(5);
+        assert_eq!(plan(&load_balancer), vec!["b", "a"]);
+
+        let nodes = vec![node("a", 10, 5), node("b", 50, 5)];
+        let load_balancer = LatencyAware::new(RoundRobin::from(nodes))
+            .with_min_measured(5)
+            .with_retry_period(Duration::from_secs(0));
+        assert_eq!(plan(&load_balancer), vec!["b", "a"]);
+    }
+}
@@ -1,20 +1,89 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 
+use crate::cluster::{ConnectionPool, Node, NodeLatency};
+
+mod dc_aware;
+mod latency_aware;
 mod random;
 mod round_robin;
 mod single_node;
 
+pub use crate::load_balancing::dc_aware::DcAware;
+pub use crate::load_balancing::latency_aware::LatencyAware;
 pub use crate::load_balancing::random::Random;
 pub use crate::load_balancing::round_robin::RoundRobin;
 pub use crate::load_balancing::single_node::SingleNode;
 
+/// Strategy picking nodes for requests. Custom strategies can be used with sessions by
+/// implementing this trait.
 pub trait LoadBalancingStrategy<N>: Sized {
     fn init(&mut self, cluster: Vec<Arc<N>>);
     fn next(&self) -> Option<Arc<N>>;
+    /// Returns nodes to try for a single request, most preferred first. Nodes further in the
+    /// plan are used when preceding ones are unavailable. The default plan consists of the node
+    /// returned by `next()` only.
+    fn query_plan(&self) -> Vec<Arc<N>> {
+        self.next().into_iter().collect()
+    }
     fn remove_node<F>(&mut self, _filter: F)
     where
         F: FnMut(&N) -> bool,
     {
         // default implementation does nothing
     }
+    fn add_node(&mut self, _node: Arc<N>) {
+        // default implementation does nothing
+    }
+    /// Returns a node matching `filter` which the strategy would pick, e.g. a replica of a
+    /// partition. Strategies which don't support picking specific nodes return `None`.
+    fn find<F>(&self, _filter: F) -> Option<Arc<N>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        None
+    }
+    /// Called with metadata of cluster nodes whenever they're discovered, so strategies can
+    /// take node location into account.
+    fn update_nodes(&mut self, _nodes: &[Arc<Node>]) {
+        // default implementation does nothing
+    }
+    /// Returns how far given discovered node is, which decides the size of its pool. All
+    /// nodes are local by default.
+    fn distance(&self, _node: &Node) -> NodeDistance {
+        NodeDistance::Local
+    }
+}
+
+/// Distance of a discovered node, as seen by a load balancing strategy. Sessions discovering
+/// cluster nodes open full pools to local nodes, a single connection to remote ones, used as
+/// a fallback only, and no connections to ignored ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NodeDistance {
+    Local,
+    Remote,
+    Ignored,
+}
+
+/// Node with a known address, which strategies can match with cluster metadata.
+pub trait NodeAddr {
+    fn node_addr(&self) -> SocketAddr;
+}
+
+impl<M: bb8::ManageConnection> NodeAddr for ConnectionPool<M> {
+    fn node_addr(&self) -> SocketAddr {
+        self.get_addr()
+    }
+}
+
+/// Node whose request latencies are measured.
+pub trait NodeLatencyTracker {
+    fn latency(&self) -> &NodeLatency;
+}
+
+impl<M: bb8::ManageConnection> NodeLatencyTracker for ConnectionPool<M> {
+    fn latency(&self) -> &NodeLatency {
+        ConnectionPool::latency(self)
+    }
 }
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 use rand;
+use rand::{thread_rng, Rng};
 
 use super::LoadBalancingStrategy;
 
@@ -41,6 +42,13 @@ impl<N> LoadBalancingStrategy<N> for Random<N> where N: Sync {
         self.cluster.get(Self::rnd_idx((0, len))).map(|node| node.clone())
     }
 
+    /// Returns all nodes in random order
+    fn query_plan(&self) -> Vec<Arc<N>> {
+        let mut plan = self.cluster.clone();
+        thread_rng().shuffle(&mut plan);
+        plan
+    }
+
     fn remove_node<F>(&mut self, mut filter: F)
     where
         F: FnMut(&N) -> bool,
@@ -49,6 +57,17 @@ impl<N> LoadBalancingStrategy<N> for Random<N> where N: Sync {
             self.cluster.remove(i);
         }
     }
+
+    fn add_node(&mut self, node: Arc<N>) {
+        self.cluster.push(node);
+    }
+
+    fn find<F>(&self, mut filter: F) -> Option<Arc<N>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        self.cluster.iter().find(|node| filter(node)).cloned()
+    }
 }
 
 #[cfg(test)]
@@ -65,6 +84,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn random_query_plan() {
+        let nodes = vec!["a", "b", "c", "d", "e", "f", "g"];
+        let load_balancer = Random::from(nodes.iter().map(|value| Arc::new(*value)).collect::<Vec<Arc<&str>>>());
+
+        let mut plan: Vec<&str> = load_balancer.query_plan().iter().map(|node| **node).collect();
+        plan.sort();
+        assert_eq!(plan, nodes);
+    }
+
     #[test]
     fn remove_from_random() {
         let nodes = vec!["a"];
@@ -40,6 +40,20 @@ impl<N> LoadBalancingStrategy<N> for RoundRobin<N> where N: Sync + Send {
         self.cluster.get(next_idx).map(|node| node.clone())
     }
 
+    /// Returns all nodes, starting with the next one
+    fn query_plan(&self) -> Vec<Arc<N>> {
+        if self.cluster.is_empty() {
+            return vec![];
+        }
+
+        let mut prev_idx = self.prev_idx.lock().unwrap();
+        let next_idx = (*prev_idx + 1) % self.cluster.len();
+        *prev_idx = next_idx;
+
+        let (head, tail) = self.cluster.split_at(next_idx);
+        tail.iter().chain(head.iter()).cloned().collect()
+    }
+
     fn remove_node<F>(&mut self, mut filter: F)
     where
         F: FnMut(&N) -> bool,
@@ -48,6 +62,17 @@ impl<N> LoadBalancingStrategy<N> for RoundRobin<N> where N: Sync + Send {
             self.cluster.remove(i);
         }
     }
+
+    fn add_node(&mut self, node: Arc<N>) {
+        self.cluster.push(node);
+    }
+
+    fn find<F>(&self, mut filter: F) -> Option<Arc<N>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        self.cluster.iter().find(|node| filter(node)).cloned()
+    }
 }
 
 #[cfg(test)]
@@ -64,6 +89,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn round_robin_query_plan() {
+        let nodes = vec!["a", "b", "c"];
+        let load_balancer = RoundRobin::from(nodes.iter().map(|value| Arc::new(*value)).collect::<Vec<Arc<&str>>>());
+        fn plan(load_balancer: &RoundRobin<&'static str>) -> Vec<&'static str> {
+            load_balancer.query_plan().iter().map(|node| **node).collect()
+        }
+
+        assert_eq!(plan(&load_balancer), vec!["b", "c", "a"]);
+        assert_eq!(plan(&load_balancer), vec!["c", "a", "b"]);
+        assert!(plan(&RoundRobin::new()).is_empty());
+    }
+
     #[test]
     fn remove_from_round_robin() {
         let nodes = vec!["a", "b"];
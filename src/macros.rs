@@ -1,27 +1,24 @@
+/// Builds `QueryValues` of values of any types which convert into `Value`, i.e. built-in and
+/// `ToCqlValue` types, collections of them, `Option`s, bound as nulls for `None`, and `Unset`.
+/// Values are given in the order of their bind markers, or with names of their bind markers:
+///
+/// ```ignore
+/// let values = query_values!(id, "name", 42, Some(vec![1, 2]));
+/// let values = query_values!("id" => id, "nickname" => None::<String>);
+/// ```
 #[macro_export]
 macro_rules! query_values {
-    ($($value:expr),*) => {
-        {
-            use cdrs_tokio::types::value::Value;
-            use cdrs_tokio::query::QueryValues;
-            let mut values: Vec<Value> = Vec::new();
-            $(
-                values.push($value.into());
-            )*
-            QueryValues::SimpleValues(values)
-        }
+    ($($value:expr),* $(,)?) => {
+        $crate::query::QueryValues::SimpleValues(vec![
+            $($crate::types::value::Value::from($value)),*
+        ])
     };
-    ($($name:expr => $value:expr),*) => {
-        {
-            use cdrs_tokio::types::value::Value;
-            use cdrs_tokio::query::QueryValues;
-            use std::collections::HashMap;
-            let mut values: HashMap<String, Value> = HashMap::new();
-            $(
-                values.insert($name.to_string(), $value.into());
-            )*
-            QueryValues::NamedValues(values)
-        }
+    ($($name:expr => $value:expr),* $(,)?) => {
+        $crate::query::QueryValues::NamedValues(
+            vec![$(($name.to_string(), $crate::types::value::Value::from($value))),*]
+                .into_iter()
+                .collect(),
+        )
     };
 }
 
@@ -36,6 +33,28 @@ macro_rules! builder_opt_field {
 }
 
 macro_rules! list_as_rust {
+    // types which are not supported by `as_rust_type!` are decoded with given function
+    ($into_type:ty, $decode:path) => (
+        impl AsRustType<Vec<$into_type>> for List {
+            fn as_rust_type(&self) -> Result<Option<Vec<$into_type>>> {
+                match self.metadata.value {
+                    Some(ColTypeOptionValue::CList(ref type_option)) |
+                    Some(ColTypeOptionValue::CSet(ref type_option)) => self
+                        .data
+                        .iter()
+                        .map(|bytes| {
+                            $decode(type_option.as_ref(), bytes)?
+                                .ok_or_else(|| Error::General("List item is null".into()))
+                        })
+                        .collect::<Result<_>>()
+                        .map(Some),
+                    _ => Err(Error::General(format!("Invalid conversion. \
+                            Cannot convert {:?} into List (valid types: List, Set).",
+                            self.metadata.value)))
+                }
+            }
+        }
+    );
     ($($into_type:tt)+) => (
         impl AsRustType<Vec<$($into_type)+>> for List {
             fn as_rust_type(&self) -> Result<Option<Vec<$($into_type)+>>> {
@@ -96,7 +115,7 @@ macro_rules! into_rust_by_name {
         impl IntoRustByName<$($into_type)+> for Row {
             fn get_by_name(&self, name: &str) -> Result<Option<$($into_type)+>> {
                 self.get_col_spec_by_name(name)
-                    .ok_or(column_is_empty_err(name))
+                    .ok_or_else(|| column_not_found_err(name))
                     .and_then(|(col_spec, cbytes)| {
                         let ref col_type = col_spec.col_type;
                         as_rust_type!(col_type, cbytes, $($into_type)+)
@@ -108,7 +127,7 @@ macro_rules! into_rust_by_name {
         impl IntoRustByName<$($into_type)+> for UDT {
             fn get_by_name(&self, name: &str) -> Result<Option<$($into_type)+>> {
                 self.data.get(name)
-                .ok_or(column_is_empty_err(name))
+                .ok_or_else(|| column_not_found_err(name))
                 .and_then(|v| {
                     let &(ref col_type, ref bytes) = v;
                     let converted = as_rust_type!(col_type, bytes, $($into_type)+);
@@ -117,6 +136,25 @@ macro_rules! into_rust_by_name {
             }
         }
     );
+    // types which are not supported by `as_rust_type!` are decoded with given function
+    (Row, $into_type:ty, $decode:path) => (
+        impl IntoRustByName<$into_type> for Row {
+            fn get_by_name(&self, name: &str) -> Result<Option<$into_type>> {
+                self.get_col_spec_by_name(name)
+                    .ok_or_else(|| column_not_found_err(name))
+                    .and_then(|(col_spec, cbytes)| $decode(&col_spec.col_type, cbytes))
+            }
+        }
+    );
+    (UDT, $into_type:ty, $decode:path) => (
+        impl IntoRustByName<$into_type> for UDT {
+            fn get_by_name(&self, name: &str) -> Result<Option<$into_type>> {
+                self.data.get(name)
+                    .ok_or_else(|| column_not_found_err(name))
+                    .and_then(|&(ref col_type, ref bytes)| $decode(col_type, bytes))
+            }
+        }
+    );
 }
 
 macro_rules! into_rust_by_index {
@@ -125,7 +163,7 @@ macro_rules! into_rust_by_index {
             fn get_by_index(&self, index: usize) -> Result<Option<$($into_type)+>> {
                 self.data
                     .get(index)
-                    .ok_or(column_is_empty_err(index))
+                    .ok_or_else(|| column_not_found_err(index))
                     .and_then(|v| {
                         let &(ref col_type, ref bytes) = v;
                         let converted = as_rust_type!(col_type, bytes, $($into_type)+);
@@ -138,7 +176,7 @@ macro_rules! into_rust_by_index {
         impl IntoRustByIndex<$($into_type)+> for Row {
             fn get_by_index(&self, index: usize) -> Result<Option<$($into_type)+>> {
                 self.get_col_spec_by_index(index)
-                    .ok_or(column_is_empty_err(index))
+                    .ok_or_else(|| column_not_found_err(index))
                     .and_then(|(col_spec, cbytes)| {
                         let ref col_type = col_spec.col_type;
                         as_rust_type!(col_type, cbytes, $($into_type)+)
@@ -146,15 +184,43 @@ macro_rules! into_rust_by_index {
             }
         }
     );
+    // types which are not supported by `as_rust_type!` are decoded with given function
+    (Tuple, $into_type:ty, $decode:path) => (
+        impl IntoRustByIndex<$into_type> for Tuple {
+            fn get_by_index(&self, index: usize) -> Result<Option<$into_type>> {
+                self.data
+                    .get(index)
+                    .ok_or_else(|| column_not_found_err(index))
+                    .and_then(|&(ref col_type, ref bytes)| $decode(col_type, bytes))
+            }
+        }
+    );
+    (Row, $into_type:ty, $decode:path) => (
+        impl IntoRustByIndex<$into_type> for Row {
+            fn get_by_index(&self, index: usize) -> Result<Option<$into_type>> {
+                self.get_col_spec_by_index(index)
+                    .ok_or_else(|| column_not_found_err(index))
+                    .and_then(|(col_spec, cbytes)| $decode(&col_spec.col_type, cbytes))
+            }
+        }
+    );
 }
 
 macro_rules! as_res_opt {
-    ($data_value:ident, $deserialize:expr) => {
+    // types whose empty values are empty values of Rust types, i.e. text and blobs
+    ($data_value:ident, $deserialize:expr, empty) => {
         match $data_value.as_plain() {
             Some(ref bytes) => ($deserialize)(bytes).map(|v| Some(v)).map_err(Into::into),
             None => Ok(None),
         }
     };
+    ($data_value:ident, $deserialize:expr) => {
+        match $data_value.non_empty_slice() {
+            Ok(Some(bytes)) => ($deserialize)(bytes).map(|v| Some(v)).map_err(Into::into),
+            Ok(None) => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
 }
 
 /// Decodes any Cassandra data type into the corresponding Rust type,
@@ -163,19 +229,20 @@ macro_rules! as_res_opt {
 macro_rules! as_rust_type {
     ($data_type_option:ident, $data_value:ident, Blob) => {
         match $data_type_option.id {
-            ColType::Blob => as_res_opt!($data_value, decode_blob),
+            // values of custom types are passed through as they are
+            ColType::Blob | ColType::Custom => as_res_opt!($data_value, decode_blob, empty),
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
-                 Cannot convert {:?} into Vec<u8> (valid types: Blob).",
+                 Cannot convert {:?} into Vec<u8> (valid types: Blob, Custom).",
                 $data_type_option.id
             ))),
         }
     };
     ($data_type_option:ident, $data_value:ident, String) => {
         match $data_type_option.id {
-            ColType::Custom => as_res_opt!($data_value, decode_custom),
-            ColType::Ascii => as_res_opt!($data_value, decode_ascii),
-            ColType::Varchar => as_res_opt!($data_value, decode_varchar),
+            ColType::Custom => as_res_opt!($data_value, decode_custom, empty),
+            ColType::Ascii => as_res_opt!($data_value, decode_ascii, empty),
+            ColType::Varchar => as_res_opt!($data_value, decode_varchar, empty),
             // TODO: clarify when to use decode_text.
             // it's not mentioned in
             // https://github.com/apache/cassandra/blob/trunk/doc/native_protocol_v4.spec#L582
@@ -204,10 +271,30 @@ macro_rules! as_rust_type {
             ColType::Time => as_res_opt!($data_value, decode_time),
             ColType::Varint => as_res_opt!($data_value, decode_varint),
             ColType::Counter => as_res_opt!($data_value, decode_bigint),
+            // smaller integers are widened
+            ColType::Int => as_res_opt!($data_value, decode_int).map(|value| value.map(i64::from)),
+            ColType::Smallint => as_res_opt!($data_value, decode_smallint).map(|value| value.map(i64::from)),
+            ColType::Tinyint => as_res_opt!($data_value, decode_tinyint).map(|value| value.map(i64::from)),
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
                  Cannot convert {:?} into i64 (valid types: Bigint, Timestamp, Time, Variant,\
-                 Counter).",
+                 Counter, Int, Smallint, Tinyint).",
+                $data_type_option.id
+            ))),
+        }
+    };
+    ($data_type_option:ident, $data_value:ident, i128) => {
+        match $data_type_option.id {
+            ColType::Varint => as_res_opt!($data_value, decode_varint_i128),
+            // smaller integers are widened
+            ColType::Bigint | ColType::Counter => as_res_opt!($data_value, decode_bigint).map(|value| value.map(i128::from)),
+            ColType::Int => as_res_opt!($data_value, decode_int).map(|value| value.map(i128::from)),
+            ColType::Smallint => as_res_opt!($data_value, decode_smallint).map(|value| value.map(i128::from)),
+            ColType::Tinyint => as_res_opt!($data_value, decode_tinyint).map(|value| value.map(i128::from)),
+            _ => Err(Error::General(format!(
+                "Invalid conversion. \
+                 Cannot convert {:?} into i128 (valid types: Varint, Bigint, Counter, Int,\
+                 Smallint, Tinyint).",
                 $data_type_option.id
             ))),
         }
@@ -216,9 +303,12 @@ macro_rules! as_rust_type {
         match $data_type_option.id {
             ColType::Int => as_res_opt!($data_value, decode_int),
             ColType::Date => as_res_opt!($data_value, decode_date),
+            // smaller integers are widened
+            ColType::Smallint => as_res_opt!($data_value, decode_smallint).map(|value| value.map(i32::from)),
+            ColType::Tinyint => as_res_opt!($data_value, decode_tinyint).map(|value| value.map(i32::from)),
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
-                 Cannot convert {:?} into i32 (valid types: Int, Date).",
+                 Cannot convert {:?} into i32 (valid types: Int, Date, Smallint, Tinyint).",
                 $data_type_option.id
             ))),
         }
@@ -226,9 +316,11 @@ macro_rules! as_rust_type {
     ($data_type_option:ident, $data_value:ident, i16) => {
         match $data_type_option.id {
             ColType::Smallint => as_res_opt!($data_value, decode_smallint),
+            // smaller integers are widened
+            ColType::Tinyint => as_res_opt!($data_value, decode_tinyint).map(|value| value.map(i16::from)),
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
-                 Cannot convert {:?} into i16 (valid types: Smallint).",
+                 Cannot convert {:?} into i16 (valid types: Smallint, Tinyint).",
                 $data_type_option.id
             ))),
         }
@@ -331,26 +423,30 @@ macro_rules! as_rust_type {
     };
     ($data_type_option:ident, $data_value:ident, List) => {
         match $data_type_option.id {
-            ColType::List | ColType::Set => match $data_value.as_slice() {
-                Some(ref bytes) => decode_list(bytes)
+            ColType::List | ColType::Set => match $data_value.non_empty_slice() {
+                Ok(Some(ref bytes)) => decode_list(bytes)
                     .map(|data| Some(List::new(data, $data_type_option.clone())))
                     .map_err(Into::into),
-                None => Ok(None),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
             },
+            // vectors are sent as custom types
+            ColType::Custom => crate::types::vector::as_list(&$data_type_option, &$data_value),
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
-                 Cannot convert {:?} into List (valid types: List, Set).",
+                 Cannot convert {:?} into List (valid types: List, Set, vector).",
                 $data_type_option.id
             ))),
         }
     };
     ($data_type_option:ident, $data_value:ident, Map) => {
         match $data_type_option.id {
-            ColType::Map => match $data_value.as_slice() {
-                Some(ref bytes) => decode_map(bytes)
+            ColType::Map => match $data_value.non_empty_slice() {
+                Ok(Some(ref bytes)) => decode_map(bytes)
                     .map(|data| Some(Map::new(data, $data_type_option.clone())))
                     .map_err(Into::into),
-                None => Ok(None),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
             },
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
@@ -364,11 +460,12 @@ macro_rules! as_rust_type {
             ColTypeOption {
                 id: ColType::Udt,
                 value: Some(ColTypeOptionValue::UdtType(ref list_type_option)),
-            } => match $data_value.as_slice() {
-                Some(ref bytes) => decode_udt(bytes, list_type_option.descriptions.len())
+            } => match $data_value.non_empty_slice() {
+                Ok(Some(ref bytes)) => decode_udt(bytes, list_type_option.descriptions.len())
                     .map(|data| Some(UDT::new(data, list_type_option)))
                     .map_err(Into::into),
-                None => Ok(None),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
             },
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
@@ -382,11 +479,12 @@ macro_rules! as_rust_type {
             ColTypeOption {
                 id: ColType::Tuple,
                 value: Some(ColTypeOptionValue::TupleType(ref list_type_option)),
-            } => match $data_value.as_slice() {
-                Some(ref bytes) => decode_tuple(bytes, list_type_option.types.len())
+            } => match $data_value.non_empty_slice() {
+                Ok(Some(ref bytes)) => decode_tuple(bytes, list_type_option.types.len())
                     .map(|data| Some(Tuple::new(data, list_type_option)))
                     .map_err(Into::into),
-                None => Ok(None),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
             },
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
@@ -397,15 +495,16 @@ macro_rules! as_rust_type {
     };
     ($data_type_option:ident, $data_value:ident, PrimitiveDateTime) => {
         match $data_type_option.id {
-            ColType::Timestamp => match $data_value.as_slice() {
-                Some(ref bytes) => decode_timestamp(bytes)
+            ColType::Timestamp => match $data_value.non_empty_slice() {
+                Ok(Some(ref bytes)) => decode_timestamp(bytes)
                     .map(|ts| {
                         let unix_epoch = time::date!(1970-01-01).midnight();
                         let tm = unix_epoch + time::Duration::new(ts / 1_000, (ts % 1_000 * 1_000_000) as i32);
                         Some(tm)
                     })
                     .map_err(Into::into),
-                None => Ok(None),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
             },
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
@@ -416,9 +515,10 @@ macro_rules! as_rust_type {
     };
     ($data_type_option:ident, $data_value:ident, Decimal) => {
         match $data_type_option.id {
-            ColType::Decimal => match $data_value.as_slice() {
-                Some(ref bytes) => decode_decimal(bytes).map(|d| Some(d)).map_err(Into::into),
-                None => Ok(None),
+            ColType::Decimal => match $data_value.non_empty_slice() {
+                Ok(Some(ref bytes)) => decode_decimal(bytes).map(|d| Some(d)).map_err(Into::into),
+                Ok(None) => Ok(None),
+                Err(err) => Err(err),
             },
             _ => Err(Error::General(format!(
                 "Invalid conversion. \
@@ -0,0 +1,333 @@
+//! Tunneling connections through SOCKS5 or HTTP CONNECT proxies.
+//!
+//! Proxies are configured per node with `proxy()` method of node config builders. The driver
+//! connects to the proxy, asks it to open a tunnel to the node and then talks CQL through that
+//! tunnel.
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Supported proxy protocols.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProxyKind {
+    /// SOCKS version 5 proxy.
+    Socks5,
+    /// HTTP proxy supporting the CONNECT method.
+    HttpConnect,
+}
+
+/// Credentials used for authenticating to a proxy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Proxy configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProxyConfig {
+    pub kind: ProxyKind,
+    /// Address of the proxy server.
+    pub addr: SocketAddr,
+    /// Optional credentials. Username/password authentication is used for SOCKS5 and Basic
+    /// authentication for HTTP CONNECT.
+    pub credentials: Option<ProxyCredentials>,
+}
+
+impl ProxyConfig {
+    /// Creates SOCKS5 proxy configuration without authentication.
+    pub fn socks5(addr: SocketAddr) -> Self {
+        ProxyConfig {
+            kind: ProxyKind::Socks5,
+            addr,
+            credentials: None,
+        }
+    }
+
+    /// Creates HTTP CONNECT proxy configuration without authentication.
+    pub fn http_connect(addr: SocketAddr) -> Self {
+        ProxyConfig {
+            kind: ProxyKind::HttpConnect,
+            addr,
+            credentials: None,
+        }
+    }
+
+    /// Sets credentials used for authenticating to the proxy.
+    pub fn with_credentials<U: ToString, P: ToString>(mut self, username: U, password: P) -> Self {
+        self.credentials = Some(ProxyCredentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        self
+    }
+}
+
+/// Asks the proxy on the other side of `stream` to open a tunnel to `target`.
+pub(crate) async fn open_tunnel<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    config: &ProxyConfig,
+    target: SocketAddr,
+) -> io::Result<()> {
+    match config.kind {
+        ProxyKind::Socks5 => socks5_connect(stream, config.credentials.as_ref(), target).await,
+        ProxyKind::HttpConnect => {
+            http_connect(stream, config.credentials.as_ref(), target).await
+        }
+    }
+}
+
+const SOCKS_VERSION: u8 = 5;
+const SOCKS_NO_AUTH: u8 = 0;
+const SOCKS_USERNAME_PASSWORD: u8 = 2;
+const SOCKS_NO_ACCEPTABLE_METHOD: u8 = 0xff;
+const SOCKS_USERNAME_PASSWORD_VERSION: u8 = 1;
+const SOCKS_CMD_CONNECT: u8 = 1;
+const SOCKS_ATYP_IPV4: u8 = 1;
+const SOCKS_ATYP_DOMAIN: u8 = 3;
+const SOCKS_ATYP_IPV6: u8 = 4;
+
+async fn socks5_connect<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    credentials: Option<&ProxyCredentials>,
+    target: SocketAddr,
+) -> io::Result<()> {
+    let greeting = match credentials {
+        Some(_) => vec![SOCKS_VERSION, 2, SOCKS_NO_AUTH, SOCKS_USERNAME_PASSWORD],
+        None => vec![SOCKS_VERSION, 1, SOCKS_NO_AUTH],
+    };
+    stream.write_all(&greeting).await?;
+
+    let mut method = [0u8; 2];
+    stream.read_exact(&mut method).await?;
+    if method[0] != SOCKS_VERSION {
+        return Err(proxy_error("Invalid SOCKS5 proxy response"));
+    }
+
+    match (method[1], credentials) {
+        (SOCKS_NO_AUTH, _) => {}
+        (SOCKS_USERNAME_PASSWORD, Some(credentials)) => {
+            socks5_authenticate(stream, credentials).await?
+        }
+        (SOCKS_NO_ACCEPTABLE_METHOD, _) => {
+            return Err(proxy_error(
+                "SOCKS5 proxy doesn't accept any of offered authentication methods",
+            ))
+        }
+        (method, _) => {
+            return Err(proxy_error(&format!(
+                "SOCKS5 proxy selected unsupported authentication method {}",
+                method
+            )))
+        }
+    }
+
+    let mut request = vec![SOCKS_VERSION, SOCKS_CMD_CONNECT, 0];
+    match target {
+        SocketAddr::V4(addr) => {
+            request.push(SOCKS_ATYP_IPV4);
+            request.extend_from_slice(&addr.ip().octets());
+        }
+        SocketAddr::V6(addr) => {
+            request.push(SOCKS_ATYP_IPV6);
+            request.extend_from_slice(&addr.ip().octets());
+        }
+    }
+    request.extend_from_slice(&target.port().to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        return Err(proxy_error("Invalid SOCKS5 proxy response"));
+    }
+
+    if reply[1] != 0 {
+        return Err(proxy_error(&format!(
+            "SOCKS5 proxy failed to connect to {} - reply code {}",
+            target, reply[1]
+        )));
+    }
+
+    // skip bound address and port
+    let bound_addr_len = match reply[3] {
+        SOCKS_ATYP_IPV4 => 4,
+        SOCKS_ATYP_IPV6 => 16,
+        SOCKS_ATYP_DOMAIN => stream.read_u8().await? as usize,
+        _ => return Err(proxy_error("Invalid SOCKS5 proxy response")),
+    };
+    let mut bound_addr = vec![0u8; bound_addr_len + 2];
+    stream.read_exact(&mut bound_addr).await?;
+
+    Ok(())
+}
+
+async fn socks5_authenticate<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    credentials: &ProxyCredentials,
+) -> io::Result<()> {
+    let username = credentials.username.as_bytes();
+    let password = credentials.password.as_bytes();
+    if username.len() > u8::MAX as usize || password.len() > u8::MAX as usize {
+        return Err(proxy_error("SOCKS5 proxy username and password must be at most 255 bytes"));
+    }
+
+    let mut request = vec![SOCKS_USERNAME_PASSWORD_VERSION, username.len() as u8];
+    request.extend_from_slice(username);
+    request.push(password.len() as u8);
+    request.extend_from_slice(password);
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(proxy_error("SOCKS5 proxy authentication failed"));
+    }
+
+    Ok(())
+}
+
+/// Maximum size of HTTP proxy response headers.
+const MAX_HTTP_RESPONSE_SIZE: usize = 8192;
+
+async fn http_connect<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    credentials: Option<&ProxyCredentials>,
+    target: SocketAddr,
+) -> io::Result<()> {
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if let Some(credentials) = credentials {
+        let token = base64::encode(format!("{}:{}", credentials.username, credentials.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // read byte by byte in order not to consume any data past the headers
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_HTTP_RESPONSE_SIZE {
+            return Err(proxy_error("HTTP proxy response is too long"));
+        }
+
+        response.push(stream.read_u8().await?);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1);
+    if !status_line.starts_with("HTTP/1.") || status != Some("200") {
+        return Err(proxy_error(&format!(
+            "HTTP proxy failed to connect to {}: {}",
+            target, status_line
+        )));
+    }
+
+    Ok(())
+}
+
+fn proxy_error(message: &str) -> io::Error {
+    io::Error::other(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+
+    #[tokio::test]
+    async fn socks5_tunnel_with_credentials() {
+        let (mut client, mut proxy) = duplex(1024);
+        let target: SocketAddr = "10.0.0.1:9042".parse().unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut greeting = [0u8; 4];
+            proxy.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [5, 2, 0, 2]);
+            proxy.write_all(&[5, 2]).await.unwrap();
+
+            let mut auth = [0u8; 11];
+            proxy.read_exact(&mut auth).await.unwrap();
+            assert_eq!(&auth, b"\x01\x04user\x04pass");
+            proxy.write_all(&[1, 0]).await.unwrap();
+
+            let mut request = [0u8; 10];
+            proxy.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, [5, 1, 0, 1, 10, 0, 0, 1, 0x23, 0x52]);
+            proxy.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await.unwrap();
+        });
+
+        let config = ProxyConfig::socks5("127.0.0.1:1080".parse().unwrap())
+            .with_credentials("user", "pass");
+        open_tunnel(&mut client, &config, target).await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn socks5_connection_refused() {
+        let (mut client, mut proxy) = duplex(1024);
+
+        tokio::spawn(async move {
+            let mut greeting = [0u8; 3];
+            proxy.read_exact(&mut greeting).await.unwrap();
+            proxy.write_all(&[5, 0]).await.unwrap();
+
+            let mut request = [0u8; 10];
+            proxy.read_exact(&mut request).await.unwrap();
+            proxy.write_all(&[5, 5, 0, 1, 0, 0, 0, 0, 0, 0]).await.unwrap();
+        });
+
+        let config = ProxyConfig::socks5("127.0.0.1:1080".parse().unwrap());
+        let result = open_tunnel(&mut client, &config, "10.0.0.1:9042".parse().unwrap()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn http_connect_tunnel() {
+        let (mut client, mut proxy) = duplex(1024);
+
+        let server = tokio::spawn(async move {
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(proxy.read_u8().await.unwrap());
+            }
+
+            let request = String::from_utf8(request).unwrap();
+            assert!(request.starts_with("CONNECT 10.0.0.1:9042 HTTP/1.1\r\n"));
+            assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+
+            proxy
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let config = ProxyConfig::http_connect("127.0.0.1:3128".parse().unwrap())
+            .with_credentials("user", "pass");
+        open_tunnel(&mut client, &config, "10.0.0.1:9042".parse().unwrap())
+            .await
+            .unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn http_connect_forbidden() {
+        let (mut client, mut proxy) = duplex(1024);
+
+        tokio::spawn(async move {
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(proxy.read_u8().await.unwrap());
+            }
+
+            proxy
+                .write_all(b"HTTP/1.1 403 Forbidden\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let config = ProxyConfig::http_connect("127.0.0.1:3128".parse().unwrap());
+        let result = open_tunnel(&mut client, &config, "10.0.0.1:9042".parse().unwrap()).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,432 @@
+use std::panic::Location;
+use std::time::Duration;
+
+use crate::consistency::Consistency;
+use crate::error::{Error as CError, Result as CResult};
+use crate::frame::frame_batch::{BatchQuery, BatchQuerySubj, BatchType, BodyReqBatch};
+use crate::query::{PreparedQuery, QueryFlags, QueryValues, RequestPriority};
+use crate::types::{CBytesShort, CStringLong};
+
+pub type QueryBatch = BodyReqBatch;
+
+#[deprecated(note = "`BatchQueryBuilder` is renamed to `BatchBuilder`.")]
+pub type BatchQueryBuilder = BatchBuilder;
+
+/// Builder of batches mixing simple and prepared statements, each with its own values.
+#[derive(Debug)]
+pub struct BatchBuilder {
+    batch_type: BatchType,
+    queries: Vec<BatchQuery>,
+    /// Texts of the queries, if known, used for validating counter batches.
+    query_texts: Vec<Option<String>>,
+    consistency: Option<Consistency>,
+    serial_consistency: Option<Consistency>,
+    timestamp: Option<i64>,
+    is_idempotent: bool,
+    deadline: Option<Duration>,
+    priority: RequestPriority,
+    routing: Option<BatchRouting>,
+    explicit_routing: bool,
+}
+
+/// Partition a batch is routed to.
+#[derive(Debug)]
+struct BatchRouting {
+    keyspace: String,
+    table: Option<String>,
+    routing_key: Vec<u8>,
+}
+
+impl Default for BatchBuilder {
+    fn default() -> Self {
+        BatchBuilder::new()
+    }
+}
+
+impl BatchBuilder {
+    pub fn new() -> BatchBuilder {
+        BatchBuilder {
+            batch_type: BatchType::Logged,
+            queries: vec![],
+            query_texts: vec![],
+            consistency: None,
+            serial_consistency: None,
+            timestamp: None,
+            is_idempotent: false,
+            deadline: None,
+            priority: Default::default(),
+            routing: None,
+            explicit_routing: false,
+        }
+    }
+
+    /// Sets type of the batch. Default is `Logged`.
+    pub fn batch_type(mut self, batch_type: BatchType) -> Self {
+        self.batch_type = batch_type;
+        self
+    }
+
+    /// Add a query (non-prepared one)
+    pub fn add_query<T: Into<String>, V: Into<QueryValues>>(mut self, query: T, values: V) -> Self {
+        let query = query.into();
+        self.query_texts.push(Some(query.clone()));
+        self.queries.push(BatchQuery {
+            is_prepared: false,
+            subject: BatchQuerySubj::QueryString(CStringLong::new(query)),
+            values: values.into(),
+        });
+        self
+    }
+
+    /// Add a prepared query. Batches starting with a prepared query are routed to nodes storing
+    /// its partition, if its partition key is known and bound.
+    pub fn add_prepared<V: Into<QueryValues>>(
+        mut self,
+        prepared: &PreparedQuery,
+        values: V,
+    ) -> Self {
+        let values = values.into();
+
+        if self.queries.is_empty() && !self.explicit_routing {
+            self.routing = prepared
+                .keyspace
+                .clone()
+                .zip(prepared.routing_key(&values))
+                .map(|(keyspace, routing_key)| BatchRouting {
+                    keyspace,
+                    table: prepared.table.clone(),
+                    routing_key,
+                });
+        }
+
+        self.query_texts.push(prepared.query.clone());
+        self.queries.push(BatchQuery {
+            is_prepared: true,
+            subject: BatchQuerySubj::PreparedId(prepared.id.clone()),
+            values,
+        });
+        self
+    }
+
+    /// Add a query (prepared one) by its id. Prefer `add_prepared()`, which allows validating
+    /// counter batches.
+    pub fn add_query_prepared<V: Into<QueryValues>>(
+        mut self,
+        query_id: CBytesShort,
+        values: V,
+    ) -> Self {
+        self.query_texts.push(None);
+        self.queries.push(BatchQuery {
+            is_prepared: true,
+            subject: BatchQuerySubj::PreparedId(query_id),
+            values: values.into(),
+        });
+        self
+    }
+
+    pub fn clear_queries(mut self) -> Self {
+        self.queries = vec![];
+        self.query_texts = vec![];
+        if !self.explicit_routing {
+            self.routing = None;
+        }
+        self
+    }
+
+    /// Routes the batch to nodes storing the partition with given serialized key in given
+    /// keyspace, overriding the partition of its first statement. Routing keys of partition keys
+    /// with many columns are serialized with `cluster::routing_key()`.
+    pub fn routing_key<K: Into<String>>(mut self, keyspace: K, routing_key: Vec<u8>) -> Self {
+        self.routing = Some(BatchRouting {
+            keyspace: keyspace.into(),
+            table: None,
+            routing_key,
+        });
+        self.explicit_routing = true;
+        self
+    }
+
+    /// Sets consistency of the batch. Default is the default consistency of the session, `One`
+    /// unless configured otherwise.
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    /// Sets serial consistency of conditional statements of the batch, which must be `Serial`
+    /// or `LocalSerial`. Default is the default serial consistency of the session.
+    pub fn serial_consistency(mut self, serial_consistency: Option<Consistency>) -> Self {
+        self.serial_consistency = serial_consistency;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: Option<i64>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Marks the batch as idempotent, i.e. safe to apply more than once, which allows retrying
+    /// it after ambiguous failures and executing it speculatively. Batches are not idempotent by
+    /// default.
+    pub fn idempotent(mut self, is_idempotent: bool) -> Self {
+        self.is_idempotent = is_idempotent;
+        self
+    }
+
+    /// Sets the time limit of the batch, including retries and speculative executions,
+    /// overriding the request deadline of the session.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the priority of the batch when connections are saturated. Batches have `Normal`
+    /// priority by default.
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Builds the batch. Fails if values of some statements have names and of others don't,
+    /// the serial consistency is not serial, or a counter batch is idempotent or contains
+    /// statements other than updates and deletes, a timestamp, or updates with a TTL or a
+    /// timestamp. Statements prepared outside of the builder, whose text is unknown, are not
+    /// validated. The location of the
+    /// call is kept for attributing batch warnings to it.
+    #[track_caller]
+    pub fn finalize(self) -> CResult<BodyReqBatch> {
+        let location = Location::caller();
+
+        let mut flags = vec![];
+
+        if let Some(serial_consistency) = self.serial_consistency {
+            serial_consistency.check_serial()?;
+            flags.push(QueryFlags::WithSerialConsistency);
+        }
+
+        if self.timestamp.is_some() {
+            flags.push(QueryFlags::WithDefaultTimestamp);
+        }
+
+        if self.batch_type == BatchType::Counter {
+            self.check_counter_batch()?;
+        }
+
+        let with_names_for_values = self.queries.iter().all(|q| q.values.with_names());
+
+        if !with_names_for_values {
+            let some_names_for_values = self.queries.iter().any(|q| q.values.with_names());
+
+            if some_names_for_values {
+                return Err(CError::General(String::from(
+                    "Inconsistent query values - mixed \
+                     with and without names values",
+                )));
+            }
+        }
+
+        if with_names_for_values {
+            flags.push(QueryFlags::WithNamesForValues);
+        }
+
+        Ok(BodyReqBatch {
+            batch_type: self.batch_type,
+            queries: self.queries,
+            query_flags: flags,
+            consistency: self.consistency.unwrap_or_default(),
+            explicit_consistency: self.consistency.is_some(),
+            serial_consistency: self.serial_consistency,
+            timestamp: self.timestamp,
+            is_idempotent: self.is_idempotent,
+            deadline: self.deadline,
+            priority: self.priority,
+            location: Some(location),
+            keyspace: self
+                .routing
+                .as_ref()
+                .map(|routing| routing.keyspace.clone()),
+            table: self
+                .routing
+                .as_ref()
+                .and_then(|routing| routing.table.clone()),
+            routing_key: self.routing.map(|routing| routing.routing_key),
+        })
+    }
+
+    fn check_counter_batch(&self) -> CResult<()> {
+        // counter updates add to the current value, so applying them again is not safe
+        if self.is_idempotent {
+            return Err("Counter batches can't be idempotent".into());
+        }
+
+        // nodes reject custom timestamps of counter batches and TTLs or timestamps of updates
+        if self.timestamp.is_some() {
+            return Err("Counter batches can't have a timestamp".into());
+        }
+
+        let non_counter = self.query_texts.iter().flatten().find(|query| {
+            let keyword = query.split_whitespace().next().unwrap_or_default();
+            !keyword.eq_ignore_ascii_case("UPDATE") && !keyword.eq_ignore_ascii_case("DELETE")
+        });
+
+        if let Some(query) = non_counter {
+            return Err(format!(
+                "Counter batches can only contain counter updates and deletes, got: {}",
+                query
+            )
+            .into());
+        }
+
+        let with_using = self
+            .query_texts
+            .iter()
+            .flatten()
+            .find(|query| has_ttl_or_timestamp(query));
+        match with_using {
+            Some(query) => Err(format!(
+                "Counter updates can't have a TTL or timestamp, got: {}",
+                query
+            )
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks if the `USING` clause of a statement, which precedes its `SET` or `WHERE` clause,
+/// sets a TTL or a timestamp.
+fn has_ttl_or_timestamp(query: &str) -> bool {
+    query
+        .split_whitespace()
+        .skip_while(|word| !word.eq_ignore_ascii_case("USING"))
+        .take_while(|word| !word.eq_ignore_ascii_case("SET") && !word.eq_ignore_ascii_case("WHERE"))
+        .any(|word| word.eq_ignore_ascii_case("TTL") || word.eq_ignore_ascii_case("TIMESTAMP"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixes_simple_and_prepared_statements() {
+        let mut prepared = PreparedQuery::from(CBytesShort::new(vec![1]));
+        prepared.query = Some("INSERT INTO ks.t (a) VALUES (?)".to_string());
+
+        let batch = BatchBuilder::new()
+            .add_query("INSERT INTO ks.t (a) VALUES (?)", vec![1])
+            .add_prepared(&prepared, vec![2])
+            .batch_type(BatchType::Unlogged)
+            .consistency(Consistency::Quorum)
+            .serial_consistency(Some(Consistency::LocalSerial))
+            .timestamp(Some(10))
+            .finalize()
+            .unwrap();
+
+        assert_eq!(batch.queries.len(), 2);
+        assert!(!batch.queries[0].is_prepared);
+        assert!(batch.queries[1].is_prepared);
+        assert_eq!(batch.batch_type, BatchType::Unlogged);
+        assert!(batch.explicit_consistency);
+        assert_eq!(batch.query_flags.len(), 2);
+        assert_eq!(
+            batch.location.map(|location| location.file()),
+            Some(file!())
+        );
+
+        assert!(BatchBuilder::new()
+            .serial_consistency(Some(Consistency::Quorum))
+            .finalize()
+            .is_err());
+    }
+
+    #[test]
+    fn validates_counter_batches() {
+        let update = "UPDATE ks.counters SET c = c + ? WHERE k = ?";
+        let counter = || {
+            BatchBuilder::new()
+                .batch_type(BatchType::Counter)
+                .add_query(update, vec![1i64])
+                .add_query(" delete c FROM ks.counters WHERE k = ?", vec![2])
+        };
+        let error = |batch: BatchBuilder| match batch.finalize() {
+            Err(CError::General(message)) => message,
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        };
+        assert!(counter().finalize().is_ok());
+        assert!(counter().idempotent(true).finalize().is_err());
+        assert_eq!(
+            error(counter().timestamp(Some(10))),
+            "Counter batches can't have a timestamp"
+        );
+
+        for update in &[
+            "UPDATE ks.counters USING TTL 60 SET c = c + ? WHERE k = ?",
+            "update ks.counters using timestamp 1 set c = c - ? where k = ?",
+            "UPDATE ks.counters USING TIMESTAMP 1 AND TTL 60 SET c = c + ? WHERE k = ?",
+        ] {
+            assert_eq!(
+                error(counter().add_query(*update, vec![1i64])),
+                format!(
+                    "Counter updates can't have a TTL or timestamp, got: {}",
+                    update
+                )
+            );
+        }
+        assert!(counter()
+            .add_query(
+                "UPDATE ks.counters SET ttl = ttl + ?, timestamp = timestamp + ? WHERE k = ?",
+                vec![1i64, 1]
+            )
+            .finalize()
+            .is_ok());
+        assert!(counter()
+            .add_query("INSERT INTO ks.t (a) VALUES (?)", vec![1])
+            .finalize()
+            .is_err());
+
+        let mut prepared = PreparedQuery::from(CBytesShort::new(vec![1]));
+        prepared.query = Some("INSERT INTO ks.t (a) VALUES (?)".to_string());
+        assert!(counter()
+            .add_prepared(&prepared, vec![1])
+            .finalize()
+            .is_err());
+        assert!(counter()
+            .add_query_prepared(prepared.id.clone(), vec![1])
+            .finalize()
+            .is_ok());
+    }
+
+    #[test]
+    fn routes_by_first_statement() {
+        let mut prepared = PreparedQuery::from(CBytesShort::new(vec![1]));
+        prepared.keyspace = Some("ks".to_string());
+        prepared.table = Some("t".to_string());
+        prepared.pk_indexes = vec![0];
+
+        let batch = BatchBuilder::new()
+            .add_prepared(&prepared, vec![7i32])
+            .add_query("INSERT INTO ks.t (a) VALUES (1)", vec![0i32; 0])
+            .finalize()
+            .unwrap();
+        assert_eq!(batch.keyspace.as_deref(), Some("ks"));
+        assert_eq!(batch.table.as_deref(), Some("t"));
+        assert_eq!(batch.routing_key, Some(7i32.to_be_bytes().to_vec()));
+
+        let batch = BatchBuilder::new()
+            .add_query("INSERT INTO ks.t (a) VALUES (1)", vec![0i32; 0])
+            .add_prepared(&prepared, vec![7i32])
+            .finalize()
+            .unwrap();
+        assert!(batch.routing_key.is_none());
+
+        let batch = BatchBuilder::new()
+            .routing_key("other", vec![1])
+            .add_prepared(&prepared, vec![7i32])
+            .clear_queries()
+            .finalize()
+            .unwrap();
+        assert_eq!(batch.keyspace.as_deref(), Some("other"));
+        assert!(batch.table.is_none());
+        assert_eq!(batch.routing_key, Some(vec![1]));
+    }
+}
@@ -1,36 +1,106 @@
 use bb8;
-use tokio::sync::Mutex;
 use async_trait::async_trait;
+use std::mem;
 
-use crate::cluster::{GetCompressor, GetConnection, ResponseCache};
+use crate::cluster::{GetCompressor, GetConnection};
 use crate::error;
-use crate::frame::traits::IntoBytes;
 use crate::frame::Frame;
-use crate::query::batch_query_builder::QueryBatch;
+use crate::query::batch_builder::QueryBatch;
+use crate::query::{BatchWarning, QueryFlags};
 use crate::transport::CDRSTransport;
+use crate::types::SHORT_LEN;
 
-use super::utils::{prepare_flags, send_frame};
+use super::utils::{prepare_flags, send_request, Request, Routing};
 
 #[async_trait]
 pub trait BatchExecutor<
     T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
->: GetConnection<T, M> + GetCompressor<'static> + ResponseCache + Sync
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+>: GetConnection<T, M> + GetCompressor<'static> + Sync
 {
     async fn batch_with_params_tw(
         &self,
-        batch: QueryBatch,
+        mut batch: QueryBatch,
         with_tracing: bool,
         with_warnings: bool,
     ) -> error::Result<Frame>
     where
         Self: Sized,
     {
+        if !batch.explicit_consistency {
+            batch.consistency = self.default_consistency();
+        }
+
+        if batch.serial_consistency.is_none() {
+            batch.serial_consistency = self.default_serial_consistency();
+            if batch.serial_consistency.is_some() {
+                batch.query_flags.push(QueryFlags::WithSerialConsistency);
+            }
+        }
+
+        if batch.timestamp.is_none() {
+            batch.timestamp = self.timestamp_generator().next_timestamp();
+            if batch.timestamp.is_some() {
+                batch.query_flags.push(QueryFlags::WithDefaultTimestamp);
+            }
+        }
+
+        // consistency is followed by flags byte, serial consistency and timestamp
+        let mut consistency_tail = SHORT_LEN + 1;
+        if batch.serial_consistency.is_some() {
+            consistency_tail += SHORT_LEN;
+        }
+        if batch.timestamp.is_some() {
+            consistency_tail += mem::size_of::<i64>();
+        }
+
+        // batches go to nodes storing the partition, if it's known
+        let keyspace = batch.keyspace.take();
+        let table = batch.table.take();
+        let token = batch
+            .routing_key
+            .take()
+            .map(|routing_key| self.partitioner().hash(&routing_key));
+        let routing = keyspace
+            .as_deref()
+            .zip(token)
+            .map(|(keyspace, token)| Routing {
+                keyspace,
+                table: table.as_deref(),
+                token,
+            });
+
+        let is_idempotent = batch.is_idempotent;
+        let deadline = batch.deadline;
+        let priority = batch.priority;
+        let location = batch.location;
+        let statements = batch.queries.len();
         let flags = prepare_flags(with_tracing, with_warnings);
 
         let query_frame = Frame::new_req_batch(batch, flags);
 
-        send_frame(self, query_frame.into_cbytes(), query_frame.stream ).await
+        let consistency_offset = query_frame.body.len() - consistency_tail;
+
+        // statements follow the batch type and their count
+        let size = consistency_offset - 1 - SHORT_LEN;
+        let thresholds = self.batch_warning_thresholds();
+        if thresholds.are_exceeded(size, statements) {
+            self.warning_handler().handle_batch_warning(&BatchWarning {
+                size,
+                statements,
+                keyspace: keyspace.clone(),
+                table: table.clone(),
+                location,
+            });
+        }
+
+        let request = Request::new(query_frame)
+            .with_routing(routing)
+            .with_consistency_offset(consistency_offset)
+            .with_idempotent(is_idempotent)
+            .with_deadline(deadline)
+            .with_priority(priority);
+        send_request(self, request).await
     }
 
     async fn batch_with_params(&self, batch: QueryBatch) -> error::Result<Frame>
@@ -0,0 +1,202 @@
+use bytes::{Buf, Bytes};
+use futures_core::Stream;
+use std::cmp;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+
+use crate::error;
+use crate::query::RowStream;
+use crate::types::IntoRustByName;
+
+/// Stream of chunks of a blob which is stored in rows of chunks, e.g. `(id, chunk, data)` rows
+/// clustered by the index of the chunk, as written by `write_blob_chunks()`. Chunks are read
+/// from a column of rows of a `RowStream`, which fetches them a page at a time, so only a page
+/// of chunks is held in memory, or two with prefetching. Null chunks are read as empty ones.
+pub struct BlobStream<'a> {
+    rows: RowStream<'a>,
+    column: String,
+}
+
+impl<'a> BlobStream<'a> {
+    /// Creates a stream of chunks read from a blob column with given name.
+    pub fn new(rows: RowStream<'a>, column: &str) -> Self {
+        BlobStream {
+            rows,
+            column: column.to_string(),
+        }
+    }
+
+    /// Converts the stream into an `AsyncRead` of the whole blob.
+    pub fn into_async_read(self) -> BlobReader<'a> {
+        BlobReader {
+            chunks: self,
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl<'a> Stream for BlobStream<'a> {
+    type Item = error::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        Pin::new(&mut this.rows).poll_next(cx).map(|row| {
+            row.map(|row| {
+                row.and_then(|row| row.get_by_name(&this.column))
+                    .map(Option::unwrap_or_default)
+            })
+        })
+    }
+}
+
+/// `AsyncRead` of a blob stored in rows of chunks, as returned by
+/// `BlobStream::into_async_read()`. Errors of reading chunks are returned as `io::Error` of
+/// kind `Other`, wrapping them.
+pub struct BlobReader<'a> {
+    chunks: BlobStream<'a>,
+    chunk: Bytes,
+}
+
+impl<'a> AsyncRead for BlobReader<'a> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        while this.chunk.is_empty() {
+            match Pin::new(&mut this.chunks).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.chunk = chunk,
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Err(io::Error::other(error))),
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let len = cmp::min(buf.remaining(), this.chunk.len());
+        buf.put_slice(&this.chunk[..len]);
+        this.chunk.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Reads `reader` to its end in chunks of `chunk_size` bytes, the last of which may be shorter,
+/// and writes them one by one with `write_chunk`, e.g. by inserting them as rows read later by
+/// `BlobStream`. `write_chunk` is called with the index of a chunk, counted from 0, and its
+/// data, and the next chunk is read once the previous one is written, so only one chunk is
+/// held in memory. Returns the number of chunks.
+pub async fn write_blob_chunks<R, F, W>(
+    mut reader: R,
+    chunk_size: usize,
+    mut write_chunk: F,
+) -> error::Result<i32>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(i32, Vec<u8>) -> W,
+    W: Future<Output = error::Result<()>>,
+{
+    if chunk_size == 0 {
+        return Err("Chunk size must be greater than 0".into());
+    }
+
+    let mut index = 0;
+    loop {
+        let mut chunk = vec![0; chunk_size];
+        let mut len = 0;
+        while len < chunk_size {
+            match reader.read(&mut chunk[len..]).await? {
+                0 => break,
+                read => len += read,
+            }
+        }
+
+        if len == 0 {
+            return Ok(index);
+        }
+
+        chunk.truncate(len);
+        write_chunk(index, chunk).await?;
+        index += 1;
+
+        if len < chunk_size {
+            return Ok(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Frame;
+    use crate::query::Page;
+    use crate::test_utils::rows_frame;
+
+    /// Returns a page of rows with a single blob column.
+    fn chunks_frame(chunks: &[&[u8]], paging_state: Option<&[u8]>) -> Frame {
+        let rows = chunks
+            .iter()
+            .map(|chunk| vec![Some(chunk.to_vec())])
+            .collect();
+        rows_frame(&[("data", vec![0, 0x03])], rows, paging_state)
+    }
+
+    fn blob_stream() -> BlobStream<'static> {
+        let rows = RowStream::new(|paging_state| {
+            let frame = match paging_state {
+                None => chunks_frame(&[b"abc", b"de"], Some(b"a")),
+                Some(_) => chunks_frame(&[b"", b"fgh"], None),
+            };
+
+            Box::pin(async move { Page::from_frame(frame) })
+        });
+
+        BlobStream::new(rows, "data")
+    }
+
+    #[tokio::test]
+    async fn reads_blob_chunks() {
+        let mut blob = vec![];
+        let mut reader = blob_stream().into_async_read();
+        let mut buf = [0; 2];
+        loop {
+            match reader.read(&mut buf).await.unwrap() {
+                0 => break,
+                len => blob.extend_from_slice(&buf[..len]),
+            }
+        }
+
+        assert_eq!(blob, b"abcdefgh");
+    }
+
+    #[tokio::test]
+    async fn writes_blob_chunks() {
+        let mut chunks = vec![];
+        let count = write_blob_chunks(&b"abcdefgh"[..], 3, |index, chunk| {
+            chunks.push((index, chunk));
+            async { Ok(()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(
+            chunks,
+            vec![
+                (0, b"abc".to_vec()),
+                (1, b"def".to_vec()),
+                (2, b"gh".to_vec())
+            ]
+        );
+
+        let count = write_blob_chunks(&b""[..], 3, |_, _| async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(write_blob_chunks(&b"a"[..], 0, |_, _| async { Ok(()) })
+            .await
+            .is_err());
+    }
+}
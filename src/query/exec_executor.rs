@@ -1,38 +1,109 @@
 use async_trait::async_trait;
 use bb8;
-use tokio::sync::Mutex;
 
-use crate::cluster::{GetCompressor, GetConnection, ResponseCache};
+use crate::cluster::{GetCompressor, GetConnection};
 use crate::error;
-use crate::frame::{Frame, IntoBytes};
-use crate::query::{QueryParams, QueryParamsBuilder, QueryValues};
+use crate::frame::{Frame, IntoBytes, TryFromRow};
+use crate::query::{
+    LwtResult, Page, PreparedQuery, QueryParams, QueryParamsBuilder, QueryValues, RowStream, TypedRowStream,
+    DEFAULT_PAGE_SIZE,
+};
 use crate::transport::CDRSTransport;
-use crate::types::CBytesShort;
 
-use super::utils::{prepare_flags, send_frame};
-
-pub type PreparedQuery = CBytesShort;
+use super::utils::{into_rows, prepare_flags, prepare_query, send_request, Request, Routing};
 
 #[async_trait]
 pub trait ExecExecutor<
     T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
->: GetConnection<T, M> + GetCompressor<'static> + ResponseCache + Sync
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+>: GetConnection<T, M> + GetCompressor<'static> + Sync
 {
     async fn exec_with_params_tw(
         &self,
         prepared: &PreparedQuery,
-        query_parameters: QueryParams,
+        mut query_parameters: QueryParams,
         with_tracing: bool,
         with_warnings: bool,
     ) -> error::Result<Frame>
     where
         Self: Sized,
     {
+        if !query_parameters.explicit_consistency {
+            query_parameters.consistency = self.default_consistency();
+        }
+
+        match query_parameters.serial_consistency {
+            Some(serial_consistency) => serial_consistency.check_serial()?,
+            None => {
+                if let Some(serial_consistency) = self.default_serial_consistency() {
+                    query_parameters.set_serial_consistency(serial_consistency);
+                }
+            }
+        }
+
+        let timestamp = query_parameters
+            .timestamp
+            .or_else(|| self.timestamp_generator().next_timestamp());
+        if let Some(timestamp) = timestamp {
+            query_parameters.set_timestamp(timestamp);
+        }
+
+        if let Some(ref values) = query_parameters.values {
+            prepared.check_named_values(values)?;
+        }
+
+        // rows are decoded with result metadata cached when the query was prepared
+        let result_metadata = prepared.skippable_result_metadata().cloned();
+        if result_metadata.is_some() {
+            query_parameters.set_skip_metadata();
+        }
+
+        // executions go to nodes storing the partition, if it's known, unless it's set
+        // explicitly
+        let explicit_routing = query_parameters.take_routing(&*self.partitioner());
+        let routing = match explicit_routing {
+            Some((ref keyspace, ref token)) => Some(Routing {
+                keyspace,
+                table: None,
+                token: token.clone(),
+            }),
+            None => {
+                let token = query_parameters
+                    .values
+                    .as_ref()
+                    .and_then(|values| prepared.routing_key(values))
+                    .map(|routing_key| self.partitioner().hash(&routing_key));
+                prepared
+                    .keyspace
+                    .as_deref()
+                    .zip(token)
+                    .map(|(keyspace, token)| Routing {
+                        keyspace,
+                        table: prepared.table.as_deref(),
+                        token,
+                    })
+            }
+        };
+
+        let target_node = query_parameters.target_node.take();
+        let is_idempotent = prepared.is_idempotent || query_parameters.is_idempotent;
+        let deadline = query_parameters.deadline;
+        let priority = query_parameters.priority;
         let flags = prepare_flags(with_tracing, with_warnings);
-        let options_frame = Frame::new_req_execute(prepared, query_parameters, flags);
+        let options_frame = Frame::new_req_execute(&prepared.id, query_parameters, flags);
+
+        // consistency follows the id of the prepared query
+        let request = Request::new(options_frame)
+            .with_routing(routing)
+            .with_target_node(target_node)
+            .with_consistency_offset(prepared.id.into_cbytes().len())
+            .with_idempotent(is_idempotent)
+            .with_deadline(deadline)
+            .with_priority(priority)
+            .with_prepared_query(prepared.query.as_deref())
+            .with_result_metadata(result_metadata);
 
-        send_frame(self, options_frame.into_cbytes(), options_frame.stream).await
+        send_request(self, request).await
     }
 
     async fn exec_with_params(
@@ -91,4 +162,112 @@ pub trait ExecExecutor<
     {
         self.exec_tw(prepared, false, false).await
     }
+
+    /// Executes a prepared query and returns a stream of its rows, which fetches further pages
+    /// as it's consumed. Page size is taken from query params, or `DEFAULT_PAGE_SIZE` if not
+    /// set.
+    fn exec_iter<'a>(
+        &'a self,
+        prepared: &'a PreparedQuery,
+        mut query_parameters: QueryParams,
+    ) -> RowStream<'a>
+    where
+        Self: Sized,
+    {
+        if query_parameters.page_size.is_none() {
+            query_parameters.set_page_size(DEFAULT_PAGE_SIZE);
+        }
+
+        RowStream::new(move |paging_state| {
+            let mut query_parameters = query_parameters.clone();
+            if let Some(paging_state) = paging_state {
+                query_parameters.set_paging_state(paging_state);
+            }
+
+            let response = self.exec_with_params(prepared, query_parameters);
+            Box::pin(async move { response.await.and_then(Page::from_frame) })
+        })
+    }
+
+    /// Executes a prepared query with given values and converts all returned rows.
+    async fn exec_map<V: Into<QueryValues> + Sync + Send, R: TryFromRow + Send>(
+        &self,
+        prepared: &PreparedQuery,
+        values: V,
+    ) -> error::Result<Vec<R>>
+    where
+        Self: Sized,
+    {
+        let frame = self.exec_with_values(prepared, values).await?;
+        into_rows(frame)?
+            .into_iter()
+            .map(R::try_from_row)
+            .collect()
+    }
+
+    /// Executes a prepared query with given values and returns a stream of converted rows,
+    /// which fetches further pages as it's consumed.
+    fn exec_map_iter<'a, V: Into<QueryValues>, R: TryFromRow>(
+        &'a self,
+        prepared: &'a PreparedQuery,
+        values: V,
+    ) -> TypedRowStream<'a, R>
+    where
+        Self: Sized,
+    {
+        let query_parameters = QueryParamsBuilder::new().values(values.into()).finalize();
+        self.exec_iter(prepared, query_parameters).into_typed()
+    }
+
+    /// Executes a query with given values as a prepared statement. The statement is prepared
+    /// on first use and kept in the prepared statement cache of the session, so following
+    /// executions of the same query reuse it.
+    async fn execute_cached<Q: ToString + Send, V: Into<QueryValues> + Sync + Send>(
+        &self,
+        query: Q,
+        values: V,
+    ) -> error::Result<Frame>
+    where
+        Self: Sized,
+    {
+        let query = query.to_string();
+        let cache = self.prepared_cache();
+        // unqualified tables resolve to different ones in different keyspaces
+        let keyspace = self.keyspace();
+        let cached = cache
+            .as_ref()
+            .and_then(|cache| cache.get(keyspace.as_deref(), &query));
+
+        let prepared = match cached {
+            Some(prepared) => prepared,
+            None => {
+                let mut prepared =
+                    PreparedQuery::from(prepare_query(self, query.clone(), vec![]).await?);
+                prepared.query = Some(query.clone());
+
+                if let Some(ref cache) = cache {
+                    cache.insert(keyspace.as_deref(), &query, prepared.clone());
+                }
+
+                prepared
+            }
+        };
+
+        self.exec_with_values(&prepared, values).await
+    }
+
+    /// Executes a prepared lightweight transaction, i.e. a conditional insert, update or delete,
+    /// with given values and reads whether it was applied, along with the existing row if it
+    /// wasn't.
+    async fn execute_lwt<V: Into<QueryValues> + Sync + Send>(
+        &self,
+        prepared: &PreparedQuery,
+        values: V,
+    ) -> error::Result<LwtResult>
+    where
+        Self: Sized,
+    {
+        let frame = self.exec_with_values(prepared, values).await?;
+        LwtResult::from_frame(frame)
+    }
 }
@@ -0,0 +1,78 @@
+use crate::error;
+use crate::frame::Frame;
+use crate::types::rows::Row;
+use crate::types::IntoRustByName;
+
+use super::utils::into_rows;
+
+/// Name of the column telling whether a lightweight transaction was applied.
+const APPLIED_COLUMN: &str = "[applied]";
+
+/// Result of a lightweight transaction, i.e. a conditional insert, update or delete, as returned
+/// by `ExecExecutor::execute_lwt()`.
+#[derive(Clone, Debug)]
+pub struct LwtResult {
+    /// Whether the condition was met and the statement was applied.
+    pub applied: bool,
+    /// The existing row which didn't meet the condition of a statement which wasn't applied,
+    /// if there is one. It contains the `[applied]` column and the columns of the condition.
+    pub existing: Option<Row>,
+}
+
+impl LwtResult {
+    /// Reads the result of a lightweight transaction from a response. Only the first row is
+    /// read from results of conditional batches.
+    pub fn from_frame(frame: Frame) -> error::Result<Self> {
+        let row = into_rows(frame)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| error::Error::from("Lightweight transaction returned no rows"))?;
+
+        LwtResult::from_row(row)
+    }
+
+    /// Reads the result of a lightweight transaction from a row with the `[applied]` column.
+    pub fn from_row(row: Row) -> error::Result<Self> {
+        let applied: bool = row.get_r_by_name(APPLIED_COLUMN)?;
+        let existing = if !applied && row.columns_count() > 1 {
+            Some(row)
+        } else {
+            None
+        };
+
+        Ok(LwtResult { applied, existing })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::rows_frame;
+
+    fn lwt_frame(applied: bool, existing: Option<i32>) -> Frame {
+        let mut columns = vec![(APPLIED_COLUMN, vec![0, 0x04])];
+        let mut row = vec![Some(vec![applied as u8])];
+        if let Some(value) = existing {
+            columns.push(("value", vec![0, 0x09]));
+            row.push(Some(value.to_be_bytes().to_vec()));
+        }
+
+        rows_frame(&columns, vec![row], None)
+    }
+
+    #[test]
+    fn reads_lwt_results() {
+        let result = LwtResult::from_frame(lwt_frame(true, None)).unwrap();
+        assert!(result.applied);
+        assert!(result.existing.is_none());
+
+        let result = LwtResult::from_frame(lwt_frame(false, None)).unwrap();
+        assert!(!result.applied);
+        assert!(result.existing.is_none());
+
+        let result = LwtResult::from_frame(lwt_frame(false, Some(7))).unwrap();
+        assert!(!result.applied);
+        let value: i32 = result.existing.unwrap().get_r_by_name("value").unwrap();
+        assert_eq!(value, 7);
+    }
+}
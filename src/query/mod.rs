@@ -1,22 +1,54 @@
 mod batch_executor;
-mod batch_query_builder;
+mod batch_builder;
+mod blob_stream;
 mod exec_executor;
+mod lwt_result;
 mod prepare_executor;
+mod prepared_cache;
+mod paging_state;
 mod query;
+mod query_builder;
 mod query_executor;
 mod query_flags;
 mod query_params;
 mod query_params_builder;
+mod query_result;
 mod query_values;
+mod row_stream;
+mod timestamp_generator;
 mod utils;
+mod warning_handler;
+mod write_combiner;
 
 pub use crate::query::batch_executor::BatchExecutor;
-pub use crate::query::batch_query_builder::{BatchQueryBuilder, QueryBatch};
+#[cfg(feature = "derive")]
+pub use cdrs_tokio_derive::checked_query;
+#[allow(deprecated)]
+pub use crate::query::batch_builder::{BatchBuilder, BatchQueryBuilder, QueryBatch};
+pub use crate::query::blob_stream::{write_blob_chunks, BlobReader, BlobStream};
 pub use crate::query::exec_executor::ExecExecutor;
+pub use crate::query::lwt_result::LwtResult;
+pub use crate::query::paging_state::PagingState;
 pub use crate::query::prepare_executor::{PrepareExecutor, PreparedQuery};
-pub use crate::query::query::Query;
+pub use crate::query::prepared_cache::PreparedCache;
+pub use crate::query::query::{named_bind_markers, Query};
+pub use crate::query::query_builder::{
+    Collection, DeleteBuilder, Elements, InsertBuilder, Operator, SelectBuilder, UpdateBuilder,
+};
 pub use crate::query::query_executor::QueryExecutor;
 pub use crate::query::query_flags::QueryFlags;
-pub use crate::query::query_params::QueryParams;
+pub use crate::query::query_params::{QueryParams, RequestPriority, TargetNode};
 pub use crate::query::query_params_builder::QueryParamsBuilder;
+pub use crate::query::query_result::QueryResult;
 pub use crate::query::query_values::QueryValues;
+pub use crate::query::row_stream::{Page, RowStream, TypedRowStream, DEFAULT_PAGE_SIZE};
+pub use crate::query::timestamp_generator::{
+    MonotonicTimestampGenerator, ServerSideTimestampGenerator, TimestampGenerator,
+};
+pub use crate::query::warning_handler::{
+    BatchWarning, BatchWarningThresholds, CallbackWarningHandler, LogWarningHandler,
+    WarningHandler,
+};
+pub use crate::query::write_combiner::{WriteCombiner, WriteCombinerConfig};
+
+pub(crate) use crate::query::utils::{prepare_node_query, prepare_on_node};
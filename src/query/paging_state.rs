@@ -0,0 +1,130 @@
+#[cfg(feature = "signed-paging-state")]
+use hmac::{Hmac, Mac};
+#[cfg(feature = "signed-paging-state")]
+use sha2::Sha256;
+
+use crate::error;
+use crate::types::CBytes;
+
+/// Version of the serialized paging state format.
+const FORMAT_VERSION: u8 = 1;
+
+#[cfg(feature = "signed-paging-state")]
+const SIGNATURE_LEN: usize = 32;
+
+/// Opaque position in results of a paged query, from which the next page is fetched. Paging
+/// states can be serialized with `to_bytes()` and read back with `from_bytes()`, e.g. to be
+/// handed to clients of a stateless service as page cursors.
+///
+/// Paging states are only valid for the query which produced them. With the
+/// `signed-paging-state` feature, they can be signed with a secret key, so cursors modified by
+/// clients are rejected instead of being sent to nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PagingState {
+    bytes: Vec<u8>,
+}
+
+impl PagingState {
+    /// Serializes the paging state.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.bytes.len() + 1);
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+
+    /// Reads a paging state serialized with `to_bytes()`.
+    pub fn from_bytes(bytes: &[u8]) -> error::Result<Self> {
+        match bytes.split_first() {
+            Some((&FORMAT_VERSION, state)) if !state.is_empty() => Ok(PagingState {
+                bytes: state.to_vec(),
+            }),
+            Some((&FORMAT_VERSION, _)) => Err("Empty paging state".into()),
+            Some((version, _)) => {
+                Err(format!("Unsupported paging state version {}", version).into())
+            }
+            None => Err("Empty paging state".into()),
+        }
+    }
+
+    /// Serializes the paging state along with its HMAC-SHA256 signature made with given key.
+    #[cfg(feature = "signed-paging-state")]
+    pub fn to_signed_bytes(&self, key: &[u8]) -> Vec<u8> {
+        let mut bytes = self.to_bytes();
+        let signature = Self::mac(key, &bytes).finalize().into_bytes();
+        bytes.extend_from_slice(&signature);
+        bytes
+    }
+
+    /// Reads a paging state serialized with `to_signed_bytes()`, verifying its signature made
+    /// with given key.
+    #[cfg(feature = "signed-paging-state")]
+    pub fn from_signed_bytes(bytes: &[u8], key: &[u8]) -> error::Result<Self> {
+        if bytes.len() < SIGNATURE_LEN {
+            return Err("Paging state is not signed".into());
+        }
+
+        let (state, signature) = bytes.split_at(bytes.len() - SIGNATURE_LEN);
+        Self::mac(key, state)
+            .verify_slice(signature)
+            .map_err(|_| error::Error::from("Invalid paging state signature"))?;
+
+        Self::from_bytes(state)
+    }
+
+    #[cfg(feature = "signed-paging-state")]
+    fn mac(key: &[u8], bytes: &[u8]) -> Hmac<Sha256> {
+        // HMAC accepts keys of any length
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC key of any length");
+        mac.update(bytes);
+        mac
+    }
+}
+
+impl From<CBytes> for PagingState {
+    fn from(bytes: CBytes) -> Self {
+        PagingState {
+            bytes: bytes.into_plain().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<PagingState> for CBytes {
+    fn from(state: PagingState) -> Self {
+        CBytes::new(state.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_paging_states() {
+        let state = PagingState::from(CBytes::new(vec![1, 2, 3]));
+        let bytes = state.to_bytes();
+        assert_eq!(bytes, vec![FORMAT_VERSION, 1, 2, 3]);
+        assert_eq!(PagingState::from_bytes(&bytes).unwrap(), state);
+        assert_eq!(CBytes::from(state), CBytes::new(vec![1, 2, 3]));
+
+        assert!(PagingState::from_bytes(&[]).is_err());
+        assert!(PagingState::from_bytes(&[FORMAT_VERSION]).is_err());
+        assert!(PagingState::from_bytes(&[7, 1, 2, 3]).is_err());
+    }
+
+    #[cfg(feature = "signed-paging-state")]
+    #[test]
+    fn verifies_signatures() {
+        let state = PagingState::from(CBytes::new(vec![1, 2, 3]));
+        let mut bytes = state.to_signed_bytes(b"secret");
+        assert_eq!(
+            PagingState::from_signed_bytes(&bytes, b"secret").unwrap(),
+            state
+        );
+        assert!(PagingState::from_signed_bytes(&bytes, b"other").is_err());
+
+        bytes[1] ^= 1;
+        assert!(PagingState::from_signed_bytes(&bytes, b"secret").is_err());
+        assert!(PagingState::from_signed_bytes(&bytes[..10], b"secret").is_err());
+    }
+}
@@ -1,23 +1,174 @@
 use async_trait::async_trait;
 use bb8;
-use tokio::sync::Mutex;
+use std::sync::Arc;
 
-use crate::cluster::{GetCompressor, GetConnection, ResponseCache};
+use crate::cluster::{routing_key, GetCompressor, GetConnection};
 use crate::error;
-use crate::frame::frame_result::BodyResResultPrepared;
-use crate::frame::{Frame, IntoBytes};
+use crate::frame::frame_result::{BodyResResultPrepared, RowsMetadata, RowsMetadataFlag};
+use crate::query::QueryValues;
 use crate::transport::CDRSTransport;
+use crate::types::value::ValueType;
 use crate::types::CBytesShort;
 
-use super::utils::{prepare_flags, send_frame};
+use super::utils::{prepare_flags, prepare_query};
 
-pub type PreparedQuery = CBytesShort;
+/// Prepared query, along with metadata needed for routing its executions to nodes storing
+/// the partition.
+#[derive(Debug, Clone)]
+pub struct PreparedQuery {
+    /// Id assigned to the query by the node which prepared it.
+    pub id: CBytesShort,
+    /// Text of the query, used for preparing it again on nodes which don't know its id, e.g.
+    /// after a restart.
+    pub query: Option<String>,
+    /// Keyspace of the table the query refers to, if known.
+    pub keyspace: Option<String>,
+    /// Table the query refers to, if known.
+    pub table: Option<String>,
+    /// Indexes of bound values which form the partition key, in partition key order.
+    pub pk_indexes: Vec<usize>,
+    /// Names of bound values, used for finding partition key values bound by name.
+    pub bind_names: Vec<String>,
+    /// Whether executions of the query can be safely applied more than once, which allows
+    /// retrying them after ambiguous failures and executing them speculatively.
+    pub is_idempotent: bool,
+    /// Metadata of rows returned by executions of the query, if it returns any. Executions of
+    /// queries with known result metadata ask nodes to leave it out of responses.
+    pub result_metadata: Option<Arc<RowsMetadata>>,
+}
+
+impl PreparedQuery {
+    /// Marks executions of the query as idempotent, i.e. safe to apply more than once.
+    pub fn idempotent(mut self, is_idempotent: bool) -> Self {
+        self.is_idempotent = is_idempotent;
+        self
+    }
+
+    /// Checks that values with names include values of all bind markers of the query.
+    pub fn check_named_values(&self, values: &QueryValues) -> error::Result<()> {
+        values.check_names(self.bind_names.iter().map(String::as_str))
+    }
+
+    /// Serializes partition key values found in `values` the way Cassandra does for computing
+    /// tokens. Returns `None` if the partition key is unknown or not all of its values are bound.
+    pub fn routing_key(&self, values: &QueryValues) -> Option<Vec<u8>> {
+        if self.pk_indexes.is_empty() {
+            return None;
+        }
+
+        let values = self
+            .pk_indexes
+            .iter()
+            .map(|index| {
+                let value = match *values {
+                    QueryValues::SimpleValues(ref values) => values.get(*index),
+                    QueryValues::NamedValues(ref values) => {
+                        values.get(self.bind_names.get(*index)?)
+                    }
+                }?;
+
+                match value.value_type {
+                    ValueType::Normal(_) => Some(value.body.as_slice()),
+                    ValueType::Null | ValueType::NotSet => None,
+                }
+            })
+            .collect::<Option<Vec<&[u8]>>>()?;
+
+        Some(routing_key(&values))
+    }
+
+    /// Returns metadata of rows which executions can ask nodes to leave out of responses.
+    /// Queries selecting all columns get it in every response, as their columns change along
+    /// with the schema.
+    pub fn skippable_result_metadata(&self) -> Option<&Arc<RowsMetadata>> {
+        match self.query {
+            Some(ref query) if selects_all_columns(query) => None,
+            _ => self.result_metadata.as_ref(),
+        }
+    }
+}
+
+/// Checks if a query is a `SELECT *`, optionally with `JSON` or `DISTINCT`.
+fn selects_all_columns(query: &str) -> bool {
+    let mut rest = query.trim_start();
+    for keyword in ["SELECT", "JSON", "DISTINCT"] {
+        match rest.get(..keyword.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(keyword) => {
+                rest = rest[keyword.len()..].trim_start();
+            }
+            _ if keyword == "SELECT" => return false,
+            _ => {}
+        }
+    }
+
+    rest.starts_with('*')
+}
+
+impl From<BodyResResultPrepared> for PreparedQuery {
+    fn from(prepared: BodyResResultPrepared) -> Self {
+        let metadata = prepared.metadata;
+        let result_metadata = Some(prepared.result_metadata)
+            .filter(|result_metadata| {
+                result_metadata.columns_count > 0
+                    && !RowsMetadataFlag::has_no_metadata(result_metadata.flags)
+            })
+            .map(Arc::new);
+        let (keyspace, table) = match metadata.global_table_spec {
+            Some((ref keyspace, ref table)) => (
+                Some(keyspace.as_str().to_string()),
+                Some(table.as_str().to_string()),
+            ),
+            None => match metadata.col_specs.first() {
+                Some(col_spec) => (
+                    col_spec.ksname.as_ref().map(|keyspace| keyspace.as_plain()),
+                    col_spec.tablename.as_ref().map(|table| table.as_plain()),
+                ),
+                None => (None, None),
+            },
+        };
+
+        PreparedQuery {
+            id: prepared.id,
+            query: None,
+            keyspace,
+            table,
+            pk_indexes: metadata
+                .pk_indexes
+                .iter()
+                .map(|index| *index as usize)
+                .collect(),
+            bind_names: metadata
+                .col_specs
+                .iter()
+                .map(|col_spec| col_spec.name.as_str().to_string())
+                .collect(),
+            is_idempotent: false,
+            result_metadata,
+        }
+    }
+}
+
+impl From<CBytesShort> for PreparedQuery {
+    /// Creates a prepared query from a bare id. Its executions are not routed to replicas.
+    fn from(id: CBytesShort) -> Self {
+        PreparedQuery {
+            id,
+            query: None,
+            keyspace: None,
+            table: None,
+            pk_indexes: vec![],
+            bind_names: vec![],
+            is_idempotent: false,
+            result_metadata: None,
+        }
+    }
+}
 
 #[async_trait]
 pub trait PrepareExecutor<
     T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
->: GetConnection<T, M> + GetCompressor<'static> + ResponseCache + Sync
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+>: GetConnection<T, M> + GetCompressor<'static> + Sync
 {
     /// It prepares a query for execution, along with query itself the
     /// method takes `with_tracing` and `with_warnings` flags to get
@@ -33,17 +184,7 @@ pub trait PrepareExecutor<
         Self: Sized,
     {
         let flags = prepare_flags(with_tracing, with_warnings);
-
-        let query_frame = Frame::new_req_prepare(query.to_string(), flags);
-
-        send_frame(self, query_frame.into_cbytes(), query_frame.stream)
-            .await
-            .and_then(|response| response.get_body())
-            .and_then(|body| {
-                Ok(body
-                    .into_prepared()
-                    .expect("CDRS BUG: cannot convert frame into prepared"))
-            })
+        prepare_query(self, query.to_string(), flags).await
     }
 
     /// It prepares query without additional tracing information and warnings.
@@ -58,7 +199,7 @@ pub trait PrepareExecutor<
     /// It prepares a query for execution, along with query itself
     /// the method takes `with_tracing` and `with_warnings` flags
     /// to get tracing information and warnings. Return the prepared
-    /// query ID.
+    /// query.
     async fn prepare_tw<Q: ToString + Sync + Send>(
         &self,
         query: Q,
@@ -68,12 +209,22 @@ pub trait PrepareExecutor<
     where
         Self: Sized,
     {
-        self.prepare_raw_tw(query, with_tracing, with_warnings).await
-            .map(|x| x.id)
+        let query = query.to_string();
+        let mut prepared = self
+            .prepare_raw_tw(query.clone(), with_tracing, with_warnings)
+            .await
+            .map(PreparedQuery::from)?;
+
+        prepared.query = Some(query.clone());
+        if let Some(cache) = self.prepared_cache() {
+            cache.insert(self.keyspace().as_deref(), &query, prepared.clone());
+        }
+
+        Ok(prepared)
     }
 
     /// It prepares query without additional tracing information and warnings.
-    /// Return the prepared query ID.
+    /// Return the prepared query.
     async fn prepare<Q: ToString + Sync + Send>(&self, query: Q) -> error::Result<PreparedQuery>
     where
         Self: Sized + Sync,
@@ -81,3 +232,68 @@ pub trait PrepareExecutor<
         self.prepare_tw(query, false, false).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::value::{Bytes, Value};
+
+    fn prepared_query(pk_indexes: Vec<usize>) -> PreparedQuery {
+        PreparedQuery {
+            id: CBytesShort::new(vec![1]),
+            query: None,
+            keyspace: Some("ks".into()),
+            table: Some("t".into()),
+            pk_indexes,
+            bind_names: vec!["a".into(), "b".into(), "c".into()],
+            is_idempotent: false,
+            result_metadata: None,
+        }
+    }
+
+    #[test]
+    fn serializes_routing_keys() {
+        let values = QueryValues::SimpleValues(vec![
+            Value::new_normal(Bytes::new(vec![1, 2])),
+            Value::new_normal(Bytes::new(vec![3])),
+            Value::new_null(),
+        ]);
+
+        assert_eq!(prepared_query(vec![1]).routing_key(&values), Some(vec![3]));
+        assert_eq!(
+            prepared_query(vec![1, 0]).routing_key(&values),
+            Some(vec![0, 1, 3, 0, 0, 2, 1, 2, 0])
+        );
+        assert_eq!(prepared_query(vec![2]).routing_key(&values), None);
+        assert_eq!(prepared_query(vec![]).routing_key(&values), None);
+
+        let named_values = QueryValues::NamedValues(
+            vec![("b".to_string(), Value::new_normal(Bytes::new(vec![3])))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(prepared_query(vec![1]).routing_key(&named_values), Some(vec![3]));
+    }
+
+    #[test]
+    fn detects_queries_selecting_all_columns() {
+        assert!(selects_all_columns("SELECT * FROM ks.t"));
+        assert!(selects_all_columns(" select json *from t"));
+        assert!(selects_all_columns("SELECT DISTINCT * FROM t"));
+        assert!(!selects_all_columns("SELECT a, b FROM t"));
+        assert!(!selects_all_columns("SELECT count(*) FROM t"));
+        assert!(!selects_all_columns("INSERT INTO t (a) VALUES (?)"));
+
+        let mut prepared = prepared_query(vec![]);
+        prepared.result_metadata = Some(Arc::new(RowsMetadata {
+            flags: 0,
+            columns_count: 0,
+            paging_state: None,
+            global_table_space: None,
+            col_specs: vec![],
+        }));
+        assert!(prepared.skippable_result_metadata().is_some());
+        prepared.query = Some("SELECT * FROM t".into());
+        assert!(prepared.skippable_result_metadata().is_none());
+    }
+}
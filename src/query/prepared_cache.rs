@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::frame::frame_result::RowsMetadata;
+use crate::query::PreparedQuery;
+
+type CacheKey = (Option<String>, String);
+
+/// Cache of prepared statements keyed by query text and the keyspace they were prepared in,
+/// used by `ExecExecutor::execute_cached()`. Statements prepared by sessions are cached too, so
+/// they can be prepared on nodes joining the cluster. Once the cache is full, the least
+/// recently used statement is evicted. A cache with zero capacity stores nothing.
+#[derive(Debug)]
+pub struct PreparedCache {
+    capacity: AtomicUsize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    statements: HashMap<CacheKey, (PreparedQuery, u64)>,
+    // keys of statements by the use they were last used at, least recently used first
+    recency: BTreeMap<u64, CacheKey>,
+    uses: u64,
+}
+
+impl PreparedCache {
+    /// Capacity of caches of sessions which don't have one set explicitly.
+    pub const DEFAULT_CAPACITY: usize = 1000;
+
+    /// Creates a cache holding at most given number of statements.
+    pub fn new(capacity: usize) -> Self {
+        PreparedCache {
+            capacity: AtomicUsize::new(capacity),
+            entries: Default::default(),
+        }
+    }
+
+    /// Returns the maximum number of cached statements.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum number of cached statements, evicting least recently used ones which
+    /// don't fit anymore.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut entries = self.entries.lock().unwrap();
+        self.capacity.store(capacity, Ordering::Relaxed);
+        while entries.statements.len() > capacity {
+            entries.evict();
+        }
+    }
+
+    /// Returns keyspaces and texts of cached queries.
+    pub fn queries(&self) -> Vec<(Option<String>, String)> {
+        self.entries
+            .lock()
+            .unwrap()
+            .statements
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the number of cached statements.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().statements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a statement prepared from given query in given keyspace, marking it as recently
+    /// used.
+    pub fn get(&self, keyspace: Option<&str>, query: &str) -> Option<PreparedQuery> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.uses += 1;
+        let uses = entries.uses;
+
+        let key = key(keyspace, query);
+        let (prepared, last_used) = match entries.statements.get_mut(&key) {
+            Some(&mut (ref prepared, ref mut last_used)) => {
+                (prepared.clone(), std::mem::replace(last_used, uses))
+            }
+            None => return None,
+        };
+
+        entries.recency.remove(&last_used);
+        entries.recency.insert(uses, key);
+        Some(prepared)
+    }
+
+    /// Stores a statement prepared from given query in given keyspace, evicting the least
+    /// recently used one if the cache is full.
+    pub fn insert(&self, keyspace: Option<&str>, query: &str, prepared: PreparedQuery) {
+        let mut entries = self.entries.lock().unwrap();
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+
+        entries.uses += 1;
+        let uses = entries.uses;
+        let key = key(keyspace, query);
+
+        if !entries.statements.contains_key(&key) && entries.statements.len() >= capacity {
+            entries.evict();
+        }
+
+        entries.recency.insert(uses, key.clone());
+        if let Some((_, last_used)) = entries.statements.insert(key, (prepared, uses)) {
+            entries.recency.remove(&last_used);
+        }
+    }
+
+    /// Replaces metadata of rows returned by a cached statement prepared from given query in
+    /// given keyspace, e.g. after it was prepared again following a schema change.
+    pub fn set_result_metadata(
+        &self,
+        keyspace: Option<&str>,
+        query: &str,
+        result_metadata: Option<Arc<RowsMetadata>>,
+    ) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some((prepared, _)) = entries.statements.get_mut(&key(keyspace, query)) {
+            prepared.result_metadata = result_metadata;
+        }
+    }
+
+    /// Evicts a statement prepared from given query in given keyspace.
+    pub fn remove(&self, keyspace: Option<&str>, query: &str) -> Option<PreparedQuery> {
+        let mut entries = self.entries.lock().unwrap();
+        let (prepared, last_used) = entries.statements.remove(&key(keyspace, query))?;
+        entries.recency.remove(&last_used);
+        Some(prepared)
+    }
+
+    /// Evicts all statements.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.statements.clear();
+        entries.recency.clear();
+    }
+}
+
+impl Entries {
+    /// Removes the least recently used statement.
+    fn evict(&mut self) {
+        if let Some((_, least_recently_used)) = self.recency.pop_first() {
+            self.statements.remove(&least_recently_used);
+        }
+    }
+}
+
+impl Default for PreparedCache {
+    fn default() -> Self {
+        PreparedCache::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+fn key(keyspace: Option<&str>, query: &str) -> CacheKey {
+    (keyspace.map(str::to_string), query.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CBytesShort;
+
+    fn prepared(id: u8) -> PreparedQuery {
+        PreparedQuery::from(CBytesShort::new(vec![id]))
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = PreparedCache::new(2);
+        cache.insert(None, "a", prepared(1));
+        cache.insert(Some("ks"), "a", prepared(2));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(None, "a").unwrap().id.into_plain(), Some(vec![1]));
+
+        cache.insert(None, "b", prepared(3));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(Some("ks"), "a").is_none());
+        assert!(cache.get(None, "a").is_some());
+        assert!(cache.get(None, "b").is_some());
+
+        assert_eq!(cache.queries().len(), 2);
+        cache.set_capacity(1);
+        assert_eq!(cache.queries(), vec![(None, "b".to_string())]);
+
+        assert!(cache.remove(None, "b").is_some());
+        assert!(cache.is_empty());
+        cache.insert(None, "c", prepared(4));
+        cache.clear();
+        assert!(cache.is_empty());
+
+        let disabled = PreparedCache::new(0);
+        disabled.insert(None, "a", prepared(1));
+        assert!(disabled.get(None, "a").is_none());
+    }
+}
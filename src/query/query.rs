@@ -1,4 +1,5 @@
 use super::QueryParams;
+use crate::error;
 
 /// Structure that represents CQL query and parameters which will be applied during
 /// its execution
@@ -7,3 +8,133 @@ pub struct Query {
     pub query: String,
     pub params: QueryParams,
 }
+
+impl Query {
+    /// Checks if the query can be safely executed more than once, as set with
+    /// `QueryParamsBuilder::idempotent()`.
+    pub fn is_idempotent(&self) -> bool {
+        self.params.is_idempotent
+    }
+
+    /// Checks that values with names, if the query has them, include values of all named bind
+    /// markers of the query.
+    pub fn check_named_values(&self) -> error::Result<()> {
+        match self.params.values {
+            Some(ref values) if values.with_names() => {
+                let names = named_bind_markers(&self.query);
+                values.check_names(names.iter().map(String::as_str))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns names of `:name` bind markers of a query, in order of their first occurrence.
+/// Unquoted names are lowercased, as done by Cassandra, while quoted ones (`:"Name"`) are kept
+/// as they are. Markers within string literals and comments are skipped.
+pub fn named_bind_markers(query: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => skip_until(&mut chars, "'"),
+            '"' => skip_until(&mut chars, "\""),
+            '$' if chars.peek() == Some(&'$') => {
+                chars.next();
+                skip_until(&mut chars, "$$");
+            }
+            '-' if chars.peek() == Some(&'-') => skip_until(&mut chars, "\n"),
+            '/' if chars.peek() == Some(&'/') => skip_until(&mut chars, "\n"),
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                skip_until(&mut chars, "*/");
+            }
+            ':' => {
+                let name = match chars.peek() {
+                    Some(&'"') => {
+                        chars.next();
+                        let mut name = String::new();
+                        for c in chars.by_ref() {
+                            if c == '"' {
+                                break;
+                            }
+                            name.push(c);
+                        }
+                        name
+                    }
+                    Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+                        let mut name = String::new();
+                        while let Some(&c) = chars.peek() {
+                            if !c.is_ascii_alphanumeric() && c != '_' {
+                                break;
+                            }
+                            name.push(c.to_ascii_lowercase());
+                            chars.next();
+                        }
+                        name
+                    }
+                    _ => continue,
+                };
+
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    names
+}
+
+/// Advances past the next occurrence of given terminator, or to the end.
+fn skip_until<I: Iterator<Item = char>>(chars: &mut I, terminator: &str) {
+    let terminator: Vec<char> = terminator.chars().collect();
+    let mut matched = 0;
+
+    for c in chars {
+        if c == terminator[matched] {
+            matched += 1;
+            if matched == terminator.len() {
+                return;
+            }
+        } else {
+            matched = if c == terminator[0] { 1 } else { 0 };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryParamsBuilder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn finds_named_bind_markers() {
+        assert_eq!(
+            named_bind_markers(
+                "UPDATE ks.t SET v = :Value, s = ':skipped' -- :comment\n\
+                 WHERE id = :id AND \"c:ol\" = :\"Quoted\" /* :block */ AND v2 = :value"
+            ),
+            vec!["value", "id", "Quoted"]
+        );
+        assert!(named_bind_markers("SELECT * FROM ks.t WHERE id = ?").is_empty());
+        assert!(named_bind_markers("INSERT INTO ks.t (m) VALUES ({'a': 1, 'b':2})").is_empty());
+    }
+
+    #[test]
+    fn checks_named_values() {
+        let query = |values: HashMap<&str, i32>| Query {
+            query: "SELECT * FROM ks.t WHERE a = :a AND b = :b".into(),
+            params: QueryParamsBuilder::new().values_by_name(values).finalize(),
+        };
+
+        let values: HashMap<&str, i32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+        assert!(query(values).check_named_values().is_ok());
+
+        let values: HashMap<&str, i32> = vec![("a", 1)].into_iter().collect();
+        assert!(query(values).check_named_values().is_err());
+    }
+}
@@ -0,0 +1,851 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::error;
+use crate::query::{Query, QueryParamsBuilder, QueryValues};
+use crate::types::value::{Bytes, Value};
+
+/// Comparison operators of `WHERE` and `IF` clauses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    ContainsKey,
+}
+
+impl Operator {
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Contains => "CONTAINS",
+            Operator::ContainsKey => "CONTAINS KEY",
+        }
+    }
+}
+
+/// Elements added to or removed from list and set columns, or keys of entries removed from map
+/// columns, given as a `Vec` for lists and as a set for sets and maps.
+pub trait Elements: Into<Bytes> {}
+
+impl<T: Into<Bytes> + Clone + Debug> Elements for Vec<T> {}
+
+impl<T: Into<Bytes>> Elements for HashSet<T> {}
+
+impl<T: Into<Bytes>> Elements for BTreeSet<T> {}
+
+/// Collections added to collection columns, i.e. `Elements` of lists and sets, or maps of
+/// entries of map columns.
+pub trait Collection: Into<Bytes> {}
+
+impl<T: Into<Bytes> + Clone + Debug> Collection for Vec<T> {}
+
+impl<T: Into<Bytes>> Collection for HashSet<T> {}
+
+impl<T: Into<Bytes>> Collection for BTreeSet<T> {}
+
+impl<K, V> Collection for HashMap<K, V>
+where
+    K: Into<Bytes> + Clone + Debug + Hash + Eq,
+    V: Into<Bytes> + Clone + Debug,
+{
+}
+
+impl<K: Into<Bytes>, V: Into<Bytes>> Collection for BTreeMap<K, V> {}
+
+/// Parts of a clause, e.g. relations of a `WHERE` clause, along with values of their bind
+/// markers.
+#[derive(Debug, Default)]
+struct Clause {
+    parts: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl Clause {
+    fn push<V: Into<Value>>(&mut self, column: &str, operator: Operator, value: V) {
+        self.parts
+            .push(format!("{} {} ?", identifier(column), operator.as_str()));
+        self.values.push(value.into());
+    }
+
+    /// Pushes an assignment adding given value to a column, or subtracting it, e.g. to a
+    /// counter or to a collection.
+    fn push_operation<V: Into<Value>>(&mut self, column: &str, sign: char, value: V) {
+        let column = identifier(column);
+        self.parts
+            .push(format!("{} = {} {} ?", column, column, sign));
+        self.values.push(value.into());
+    }
+
+    /// Pushes an assignment prepending given elements to a list column.
+    fn push_prepend<V: Into<Value>>(&mut self, column: &str, value: V) {
+        let column = identifier(column);
+        self.parts.push(format!("{} = ? + {}", column, column));
+        self.values.push(value.into());
+    }
+
+    /// Pushes an assignment setting an entry of a map column, or an element of a list column.
+    fn push_entry<K: Into<Value>, V: Into<Value>>(&mut self, column: &str, key: K, value: V) {
+        self.parts.push(format!("{}[?] = ?", identifier(column)));
+        self.values.push(key.into());
+        self.values.push(value.into());
+    }
+
+    fn push_in<V: Into<Value>>(&mut self, column: &str, values: Vec<V>) {
+        let markers = vec!["?"; values.len()].join(", ");
+        self.parts
+            .push(format!("{} IN ({})", identifier(column), markers));
+        self.values.extend(values.into_iter().map(Into::into));
+    }
+
+    /// Appends the clause to a query, if it has any parts.
+    fn write(&self, query: &mut String, keyword: &str, separator: &str) {
+        if !self.parts.is_empty() {
+            query.push_str(keyword);
+            query.push_str(&self.parts.join(separator));
+        }
+    }
+}
+
+/// Reserved CQL keywords, which can't be used as unquoted identifiers.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "add",
+    "allow",
+    "alter",
+    "and",
+    "apply",
+    "asc",
+    "authorize",
+    "batch",
+    "begin",
+    "by",
+    "columnfamily",
+    "create",
+    "default",
+    "delete",
+    "desc",
+    "describe",
+    "drop",
+    "entries",
+    "execute",
+    "from",
+    "full",
+    "grant",
+    "if",
+    "in",
+    "index",
+    "infinity",
+    "insert",
+    "into",
+    "is",
+    "keyspace",
+    "limit",
+    "materialized",
+    "mbean",
+    "mbeans",
+    "modify",
+    "nan",
+    "norecursive",
+    "not",
+    "null",
+    "of",
+    "on",
+    "or",
+    "order",
+    "primary",
+    "rename",
+    "replace",
+    "revoke",
+    "schema",
+    "select",
+    "set",
+    "table",
+    "to",
+    "token",
+    "truncate",
+    "unlogged",
+    "unset",
+    "update",
+    "use",
+    "using",
+    "view",
+    "where",
+    "with",
+];
+
+/// Returns the identifier of a column, keyspace or table as it should appear in a query.
+/// Unquoted identifiers are case-insensitive, so only lowercase ones which aren't keywords
+/// are left unquoted. Others are quoted, which keeps their case and prevents them from being
+/// used for injecting CQL.
+fn identifier(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_unquoted = matches!(chars.next(), Some(c) if c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !RESERVED_KEYWORDS.contains(&name);
+
+    if is_unquoted {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Returns the name of a table, optionally qualified with its keyspace, as it should appear in
+/// a query.
+fn table_name(name: &str) -> String {
+    match name.split_once('.') {
+        Some((keyspace, table)) => format!("{}.{}", identifier(keyspace), identifier(table)),
+        None => identifier(name),
+    }
+}
+
+/// Appends the `USING` clause of a statement, if it has a TTL or a timestamp.
+fn write_using(query: &mut String, ttl: Option<i32>, timestamp: Option<i64>) {
+    let options: Vec<String> = ttl
+        .map(|ttl| format!("TTL {}", ttl))
+        .into_iter()
+        .chain(timestamp.map(|timestamp| format!("TIMESTAMP {}", timestamp)))
+        .collect();
+
+    if !options.is_empty() {
+        query.push_str(" USING ");
+        query.push_str(&options.join(" AND "));
+    }
+}
+
+/// Checks that a lightweight transaction is conditioned either on the existence of the row or
+/// on values of its columns, as both can't be combined.
+fn check_conditions(if_exists: bool, conditions: &Clause) -> error::Result<()> {
+    if if_exists && !conditions.parts.is_empty() {
+        return Err(error::Error::General(
+            "IF EXISTS can't be combined with conditions of columns".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn into_query(query: String, values: Vec<Value>) -> Query {
+    let mut params = QueryParamsBuilder::new();
+    if !values.is_empty() {
+        params = params.values(QueryValues::SimpleValues(values));
+    }
+
+    Query {
+        query,
+        params: params.finalize(),
+    }
+}
+
+/// Builder of `SELECT` statements. Values of relations are bound to the built query rather
+/// than concatenated into its text, and identifiers are quoted when needed.
+///
+/// ```
+/// use cdrs_tokio::query::{Operator, SelectBuilder};
+///
+/// let query = SelectBuilder::new("ks.users")
+///     .columns(&["name", "email"])
+///     .where_eq("id", 1)
+///     .where_op("age", Operator::Gt, 18)
+///     .limit(10)
+///     .build();
+///
+/// assert_eq!(
+///     query.query,
+///     "SELECT name, email FROM ks.users WHERE id = ? AND age > ? LIMIT 10"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SelectBuilder {
+    table: String,
+    columns: Vec<String>,
+    relations: Clause,
+    order_by: Vec<String>,
+    limit: Option<i32>,
+    allow_filtering: bool,
+}
+
+impl SelectBuilder {
+    /// Creates a builder selecting from given table, optionally qualified with its keyspace.
+    pub fn new<T: Into<String>>(table: T) -> Self {
+        SelectBuilder {
+            table: table.into(),
+            columns: vec![],
+            relations: Default::default(),
+            order_by: vec![],
+            limit: None,
+            allow_filtering: false,
+        }
+    }
+
+    /// Sets selected columns. All columns are selected by default.
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|column| identifier(column)).collect();
+        self
+    }
+
+    /// Adds a relation requiring given column to be equal to given value.
+    pub fn where_eq<V: Into<Value>>(self, column: &str, value: V) -> Self {
+        self.where_op(column, Operator::Eq, value)
+    }
+
+    /// Adds a relation comparing given column with given value.
+    pub fn where_op<V: Into<Value>>(mut self, column: &str, operator: Operator, value: V) -> Self {
+        self.relations.push(column, operator, value);
+        self
+    }
+
+    /// Adds a relation requiring given column to be equal to one of given values.
+    pub fn where_in<V: Into<Value>>(mut self, column: &str, values: Vec<V>) -> Self {
+        self.relations.push_in(column, values);
+        self
+    }
+
+    /// Orders rows by given clustering column, ascending.
+    pub fn order_by(mut self, column: &str) -> Self {
+        self.order_by.push(format!("{} ASC", identifier(column)));
+        self
+    }
+
+    /// Orders rows by given clustering column, descending.
+    pub fn order_by_desc(mut self, column: &str) -> Self {
+        self.order_by.push(format!("{} DESC", identifier(column)));
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn allow_filtering(mut self) -> Self {
+        self.allow_filtering = true;
+        self
+    }
+
+    pub fn build(self) -> Query {
+        let columns = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            self.columns.join(", ")
+        };
+
+        let mut query = format!("SELECT {} FROM {}", columns, table_name(&self.table));
+        self.relations.write(&mut query, " WHERE ", " AND ");
+        if !self.order_by.is_empty() {
+            query.push_str(" ORDER BY ");
+            query.push_str(&self.order_by.join(", "));
+        }
+        if let Some(limit) = self.limit {
+            query.push_str(&format!(" LIMIT {}", limit));
+        }
+        if self.allow_filtering {
+            query.push_str(" ALLOW FILTERING");
+        }
+
+        into_query(query, self.relations.values)
+    }
+}
+
+/// Builder of `INSERT` statements.
+///
+/// ```
+/// use cdrs_tokio::query::InsertBuilder;
+///
+/// let query = InsertBuilder::new("ks.users")
+///     .value("id", 1)
+///     .value("name", "Alice")
+///     .if_not_exists()
+///     .ttl(3600)
+///     .build();
+///
+/// assert_eq!(
+///     query.query,
+///     "INSERT INTO ks.users (id, name) VALUES (?, ?) IF NOT EXISTS USING TTL 3600"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct InsertBuilder {
+    table: String,
+    columns: Vec<String>,
+    values: Vec<Value>,
+    if_not_exists: bool,
+    ttl: Option<i32>,
+    timestamp: Option<i64>,
+}
+
+impl InsertBuilder {
+    /// Creates a builder inserting into given table, optionally qualified with its keyspace.
+    pub fn new<T: Into<String>>(table: T) -> Self {
+        InsertBuilder {
+            table: table.into(),
+            columns: vec![],
+            values: vec![],
+            if_not_exists: false,
+            ttl: None,
+            timestamp: None,
+        }
+    }
+
+    /// Sets the value of given column.
+    pub fn value<V: Into<Value>>(mut self, column: &str, value: V) -> Self {
+        self.columns.push(identifier(column));
+        self.values.push(value.into());
+        self
+    }
+
+    /// Makes the insert a lightweight transaction, applied only if the row doesn't exist.
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    /// Sets the time to live of inserted values, in seconds.
+    pub fn ttl(mut self, ttl: i32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the timestamp of the write, in microseconds since the Unix epoch.
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn build(self) -> Query {
+        let markers = vec!["?"; self.values.len()].join(", ");
+        let mut query = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table_name(&self.table),
+            self.columns.join(", "),
+            markers
+        );
+        if self.if_not_exists {
+            query.push_str(" IF NOT EXISTS");
+        }
+        write_using(&mut query, self.ttl, self.timestamp);
+
+        into_query(query, self.values)
+    }
+}
+
+/// Builder of `UPDATE` statements.
+///
+/// ```
+/// use cdrs_tokio::query::UpdateBuilder;
+///
+/// let query = UpdateBuilder::new("ks.users")
+///     .set("email", "alice@example.com")
+///     .where_eq("id", 1)
+///     .if_eq("email", "alice@example.org")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(
+///     query.query,
+///     "UPDATE ks.users SET email = ? WHERE id = ? IF email = ?"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct UpdateBuilder {
+    table: String,
+    assignments: Clause,
+    relations: Clause,
+    conditions: Clause,
+    if_exists: bool,
+    ttl: Option<i32>,
+    timestamp: Option<i64>,
+}
+
+impl UpdateBuilder {
+    /// Creates a builder updating given table, optionally qualified with its keyspace.
+    pub fn new<T: Into<String>>(table: T) -> Self {
+        UpdateBuilder {
+            table: table.into(),
+            assignments: Default::default(),
+            relations: Default::default(),
+            conditions: Default::default(),
+            if_exists: false,
+            ttl: None,
+            timestamp: None,
+        }
+    }
+
+    /// Sets the value of given column.
+    pub fn set<V: Into<Value>>(mut self, column: &str, value: V) -> Self {
+        self.assignments.push(column, Operator::Eq, value);
+        self
+    }
+
+    /// Adds given value to a counter column, i.e. `SET column = column + ?`. Counter updates
+    /// can't have a TTL or timestamp and can only be batched in counter batches.
+    pub fn increment(mut self, column: &str, by: i64) -> Self {
+        self.assignments.push_operation(column, '+', by);
+        self
+    }
+
+    /// Subtracts given value from a counter column, i.e. `SET column = column - ?`.
+    pub fn decrement(mut self, column: &str, by: i64) -> Self {
+        self.assignments.push_operation(column, '-', by);
+        self
+    }
+
+    /// Appends elements to a list column, or adds them to a set column, i.e.
+    /// `SET column = column + ?`. Elements are given as a `Vec` for lists and as a set for sets,
+    /// and entries are added to map columns the same way, given as a map.
+    pub fn append<C: Collection>(mut self, column: &str, elements: C) -> Self {
+        self.assignments.push_operation(column, '+', elements);
+        self
+    }
+
+    /// Prepends elements to a list column, i.e. `SET column = ? + column`.
+    pub fn prepend<T>(mut self, column: &str, elements: Vec<T>) -> Self
+    where
+        T: Into<Bytes> + Clone + Debug,
+    {
+        self.assignments.push_prepend(column, elements);
+        self
+    }
+
+    /// Removes all occurrences of elements from a list column, elements from a set column or
+    /// entries of given keys from a map column, i.e. `SET column = column - ?`. Elements and
+    /// keys are given as a `Vec` for lists and as a set for sets and maps.
+    pub fn remove<E: Elements>(mut self, column: &str, elements: E) -> Self {
+        self.assignments.push_operation(column, '-', elements);
+        self
+    }
+
+    /// Sets the value of an entry of a map column, i.e. `SET column[?] = ?`, or of the element
+    /// at given index of a list column, given as an `i32`. Keys and values cannot be null.
+    pub fn put<K: Into<Bytes>, V: Into<Bytes>>(mut self, column: &str, key: K, value: V) -> Self {
+        self.assignments.push_entry(column, key, value);
+        self
+    }
+
+    /// Adds a relation requiring given column to be equal to given value.
+    pub fn where_eq<V: Into<Value>>(self, column: &str, value: V) -> Self {
+        self.where_op(column, Operator::Eq, value)
+    }
+
+    /// Adds a relation comparing given column with given value.
+    pub fn where_op<V: Into<Value>>(mut self, column: &str, operator: Operator, value: V) -> Self {
+        self.relations.push(column, operator, value);
+        self
+    }
+
+    /// Adds a relation requiring given column to be equal to one of given values.
+    pub fn where_in<V: Into<Value>>(mut self, column: &str, values: Vec<V>) -> Self {
+        self.relations.push_in(column, values);
+        self
+    }
+
+    /// Makes the update a lightweight transaction, applied only if given column is equal to
+    /// given value.
+    pub fn if_eq<V: Into<Value>>(self, column: &str, value: V) -> Self {
+        self.if_op(column, Operator::Eq, value)
+    }
+
+    /// Makes the update a lightweight transaction, applied only if given column compares with
+    /// given value as given by the operator.
+    pub fn if_op<V: Into<Value>>(mut self, column: &str, operator: Operator, value: V) -> Self {
+        self.conditions.push(column, operator, value);
+        self
+    }
+
+    /// Makes the update a lightweight transaction, applied only if the row exists. Can't be
+    /// combined with conditions of columns.
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    /// Sets the time to live of updated values, in seconds.
+    pub fn ttl(mut self, ttl: i32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the timestamp of the write, in microseconds since the Unix epoch.
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Builds the query. Fails if it has both `IF EXISTS` and conditions of columns.
+    pub fn build(self) -> error::Result<Query> {
+        check_conditions(self.if_exists, &self.conditions)?;
+
+        let mut query = format!("UPDATE {}", table_name(&self.table));
+        write_using(&mut query, self.ttl, self.timestamp);
+        self.assignments.write(&mut query, " SET ", ", ");
+        self.relations.write(&mut query, " WHERE ", " AND ");
+        if self.if_exists {
+            query.push_str(" IF EXISTS");
+        } else {
+            self.conditions.write(&mut query, " IF ", " AND ");
+        }
+
+        let mut values = self.assignments.values;
+        values.extend(self.relations.values);
+        values.extend(self.conditions.values);
+
+        Ok(into_query(query, values))
+    }
+}
+
+/// Builder of `DELETE` statements.
+///
+/// ```
+/// use cdrs_tokio::query::DeleteBuilder;
+///
+/// let query = DeleteBuilder::new("ks.users")
+///     .columns(&["email"])
+///     .where_eq("id", 1)
+///     .if_exists()
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(query.query, "DELETE email FROM ks.users WHERE id = ? IF EXISTS");
+/// ```
+#[derive(Debug)]
+pub struct DeleteBuilder {
+    table: String,
+    columns: Vec<String>,
+    relations: Clause,
+    conditions: Clause,
+    if_exists: bool,
+    timestamp: Option<i64>,
+}
+
+impl DeleteBuilder {
+    /// Creates a builder deleting from given table, optionally qualified with its keyspace.
+    pub fn new<T: Into<String>>(table: T) -> Self {
+        DeleteBuilder {
+            table: table.into(),
+            columns: vec![],
+            relations: Default::default(),
+            conditions: Default::default(),
+            if_exists: false,
+            timestamp: None,
+        }
+    }
+
+    /// Sets columns to delete. Whole rows are deleted by default.
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|column| identifier(column)).collect();
+        self
+    }
+
+    /// Adds a relation requiring given column to be equal to given value.
+    pub fn where_eq<V: Into<Value>>(self, column: &str, value: V) -> Self {
+        self.where_op(column, Operator::Eq, value)
+    }
+
+    /// Adds a relation comparing given column with given value.
+    pub fn where_op<V: Into<Value>>(mut self, column: &str, operator: Operator, value: V) -> Self {
+        self.relations.push(column, operator, value);
+        self
+    }
+
+    /// Adds a relation requiring given column to be equal to one of given values.
+    pub fn where_in<V: Into<Value>>(mut self, column: &str, values: Vec<V>) -> Self {
+        self.relations.push_in(column, values);
+        self
+    }
+
+    /// Makes the delete a lightweight transaction, applied only if given column is equal to
+    /// given value.
+    pub fn if_eq<V: Into<Value>>(self, column: &str, value: V) -> Self {
+        self.if_op(column, Operator::Eq, value)
+    }
+
+    /// Makes the delete a lightweight transaction, applied only if given column compares with
+    /// given value as given by the operator.
+    pub fn if_op<V: Into<Value>>(mut self, column: &str, operator: Operator, value: V) -> Self {
+        self.conditions.push(column, operator, value);
+        self
+    }
+
+    /// Makes the delete a lightweight transaction, applied only if the row exists. Can't be
+    /// combined with conditions of columns.
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    /// Sets the timestamp of the delete, in microseconds since the Unix epoch.
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Builds the query. Fails if it has both `IF EXISTS` and conditions of columns.
+    pub fn build(self) -> error::Result<Query> {
+        check_conditions(self.if_exists, &self.conditions)?;
+
+        let mut query = "DELETE ".to_string();
+        if !self.columns.is_empty() {
+            query.push_str(&self.columns.join(", "));
+            query.push(' ');
+        }
+        query.push_str("FROM ");
+        query.push_str(&table_name(&self.table));
+        write_using(&mut query, None, self.timestamp);
+        self.relations.write(&mut query, " WHERE ", " AND ");
+        if self.if_exists {
+            query.push_str(" IF EXISTS");
+        } else {
+            self.conditions.write(&mut query, " IF ", " AND ");
+        }
+
+        let mut values = self.relations.values;
+        values.extend(self.conditions.values);
+
+        Ok(into_query(query, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn values_len(query: &Query) -> usize {
+        query.params.values.as_ref().map_or(0, QueryValues::len)
+    }
+
+    #[test]
+    fn builds_selects_and_inserts() {
+        let query = SelectBuilder::new("ks.events")
+            .where_eq("user", 1)
+            .where_in("day", vec![1, 2])
+            .where_op("Time", Operator::Ge, 10i64)
+            .where_op("token", Operator::Lt, 5i64)
+            .order_by_desc("time")
+            .allow_filtering()
+            .build();
+        assert_eq!(
+            query.query,
+            "SELECT * FROM ks.events WHERE user = ? AND day IN (?, ?) AND \"Time\" >= ? \
+             AND \"token\" < ? ORDER BY time DESC ALLOW FILTERING"
+        );
+        assert_eq!(values_len(&query), 5);
+
+        let query = InsertBuilder::new("events")
+            .value("user", 1)
+            .value("a\" = 1; --", 2)
+            .timestamp(100)
+            .ttl(60)
+            .build();
+        assert_eq!(
+            query.query,
+            "INSERT INTO events (user, \"a\"\" = 1; --\") VALUES (?, ?) USING TTL 60 AND TIMESTAMP 100"
+        );
+        assert_eq!(values_len(&query), 2);
+
+        assert!(SelectBuilder::new("t").build().params.values.is_none());
+    }
+
+    #[test]
+    fn builds_updates_and_deletes() {
+        let query = UpdateBuilder::new("ks.users")
+            .set("name", "Bob")
+            .set("age", 30)
+            .where_eq("id", 1)
+            .if_op("age", Operator::Lt, 30)
+            .timestamp(100)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.query,
+            "UPDATE ks.users USING TIMESTAMP 100 SET name = ?, age = ? WHERE id = ? IF age < ?"
+        );
+        assert_eq!(values_len(&query), 4);
+
+        let query = DeleteBuilder::new("ks.users")
+            .where_eq("id", 1)
+            .if_eq("name", "Bob")
+            .timestamp(100)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.query,
+            "DELETE FROM ks.users USING TIMESTAMP 100 WHERE id = ? IF name = ?"
+        );
+        assert_eq!(values_len(&query), 2);
+
+        assert!(UpdateBuilder::new("ks.users")
+            .set("name", "Bob")
+            .where_eq("id", 1)
+            .if_eq("name", "Alice")
+            .if_exists()
+            .build()
+            .is_err());
+        assert!(DeleteBuilder::new("ks.users")
+            .where_eq("id", 1)
+            .if_exists()
+            .if_eq("name", "Bob")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn builds_counter_updates() {
+        let query = UpdateBuilder::new("ks.counters")
+            .increment("views", 1)
+            .decrement("old views", 2)
+            .where_eq("id", 3)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.query,
+            "UPDATE ks.counters SET views = views + ?, \"old views\" = \"old views\" - ? WHERE id = ?"
+        );
+
+        let values: Vec<Vec<u8>> = match query.params.values {
+            Some(QueryValues::SimpleValues(values)) => {
+                values.into_iter().map(|value| value.body).collect()
+            }
+            values => panic!("unexpected values: {:?}", values),
+        };
+        assert_eq!(
+            values,
+            vec![
+                1i64.to_be_bytes().to_vec(),
+                2i64.to_be_bytes().to_vec(),
+                3i32.to_be_bytes().to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_collection_updates() {
+        let tags: HashSet<&str> = vec!["old"].into_iter().collect();
+        let query = UpdateBuilder::new("ks.users")
+            .append("emails", vec!["b@example.com"])
+            .prepend("logins", vec![100i64])
+            .remove("tags", tags)
+            .put("settings", "theme", "dark")
+            .put("scores", 0, 10)
+            .where_eq("id", 1)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.query,
+            "UPDATE ks.users SET emails = emails + ?, logins = ? + logins, tags = tags - ?, \
+             settings[?] = ?, scores[?] = ? WHERE id = ?"
+        );
+        assert_eq!(values_len(&query), 8);
+    }
+}
@@ -1,41 +1,92 @@
 use bb8;
-use tokio::sync::Mutex;
 use async_trait::async_trait;
 
-use crate::cluster::{GetCompressor, GetConnection, ResponseCache};
+use crate::cluster::{GetCompressor, GetConnection};
 use crate::error;
-use crate::frame::{Frame, IntoBytes};
-use crate::query::{Query, QueryParams, QueryParamsBuilder, QueryValues};
+use crate::frame::{Frame, TryFromRow};
+use crate::query::{
+    Page, Query, QueryParams, QueryParamsBuilder, QueryValues, RowStream, TypedRowStream,
+    DEFAULT_PAGE_SIZE,
+};
 use crate::transport::CDRSTransport;
+#[cfg(feature = "json")]
+use crate::types::json::{to_json, Json};
+use crate::types::LONG_STR_LEN;
+#[cfg(feature = "json")]
+use serde::{de::DeserializeOwned, Serialize};
 
-use super::utils::{prepare_flags, send_frame};
+use super::utils::{into_one_row, into_rows, prepare_flags, send_request, Request, Routing};
 
 #[async_trait]
 pub trait QueryExecutor<
     T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
->: GetConnection<T, M> + GetCompressor<'static> + ResponseCache + Sync
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+>: GetConnection<T, M> + GetCompressor<'static> + Sync
 {
     async fn query_with_params_tw<Q: ToString + Send>(
         &self,
         query: Q,
-        query_params: QueryParams,
+        mut query_params: QueryParams,
         with_tracing: bool,
         with_warnings: bool,
     ) -> error::Result<Frame>
     where
         Self: Sized,
     {
+        if !query_params.explicit_consistency {
+            query_params.consistency = self.default_consistency();
+        }
+
+        match query_params.serial_consistency {
+            Some(serial_consistency) => serial_consistency.check_serial()?,
+            None => {
+                if let Some(serial_consistency) = self.default_serial_consistency() {
+                    query_params.set_serial_consistency(serial_consistency);
+                }
+            }
+        }
+
+        // queries without explicit timestamps get ones from the client, if it generates them
+        let timestamp = query_params
+            .timestamp
+            .or_else(|| self.timestamp_generator().next_timestamp());
+        if let Some(timestamp) = timestamp {
+            query_params.set_timestamp(timestamp);
+        }
+
+        // queries go to nodes storing the partition, if it's set explicitly
+        let routing = query_params.take_routing(&*self.partitioner());
+        let target_node = query_params.target_node.take();
+
         let query = Query {
             query: query.to_string(),
             params: query_params,
         };
+        query.check_named_values()?;
+
+        // consistency follows the query string
+        let consistency_offset = LONG_STR_LEN + query.query.len();
+        let is_idempotent = query.is_idempotent();
+        let deadline = query.params.deadline;
+        let priority = query.params.priority;
 
         let flags = prepare_flags(with_tracing, with_warnings);
 
         let query_frame = Frame::new_query(query, flags);
 
-        send_frame(self, query_frame.into_cbytes(), query_frame.stream).await
+        let routing = routing.as_ref().map(|(keyspace, token)| Routing {
+            keyspace,
+            table: None,
+            token: token.clone(),
+        });
+        let request = Request::new(query_frame)
+            .with_routing(routing)
+            .with_target_node(target_node)
+            .with_consistency_offset(consistency_offset)
+            .with_idempotent(is_idempotent)
+            .with_deadline(deadline)
+            .with_priority(priority);
+        send_request(self, request).await
     }
 
     /// Executes a query with default parameters:
@@ -102,4 +153,112 @@ pub trait QueryExecutor<
     {
         self.query_with_params_tw(query, query_params, false, false).await
     }
+
+    /// Executes a query and returns a stream of its rows, which fetches further pages as it's
+    /// consumed. Page size is taken from query params, or `DEFAULT_PAGE_SIZE` if not set.
+    fn query_iter<Q: ToString>(&self, query: Q, mut query_params: QueryParams) -> RowStream<'_>
+    where
+        Self: Sized,
+    {
+        if query_params.page_size.is_none() {
+            query_params.set_page_size(DEFAULT_PAGE_SIZE);
+        }
+
+        let query = query.to_string();
+        RowStream::new(move |paging_state| {
+            let mut query_params = query_params.clone();
+            if let Some(paging_state) = paging_state {
+                query_params.set_paging_state(paging_state);
+            }
+
+            let response = self.query_with_params(query.clone(), query_params);
+            Box::pin(async move { response.await.and_then(Page::from_frame) })
+        })
+    }
+
+    /// Executes a query which should return exactly one row and converts the row. Fails if
+    /// the query returns no rows or more than one.
+    async fn query_one<Q: ToString + Send, R: TryFromRow + Send>(
+        &self,
+        query: Q,
+        query_params: QueryParams,
+    ) -> error::Result<R>
+    where
+        Self: Sized,
+    {
+        let frame = self.query_with_params(query, query_params).await?;
+        into_one_row(frame).and_then(R::try_from_row)
+    }
+
+    /// Executes a query and converts its first row, if it returned any.
+    async fn query_first<Q: ToString + Send, R: TryFromRow + Send>(
+        &self,
+        query: Q,
+        query_params: QueryParams,
+    ) -> error::Result<Option<R>>
+    where
+        Self: Sized,
+    {
+        let frame = self.query_with_params(query, query_params).await?;
+        into_rows(frame)?
+            .into_iter()
+            .next()
+            .map(R::try_from_row)
+            .transpose()
+    }
+
+    /// Executes a query with given values and converts all returned rows.
+    async fn query_map<Q: ToString + Send, V: Into<QueryValues> + Send, R: TryFromRow + Send>(
+        &self,
+        query: Q,
+        values: V,
+    ) -> error::Result<Vec<R>>
+    where
+        Self: Sized,
+    {
+        let frame = self.query_with_values(query, values).await?;
+        into_rows(frame)?
+            .into_iter()
+            .map(R::try_from_row)
+            .collect()
+    }
+
+    /// Executes a query with given values and returns a stream of converted rows, which
+    /// fetches further pages as it's consumed.
+    fn query_map_iter<Q: ToString, V: Into<QueryValues>, R: TryFromRow>(
+        &self,
+        query: Q,
+        values: V,
+    ) -> TypedRowStream<'_, R>
+    where
+        Self: Sized,
+    {
+        let query_params = QueryParamsBuilder::new().values(values.into()).finalize();
+        self.query_iter(query, query_params).into_typed()
+    }
+    /// Executes a `SELECT JSON` query with given values and deserializes the JSON of returned
+    /// rows, e.g. into `serde_json::Value` or a type implementing `Deserialize`.
+    #[cfg(feature = "json")]
+    async fn query_json<Q, V, R>(&self, query: Q, values: V) -> error::Result<Vec<R>>
+    where
+        Self: Sized,
+        Q: ToString + Send,
+        V: Into<QueryValues> + Send,
+        R: DeserializeOwned + Send,
+    {
+        let rows: Vec<Json<R>> = self.query_map(query, values).await?;
+        Ok(rows.into_iter().map(Json::into_inner).collect())
+    }
+
+    /// Inserts a value serialized as JSON into given table with `INSERT INTO table JSON ?`.
+    /// Columns missing from the JSON are set to null, as with CQL's `INSERT JSON`.
+    #[cfg(feature = "json")]
+    async fn insert_json<S: Serialize + Sync>(&self, table: &str, value: &S) -> error::Result<Frame>
+    where
+        Self: Sized,
+    {
+        let json = to_json(value)?;
+        self.query_with_values(format!("INSERT INTO {} JSON ?", table), query_values!(json))
+            .await
+    }
 }
@@ -1,15 +1,27 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::net::SocketAddr;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::cluster::{Partitioner, Token};
 use crate::consistency::Consistency;
 use crate::frame::AsByte;
 use crate::frame::IntoBytes;
 use crate::query::query_flags::QueryFlags;
 use crate::query::query_values::QueryValues;
+use crate::types::value::Value;
 use crate::types::{to_bigint, to_int, to_short, CBytes};
 
 /// Parameters of Query for query operation.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct QueryParams {
     /// Cassandra consistency level.
     pub consistency: Consistency,
+    /// Whether the consistency was set explicitly, rather than left to the default consistency
+    /// of the session. Not sent to the server.
+    pub explicit_consistency: bool,
     /// Array of query flags.
     pub flags: Vec<QueryFlags>,
     /// Were values provided with names
@@ -24,15 +36,149 @@ pub struct QueryParams {
     pub serial_consistency: Option<Consistency>,
     /// Timestamp.
     pub timestamp: Option<i64>,
+    /// Whether the query can be safely executed more than once, e.g. speculatively. Not sent
+    /// to the server.
+    pub is_idempotent: bool,
+    /// Time limit of the query, including retries and speculative executions. Not sent to the
+    /// server.
+    pub deadline: Option<Duration>,
+    /// Priority of the query when connections are saturated. Not sent to the server.
+    pub priority: RequestPriority,
+    /// Keyspace of the partition the query is routed to, if set explicitly. Not sent to the
+    /// server.
+    pub keyspace: Option<String>,
+    /// Serialized partition key of the partition the query is routed to. Not sent to the
+    /// server.
+    pub routing_key: Option<Vec<u8>>,
+    /// Token of the partition the query is routed to, taking precedence over the routing key.
+    /// Not sent to the server.
+    pub token: Option<Token>,
+    /// Node the query is sent to, regardless of its partition and the load balancing strategy.
+    /// Not sent to the server.
+    pub target_node: Option<TargetNode>,
+}
+
+/// Node a request is pinned to, e.g. for querying node-local virtual tables like
+/// `system.clients`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetNode {
+    Addr(SocketAddr),
+    /// Host id of a node discovered by `ClusterSessionBuilder`, as in `system.local`.
+    HostId(Uuid),
+}
+
+/// Priority of a request, e.g. to keep user-facing queries fast while bulk jobs saturate
+/// connections. Once a connection has as many requests in flight as it allows, waiting requests
+/// are sent in order of their priority, and in order of arrival within a priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RequestPriority {
+    Background,
+    #[default]
+    Normal,
+    Interactive,
+}
+
+impl fmt::Display for TargetNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetNode::Addr(addr) => write!(f, "{}", addr),
+            TargetNode::HostId(host_id) => write!(f, "with host id {}", host_id),
+        }
+    }
 }
 
 impl QueryParams {
+    /// Sets consistency of Query request params, overriding the default consistency of the
+    /// session.
+    pub fn set_consistency(&mut self, consistency: Consistency) {
+        self.consistency = consistency;
+        self.explicit_consistency = true;
+    }
+
     /// Sets values of Query request params.
     pub fn set_values(&mut self, values: QueryValues) {
-        self.flags.push(QueryFlags::Value);
+        let flags = self.flags_as_byte();
+        if !QueryFlags::has_value(flags) {
+            self.flags.push(QueryFlags::Value);
+        }
+
+        let with_names = values.with_names();
+        if with_names && !QueryFlags::has_with_names_for_values(flags) {
+            self.flags.push(QueryFlags::WithNamesForValues);
+        }
+        if !with_names {
+            self.flags
+                .retain(|flag| !matches!(flag, QueryFlags::WithNamesForValues));
+        }
+
+        self.with_names = Some(with_names);
         self.values = Some(values);
     }
 
+    /// Sets values of `:name` bind markers of Query request params.
+    pub fn set_values_by_name<S, V>(&mut self, values: HashMap<S, V>)
+    where
+        S: ToString + Hash + Eq,
+        V: Into<Value> + Clone,
+    {
+        self.set_values(values.into());
+    }
+
+    /// Sets page size of Query request params.
+    pub fn set_page_size(&mut self, page_size: i32) {
+        if !QueryFlags::has_page_size(self.flags_as_byte()) {
+            self.flags.push(QueryFlags::PageSize);
+        }
+        self.page_size = Some(page_size);
+    }
+
+    /// Sets paging state of Query request params.
+    pub fn set_paging_state(&mut self, paging_state: CBytes) {
+        if !QueryFlags::has_with_paging_state(self.flags_as_byte()) {
+            self.flags.push(QueryFlags::WithPagingState);
+        }
+        self.paging_state = Some(paging_state);
+    }
+
+    /// Sets serial consistency of Query request params, used by conditional updates.
+    pub fn set_serial_consistency(&mut self, serial_consistency: Consistency) {
+        if !QueryFlags::has_with_serial_consistency(self.flags_as_byte()) {
+            self.flags.push(QueryFlags::WithSerialConsistency);
+        }
+        self.serial_consistency = Some(serial_consistency);
+    }
+
+    /// Asks nodes to leave metadata out of returned rows, for executions of prepared queries
+    /// whose result metadata is already known.
+    pub fn set_skip_metadata(&mut self) {
+        if !QueryFlags::has_skip_metadata(self.flags_as_byte()) {
+            self.flags.push(QueryFlags::SkipMetadata);
+        }
+    }
+
+    /// Takes the explicitly set partition of the query, as its keyspace and token computed with
+    /// given partitioner.
+    pub(crate) fn take_routing(
+        &mut self,
+        partitioner: &dyn Partitioner,
+    ) -> Option<(String, Token)> {
+        let routing_key = self.routing_key.take();
+        let token = self
+            .token
+            .take()
+            .or_else(|| routing_key.map(|routing_key| partitioner.hash(&routing_key)));
+
+        self.keyspace.take().zip(token)
+    }
+
+    /// Sets default timestamp of Query request params.
+    pub fn set_timestamp(&mut self, timestamp: i64) {
+        if !QueryFlags::has_with_default_timestamp(self.flags_as_byte()) {
+            self.flags.push(QueryFlags::WithDefaultTimestamp);
+        }
+        self.timestamp = Some(timestamp);
+    }
+
     fn flags_as_byte(&self) -> u8 {
         self.flags.iter().fold(0, |acc, flag| acc | flag.as_byte())
     }
@@ -1,10 +1,16 @@
-use super::{QueryFlags, QueryParams, QueryValues};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Duration;
+
+use super::{QueryFlags, QueryParams, QueryValues, RequestPriority, TargetNode};
+use crate::cluster::Token;
 use crate::consistency::Consistency;
+use crate::types::value::Value;
 use crate::types::CBytes;
 
 #[derive(Debug, Default)]
 pub struct QueryParamsBuilder {
-    consistency: Consistency,
+    consistency: Option<Consistency>,
     flags: Option<Vec<QueryFlags>>,
     values: Option<QueryValues>,
     with_names: Option<bool>,
@@ -12,18 +18,26 @@ pub struct QueryParamsBuilder {
     paging_state: Option<CBytes>,
     serial_consistency: Option<Consistency>,
     timestamp: Option<i64>,
+    is_idempotent: bool,
+    deadline: Option<Duration>,
+    priority: RequestPriority,
+    keyspace: Option<String>,
+    routing_key: Option<Vec<u8>>,
+    token: Option<Token>,
+    target_node: Option<TargetNode>,
 }
 
 impl QueryParamsBuilder {
     /// Factory function that returns new `QueryBuilder`.
-    /// Default consistency level is `One`
+    /// Default consistency level is the default consistency of the session, `One` unless
+    /// configured otherwise
     pub fn new() -> QueryParamsBuilder {
         Default::default()
     }
 
     /// Sets new query consistency
     pub fn consistency(mut self, consistency: Consistency) -> Self {
-        self.consistency = consistency;
+        self.consistency = Some(consistency);
 
         self
     }
@@ -48,6 +62,15 @@ impl QueryParamsBuilder {
         self
     }
 
+    /// Sets values of `:name` bind markers.
+    pub fn values_by_name<S, V>(self, values: HashMap<S, V>) -> Self
+    where
+        S: ToString + Hash + Eq,
+        V: Into<Value> + Clone,
+    {
+        self.values(values.into())
+    }
+
     /// Sets new with_names parameter value.
     builder_opt_field!(with_names, bool);
 
@@ -75,16 +98,80 @@ impl QueryParamsBuilder {
         self
     }
 
-    /// Sets new serial_consistency value.
-    builder_opt_field!(serial_consistency, Consistency);
+    /// Sets serial consistency of conditional updates, which has to be `Serial` or
+    /// `LocalSerial`. Default is the default serial consistency of the session, if any, or
+    /// `Serial` otherwise.
+    pub fn serial_consistency(mut self, serial_consistency: Consistency) -> Self {
+        self.serial_consistency = Some(serial_consistency);
+        self.flags = self.flags.or(Some(vec![])).map(|mut flags| {
+            flags.push(QueryFlags::WithSerialConsistency);
+            flags
+        });
+
+        self
+    }
 
     /// Sets new timestamp value.
     builder_opt_field!(timestamp, i64);
 
+    /// Marks the query as idempotent, i.e. safe to execute more than once, which allows
+    /// executing it speculatively. Queries are not idempotent by default.
+    pub fn idempotent(mut self, is_idempotent: bool) -> Self {
+        self.is_idempotent = is_idempotent;
+
+        self
+    }
+
+    /// Sets the time limit of the query, including retries and speculative executions,
+    /// overriding the request deadline of the session.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+
+        self
+    }
+
+    /// Sets the priority of the query when connections are saturated. Queries have `Normal`
+    /// priority by default.
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Routes the query to nodes storing the partition with given serialized key in given
+    /// keyspace, for queries whose partition can't be inferred, e.g. ones with values written
+    /// into the query text. Routing keys of partition keys with many columns are serialized
+    /// with `cluster::routing_key()`. Overrides the partition inferred for prepared queries.
+    pub fn routing_key<K: Into<String>>(mut self, keyspace: K, routing_key: Vec<u8>) -> Self {
+        self.keyspace = Some(keyspace.into());
+        self.routing_key = Some(routing_key);
+
+        self
+    }
+
+    /// Routes the query to nodes storing given token in given keyspace, as `routing_key()`
+    /// does for tokens which are already known.
+    pub fn token<K: Into<String>>(mut self, keyspace: K, token: Token) -> Self {
+        self.keyspace = Some(keyspace.into());
+        self.token = Some(token);
+
+        self
+    }
+
+    /// Sends the query to given node only, bypassing token-aware routing and the load
+    /// balancing strategy. Failed queries are retried on the same node only, and not executed
+    /// speculatively.
+    pub fn target_node(mut self, target_node: TargetNode) -> Self {
+        self.target_node = Some(target_node);
+
+        self
+    }
+
     /// Finalizes query building process and returns query itself
     pub fn finalize(self) -> QueryParams {
         QueryParams {
-            consistency: self.consistency,
+            consistency: self.consistency.unwrap_or_default(),
+            explicit_consistency: self.consistency.is_some(),
             flags: self.flags.unwrap_or(vec![]),
             values: self.values,
             with_names: self.with_names,
@@ -92,6 +179,74 @@ impl QueryParamsBuilder {
             paging_state: self.paging_state,
             serial_consistency: self.serial_consistency,
             timestamp: self.timestamp,
+            is_idempotent: self.is_idempotent,
+            deadline: self.deadline,
+            priority: self.priority,
+            keyspace: self.keyspace,
+            routing_key: self.routing_key,
+            token: self.token,
+            target_node: self.target_node,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::{Murmur3Partitioner, Partitioner};
+
+    #[test]
+    fn tracks_explicit_consistency() {
+        let params = QueryParamsBuilder::new().finalize();
+        assert_eq!(params.consistency, Consistency::One);
+        assert!(!params.explicit_consistency);
+
+        let mut params = QueryParamsBuilder::new()
+            .consistency(Consistency::Quorum)
+            .finalize();
+        assert_eq!(params.consistency, Consistency::Quorum);
+        assert!(params.explicit_consistency);
+
+        params.explicit_consistency = false;
+        params.set_consistency(Consistency::All);
+        assert!(params.explicit_consistency);
+    }
+
+    #[test]
+    fn sets_explicit_routing() {
+        let partitioner = Murmur3Partitioner;
+        let mut params = QueryParamsBuilder::new()
+            .routing_key("ks", vec![1, 2])
+            .finalize();
+        assert_eq!(
+            params.take_routing(&partitioner),
+            Some(("ks".to_string(), partitioner.hash(&[1, 2])))
+        );
+        assert!(params.take_routing(&partitioner).is_none());
+
+        let mut params = QueryParamsBuilder::new()
+            .routing_key("ks", vec![1, 2])
+            .token("other", Token::Murmur3(7))
+            .finalize();
+        assert_eq!(
+            params.take_routing(&partitioner),
+            Some(("other".to_string(), Token::Murmur3(7)))
+        );
+
+        assert!(QueryParamsBuilder::new()
+            .finalize()
+            .take_routing(&partitioner)
+            .is_none());
+    }
+
+    #[test]
+    fn pins_queries_to_nodes() {
+        let addr = "127.0.0.1:9042".parse().unwrap();
+        let params = QueryParamsBuilder::new()
+            .target_node(TargetNode::Addr(addr))
+            .finalize();
+        assert_eq!(params.target_node, Some(TargetNode::Addr(addr)));
+        assert_eq!(TargetNode::Addr(addr).to_string(), "127.0.0.1:9042");
+        assert!(QueryParamsBuilder::new().finalize().target_node.is_none());
+    }
+}
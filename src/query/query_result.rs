@@ -0,0 +1,96 @@
+use uuid::Uuid;
+
+use crate::error;
+use crate::frame::frame_result::RowsMetadata;
+use crate::frame::Frame;
+use crate::types::rows::Row;
+
+/// Result of a query, execution or batch, along with the information nodes attach to responses
+/// to requests sent with tracing or warnings, e.g. with `QueryExecutor::query_tw()`.
+#[derive(Clone, Debug)]
+pub struct QueryResult {
+    /// Rows of the result, if it's a result of a statement returning rows.
+    pub rows: Option<Vec<Row>>,
+    /// Metadata of the rows, i.e. names and types of their columns, which is known even if no
+    /// rows are returned.
+    pub metadata: Option<RowsMetadata>,
+    /// Id of the trace of the request, if it was traced. The trace is read with
+    /// `Session::get_query_trace()`.
+    pub tracing_id: Option<Uuid>,
+    /// Warnings nodes issued while processing the request.
+    pub warnings: Vec<String>,
+}
+
+impl QueryResult {
+    /// Reads the result of a request from its response.
+    pub fn from_frame(frame: Frame) -> error::Result<Self> {
+        let body = frame.get_body()?;
+        let metadata = body.as_rows_metadata();
+        let rows = body.into_rows();
+
+        Ok(QueryResult {
+            rows,
+            metadata,
+            tracing_id: frame.tracing_id,
+            warnings: frame.warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{Flag, Opcode, Version};
+
+    #[test]
+    fn reads_tracing_id_and_warnings() {
+        let tracing_id = Uuid::from_u128(7);
+        let frame = Frame::new(
+            Version::Response,
+            vec![Flag::Tracing, Flag::Warning],
+            Opcode::Result,
+            vec![0, 0, 0, 1],
+            Some(tracing_id),
+            vec!["Batch is too large".to_string()],
+        );
+
+        let result = QueryResult::from_frame(frame).unwrap();
+        assert!(result.rows.is_none());
+        assert!(result.metadata.is_none());
+        assert_eq!(result.tracing_id, Some(tracing_id));
+        assert_eq!(result.warnings, vec!["Batch is too large".to_string()]);
+    }
+
+    #[test]
+    fn reads_metadata_of_empty_results() {
+        let mut body = vec![0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 3];
+        body.extend_from_slice(&[0, 2, b'k', b's', 0, 5, b'u', b's', b'e', b'r', b's']);
+        body.extend_from_slice(&[0, 2, b'i', b'd', 0, 0x09]);
+        body.extend_from_slice(&[0, 4, b'n', b'a', b'm', b'e', 0, 0x0D]);
+        body.extend_from_slice(&[
+            0, 4, b't', b'a', b'g', b's', 0, 0x21, 0, 0x0D, 0, 0x20, 0, 0x09,
+        ]);
+        body.extend_from_slice(&[0, 0, 0, 0]);
+        let frame = Frame::new(
+            Version::Response,
+            vec![],
+            Opcode::Result,
+            body,
+            None,
+            vec![],
+        );
+
+        let result = QueryResult::from_frame(frame).unwrap();
+        assert!(result.rows.unwrap().is_empty());
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata.keyspace(), Some("ks"));
+        assert_eq!(metadata.table(), Some("users"));
+        assert_eq!(metadata.column_names(), vec!["id", "name", "tags"]);
+        assert_eq!(metadata.column_index("name"), Some(1));
+        assert_eq!(
+            metadata.column_type("tags").unwrap().to_string(),
+            "map<text, frozen<list<int>>>"
+        );
+        assert!(metadata.column_type("missing").is_none());
+    }
+}
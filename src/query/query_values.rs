@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use crate::error;
 use crate::frame::IntoBytes;
 use crate::types::value::Value;
 use crate::types::CString;
@@ -23,6 +24,26 @@ impl QueryValues {
         }
     }
 
+    /// Checks that values with names include values of all given names. Values without names
+    /// are not checked.
+    pub fn check_names<'a, I: IntoIterator<Item = &'a str>>(&self, names: I) -> error::Result<()> {
+        if let QueryValues::NamedValues(ref values) = *self {
+            let missing: Vec<&str> = names
+                .into_iter()
+                .filter(|name| !values.contains_key(*name))
+                .collect();
+
+            if !missing.is_empty() {
+                return Err(error::Error::General(format!(
+                    "Missing values of bind markers: {}",
+                    missing.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     /// It return number of values.
     pub fn len(&self) -> usize {
         match *self {
@@ -89,3 +110,36 @@ impl IntoBytes for QueryValues {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_query_values() {
+        let nickname: Option<&str> = None;
+        let values = query_values!(1i32, "name", Some(vec![1i64, 2]), nickname,);
+        assert_eq!(
+            values,
+            QueryValues::SimpleValues(vec![
+                Value::from(1i32),
+                Value::from("name"),
+                Value::from(vec![1i64, 2]),
+                Value::new_null(),
+            ])
+        );
+
+        let values = query_values!("id" => 1i32, "nickname" => nickname);
+        assert!(values.with_names());
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            values,
+            QueryValues::from(
+                vec![("id", Value::from(1i32)), ("nickname", Value::new_null())]
+                    .into_iter()
+                    .collect::<HashMap<_, _>>()
+            )
+        );
+        assert_eq!(query_values!(), QueryValues::SimpleValues(vec![]));
+    }
+}
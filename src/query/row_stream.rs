@@ -0,0 +1,258 @@
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::error;
+use crate::frame::frame_result::RowsMetadataFlag;
+use crate::frame::{Frame, TryFromRow};
+use crate::query::PagingState;
+use crate::types::rows::Row;
+use crate::types::CBytes;
+
+/// Page size of row streams which don't have one set explicitly.
+pub const DEFAULT_PAGE_SIZE: i32 = 5000;
+
+/// Single page of rows, along with the paging state of the next one, if there is one.
+#[derive(Debug)]
+pub struct Page {
+    pub rows: Vec<Row>,
+    pub paging_state: Option<CBytes>,
+}
+
+impl Page {
+    /// Reads a page from a response to a paged query or execution.
+    pub fn from_frame(frame: Frame) -> error::Result<Self> {
+        let body = frame.into_body()?;
+        let metadata = body
+            .as_rows_metadata()
+            .ok_or_else(|| error::Error::from("Paged query should yield a vector of rows"))?;
+
+        let paging_state = if RowsMetadataFlag::has_has_more_pages(metadata.flags) {
+            metadata.paging_state
+        } else {
+            None
+        };
+
+        let rows = body
+            .into_rows()
+            .ok_or_else(|| error::Error::from("Paged query should yield a vector of rows"))?;
+
+        Ok(Page { rows, paging_state })
+    }
+}
+
+type PageFuture<'a> = Pin<Box<dyn Future<Output = error::Result<Page>> + Send + 'a>>;
+type FetchPage<'a> = Box<dyn FnMut(Option<CBytes>) -> PageFuture<'a> + Send + 'a>;
+
+/// Stream of rows of a query or execution, which fetches further pages as it's consumed, as
+/// returned by `QueryExecutor::query_iter()` and `ExecExecutor::exec_iter()`. Fetching a page
+/// fails the stream with the error, after which it ends.
+///
+/// With prefetching enabled, the next page is requested as soon as the previous one arrives,
+/// so it's likely to be ready once buffered rows are consumed, at the cost of holding up to two
+/// pages in memory.
+pub struct RowStream<'a> {
+    fetch_page: FetchPage<'a>,
+    rows: VecDeque<Row>,
+    next_page: Option<PageFuture<'a>>,
+    paging_state: Option<CBytes>,
+    prefetch: bool,
+}
+
+impl<'a> RowStream<'a> {
+    /// Creates a stream fetching pages with given function, which is called with the paging
+    /// state of the page to fetch, `None` for the first one.
+    pub fn new<F>(mut fetch_page: F) -> Self
+    where
+        F: FnMut(Option<CBytes>) -> PageFuture<'a> + Send + 'a,
+    {
+        let next_page = Some(fetch_page(None));
+        RowStream {
+            fetch_page: Box::new(fetch_page),
+            rows: VecDeque::new(),
+            next_page,
+            paging_state: None,
+            prefetch: false,
+        }
+    }
+
+    /// Enables or disables prefetching of the next page. Disabled by default.
+    pub fn with_prefetch(mut self, prefetch: bool) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    /// Converts rows of the stream with `TryFromRow`.
+    pub fn into_typed<R: TryFromRow>(self) -> TypedRowStream<'a, R> {
+        TypedRowStream {
+            rows: self,
+            row_type: PhantomData,
+        }
+    }
+
+    /// Returns the paging state of the first page which wasn't requested yet, if there's one,
+    /// from which paging can be resumed later.
+    pub fn paging_state(&self) -> Option<PagingState> {
+        self.paging_state.clone().map(PagingState::from)
+    }
+}
+
+impl<'a> Stream for RowStream<'a> {
+    type Item = error::Result<Row>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(ref mut next_page) = this.next_page {
+                match next_page.as_mut().poll(cx) {
+                    Poll::Ready(Ok(page)) => {
+                        this.next_page = None;
+                        this.rows.extend(page.rows);
+                        this.paging_state = page.paging_state;
+                    }
+                    Poll::Ready(Err(error)) => {
+                        this.next_page = None;
+                        this.paging_state = None;
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                    // buffered rows can be consumed while the page is being fetched
+                    Poll::Pending if this.rows.is_empty() => return Poll::Pending,
+                    Poll::Pending => {}
+                }
+            }
+
+            if this.next_page.is_none() && (this.prefetch || this.rows.is_empty()) {
+                if let Some(paging_state) = this.paging_state.take() {
+                    let next_page = (this.fetch_page)(Some(paging_state));
+                    this.next_page = Some(next_page);
+                    continue;
+                }
+            }
+
+            return Poll::Ready(this.rows.pop_front().map(Ok));
+        }
+    }
+}
+
+/// Stream of rows converted with `TryFromRow`, as returned by `RowStream::into_typed()`.
+pub struct TypedRowStream<'a, R> {
+    rows: RowStream<'a>,
+    row_type: PhantomData<fn() -> R>,
+}
+
+impl<'a, R> TypedRowStream<'a, R> {
+    /// Returns the underlying stream of rows.
+    pub fn rows(&self) -> &RowStream<'a> {
+        &self.rows
+    }
+}
+
+impl<'a, R: TryFromRow> Stream for TypedRowStream<'a, R> {
+    type Item = error::Result<R>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.rows)
+            .poll_next(cx)
+            .map(|row| row.map(|row| row.and_then(R::try_from_row)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+    use std::future::poll_fn;
+    use std::sync::{Arc, Mutex};
+
+    fn rows_frame(count: usize, paging_state: Option<&[u8]>) -> Frame {
+        test_utils::rows_frame(&[], vec![vec![]; count], paging_state)
+    }
+
+    async fn collect(mut stream: RowStream<'_>) -> Vec<error::Result<Row>> {
+        let mut rows = vec![];
+        while let Some(row) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            rows.push(row);
+        }
+
+        rows
+    }
+
+    fn pages(requested: Arc<Mutex<Vec<Option<CBytes>>>>) -> RowStream<'static> {
+        RowStream::new(move |paging_state| {
+            requested.lock().unwrap().push(paging_state.clone());
+            let frame = match paging_state.and_then(|state| state.into_plain()) {
+                None => rows_frame(2, Some(b"a")),
+                Some(state) if state == b"a" => rows_frame(0, Some(b"b")),
+                Some(_) => rows_frame(1, None),
+            };
+
+            Box::pin(async move { Page::from_frame(frame) })
+        })
+    }
+
+    #[tokio::test]
+    async fn follows_paging_state() {
+        let requested = Arc::new(Mutex::new(vec![]));
+        let rows = collect(pages(requested.clone())).await;
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row.is_ok()));
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec![
+                None,
+                Some(CBytes::new(b"a".to_vec())),
+                Some(CBytes::new(b"b".to_vec()))
+            ]
+        );
+
+        let requested = Arc::new(Mutex::new(vec![]));
+        let rows = collect(pages(requested.clone()).with_prefetch(true)).await;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(requested.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn ends_after_errors() {
+        let stream = RowStream::new(|paging_state| {
+            let frame = rows_frame(1, Some(b"a"));
+            Box::pin(async move {
+                match paging_state {
+                    None => Page::from_frame(frame),
+                    Some(_) => Err(error::Error::from("page failed")),
+                }
+            })
+        });
+
+        let rows = collect(stream).await;
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].is_ok());
+        assert!(rows[1].is_err());
+    }
+
+    #[derive(Debug)]
+    struct Counted;
+
+    impl TryFromRow for Counted {
+        fn try_from_row(_row: Row) -> error::Result<Self> {
+            Ok(Counted)
+        }
+    }
+
+    #[tokio::test]
+    async fn converts_rows() {
+        let requested = Arc::new(Mutex::new(vec![]));
+        let mut stream = pages(requested).into_typed::<Counted>();
+
+        let mut count = 0;
+        while let Some(row) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            assert!(row.is_ok());
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        assert!(stream.rows().paging_state().is_none());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Generates default timestamps of queries, executions and batches which don't have one set
+/// explicitly. Timestamps generated by the client keep the order of writes issued by it, even
+/// if they reach coordinators with skewed clocks.
+pub trait TimestampGenerator: fmt::Debug {
+    /// Returns the next timestamp, in microseconds since the Unix epoch, or `None` to let the
+    /// coordinator assign it.
+    fn next_timestamp(&self) -> Option<i64>;
+}
+
+/// Generator leaving timestamps to coordinators.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ServerSideTimestampGenerator;
+
+impl TimestampGenerator for ServerSideTimestampGenerator {
+    fn next_timestamp(&self) -> Option<i64> {
+        None
+    }
+}
+
+/// Generator returning the current time in microseconds, which is guaranteed to be strictly
+/// increasing - if the clock doesn't move forward between calls or goes back, e.g. after being
+/// adjusted, the previous timestamp incremented by one is returned instead, so later writes
+/// are never shadowed by earlier ones. This is the default generator of sessions.
+#[derive(Debug, Default)]
+pub struct MonotonicTimestampGenerator {
+    last: AtomicI64,
+}
+
+impl MonotonicTimestampGenerator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl TimestampGenerator for MonotonicTimestampGenerator {
+    fn next_timestamp(&self) -> Option<i64> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as i64)
+            .unwrap_or_default();
+
+        // the closure always returns a value, so the update can't fail
+        let last = self
+            .last
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_else(|last| last);
+
+        Some(now.max(last + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_increasing_timestamps() {
+        let generator = MonotonicTimestampGenerator::new();
+        let first = generator.next_timestamp().unwrap();
+        let second = generator.next_timestamp().unwrap();
+        assert!(first > 0);
+        assert!(second > first);
+
+        // a clock which went back doesn't make timestamps go back
+        let future = second + 60_000_000;
+        generator.last.store(future, Ordering::SeqCst);
+        assert_eq!(generator.next_timestamp(), Some(future + 1));
+        assert_eq!(generator.next_timestamp(), Some(future + 2));
+
+        assert_eq!(ServerSideTimestampGenerator.next_timestamp(), None);
+    }
+}
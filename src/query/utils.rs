@@ -1,10 +1,22 @@
-use tokio::sync::Mutex;
+use std::future::{poll_fn, Future};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+use tokio::time::{sleep, timeout};
 
-use crate::cluster::{GetCompressor, GetConnection, ResponseCache};
+use crate::cluster::{ConnectionPool, GetConnection, Tablet, Token, TABLETS_ROUTING_PAYLOAD};
+use crate::consistency::Consistency;
 use crate::error;
-use crate::frame::parser::from_connection;
-use crate::frame::{Flag, Frame, StreamId};
+use crate::frame::frame_error::AdditionalErrorInfo;
+use crate::frame::frame_result::{BodyResResultPrepared, ResultKind, RowsMetadata};
+use crate::frame::traits::{FromBytes, IntoBytes};
+use crate::frame::{Flag, Frame, Opcode, Version};
+use crate::query::{PreparedQuery, RequestPriority, TargetNode};
+use crate::retry::{is_ambiguous, RetryDecision, RetryPolicy, RetryRequest};
 use crate::transport::CDRSTransport;
+use crate::types::rows::Row;
 
 pub fn prepare_flags(with_tracing: bool, with_warnings: bool) -> Vec<Flag> {
     let mut flags = vec![];
@@ -20,47 +32,690 @@ pub fn prepare_flags(with_tracing: bool, with_warnings: bool) -> Vec<Flag> {
     flags
 }
 
-pub async fn send_frame<S, T, M>(sender: &S, frame_bytes: Vec<u8>, stream_id: StreamId) -> error::Result<Frame>
+pub async fn send_frame<S, T, M>(sender: &S, frame: Frame) -> error::Result<Frame>
 where
-    S: GetConnection<T, M> + GetCompressor<'static> + ResponseCache + Sized,
+    S: GetConnection<T, M> + Sized + Sync,
     T: CDRSTransport + Unpin + 'static,
-    M: bb8::ManageConnection<Connection = Mutex<T>, Error = error::Error> + Sized,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
 {
-    let ref compression = sender.get_compressor();
-
-    let transport = sender
-        .get_connection()
-        .await
-        .ok_or(error::Error::from("Unable to get transport"))?
-        .get_pool();
-
-    let pool = transport
-        .get()
-        .await
-        .map_err(|error| error::Error::from(error.to_string()))?;
-
-    let write_res = pool
-        .lock()
-        .await
-        .write(frame_bytes.as_slice())
-        .await
-        .map_err(error::Error::from);
-
-    let result = write_res.map(|_| pool);
-    match result {
-        Ok(ref pool) => loop {
-            let frame = from_connection(pool, compression).await?;
-            if let Some(frame) = sender.match_or_cache_response(stream_id, frame).await {
+    send_request(sender, Request::new(frame)).await
+}
+
+/// Prepares a query on the first node of the query plan, and then on all other nodes of it, so
+/// executions routed to any of them find it prepared. Each node prepares the query once.
+/// Failures of other nodes are only logged, as such nodes prepare the query again on demand.
+pub async fn prepare_query<S, T, M>(
+    sender: &S,
+    query: String,
+    flags: Vec<Flag>,
+) -> error::Result<BodyResResultPrepared>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    let prepare_frame = Frame::new_req_prepare(query.clone(), flags);
+    let mut nodes = sender.get_all_connections().await;
+    let response = if nodes.is_empty() {
+        send_frame(sender, prepare_frame).await?
+    } else {
+        let node = nodes.remove(0);
+        send_request_to(sender, Request::new(prepare_frame), node).await?
+    };
+
+    let prepared = response
+        .get_body()?
+        .into_prepared()
+        .ok_or_else(|| error::Error::from("Prepare should yield a prepared query"))?;
+
+    join_all(
+        nodes
+            .iter()
+            .map(|node| prepare_on_node(node, &query))
+            .collect(),
+    )
+    .await;
+
+    Ok(prepared)
+}
+
+/// Prepares a query on given node, logging failures.
+pub async fn prepare_on_node<T, M>(node: &ConnectionPool<M>, query: &str)
+where
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    if let Err(error) = prepare_node_query(node, query).await {
+        warn!("Unable to prepare query on {}: {}", node.get_addr(), error);
+    }
+}
+
+/// Prepares a query on given node.
+pub async fn prepare_node_query<T, M>(
+    node: &ConnectionPool<M>,
+    query: &str,
+) -> error::Result<BodyResResultPrepared>
+where
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    let prepare_frame = Frame::new_req_prepare(query.to_string(), vec![]);
+    send_to_node(node, &prepare_frame, None, None, RequestPriority::Normal)
+        .await?
+        .get_body()?
+        .into_prepared()
+        .ok_or_else(|| error::Error::from("Prepare should yield a prepared query"))
+}
+
+/// Runs given futures concurrently until all of them complete.
+async fn join_all<F: Future<Output = ()>>(futures: Vec<F>) {
+    let mut futures: Vec<Option<Pin<Box<F>>>> =
+        futures.into_iter().map(|future| Some(Box::pin(future))).collect();
+
+    poll_fn(|cx| {
+        let mut pending = false;
+        for slot in futures.iter_mut() {
+            if let Some(ref mut future) = *slot {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(()) => *slot = None,
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await
+}
+
+/// Destination of a request addressing a single partition.
+#[derive(Clone)]
+pub struct Routing<'a> {
+    pub keyspace: &'a str,
+    /// Table of the partition, used for finding tablets.
+    pub table: Option<&'a str>,
+    pub token: Token,
+}
+
+/// Request frame along with information used for routing and retrying it.
+#[derive(Clone)]
+pub struct Request<'a> {
+    pub frame: Frame,
+    pub routing: Option<Routing<'a>>,
+    /// Node the request is pinned to, overriding its routing.
+    pub target_node: Option<TargetNode>,
+    /// Position of the consistency in the frame body, for requests which have one. Requests
+    /// without consistency are not retried.
+    pub consistency_offset: Option<usize>,
+    /// Whether the request can be safely executed more than once, e.g. speculatively.
+    pub is_idempotent: bool,
+    /// Time limit of the request, including retries and speculative executions. Overrides the
+    /// deadline of the sender.
+    pub deadline: Option<Duration>,
+    /// Priority of the request on saturated connections.
+    pub priority: RequestPriority,
+    /// Text of the prepared query executed by the request, if known. Nodes which don't know
+    /// the id of the query get it prepared again, after which the request is retried once.
+    pub prepared_query: Option<&'a str>,
+    /// Metadata of rows left out of responses to the request, replaced when the query is
+    /// prepared again.
+    pub result_metadata: Option<Arc<RowsMetadata>>,
+}
+
+impl<'a> Request<'a> {
+    pub fn new(frame: Frame) -> Self {
+        Request {
+            frame,
+            routing: None,
+            target_node: None,
+            consistency_offset: None,
+            is_idempotent: false,
+            deadline: None,
+            priority: Default::default(),
+            prepared_query: None,
+            result_metadata: None,
+        }
+    }
+
+    pub fn with_routing(mut self, routing: Option<Routing<'a>>) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    pub fn with_target_node(mut self, target_node: Option<TargetNode>) -> Self {
+        self.target_node = target_node;
+        self
+    }
+
+    pub fn with_consistency_offset(mut self, consistency_offset: usize) -> Self {
+        self.consistency_offset = Some(consistency_offset);
+        self
+    }
+
+    pub fn with_idempotent(mut self, is_idempotent: bool) -> Self {
+        self.is_idempotent = is_idempotent;
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_prepared_query(mut self, prepared_query: Option<&'a str>) -> Self {
+        self.prepared_query = prepared_query;
+        self
+    }
+
+    pub fn with_result_metadata(mut self, result_metadata: Option<Arc<RowsMetadata>>) -> Self {
+        self.result_metadata = result_metadata;
+        self
+    }
+
+    fn consistency(&self) -> Option<Consistency> {
+        let offset = self.consistency_offset?;
+        let bytes = self.frame.body.get(offset..offset + 2)?;
+        Consistency::from_bytes(bytes).ok()
+    }
+
+    fn set_consistency(&mut self, consistency: Consistency) {
+        if let Some(offset) = self.consistency_offset {
+            self.frame.body[offset..offset + 2].copy_from_slice(&consistency.into_cbytes());
+        }
+    }
+}
+
+/// Sends a request to a node storing the partition given by its routing, if present. Failed
+/// requests are retried as decided by the retry policy of the sender. Idempotent requests
+/// are additionally sent to further nodes as decided by the speculative execution policy of
+/// the sender, and the first response is used. All of that has to fit within the deadline of
+/// the request or the sender, if any.
+pub async fn send_request<S, T, M>(sender: &S, request: Request<'_>) -> error::Result<Frame>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    let deadline = request.deadline;
+    send_within_deadline(sender, deadline, send_routed(sender, request)).await
+}
+
+/// Sends a request to given node instead of one picked by its routing, retrying it there as
+/// decided by the retry policy of the sender.
+async fn send_request_to<S, T, M>(
+    sender: &S,
+    request: Request<'_>,
+    node: Arc<ConnectionPool<M>>,
+) -> error::Result<Frame>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    let deadline = request.deadline;
+    let response = execute(sender, request, node, None, None, vec![]);
+    send_within_deadline(sender, deadline, response).await
+}
+
+/// Awaits a response while the session is running, failing once given deadline, or the one of
+/// the sender, passes.
+async fn send_within_deadline<S, T, M, F>(
+    sender: &S,
+    deadline: Option<Duration>,
+    response: F,
+) -> error::Result<Frame>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+    F: Future<Output = error::Result<Frame>>,
+{
+    let _request = sender
+        .start_request()
+        .ok_or_else(|| error::Error::from("Session is shut down"))?;
+
+    match deadline.or_else(|| sender.request_deadline()) {
+        // executions in flight are cancelled when the deadline passes
+        Some(deadline) => timeout(deadline, response).await.unwrap_or_else(|_| {
+            Err(error::Error::Timeout(format!(
+                "Request deadline of {:?} exceeded",
+                deadline
+            )))
+        }),
+        None => response.await,
+    }
+}
+
+/// Sends a request to the node given by its routing, retrying and executing it speculatively
+/// as needed.
+async fn send_routed<S, T, M>(sender: &S, request: Request<'_>) -> error::Result<Frame>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    // pinned requests are neither routed elsewhere nor executed speculatively
+    if let Some(target_node) = request.target_node {
+        let node = sender
+            .get_connection_to(&target_node)
+            .await
+            .ok_or_else(|| {
+                error::Error::General(format!("Node {} is not available", target_node))
+            })?;
+        return execute(sender, request, node, None, None, vec![]).await;
+    }
+
+    // shards and tablets are only known for Murmur3 tokens, used by Scylla
+    let token = match request.routing {
+        Some(Routing {
+            token: Token::Murmur3(token),
+            ..
+        }) => Some(token),
+        _ => None,
+    };
+
+    let tablet_node = match (&request.routing, token) {
+        (
+            Some(Routing {
+                keyspace,
+                table: Some(table),
+                ..
+            }),
+            Some(token),
+        ) => {
+            sender
+                .get_connection_for_tablet(keyspace, table, token)
+                .await
+        }
+        _ => None,
+    };
+
+    let (node, shard) = match tablet_node {
+        Some((node, shard)) => (node, Some(shard)),
+        None => {
+            let node = match request.routing {
+                Some(ref routing) => {
+                    sender
+                        .get_connection_for_token(routing.keyspace, &routing.token)
+                        .await
+                }
+                None => sender.get_connection().await,
+            }
+            .ok_or(error::Error::from("Unable to get transport"))?;
+            (node, None)
+        }
+    };
+
+    let speculative_execution_policy = sender.speculative_execution_policy();
+    let start = Instant::now();
+
+    let delay = if request.is_idempotent {
+        speculative_execution_policy.next_execution_delay(1)
+    } else {
+        None
+    };
+    let response = match delay {
+        Some(delay) => send_speculatively(sender, request, node, shard, token, delay).await,
+        None => execute(sender, request, node, shard, token, vec![]).await,
+    };
+
+    if response.is_ok() {
+        speculative_execution_policy.record_latency(start.elapsed());
+    }
+
+    response
+}
+
+/// Executes a request starting with given node, and starts further executions on next nodes
+/// of the query plan whenever the speculative execution policy delay passes without a
+/// response. Returns the first successful response, cancelling other executions, or the last
+/// error if all executions failed.
+async fn send_speculatively<S, T, M>(
+    sender: &S,
+    request: Request<'_>,
+    node: Arc<ConnectionPool<M>>,
+    shard: Option<u32>,
+    token: Option<i64>,
+    delay: Duration,
+) -> error::Result<Frame>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    let speculative_execution_policy = sender.speculative_execution_policy();
+    let mut used = vec![node.get_addr()];
+    let mut executions = vec![Box::pin(execute(
+        sender,
+        request.clone(),
+        node,
+        shard,
+        token,
+        vec![],
+    ))];
+    let mut started = 1;
+    let mut timer = Some(Box::pin(sleep(delay)));
+
+    loop {
+        // either an execution finished or it's time for the next one
+        let finished = poll_fn(|cx| {
+            for (index, execution) in executions.iter_mut().enumerate() {
+                if let Poll::Ready(response) = execution.as_mut().poll(cx) {
+                    return Poll::Ready(Some((index, response)));
+                }
+            }
+
+            match timer {
+                Some(ref mut timer) => timer.as_mut().poll(cx).map(|_| None),
+                None => Poll::Pending,
+            }
+        })
+        .await;
+
+        match finished {
+            Some((index, response)) => {
+                // dropping the remaining executions cancels them
+                if response.is_ok() || executions.len() == 1 {
+                    return response;
+                }
+
+                drop(executions.swap_remove(index));
+            }
+            None => {
+                timer = None;
+
+                // shards of tablets are known only for their replicas
+                if let Some(node) = sender.get_next_connection(&used).await {
+                    debug!("Starting speculative execution on {}", node.get_addr());
+
+                    used.push(node.get_addr());
+                    executions.push(Box::pin(execute(
+                        sender,
+                        request.clone(),
+                        node,
+                        None,
+                        token,
+                        used.clone(),
+                    )));
+                    started += 1;
+
+                    timer = speculative_execution_policy
+                        .next_execution_delay(started)
+                        .map(|delay| Box::pin(sleep(delay)));
+                }
+            }
+        }
+    }
+}
+
+/// Sends a request to given node, retrying it as decided by the retry policy of the sender.
+/// Nodes which were already tried by other executions are skipped when retrying on the next
+/// node.
+async fn execute<S, T, M>(
+    sender: &S,
+    mut request: Request<'_>,
+    mut node: Arc<ConnectionPool<M>>,
+    mut shard: Option<u32>,
+    token: Option<i64>,
+    mut tried: Vec<SocketAddr>,
+) -> error::Result<Frame>
+where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    let retry_policy = sender.retry_policy();
+    let mut retry_count = 0;
+    let mut reprepared = false;
+
+    loop {
+        let response = send_to_node(&node, &request.frame, shard, token, request.priority).await;
+        let error = match response {
+            Ok(mut frame) => {
+                after_response(sender, &request, node.get_addr(), &frame).await;
+                frame.result_metadata = request.result_metadata;
                 return Ok(frame);
             }
+            Err(error) => error,
+        };
+
+        // nodes forget prepared queries e.g. when restarted
+        if !reprepared && is_unprepared(&error) {
+            if let Some(query) = request.prepared_query {
+                debug!("Preparing query again on {}", node.get_addr());
+
+                let prepare_frame = Frame::new_req_prepare(query.to_string(), vec![]);
+                let response =
+                    send_to_node(&node, &prepare_frame, shard, token, request.priority).await?;
+                reprepared = true;
+
+                // columns of rows may have changed along with the schema
+                if request.result_metadata.is_some() {
+                    let prepared = response
+                        .get_body()?
+                        .into_prepared()
+                        .map(PreparedQuery::from);
+                    request.result_metadata =
+                        prepared.and_then(|prepared| prepared.result_metadata);
+                    if let Some(cache) = sender.prepared_cache() {
+                        let keyspace = sender.keyspace();
+                        cache.set_result_metadata(
+                            keyspace.as_deref(),
+                            query,
+                            request.result_metadata.clone(),
+                        );
+                    }
+                }
+                continue;
+            }
+        }
+
+        let consistency = match request.consistency() {
+            Some(consistency) => consistency,
+            None => return Err(error),
+        };
+        let retry_request = RetryRequest {
+            consistency,
+            retry_count,
+            is_idempotent: request.is_idempotent,
+        };
+
+        let decision = retry_decision(&*retry_policy, &retry_request, &error);
+
+        match decision {
+            RetryDecision::RetrySameNode(consistency) => request.set_consistency(consistency),
+            RetryDecision::RetryNextNode(_) if request.target_node.is_some() => return Err(error),
+            RetryDecision::RetryNextNode(consistency) => {
+                request.set_consistency(consistency);
+                tried.push(node.get_addr());
+                node = match sender.get_next_connection(&tried).await {
+                    Some(node) => node,
+                    None => return Err(error),
+                };
+                // shards of tablets are known only for their replicas
+                shard = None;
+                reprepared = false;
+            }
+            RetryDecision::Rethrow => return Err(error),
+            RetryDecision::Ignore => return Ok(void_result()),
+        }
+
+        debug!("Retrying request ({:?}) after error: {}", decision, error);
+        retry_count += 1;
+    }
+}
+
+/// Decides what to do with a failed request. Requests which might have been applied are
+/// returned to the caller unless they're idempotent.
+fn retry_decision(
+    policy: &dyn RetryPolicy,
+    request: &RetryRequest,
+    error: &error::Error,
+) -> RetryDecision {
+    if !request.is_idempotent && is_ambiguous(error) {
+        return RetryDecision::Rethrow;
+    }
+
+    match *error {
+        error::Error::Server(ref server_error) => match server_error.additional_info {
+            AdditionalErrorInfo::ReadTimeout(ref timeout) => {
+                policy.on_read_timeout(request, timeout)
+            }
+            AdditionalErrorInfo::WriteTimeout(ref timeout) => {
+                policy.on_write_timeout(request, timeout)
+            }
+            AdditionalErrorInfo::Unavailable(ref unavailable) => {
+                policy.on_unavailable(request, unavailable)
+            }
+            AdditionalErrorInfo::Server(_)
+            | AdditionalErrorInfo::Overloaded(_)
+            | AdditionalErrorInfo::IsBootstrapping(_)
+            | AdditionalErrorInfo::Truncate(_)
+            | AdditionalErrorInfo::ReadFailure(_)
+            | AdditionalErrorInfo::WriteFailure(_) => policy.on_request_error(request, error),
+            // errors of the request itself, e.g. syntax errors
+            _ => RetryDecision::Rethrow,
         },
-        Err(error) => return Err(error)
+        error::Error::Io(_) | error::Error::Timeout(_) | error::Error::ConnectionBroken(_) => {
+            policy.on_request_error(request, error)
+        }
+        _ => RetryDecision::Rethrow,
+    }
+}
+
+/// Checks if a request failed because the node doesn't know the id of the executed prepared
+/// query.
+fn is_unprepared(error: &error::Error) -> bool {
+    match *error {
+        error::Error::Server(ref server_error) => matches!(
+            server_error.additional_info,
+            AdditionalErrorInfo::Unprepared(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Sends a frame with given priority to given node, over a connection to given shard or the
+/// shard owning given token, if known.
+async fn send_to_node<T, M>(
+    node: &ConnectionPool<M>,
+    frame: &Frame,
+    shard: Option<u32>,
+    token: Option<i64>,
+    priority: RequestPriority,
+) -> error::Result<Frame>
+where
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    // the connection is multiplexed, so it can be returned to the pool right away and shared by
+    // other requests while this one is waiting for the response
+    let transport = node.connection_to(shard, token, priority).await?;
+
+    let start = Instant::now();
+    let response = transport.write_frame_with_priority(frame, priority).await;
+
+    // timed out requests count too, as they're the slowest ones
+    if let Ok(_) | Err(error::Error::Timeout(_)) = response {
+        node.latency().record(start.elapsed());
+    }
+
+    response
+}
+
+/// Handles side effects of a successful response.
+async fn after_response<S, T, M>(
+    sender: &S,
+    request: &Request<'_>,
+    addr: SocketAddr,
+    frame: &Frame,
+) where
+    S: GetConnection<T, M> + Sized + Sync,
+    T: CDRSTransport + Unpin + 'static,
+    M: bb8::ManageConnection<Connection = T, Error = error::Error> + Sized,
+{
+    if !frame.warnings.is_empty() {
+        sender.warning_handler().handle_warnings(addr, &frame.warnings);
+    }
+
+    if is_schema_change(frame) {
+        sender.after_schema_change().await;
+    }
+
+    // nodes not storing a tablet of the partition send its replicas
+    if let (
+        Some(Routing {
+            keyspace,
+            table: Some(table),
+            ..
+        }),
+        Some(payload),
+    ) = (
+        &request.routing,
+        frame.custom_payload.get(TABLETS_ROUTING_PAYLOAD),
+    ) {
+        match Tablet::from_payload(payload) {
+            Ok(tablet) => sender.add_tablet(keyspace, table, tablet),
+            Err(error) => warn!("Invalid tablet routing information: {}", error),
+        }
+    }
+}
+
+/// Returns an empty result, for requests whose failure is ignored by the retry policy.
+fn void_result() -> Frame {
+    Frame::new(
+        Version::Response,
+        vec![],
+        Opcode::Result,
+        ResultKind::Void.into_cbytes(),
+        None,
+        vec![],
+    )
+}
+
+/// Returns rows of a response to a query or execution.
+pub fn into_rows(frame: Frame) -> error::Result<Vec<Row>> {
+    frame
+        .get_body()?
+        .into_rows()
+        .ok_or_else(|| error::Error::from("Query should yield a vector of rows"))
+}
+
+/// Returns the only row of a response, failing if there are none or more.
+pub fn into_one_row(frame: Frame) -> error::Result<Row> {
+    let mut rows = into_rows(frame)?.into_iter();
+    match (rows.next(), rows.next()) {
+        (Some(row), None) => Ok(row),
+        (None, _) => Err("Query returned no rows".into()),
+        (Some(_), Some(_)) => Err("Query returned more than one row".into()),
     }
 }
 
+/// Checks if the frame is a response to a statement which altered the schema.
+fn is_schema_change(frame: &Frame) -> bool {
+    frame.opcode == Opcode::Result
+        && frame.body.get(..4) == Some(&ResultKind::SchemaChange.into_cbytes()[..])
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::authenticators::NoneAuthenticator;
+    use crate::cluster::{TcpConnectionPool, TcpConnectionsManager};
+    use crate::frame::frame_error::{CDRSError, UnpreparedError, WriteTimeoutError, WriteType};
+    use crate::retry::DefaultRetryPolicy;
+    use crate::transport::TransportTcp;
+    use crate::types::{CBytesShort, CString};
+    use async_trait::async_trait;
 
     #[test]
     fn prepare_flags_test() {
@@ -71,4 +726,167 @@ mod test {
             vec![Flag::Tracing, Flag::Warning]
         );
     }
+
+    #[test]
+    fn detects_schema_changes() {
+        let frame =
+            |opcode, body| Frame::new(Version::Response, vec![], opcode, body, None, vec![]);
+
+        assert!(is_schema_change(&frame(
+            Opcode::Result,
+            vec![0, 0, 0, 5, 0, 7]
+        )));
+        assert!(!is_schema_change(&frame(Opcode::Result, vec![0, 0, 0, 2])));
+        assert!(!is_schema_change(&frame(Opcode::Result, vec![0, 0])));
+        assert!(!is_schema_change(&frame(Opcode::Event, vec![0, 0, 0, 5])));
+    }
+
+    #[test]
+    fn replaces_consistency_of_requests() {
+        let frame = Frame::new_req_query(
+            "SELECT * FROM t".into(),
+            Consistency::Quorum,
+            None,
+            None,
+            None,
+            None,
+            Some(Consistency::Serial),
+            None,
+            vec![],
+        );
+        let mut request = Request::new(frame).with_consistency_offset(4 + 15);
+        assert_eq!(request.consistency(), Some(Consistency::Quorum));
+
+        request.set_consistency(Consistency::One);
+        assert_eq!(request.consistency(), Some(Consistency::One));
+        assert_eq!(request.frame.body[19..21], [0, 1]);
+
+        assert_eq!(Request::new(void_result()).consistency(), None);
+    }
+
+    #[test]
+    fn refuses_to_replay_ambiguous_failures_of_non_idempotent_requests() {
+        let write_timeout = error::Error::Server(CDRSError {
+            error_code: 0x1100,
+            message: CString::new("timeout".into()),
+            additional_info: AdditionalErrorInfo::WriteTimeout(WriteTimeoutError {
+                cl: Consistency::Quorum,
+                received: 0,
+                blockfor: 2,
+                write_type: WriteType::BatchLog,
+            }),
+        });
+        let request = |is_idempotent| RetryRequest {
+            consistency: Consistency::Quorum,
+            retry_count: 0,
+            is_idempotent,
+        };
+
+        assert_eq!(
+            retry_decision(&DefaultRetryPolicy, &request(true), &write_timeout),
+            RetryDecision::RetrySameNode(Consistency::Quorum)
+        );
+        assert_eq!(
+            retry_decision(&DefaultRetryPolicy, &request(false), &write_timeout),
+            RetryDecision::Rethrow
+        );
+        assert_eq!(
+            retry_decision(
+                &DefaultRetryPolicy,
+                &request(false),
+                &error::Error::Io(std::io::ErrorKind::ConnectionRefused.into())
+            ),
+            RetryDecision::RetryNextNode(Consistency::Quorum)
+        );
+    }
+
+    #[test]
+    fn detects_unprepared_queries() {
+        let unprepared = error::Error::Server(CDRSError {
+            error_code: 0x2500,
+            message: CString::new("unprepared".into()),
+            additional_info: AdditionalErrorInfo::Unprepared(UnpreparedError {
+                id: CBytesShort::new(vec![1]),
+            }),
+        });
+
+        assert!(is_unprepared(&unprepared));
+        assert!(!is_unprepared(&error::Error::from("other")));
+    }
+
+    #[tokio::test]
+    async fn joins_all_futures() {
+        // the first future completes only if the second one runs concurrently
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let mut receiver = Some(receiver);
+        let mut sender = Some(sender);
+        let finished = std::sync::Mutex::new(vec![]);
+
+        let futures = (0..2)
+            .map(|index| {
+                let receiver = receiver.take();
+                let sender = if index == 1 { sender.take() } else { None };
+                let finished = &finished;
+                async move {
+                    if let Some(receiver) = receiver {
+                        receiver.await.unwrap();
+                    }
+                    if let Some(sender) = sender {
+                        sender.send(()).unwrap();
+                    }
+                    finished.lock().unwrap().push(index);
+                }
+            })
+            .collect();
+
+        join_all(futures).await;
+        assert_eq!(*finished.lock().unwrap(), vec![1, 0]);
+    }
+
+    struct UnreachableSender;
+
+    #[async_trait]
+    impl GetConnection<TransportTcp, TcpConnectionsManager<NoneAuthenticator>> for UnreachableSender {
+        async fn get_connection(&self) -> Option<Arc<TcpConnectionPool<NoneAuthenticator>>> {
+            sleep(Duration::from_secs(60)).await;
+            None
+        }
+
+        fn request_deadline(&self) -> Option<Duration> {
+            Some(Duration::from_millis(10))
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_requests_exceeding_deadline() {
+        let result = send_frame(&UnreachableSender, void_result()).await;
+        assert!(matches!(result, Err(error::Error::Timeout(_))));
+
+        let request = Request::new(void_result()).with_deadline(Some(Duration::from_millis(5)));
+        let result = send_request(&UnreachableSender, request).await;
+        assert!(matches!(result, Err(error::Error::Timeout(_))));
+    }
+
+    #[test]
+    fn reads_single_rows() {
+        let rows = |count: i32| {
+            let mut body = vec![0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0];
+            body.extend_from_slice(&count.to_be_bytes());
+            Frame::new(
+                Version::Response,
+                vec![],
+                Opcode::Result,
+                body,
+                None,
+                vec![],
+            )
+        };
+
+        assert_eq!(into_rows(rows(2)).unwrap().len(), 2);
+        assert!(into_rows(void_result()).is_err());
+
+        assert!(into_one_row(rows(1)).is_ok());
+        assert!(into_one_row(rows(0)).is_err());
+        assert!(into_one_row(rows(2)).is_err());
+    }
 }
@@ -0,0 +1,160 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::panic::Location;
+
+/// Handles warnings nodes attach to responses, e.g. about reading many tombstones or about
+/// large batches, which would otherwise go unnoticed unless read from each response.
+pub trait WarningHandler: fmt::Debug {
+    /// Called with warnings of a successful response from the node with given address.
+    fn handle_warnings(&self, addr: SocketAddr, warnings: &[String]);
+
+    /// Called before sending a batch which exceeds the batch warning thresholds of the
+    /// session. Logs the warning with the `log` crate by default.
+    fn handle_batch_warning(&self, warning: &BatchWarning) {
+        warn!("{}", warning);
+    }
+}
+
+/// Thresholds of batches, which are checked before they're sent. They mirror batch warnings of
+/// nodes, e.g. `batch_size_warn_threshold` of Cassandra, but catch large batches earlier, along
+/// with the code building them. Both checks are disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchWarningThresholds {
+    /// Serialized size of statements of a batch, including their values, in bytes.
+    pub size: Option<usize>,
+    /// Number of statements of a batch.
+    pub statements: Option<usize>,
+}
+
+impl BatchWarningThresholds {
+    /// Checks if a batch of given serialized size and number of statements exceeds any of the
+    /// thresholds.
+    pub fn are_exceeded(&self, size: usize, statements: usize) -> bool {
+        self.size.is_some_and(|threshold| size > threshold)
+            || self
+                .statements
+                .is_some_and(|threshold| statements > threshold)
+    }
+}
+
+/// Batch exceeding the batch warning thresholds of the session, which is about to be sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchWarning {
+    /// Serialized size of statements of the batch, including their values, in bytes.
+    pub size: usize,
+    /// Number of statements of the batch.
+    pub statements: usize,
+    /// Keyspace of the partition the batch is routed to, if known.
+    pub keyspace: Option<String>,
+    /// Table of the partition the batch is routed to, if known.
+    pub table: Option<String>,
+    /// Location of the `BatchBuilder::finalize()` call which built the batch, if known.
+    pub location: Option<&'static Location<'static>>,
+}
+
+impl fmt::Display for BatchWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Batch of {} statements and {} bytes",
+            self.statements, self.size
+        )?;
+
+        match (&self.keyspace, &self.table) {
+            (Some(keyspace), Some(table)) => write!(f, " to {}.{}", keyspace, table)?,
+            (Some(keyspace), None) => write!(f, " to {}", keyspace)?,
+            _ => {}
+        }
+
+        if let Some(location) = self.location {
+            write!(f, " built at {}", location)?;
+        }
+
+        write!(f, " exceeds batch warning thresholds")
+    }
+}
+
+/// Handler logging warnings with the `log` crate. This is the default handler of sessions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogWarningHandler;
+
+impl WarningHandler for LogWarningHandler {
+    fn handle_warnings(&self, addr: SocketAddr, warnings: &[String]) {
+        for warning in warnings {
+            warn!("Warning from {}: {}", addr, warning);
+        }
+    }
+}
+
+/// Handler passing warnings to a callback, e.g. for collecting them in metrics.
+pub struct CallbackWarningHandler<F> {
+    callback: F,
+}
+
+impl<F: Fn(SocketAddr, &[String])> CallbackWarningHandler<F> {
+    pub fn new(callback: F) -> Self {
+        CallbackWarningHandler { callback }
+    }
+}
+
+impl<F> fmt::Debug for CallbackWarningHandler<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackWarningHandler").finish()
+    }
+}
+
+impl<F: Fn(SocketAddr, &[String])> WarningHandler for CallbackWarningHandler<F> {
+    fn handle_warnings(&self, addr: SocketAddr, warnings: &[String]) {
+        (self.callback)(addr, warnings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn passes_warnings_to_callback() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let handler = {
+            let received = received.clone();
+            CallbackWarningHandler::new(move |addr: SocketAddr, warnings: &[String]| {
+                received.lock().unwrap().push((addr, warnings.to_vec()));
+            })
+        };
+
+        let addr: SocketAddr = "127.0.0.1:9042".parse().unwrap();
+        handler.handle_warnings(addr, &["Read 1000 tombstones".to_string()]);
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![(addr, vec!["Read 1000 tombstones".to_string()])]
+        );
+    }
+
+    #[test]
+    fn checks_batch_warning_thresholds() {
+        assert!(!BatchWarningThresholds::default().are_exceeded(usize::MAX, usize::MAX));
+
+        let thresholds = BatchWarningThresholds {
+            size: Some(5120),
+            statements: Some(10),
+        };
+        assert!(!thresholds.are_exceeded(5120, 10));
+        assert!(thresholds.are_exceeded(5121, 1));
+        assert!(thresholds.are_exceeded(100, 11));
+
+        let warning = BatchWarning {
+            size: 6000,
+            statements: 2,
+            keyspace: Some("ks".to_string()),
+            table: Some("t".to_string()),
+            location: None,
+        };
+        assert_eq!(
+            warning.to_string(),
+            "Batch of 2 statements and 6000 bytes to ks.t exceeds batch warning thresholds"
+        );
+    }
+}
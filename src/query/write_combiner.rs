@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::{oneshot, Semaphore};
+use tokio::time::{sleep_until, Instant};
+
+use crate::consistency::Consistency;
+use crate::error::{Error, Result};
+use crate::frame::frame_batch::BatchType;
+use crate::frame::Frame;
+use crate::query::{BatchBuilder, PreparedQuery, QueryBatch, QueryValues};
+
+/// Configuration of a `WriteCombiner`.
+#[derive(Clone, Debug)]
+pub struct WriteCombinerConfig {
+    /// Time a write waits for other writes to the same partition, counted from the first write
+    /// of a batch.
+    pub window: Duration,
+    /// Maximum number of statements of a batch. Full batches are sent without waiting.
+    pub max_statements: usize,
+    /// Consistency of batches. `None` uses the default consistency of the session.
+    pub consistency: Option<Consistency>,
+    /// Maximum number of writes waiting to be combined. Writers wait for room in the queue, so
+    /// they're slowed down once batches aren't applied as fast as writes arrive.
+    pub max_queued_writes: usize,
+    /// Maximum number of batches sent at the same time. Further writes stay queued until one
+    /// of them is applied.
+    pub max_in_flight_batches: usize,
+}
+
+impl WriteCombinerConfig {
+    const DEFAULT_WINDOW: Duration = Duration::from_millis(2);
+    const DEFAULT_MAX_STATEMENTS: usize = 32;
+    const DEFAULT_MAX_QUEUED_WRITES: usize = 1024;
+    const DEFAULT_MAX_IN_FLIGHT_BATCHES: usize = 16;
+}
+
+impl Default for WriteCombinerConfig {
+    fn default() -> Self {
+        WriteCombinerConfig {
+            window: Self::DEFAULT_WINDOW,
+            max_statements: Self::DEFAULT_MAX_STATEMENTS,
+            consistency: None,
+            max_queued_writes: Self::DEFAULT_MAX_QUEUED_WRITES,
+            max_in_flight_batches: Self::DEFAULT_MAX_IN_FLIGHT_BATCHES,
+        }
+    }
+}
+
+/// Keyspace, table and routing key of a partition, and whether values are bound by name, since
+/// batches can't mix values with and without names.
+type Partition = (String, Option<String>, Vec<u8>, bool);
+
+struct Write {
+    prepared: PreparedQuery,
+    values: QueryValues,
+    result: oneshot::Sender<Result<()>>,
+}
+
+struct PendingBatch {
+    writes: Vec<Write>,
+    deadline: Instant,
+}
+
+/// Opt-in combiner of writes of prepared statements to the same partition, which arrive within
+/// a short window, into unlogged batches. It improves throughput of many small writes, e.g. of
+/// telemetry, since a single request applies many of them. Writes whose partition isn't known,
+/// i.e. its keyspace or partition key values, are sent alone.
+///
+/// Batches are sent in the background with given function, e.g. calling
+/// `BatchExecutor::batch_with_params()` of a shared session, at most `max_in_flight_batches` at
+/// a time, and the combiner stops once all its clones are dropped, sending pending batches
+/// first. Counter updates and conditional
+/// statements can't be combined into unlogged batches, so they shouldn't be written with it.
+#[derive(Clone, Debug)]
+pub struct WriteCombiner {
+    writes: Sender<Write>,
+}
+
+impl WriteCombiner {
+    /// Creates a combiner sending batches with given function. It has to be called within a
+    /// Tokio runtime.
+    pub fn new<F, R>(config: WriteCombinerConfig, send_batch: F) -> Self
+    where
+        F: Fn(QueryBatch) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Frame>> + Send + 'static,
+    {
+        let (writes, receiver) = channel(config.max_queued_writes.max(1));
+        tokio::spawn(combine(config, receiver, Arc::new(send_batch)));
+        WriteCombiner { writes }
+    }
+
+    /// Queues a write, waiting for room in the queue, and returns a future resolved once the
+    /// batch it's combined into is applied. Fire-and-forget writes drop the returned future.
+    pub async fn write<V: Into<QueryValues>>(
+        &self,
+        prepared: &PreparedQuery,
+        values: V,
+    ) -> Result<impl Future<Output = Result<()>> + Send + 'static> {
+        let (sender, result) = oneshot::channel();
+        let write = Write {
+            prepared: prepared.clone(),
+            values: values.into(),
+            result: sender,
+        };
+        self.writes
+            .send(write)
+            .await
+            .map_err(|_| Error::from("Write combiner is stopped"))?;
+
+        Ok(async move {
+            result
+                .await
+                .unwrap_or_else(|_| Err("Write combiner is stopped".into()))
+        })
+    }
+}
+
+async fn combine<F, R>(config: WriteCombinerConfig, mut writes: Receiver<Write>, send_batch: Arc<F>)
+where
+    F: Fn(QueryBatch) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<Frame>> + Send + 'static,
+{
+    let in_flight = Arc::new(Semaphore::new(config.max_in_flight_batches.max(1)));
+    let mut pending: HashMap<Partition, PendingBatch> = HashMap::new();
+
+    loop {
+        let deadline = pending.values().map(|batch| batch.deadline).min();
+
+        tokio::select! {
+            write = writes.recv() => {
+                let write = match write {
+                    Some(write) => write,
+                    None => break,
+                };
+
+                let partition = match partition(&write) {
+                    Some(partition) => partition,
+                    None => {
+                        flush(&config, vec![write], &send_batch, &in_flight).await;
+                        continue;
+                    }
+                };
+
+                let batch = pending.entry(partition.clone()).or_insert_with(|| PendingBatch {
+                    writes: vec![],
+                    deadline: Instant::now() + config.window,
+                });
+                batch.writes.push(write);
+
+                if batch.writes.len() >= config.max_statements {
+                    if let Some(batch) = pending.remove(&partition) {
+                        flush(&config, batch.writes, &send_batch, &in_flight).await;
+                    }
+                }
+            }
+            _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                let now = Instant::now();
+                let expired: Vec<_> = pending
+                    .iter()
+                    .filter(|(_, batch)| batch.deadline <= now)
+                    .map(|(partition, _)| partition.clone())
+                    .collect();
+
+                for partition in expired {
+                    if let Some(batch) = pending.remove(&partition) {
+                        flush(&config, batch.writes, &send_batch, &in_flight).await;
+                    }
+                }
+            }
+        }
+    }
+
+    for (_, batch) in pending.drain() {
+        flush(&config, batch.writes, &send_batch, &in_flight).await;
+    }
+}
+
+fn partition(write: &Write) -> Option<Partition> {
+    let prepared = &write.prepared;
+    let keyspace = prepared.keyspace.clone()?;
+    let routing_key = prepared.routing_key(&write.values)?;
+
+    Some((
+        keyspace,
+        prepared.table.clone(),
+        routing_key,
+        write.values.with_names(),
+    ))
+}
+
+/// Sends writes as an unlogged batch in the background, passing its result to all of them. It
+/// waits while the maximum number of batches is in flight, so writes stay queued meanwhile.
+async fn flush<F, R>(
+    config: &WriteCombinerConfig,
+    writes: Vec<Write>,
+    send_batch: &Arc<F>,
+    in_flight: &Arc<Semaphore>,
+) where
+    F: Fn(QueryBatch) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<Frame>> + Send + 'static,
+{
+    let mut batch = BatchBuilder::new()
+        .batch_type(BatchType::Unlogged)
+        .idempotent(writes.iter().all(|write| write.prepared.is_idempotent));
+    if let Some(consistency) = config.consistency {
+        batch = batch.consistency(consistency);
+    }
+
+    let mut results = Vec::with_capacity(writes.len());
+    for write in writes {
+        batch = batch.add_prepared(&write.prepared, write.values);
+        results.push(write.result);
+    }
+
+    let batch = batch.finalize();
+    let send_batch = send_batch.clone();
+    // the semaphore is never closed
+    let permit = in_flight.clone().acquire_owned().await.ok();
+    tokio::spawn(async move {
+        let result = match batch {
+            Ok(batch) => send_batch(batch).await.map(|_| ()),
+            Err(error) => Err(error),
+        };
+        drop(permit);
+
+        for sender in results {
+            let _ = sender.send(result.as_ref().map(|_| ()).map_err(duplicate));
+        }
+    });
+}
+
+/// Copies an error of a batch for each of its writes. Errors which can't be copied are
+/// converted into general errors with the same message.
+fn duplicate(error: &Error) -> Error {
+    match error {
+        Error::General(message) => Error::General(message.clone()),
+        Error::Timeout(message) => Error::Timeout(message.clone()),
+        Error::ConnectionBroken(message) => Error::ConnectionBroken(message.clone()),
+        Error::MissingColumn(name) => Error::MissingColumn(name.clone()),
+        error => Error::General(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{Opcode, Version};
+    use crate::types::CBytesShort;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    fn prepared_query(keyspace: Option<&str>) -> PreparedQuery {
+        PreparedQuery {
+            id: CBytesShort::new(vec![1]),
+            query: None,
+            keyspace: keyspace.map(Into::into),
+            table: Some("t".into()),
+            pk_indexes: vec![0],
+            bind_names: vec![],
+            is_idempotent: true,
+            result_metadata: None,
+        }
+    }
+
+    fn void_frame() -> Frame {
+        Frame::new(
+            Version::Response,
+            vec![],
+            Opcode::Result,
+            vec![0, 0, 0, 1],
+            None,
+            vec![],
+        )
+    }
+
+    #[tokio::test]
+    async fn combines_writes_to_the_same_partition() {
+        let batches = Arc::new(Mutex::new(vec![]));
+        let sent = batches.clone();
+        let combiner = WriteCombiner::new(
+            WriteCombinerConfig {
+                window: Duration::from_millis(20),
+                max_statements: 2,
+                consistency: Some(Consistency::Quorum),
+                ..WriteCombinerConfig::default()
+            },
+            move |batch: QueryBatch| {
+                sent.lock().unwrap().push(batch);
+                async { Ok(void_frame()) }
+            },
+        );
+
+        let prepared = prepared_query(Some("ks"));
+        let writes = vec![
+            combiner.write(&prepared, query_values!(1, "a")).await,
+            combiner
+                .write(&prepared_query(None), query_values!(1, "b"))
+                .await,
+            combiner.write(&prepared, query_values!(2, "c")).await,
+            combiner.write(&prepared, query_values!(1, "d")).await,
+            combiner.write(&prepared, query_values!(1, "e")).await,
+        ];
+        for write in writes {
+            write.unwrap().await.unwrap();
+        }
+
+        let batches = batches.lock().unwrap();
+        let mut sizes: Vec<_> = batches
+            .iter()
+            .map(|batch| (batch.routing_key.clone(), batch.queries.len()))
+            .collect();
+        sizes.sort();
+        assert_eq!(
+            sizes,
+            vec![
+                (None, 1),
+                (Some(1i32.to_be_bytes().to_vec()), 1),
+                (Some(1i32.to_be_bytes().to_vec()), 2),
+                (Some(2i32.to_be_bytes().to_vec()), 1),
+            ]
+        );
+        assert!(batches.iter().all(|batch| {
+            batch.batch_type == BatchType::Unlogged
+                && batch.consistency == Consistency::Quorum
+                && batch.is_idempotent
+        }));
+    }
+
+    #[tokio::test]
+    async fn passes_errors_to_all_writes() {
+        let combiner = WriteCombiner::new(WriteCombinerConfig::default(), |_| async {
+            Err(Error::Timeout("no response".into()))
+        });
+
+        let prepared = prepared_query(Some("ks"));
+        let first = combiner.write(&prepared, query_values!(1)).await.unwrap();
+        let second = combiner.write(&prepared, query_values!(1)).await.unwrap();
+        drop(combiner);
+
+        assert!(matches!(first.await, Err(Error::Timeout(_))));
+        assert!(matches!(second.await, Err(Error::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn limits_batches_in_flight() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+        let (counted, counted_max) = (active.clone(), max_active.clone());
+        let combiner = WriteCombiner::new(
+            WriteCombinerConfig {
+                max_statements: 1,
+                max_queued_writes: 1,
+                max_in_flight_batches: 2,
+                ..WriteCombinerConfig::default()
+            },
+            move |_| {
+                let (active, max_active) = (counted.clone(), counted_max.clone());
+                async move {
+                    let count = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(count, Ordering::SeqCst);
+                    sleep_until(Instant::now() + Duration::from_millis(5)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    Ok(void_frame())
+                }
+            },
+        );
+
+        let prepared = prepared_query(Some("ks"));
+        let mut writes = vec![];
+        for key in 0..6 {
+            writes.push(combiner.write(&prepared, query_values!(key)).await.unwrap());
+        }
+        for write in writes {
+            write.await.unwrap();
+        }
+
+        assert_eq!(max_active.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,18 @@
+//! Policies which decide how the driver reacts to failures.
+
+mod reconnection_policy;
+mod retry_policy;
+mod speculative_execution;
+
+pub use crate::retry::reconnection_policy::{
+    ConstantReconnectionPolicy, ExponentialReconnectionPolicy, ReconnectionPolicy,
+    ReconnectionSchedule,
+};
+pub use crate::retry::retry_policy::{
+    is_ambiguous, is_not_executed, DefaultRetryPolicy, DowngradingConsistencyRetryPolicy,
+    RetryDecision, RetryPolicy, RetryRequest,
+};
+pub use crate::retry::speculative_execution::{
+    ConstantSpeculativeExecutionPolicy, NoSpeculativeExecutionPolicy,
+    PercentileSpeculativeExecutionPolicy, SpeculativeExecutionPolicy,
+};
@@ -0,0 +1,142 @@
+use rand;
+use std::time::Duration;
+
+/// Decides how long to wait between attempts of reconnecting to a node which went down.
+pub trait ReconnectionPolicy {
+    /// Creates a new schedule for a node which has just gone down.
+    fn new_node_schedule(&self) -> Box<dyn ReconnectionSchedule + Send + Sync>;
+}
+
+/// Schedule of reconnection attempts to a single node. A new schedule is created each time a
+/// node goes down.
+pub trait ReconnectionSchedule {
+    /// Returns the delay before the next reconnection attempt.
+    fn next_delay(&mut self) -> Duration;
+}
+
+/// Policy which waits the same amount of time between reconnection attempts.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantReconnectionPolicy {
+    delay: Duration,
+}
+
+impl ConstantReconnectionPolicy {
+    pub fn new(delay: Duration) -> Self {
+        ConstantReconnectionPolicy { delay }
+    }
+}
+
+impl Default for ConstantReconnectionPolicy {
+    fn default() -> Self {
+        ConstantReconnectionPolicy::new(Duration::from_secs(1))
+    }
+}
+
+impl ReconnectionPolicy for ConstantReconnectionPolicy {
+    fn new_node_schedule(&self) -> Box<dyn ReconnectionSchedule + Send + Sync> {
+        Box::new(ConstantReconnectionSchedule { delay: self.delay })
+    }
+}
+
+struct ConstantReconnectionSchedule {
+    delay: Duration,
+}
+
+impl ReconnectionSchedule for ConstantReconnectionSchedule {
+    fn next_delay(&mut self) -> Duration {
+        self.delay
+    }
+}
+
+/// Policy which doubles the delay after each failed reconnection attempt, starting with
+/// `base_delay` and capped at `max_delay`. Delays are randomized by up to 15% in order to avoid
+/// many clients reconnecting at the same time.
+#[derive(Clone, Copy, Debug)]
+pub struct ExponentialReconnectionPolicy {
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl ExponentialReconnectionPolicy {
+    pub fn new(base_delay: Duration, max_delay: Duration) -> Self {
+        ExponentialReconnectionPolicy {
+            base_delay,
+            max_delay: max_delay.max(base_delay),
+        }
+    }
+}
+
+impl Default for ExponentialReconnectionPolicy {
+    fn default() -> Self {
+        ExponentialReconnectionPolicy::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+impl ReconnectionPolicy for ExponentialReconnectionPolicy {
+    fn new_node_schedule(&self) -> Box<dyn ReconnectionSchedule + Send + Sync> {
+        Box::new(ExponentialReconnectionSchedule {
+            base_delay: self.base_delay,
+            max_delay: self.max_delay,
+            attempt: 0,
+        })
+    }
+}
+
+struct ExponentialReconnectionSchedule {
+    base_delay: Duration,
+    max_delay: Duration,
+    attempt: u32,
+}
+
+impl ExponentialReconnectionSchedule {
+    const MAX_JITTER: f64 = 0.15;
+}
+
+impl ReconnectionSchedule for ExponentialReconnectionSchedule {
+    fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .base_delay
+            .checked_mul(1 << self.attempt.min(31))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        self.attempt = self.attempt.saturating_add(1);
+
+        let jitter = 1.0 + Self::MAX_JITTER * (2.0 * rand::random::<f64>() - 1.0);
+        delay.mul_f64(jitter).min(self.max_delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_delay() {
+        let mut schedule = ConstantReconnectionPolicy::new(Duration::from_secs(2)).new_node_schedule();
+        for _ in 0..10 {
+            assert_eq!(schedule.next_delay(), Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn exponential_delay_grows() {
+        let mut schedule =
+            ExponentialReconnectionPolicy::new(Duration::from_secs(1), Duration::from_secs(60))
+                .new_node_schedule();
+
+        let first = schedule.next_delay();
+        assert!(first >= Duration::from_millis(850) && first <= Duration::from_millis(1150));
+
+        let second = schedule.next_delay();
+        assert!(second >= Duration::from_millis(1700) && second <= Duration::from_millis(2300));
+    }
+
+    #[test]
+    fn exponential_delay_is_capped() {
+        let mut schedule =
+            ExponentialReconnectionPolicy::new(Duration::from_secs(1), Duration::from_secs(10))
+                .new_node_schedule();
+        for _ in 0..100 {
+            assert!(schedule.next_delay() <= Duration::from_secs(10));
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::fmt;
+
+use crate::consistency::Consistency;
+use crate::error::Error;
+use crate::frame::frame_error::{
+    AdditionalErrorInfo, ReadTimeoutError, UnavailableError, WriteTimeoutError, WriteType,
+};
+
+/// Request which failed, as seen by a retry policy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryRequest {
+    /// Consistency of the failed attempt.
+    pub consistency: Consistency,
+    /// Number of retries done so far, 0 for the first attempt.
+    pub retry_count: usize,
+    /// Whether the request can be safely applied more than once. Policies are consulted about
+    /// ambiguous failures, after which it's unknown whether the request was applied, only for
+    /// idempotent requests - others are returned to the caller.
+    pub is_idempotent: bool,
+}
+
+/// What to do with a failed request.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RetryDecision {
+    /// Sends the request again to the same node, with given consistency.
+    RetrySameNode(Consistency),
+    /// Sends the request to the next node of the query plan, with given consistency.
+    RetryNextNode(Consistency),
+    /// Returns the error to the caller.
+    Rethrow,
+    /// Returns an empty result to the caller, as if the request succeeded.
+    Ignore,
+}
+
+/// Decides whether failed requests are retried. Server errors other than timeouts and
+/// unavailability, which can't be fixed by retrying, e.g. syntax errors, are always returned
+/// to the caller.
+pub trait RetryPolicy: fmt::Debug {
+    /// Called when replicas didn't respond to a read in time.
+    fn on_read_timeout(&self, request: &RetryRequest, error: &ReadTimeoutError) -> RetryDecision;
+
+    /// Called when replicas didn't acknowledge a write in time.
+    fn on_write_timeout(&self, request: &RetryRequest, error: &WriteTimeoutError) -> RetryDecision;
+
+    /// Called when the coordinator knew there were not enough live replicas to reach the
+    /// requested consistency, so the request was not sent to them.
+    fn on_unavailable(&self, request: &RetryRequest, error: &UnavailableError) -> RetryDecision;
+
+    /// Called when the request failed because of the coordinator, e.g. it was overloaded, or
+    /// the connection to it, e.g. it broke or the request timed out.
+    fn on_request_error(&self, request: &RetryRequest, error: &Error) -> RetryDecision;
+}
+
+/// Checks if a request failed in a way which guarantees it was not executed, so it can be
+/// safely sent to another node.
+pub fn is_not_executed(error: &Error) -> bool {
+    match error {
+        // connections which can't be established never carried the request, while failures of
+        // established connections are reported as `ConnectionBroken`, since the request could
+        // have been sent
+        Error::Io(_) => true,
+        Error::Server(error) => matches!(
+            error.additional_info,
+            AdditionalErrorInfo::Overloaded(_) | AdditionalErrorInfo::IsBootstrapping(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Checks if a request failed in a way which leaves it unknown whether it was applied, e.g. a
+/// write timed out or the connection broke after the request was sent.
+pub fn is_ambiguous(error: &Error) -> bool {
+    match error {
+        Error::Timeout(_) | Error::ConnectionBroken(_) => true,
+        Error::Server(error) => matches!(
+            error.additional_info,
+            AdditionalErrorInfo::WriteTimeout(_)
+                | AdditionalErrorInfo::WriteFailure(_)
+                | AdditionalErrorInfo::Server(_)
+                | AdditionalErrorInfo::Truncate(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Policy which retries only when the retry is likely to succeed and can't do harm:
+///
+/// * read timeouts are retried once on the same node if enough replicas responded, but the
+///   data was not retrieved,
+/// * write timeouts are retried once on the same node only for batch log writes,
+/// * unavailable errors are retried once on the next node, which might see more live replicas,
+/// * requests which were not executed, e.g. because the node was overloaded or couldn't be
+///   connected to, are retried on the next node,
+/// * idempotent requests which timed out or whose connection broke are retried on the next
+///   node.
+///
+/// This is the default policy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultRetryPolicy;
+
+impl RetryPolicy for DefaultRetryPolicy {
+    fn on_read_timeout(&self, request: &RetryRequest, error: &ReadTimeoutError) -> RetryDecision {
+        if request.retry_count == 0
+            && error.received >= error.blockfor
+            && !error.replica_has_responded()
+        {
+            RetryDecision::RetrySameNode(request.consistency)
+        } else {
+            RetryDecision::Rethrow
+        }
+    }
+
+    fn on_write_timeout(&self, request: &RetryRequest, error: &WriteTimeoutError) -> RetryDecision {
+        // batch log writes are idempotent
+        if request.retry_count == 0 && matches!(error.write_type, WriteType::BatchLog) {
+            RetryDecision::RetrySameNode(request.consistency)
+        } else {
+            RetryDecision::Rethrow
+        }
+    }
+
+    fn on_unavailable(&self, request: &RetryRequest, _error: &UnavailableError) -> RetryDecision {
+        if request.retry_count == 0 {
+            RetryDecision::RetryNextNode(request.consistency)
+        } else {
+            RetryDecision::Rethrow
+        }
+    }
+
+    fn on_request_error(&self, request: &RetryRequest, error: &Error) -> RetryDecision {
+        let is_aborted = matches!(error, Error::Timeout(_) | Error::ConnectionBroken(_));
+        if is_not_executed(error) || (request.is_idempotent && is_aborted) {
+            RetryDecision::RetryNextNode(request.consistency)
+        } else {
+            RetryDecision::Rethrow
+        }
+    }
+}
+
+/// Policy which behaves like `DefaultRetryPolicy`, but additionally retries requests with a
+/// lower consistency when not enough replicas responded or are alive, and ignores write
+/// timeouts of writes which reached at least one replica.
+///
+/// The consistency of retried requests is lowered to the highest one likely to succeed, so
+/// the outcome doesn't meet the originally requested consistency guarantees - this policy
+/// should only be used when availability matters more than consistency.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DowngradingConsistencyRetryPolicy;
+
+impl DowngradingConsistencyRetryPolicy {
+    /// Returns the highest consistency reachable with given number of replicas.
+    fn max_likely_to_work(replicas: i32) -> RetryDecision {
+        match replicas {
+            _ if replicas >= 3 => RetryDecision::RetrySameNode(Consistency::Three),
+            2 => RetryDecision::RetrySameNode(Consistency::Two),
+            1 => RetryDecision::RetrySameNode(Consistency::One),
+            _ => RetryDecision::Rethrow,
+        }
+    }
+}
+
+fn is_serial(consistency: Consistency) -> bool {
+    consistency == Consistency::Serial || consistency == Consistency::LocalSerial
+}
+
+impl RetryPolicy for DowngradingConsistencyRetryPolicy {
+    fn on_read_timeout(&self, request: &RetryRequest, error: &ReadTimeoutError) -> RetryDecision {
+        if request.retry_count != 0 || is_serial(request.consistency) {
+            return RetryDecision::Rethrow;
+        }
+
+        if error.received < error.blockfor {
+            Self::max_likely_to_work(error.received)
+        } else if !error.replica_has_responded() {
+            RetryDecision::RetrySameNode(request.consistency)
+        } else {
+            RetryDecision::Rethrow
+        }
+    }
+
+    fn on_write_timeout(&self, request: &RetryRequest, error: &WriteTimeoutError) -> RetryDecision {
+        if request.retry_count != 0 {
+            return RetryDecision::Rethrow;
+        }
+
+        match error.write_type {
+            // the write reached a replica, so it will eventually be propagated to others
+            WriteType::Simple | WriteType::Batch if error.received > 0 => RetryDecision::Ignore,
+            WriteType::UnloggedBatch => Self::max_likely_to_work(error.received),
+            WriteType::BatchLog => RetryDecision::RetrySameNode(request.consistency),
+            _ => RetryDecision::Rethrow,
+        }
+    }
+
+    fn on_unavailable(&self, request: &RetryRequest, error: &UnavailableError) -> RetryDecision {
+        if request.retry_count != 0 {
+            return RetryDecision::Rethrow;
+        }
+
+        // serial consistency can't be lowered, but another coordinator might see more replicas
+        if is_serial(request.consistency) {
+            return RetryDecision::RetryNextNode(request.consistency);
+        }
+
+        Self::max_likely_to_work(error.alive)
+    }
+
+    fn on_request_error(&self, request: &RetryRequest, error: &Error) -> RetryDecision {
+        DefaultRetryPolicy.on_request_error(request, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::frame_error::{CDRSError, SimpleError};
+    use crate::frame::FromCursor;
+    use crate::types::CString;
+    use std::io::Cursor;
+
+    fn request(consistency: Consistency, retry_count: usize) -> RetryRequest {
+        RetryRequest {
+            consistency,
+            retry_count,
+            is_idempotent: false,
+        }
+    }
+
+    fn read_timeout(received: i32, blockfor: i32, data_present: bool) -> ReadTimeoutError {
+        let mut bytes = vec![0, 4];
+        bytes.extend_from_slice(&received.to_be_bytes());
+        bytes.extend_from_slice(&blockfor.to_be_bytes());
+        bytes.push(data_present as u8);
+        ReadTimeoutError::from_cursor(&mut Cursor::new(&bytes[..])).unwrap()
+    }
+
+    fn write_timeout(received: i32, write_type: WriteType) -> WriteTimeoutError {
+        WriteTimeoutError {
+            cl: Consistency::Quorum,
+            received,
+            blockfor: 2,
+            write_type,
+        }
+    }
+
+    fn unavailable(alive: i32) -> UnavailableError {
+        UnavailableError {
+            cl: Consistency::Quorum,
+            required: 2,
+            alive,
+        }
+    }
+
+    #[test]
+    fn default_policy_retries_safe_failures_once() {
+        let policy = DefaultRetryPolicy;
+        let first = request(Consistency::Quorum, 0);
+        let second = request(Consistency::Quorum, 1);
+
+        assert_eq!(
+            policy.on_read_timeout(&first, &read_timeout(2, 2, false)),
+            RetryDecision::RetrySameNode(Consistency::Quorum)
+        );
+        assert_eq!(
+            policy.on_read_timeout(&first, &read_timeout(1, 2, false)),
+            RetryDecision::Rethrow
+        );
+        assert_eq!(
+            policy.on_read_timeout(&second, &read_timeout(2, 2, false)),
+            RetryDecision::Rethrow
+        );
+
+        assert_eq!(
+            policy.on_write_timeout(&first, &write_timeout(0, WriteType::BatchLog)),
+            RetryDecision::RetrySameNode(Consistency::Quorum)
+        );
+        assert_eq!(
+            policy.on_write_timeout(&first, &write_timeout(0, WriteType::Simple)),
+            RetryDecision::Rethrow
+        );
+
+        assert_eq!(
+            policy.on_unavailable(&first, &unavailable(1)),
+            RetryDecision::RetryNextNode(Consistency::Quorum)
+        );
+        assert_eq!(
+            policy.on_unavailable(&second, &unavailable(1)),
+            RetryDecision::Rethrow
+        );
+    }
+
+    #[test]
+    fn default_policy_retries_requests_which_were_not_executed() {
+        let policy = DefaultRetryPolicy;
+        let overloaded = Error::Server(CDRSError {
+            error_code: 0x1001,
+            message: CString::new("overloaded".into()),
+            additional_info: AdditionalErrorInfo::Overloaded(SimpleError {}),
+        });
+
+        assert_eq!(
+            policy.on_request_error(&request(Consistency::One, 3), &overloaded),
+            RetryDecision::RetryNextNode(Consistency::One)
+        );
+        assert_eq!(
+            policy.on_request_error(
+                &request(Consistency::One, 0),
+                &Error::Timeout("timeout".into())
+            ),
+            RetryDecision::Rethrow
+        );
+
+        let idempotent = RetryRequest {
+            is_idempotent: true,
+            ..request(Consistency::One, 0)
+        };
+        assert_eq!(
+            policy.on_request_error(&idempotent, &Error::Timeout("timeout".into())),
+            RetryDecision::RetryNextNode(Consistency::One)
+        );
+        assert!(is_ambiguous(&Error::Timeout("timeout".into())));
+        assert!(!is_ambiguous(&overloaded));
+    }
+
+    #[test]
+    fn downgrading_policy_lowers_consistency() {
+        let policy = DowngradingConsistencyRetryPolicy;
+        let first = request(Consistency::Quorum, 0);
+
+        assert_eq!(
+            policy.on_read_timeout(&first, &read_timeout(1, 2, false)),
+            RetryDecision::RetrySameNode(Consistency::One)
+        );
+        assert_eq!(
+            policy.on_read_timeout(&first, &read_timeout(0, 2, false)),
+            RetryDecision::Rethrow
+        );
+        assert_eq!(
+            policy.on_read_timeout(&request(Consistency::Serial, 0), &read_timeout(1, 2, false)),
+            RetryDecision::Rethrow
+        );
+
+        assert_eq!(
+            policy.on_write_timeout(&first, &write_timeout(1, WriteType::Simple)),
+            RetryDecision::Ignore
+        );
+        assert_eq!(
+            policy.on_write_timeout(&first, &write_timeout(0, WriteType::Simple)),
+            RetryDecision::Rethrow
+        );
+        assert_eq!(
+            policy.on_write_timeout(&first, &write_timeout(2, WriteType::UnloggedBatch)),
+            RetryDecision::RetrySameNode(Consistency::Two)
+        );
+
+        assert_eq!(
+            policy.on_unavailable(&first, &unavailable(3)),
+            RetryDecision::RetrySameNode(Consistency::Three)
+        );
+        assert_eq!(
+            policy.on_unavailable(&request(Consistency::LocalSerial, 0), &unavailable(1)),
+            RetryDecision::RetryNextNode(Consistency::LocalSerial)
+        );
+        assert_eq!(
+            policy.on_unavailable(&request(Consistency::Quorum, 1), &unavailable(1)),
+            RetryDecision::Rethrow
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Decides when idempotent requests are sent to further nodes of the query plan while
+/// previous executions are still waiting for responses, in order to reduce tail latency. The
+/// first response is used and other executions are cancelled.
+///
+/// Requests which are not idempotent are never executed speculatively, since they could be
+/// applied more than once.
+pub trait SpeculativeExecutionPolicy: fmt::Debug {
+    /// Returns how long to wait for a response before starting another execution, given the
+    /// number of executions started so far, or `None` if no more executions should be started.
+    fn next_execution_delay(&self, started: usize) -> Option<Duration>;
+
+    /// Called with latencies of successful executions.
+    fn record_latency(&self, _latency: Duration) {}
+}
+
+/// Policy which never starts speculative executions. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoSpeculativeExecutionPolicy;
+
+impl SpeculativeExecutionPolicy for NoSpeculativeExecutionPolicy {
+    fn next_execution_delay(&self, _started: usize) -> Option<Duration> {
+        None
+    }
+}
+
+/// Policy which starts speculative executions at a constant interval, up to a total number of
+/// executions, including the first one.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantSpeculativeExecutionPolicy {
+    delay: Duration,
+    max_executions: usize,
+}
+
+impl ConstantSpeculativeExecutionPolicy {
+    pub fn new(delay: Duration, max_executions: usize) -> Self {
+        ConstantSpeculativeExecutionPolicy {
+            delay,
+            max_executions,
+        }
+    }
+}
+
+impl SpeculativeExecutionPolicy for ConstantSpeculativeExecutionPolicy {
+    fn next_execution_delay(&self, started: usize) -> Option<Duration> {
+        if started < self.max_executions {
+            Some(self.delay)
+        } else {
+            None
+        }
+    }
+}
+
+/// Policy which starts a speculative execution once a request takes longer than given
+/// percentile of recent request latencies, e.g. 99th, up to a total number of executions,
+/// including the first one. Latencies are tracked over a window of the most recent requests
+/// (1000 by default), and no speculative executions are started until enough of them were
+/// measured (100 by default).
+#[derive(Debug)]
+pub struct PercentileSpeculativeExecutionPolicy {
+    percentile: f64,
+    max_executions: usize,
+    window: usize,
+    min_samples: usize,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl PercentileSpeculativeExecutionPolicy {
+    /// Creates a policy using given percentile, between 0 and 100.
+    pub fn new(percentile: f64, max_executions: usize) -> Self {
+        PercentileSpeculativeExecutionPolicy {
+            percentile: percentile.clamp(0.0, 100.0),
+            max_executions,
+            window: 1000,
+            min_samples: 100,
+            latencies: Default::default(),
+        }
+    }
+
+    /// Sets how many recent latencies are taken into account. Default is 1000.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Sets how many latencies need to be measured before speculative executions are
+    /// started. Default is 100.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Returns the configured percentile of recent latencies, if enough were measured.
+    pub fn current_threshold(&self) -> Option<Duration> {
+        let mut latencies: Vec<Duration> = self.latencies.lock().unwrap().iter().cloned().collect();
+        if latencies.is_empty() || latencies.len() < self.min_samples {
+            return None;
+        }
+
+        latencies.sort_unstable();
+        let rank = (self.percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+        Some(latencies[rank.max(1).min(latencies.len()) - 1])
+    }
+}
+
+impl SpeculativeExecutionPolicy for PercentileSpeculativeExecutionPolicy {
+    fn next_execution_delay(&self, started: usize) -> Option<Duration> {
+        if started < self.max_executions {
+            self.current_threshold()
+        } else {
+            None
+        }
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        let mut latencies = self.latencies.lock().unwrap();
+        if latencies.len() >= self.window {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_constant_executions() {
+        let policy = ConstantSpeculativeExecutionPolicy::new(Duration::from_millis(10), 2);
+        assert_eq!(
+            policy.next_execution_delay(1),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(policy.next_execution_delay(2), None);
+        assert_eq!(NoSpeculativeExecutionPolicy.next_execution_delay(1), None);
+    }
+
+    #[test]
+    fn uses_latency_percentile() {
+        let policy = PercentileSpeculativeExecutionPolicy::new(90.0, 3)
+            .with_window(10)
+            .with_min_samples(5);
+        for latency in 1..=4 {
+            policy.record_latency(Duration::from_millis(latency));
+        }
+        assert_eq!(policy.next_execution_delay(1), None);
+
+        // the window keeps latencies from 11 to 20 ms
+        for latency in 5..=20 {
+            policy.record_latency(Duration::from_millis(latency));
+        }
+        assert_eq!(
+            policy.next_execution_delay(1),
+            Some(Duration::from_millis(19))
+        );
+        assert_eq!(policy.next_execution_delay(3), None);
+    }
+}
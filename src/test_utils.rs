@@ -0,0 +1,167 @@
+//! Fixtures shared by unit tests.
+
+#[cfg(any(feature = "bigdecimal", feature = "chrono", feature = "time"))]
+use std::convert::TryFrom;
+
+#[cfg(any(feature = "bigdecimal", feature = "chrono", feature = "time"))]
+use crate::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::frame::frame_result::{ColType, ColTypeOption, ColTypeOptionValue};
+use crate::frame::{AsByte, Frame, Opcode, Version};
+use crate::types::rows::Row;
+use crate::types::value::{Bytes, Value};
+use crate::types::CBytes;
+
+/// Encodes a `[string]`.
+pub fn string(value: &str) -> Vec<u8> {
+    let mut bytes = (value.len() as i16).to_be_bytes().to_vec();
+    bytes.extend_from_slice(value.as_bytes());
+    bytes
+}
+
+/// Encodes a `[bytes]` value.
+pub fn value(bytes: &[u8]) -> Vec<u8> {
+    let mut value = (bytes.len() as i32).to_be_bytes().to_vec();
+    value.extend_from_slice(bytes);
+    value
+}
+
+/// Builds a `Rows` result of columns of table `ks.t`, given by name and encoded type, and rows
+/// of their values, where `None` is null. Results with a paging state have more pages.
+pub fn rows_frame(
+    columns: &[(&str, Vec<u8>)],
+    rows: Vec<Vec<Option<Vec<u8>>>>,
+    paging_state: Option<&[u8]>,
+) -> Frame {
+    let flags: i32 = if paging_state.is_some() {
+        0x0003
+    } else {
+        0x0001
+    };
+    let mut body = vec![0, 0, 0, 2];
+    body.extend_from_slice(&flags.to_be_bytes());
+    body.extend_from_slice(&(columns.len() as i32).to_be_bytes());
+    if let Some(paging_state) = paging_state {
+        body.extend(value(paging_state));
+    }
+
+    body.extend(string("ks"));
+    body.extend(string("t"));
+    for (name, col_type) in columns {
+        body.extend(string(name));
+        body.extend_from_slice(col_type);
+    }
+
+    body.extend_from_slice(&(rows.len() as i32).to_be_bytes());
+    for row in rows {
+        for column_value in row {
+            match column_value {
+                Some(column_value) => body.extend(value(&column_value)),
+                None => body.extend_from_slice(&(-1i32).to_be_bytes()),
+            }
+        }
+    }
+
+    Frame::new(
+        Version::Response,
+        vec![],
+        Opcode::Result,
+        body,
+        None,
+        vec![],
+    )
+}
+
+/// Builds a single row of given columns, given by name, encoded type and value.
+pub fn row(columns: Vec<(&str, Vec<u8>, Option<Vec<u8>>)>) -> Row {
+    let (columns, values): (Vec<_>, Vec<_>) = columns
+        .into_iter()
+        .map(|(name, col_type, column_value)| ((name, col_type), column_value))
+        .unzip();
+
+    rows_frame(&columns, vec![values], None)
+        .get_body()
+        .unwrap()
+        .into_rows()
+        .unwrap()
+        .remove(0)
+}
+
+/// Returns a type without options, e.g. of native types.
+pub fn col_type(id: ColType) -> ColTypeOption {
+    ColTypeOption { id, value: None }
+}
+
+/// Returns a type with given options, e.g. of collections and UDTs.
+pub fn col_type_with(id: ColType, value: ColTypeOptionValue) -> ColTypeOption {
+    ColTypeOption {
+        id,
+        value: Some(value),
+    }
+}
+
+/// Encodes a value as it's bound.
+pub fn encode<T: Into<Bytes>>(value: T) -> CBytes {
+    CBytes::new(Value::new_normal(value).body)
+}
+
+/// Encodes a value which is bound with a fallible conversion.
+#[cfg(any(feature = "bigdecimal", feature = "chrono", feature = "time"))]
+pub fn try_encode<T>(value: T) -> CBytes
+where
+    Bytes: TryFrom<T, Error = Error>,
+{
+    encode(Bytes::try_from(value).unwrap())
+}
+
+/// Answers requests read from given stream like a node which doesn't require authentication -
+/// PREPARE requests with a statement without columns, recording their query texts, and other
+/// requests with void results.
+pub async fn serve_requests<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    prepared: Arc<Mutex<Vec<String>>>,
+) {
+    let mut header = [0u8; 9];
+    while stream.read_exact(&mut header).await.is_ok() {
+        let length = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+        let mut body = vec![0u8; length as usize];
+        if stream.read_exact(&mut body).await.is_err() {
+            break;
+        }
+
+        let (opcode, result) = match header[4] {
+            opcode if opcode == Opcode::Options.as_byte() => (Opcode::Supported, vec![0, 0]),
+            opcode if opcode == Opcode::Startup.as_byte() => (Opcode::Ready, vec![]),
+            opcode if opcode == Opcode::Prepare.as_byte() => {
+                let query_length = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                let query = &body[4..4 + query_length as usize];
+                prepared
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8(query.to_vec()).unwrap());
+
+                // id and metadata without columns
+                let mut result = vec![0, 0, 0, 4, 0, 1, 7];
+                result.extend_from_slice(&[0; 8]);
+                if !cfg!(feature = "v3") {
+                    // primary key count
+                    result.extend_from_slice(&[0; 4]);
+                }
+                // skipped result metadata
+                result.extend_from_slice(&[0, 0, 0, 4, 0, 0, 0, 0]);
+                (Opcode::Result, result)
+            }
+            _ => (Opcode::Result, vec![0, 0, 0, 1]),
+        };
+
+        let mut response = vec![Version::Response.as_byte(), 0, header[2], header[3]];
+        response.push(opcode.as_byte());
+        response.extend_from_slice(&(result.len() as u32).to_be_bytes());
+        response.extend_from_slice(&result);
+        if stream.write_all(&response).await.is_err() {
+            break;
+        }
+    }
+}
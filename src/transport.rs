@@ -7,174 +7,1884 @@
 //! * [`TransportTcp`][tTcp] is default TCP transport which is usually used to establish
 //!connection and exchange frames.
 //!
-//! * `TransportTls` is a transport which is used to establish SSL encrypted connection
+//! * `TransportRustls` is a transport which is used to establish SSL encrypted connection
 //!with Apache Cassandra server. **Note:** this option is available if and only if CDRS is imported
-//!with `ssl` feature.
-#[cfg(feature = "rust-tls")]
-use std::sync::Arc;
-#[cfg(feature = "rust-tls")]
-use tokio_rustls::{TlsConnector as RustlsConnector, client::TlsStream as RustlsStream};
-use std::io;
-use tokio::io::{AsyncWriteExt, AsyncWrite, AsyncRead, ReadBuf};
-use std::task::Context;
-use tokio::macros::support::{Pin, Poll};
-use std::io::Error;
-use std::net;
-use tokio::net::TcpStream;
+//!with `rust-tls` feature.
+//!
+//! * `TransportUnix` is a transport which connects over a Unix domain socket, e.g. to a local
+//!   CQL proxy. **Note:** this option is available only on Unix platforms.
+//!
+//!Every transport multiplexes requests over a single connection. Each request gets a protocol
+//!stream id assigned by a background writer task, and a background reader task routes responses
+//!back to the waiting requests by their stream ids, so many requests can be in flight on one
+//!connection at the same time. Requests which queue up while the writer task is busy are
+//!coalesced into a single vectored write.
 use async_trait::async_trait;
+use fnv::FnvHashMap;
+#[cfg(unix)]
+use fnv::FnvHasher;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+#[cfg(unix)]
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::IoSlice;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{split, AsyncRead, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::net::{lookup_host, TcpSocket, TcpStream};
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, timeout, Instant};
+#[cfg(feature = "rust-tls")]
+use tokio_rustls::TlsConnector as RustlsConnector;
+
+use crate::cluster::ShardInfo;
+use crate::compression::Compression;
+use crate::consistency::Consistency;
+use crate::error;
+use crate::frame::frame_response::ResponseBody;
+use crate::frame::frame_result::ResResultBody;
+use crate::frame::parser::{convert_frame_into_result, parse_raw_frame};
+use crate::frame::{Frame, IntoBytes, Opcode, StreamId};
+use crate::proxy::{open_tunnel, ProxyConfig};
+use crate::query::RequestPriority;
+
+/// Size of the queue of requests waiting to be written to a connection.
+const WRITE_BUFFER_SIZE: usize = 128;
+
+/// Maximum number of queued request frames written to a connection at once.
+const MAX_COALESCED_FRAMES: usize = 64;
+
+/// Highest stream id which can be used by requests. Negative ids are reserved for server events.
+const MAX_STREAM_ID: StreamId = StreamId::MAX;
+
+/// Position of stream id bytes in an encoded frame.
+const STREAM_ID_OFFSET: usize = 2;
+
+/// Maximum number of source ports tried when connecting to a specific shard.
+const MAX_SOURCE_PORT_ATTEMPTS: usize = 16;
+
+/// Channel used for passing server event frames (frames with negative stream id) received by
+/// a transport.
+pub type EventFrameSender = mpsc::UnboundedSender<Frame>;
+
+/// Configuration of a single connection.
+#[derive(Clone, Debug)]
+pub struct TransportConfig {
+    /// Interval of inactivity after which a connection sends an OPTIONS request to check if it
+    /// is still alive. `None` disables heartbeats.
+    pub heartbeat_interval: Option<Duration>,
+    /// Time to wait for a heartbeat response before the connection is considered broken.
+    pub heartbeat_timeout: Duration,
+    /// Sets `TCP_NODELAY`, which disables Nagle's algorithm.
+    pub tcp_nodelay: bool,
+    /// Sets `SO_KEEPALIVE`.
+    pub tcp_keepalive: bool,
+    /// Sets `SO_SNDBUF`. `None` leaves the system default.
+    pub send_buffer_size: Option<u32>,
+    /// Sets `SO_RCVBUF`. `None` leaves the system default.
+    pub recv_buffer_size: Option<u32>,
+    /// Local address to bind the socket to before connecting.
+    pub local_addr: Option<SocketAddr>,
+    /// Time limit for establishing a connection, including TLS handshake.
+    pub connect_timeout: Duration,
+    /// Time limit for receiving a response to a request. `None` means waiting indefinitely.
+    pub request_timeout: Option<Duration>,
+    /// Proxy to tunnel TCP connections through.
+    pub proxy: Option<ProxyConfig>,
+    /// Delay between staggered connection attempts when a hostname resolves to multiple
+    /// addresses, e.g. both IPv4 and IPv6 ones (Happy Eyeballs). The first established
+    /// connection is used. `None` connects only to the first resolved address.
+    pub happy_eyeballs_delay: Option<Duration>,
+    /// Maximum number of requests in flight on a single connection. Further requests wait for
+    /// a free slot within their request timeout. `None` means no limit other than the number of
+    /// stream ids.
+    pub max_in_flight_requests: Option<usize>,
+    /// Keyspace selected with `USE` statements, shared by connections created with clones of
+    /// this config.
+    pub keyspace: SessionKeyspace,
+}
+
+impl TransportConfig {
+    const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(30);
+    const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+    const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(12);
+    const DEFAULT_HAPPY_EYEBALLS_DELAY: Duration = Duration::from_millis(250);
+    const DEFAULT_MAX_IN_FLIGHT_REQUESTS: usize = 1024;
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        TransportConfig {
+            heartbeat_interval: None,
+            heartbeat_timeout: Self::DEFAULT_HEARTBEAT_TIMEOUT,
+            tcp_nodelay: true,
+            tcp_keepalive: false,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+            local_addr: None,
+            connect_timeout: Self::DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: Some(Self::DEFAULT_REQUEST_TIMEOUT),
+            proxy: None,
+            happy_eyeballs_delay: Some(Self::DEFAULT_HAPPY_EYEBALLS_DELAY),
+            max_in_flight_requests: Some(Self::DEFAULT_MAX_IN_FLIGHT_REQUESTS),
+            keyspace: Default::default(),
+        }
+    }
+}
+
+/// Keyspace selected with `USE` statements, shared by connections of a session. A `USE`
+/// statement only changes the keyspace of the connection it's sent on, so other connections,
+/// including ones established later, e.g. after reconnecting, issue the same `USE` before
+/// serving further requests. Cloning gives a new handle to the same keyspace.
+#[derive(Clone, Debug, Default)]
+pub struct SessionKeyspace {
+    inner: Arc<KeyspaceState>,
+}
+
+#[derive(Debug, Default)]
+struct KeyspaceState {
+    /// Incremented on each change, so connections can cheaply check if they're up to date.
+    generation: AtomicU64,
+    keyspace: Mutex<Option<String>>,
+}
+
+impl SessionKeyspace {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the selected keyspace, if there is one.
+    pub fn get(&self) -> Option<String> {
+        self.inner.keyspace.lock().unwrap().clone()
+    }
+
+    /// Selects a keyspace, which connections switch to before their next requests.
+    pub fn set(&self, keyspace: String) {
+        self.update(keyspace);
+    }
+
+    /// Selects a keyspace, returning the generation of the selection.
+    fn update(&self, keyspace: String) -> u64 {
+        let mut current = self.inner.keyspace.lock().unwrap();
+        if current.as_deref() != Some(keyspace.as_str()) {
+            *current = Some(keyspace);
+            self.inner.generation.fetch_add(1, Ordering::SeqCst);
+        }
+
+        self.inner.generation.load(Ordering::SeqCst)
+    }
+
+    fn generation(&self) -> u64 {
+        self.inner.generation.load(Ordering::SeqCst)
+    }
+
+    /// Returns the selected keyspace along with the generation of the selection.
+    fn current(&self) -> (u64, Option<String>) {
+        let keyspace = self.inner.keyspace.lock().unwrap();
+        (self.generation(), keyspace.clone())
+    }
+}
+
+/// Returns the keyspace selected by a `USE` statement, if the frame is a response to one.
+fn selected_keyspace(frame: &Frame) -> Option<String> {
+    // results start with their kind, which is 0x0003 for results of `USE` statements
+    if frame.opcode != Opcode::Result || !frame.body.starts_with(&[0, 0, 0, 3]) {
+        return None;
+    }
+
+    match frame.get_body().ok()? {
+        ResponseBody::Result(ResResultBody::SetKeyspace(result)) => Some(result.body.into_plain()),
+        _ => None,
+    }
+}
 
 // TODO [v 2.x.x]: CDRSTransport: ... + BufReader + ButWriter + ...
 ///General CDRS transport trait. Both [`TranportTcp`][transportTcp]
-///and [`TransportTls`][transportTls] has their own implementations of this trait. Generaly
-///speaking it extends/includes `io::Read` and `io::Write` traits and should be thread safe.
+///and [`TransportRustls`][transportRustls] has their own implementations of this trait.
+///Transports are cheap handles to a connection - cloning one gives a new handle to the same
+///underlying connection, which allows many concurrent requests to share it.
 ///[transportTcp]:struct.TransportTcp.html
-///[transportTls]:struct.TransportTls.html
+///[transportRustls]:struct.TransportRustls.html
 #[async_trait]
-pub trait CDRSTransport: Sized + AsyncRead + AsyncWriteExt + Send + Sync {
-    /// Creates a new independently owned handle to the underlying socket.
-    ///
-    /// The returned TcpStream is a reference to the same stream that this object references.
-    /// Both handles will read and write the same stream of data, and options set on one stream
-    /// will be propagated to the other stream.
-    async fn try_clone(&self) -> io::Result<Self>;
+pub trait CDRSTransport: Sized + Clone + Send + Sync {
+    /// Schedules a frame for writing and waits for the response with matching stream id.
+    async fn write_frame(&self, frame: &Frame) -> error::Result<Frame>;
 
-    /// Shuts down the read, write, or both halves of this connection.
-    async fn close(&mut self, close: net::Shutdown) -> io::Result<()>;
+    /// Schedules a frame like `write_frame()`, but once the connection has as many requests in
+    /// flight as it allows, requests of higher priority are sent first. Transports without such
+    /// a limit send all requests alike.
+    async fn write_frame_with_priority(
+        &self,
+        frame: &Frame,
+        _priority: RequestPriority,
+    ) -> error::Result<Frame> {
+        self.write_frame(frame).await
+    }
 
-    /// Method that checks that transport is alive
-    fn is_alive(&self) -> bool;
+    /// Checks if the connection is broken, e.g. after a read or write error.
+    fn is_broken(&self) -> bool;
+
+    /// Returns the address of the node this transport is connected to.
+    fn addr(&self) -> SocketAddr;
+
+    /// Checks if the connection can take another request without waiting for requests in
+    /// flight to finish.
+    fn has_capacity(&self) -> bool {
+        true
+    }
+
+    /// Returns sharding information if the connection is open to a sharded (Scylla) node.
+    fn shard_info(&self) -> Option<ShardInfo> {
+        None
+    }
 }
 
 /// Default Tcp transport.
+#[derive(Clone)]
 pub struct TransportTcp {
-    tcp: TcpStream,
-    addr: String,
+    inner: AsyncTransport,
+    shard_info: Option<ShardInfo>,
 }
 
 impl TransportTcp {
-    /// Constructs a new `TransportTcp`.
+    /// Constructs a new `TransportTcp`. Received frames are decompressed using given
+    /// `compression`. Server event frames are passed to `event_handler`, if present.
     ///
     /// # Examples
     ///
     /// ```no_run
+    /// use cdrs_tokio::compression::Compression;
     /// use cdrs_tokio::transport::TransportTcp;
     ///
     /// #[tokio::main]
     /// async fn main() {
     ///     let addr = "127.0.0.1:9042";
-    ///     let tcp_transport = TransportTcp::new(addr).await.unwrap();
+    ///     let tcp_transport = TransportTcp::new(addr, Compression::None, None, &Default::default())
+    ///         .await
+    ///         .unwrap();
     /// }
     /// ```
-    pub async fn new(addr: &str) -> io::Result<TransportTcp> {
-        TcpStream::connect(addr).await.map(|socket| TransportTcp {
-            tcp: socket,
-            addr: addr.to_string(),
-        })
+    pub async fn new(
+        addr: &str,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        config: &TransportConfig,
+    ) -> io::Result<TransportTcp> {
+        Self::connect(addr, compression, event_handler, config, Default::default()).await
     }
-}
 
-impl AsyncRead for TransportTcp {
-    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.tcp).poll_read(cx, buf)
-    }
-}
+    /// Constructs a new `TransportTcp` which counts orphaned requests in given counter shared
+    /// with other connections to the node.
+    pub(crate) async fn connect(
+        addr: &str,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        config: &TransportConfig,
+        orphaned_requests: Arc<AtomicUsize>,
+    ) -> io::Result<TransportTcp> {
+        let addrs = resolve_addrs(addr).await?;
+        let (socket_addr, socket) =
+            with_connect_timeout(config, connect_tcp_any(addrs, config)).await?;
 
-impl AsyncWrite for TransportTcp {
-    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize, Error>> {
-        Pin::new(&mut self.tcp).poll_write(cx, buf)
+        Ok(TransportTcp {
+            inner: AsyncTransport::new(
+                socket_addr,
+                socket,
+                compression,
+                event_handler,
+                config,
+                orphaned_requests,
+            ),
+            shard_info: None,
+        })
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        Pin::new(&mut self.tcp).poll_flush(cx)
+    /// Connects to the shard-aware port of a Scylla node from a source port which makes the
+    /// node assign the connection to given shard.
+    pub(crate) async fn connect_to_shard(
+        addr: SocketAddr,
+        shard_info: &ShardInfo,
+        shard: u32,
+        compression: Compression,
+        config: &TransportConfig,
+        orphaned_requests: Arc<AtomicUsize>,
+    ) -> io::Result<TransportTcp> {
+        let shard_aware_port = shard_info.shard_aware_port.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "Node has no shard-aware port")
+        })?;
+
+        // source port is meaningless when connecting through a proxy
+        if config.proxy.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Shard-aware connections can't go through a proxy",
+            ));
+        }
+
+        let addr = SocketAddr::new(addr.ip(), shard_aware_port);
+        let local_ip = config.local_addr.map_or_else(
+            || match addr {
+                SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            },
+            |local_addr| local_addr.ip(),
+        );
+
+        let socket = with_connect_timeout(config, async {
+            let mut last_error = None;
+            for port in shard_info.source_ports(shard).take(MAX_SOURCE_PORT_ATTEMPTS) {
+                let config = TransportConfig {
+                    local_addr: Some(SocketAddr::new(local_ip, port)),
+                    ..config.clone()
+                };
+
+                match connect_tcp(addr, &config).await {
+                    Ok(socket) => return Ok(socket),
+                    Err(err) if err.kind() == io::ErrorKind::AddrInUse => last_error = Some(err),
+                    Err(err) => return Err(err),
+                }
+            }
+
+            Err(last_error.unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::AddrInUse, "No free source port for shard")
+            }))
+        })
+        .await?;
+
+        Ok(TransportTcp {
+            inner: AsyncTransport::new(
+                addr,
+                socket,
+                compression,
+                None,
+                config,
+                orphaned_requests,
+            ),
+            shard_info: None,
+        })
     }
 
-    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        Pin::new(&mut self.tcp).poll_shutdown(cx)
+    /// Sets sharding information of the connection.
+    pub(crate) fn with_shard_info(mut self, shard_info: Option<ShardInfo>) -> Self {
+        self.shard_info = shard_info;
+        self
     }
 }
 
 #[async_trait]
 impl CDRSTransport for TransportTcp {
-    async fn try_clone(&self) -> io::Result<TransportTcp> {
-        TcpStream::connect(self.addr.as_str()).await.map(|socket| TransportTcp {
-            tcp: socket,
-            addr: self.addr.clone(),
-        })
+    #[inline]
+    async fn write_frame(&self, frame: &Frame) -> error::Result<Frame> {
+        self.inner.write_frame(frame).await
     }
 
-    async fn close(&mut self, _close: net::Shutdown) -> io::Result<()> {
-        self.tcp.shutdown().await
+    #[inline]
+    async fn write_frame_with_priority(
+        &self,
+        frame: &Frame,
+        priority: RequestPriority,
+    ) -> error::Result<Frame> {
+        self.inner.write_frame_with_priority(frame, priority).await
     }
 
-    fn is_alive(&self) -> bool {
-        self.tcp.peer_addr().is_ok()
+    #[inline]
+    fn is_broken(&self) -> bool {
+        self.inner.is_broken()
+    }
+
+    #[inline]
+    fn addr(&self) -> SocketAddr {
+        self.inner.addr()
+    }
+
+    #[inline]
+    fn has_capacity(&self) -> bool {
+        self.inner.has_capacity()
+    }
+
+    #[inline]
+    fn shard_info(&self) -> Option<ShardInfo> {
+        self.shard_info
     }
 }
 
 #[cfg(feature = "rust-tls")]
+#[derive(Clone)]
 pub struct TransportRustls {
-    inner: RustlsStream<TcpStream>,
-    config: Arc<rustls::ClientConfig>,
-    addr: net::SocketAddr,
-    dns_name: webpki::DNSName,
+    inner: AsyncTransport,
 }
 
 #[cfg(feature = "rust-tls")]
 impl TransportRustls {
     ///Creates new instance with provided configuration
-    pub async fn new(addr: net::SocketAddr, dns_name: webpki::DNSName, config: Arc<rustls::ClientConfig>) -> io::Result<Self> {
-        let stream = TcpStream::connect(addr).await?;
-        let connector = RustlsConnector::from(config.clone());
-        let stream = connector.connect(dns_name.as_ref(), stream).await?;
-
-        Ok(Self {
-            inner: stream,
-            config,
+    pub async fn new(
+        addr: SocketAddr,
+        dns_name: webpki::DNSName,
+        config: Arc<rustls::ClientConfig>,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        transport_config: &TransportConfig,
+    ) -> io::Result<Self> {
+        Self::connect(
             addr,
             dns_name,
+            config,
+            compression,
+            event_handler,
+            transport_config,
+            Default::default(),
+        )
+        .await
+    }
+
+    /// Creates new instance which counts orphaned requests in given counter shared with other
+    /// connections to the node.
+    pub(crate) async fn connect(
+        addr: SocketAddr,
+        dns_name: webpki::DNSName,
+        config: Arc<rustls::ClientConfig>,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        transport_config: &TransportConfig,
+        orphaned_requests: Arc<AtomicUsize>,
+    ) -> io::Result<Self> {
+        let stream = with_connect_timeout(transport_config, async {
+            let stream = connect_tcp(addr, transport_config).await?;
+            let connector = RustlsConnector::from(config);
+            connector.connect(dns_name.as_ref(), stream).await
+        })
+        .await?;
+
+        Ok(Self {
+            inner: AsyncTransport::new(
+                addr,
+                stream,
+                compression,
+                event_handler,
+                transport_config,
+                orphaned_requests,
+            ),
         })
     }
 }
 
 #[cfg(feature = "rust-tls")]
-impl AsyncRead for TransportRustls {
+#[async_trait]
+impl CDRSTransport for TransportRustls {
     #[inline]
-    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.inner).poll_read(cx, buf)
+    async fn write_frame(&self, frame: &Frame) -> error::Result<Frame> {
+        self.inner.write_frame(frame).await
+    }
+
+    #[inline]
+    async fn write_frame_with_priority(
+        &self,
+        frame: &Frame,
+        priority: RequestPriority,
+    ) -> error::Result<Frame> {
+        self.inner.write_frame_with_priority(frame, priority).await
     }
-}
 
-#[cfg(feature = "rust-tls")]
-impl AsyncWrite for TransportRustls {
     #[inline]
-    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize, Error>> {
-        Pin::new(&mut self.inner).poll_write(cx, buf)
+    fn is_broken(&self) -> bool {
+        self.inner.is_broken()
     }
 
     #[inline]
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        Pin::new(&mut self.inner).poll_flush(cx)
+    fn addr(&self) -> SocketAddr {
+        self.inner.addr()
     }
 
     #[inline]
-    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        Pin::new(&mut self.inner).poll_shutdown(cx)
+    fn has_capacity(&self) -> bool {
+        self.inner.has_capacity()
     }
 }
 
-#[cfg(feature = "rust-tls")]
+/// Transport connecting over a Unix domain socket.
+#[cfg(unix)]
+#[derive(Clone)]
+pub struct TransportUnix {
+    inner: AsyncTransport,
+}
+
+#[cfg(unix)]
+impl TransportUnix {
+    /// Returns the address reported by `addr()` of connections to the socket at given path.
+    /// Unix sockets don't have IP addresses, so each path is mapped to a distinct address in the
+    /// discard-only prefix `100::/64`, which keeps nodes apart in maps keyed by address.
+    pub fn socket_addr<P: AsRef<Path>>(path: P) -> SocketAddr {
+        let mut hasher = FnvHasher::default();
+        path.as_ref().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let ip = Ipv6Addr::new(
+            0x100,
+            0,
+            0,
+            0,
+            (hash >> 48) as u16,
+            (hash >> 32) as u16,
+            (hash >> 16) as u16,
+            hash as u16,
+        );
+        SocketAddr::new(IpAddr::V6(ip), 0)
+    }
+
+    /// Constructs a new `TransportUnix` connected to the socket at given `path`. Socket options
+    /// from `config` don't apply to Unix sockets.
+    pub async fn new<P: AsRef<Path>>(
+        path: P,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        config: &TransportConfig,
+    ) -> io::Result<TransportUnix> {
+        Self::connect(path, compression, event_handler, config, Default::default()).await
+    }
+
+    /// Constructs a new `TransportUnix` which counts orphaned requests in given counter shared
+    /// with other connections to the node.
+    pub(crate) async fn connect<P: AsRef<Path>>(
+        path: P,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        config: &TransportConfig,
+        orphaned_requests: Arc<AtomicUsize>,
+    ) -> io::Result<TransportUnix> {
+        let addr = Self::socket_addr(&path);
+        let socket = with_connect_timeout(config, UnixStream::connect(path)).await?;
+
+        Ok(TransportUnix {
+            inner: AsyncTransport::new(
+                addr,
+                socket,
+                compression,
+                event_handler,
+                config,
+                orphaned_requests,
+            ),
+        })
+    }
+}
+
+#[cfg(unix)]
 #[async_trait]
-impl CDRSTransport for TransportRustls {
+impl CDRSTransport for TransportUnix {
+    #[inline]
+    async fn write_frame(&self, frame: &Frame) -> error::Result<Frame> {
+        self.inner.write_frame(frame).await
+    }
+
+    #[inline]
+    async fn write_frame_with_priority(
+        &self,
+        frame: &Frame,
+        priority: RequestPriority,
+    ) -> error::Result<Frame> {
+        self.inner.write_frame_with_priority(frame, priority).await
+    }
+
+    #[inline]
+    fn is_broken(&self) -> bool {
+        self.inner.is_broken()
+    }
+
+    #[inline]
+    fn addr(&self) -> SocketAddr {
+        self.inner.addr()
+    }
+
+    #[inline]
+    fn has_capacity(&self) -> bool {
+        self.inner.has_capacity()
+    }
+}
+
+type ResponseHandler = oneshot::Sender<error::Result<Frame>>;
+
+type ResponseHandlerMap = Arc<Mutex<FnvHashMap<StreamId, ResponseHandler>>>;
+
+struct Request {
+    frame: Vec<u8>,
+    handler: ResponseHandler,
+}
+
+/// Limit of requests in flight on a connection, or of requests borrowing connections of a pool.
+/// Once all slots are taken, requests wait for one in order of their priority, and in order of
+/// arrival within a priority.
+pub(crate) struct InFlightSlots {
+    state: Mutex<InFlightSlotsState>,
+}
+
+struct InFlightSlotsState {
+    available: usize,
+    waiters: BTreeMap<RequestPriority, VecDeque<oneshot::Sender<()>>>,
+}
+
+/// Slot taken by a request, which is released when dropped.
+pub(crate) struct InFlightSlot {
+    slots: Arc<InFlightSlots>,
+}
+
+/// Request waiting for a slot. A slot granted to a request, which was cancelled before taking
+/// it, is released again.
+struct InFlightSlotWaiter {
+    granted: Option<oneshot::Receiver<()>>,
+    slots: Arc<InFlightSlots>,
+}
+
+impl InFlightSlots {
+    pub(crate) fn new(slots: usize) -> Self {
+        InFlightSlots {
+            state: Mutex::new(InFlightSlotsState {
+                available: slots,
+                waiters: BTreeMap::new(),
+            }),
+        }
+    }
+
+    fn has_available(&self) -> bool {
+        self.state.lock().unwrap().available > 0
+    }
+
+    /// Takes a slot, waiting for one if all are taken.
+    pub(crate) async fn acquire(self: &Arc<Self>, priority: RequestPriority) -> InFlightSlot {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                None
+            } else {
+                let (sender, granted) = oneshot::channel();
+                state.waiters.entry(priority).or_default().push_back(sender);
+                Some(granted)
+            }
+        };
+
+        let mut waiter = InFlightSlotWaiter {
+            granted,
+            slots: self.clone(),
+        };
+        if let Some(ref mut granted) = waiter.granted {
+            // waiters are only dropped after being granted a slot
+            let _ = granted.await;
+            waiter.granted = None;
+        }
+
+        InFlightSlot {
+            slots: self.clone(),
+        }
+    }
+
+    /// Hands a released slot over to the first request of the highest priority, which is still
+    /// waiting, or makes it available.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        for waiters in state.waiters.values_mut().rev() {
+            while let Some(waiter) = waiters.pop_front() {
+                if waiter.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
+
+        state.available += 1;
+    }
+}
+
+impl Drop for InFlightSlot {
+    fn drop(&mut self) {
+        self.slots.release();
+    }
+}
+
+impl Drop for InFlightSlotWaiter {
+    fn drop(&mut self) {
+        if let Some(mut granted) = self.granted.take() {
+            granted.close();
+            if granted.try_recv().is_ok() {
+                self.slots.release();
+            }
+        }
+    }
+}
+
+/// Connection actor shared by concrete transports. It owns a writer task, which assigns stream
+/// ids and writes queued requests, and a reader task, which routes responses back to requests.
+/// Optionally, a heartbeat task checks if an idle connection is still alive. All tasks stop when
+/// all handles to the transport are dropped.
+#[derive(Clone)]
+struct AsyncTransport {
+    addr: SocketAddr,
+    write_sender: mpsc::Sender<Request>,
+    is_broken: Arc<AtomicBool>,
+    request_timeout: Option<Duration>,
+    in_flight_slots: Option<Arc<InFlightSlots>>,
+    keyspace: SessionKeyspace,
+    /// Generation of the session keyspace the connection switched to.
+    keyspace_generation: Arc<AtomicU64>,
+}
+
+impl AsyncTransport {
+    fn new<T: AsyncRead + AsyncWrite + Send + 'static>(
+        addr: SocketAddr,
+        stream: T,
+        compression: Compression,
+        event_handler: Option<EventFrameSender>,
+        config: &TransportConfig,
+        orphaned_requests: Arc<AtomicUsize>,
+    ) -> Self {
+        let (read_half, write_half) = split(stream);
+        let (write_sender, write_receiver) = mpsc::channel(WRITE_BUFFER_SIZE);
+        let is_broken = Arc::new(AtomicBool::new(false));
+        let response_handlers: ResponseHandlerMap = Default::default();
+        let last_activity = Arc::new(Mutex::new(Instant::now()));
+
+        if let Some(heartbeat_interval) = config.heartbeat_interval {
+            tokio::spawn(Self::start_heartbeat(
+                write_sender.downgrade(),
+                heartbeat_interval,
+                config.heartbeat_timeout,
+                last_activity.clone(),
+                is_broken.clone(),
+            ));
+        }
+
+        tokio::spawn(Self::start_writing(
+            write_half,
+            write_receiver,
+            response_handlers.clone(),
+            is_broken.clone(),
+        ));
+
+        tokio::spawn(Self::start_reading(
+            read_half,
+            compression,
+            response_handlers,
+            event_handler,
+            last_activity,
+            is_broken.clone(),
+            orphaned_requests,
+        ));
+
+        AsyncTransport {
+            addr,
+            write_sender,
+            is_broken,
+            request_timeout: config.request_timeout,
+            in_flight_slots: config
+                .max_in_flight_requests
+                .map(|max_in_flight_requests| Arc::new(InFlightSlots::new(max_in_flight_requests))),
+            keyspace: config.keyspace.clone(),
+            keyspace_generation: Default::default(),
+        }
+    }
+
+    async fn write_frame(&self, frame: &Frame) -> error::Result<Frame> {
+        self.write_frame_with_priority(frame, Default::default())
+            .await
+    }
+
+    async fn write_frame_with_priority(
+        &self,
+        frame: &Frame,
+        priority: RequestPriority,
+    ) -> error::Result<Frame> {
+        let is_statement = matches!(
+            frame.opcode,
+            Opcode::Query | Opcode::Prepare | Opcode::Execute | Opcode::Batch
+        );
+        if is_statement {
+            self.use_keyspace(priority).await;
+        }
+
+        let response = self.send_frame(frame, priority).await?;
+
+        if let Some(keyspace) = selected_keyspace(&response) {
+            let generation = self.keyspace.update(keyspace);
+            self.keyspace_generation.store(generation, Ordering::SeqCst);
+        }
+
+        Ok(response)
+    }
+
+    /// Switches the connection to the session keyspace, if it has changed since the last
+    /// switch. Failures are only logged, leaving the connection in its previous keyspace, so
+    /// statements with fully qualified tables still go through, and the switch isn't
+    /// attempted again until the keyspace changes.
+    async fn use_keyspace(&self, priority: RequestPriority) {
+        if self.keyspace_generation.load(Ordering::SeqCst) == self.keyspace.generation() {
+            return;
+        }
+
+        let (generation, keyspace) = self.keyspace.current();
+        if let Some(keyspace) = keyspace {
+            let query = format!("USE \"{}\"", keyspace.replace('"', "\"\""));
+            let use_frame = Frame::new_req_query(
+                query,
+                Consistency::One,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                vec![],
+            );
+            if let Err(error) = self.send_frame(&use_frame, priority).await {
+                warn!(
+                    "Unable to switch connection to {} to keyspace {}: {}",
+                    self.addr, keyspace, error
+                );
+            }
+        }
+
+        self.keyspace_generation.store(generation, Ordering::SeqCst);
+    }
+
+    async fn send_frame(&self, frame: &Frame, priority: RequestPriority) -> error::Result<Frame> {
+        let (handler, response) = oneshot::channel();
+
+        let request = async move {
+            let _slot = match self.in_flight_slots {
+                Some(ref slots) => Some(slots.acquire(priority).await),
+                None => None,
+            };
+
+            self.write_sender
+                .send(Request {
+                    frame: frame.into_cbytes(),
+                    handler,
+                })
+                .await
+                .map_err(|_| error::Error::General("Connection closed".into()))?;
+
+            response
+                .await
+                .map_err(|_| error::Error::General("Connection closed".into()))?
+        };
+
+        match self.request_timeout {
+            Some(request_timeout) => timeout(request_timeout, request).await.map_err(|_| {
+                error::Error::Timeout(format!("Request to {} timed out", self.addr))
+            })?,
+            None => request.await,
+        }
+    }
+
+    #[inline]
+    fn is_broken(&self) -> bool {
+        self.is_broken.load(Ordering::Relaxed)
+    }
+
     #[inline]
-    async fn try_clone(&self) -> io::Result<Self> {
-        Self::new(self.addr, self.dns_name.clone(), self.config.clone()).await
+    fn addr(&self) -> SocketAddr {
+        self.addr
     }
 
-    async fn close(&mut self, _close: net::Shutdown) -> io::Result<()> {
-        self.inner.get_mut().0.shutdown().await
+    #[inline]
+    fn has_capacity(&self) -> bool {
+        self.in_flight_slots
+            .as_ref()
+            .is_none_or(|slots| slots.has_available())
+    }
+
+    async fn start_writing<T: AsyncWrite>(
+        mut write_half: WriteHalf<T>,
+        mut write_receiver: mpsc::Receiver<Request>,
+        response_handlers: ResponseHandlerMap,
+        is_broken: Arc<AtomicBool>,
+    ) {
+        let mut next_stream_id: StreamId = 0;
+        let mut frames = Vec::with_capacity(MAX_COALESCED_FRAMES);
+        let mut stream_ids = Vec::with_capacity(MAX_COALESCED_FRAMES);
+
+        while let Some(request) = write_receiver.recv().await {
+            let mut next_request = Some(request);
+
+            // requests which queued up in the meantime are coalesced into a single write
+            while let Some(mut request) = next_request.take() {
+                // requests cancelled while queued don't need to be sent at all
+                let stream_id = if request.handler.is_closed() {
+                    None
+                } else {
+                    let mut response_handlers = response_handlers.lock().unwrap();
+
+                    // checked under the lock, so the reader can't miss a handler when draining
+                    let stream_id = if is_broken.load(Ordering::Relaxed) {
+                        Err(error::Error::ConnectionBroken("Connection is broken".into()))
+                    } else {
+                        Self::next_free_stream_id(&response_handlers, next_stream_id)
+                            .ok_or_else(|| error::Error::General("No free stream id available".into()))
+                    };
+
+                    match stream_id {
+                        Ok(stream_id) => {
+                            response_handlers.insert(stream_id, request.handler);
+                            Some(stream_id)
+                        }
+                        Err(error) => {
+                            let _ = request.handler.send(Err(error));
+                            None
+                        }
+                    }
+                };
+
+                if let Some(stream_id) = stream_id {
+                    next_stream_id = if stream_id == MAX_STREAM_ID {
+                        0
+                    } else {
+                        stream_id + 1
+                    };
+
+                    request.frame[STREAM_ID_OFFSET..STREAM_ID_OFFSET + 2]
+                        .copy_from_slice(&stream_id.to_be_bytes());
+
+                    frames.push(request.frame);
+                    stream_ids.push(stream_id);
+                }
+
+                if frames.len() < MAX_COALESCED_FRAMES {
+                    next_request = write_receiver.try_recv().ok();
+                }
+            }
+
+            if frames.is_empty() {
+                continue;
+            }
+
+            if let Err(error) = write_all_vectored(&mut write_half, &frames).await {
+                is_broken.store(true, Ordering::Relaxed);
+
+                // frames could have been written partially, so they might have reached the node
+                let message = format!("Failed to write requests: {}", error);
+                let mut response_handlers = response_handlers.lock().unwrap();
+                for stream_id in &stream_ids {
+                    if let Some(handler) = response_handlers.remove(stream_id) {
+                        let error = error::Error::ConnectionBroken(message.clone());
+                        let _ = handler.send(Err(error));
+                    }
+                }
+
+                break;
+            }
+
+            frames.clear();
+            stream_ids.clear();
+        }
+
+        let _ = write_half.shutdown().await;
+    }
+
+    async fn start_reading<T: AsyncRead>(
+        mut read_half: ReadHalf<T>,
+        compression: Compression,
+        response_handlers: ResponseHandlerMap,
+        event_handler: Option<EventFrameSender>,
+        last_activity: Arc<Mutex<Instant>>,
+        is_broken: Arc<AtomicBool>,
+        orphaned_requests: Arc<AtomicUsize>,
+    ) {
+        loop {
+            match parse_raw_frame(&mut read_half, &compression).await {
+                Ok(frame) => {
+                    *last_activity.lock().unwrap() = Instant::now();
+
+                    if frame.stream < 0 {
+                        if let Some(ref event_handler) = event_handler {
+                            let _ = event_handler.send(frame);
+                        }
+
+                        continue;
+                    }
+
+                    // stream ids of orphaned requests (timed out or cancelled after being sent)
+                    // stay reserved until their late responses arrive and are discarded here
+                    let handler = response_handlers.lock().unwrap().remove(&frame.stream);
+                    match handler {
+                        Some(handler) => {
+                            if handler.send(convert_frame_into_result(frame)).is_err() {
+                                orphaned_requests.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                        None => warn!("Unexpected response for stream id {}", frame.stream),
+                    }
+                }
+                Err(error) => {
+                    // the connection is poisoned - it's dropped by the pool and replaced, and
+                    // requests in flight fail with an error which allows retrying them
+                    is_broken.store(true, Ordering::Relaxed);
+
+                    if !matches!(error, error::Error::Io(_)) {
+                        warn!("Closing connection after failing to read a frame: {}", error);
+                    }
+
+                    let message = error.to_string();
+                    for (_, handler) in response_handlers.lock().unwrap().drain() {
+                        if handler
+                            .send(Err(error::Error::ConnectionBroken(message.clone())))
+                            .is_err()
+                        {
+                            orphaned_requests.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn start_heartbeat(
+        write_sender: mpsc::WeakSender<Request>,
+        interval: Duration,
+        heartbeat_timeout: Duration,
+        last_activity: Arc<Mutex<Instant>>,
+        is_broken: Arc<AtomicBool>,
+    ) {
+        loop {
+            let idle_time = last_activity.lock().unwrap().elapsed();
+            if idle_time < interval {
+                sleep(interval - idle_time).await;
+                continue;
+            }
+
+            if is_broken.load(Ordering::Relaxed) {
+                break;
+            }
+
+            // don't keep the connection alive when all handles are gone
+            let write_sender = match write_sender.upgrade() {
+                Some(write_sender) => write_sender,
+                None => break,
+            };
+
+            let (handler, response) = oneshot::channel();
+            let request = Request {
+                frame: Frame::new_req_options().into_cbytes(),
+                handler,
+            };
+
+            let result = timeout(heartbeat_timeout, async move {
+                write_sender.send(request).await.ok()?;
+                response.await.ok()?.ok()
+            })
+            .await;
+
+            match result {
+                Ok(Some(frame)) if frame.opcode == Opcode::Supported => {
+                    *last_activity.lock().unwrap() = Instant::now();
+                }
+                _ => {
+                    warn!("Heartbeat failed - marking connection as broken");
+                    is_broken.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn next_free_stream_id(
+        response_handlers: &FnvHashMap<StreamId, ResponseHandler>,
+        start: StreamId,
+    ) -> Option<StreamId> {
+        (start..=MAX_STREAM_ID)
+            .chain(0..start)
+            .find(|stream_id| !response_handlers.contains_key(stream_id))
+    }
+}
+
+/// Writes all buffers, using vectored writes to avoid a syscall per buffer.
+async fn write_all_vectored<T: AsyncWrite>(
+    write_half: &mut WriteHalf<T>,
+    buffers: &[Vec<u8>],
+) -> io::Result<()> {
+    let mut slices: Vec<IoSlice> = buffers.iter().map(|buffer| IoSlice::new(buffer)).collect();
+    let mut slices = &mut slices[..];
+
+    while !slices.is_empty() {
+        let written = write_half.write_vectored(slices).await?;
+        if written == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+
+        IoSlice::advance_slices(&mut slices, written);
+    }
+
+    Ok(())
+}
+
+/// Limits the time of establishing a connection to the configured connect timeout.
+async fn with_connect_timeout<T>(
+    config: &TransportConfig,
+    connect: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    timeout(config.connect_timeout, connect)
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "Connect timed out"))?
+}
+
+/// Opens a TCP connection with socket options from given `config`, tunneling it through
+/// the configured proxy, if any.
+async fn connect_tcp(addr: SocketAddr, config: &TransportConfig) -> io::Result<TcpStream> {
+    let connect_addr = config.proxy.as_ref().map_or(addr, |proxy| proxy.addr);
+    let socket = if connect_addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+
+    socket.set_nodelay(config.tcp_nodelay)?;
+    socket.set_keepalive(config.tcp_keepalive)?;
+
+    if let Some(size) = config.send_buffer_size {
+        socket.set_send_buffer_size(size)?;
+    }
+
+    if let Some(size) = config.recv_buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+
+    if let Some(local_addr) = config.local_addr {
+        socket.bind(local_addr)?;
+    }
+
+    let mut stream = socket.connect(connect_addr).await?;
+
+    if let Some(ref proxy) = config.proxy {
+        open_tunnel(&mut stream, proxy, addr).await?;
+    }
+
+    Ok(stream)
+}
+
+async fn resolve_addrs(addr: &str) -> io::Result<Vec<SocketAddr>> {
+    let addrs: Vec<SocketAddr> = lookup_host(addr).await?.collect();
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot resolve address {}", addr),
+        ));
+    }
+
+    Ok(addrs)
+}
+
+/// Connects to the first reachable address. With Happy Eyeballs enabled, attempts are started
+/// one after another with the configured delay, alternating between address families, or
+/// immediately when the previous attempt fails. The first established connection wins and
+/// other attempts are cancelled.
+async fn connect_tcp_any(
+    addrs: Vec<SocketAddr>,
+    config: &TransportConfig,
+) -> io::Result<(SocketAddr, TcpStream)> {
+    let delay = match config.happy_eyeballs_delay {
+        // all connections go to the proxy, so there's nothing to race
+        Some(delay) if addrs.len() > 1 && config.proxy.is_none() => delay,
+        _ => {
+            let addr = addrs[0];
+            return connect_tcp(addr, config).await.map(|stream| (addr, stream));
+        }
+    };
+
+    let mut addrs = interleave_families(addrs).into_iter().peekable();
+    let mut attempts = JoinSet::new();
+    let mut last_error = None;
+
+    loop {
+        if let Some(addr) = addrs.next() {
+            let config = config.clone();
+            attempts.spawn(async move { (addr, connect_tcp(addr, &config).await) });
+        }
+
+        // wait for an attempt to finish or for the delay to start the next one
+        tokio::select! {
+            Some(result) = attempts.join_next() => match result {
+                Ok((addr, Ok(stream))) => return Ok((addr, stream)),
+                Ok((addr, Err(err))) => {
+                    debug!("Connection attempt to {} failed: {}", addr, err);
+                    last_error = Some(err);
+                }
+                Err(err) => last_error = Some(io::Error::other(err)),
+            },
+            _ = sleep(delay), if addrs.peek().is_some() => {}
+            else => {
+                return Err(last_error.unwrap_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotConnected, "No address to connect to")
+                }))
+            }
+        }
+    }
+}
+
+/// Orders addresses alternating between address families, starting with the family of the
+/// first address.
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_is_ipv6 = addrs.first().is_some_and(|addr| addr.is_ipv6());
+    let (mut preferred, mut other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+
+    let mut result = Vec::with_capacity(preferred.len() + other.len());
+    preferred.reverse();
+    other.reverse();
+    while !preferred.is_empty() || !other.is_empty() {
+        result.extend(preferred.pop());
+        result.extend(other.pop());
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{AsByte, Version};
+    use std::collections::HashSet;
+    use tokio::io::{AsyncReadExt, DuplexStream};
+    use tokio::net::TcpListener;
+
+    /// Creates a transport over an in-memory stream, returning the node end of the stream.
+    fn duplex_transport(config: &TransportConfig) -> (AsyncTransport, DuplexStream) {
+        let (client, server) = tokio::io::duplex(1024);
+        let transport = AsyncTransport::new(
+            "127.0.0.1:9042".parse().unwrap(),
+            client,
+            Compression::None,
+            None,
+            config,
+            Default::default(),
+        );
+
+        (transport, server)
+    }
+
+    /// Reads a request, returning its stream id, opcode and body.
+    async fn read_request(server: &mut DuplexStream) -> io::Result<(StreamId, u8, Vec<u8>)> {
+        let mut header = [0u8; 9];
+        server.read_exact(&mut header).await?;
+
+        let length = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+        let mut body = vec![0u8; length as usize];
+        server.read_exact(&mut body).await?;
+
+        let stream_id = StreamId::from_be_bytes([header[2], header[3]]);
+        Ok((stream_id, header[4], body))
+    }
+
+    /// Writes a response to the request with given stream id.
+    async fn respond(server: &mut DuplexStream, stream_id: StreamId, opcode: Opcode, body: &[u8]) {
+        let mut response = vec![Version::Response.as_byte(), 0];
+        response.extend_from_slice(&stream_id.to_be_bytes());
+        response.push(opcode.as_byte());
+        response.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        response.extend_from_slice(body);
+        server.write_all(&response).await.unwrap();
+    }
+
+    fn query(query: &str) -> Frame {
+        Frame::new_req_query(
+            query.to_string(),
+            Consistency::One,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn maps_socket_paths_to_distinct_addresses() {
+        let addr = TransportUnix::socket_addr("/var/run/a.sock");
+        assert_eq!(addr, TransportUnix::socket_addr("/var/run/a.sock"));
+        assert_ne!(addr, TransportUnix::socket_addr("/var/run/b.sock"));
+        assert!(matches!(addr.ip(), IpAddr::V6(ip) if ip.segments()[..4] == [0x100, 0, 0, 0]));
+    }
+
+    #[test]
+    fn interleaves_address_families() {
+        let addrs: Vec<SocketAddr> = vec![
+            "[::1]:9042".parse().unwrap(),
+            "[::2]:9042".parse().unwrap(),
+            "[::3]:9042".parse().unwrap(),
+            "127.0.0.1:9042".parse().unwrap(),
+        ];
+
+        let expected: Vec<SocketAddr> = vec![
+            "[::1]:9042".parse().unwrap(),
+            "127.0.0.1:9042".parse().unwrap(),
+            "[::2]:9042".parse().unwrap(),
+            "[::3]:9042".parse().unwrap(),
+        ];
+
+        assert_eq!(interleave_families(addrs), expected);
+    }
+
+    #[tokio::test]
+    async fn writes_all_buffers_vectored() {
+        let (client, mut server) = tokio::io::duplex(4);
+        let (_, mut write_half) = split(client);
+        let buffers = vec![b"abc".to_vec(), b"defgh".to_vec(), b"ij".to_vec()];
+
+        let reader = tokio::spawn(async move {
+            let mut received = vec![0u8; 10];
+            tokio::io::AsyncReadExt::read_exact(&mut server, &mut received)
+                .await
+                .unwrap();
+            received
+        });
+
+        write_all_vectored(&mut write_half, &buffers).await.unwrap();
+        assert_eq!(reader.await.unwrap(), b"abcdefghij");
+    }
+
+    #[tokio::test]
+    async fn connects_to_first_reachable_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reachable = listener.local_addr().unwrap();
+
+        // nothing listens on port 1, so the first attempt fails
+        let addrs = vec!["127.0.0.1:1".parse().unwrap(), reachable];
+        let config = TransportConfig {
+            happy_eyeballs_delay: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+
+        let (addr, _) = connect_tcp_any(addrs, &config).await.unwrap();
+        assert_eq!(addr, reachable);
+    }
+
+    #[tokio::test]
+    async fn discards_late_responses_of_orphaned_requests() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let orphaned_requests = Arc::new(AtomicUsize::new(0));
+        let config = TransportConfig {
+            request_timeout: Some(Duration::from_millis(50)),
+            heartbeat_interval: None,
+            ..Default::default()
+        };
+        let transport = AsyncTransport::new(
+            "127.0.0.1:9042".parse().unwrap(),
+            client,
+            Compression::None,
+            None,
+            &config,
+            orphaned_requests.clone(),
+        );
+
+        let result = transport.write_frame(&Frame::new_req_options()).await;
+        assert!(matches!(result, Err(error::Error::Timeout(_))));
+
+        // respond to the timed out request with READY, using its stream id
+        let mut header = [0u8; 9];
+        tokio::io::AsyncReadExt::read_exact(&mut server, &mut header)
+            .await
+            .unwrap();
+        let response = [
+            Version::Response.as_byte(),
+            0,
+            header[2],
+            header[3],
+            Opcode::Ready.as_byte(),
+            0,
+            0,
+            0,
+            0,
+        ];
+        server.write_all(&response).await.unwrap();
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(orphaned_requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn fails_requests_in_flight_on_protocol_desync() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let config = TransportConfig {
+            heartbeat_interval: None,
+            ..Default::default()
+        };
+        let transport = AsyncTransport::new(
+            "127.0.0.1:9042".parse().unwrap(),
+            client,
+            Compression::None,
+            None,
+            &config,
+            Default::default(),
+        );
+
+        let server = tokio::spawn(async move {
+            let mut header = [0u8; 9];
+            tokio::io::AsyncReadExt::read_exact(&mut server, &mut header)
+                .await
+                .unwrap();
+
+            // unknown opcode
+            let response = [
+                Version::Response.as_byte(),
+                0,
+                header[2],
+                header[3],
+                0x42,
+                0,
+                0,
+                0,
+                0,
+            ];
+            server.write_all(&response).await.unwrap();
+            server
+        });
+
+        let result = transport.write_frame(&Frame::new_req_options()).await;
+        assert!(matches!(result, Err(error::Error::ConnectionBroken(_))));
+        assert!(transport.is_broken());
+
+        let result = transport.write_frame(&Frame::new_req_options()).await;
+        assert!(matches!(result, Err(error::Error::ConnectionBroken(_))));
+
+        drop(server.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn switches_connections_to_session_keyspace() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let keyspace = SessionKeyspace::new();
+        let config = TransportConfig {
+            heartbeat_interval: None,
+            keyspace: keyspace.clone(),
+            ..Default::default()
+        };
+        let transport = AsyncTransport::new(
+            "127.0.0.1:9042".parse().unwrap(),
+            client,
+            Compression::None,
+            None,
+            &config,
+            Default::default(),
+        );
+
+        // responds to `USE` statements with the selected keyspace and to others with void
+        let server = tokio::spawn(async move {
+            let mut queries = vec![];
+            let mut header = [0u8; 9];
+            while tokio::io::AsyncReadExt::read_exact(&mut server, &mut header)
+                .await
+                .is_ok()
+            {
+                let length = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+                let mut body = vec![0u8; length as usize];
+                tokio::io::AsyncReadExt::read_exact(&mut server, &mut body)
+                    .await
+                    .unwrap();
+
+                let query_length = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                let query = String::from_utf8(body[4..4 + query_length as usize].to_vec()).unwrap();
+
+                let mut result = vec![0, 0, 0, 1];
+                if let Some(keyspace) = query.strip_prefix("USE ") {
+                    let keyspace = keyspace.trim_matches('"');
+                    result = vec![0, 0, 0, 3, 0, keyspace.len() as u8];
+                    result.extend_from_slice(keyspace.as_bytes());
+                }
+                queries.push(query);
+
+                let mut response = vec![
+                    Version::Response.as_byte(),
+                    0,
+                    header[2],
+                    header[3],
+                    Opcode::Result.as_byte(),
+                ];
+                response.extend_from_slice(&(result.len() as u32).to_be_bytes());
+                response.extend_from_slice(&result);
+                server.write_all(&response).await.unwrap();
+            }
+            queries
+        });
+
+        let query = |query: &str| {
+            Frame::new_req_query(
+                query.to_string(),
+                Consistency::One,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                vec![],
+            )
+        };
+
+        keyspace.set("ks".to_string());
+        transport.write_frame(&query("SELECT 1")).await.unwrap();
+        transport.write_frame(&query("SELECT 2")).await.unwrap();
+
+        // `USE` sent by the application changes the keyspace of the whole session
+        transport.write_frame(&query("USE other")).await.unwrap();
+        assert_eq!(keyspace.get().as_deref(), Some("other"));
+        transport.write_frame(&query("SELECT 3")).await.unwrap();
+
+        drop(transport);
+        assert_eq!(
+            server.await.unwrap(),
+            vec![
+                "USE \"ks\"",
+                "SELECT 1",
+                "SELECT 2",
+                "USE other",
+                "SELECT 3"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn grants_in_flight_slots_by_priority() {
+        let slots = Arc::new(InFlightSlots::new(1));
+        let slot = slots.acquire(RequestPriority::Normal).await;
+        assert!(!slots.has_available());
+
+        let granted = Arc::new(Mutex::new(vec![]));
+        let mut requests = vec![];
+        for (name, priority) in [
+            ("background", RequestPriority::Background),
+            ("normal 1", RequestPriority::Normal),
+            ("cancelled", RequestPriority::Interactive),
+            ("interactive", RequestPriority::Interactive),
+            ("normal 2", RequestPriority::Normal),
+        ] {
+            let slots = slots.clone();
+            let granted = granted.clone();
+            requests.push(tokio::spawn(async move {
+                let _slot = slots.acquire(priority).await;
+                granted.lock().unwrap().push(name);
+            }));
+
+            // lets the request start waiting
+            tokio::task::yield_now().await;
+        }
+
+        requests.remove(2).abort();
+        drop(slot);
+        for request in requests {
+            request.await.unwrap();
+        }
+
+        assert_eq!(
+            *granted.lock().unwrap(),
+            vec!["interactive", "normal 1", "normal 2", "background"]
+        );
+        assert!(slots.has_available());
     }
 
-    fn is_alive(&self) -> bool {
-        self.inner.get_ref().0.peer_addr().is_ok()
+    #[tokio::test]
+    async fn routes_responses_by_stream_id() {
+        let (transport, mut server) = duplex_transport(&Default::default());
+
+        // answers requests once all of them arrive, in reverse order, echoing their bodies
+        let server = tokio::spawn(async move {
+            let mut requests = vec![];
+            for _ in 0..3 {
+                requests.push(read_request(&mut server).await.unwrap());
+            }
+
+            for (stream_id, _, body) in requests.iter().rev() {
+                respond(&mut server, *stream_id, Opcode::Ready, body).await;
+            }
+
+            requests
+        });
+
+        let frames = [query("SELECT 1"), query("SELECT 2"), query("SELECT 3")];
+        let (first, second, third) = tokio::join!(
+            transport.write_frame(&frames[0]),
+            transport.write_frame(&frames[1]),
+            transport.write_frame(&frames[2])
+        );
+        assert_eq!(first.unwrap().body, frames[0].body);
+        assert_eq!(second.unwrap().body, frames[1].body);
+        assert_eq!(third.unwrap().body, frames[2].body);
+
+        let stream_ids: HashSet<StreamId> = server
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(stream_id, _, _)| stream_id)
+            .collect();
+        assert_eq!(stream_ids.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn reuses_released_stream_ids() {
+        let (transport, mut server) = duplex_transport(&Default::default());
+
+        // the first request stays in flight, others are answered right away
+        let server = tokio::spawn(async move {
+            let mut stream_ids = vec![];
+            while let Ok((stream_id, _, _)) = read_request(&mut server).await {
+                if !stream_ids.is_empty() {
+                    respond(&mut server, stream_id, Opcode::Supported, &[0, 0]).await;
+                }
+                stream_ids.push(stream_id);
+            }
+            stream_ids
+        });
+
+        let in_flight = transport.clone();
+        let in_flight =
+            tokio::spawn(async move { in_flight.write_frame(&Frame::new_req_options()).await });
+        // lets the request get written
+        tokio::task::yield_now().await;
+
+        // stream ids wrap around, skipping the one still in use
+        for _ in 0..=MAX_STREAM_ID {
+            transport
+                .write_frame(&Frame::new_req_options())
+                .await
+                .unwrap();
+        }
+
+        in_flight.abort();
+        drop(transport);
+
+        let stream_ids = server.await.unwrap();
+        assert_eq!(stream_ids[..2], [0, 1]);
+        assert_eq!(stream_ids[stream_ids.len() - 2..], [MAX_STREAM_ID, 1]);
+    }
+
+    #[tokio::test]
+    async fn fails_requests_in_flight_when_connection_closes() {
+        let (transport, mut server) = duplex_transport(&Default::default());
+
+        // closes the connection without responding
+        let server = tokio::spawn(async move {
+            read_request(&mut server).await.unwrap();
+            read_request(&mut server).await.unwrap();
+        });
+
+        let options = Frame::new_req_options();
+        let (first, second) = tokio::join!(
+            transport.write_frame(&options),
+            transport.write_frame(&options)
+        );
+        assert!(matches!(first, Err(error::Error::ConnectionBroken(_))));
+        assert!(matches!(second, Err(error::Error::ConnectionBroken(_))));
+        assert!(transport.is_broken());
+        server.await.unwrap();
+
+        let result = transport.write_frame(&options).await;
+        assert!(matches!(result, Err(error::Error::ConnectionBroken(_))));
+    }
+
+    fn heartbeat_config() -> TransportConfig {
+        TransportConfig {
+            heartbeat_interval: Some(Duration::from_secs(30)),
+            heartbeat_timeout: Duration::from_secs(5),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_heartbeats_on_idle_connections() {
+        tokio::time::pause();
+        let (transport, mut server) = duplex_transport(&heartbeat_config());
+        let start = Instant::now();
+
+        // a heartbeat is sent after each interval of inactivity
+        for interval in 1..=2 {
+            let (stream_id, opcode, _) = read_request(&mut server).await.unwrap();
+            assert_eq!(opcode, Opcode::Options.as_byte());
+
+            let elapsed = start.elapsed().as_secs();
+            assert_eq!(elapsed, interval * 30);
+            respond(&mut server, stream_id, Opcode::Supported, &[0, 0]).await;
+        }
+
+        assert!(!transport.is_broken());
+    }
+
+    #[tokio::test]
+    async fn marks_connection_broken_on_missed_heartbeat() {
+        tokio::time::pause();
+        let (transport, mut server) = duplex_transport(&heartbeat_config());
+
+        let (_, opcode, _) = read_request(&mut server).await.unwrap();
+        assert_eq!(opcode, Opcode::Options.as_byte());
+        assert!(!transport.is_broken());
+
+        // the node doesn't respond within the heartbeat timeout
+        sleep(Duration::from_secs(6)).await;
+        assert!(transport.is_broken());
+    }
+
+    #[tokio::test]
+    async fn frees_slots_and_stream_ids_of_timed_out_requests() {
+        tokio::time::pause();
+        let config = TransportConfig {
+            request_timeout: Some(Duration::from_secs(1)),
+            max_in_flight_requests: Some(1),
+            ..Default::default()
+        };
+        let (transport, mut server) = duplex_transport(&config);
+        let options = Frame::new_req_options();
+
+        let result = transport.write_frame(&options).await;
+        assert!(matches!(result, Err(error::Error::Timeout(_))));
+        assert!(transport.has_capacity());
+
+        // requests cancelled by a deadline free their slots too
+        let result = timeout(Duration::from_millis(500), transport.write_frame(&options)).await;
+        assert!(result.is_err());
+        assert!(transport.has_capacity());
+
+        let (first, _, _) = read_request(&mut server).await.unwrap();
+        let (second, _, _) = read_request(&mut server).await.unwrap();
+        assert_eq!((first, second), (0, 1));
+
+        // stream ids stay reserved until late responses arrive, so they can't be mistaken for
+        // responses to other requests
+        respond(&mut server, first, Opcode::Supported, &[0, 0]).await;
+
+        let server = tokio::spawn(async move {
+            let mut stream_ids = vec![];
+            while let Ok((stream_id, _, _)) = read_request(&mut server).await {
+                respond(&mut server, stream_id, Opcode::Supported, &[0, 0]).await;
+                stream_ids.push(stream_id);
+            }
+            stream_ids
+        });
+
+        for _ in 0..MAX_STREAM_ID {
+            transport.write_frame(&options).await.unwrap();
+        }
+        drop(transport);
+
+        // the stream id of the first request is reused, while the second one is still reserved
+        let stream_ids = server.await.unwrap();
+        assert_eq!(stream_ids[stream_ids.len() - 2..], [MAX_STREAM_ID, 0]);
+    }
+
+    #[tokio::test]
+    async fn waits_for_free_in_flight_slots() {
+        tokio::time::pause();
+        let config = TransportConfig {
+            request_timeout: None,
+            max_in_flight_requests: Some(2),
+            ..Default::default()
+        };
+        let (transport, mut server) = duplex_transport(&config);
+
+        let requests: Vec<_> = (0..3)
+            .map(|_| {
+                let transport = transport.clone();
+                tokio::spawn(async move { transport.write_frame(&Frame::new_req_options()).await })
+            })
+            .collect();
+
+        let (first, _, _) = read_request(&mut server).await.unwrap();
+        let (second, _, _) = read_request(&mut server).await.unwrap();
+        assert!(!transport.has_capacity());
+
+        // the third request isn't sent until a response frees a slot
+        let third = timeout(Duration::from_secs(60), read_request(&mut server)).await;
+        assert!(third.is_err());
+
+        respond(&mut server, first, Opcode::Supported, &[0, 0]).await;
+        let (third, _, _) = read_request(&mut server).await.unwrap();
+        respond(&mut server, second, Opcode::Supported, &[0, 0]).await;
+        respond(&mut server, third, Opcode::Supported, &[0, 0]).await;
+
+        for request in requests {
+            assert!(request.await.unwrap().is_ok());
+        }
+        assert!(transport.has_capacity());
+    }
+
+    #[tokio::test]
+    async fn sends_waiting_requests_by_priority() {
+        let config = TransportConfig {
+            max_in_flight_requests: Some(1),
+            ..Default::default()
+        };
+        let (transport, mut server) = duplex_transport(&config);
+
+        let send = |text: &'static str, priority| {
+            let transport = transport.clone();
+            tokio::spawn(async move {
+                transport
+                    .write_frame_with_priority(&query(text), priority)
+                    .await
+            })
+        };
+
+        let first = send("first", RequestPriority::Normal);
+        let (stream_id, _, _) = read_request(&mut server).await.unwrap();
+
+        let mut waiting = vec![];
+        for (text, priority) in [
+            ("background", RequestPriority::Background),
+            ("normal", RequestPriority::Normal),
+            ("interactive", RequestPriority::Interactive),
+        ] {
+            waiting.push(send(text, priority));
+            // lets the request start waiting
+            tokio::task::yield_now().await;
+        }
+
+        let mut sent = vec![];
+        let mut stream_id = stream_id;
+        for _ in 0..waiting.len() {
+            respond(&mut server, stream_id, Opcode::Supported, &[0, 0]).await;
+            let (next, _, body) = read_request(&mut server).await.unwrap();
+            sent.push(body);
+            stream_id = next;
+        }
+        respond(&mut server, stream_id, Opcode::Supported, &[0, 0]).await;
+
+        assert_eq!(
+            sent,
+            vec![
+                query("interactive").body,
+                query("normal").body,
+                query("background").body
+            ]
+        );
+        assert!(first.await.unwrap().is_ok());
+        for request in waiting {
+            assert!(request.await.unwrap().is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn releases_slots_granted_to_cancelled_requests() {
+        let slots = Arc::new(InFlightSlots::new(1));
+        let slot = slots.acquire(RequestPriority::Normal).await;
+
+        let waiting = slots.clone();
+        let request = tokio::spawn(async move {
+            let _slot = waiting.acquire(RequestPriority::Normal).await;
+        });
+        // lets the request start waiting
+        tokio::task::yield_now().await;
+
+        // the slot is handed over to the request, which is cancelled before taking it
+        drop(slot);
+        request.abort();
+        assert!(request.await.unwrap_err().is_cancelled());
+
+        assert!(slots.has_available());
+        drop(slots.acquire(RequestPriority::Normal).await);
     }
 }
@@ -0,0 +1,115 @@
+//! Conversions between CQL `decimal` and `varint` values and `BigDecimal` and `BigInt`, enabled
+//! with the `bigdecimal` feature. Unlike `Decimal` and `i64`, they hold values of any size.
+
+use std::convert::TryFrom;
+
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+
+use crate::error::{invalid_conversion_err, Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption};
+use crate::types::value::Bytes;
+use crate::types::{to_int, try_i32_from_bytes, CBytes};
+
+pub(crate) fn as_big_decimal(
+    col_type: &ColTypeOption,
+    bytes: &CBytes,
+) -> Result<Option<BigDecimal>> {
+    if !matches!(col_type.id, ColType::Decimal) {
+        return Err(invalid_conversion_err(col_type, "BigDecimal", "Decimal"));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) if bytes.len() < 4 => Err(Error::General("Truncated decimal".to_string())),
+        Some(bytes) => {
+            let scale = try_i32_from_bytes(&bytes[..4])?;
+            let unscaled = BigInt::from_signed_bytes_be(&bytes[4..]);
+            Ok(Some(BigDecimal::new(unscaled, scale as i64)))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_big_int(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<BigInt>> {
+    if !matches!(col_type.id, ColType::Varint) {
+        return Err(invalid_conversion_err(col_type, "BigInt", "Varint"));
+    }
+
+    Ok(bytes.non_empty_slice()?.map(BigInt::from_signed_bytes_be))
+}
+
+/// Fails if the scale of the value doesn't fit in `i32`, as Cassandra's scales don't.
+impl TryFrom<BigDecimal> for Bytes {
+    type Error = Error;
+
+    fn try_from(value: BigDecimal) -> Result<Bytes> {
+        let (unscaled, scale) = value.as_bigint_and_exponent();
+        let scale = i32::try_from(scale)
+            .map_err(|_| Error::General(format!("Scale {} of decimal is out of range", scale)))?;
+
+        let mut bytes = to_int(scale);
+        bytes.extend(unscaled.to_signed_bytes_be());
+        Ok(Bytes::new(bytes))
+    }
+}
+
+impl From<BigInt> for Bytes {
+    fn from(value: BigInt) -> Bytes {
+        Bytes::new(value.to_signed_bytes_be())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::test_utils::{col_type, encode, try_encode};
+
+    #[test]
+    fn converts_decimals() {
+        let decimal = BigDecimal::from_str("-12.9").unwrap();
+        assert_eq!(
+            try_encode(decimal.clone()).as_slice().unwrap(),
+            &[0, 0, 0, 1, 0xFF, 0x7F]
+        );
+
+        for decimal in &[
+            decimal.clone(),
+            BigDecimal::from_str("0").unwrap(),
+            BigDecimal::from_str("123456789012345678901234567890.0123456789").unwrap(),
+            BigDecimal::from_str("1e40").unwrap(),
+        ] {
+            assert_eq!(
+                as_big_decimal(&col_type(ColType::Decimal), &try_encode(decimal.clone())).unwrap(),
+                Some(decimal.clone())
+            );
+        }
+
+        assert!(as_big_decimal(&col_type(ColType::Decimal), &CBytes::new(vec![0, 0])).is_err());
+        assert!(as_big_decimal(&col_type(ColType::Double), &try_encode(decimal)).is_err());
+        assert!(
+            Bytes::try_from(BigDecimal::new(BigInt::from(1), i64::from(i32::MAX) + 1)).is_err()
+        );
+    }
+
+    #[test]
+    fn converts_varints() {
+        assert_eq!(encode(BigInt::from(128)).as_slice().unwrap(), &[0x00, 0x80]);
+        assert_eq!(
+            encode(BigInt::from(-129)).as_slice().unwrap(),
+            &[0xFF, 0x7F]
+        );
+
+        let big = BigInt::from_str("-123456789012345678901234567890").unwrap();
+        assert_eq!(
+            as_big_int(&col_type(ColType::Varint), &encode(big.clone())).unwrap(),
+            Some(big)
+        );
+        assert_eq!(
+            as_big_int(&col_type(ColType::Varint), &CBytes::new_empty()).unwrap(),
+            None
+        );
+        assert!(as_big_int(&col_type(ColType::Bigint), &encode(BigInt::from(1))).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Conversions between CQL `timestamp`, `date`, `time` and `duration` values and `chrono` types,
+//! enabled with the `chrono` feature.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use std::convert::TryFrom;
+
+use crate::error::{invalid_conversion_err, Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption, ColTypeOptionValue};
+use crate::types::data_serialization_types::*;
+use crate::types::value::Bytes;
+use crate::types::{to_bigint, to_duration, to_u, CBytes};
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+fn unix_epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+pub(crate) fn as_date_time(
+    col_type: &ColTypeOption,
+    bytes: &CBytes,
+) -> Result<Option<DateTime<Utc>>> {
+    if !matches!(col_type.id, ColType::Timestamp) {
+        return Err(invalid_conversion_err(
+            col_type,
+            "DateTime<Utc>",
+            "Timestamp",
+        ));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => {
+            let millis = decode_timestamp(bytes)?;
+            Utc.timestamp_millis_opt(millis)
+                .single()
+                .map(Some)
+                .ok_or_else(|| Error::General(format!("Timestamp {} is out of range", millis)))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_naive_date(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<NaiveDate>> {
+    if !matches!(col_type.id, ColType::Date) {
+        return Err(invalid_conversion_err(col_type, "NaiveDate", "Date"));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => {
+            let days = decode_date(bytes)? as u32 as i64 - EPOCH_DAY;
+            unix_epoch()
+                .checked_add_signed(Duration::days(days))
+                .map(Some)
+                .ok_or_else(|| Error::General(format!("Date {} is out of range", days)))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_naive_time(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<NaiveTime>> {
+    if !matches!(col_type.id, ColType::Time) {
+        return Err(invalid_conversion_err(col_type, "NaiveTime", "Time"));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => {
+            let nanos = decode_time(bytes)?;
+            if !(0..NANOS_PER_DAY).contains(&nanos) {
+                return Err(Error::General(format!("Time {} is out of range", nanos)));
+            }
+
+            Ok(NaiveTime::from_num_seconds_from_midnight_opt(
+                (nanos / NANOS_PER_SECOND) as u32,
+                (nanos % NANOS_PER_SECOND) as u32,
+            ))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_duration(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Duration>> {
+    match (&col_type.id, &col_type.value) {
+        (ColType::Custom, Some(ColTypeOptionValue::CString(class)))
+            if class.as_str() == DURATION_TYPE => {}
+        _ => return Err(invalid_conversion_err(col_type, "Duration", "Duration")),
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => match decode_duration(bytes)? {
+            (0, days, nanos) => Ok(Some(
+                Duration::days(days as i64) + Duration::nanoseconds(nanos),
+            )),
+            // months have different numbers of days
+            (months, _, _) => Err(Error::General(format!(
+                "Duration of {} months cannot be converted into Duration",
+                months
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+impl From<DateTime<Utc>> for Bytes {
+    fn from(value: DateTime<Utc>) -> Bytes {
+        Bytes::new(to_bigint(value.timestamp_millis()))
+    }
+}
+
+impl TryFrom<NaiveDate> for Bytes {
+    type Error = Error;
+
+    fn try_from(value: NaiveDate) -> Result<Bytes> {
+        let days = value.signed_duration_since(unix_epoch()).num_days();
+        u32::try_from(days + EPOCH_DAY)
+            .map(|days| Bytes::new(to_u(days)))
+            .map_err(|_| Error::General(format!("Date {} is out of range", value)))
+    }
+}
+
+impl From<NaiveTime> for Bytes {
+    fn from(value: NaiveTime) -> Bytes {
+        let nanos =
+            value.num_seconds_from_midnight() as i64 * NANOS_PER_SECOND + value.nanosecond() as i64;
+        Bytes::new(to_bigint(nanos))
+    }
+}
+
+/// Durations are encoded as days and nanoseconds, without months.
+impl TryFrom<Duration> for Bytes {
+    type Error = Error;
+
+    fn try_from(value: Duration) -> Result<Bytes> {
+        let days = value.num_days();
+        let nanos = (value - Duration::days(days)).num_nanoseconds().unwrap();
+        i32::try_from(days)
+            .map(|days| Bytes::new(to_duration(0, days, nanos)))
+            .map_err(|_| Error::General(format!("Duration of {} days is out of range", days)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{col_type, encode, try_encode};
+    use crate::types::CString;
+
+    #[test]
+    fn converts_timestamps() {
+        let timestamp = Utc.timestamp_millis_opt(-1_500).unwrap();
+        let bytes = encode(timestamp);
+
+        assert_eq!(bytes.as_slice().unwrap(), &to_bigint(-1_500)[..]);
+        assert_eq!(
+            as_date_time(&col_type(ColType::Timestamp), &bytes).unwrap(),
+            Some(timestamp)
+        );
+        assert!(as_date_time(&col_type(ColType::Bigint), &bytes).is_err());
+    }
+
+    #[test]
+    fn converts_dates_from_epoch_days() {
+        let epoch = try_encode(unix_epoch());
+        assert_eq!(epoch.as_slice().unwrap(), &[0x80, 0, 0, 0]);
+
+        for date in &[
+            NaiveDate::from_ymd_opt(1969, 12, 31).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 9, 20).unwrap(),
+        ] {
+            assert_eq!(
+                as_naive_date(&col_type(ColType::Date), &try_encode(*date)).unwrap(),
+                Some(*date)
+            );
+        }
+        assert_eq!(
+            try_encode(NaiveDate::from_ymd_opt(1969, 12, 31).unwrap())
+                .as_slice()
+                .unwrap(),
+            &[0x7F, 0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn converts_times() {
+        let time = NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).unwrap();
+
+        assert_eq!(
+            as_naive_time(&col_type(ColType::Time), &encode(time)).unwrap(),
+            Some(time)
+        );
+        assert!(as_naive_time(
+            &col_type(ColType::Time),
+            &CBytes::new(to_bigint(NANOS_PER_DAY))
+        )
+        .is_err());
+        assert_eq!(
+            as_naive_time(&col_type(ColType::Time), &CBytes::new_empty()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn converts_durations() {
+        let duration_type = ColTypeOption {
+            id: ColType::Custom,
+            value: Some(ColTypeOptionValue::CString(CString::new(
+                DURATION_TYPE.to_string(),
+            ))),
+        };
+        let duration = -(Duration::days(3) + Duration::nanoseconds(42));
+
+        assert_eq!(
+            as_duration(&duration_type, &try_encode(duration)).unwrap(),
+            Some(duration)
+        );
+        assert!(as_duration(&duration_type, &CBytes::new(to_duration(1, 0, 0))).is_err());
+        assert!(as_duration(&col_type(ColType::Custom), &try_encode(duration)).is_err());
+        assert!(Bytes::try_from(Duration::days(i32::MAX as i64 + 1)).is_err());
+    }
+}
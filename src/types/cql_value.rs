@@ -0,0 +1,697 @@
+//! Dynamic representation of values of any CQL type, read with the type of their column, for
+//! tools which don't know the schema at compile time, e.g. admin UIs or exporters.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption, ColTypeOptionValue};
+use crate::frame::{IntoBytes, TryFromRow};
+use crate::types::blob::Blob;
+use crate::types::data_serialization_types::*;
+use crate::types::decimal::Decimal;
+use crate::types::rows::Row;
+use crate::types::value::{Bytes, Value};
+use crate::types::vector::parse_vector_type;
+use crate::types::{to_bigint, to_duration, to_int, to_u, CBytes, FromCqlValue};
+
+/// Value of any CQL type. Collections, UDTs and tuples hold values of their elements, in the
+/// order they're sent in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CqlValue {
+    Null,
+    /// Empty value of a type which has no empty representation, e.g. `int`.
+    Empty,
+    Ascii(String),
+    Bigint(i64),
+    Blob(Vec<u8>),
+    Boolean(bool),
+    Counter(i64),
+    Decimal(Decimal),
+    Double(f64),
+    Float(f32),
+    Int(i32),
+    /// Milliseconds since the unix epoch.
+    Timestamp(i64),
+    Uuid(Uuid),
+    Text(String),
+    Varint(i128),
+    Timeuuid(Uuid),
+    Inet(IpAddr),
+    /// Days since the unix epoch.
+    Date(i32),
+    /// Nanoseconds since midnight.
+    Time(i64),
+    Smallint(i16),
+    Tinyint(i8),
+    Duration {
+        months: i32,
+        days: i32,
+        nanoseconds: i64,
+    },
+    List(Vec<CqlValue>),
+    Set(Vec<CqlValue>),
+    Map(Vec<(CqlValue, CqlValue)>),
+    /// Fields with their names.
+    Udt(Vec<(String, CqlValue)>),
+    Tuple(Vec<CqlValue>),
+    Vector(Vec<CqlValue>),
+    /// Raw bytes of a value of a custom type other than `duration` and `vector`, e.g. of a type
+    /// implemented by a Java class on the server, which can't be decoded without knowing it.
+    Custom {
+        class: String,
+        bytes: Vec<u8>,
+    },
+}
+
+impl CqlValue {
+    /// Checks if the value is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, CqlValue::Null)
+    }
+
+    fn from_cbytes(col_type: &ColTypeOption, bytes: &CBytes) -> Result<CqlValue> {
+        Ok(CqlValue::from_cql_value(col_type, bytes)?.unwrap_or(CqlValue::Null))
+    }
+
+    /// Encodes the value as a value of given type, e.g. of a field of a UDT. It fails if it
+    /// isn't a value of the type, i.e. of the variant of the type, except that `Text` is also an
+    /// `ascii` value if it's ASCII, and `List` and `Set` are values of both lists and sets.
+    pub fn encode(&self, col_type: &ColTypeOption) -> Result<Value> {
+        match self {
+            CqlValue::Null => Ok(Value::new_null()),
+            CqlValue::Empty => Ok(Value::new_normal(Bytes::new(vec![]))),
+            _ => encode(self, col_type).map(Value::new_normal),
+        }
+    }
+}
+
+impl From<String> for CqlValue {
+    fn from(value: String) -> Self {
+        CqlValue::Text(value)
+    }
+}
+
+impl<'a> From<&'a str> for CqlValue {
+    fn from(value: &'a str) -> Self {
+        CqlValue::Text(value.to_string())
+    }
+}
+
+impl From<bool> for CqlValue {
+    fn from(value: bool) -> Self {
+        CqlValue::Boolean(value)
+    }
+}
+
+impl From<i8> for CqlValue {
+    fn from(value: i8) -> Self {
+        CqlValue::Tinyint(value)
+    }
+}
+
+impl From<i16> for CqlValue {
+    fn from(value: i16) -> Self {
+        CqlValue::Smallint(value)
+    }
+}
+
+impl From<i32> for CqlValue {
+    fn from(value: i32) -> Self {
+        CqlValue::Int(value)
+    }
+}
+
+impl From<i64> for CqlValue {
+    fn from(value: i64) -> Self {
+        CqlValue::Bigint(value)
+    }
+}
+
+impl From<i128> for CqlValue {
+    fn from(value: i128) -> Self {
+        CqlValue::Varint(value)
+    }
+}
+
+impl From<f32> for CqlValue {
+    fn from(value: f32) -> Self {
+        CqlValue::Float(value)
+    }
+}
+
+impl From<f64> for CqlValue {
+    fn from(value: f64) -> Self {
+        CqlValue::Double(value)
+    }
+}
+
+impl From<Decimal> for CqlValue {
+    fn from(value: Decimal) -> Self {
+        CqlValue::Decimal(value)
+    }
+}
+
+impl From<Uuid> for CqlValue {
+    fn from(value: Uuid) -> Self {
+        CqlValue::Uuid(value)
+    }
+}
+
+impl From<IpAddr> for CqlValue {
+    fn from(value: IpAddr) -> Self {
+        CqlValue::Inet(value)
+    }
+}
+
+impl From<Blob> for CqlValue {
+    fn from(value: Blob) -> Self {
+        CqlValue::Blob(value.into_vec())
+    }
+}
+
+impl<T: Into<CqlValue>> From<Option<T>> for CqlValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(CqlValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<CqlValue>> From<Vec<T>> for CqlValue {
+    fn from(value: Vec<T>) -> Self {
+        CqlValue::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<CqlValue>, V: Into<CqlValue>> From<HashMap<K, V>> for CqlValue {
+    fn from(value: HashMap<K, V>) -> Self {
+        CqlValue::Map(
+            value
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Null values are read as `None` rather than `CqlValue::Null`, like values of other types.
+impl FromCqlValue for CqlValue {
+    fn from_cql_value(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Self>> {
+        bytes
+            .as_slice()
+            .map(|bytes| decode(col_type, bytes))
+            .transpose()
+    }
+}
+
+/// Rows are converted into values of all their columns by column names.
+impl TryFromRow for HashMap<String, CqlValue> {
+    fn try_from_row(row: Row) -> Result<Self> {
+        row.to_cql_values()
+    }
+}
+
+fn decode(col_type: &ColTypeOption, bytes: &[u8]) -> Result<CqlValue> {
+    let empty_allowed = matches!(
+        col_type.id,
+        ColType::Custom | ColType::Ascii | ColType::Varchar | ColType::Blob
+    );
+    if bytes.is_empty() && !empty_allowed {
+        return Ok(CqlValue::Empty);
+    }
+
+    let value = match col_type.id {
+        ColType::Custom => return decode_custom_value(col_type, bytes),
+        ColType::Ascii => CqlValue::Ascii(decode_ascii(bytes)?),
+        ColType::Bigint => CqlValue::Bigint(decode_bigint(bytes)?),
+        ColType::Blob => CqlValue::Blob(bytes.to_vec()),
+        ColType::Boolean => CqlValue::Boolean(decode_boolean(bytes)?),
+        ColType::Counter => CqlValue::Counter(decode_bigint(bytes)?),
+        ColType::Decimal => CqlValue::Decimal(decode_decimal(bytes)?),
+        ColType::Double => CqlValue::Double(decode_double(bytes)?),
+        ColType::Float => CqlValue::Float(decode_float(bytes)?),
+        ColType::Int => CqlValue::Int(decode_int(bytes)?),
+        ColType::Timestamp => CqlValue::Timestamp(decode_timestamp(bytes)?),
+        ColType::Uuid => CqlValue::Uuid(decode_timeuuid(bytes)?),
+        ColType::Varchar => CqlValue::Text(decode_varchar(bytes)?),
+        ColType::Varint => CqlValue::Varint(decode_varint_i128(bytes)?),
+        ColType::Timeuuid => CqlValue::Timeuuid(decode_timeuuid(bytes)?),
+        ColType::Inet => CqlValue::Inet(decode_inet(bytes)?),
+        ColType::Date => CqlValue::Date((decode_date(bytes)? as u32 as i64 - EPOCH_DAY) as i32),
+        ColType::Time => CqlValue::Time(decode_time(bytes)?),
+        ColType::Smallint => CqlValue::Smallint(decode_smallint(bytes)?),
+        ColType::Tinyint => CqlValue::Tinyint(decode_tinyint(bytes)?),
+        ColType::List | ColType::Set => {
+            let element_type = match col_type.value {
+                Some(ColTypeOptionValue::CList(ref element_type))
+                | Some(ColTypeOptionValue::CSet(ref element_type)) => element_type,
+                _ => return Err(invalid_metadata(col_type)),
+            };
+            let elements = decode_list(bytes)?
+                .iter()
+                .map(|element| CqlValue::from_cbytes(element_type, element))
+                .collect::<Result<_>>()?;
+
+            match col_type.id {
+                ColType::List => CqlValue::List(elements),
+                _ => CqlValue::Set(elements),
+            }
+        }
+        ColType::Map => {
+            let (key_type, value_type) = match col_type.value {
+                Some(ColTypeOptionValue::CMap((ref key_type, ref value_type))) => {
+                    (key_type, value_type)
+                }
+                _ => return Err(invalid_metadata(col_type)),
+            };
+            let entries = decode_map(bytes)?
+                .iter()
+                .map(|(key, value)| {
+                    Ok((
+                        CqlValue::from_cbytes(key_type, key)?,
+                        CqlValue::from_cbytes(value_type, value)?,
+                    ))
+                })
+                .collect::<Result<_>>()?;
+
+            CqlValue::Map(entries)
+        }
+        ColType::Udt => {
+            let udt = match col_type.value {
+                Some(ColTypeOptionValue::UdtType(ref udt)) => udt,
+                _ => return Err(invalid_metadata(col_type)),
+            };
+            let fields = decode_udt(bytes, udt.descriptions.len())?
+                .iter()
+                .zip(udt.descriptions.iter())
+                .map(|(value, (name, field_type))| {
+                    Ok((name.as_plain(), CqlValue::from_cbytes(field_type, value)?))
+                })
+                .collect::<Result<_>>()?;
+
+            CqlValue::Udt(fields)
+        }
+        ColType::Tuple => {
+            let tuple = match col_type.value {
+                Some(ColTypeOptionValue::TupleType(ref tuple)) => tuple,
+                _ => return Err(invalid_metadata(col_type)),
+            };
+            let elements = decode_tuple(bytes, tuple.types.len())?
+                .iter()
+                .zip(tuple.types.iter())
+                .map(|(value, element_type)| CqlValue::from_cbytes(element_type, value))
+                .collect::<Result<_>>()?;
+
+            CqlValue::Tuple(elements)
+        }
+        ColType::Null => CqlValue::Null,
+    };
+
+    Ok(value)
+}
+
+fn encode(value: &CqlValue, col_type: &ColTypeOption) -> Result<Bytes> {
+    let bytes =
+        match (value, &col_type.id) {
+            (CqlValue::Ascii(value), ColType::Ascii) | (CqlValue::Text(value), ColType::Ascii)
+                if value.is_ascii() =>
+            {
+                value.as_str().into()
+            }
+            (CqlValue::Ascii(value), ColType::Varchar)
+            | (CqlValue::Text(value), ColType::Varchar) => value.as_str().into(),
+            (CqlValue::Bigint(value), ColType::Bigint)
+            | (CqlValue::Counter(value), ColType::Counter)
+            | (CqlValue::Timestamp(value), ColType::Timestamp)
+            | (CqlValue::Time(value), ColType::Time) => Bytes::new(to_bigint(*value)),
+            (CqlValue::Blob(value), ColType::Blob) => Bytes::new(value.clone()),
+            (CqlValue::Boolean(value), ColType::Boolean) => (*value).into(),
+            (CqlValue::Decimal(value), ColType::Decimal) => value.clone().into(),
+            (CqlValue::Double(value), ColType::Double) => (*value).into(),
+            (CqlValue::Float(value), ColType::Float) => (*value).into(),
+            (CqlValue::Int(value), ColType::Int) => (*value).into(),
+            (CqlValue::Uuid(value), ColType::Uuid)
+            | (CqlValue::Timeuuid(value), ColType::Timeuuid) => (*value).into(),
+            (CqlValue::Varint(value), ColType::Varint) => (*value).into(),
+            (CqlValue::Inet(value), ColType::Inet) => (*value).into(),
+            // days since the unix epoch always fit in days since the epoch day
+            (CqlValue::Date(value), ColType::Date) => {
+                Bytes::new(to_u((*value as i64 + EPOCH_DAY) as u32))
+            }
+            (CqlValue::Smallint(value), ColType::Smallint) => (*value).into(),
+            (CqlValue::Tinyint(value), ColType::Tinyint) => (*value).into(),
+            (CqlValue::List(elements), ColType::List | ColType::Set)
+            | (CqlValue::Set(elements), ColType::List | ColType::Set) => {
+                let element_type = match col_type.value {
+                    Some(ColTypeOptionValue::CList(ref element_type))
+                    | Some(ColTypeOptionValue::CSet(ref element_type)) => element_type,
+                    _ => return Err(invalid_metadata(col_type)),
+                };
+
+                let mut bytes = to_int(elements.len() as i32);
+                for element in elements {
+                    bytes.extend(element.encode(element_type)?.into_cbytes());
+                }
+                Bytes::new(bytes)
+            }
+            (CqlValue::Map(entries), ColType::Map) => {
+                let (key_type, value_type) = match col_type.value {
+                    Some(ColTypeOptionValue::CMap((ref key_type, ref value_type))) => {
+                        (key_type, value_type)
+                    }
+                    _ => return Err(invalid_metadata(col_type)),
+                };
+
+                let mut bytes = to_int(entries.len() as i32);
+                for (key, value) in entries {
+                    bytes.extend(key.encode(key_type)?.into_cbytes());
+                    bytes.extend(value.encode(value_type)?.into_cbytes());
+                }
+                Bytes::new(bytes)
+            }
+            (CqlValue::Udt(fields), ColType::Udt) => {
+                let udt = match col_type.value {
+                    Some(ColTypeOptionValue::UdtType(ref udt)) => udt,
+                    _ => return Err(invalid_metadata(col_type)),
+                };
+                if let Some((name, _)) = fields.iter().find(|(name, _)| {
+                    !udt.descriptions
+                        .iter()
+                        .any(|(field, _)| field.as_str() == name)
+                }) {
+                    return Err(Error::General(format!(
+                        "UDT {} has no field {}",
+                        udt.udt_name.as_str(),
+                        name
+                    )));
+                }
+
+                // fields which aren't given are null
+                let mut bytes = vec![];
+                for (name, field_type) in &udt.descriptions {
+                    let value = match fields.iter().find(|(field, _)| field == name.as_str()) {
+                        Some((_, value)) => value.encode(field_type)?,
+                        None => Value::new_null(),
+                    };
+                    bytes.extend(value.into_cbytes());
+                }
+                Bytes::new(bytes)
+            }
+            (CqlValue::Tuple(elements), ColType::Tuple) => {
+                let tuple = match col_type.value {
+                    Some(ColTypeOptionValue::TupleType(ref tuple)) => tuple,
+                    _ => return Err(invalid_metadata(col_type)),
+                };
+                if elements.len() != tuple.types.len() {
+                    return Err(Error::General(format!(
+                        "Tuple of {} elements isn't a value of a tuple of {} elements",
+                        elements.len(),
+                        tuple.types.len()
+                    )));
+                }
+
+                let mut bytes = vec![];
+                for (element, element_type) in elements.iter().zip(tuple.types.iter()) {
+                    bytes.extend(element.encode(element_type)?.into_cbytes());
+                }
+                Bytes::new(bytes)
+            }
+            (_, ColType::Custom) => return encode_custom_value(value, col_type),
+            _ => return Err(invalid_value(value, col_type)),
+        };
+
+    Ok(bytes)
+}
+
+fn encode_custom_value(value: &CqlValue, col_type: &ColTypeOption) -> Result<Bytes> {
+    let class = match col_type.value {
+        Some(ColTypeOptionValue::CString(ref class)) => class.as_str(),
+        _ => "",
+    };
+
+    match value {
+        CqlValue::Duration {
+            months,
+            days,
+            nanoseconds,
+        } if class == DURATION_TYPE => Ok(Bytes::new(to_duration(*months, *days, *nanoseconds))),
+        CqlValue::Vector(elements) => match parse_vector_type(class) {
+            Some((element, size, dimension)) if elements.len() == dimension => {
+                let element_type = ColTypeOption {
+                    id: element,
+                    value: None,
+                };
+                // elements are of fixed size, so they're encoded without their size
+                let mut bytes = vec![];
+                for element in elements {
+                    bytes.extend(element.encode(&element_type)?.body);
+                }
+                if bytes.len() != size * dimension {
+                    return Err(Error::General(
+                        "Elements of vectors cannot be null or empty".to_string(),
+                    ));
+                }
+                Ok(Bytes::new(bytes))
+            }
+            Some((_, _, dimension)) => Err(Error::General(format!(
+                "Vector of {} elements isn't a value of a vector of {} elements",
+                elements.len(),
+                dimension
+            ))),
+            None => Err(invalid_value(value, col_type)),
+        },
+        CqlValue::Custom {
+            class: value_class,
+            bytes,
+        } if value_class == class => Ok(Bytes::new(bytes.clone())),
+        _ => Err(invalid_value(value, col_type)),
+    }
+}
+
+fn invalid_value(value: &CqlValue, col_type: &ColTypeOption) -> Error {
+    Error::General(format!(
+        "{:?} isn't a value of type {:?}",
+        value, col_type.id
+    ))
+}
+
+fn decode_custom_value(col_type: &ColTypeOption, bytes: &[u8]) -> Result<CqlValue> {
+    let class = match col_type.value {
+        Some(ColTypeOptionValue::CString(ref class)) => class.as_str(),
+        _ => "",
+    };
+
+    let vector_type = parse_vector_type(class);
+    if bytes.is_empty() && (class == DURATION_TYPE || vector_type.is_some()) {
+        return Ok(CqlValue::Empty);
+    }
+
+    if class == DURATION_TYPE {
+        let (months, days, nanoseconds) = decode_duration(bytes)?;
+        return Ok(CqlValue::Duration {
+            months,
+            days,
+            nanoseconds,
+        });
+    }
+
+    match vector_type {
+        Some((element, size, dimension)) if bytes.len() == size * dimension => {
+            let element_type = ColTypeOption {
+                id: element,
+                value: None,
+            };
+            let elements = bytes
+                .chunks(size)
+                .map(|element| decode(&element_type, element))
+                .collect::<Result<_>>()?;
+
+            Ok(CqlValue::Vector(elements))
+        }
+        Some((_, size, dimension)) => Err(Error::General(format!(
+            "Vector of {} bytes doesn't have {} elements of {} bytes",
+            bytes.len(),
+            dimension,
+            size
+        ))),
+        None => Ok(CqlValue::Custom {
+            class: class.to_string(),
+            bytes: bytes.to_vec(),
+        }),
+    }
+}
+
+fn invalid_metadata(col_type: &ColTypeOption) -> Error {
+    Error::General(format!(
+        "Type {:?} is missing types of its elements",
+        col_type.id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::frame_result::CUdt;
+    use crate::test_utils::{col_type, col_type_with, encode};
+    use crate::types::value::Value;
+    use crate::types::vector::CqlVector;
+    use crate::types::CString;
+
+    #[test]
+    fn reads_values_of_any_type() {
+        let int = col_type(ColType::Int);
+        let text = col_type(ColType::Varchar);
+        assert_eq!(
+            CqlValue::from_cbytes(&int, &encode(5i32)).unwrap(),
+            CqlValue::Int(5)
+        );
+        assert_eq!(
+            CqlValue::from_cbytes(&int, &CBytes::new_empty()).unwrap(),
+            CqlValue::Null
+        );
+        assert_eq!(
+            CqlValue::from_cbytes(&int, &CBytes::new(vec![])).unwrap(),
+            CqlValue::Empty
+        );
+        assert_eq!(
+            CqlValue::from_cbytes(&text, &CBytes::new(vec![])).unwrap(),
+            CqlValue::Text(String::new())
+        );
+
+        let map = col_type_with(
+            ColType::Map,
+            ColTypeOptionValue::CMap((
+                Box::new(text.clone()),
+                Box::new(col_type_with(
+                    ColType::List,
+                    ColTypeOptionValue::CList(Box::new(int.clone())),
+                )),
+            )),
+        );
+        let value: HashMap<&str, Vec<i32>> = vec![("a", vec![1, 2])].into_iter().collect();
+        assert_eq!(
+            CqlValue::from_cbytes(&map, &encode(value)).unwrap(),
+            CqlValue::Map(vec![(
+                CqlValue::Text("a".to_string()),
+                CqlValue::List(vec![CqlValue::Int(1), CqlValue::Int(2)])
+            )])
+        );
+
+        let udt = col_type_with(
+            ColType::Udt,
+            ColTypeOptionValue::UdtType(CUdt {
+                ks: CString::new("ks".to_string()),
+                udt_name: CString::new("point".to_string()),
+                descriptions: vec![
+                    (CString::new("x".to_string()), int.clone()),
+                    (CString::new("label".to_string()), text),
+                ],
+            }),
+        );
+        let mut value = Value::new_normal(3i32).into_cbytes();
+        value.extend(Value::new_null().into_cbytes());
+        assert_eq!(
+            CqlValue::from_cbytes(&udt, &CBytes::new(value)).unwrap(),
+            CqlValue::Udt(vec![
+                ("x".to_string(), CqlValue::Int(3)),
+                ("label".to_string(), CqlValue::Null)
+            ])
+        );
+    }
+
+    #[test]
+    fn reads_custom_types() {
+        let custom = |class: &str| {
+            col_type_with(
+                ColType::Custom,
+                ColTypeOptionValue::CString(CString::new(class.to_string())),
+            )
+        };
+
+        assert_eq!(
+            CqlValue::from_cbytes(&custom(DURATION_TYPE), &CBytes::new(vec![2, 4, 6])).unwrap(),
+            CqlValue::Duration {
+                months: 1,
+                days: 2,
+                nanoseconds: 3
+            }
+        );
+
+        let vector = custom(
+            "org.apache.cassandra.db.marshal.VectorType(\
+             org.apache.cassandra.db.marshal.Int32Type,2)",
+        );
+        assert_eq!(
+            CqlValue::from_cbytes(&vector, &encode(CqlVector(vec![1i32, 2]))).unwrap(),
+            CqlValue::Vector(vec![CqlValue::Int(1), CqlValue::Int(2)])
+        );
+        assert!(CqlValue::from_cbytes(&vector, &encode(CqlVector(vec![1i32]))).is_err());
+
+        assert_eq!(
+            CqlValue::from_cbytes(&custom("Other"), &CBytes::new(vec![1])).unwrap(),
+            CqlValue::Custom {
+                class: "Other".to_string(),
+                bytes: vec![1]
+            }
+        );
+    }
+
+    #[test]
+    fn encodes_values_of_given_types() {
+        let int = col_type(ColType::Int);
+        let ascii = col_type(ColType::Ascii);
+        let set = col_type_with(
+            ColType::Set,
+            ColTypeOptionValue::CSet(Box::new(int.clone())),
+        );
+        let udt = col_type_with(
+            ColType::Udt,
+            ColTypeOptionValue::UdtType(CUdt {
+                ks: CString::new("ks".to_string()),
+                udt_name: CString::new("point".to_string()),
+                descriptions: vec![
+                    (CString::new("x".to_string()), int.clone()),
+                    (CString::new("tags".to_string()), set.clone()),
+                ],
+            }),
+        );
+
+        let value = CqlValue::Udt(vec![("tags".to_string(), CqlValue::from(vec![1, 2]))]);
+        let bytes = CBytes::new(value.encode(&udt).unwrap().body);
+        assert_eq!(
+            CqlValue::from_cbytes(&udt, &bytes).unwrap(),
+            CqlValue::Udt(vec![
+                ("x".to_string(), CqlValue::Null),
+                (
+                    "tags".to_string(),
+                    CqlValue::Set(vec![CqlValue::Int(1), CqlValue::Int(2)])
+                )
+            ])
+        );
+
+        assert!(CqlValue::from("abc").encode(&ascii).is_ok());
+        assert!(CqlValue::from("é").encode(&ascii).is_err());
+        assert!(CqlValue::Float(1.0).encode(&int).is_err());
+        assert!(CqlValue::from(vec![1i64]).encode(&set).is_err());
+        assert!(CqlValue::Udt(vec![("y".to_string(), CqlValue::Int(1))])
+            .encode(&udt)
+            .is_err());
+
+        let vector = col_type_with(
+            ColType::Custom,
+            ColTypeOptionValue::CString(CString::new(
+                "org.apache.cassandra.db.marshal.VectorType(\
+                 org.apache.cassandra.db.marshal.Int32Type,2)"
+                    .to_string(),
+            )),
+        );
+        let value = CqlValue::Vector(vec![CqlValue::Int(1), CqlValue::Int(2)]);
+        assert_eq!(
+            value.encode(&vector).unwrap(),
+            Value::new_normal(CqlVector(vec![1i32, 2]))
+        );
+        assert!(CqlValue::Vector(vec![CqlValue::Int(1), CqlValue::Null])
+            .encode(&vector)
+            .is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::io;
 use std::net;
 use std::string::FromUtf8Error;
@@ -11,6 +12,15 @@ use uuid;
 
 // https://github.com/apache/cassandra/blob/trunk/doc/native_protocol_v4.spec#L813
 
+/// Class of Cassandra `duration` types, which are sent as custom types.
+pub const DURATION_TYPE: &str = "org.apache.cassandra.db.marshal.DurationType";
+
+/// Cassandra `date` values are unsigned numbers of days, with the unix epoch at 2^31.
+pub const EPOCH_DAY: i64 = 1 << 31;
+
+/// Number of nanoseconds in a day, the range of Cassandra `time` values.
+pub const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
 // Decodes Cassandra `ascii` data (bytes) into Rust's `Result<String, FromUtf8Error>`.
 pub fn decode_custom(bytes: &[u8]) -> Result<String, FromUtf8Error> {
     Ok(String::from_utf8_lossy(bytes).into_owned())
@@ -157,12 +167,15 @@ pub fn decode_map(bytes: &[u8]) -> Result<Vec<(CBytes, CBytes)>, io::Error> {
 
 // Decodes Cassandra `smallint` data (bytes) into Rust's `Result<i16, io::Error>`
 pub fn decode_smallint(bytes: &[u8]) -> Result<i16, io::Error> {
-    try_from_bytes(bytes).map(|i| i as i16)
+    try_i16_from_bytes(bytes)
 }
 
 // Decodes Cassandra `tinyint` data (bytes) into Rust's `Result<i8, io::Error>`
 pub fn decode_tinyint(bytes: &[u8]) -> Result<i8, io::Error> {
-    Ok(bytes[0] as i8)
+    bytes
+        .first()
+        .map(|byte| *byte as i8)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated tinyint"))
 }
 
 // Decodes Cassandra `text` data (bytes) into Rust's `Result<String, FromUtf8Error>`.
@@ -180,9 +193,70 @@ pub fn decode_timeuuid(bytes: &[u8]) -> Result<uuid::Uuid, uuid::Error> {
     uuid::Uuid::from_slice(bytes)
 }
 
-// Decodes Cassandra `varint` data (bytes) into Rust's `Result<i64, io::Error>`
+// Decodes Cassandra `varint` data (bytes) into Rust's `Result<i64, io::Error>`, failing for
+// values which don't fit in `i64`.
 pub fn decode_varint(bytes: &[u8]) -> Result<i64, io::Error> {
-    try_i_from_bytes(bytes)
+    let value = decode_varint_i128(bytes)?;
+    i64::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Varint {} doesn't fit in i64", value),
+        )
+    })
+}
+
+// Decodes Cassandra `varint` data (bytes) into Rust's `Result<i128, io::Error>`, failing for
+// values which don't fit in `i128`.
+pub fn decode_varint_i128(bytes: &[u8]) -> Result<i128, io::Error> {
+    let sign = match bytes.first() {
+        Some(byte) if *byte & 0x80 != 0 => 0xFF,
+        Some(_) => 0x00,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Truncated varint",
+            ))
+        }
+    };
+
+    // values aren't required to be encoded with the least number of bytes
+    let mut bytes = bytes;
+    while bytes.len() > 16 && bytes[0] == sign && (bytes[1] ^ sign) & 0x80 == 0 {
+        bytes = &bytes[1..];
+    }
+    if bytes.len() > 16 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Varint doesn't fit in i128",
+        ));
+    }
+
+    let mut value = [sign; 16];
+    value[16 - bytes.len()..].copy_from_slice(bytes);
+    Ok(i128::from_be_bytes(value))
+}
+
+// Decodes Cassandra `duration` data (bytes) into Rust's `Result<(i32, i32, i64), io::Error>`
+// of months, days and nanoseconds, each encoded as a zigzag-encoded variable length integer.
+pub fn decode_duration(bytes: &[u8]) -> Result<(i32, i32, i64), io::Error> {
+    let mut values = [0i64; 3];
+    let mut bytes = bytes.iter();
+    for value in values.iter_mut() {
+        let first = *bytes
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated duration"))?;
+        let extra_bytes = first.leading_ones();
+        let mut unsigned = u64::from(first & 0xFFu8.checked_shr(extra_bytes).unwrap_or(0));
+        for _ in 0..extra_bytes {
+            let byte = *bytes.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated duration")
+            })?;
+            unsigned = (unsigned << 8) | u64::from(byte);
+        }
+        *value = (unsigned >> 1) as i64 ^ -((unsigned & 1) as i64);
+    }
+
+    Ok((values[0] as i32, values[1] as i32, values[2]))
 }
 
 // Decodes Cassandra `Udt` data (bytes) into Rust's `Result<Vec<CBytes>, io::Error>`
@@ -335,6 +409,8 @@ mod tests {
     #[test]
     fn decode_tinyint_test() {
         assert_eq!(decode_tinyint(&[10]).unwrap(), 10);
+        assert_eq!(decode_tinyint(&[0xFF]).unwrap(), -1);
+        assert!(decode_tinyint(&[]).is_err());
     }
 
     #[test]
@@ -395,6 +471,45 @@ mod tests {
         assert_eq!(decode_varint(&[0xFF]).unwrap(), -1);
         assert_eq!(decode_varint(&[0x80]).unwrap(), -128);
         assert_eq!(decode_varint(&[0xFF, 0x7F]).unwrap(), -129);
+        assert!(decode_varint(&[0x00, 0x80, 0, 0, 0, 0, 0, 0, 0]).is_err());
+        assert!(decode_varint(&[]).is_err());
+    }
+
+    #[test]
+    fn decode_varint_i128_test() {
+        assert_eq!(
+            decode_varint_i128(&[0x00, 0x80, 0, 0, 0, 0, 0, 0, 0]).unwrap(),
+            1 << 63
+        );
+        assert_eq!(
+            decode_varint_i128(&[0x80; 16]).unwrap(),
+            i128::from_be_bytes([0x80; 16])
+        );
+        let mut padded = vec![0xFF; 4];
+        padded.extend_from_slice(&i128::MIN.to_be_bytes());
+        assert_eq!(decode_varint_i128(&padded).unwrap(), i128::MIN);
+        let mut too_large = vec![0x00];
+        too_large.extend_from_slice(&u128::MAX.to_be_bytes());
+        assert!(decode_varint_i128(&too_large).is_err());
+    }
+
+    #[test]
+    fn decode_duration_test() {
+        assert_eq!(
+            decode_duration(&[0x02, 0x01, 0xF0, 0x77, 0x35, 0x94, 0x00]).unwrap(),
+            (1, -1, 1_000_000_000)
+        );
+        assert_eq!(decode_duration(&[0x00, 0x80, 0x80, 0x00]).unwrap(), (0, 64, 0));
+        assert!(decode_duration(&[0x00, 0x80]).is_err());
+
+        for &(months, days, nanos) in &[(0, 0, i64::MAX), (i32::MIN, i32::MAX, i64::MIN)] {
+            let bytes = to_duration(months, days, nanos);
+            assert_eq!(decode_duration(&bytes).unwrap(), (months, days, nanos));
+        }
+        assert_eq!(
+            to_duration(1, -1, 1_000_000_000),
+            vec![0x02, 0x01, 0xF0, 0x77, 0x35, 0x94, 0x00]
+        );
     }
 
     #[test]
@@ -0,0 +1,449 @@
+//! Deserialization of rows and column values into `serde::Deserialize` types, enabled with the
+//! `serde` feature.
+//!
+//! Rows are deserialized as maps of column names to values, e.g. into structs, or as sequences
+//! of values, e.g. into tuples. UDTs are maps of field names to values, lists, sets and tuples
+//! are sequences, and nulls are `None`. Values are decoded into owned data, so types borrowing
+//! from the input, such as `&str`, are not supported.
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::convert::TryFrom;
+use std::fmt;
+use std::vec;
+
+use crate::error::{Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption, ColTypeOptionValue};
+use crate::types::data_serialization_types::*;
+use crate::types::rows::Row;
+use crate::types::{CBytes, CString};
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::General(msg.to_string())
+    }
+}
+
+/// Deserializes a row into a `serde::Deserialize` type.
+pub fn from_row<T: DeserializeOwned>(row: &Row) -> Result<T> {
+    T::deserialize(RowDeserializer::new(row))
+}
+
+/// Deserializes all rows of a result set into a `serde::Deserialize` type.
+pub fn from_rows<T: DeserializeOwned>(rows: &[Row]) -> Result<Vec<T>> {
+    rows.iter().map(from_row).collect()
+}
+
+/// Deserializer of a row, as a map of column names to values or a sequence of values.
+pub struct RowDeserializer<'a> {
+    row: &'a Row,
+}
+
+impl<'a> RowDeserializer<'a> {
+    pub fn new(row: &'a Row) -> Self {
+        RowDeserializer { row }
+    }
+
+    fn values(&self) -> impl Iterator<Item = ValueDeserializer<'a>> {
+        self.row
+            .columns()
+            .map(|(spec, value)| ValueDeserializer::new(&spec.col_type, value.as_plain()))
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for RowDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let columns = self.row.columns().map(|(spec, value)| {
+            (
+                spec.name.as_str().to_string(),
+                ValueDeserializer::new(&spec.col_type, value.as_plain()),
+            )
+        });
+
+        visitor.visit_map(MapDeserializer::new(columns))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(SeqDeserializer::new(self.values()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+/// Deserializer of a single value of given type, which is `None` for nulls.
+pub struct ValueDeserializer<'a> {
+    col_type: &'a ColTypeOption,
+    bytes: Option<Vec<u8>>,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    pub fn new(col_type: &'a ColTypeOption, bytes: Option<Vec<u8>>) -> Self {
+        ValueDeserializer { col_type, bytes }
+    }
+
+    fn invalid_type(&self) -> Error {
+        Error::General(format!(
+            "Invalid column type {:?} for deserialization",
+            self.col_type
+        ))
+    }
+}
+
+impl<'a, 'de> IntoDeserializer<'de, Error> for ValueDeserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let bytes = match self.bytes {
+            Some(ref bytes) => bytes.as_slice(),
+            None => return visitor.visit_none(),
+        };
+
+        // only text and blobs have empty values
+        let has_empty_values = matches!(
+            self.col_type.id,
+            ColType::Custom | ColType::Ascii | ColType::Varchar | ColType::Blob
+        );
+        if bytes.is_empty() && !has_empty_values {
+            return Err(Error::General(format!(
+                "Empty value of {:?} cannot be deserialized",
+                self.col_type.id
+            )));
+        }
+
+        match self.col_type.id {
+            ColType::Custom | ColType::Ascii | ColType::Varchar => {
+                visitor.visit_string(decode_varchar(bytes)?)
+            }
+            ColType::Bigint | ColType::Counter | ColType::Timestamp | ColType::Time => {
+                visitor.visit_i64(decode_bigint(bytes)?)
+            }
+            ColType::Varint => {
+                let value = decode_varint_i128(bytes)?;
+                match i64::try_from(value) {
+                    Ok(value) => visitor.visit_i64(value),
+                    Err(_) => visitor.visit_i128(value),
+                }
+            }
+            ColType::Blob => visitor.visit_byte_buf(bytes.to_vec()),
+            ColType::Boolean => visitor.visit_bool(decode_boolean(bytes)?),
+            ColType::Decimal => visitor.visit_f64(decode_decimal(bytes)?.as_plain()),
+            ColType::Double => visitor.visit_f64(decode_double(bytes)?),
+            ColType::Float => visitor.visit_f32(decode_float(bytes)?),
+            ColType::Int => visitor.visit_i32(decode_int(bytes)?),
+            ColType::Date => visitor.visit_i32(decode_date(bytes)?),
+            ColType::Smallint => visitor.visit_i16(decode_smallint(bytes)?),
+            ColType::Tinyint => visitor.visit_i8(decode_tinyint(bytes)?),
+            ColType::Uuid | ColType::Timeuuid => {
+                visitor.visit_string(decode_timeuuid(bytes)?.to_hyphenated().to_string())
+            }
+            ColType::Inet => visitor.visit_string(decode_inet(bytes)?.to_string()),
+            ColType::List | ColType::Set => match self.col_type.value {
+                Some(ColTypeOptionValue::CList(ref item_type))
+                | Some(ColTypeOptionValue::CSet(ref item_type)) => {
+                    visitor.visit_seq(ValuesAccess::new(decode_list(bytes)?, |_| item_type))
+                }
+                _ => Err(self.invalid_type()),
+            },
+            ColType::Tuple => match self.col_type.value {
+                Some(ColTypeOptionValue::TupleType(ref tuple)) => {
+                    let values = decode_tuple(bytes, tuple.types.len())?;
+                    visitor.visit_seq(ValuesAccess::new(values, |i| &tuple.types[i]))
+                }
+                _ => Err(self.invalid_type()),
+            },
+            ColType::Map => match self.col_type.value {
+                Some(ColTypeOptionValue::CMap((ref key_type, ref value_type))) => {
+                    let entries = decode_map(bytes)?.into_iter().map(|(key, value)| {
+                        (
+                            ValueDeserializer::new(key_type, key.into_plain()),
+                            ValueDeserializer::new(value_type, value.into_plain()),
+                        )
+                    });
+                    visitor.visit_map(MapDeserializer::new(entries))
+                }
+                _ => Err(self.invalid_type()),
+            },
+            ColType::Udt => match self.col_type.value {
+                Some(ColTypeOptionValue::UdtType(ref udt)) => {
+                    let values = decode_udt(bytes, udt.descriptions.len())?;
+                    visitor.visit_map(UdtAccess {
+                        fields: udt.descriptions.iter().zip(values),
+                        value: None,
+                    })
+                }
+                _ => Err(self.invalid_type()),
+            },
+            ColType::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.bytes {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // blobs are deserialized as sequences of bytes, e.g. into `Vec<u8>`
+        match (&self.col_type.id, self.bytes) {
+            (ColType::Blob, Some(bytes)) => {
+                visitor.visit_seq(SeqDeserializer::new(bytes.into_iter()))
+            }
+            (_, bytes) => ValueDeserializer::new(self.col_type, bytes).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        // textual values are deserialized as unit variants of the same name
+        match self.col_type.id {
+            ColType::Custom | ColType::Ascii | ColType::Varchar => match self.bytes {
+                Some(ref bytes) => {
+                    let variant: de::value::StringDeserializer<Error> =
+                        decode_varchar(bytes)?.into_deserializer();
+                    visitor.visit_enum(variant)
+                }
+                None => Err(Error::General("Cannot deserialize null as enum".into())),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Access to values of a collection or tuple, with types given by their index.
+struct ValuesAccess<F> {
+    values: vec::IntoIter<CBytes>,
+    index: usize,
+    col_type: F,
+}
+
+impl<'a, F: Fn(usize) -> &'a ColTypeOption> ValuesAccess<F> {
+    fn new(values: Vec<CBytes>, col_type: F) -> Self {
+        ValuesAccess {
+            values: values.into_iter(),
+            index: 0,
+            col_type,
+        }
+    }
+}
+
+impl<'de, 'a, F: Fn(usize) -> &'a ColTypeOption> SeqAccess<'de> for ValuesAccess<F> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        match self.values.next() {
+            Some(value) => {
+                let col_type = (self.col_type)(self.index);
+                self.index += 1;
+                seed.deserialize(ValueDeserializer::new(col_type, value.into_plain()))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// Access to fields of a UDT value, given by their names and types.
+struct UdtAccess<'a, I> {
+    fields: I,
+    value: Option<ValueDeserializer<'a>>,
+}
+
+impl<'de, 'a, I> MapAccess<'de> for UdtAccess<'a, I>
+where
+    I: Iterator<Item = (&'a (CString, ColTypeOption), CBytes)>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.fields.next() {
+            Some(((name, col_type), value)) => {
+                self.value = Some(ValueDeserializer::new(col_type, value.into_plain()));
+                seed.deserialize(name.as_str().to_string().into_deserializer())
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(Error::General("UDT value requested before its name".into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::test_utils::{row, string, value};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Address {
+        street: String,
+        number: Option<i32>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Active,
+        Disabled,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct User {
+        id: Uuid,
+        name: String,
+        email: Option<String>,
+        logins: i64,
+        tags: Vec<String>,
+        scores: HashMap<String, i32>,
+        address: Address,
+        avatar: Vec<u8>,
+        status: Status,
+    }
+
+    fn user_row() -> Row {
+        let id = Uuid::parse_str("8ef2a5fc-1c4f-11ec-9621-0242ac130002").unwrap();
+
+        let mut tags = 2i32.to_be_bytes().to_vec();
+        tags.extend(value(b"admin"));
+        tags.extend(value(b"ops"));
+
+        let mut scores = 1i32.to_be_bytes().to_vec();
+        scores.extend(value(b"chess"));
+        scores.extend(value(&1200i32.to_be_bytes()));
+
+        let mut address_type = vec![0, 0x30];
+        address_type.extend(string("ks"));
+        address_type.extend(string("address"));
+        address_type.extend_from_slice(&2i16.to_be_bytes());
+        address_type.extend(string("street"));
+        address_type.extend_from_slice(&[0, 0x0D]);
+        address_type.extend(string("number"));
+        address_type.extend_from_slice(&[0, 0x09]);
+
+        let mut address = value(b"Main Street");
+        address.extend_from_slice(&(-1i32).to_be_bytes());
+
+        row(vec![
+            ("id", vec![0, 0x0C], Some(id.as_bytes().to_vec())),
+            ("name", vec![0, 0x0D], Some(b"alice".to_vec())),
+            ("email", vec![0, 0x0D], None),
+            ("logins", vec![0, 0x02], Some(3i64.to_be_bytes().to_vec())),
+            ("tags", vec![0, 0x22, 0, 0x0D], Some(tags)),
+            ("scores", vec![0, 0x21, 0, 0x0D, 0, 0x09], Some(scores)),
+            ("address", address_type, Some(address)),
+            ("avatar", vec![0, 0x03], Some(vec![1, 2, 3])),
+            ("status", vec![0, 0x0D], Some(b"active".to_vec())),
+        ])
+    }
+
+    #[test]
+    fn deserializes_rows_into_structs() {
+        let user: User = from_row(&user_row()).unwrap();
+
+        let mut scores = HashMap::new();
+        scores.insert("chess".to_string(), 1200);
+        assert_eq!(
+            user,
+            User {
+                id: Uuid::parse_str("8ef2a5fc-1c4f-11ec-9621-0242ac130002").unwrap(),
+                name: "alice".to_string(),
+                email: None,
+                logins: 3,
+                tags: vec!["admin".to_string(), "ops".to_string()],
+                scores,
+                address: Address {
+                    street: "Main Street".to_string(),
+                    number: None,
+                },
+                avatar: vec![1, 2, 3],
+                status: Status::Active,
+            }
+        );
+    }
+
+    #[test]
+    fn deserializes_rows_into_tuples() {
+        let row = row(vec![
+            ("name", vec![0, 0x0D], Some(b"alice".to_vec())),
+            ("age", vec![0, 0x09], Some(30i32.to_be_bytes().to_vec())),
+        ]);
+
+        let (name, age): (String, i64) = from_row(&row).unwrap();
+        assert_eq!(name, "alice");
+        assert_eq!(age, 30);
+    }
+
+    #[test]
+    fn fails_on_nulls_of_required_fields() {
+        #[derive(Debug, Deserialize)]
+        struct Required {
+            #[allow(dead_code)]
+            email: String,
+        }
+
+        assert!(from_row::<Required>(&user_row()).is_err());
+    }
+}
@@ -33,6 +33,7 @@ pub trait FromCDRS {
 impl FromCDRS for Blob {}
 impl FromCDRS for String {}
 impl FromCDRS for bool {}
+impl FromCDRS for i128 {}
 impl FromCDRS for i64 {}
 impl FromCDRS for i32 {}
 impl FromCDRS for i16 {}
@@ -51,6 +52,26 @@ impl FromCDRS for NonZeroI8 {}
 impl FromCDRS for NonZeroI16 {}
 impl FromCDRS for NonZeroI32 {}
 impl FromCDRS for NonZeroI64 {}
+#[cfg(feature = "chrono")]
+impl FromCDRS for chrono::DateTime<chrono::Utc> {}
+#[cfg(feature = "chrono")]
+impl FromCDRS for chrono::NaiveDate {}
+#[cfg(feature = "chrono")]
+impl FromCDRS for chrono::NaiveTime {}
+#[cfg(feature = "chrono")]
+impl FromCDRS for chrono::Duration {}
+#[cfg(feature = "time")]
+impl FromCDRS for time03::OffsetDateTime {}
+#[cfg(feature = "time")]
+impl FromCDRS for time03::Date {}
+#[cfg(feature = "time")]
+impl FromCDRS for time03::Time {}
+#[cfg(feature = "time")]
+impl FromCDRS for time03::Duration {}
+#[cfg(feature = "bigdecimal")]
+impl FromCDRS for bigdecimal::BigDecimal {}
+#[cfg(feature = "bigdecimal")]
+impl FromCDRS for num_bigint::BigInt {}
 
 pub trait FromCDRSByName {
     fn from_cdrs_by_name<T>(cdrs_type: &T, name: &str) -> CDRSResult<Option<Self>>
@@ -73,6 +94,7 @@ pub trait FromCDRSByName {
 impl FromCDRSByName for Blob {}
 impl FromCDRSByName for String {}
 impl FromCDRSByName for bool {}
+impl FromCDRSByName for i128 {}
 impl FromCDRSByName for i64 {}
 impl FromCDRSByName for i32 {}
 impl FromCDRSByName for i16 {}
@@ -91,3 +113,23 @@ impl FromCDRSByName for NonZeroI8 {}
 impl FromCDRSByName for NonZeroI16 {}
 impl FromCDRSByName for NonZeroI32 {}
 impl FromCDRSByName for NonZeroI64 {}
+#[cfg(feature = "chrono")]
+impl FromCDRSByName for chrono::DateTime<chrono::Utc> {}
+#[cfg(feature = "chrono")]
+impl FromCDRSByName for chrono::NaiveDate {}
+#[cfg(feature = "chrono")]
+impl FromCDRSByName for chrono::NaiveTime {}
+#[cfg(feature = "chrono")]
+impl FromCDRSByName for chrono::Duration {}
+#[cfg(feature = "time")]
+impl FromCDRSByName for time03::OffsetDateTime {}
+#[cfg(feature = "time")]
+impl FromCDRSByName for time03::Date {}
+#[cfg(feature = "time")]
+impl FromCDRSByName for time03::Time {}
+#[cfg(feature = "time")]
+impl FromCDRSByName for time03::Duration {}
+#[cfg(feature = "bigdecimal")]
+impl FromCDRSByName for bigdecimal::BigDecimal {}
+#[cfg(feature = "bigdecimal")]
+impl FromCDRSByName for num_bigint::BigInt {}
@@ -0,0 +1,130 @@
+//! Values serialized as JSON, for `SELECT JSON` and `INSERT ... JSON` queries, enabled with the
+//! `json` feature.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::convert::TryFrom;
+
+use crate::error::{invalid_conversion_err, null_value_err, Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption};
+use crate::frame::TryFromRow;
+use crate::types::rows::Row;
+use crate::types::value::Bytes;
+use crate::types::{ByName, CBytes, FromCqlValue};
+
+/// Name of the only column of rows returned by `SELECT JSON` queries.
+pub const JSON_COLUMN: &str = "[json]";
+
+/// Value serialized as JSON, e.g. `serde_json::Value` or a type implementing `Serialize` or
+/// `Deserialize`. It's read from `SELECT JSON` rows and text columns, and bound as text, e.g.
+/// for `INSERT INTO ... JSON ?` or `fromJson(?)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Serializes a value as JSON text.
+pub fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|err| Error::General(format!("Cannot serialize value as JSON: {}", err)))
+}
+
+impl<T: DeserializeOwned> FromCqlValue for Json<T> {
+    fn from_cql_value(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Self>> {
+        if !matches!(col_type.id, ColType::Varchar | ColType::Ascii) {
+            return Err(invalid_conversion_err(col_type, "Json", "Varchar, Ascii"));
+        }
+
+        match bytes.as_slice() {
+            Some(bytes) => serde_json::from_slice(bytes)
+                .map(|value| Some(Json(value)))
+                .map_err(|err| Error::General(format!("Cannot deserialize JSON: {}", err))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Rows of `SELECT JSON` queries are converted by deserializing their `[json]` column.
+impl<T: DeserializeOwned> TryFromRow for Json<T> {
+    fn try_from_row(row: Row) -> Result<Self> {
+        row.by_name(JSON_COLUMN)?
+            .ok_or_else(|| null_value_err(JSON_COLUMN))
+    }
+}
+
+/// Fails if the value cannot be serialized as JSON, e.g. a map with keys other than strings.
+impl<T: Serialize> TryFrom<Json<T>> for Bytes {
+    type Error = Error;
+
+    fn try_from(value: Json<T>) -> Result<Bytes> {
+        to_json(&value.0).map(|json| Bytes::new(json.into_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::test_utils::rows_frame;
+    use crate::types::value::Value;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: i32,
+        name: Option<String>,
+    }
+
+    fn json_rows(rows: &[&str]) -> Vec<Row> {
+        let rows = rows
+            .iter()
+            .map(|row| vec![Some(row.as_bytes().to_vec())])
+            .collect();
+
+        rows_frame(&[(JSON_COLUMN, vec![0, 0x0D])], rows, None)
+            .get_body()
+            .unwrap()
+            .into_rows()
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_json_rows() {
+        let mut rows =
+            json_rows(&[r#"{"id": 1, "name": "a"}"#, r#"{"id": 2, "name": null}"#]).into_iter();
+
+        let Json(user) = Json::<User>::try_from_row(rows.next().unwrap()).unwrap();
+        assert_eq!(
+            user,
+            User {
+                id: 1,
+                name: Some("a".to_string())
+            }
+        );
+
+        let Json(value) = Json::<serde_json::Value>::try_from_row(rows.next().unwrap()).unwrap();
+        assert_eq!(value, serde_json::json!({"id": 2, "name": null}));
+
+        let row = json_rows(&["{"]).pop().unwrap();
+        assert!(Json::<serde_json::Value>::try_from_row(row).is_err());
+    }
+
+    #[test]
+    fn binds_json_text() {
+        let user = User { id: 1, name: None };
+
+        assert_eq!(
+            Value::from(Bytes::try_from(Json(&user)).unwrap()),
+            Value::from(r#"{"id":1,"name":null}"#)
+        );
+        assert_eq!(to_json(&user).unwrap(), r#"{"id":1,"name":null}"#);
+
+        let mut map = std::collections::HashMap::new();
+        map.insert((1, 2), "a");
+        assert!(Bytes::try_from(Json(map)).is_err());
+    }
+}
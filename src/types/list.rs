@@ -6,8 +6,11 @@ use crate::types::decimal::Decimal;
 use crate::types::map::Map;
 use crate::types::tuple::Tuple;
 use crate::types::udt::UDT;
-use crate::types::{AsRust, AsRustType, CBytes};
+use crate::types::{AsRust, AsRustType, CBytes, FromCqlValue, FromElement};
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::net::IpAddr;
+use time::PrimitiveDateTime;
 use uuid::Uuid;
 
 // TODO: consider using pointers to ColTypeOption and Vec<CBytes> instead of owning them.
@@ -33,6 +36,23 @@ impl List {
     {
         self.data.iter().map(f).collect()
     }
+
+    fn elements<T: FromElement>(&self) -> Result<Option<Vec<T>>> {
+        match self.metadata.value {
+            Some(ColTypeOptionValue::CList(ref type_option))
+            | Some(ColTypeOptionValue::CSet(ref type_option)) => self
+                .data
+                .iter()
+                .map(|bytes| T::from_element(type_option, bytes))
+                .collect::<Result<_>>()
+                .map(Some),
+            _ => Err(Error::General(format!(
+                "Invalid conversion. \
+                 Cannot convert {:?} into List (valid types: List, Set).",
+                self.metadata.value
+            ))),
+        }
+    }
 }
 
 impl AsRust for List {}
@@ -40,6 +60,7 @@ impl AsRust for List {}
 list_as_rust!(Blob);
 list_as_rust!(String);
 list_as_rust!(bool);
+list_as_rust!(i128);
 list_as_rust!(i64);
 list_as_rust!(i32);
 list_as_rust!(i16);
@@ -53,3 +74,116 @@ list_as_rust!(Map);
 list_as_rust!(UDT);
 list_as_rust!(Tuple);
 list_as_rust!(Decimal);
+#[cfg(feature = "chrono")]
+list_as_rust!(chrono::DateTime<chrono::Utc>, super::chrono::as_date_time);
+#[cfg(feature = "chrono")]
+list_as_rust!(chrono::NaiveDate, super::chrono::as_naive_date);
+#[cfg(feature = "chrono")]
+list_as_rust!(chrono::NaiveTime, super::chrono::as_naive_time);
+#[cfg(feature = "chrono")]
+list_as_rust!(chrono::Duration, super::chrono::as_duration);
+#[cfg(feature = "time")]
+list_as_rust!(time03::OffsetDateTime, super::time03::as_offset_date_time);
+#[cfg(feature = "time")]
+list_as_rust!(time03::Date, super::time03::as_date);
+#[cfg(feature = "time")]
+list_as_rust!(time03::Time, super::time03::as_time);
+#[cfg(feature = "time")]
+list_as_rust!(time03::Duration, super::time03::as_duration);
+#[cfg(feature = "bigdecimal")]
+list_as_rust!(bigdecimal::BigDecimal, super::bigdecimal::as_big_decimal);
+#[cfg(feature = "bigdecimal")]
+list_as_rust!(num_bigint::BigInt, super::bigdecimal::as_big_int);
+
+impl<T: FromCqlValue> AsRustType<Vec<T>> for List {
+    fn as_rust_type(&self) -> Result<Option<Vec<T>>> {
+        self.elements()
+    }
+}
+
+impl<T: FromElement> AsRustType<Vec<Vec<T>>> for List {
+    fn as_rust_type(&self) -> Result<Option<Vec<Vec<T>>>> {
+        self.elements()
+    }
+}
+
+impl<K: FromElement + Eq + Hash, V: FromElement> AsRustType<Vec<HashMap<K, V>>> for List {
+    fn as_rust_type(&self) -> Result<Option<Vec<HashMap<K, V>>>> {
+        self.elements()
+    }
+}
+
+fn null_element() -> Error {
+    Error::General("Collection element is null".into())
+}
+
+macro_rules! element {
+    // types which are not supported by `as_rust_type!` are decoded with given function
+    ($into_type:ty, $decode:path) => {
+        impl FromElement for $into_type {
+            fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Self> {
+                $decode(col_type, bytes)?.ok_or_else(null_element)
+            }
+        }
+    };
+    ($into_type:ident) => {
+        impl FromElement for $into_type {
+            fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Self> {
+                as_rust_type!(col_type, bytes, $into_type)?.ok_or_else(null_element)
+            }
+        }
+    };
+}
+
+element!(Blob);
+element!(String);
+element!(bool);
+element!(i128);
+element!(i64);
+element!(i32);
+element!(i16);
+element!(i8);
+element!(f64);
+element!(f32);
+element!(IpAddr);
+element!(Uuid);
+element!(PrimitiveDateTime);
+element!(List);
+element!(Map);
+element!(UDT);
+element!(Tuple);
+element!(Decimal);
+#[cfg(feature = "chrono")]
+element!(chrono::DateTime<chrono::Utc>, super::chrono::as_date_time);
+#[cfg(feature = "chrono")]
+element!(chrono::NaiveDate, super::chrono::as_naive_date);
+#[cfg(feature = "chrono")]
+element!(chrono::NaiveTime, super::chrono::as_naive_time);
+#[cfg(feature = "chrono")]
+element!(chrono::Duration, super::chrono::as_duration);
+#[cfg(feature = "time")]
+element!(time03::OffsetDateTime, super::time03::as_offset_date_time);
+#[cfg(feature = "time")]
+element!(time03::Date, super::time03::as_date);
+#[cfg(feature = "time")]
+element!(time03::Time, super::time03::as_time);
+#[cfg(feature = "time")]
+element!(time03::Duration, super::time03::as_duration);
+#[cfg(feature = "bigdecimal")]
+element!(bigdecimal::BigDecimal, super::bigdecimal::as_big_decimal);
+#[cfg(feature = "bigdecimal")]
+element!(num_bigint::BigInt, super::bigdecimal::as_big_int);
+
+impl<T: FromCqlValue> FromElement for T {
+    fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Self> {
+        T::from_cql_value(col_type, bytes)?.ok_or_else(null_element)
+    }
+}
+
+impl<T: FromElement> FromElement for Vec<T> {
+    fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Self> {
+        List::from_element(col_type, bytes)?
+            .elements()?
+            .ok_or_else(null_element)
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::hash::Hash;
 use std::net::IpAddr;
 use time::PrimitiveDateTime;
 use uuid::Uuid;
@@ -11,7 +12,7 @@ use crate::types::decimal::Decimal;
 use crate::types::list::List;
 use crate::types::tuple::Tuple;
 use crate::types::udt::UDT;
-use crate::types::{AsRust, AsRustType, CBytes};
+use crate::types::{AsRust, AsRustType, CBytes, FromCqlValue, FromElement};
 
 #[derive(Debug)]
 pub struct Map {
@@ -27,6 +28,31 @@ impl Map {
             data: data,
         }
     }
+
+    fn entries<K, V>(&self) -> Result<Option<HashMap<K, V>>>
+    where
+        K: FromElement + Eq + Hash,
+        V: FromElement,
+    {
+        match self.metadata.value {
+            Some(ColTypeOptionValue::CMap((ref key_type, ref value_type))) => self
+                .data
+                .iter()
+                .map(|(key, value)| {
+                    Ok((
+                        K::from_element(key_type, key)?,
+                        V::from_element(value_type, value)?,
+                    ))
+                })
+                .collect::<Result<_>>()
+                .map(Some),
+            _ => Err(Error::General(format!(
+                "Invalid conversion. \
+                 Cannot convert {:?} into Map (valid types: Map).",
+                self.metadata.value
+            ))),
+        }
+    }
 }
 
 impl AsRust for Map {}
@@ -233,3 +259,145 @@ map_as_rust!({ Tuple }, { Map });
 map_as_rust!({ Tuple }, { UDT });
 map_as_rust!({ Tuple }, { Tuple });
 map_as_rust!({ Tuple }, { Decimal });
+
+impl<K: FromElement + Eq + Hash, V: FromCqlValue> AsRustType<HashMap<K, V>> for Map {
+    fn as_rust_type(&self) -> Result<Option<HashMap<K, V>>> {
+        self.entries()
+    }
+}
+
+impl<K, T> AsRustType<HashMap<K, Vec<T>>> for Map
+where
+    K: FromElement + Eq + Hash,
+    T: FromElement,
+{
+    fn as_rust_type(&self) -> Result<Option<HashMap<K, Vec<T>>>> {
+        self.entries()
+    }
+}
+
+impl<K, L, V> AsRustType<HashMap<K, HashMap<L, V>>> for Map
+where
+    K: FromElement + Eq + Hash,
+    L: FromElement + Eq + Hash,
+    V: FromElement,
+{
+    fn as_rust_type(&self) -> Result<Option<HashMap<K, HashMap<L, V>>>> {
+        self.entries()
+    }
+}
+
+impl<K: FromElement + Eq + Hash, V: FromElement> FromElement for HashMap<K, V> {
+    fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Self> {
+        Map::from_element(col_type, bytes)?
+            .entries()?
+            .ok_or_else(|| Error::General("Collection element is null".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::frame_result::CUdt;
+    use crate::frame::TryFromUDT;
+    use crate::test_utils::{col_type, col_type_with};
+    use crate::types::value::{Bytes, Value};
+    use crate::types::{CString, IntoRustByName};
+
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        label: String,
+    }
+
+    impl TryFromUDT for Point {
+        fn try_from_udt(udt: UDT) -> Result<Self> {
+            Ok(Point {
+                x: udt.get_r_by_name("x")?,
+                label: udt.get_r_by_name("label")?,
+            })
+        }
+    }
+
+    fn map_type(key: ColTypeOption, value: ColTypeOption) -> ColTypeOption {
+        let value = ColTypeOptionValue::CMap((Box::new(key), Box::new(value)));
+        col_type_with(ColType::Map, value)
+    }
+
+    fn list_type(element: ColTypeOption) -> ColTypeOption {
+        let value = ColTypeOptionValue::CList(Box::new(element));
+        col_type_with(ColType::List, value)
+    }
+
+    fn decode<T: Into<Bytes>>(value: T, metadata: ColTypeOption) -> Map {
+        let bytes = Value::new_normal(value).body;
+        Map::new(decode_map(&bytes).unwrap(), metadata)
+    }
+
+    #[test]
+    fn converts_nested_collections_of_udts() {
+        let point_type = col_type_with(
+            ColType::Udt,
+            ColTypeOptionValue::UdtType(CUdt {
+                ks: CString::new("ks".into()),
+                udt_name: CString::new("point".into()),
+                descriptions: vec![
+                    (CString::new("x".into()), col_type(ColType::Int)),
+                    (CString::new("label".into()), col_type(ColType::Varchar)),
+                ],
+            }),
+        );
+        let metadata = map_type(col_type(ColType::Varchar), list_type(point_type));
+
+        // UDT values are encoded like tuples
+        let mut points = HashMap::new();
+        points.insert("a", vec![(1, "one"), (2, "two")]);
+        let map = decode(points, metadata);
+
+        let points: HashMap<String, Vec<Point>> = map.as_r_type().unwrap();
+        assert_eq!(
+            points["a"],
+            vec![
+                Point {
+                    x: 1,
+                    label: "one".into()
+                },
+                Point {
+                    x: 2,
+                    label: "two".into()
+                }
+            ]
+        );
+        assert!(AsRustType::<HashMap<String, Vec<Vec<i32>>>>::as_rust_type(&map).is_err());
+    }
+
+    #[test]
+    fn converts_nested_maps_and_lists() {
+        let inner_type = map_type(
+            col_type(ColType::Varchar),
+            list_type(col_type(ColType::Bigint)),
+        );
+        let metadata = map_type(col_type(ColType::Int), inner_type);
+
+        let mut inner = HashMap::new();
+        inner.insert("a".to_string(), vec![1i64, 2]);
+        inner.insert("b".to_string(), vec![]);
+        let mut outer = HashMap::new();
+        outer.insert(7, inner.clone());
+        let map = decode(outer.clone(), metadata);
+
+        let converted: HashMap<i32, HashMap<String, Vec<i64>>> = map.as_r_type().unwrap();
+        assert_eq!(converted, outer);
+
+        let lists = List::new(
+            vec![CBytes::new(Value::new_normal(inner.clone()).body)],
+            list_type(map_type(
+                col_type(ColType::Varchar),
+                list_type(col_type(ColType::Bigint)),
+            )),
+        );
+        let converted: Vec<HashMap<String, Vec<i64>>> = lists.as_r_type().unwrap();
+        assert_eq!(converted, vec![inner]);
+        assert!(AsRustType::<Vec<HashMap<String, Vec<i32>>>>::as_rust_type(&lists).is_err());
+    }
+}
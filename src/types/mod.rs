@@ -3,10 +3,12 @@ use std::io;
 use std::io::{Cursor, Read};
 use std::net::SocketAddr;
 
-use crate::error::{column_is_empty_err, Error as CDRSError, Result as CDRSResult};
+use crate::error::{null_value_err, Error as CDRSError, Result as CDRSResult};
+use crate::frame::frame_result::ColTypeOption;
 use crate::frame::traits::{FromBytes, FromCursor, IntoBytes};
 use crate::types::data_serialization_types::decode_inet;
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
+use bytes::Bytes;
 
 pub const LONG_STR_LEN: usize = 4;
 pub const SHORT_LEN: usize = 2;
@@ -15,28 +17,46 @@ pub const UUID_LEN: usize = 16;
 
 #[macro_use]
 pub mod blob;
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal;
+#[cfg(feature = "chrono")]
+mod chrono;
+pub mod cql_value;
 pub mod data_serialization_types;
 pub mod decimal;
+#[cfg(feature = "serde")]
+pub mod deserializer;
 pub mod from_cdrs;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod list;
 pub mod map;
 pub mod rows;
+#[cfg(feature = "time")]
+mod time03;
+pub mod timeuuid;
 pub mod tuple;
 pub mod udt;
 pub mod value;
+pub mod vector;
 
 pub mod prelude {
     pub use crate::error::{Error, Result};
-    pub use crate::frame::{TryFromRow, TryFromUDT};
+    pub use crate::frame::frame_result::{ColType, ColTypeOption};
+    pub use crate::frame::{TryFromRow, TryFromTuple, TryFromUDT};
     pub use crate::types::blob::Blob;
+    pub use crate::types::cql_value::CqlValue;
     pub use crate::types::decimal::Decimal;
+    #[cfg(feature = "json")]
+    pub use crate::types::json::Json;
     pub use crate::types::list::List;
     pub use crate::types::map::Map;
     pub use crate::types::rows::Row;
-    pub use crate::types::tuple::Tuple;
+    pub use crate::types::tuple::{CqlTuple, Tuple};
     pub use crate::types::udt::UDT;
-    pub use crate::types::value::{Bytes, Value};
-    pub use crate::types::AsRustType;
+    pub use crate::types::value::{Bytes, MaybeEmpty, Value};
+    pub use crate::types::vector::CqlVector;
+    pub use crate::types::{AsRustType, CBytes, FromCqlValue, FromElement, ToCqlValue};
 }
 
 /// Should be used to represent a single column as a Rust value.
@@ -49,6 +69,32 @@ pub trait AsRustType<T> {
     }
 }
 
+/// Conversion of elements of collections into Rust types. Implemented for types which list
+/// elements are converted into, and for `Vec` and `HashMap` of them, so nested collections,
+/// e.g. `map<text, frozen<list<frozen<udt>>>>`, are converted level by level, checking the type
+/// of each level.
+pub trait FromElement: Sized {
+    fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> CDRSResult<Self>;
+}
+
+/// Conversion of CQL values into application types, e.g. a `Money` newtype read from a
+/// `decimal` column. Implementors are read from rows, UDTs and tuples with `get_by_name()` and
+/// `get_by_index()`, and from elements of collections, like built-in types. The type of the
+/// column is given, so implementors can check it, and values are decoded from its bytes, e.g.
+/// with functions of `data_serialization_types`. It's implemented for all `TryFromUDT` types.
+pub trait FromCqlValue: Sized {
+    /// Converts a value of given type, returning `None` for null values.
+    fn from_cql_value(col_type: &ColTypeOption, bytes: &CBytes) -> CDRSResult<Option<Self>>;
+}
+
+/// Conversion of application types into CQL values, which makes them `Into<Bytes>`, so they're
+/// bound like built-in types. Types of bound columns aren't known when values are bound, so
+/// values are encoded as the type they're bound to, e.g. by converting them into a built-in
+/// type first.
+pub trait ToCqlValue {
+    fn to_cql_value(&self) -> value::Bytes;
+}
+
 pub trait AsRust {
     fn as_rust<R>(&self) -> CDRSResult<Option<R>>
     where
@@ -72,7 +118,7 @@ pub trait IntoRustByName<R> {
 
     fn get_r_by_name(&self, name: &str) -> CDRSResult<R> {
         self.get_by_name(name)
-            .and_then(|op| op.ok_or(column_is_empty_err(name)))
+            .and_then(|op| op.ok_or_else(|| null_value_err(name)))
     }
 }
 
@@ -89,7 +135,7 @@ pub trait ByName {
         Self: IntoRustByName<R>,
     {
         self.by_name(name)
-            .and_then(|op| op.ok_or(column_is_empty_err(name)))
+            .and_then(|op| op.ok_or_else(|| null_value_err(name)))
     }
 }
 
@@ -99,7 +145,7 @@ pub trait IntoRustByIndex<R> {
 
     fn get_r_by_index(&self, index: usize) -> CDRSResult<R> {
         self.get_by_index(index)
-            .and_then(|op| op.ok_or(column_is_empty_err(index)))
+            .and_then(|op| op.ok_or_else(|| null_value_err(index)))
     }
 }
 
@@ -116,7 +162,7 @@ pub trait ByIndex {
         Self: IntoRustByIndex<R>,
     {
         self.by_index(index)
-            .and_then(|op| op.ok_or(column_is_empty_err(index)))
+            .and_then(|op| op.ok_or_else(|| null_value_err(index)))
     }
 }
 
@@ -277,36 +323,43 @@ pub fn to_bigint(int: i64) -> Vec<u8> {
 
 /// Converts integer into Cassandra's [varint].
 pub fn to_varint(int: i64) -> Vec<u8> {
-    if int == 0 {
-        return vec![0];
-    }
-
-    let mut int_bytes = to_bigint(int);
-    match int.signum() {
-        1 => {
-            int_bytes = int_bytes.into_iter().skip_while(|b| *b == 0x00).collect();
-            if int_bytes
-                .get(0)
-                .map(|b| b.leading_zeros() == 0)
-                .unwrap_or(true)
-            {
-                int_bytes.insert(0, 0x00);
-            }
-        }
-        -1 => {
-            int_bytes = int_bytes.into_iter().skip_while(|b| *b == 0xFF).collect();
-            if int_bytes
-                .get(0)
-                .map(|b| b.leading_zeros() > 0)
-                .unwrap_or(true)
-            {
-                int_bytes.insert(0, 0xFF);
-            }
+    to_varint_i128(int.into())
+}
+
+/// Converts integer into Cassandra's [varint], encoded with the least number of bytes.
+pub fn to_varint_i128(int: i128) -> Vec<u8> {
+    let bytes = int.to_be_bytes();
+    let sign = if int < 0 { 0xFF } else { 0x00 };
+    let mut start = 0;
+    while start < bytes.len() - 1
+        && bytes[start] == sign
+        && (bytes[start + 1] ^ sign) & 0x80 == 0
+    {
+        start += 1;
+    }
+
+    bytes[start..].to_vec()
+}
+
+/// Converts months, days and nanoseconds into Cassandra's `duration`, each encoded as a
+/// zigzag-encoded variable length integer.
+pub fn to_duration(months: i32, days: i32, nanos: i64) -> Vec<u8> {
+    let mut bytes = vec![];
+    for value in [months as i64, days as i64, nanos].iter() {
+        let value = ((value << 1) ^ (value >> 63)) as u64;
+        let bits = 64 - (value | 1).leading_zeros() as usize;
+        let size = bits.div_ceil(7).min(9);
+        let mut value_bytes = value.to_be_bytes()[8 - size.min(8)..].to_vec();
+        if size == 9 {
+            value_bytes.insert(0, 0xFF);
+        } else {
+            // the number of extra bytes is given by leading ones of the first byte
+            value_bytes[0] |= !(0xFFu8 >> (size - 1));
         }
-        _ => unreachable!(),
+        bytes.extend(value_bytes);
     }
 
-    int_bytes
+    bytes
 }
 
 /// Converts number i16 into Cassandra's `short`.
@@ -523,41 +576,90 @@ impl FromCursor for CStringList {
 //
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
-/// The structure that represents Cassandra byte type.
+/// The structure that represents Cassandra byte type. Values of rows share the buffer of the
+/// response they come from, so cloning them or reading them as `Bytes` doesn't copy them.
 pub struct CBytes {
-    bytes: Option<Vec<u8>>,
+    bytes: Option<Bytes>,
 }
 
 impl CBytes {
     pub fn new(bytes: Vec<u8>) -> CBytes {
-        CBytes { bytes: Some(bytes) }
+        CBytes {
+            bytes: Some(bytes.into()),
+        }
     }
 
-    /// Creates Cassandra bytes that represent empty or null value
+    /// Reads Cassandra bytes which `cursor`, reading `buffer`, is positioned at, as a slice of
+    /// `buffer` instead of a copy.
+    pub(crate) fn from_buffer(cursor: &mut Cursor<&[u8]>, buffer: &Bytes) -> CDRSResult<CBytes> {
+        let len = CInt::from_cursor(cursor)?;
+        // null or not set value
+        if len < 0 {
+            return Ok(CBytes { bytes: None });
+        }
+
+        let start = cursor.position() as usize;
+        let end = start + len as usize;
+        if end > buffer.len() {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        cursor.set_position(end as u64);
+        Ok(CBytes {
+            bytes: Some(buffer.slice(start..end)),
+        })
+    }
+
+    /// Creates Cassandra bytes that represent null value
     pub fn new_empty() -> CBytes {
         CBytes { bytes: None }
     }
 
     /// Converts `CBytes` into a plain array of bytes
     pub fn into_plain(self) -> Option<Vec<u8>> {
-        self.bytes
+        self.bytes.map(Vec::from)
     }
 
     // TODO: try to replace usage of `as_plain` by `as_slice`
     pub fn as_plain(&self) -> Option<Vec<u8>> {
-        self.bytes.clone()
+        self.bytes.as_ref().map(|bytes| bytes.to_vec())
     }
     pub fn as_slice(&self) -> Option<&[u8]> {
-        match self.bytes {
-            Some(ref v) => Some(v.as_slice()),
-            None => None,
+        self.bytes.as_deref()
+    }
+
+    /// Returns the bytes, sharing the buffer they're stored in.
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        self.bytes.as_ref()
+    }
+
+    /// Copies the bytes into a buffer of their own, so they don't keep the rest of the buffer
+    /// they're stored in, e.g. the whole response, alive.
+    pub fn into_owned(self) -> CBytes {
+        CBytes {
+            bytes: self.bytes.map(|bytes| Bytes::copy_from_slice(&bytes)),
         }
-        // self.bytes.map(|v| v.as_slice())
     }
+    /// Checks if the value is empty, i.e. of zero length. Nulls aren't empty.
     pub fn is_empty(&self) -> bool {
-        match &self.bytes {
-            None => true,
-            Some(bytes) => bytes.is_empty()
+        matches!(&self.bytes, Some(bytes) if bytes.is_empty())
+    }
+
+    /// Checks if the value is null or not set.
+    pub fn is_null(&self) -> bool {
+        self.bytes.is_none()
+    }
+
+    /// Returns bytes of a value of a type which has no empty representation, e.g. `int`, and
+    /// fails if the value is empty, as reading it as any value of the type would corrupt it.
+    pub(crate) fn non_empty_slice(&self) -> CDRSResult<Option<&[u8]>> {
+        match self.as_slice() {
+            Some([]) => Err(CDRSError::General(
+                "Value is empty. Empty values of types other than text and blob can only be read \
+                 as `MaybeEmpty`."
+                    .to_string(),
+            )),
+            bytes => Ok(bytes),
         }
     }
 }
@@ -584,7 +686,7 @@ impl IntoBytes for CBytes {
                 let mut v: Vec<u8> = vec![];
                 let l = b.len() as i32;
                 v.extend_from_slice(to_int(l).as_slice());
-                v.extend_from_slice(b.as_slice());
+                v.extend_from_slice(b);
                 v
             }
             None => vec![],
@@ -673,7 +775,7 @@ impl FromBytes for Vec<u8> {
 
 /// The structure wich represets Cassandra [inet]
 /// (https://github.com/apache/cassandra/blob/trunk/doc/native_protocol_v4.spec#L222).
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CInet {
     pub addr: SocketAddr,
 }
@@ -907,5 +1009,8 @@ mod tests {
         assert_eq!(to_varint(-1), vec![0xFF]);
         assert_eq!(to_varint(-128), vec![0x80]);
         assert_eq!(to_varint(-129), vec![0xFF, 0x7F]);
+        assert_eq!(to_varint(i64::MIN), i64::MIN.to_be_bytes().to_vec());
+        assert_eq!(to_varint_i128(1 << 64), vec![0x01, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(to_varint_i128(i128::MAX), i128::MAX.to_be_bytes().to_vec());
     }
 }
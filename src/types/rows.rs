@@ -1,20 +1,27 @@
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::net::IpAddr;
 use std::num::{NonZeroI16, NonZeroI8, NonZeroI32, NonZeroI64};
 use time::PrimitiveDateTime;
 use uuid::Uuid;
 
-use crate::error::{column_is_empty_err, Error, Result};
+use crate::error::{column_not_found_err, invalid_conversion_err, Error, Result};
 use crate::frame::frame_result::{
     BodyResResultRows, ColSpec, ColType, ColTypeOption, ColTypeOptionValue, RowsMetadata,
 };
 use crate::types::blob::Blob;
+use crate::types::cql_value::CqlValue;
 use crate::types::data_serialization_types::*;
 use crate::types::decimal::Decimal;
 use crate::types::list::List;
 use crate::types::map::Map;
 use crate::types::tuple::Tuple;
 use crate::types::udt::UDT;
-use crate::types::{ByIndex, ByName, CBytes, IntoRustByIndex, IntoRustByName};
+use crate::types::value::MaybeEmpty;
+use crate::types::vector::into_array;
+use crate::types::{
+    AsRustType, ByIndex, ByName, CBytes, FromCqlValue, IntoRustByIndex, IntoRustByName,
+};
 
 #[derive(Clone, Debug)]
 pub struct Row {
@@ -33,6 +40,67 @@ impl Row {
             .collect()
     }
 
+    /// Returns the metadata of the row, i.e. names and types of its columns.
+    pub fn metadata(&self) -> &RowsMetadata {
+        &self.metadata
+    }
+
+    /// Checks if the row has a column with given name.
+    pub fn contains_column(&self, name: &str) -> bool {
+        self.get_col_spec_by_name(name).is_some()
+    }
+
+    /// Returns specs and values of all columns of the row.
+    pub(crate) fn columns(&self) -> impl Iterator<Item = (&ColSpec, &CBytes)> {
+        self.metadata.col_specs.iter().zip(self.row_content.iter())
+    }
+
+    /// Returns the number of columns of the row.
+    pub(crate) fn columns_count(&self) -> usize {
+        self.row_content.len()
+    }
+
+    /// Returns the value of a text column with given name, borrowed from the row instead of
+    /// copied into a `String`.
+    pub fn get_str_by_name(&self, name: &str) -> Result<Option<&str>> {
+        self.get_col_spec_by_name(name)
+            .ok_or_else(|| column_not_found_err(name))
+            .and_then(|(col_spec, cbytes)| as_str(&col_spec.col_type, cbytes))
+    }
+
+    /// Returns the value of a text column with given index, borrowed from the row instead of
+    /// copied into a `String`.
+    pub fn get_str_by_index(&self, index: usize) -> Result<Option<&str>> {
+        self.get_col_spec_by_index(index)
+            .ok_or_else(|| column_not_found_err(index))
+            .and_then(|(col_spec, cbytes)| as_str(&col_spec.col_type, cbytes))
+    }
+
+    /// Converts values of all columns of the row into `CqlValue`s by column names, for rows of
+    /// any schema. Null values are converted into `CqlValue::Null`.
+    pub fn to_cql_values(&self) -> Result<HashMap<String, CqlValue>> {
+        self.columns()
+            .map(|(col_spec, value)| {
+                let value = CqlValue::from_cql_value(&col_spec.col_type, value)?;
+                Ok((col_spec.name.as_plain(), value.unwrap_or(CqlValue::Null)))
+            })
+            .collect()
+    }
+
+    /// Copies values of the row into buffers of their own. Values of rows are slices of the
+    /// response they come from, which stays in memory as long as any of them does, so rows
+    /// kept longer than the response, e.g. a few rows of a large page, should be owned.
+    pub fn into_owned(self) -> Row {
+        Row {
+            metadata: self.metadata,
+            row_content: self
+                .row_content
+                .into_iter()
+                .map(CBytes::into_owned)
+                .collect(),
+        }
+    }
+
     fn get_col_spec_by_name(&self, name: &str) -> Option<(&ColSpec, &CBytes)> {
         self.metadata
             .col_specs
@@ -52,11 +120,35 @@ impl Row {
     }
 }
 
+/// Reads a blob value, or a value of a custom type, as a slice of the buffer it's stored in
+/// instead of a copy.
+fn as_shared_bytes(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<bytes::Bytes>> {
+    match col_type.id {
+        ColType::Blob | ColType::Custom => Ok(bytes.as_bytes().cloned()),
+        _ => Err(invalid_conversion_err(col_type, "Bytes", "Blob, Custom")),
+    }
+}
+
+fn as_str<'a>(col_type: &ColTypeOption, bytes: &'a CBytes) -> Result<Option<&'a str>> {
+    match col_type.id {
+        ColType::Ascii | ColType::Varchar => bytes
+            .as_slice()
+            .map(|bytes| {
+                std::str::from_utf8(bytes)
+                    .map_err(|err| Error::General(format!("Invalid text value: {}", err)))
+            })
+            .transpose(),
+        _ => Err(invalid_conversion_err(col_type, "&str", "Ascii, Varchar")),
+    }
+}
+
 impl ByName for Row {}
 
 into_rust_by_name!(Row, Blob);
+into_rust_by_name!(Row, bytes::Bytes, as_shared_bytes);
 into_rust_by_name!(Row, String);
 into_rust_by_name!(Row, bool);
+into_rust_by_name!(Row, i128);
 into_rust_by_name!(Row, i64);
 into_rust_by_name!(Row, i32);
 into_rust_by_name!(Row, i16);
@@ -75,12 +167,34 @@ into_rust_by_name!(Row, NonZeroI8);
 into_rust_by_name!(Row, NonZeroI16);
 into_rust_by_name!(Row, NonZeroI32);
 into_rust_by_name!(Row, NonZeroI64);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(Row, chrono::DateTime<chrono::Utc>, super::chrono::as_date_time);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(Row, chrono::NaiveDate, super::chrono::as_naive_date);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(Row, chrono::NaiveTime, super::chrono::as_naive_time);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(Row, chrono::Duration, super::chrono::as_duration);
+#[cfg(feature = "time")]
+into_rust_by_name!(Row, time03::OffsetDateTime, super::time03::as_offset_date_time);
+#[cfg(feature = "time")]
+into_rust_by_name!(Row, time03::Date, super::time03::as_date);
+#[cfg(feature = "time")]
+into_rust_by_name!(Row, time03::Time, super::time03::as_time);
+#[cfg(feature = "time")]
+into_rust_by_name!(Row, time03::Duration, super::time03::as_duration);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_name!(Row, bigdecimal::BigDecimal, super::bigdecimal::as_big_decimal);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_name!(Row, num_bigint::BigInt, super::bigdecimal::as_big_int);
 
 impl ByIndex for Row {}
 
 into_rust_by_index!(Row, Blob);
+into_rust_by_index!(Row, bytes::Bytes, as_shared_bytes);
 into_rust_by_index!(Row, String);
 into_rust_by_index!(Row, bool);
+into_rust_by_index!(Row, i128);
 into_rust_by_index!(Row, i64);
 into_rust_by_index!(Row, i32);
 into_rust_by_index!(Row, i16);
@@ -99,3 +213,280 @@ into_rust_by_index!(Row, NonZeroI8);
 into_rust_by_index!(Row, NonZeroI16);
 into_rust_by_index!(Row, NonZeroI32);
 into_rust_by_index!(Row, NonZeroI64);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Row, chrono::DateTime<chrono::Utc>, super::chrono::as_date_time);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Row, chrono::NaiveDate, super::chrono::as_naive_date);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Row, chrono::NaiveTime, super::chrono::as_naive_time);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Row, chrono::Duration, super::chrono::as_duration);
+#[cfg(feature = "time")]
+into_rust_by_index!(Row, time03::OffsetDateTime, super::time03::as_offset_date_time);
+#[cfg(feature = "time")]
+into_rust_by_index!(Row, time03::Date, super::time03::as_date);
+#[cfg(feature = "time")]
+into_rust_by_index!(Row, time03::Time, super::time03::as_time);
+#[cfg(feature = "time")]
+into_rust_by_index!(Row, time03::Duration, super::time03::as_duration);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_index!(Row, bigdecimal::BigDecimal, super::bigdecimal::as_big_decimal);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_index!(Row, num_bigint::BigInt, super::bigdecimal::as_big_int);
+
+impl<T: FromCqlValue> IntoRustByName<T> for Row {
+    fn get_by_name(&self, name: &str) -> Result<Option<T>> {
+        let (col_spec, value) = self
+            .get_col_spec_by_name(name)
+            .ok_or_else(|| column_not_found_err(name))?;
+        T::from_cql_value(&col_spec.col_type, value)
+    }
+}
+
+impl<T> IntoRustByName<MaybeEmpty<T>> for Row
+where
+    Row: IntoRustByName<T>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<MaybeEmpty<T>>> {
+        match self.get_col_spec_by_name(name) {
+            Some((_, value)) if value.is_empty() => Ok(Some(MaybeEmpty::Empty)),
+            _ => IntoRustByName::<T>::get_by_name(self, name).map(|v| v.map(MaybeEmpty::Value)),
+        }
+    }
+}
+
+impl<T> IntoRustByName<Vec<T>> for Row
+where
+    List: AsRustType<Vec<T>>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<Vec<T>>> {
+        match IntoRustByName::<List>::get_by_name(self, name)? {
+            Some(list) => list.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Lists and vectors are converted into arrays of the same length.
+impl<T, const N: usize> IntoRustByName<[T; N]> for Row
+where
+    List: AsRustType<Vec<T>>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<[T; N]>> {
+        IntoRustByName::<Vec<T>>::get_by_name(self, name)?
+            .map(into_array)
+            .transpose()
+    }
+}
+
+impl<K: Eq + Hash, V> IntoRustByName<HashMap<K, V>> for Row
+where
+    Map: AsRustType<HashMap<K, V>>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<HashMap<K, V>>> {
+        match IntoRustByName::<Map>::get_by_name(self, name)? {
+            Some(map) => map.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T: FromCqlValue> IntoRustByIndex<T> for Row {
+    fn get_by_index(&self, index: usize) -> Result<Option<T>> {
+        let (col_spec, value) = self
+            .get_col_spec_by_index(index)
+            .ok_or_else(|| column_not_found_err(index))?;
+        T::from_cql_value(&col_spec.col_type, value)
+    }
+}
+
+impl<T> IntoRustByIndex<MaybeEmpty<T>> for Row
+where
+    Row: IntoRustByIndex<T>,
+{
+    fn get_by_index(&self, index: usize) -> Result<Option<MaybeEmpty<T>>> {
+        match self.get_col_spec_by_index(index) {
+            Some((_, value)) if value.is_empty() => Ok(Some(MaybeEmpty::Empty)),
+            _ => IntoRustByIndex::<T>::get_by_index(self, index).map(|v| v.map(MaybeEmpty::Value)),
+        }
+    }
+}
+
+impl<T> IntoRustByIndex<Vec<T>> for Row
+where
+    List: AsRustType<Vec<T>>,
+{
+    fn get_by_index(&self, index: usize) -> Result<Option<Vec<T>>> {
+        match IntoRustByIndex::<List>::get_by_index(self, index)? {
+            Some(list) => list.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T, const N: usize> IntoRustByIndex<[T; N]> for Row
+where
+    List: AsRustType<Vec<T>>,
+{
+    fn get_by_index(&self, index: usize) -> Result<Option<[T; N]>> {
+        IntoRustByIndex::<Vec<T>>::get_by_index(self, index)?
+            .map(into_array)
+            .transpose()
+    }
+}
+
+impl<K: Eq + Hash, V> IntoRustByIndex<HashMap<K, V>> for Row
+where
+    Map: AsRustType<HashMap<K, V>>,
+{
+    fn get_by_index(&self, index: usize) -> Result<Option<HashMap<K, V>>> {
+        match IntoRustByIndex::<Map>::get_by_index(self, index)? {
+            Some(map) => map.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::TryFromRow;
+    use crate::test_utils::row;
+    use crate::types::value::{Bytes, Value};
+    use crate::types::ToCqlValue;
+
+    #[test]
+    fn distinguishes_null_empty_and_missing_values() {
+        let row = row(vec![
+            ("null", vec![0, 0x09], None),
+            ("empty", vec![0, 0x09], Some(vec![])),
+            ("text", vec![0, 0x0D], Some(vec![])),
+        ]);
+
+        assert_eq!(row.by_name::<i32>("null").unwrap(), None);
+        assert!(matches!(
+            row.r_by_name::<i32>("null"),
+            Err(Error::General(_))
+        ));
+        assert!(row.by_name::<i32>("empty").is_err());
+        assert_eq!(
+            row.by_name::<MaybeEmpty<i32>>("empty").unwrap(),
+            Some(MaybeEmpty::Empty)
+        );
+        assert_eq!(row.by_name::<MaybeEmpty<i32>>("null").unwrap(), None);
+        assert_eq!(row.r_by_name::<String>("text").unwrap(), String::new());
+        assert_eq!(row.get_str_by_index(2).unwrap(), Some(""));
+
+        assert!(matches!(
+            row.by_name::<i32>("missing"),
+            Err(Error::MissingColumn(ref name)) if name == "missing"
+        ));
+        assert!(matches!(
+            row.by_index::<i32>(3),
+            Err(Error::MissingColumn(_))
+        ));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Money(Decimal);
+
+    impl FromCqlValue for Money {
+        fn from_cql_value(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Self>> {
+            match col_type.id {
+                ColType::Decimal => {
+                    Ok(bytes.as_slice().map(decode_decimal).transpose()?.map(Money))
+                }
+                _ => Err("Money is stored as decimal".into()),
+            }
+        }
+    }
+
+    impl ToCqlValue for Money {
+        fn to_cql_value(&self) -> Bytes {
+            self.0.clone().into()
+        }
+    }
+
+    #[test]
+    fn converts_application_types() {
+        let price = Value::new_normal(Money(Decimal::new(1250, 2))).body;
+        let row = row(vec![
+            ("price", vec![0, 0x06], Some(price)),
+            ("null", vec![0, 0x06], None),
+            ("count", vec![0, 0x09], Some(vec![0, 0, 0, 1])),
+        ]);
+
+        assert_eq!(
+            row.by_name::<Money>("price").unwrap(),
+            Some(Money(Decimal::new(1250, 2)))
+        );
+        assert_eq!(row.by_index::<Money>(1).unwrap(), None);
+        assert!(row.by_name::<Money>("count").is_err());
+        assert!(matches!(
+            row.by_name::<Money>("missing"),
+            Err(Error::MissingColumn(_))
+        ));
+    }
+
+    #[test]
+    fn converts_rows_into_cql_values() {
+        let row = row(vec![
+            ("id", vec![0, 0x09], Some(vec![0, 0, 0, 1])),
+            ("name", vec![0, 0x0D], Some(b"a".to_vec())),
+            ("score", vec![0, 0x09], None),
+        ]);
+        let values = HashMap::<String, CqlValue>::try_from_row(row).unwrap();
+
+        assert_eq!(values.len(), 3);
+        assert_eq!(values["id"], CqlValue::Int(1));
+        assert_eq!(values["name"], CqlValue::Text("a".to_string()));
+        assert!(values["score"].is_null());
+    }
+
+    #[test]
+    fn widens_integers_and_reads_large_varints() {
+        let large = Value::new_normal(i128::from(i64::MAX) + 1).body;
+        let row = row(vec![
+            ("tiny", vec![0, 0x14], Some(vec![0xFF])),
+            ("small", vec![0, 0x13], Some(vec![0x01, 0x00])),
+            ("large", vec![0, 0x0E], Some(large)),
+        ]);
+
+        assert_eq!(row.r_by_name::<i8>("tiny").unwrap(), -1);
+        assert_eq!(row.r_by_name::<i16>("tiny").unwrap(), -1);
+        assert_eq!(row.r_by_name::<i64>("tiny").unwrap(), -1);
+        assert_eq!(row.r_by_name::<i32>("small").unwrap(), 256);
+        assert_eq!(row.r_by_index::<i128>(1).unwrap(), 256);
+        assert!(row.by_name::<i8>("small").is_err());
+
+        assert_eq!(
+            row.r_by_name::<i128>("large").unwrap(),
+            i128::from(i64::MAX) + 1
+        );
+        assert!(row.by_name::<i64>("large").is_err());
+    }
+
+    #[test]
+    fn passes_custom_values_through() {
+        let row = row(vec![
+            ("id", vec![0, 0x09], Some(vec![0, 0, 0, 1])),
+            ("point", vec![0, 0x00, 0, 0], Some(vec![0xCA, 0xFE])),
+        ]);
+
+        assert_eq!(
+            row.r_by_name::<Blob>("point").unwrap().into_vec(),
+            vec![0xCA, 0xFE]
+        );
+        assert_eq!(
+            row.r_by_index::<bytes::Bytes>(1).unwrap(),
+            bytes::Bytes::from_static(&[0xCA, 0xFE])
+        );
+        assert_eq!(
+            row.to_cql_values().unwrap()["point"],
+            CqlValue::Custom {
+                class: String::new(),
+                bytes: vec![0xCA, 0xFE]
+            }
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Conversions between CQL `timestamp`, `date`, `time` and `duration` values and types of the
+//! `time` crate 0.3, enabled with the `time` feature.
+
+use std::convert::TryFrom;
+use time03::{Date, Duration, OffsetDateTime, Time};
+
+use crate::error::{invalid_conversion_err, Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption, ColTypeOptionValue};
+use crate::types::data_serialization_types::*;
+use crate::types::value::Bytes;
+use crate::types::{to_bigint, to_duration, to_u, CBytes};
+
+/// Julian day of the unix epoch.
+const UNIX_EPOCH_JULIAN_DAY: i64 = 2_440_588;
+
+const NANOS_PER_MILLI: i128 = 1_000_000;
+
+pub(crate) fn as_offset_date_time(
+    col_type: &ColTypeOption,
+    bytes: &CBytes,
+) -> Result<Option<OffsetDateTime>> {
+    if !matches!(col_type.id, ColType::Timestamp) {
+        return Err(invalid_conversion_err(
+            col_type,
+            "OffsetDateTime",
+            "Timestamp",
+        ));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => {
+            let millis = decode_timestamp(bytes)?;
+            OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * NANOS_PER_MILLI)
+                .map(Some)
+                .map_err(|_| Error::General(format!("Timestamp {} is out of range", millis)))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_date(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Date>> {
+    if !matches!(col_type.id, ColType::Date) {
+        return Err(invalid_conversion_err(col_type, "Date", "Date"));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => {
+            let days = decode_date(bytes)? as u32 as i64 - EPOCH_DAY;
+            i32::try_from(days + UNIX_EPOCH_JULIAN_DAY)
+                .ok()
+                .and_then(|julian_day| Date::from_julian_day(julian_day).ok())
+                .map(Some)
+                .ok_or_else(|| Error::General(format!("Date {} is out of range", days)))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_time(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Time>> {
+    if !matches!(col_type.id, ColType::Time) {
+        return Err(invalid_conversion_err(col_type, "Time", "Time"));
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => {
+            let nanos = decode_time(bytes)?;
+            if !(0..NANOS_PER_DAY).contains(&nanos) {
+                return Err(Error::General(format!("Time {} is out of range", nanos)));
+            }
+
+            let time = Time::MIDNIGHT + Duration::nanoseconds(nanos);
+            Ok(Some(time))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn as_duration(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Duration>> {
+    match (&col_type.id, &col_type.value) {
+        (ColType::Custom, Some(ColTypeOptionValue::CString(class)))
+            if class.as_str() == DURATION_TYPE => {}
+        _ => return Err(invalid_conversion_err(col_type, "Duration", "Duration")),
+    }
+
+    match bytes.non_empty_slice()? {
+        Some(bytes) => match decode_duration(bytes)? {
+            (0, days, nanos) => Ok(Some(
+                Duration::days(days as i64) + Duration::nanoseconds(nanos),
+            )),
+            // months have different numbers of days
+            (months, _, _) => Err(Error::General(format!(
+                "Duration of {} months cannot be converted into Duration",
+                months
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+impl From<OffsetDateTime> for Bytes {
+    fn from(value: OffsetDateTime) -> Bytes {
+        let millis = value.unix_timestamp_nanos().div_euclid(NANOS_PER_MILLI);
+        Bytes::new(to_bigint(millis as i64))
+    }
+}
+
+impl TryFrom<Date> for Bytes {
+    type Error = Error;
+
+    fn try_from(value: Date) -> Result<Bytes> {
+        let days = value.to_julian_day() as i64 - UNIX_EPOCH_JULIAN_DAY;
+        u32::try_from(days + EPOCH_DAY)
+            .map(|days| Bytes::new(to_u(days)))
+            .map_err(|_| Error::General(format!("Date {} is out of range", value)))
+    }
+}
+
+impl From<Time> for Bytes {
+    fn from(value: Time) -> Bytes {
+        let nanos = (value - Time::MIDNIGHT).whole_nanoseconds();
+        Bytes::new(to_bigint(nanos as i64))
+    }
+}
+
+/// Durations are encoded as days and nanoseconds, without months.
+impl TryFrom<Duration> for Bytes {
+    type Error = Error;
+
+    fn try_from(value: Duration) -> Result<Bytes> {
+        let days = value.whole_days();
+        let nanos = (value - Duration::days(days)).whole_nanoseconds();
+        i32::try_from(days)
+            .map(|days| Bytes::new(to_duration(0, days, nanos as i64)))
+            .map_err(|_| Error::General(format!("Duration of {} days is out of range", days)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time03::Month;
+
+    use super::*;
+    use crate::test_utils::{col_type, encode, try_encode};
+    use crate::types::CString;
+
+    #[test]
+    fn converts_timestamps() {
+        let timestamp = OffsetDateTime::from_unix_timestamp_nanos(-1_500_000_000).unwrap();
+        let bytes = encode(timestamp);
+
+        assert_eq!(bytes.as_slice().unwrap(), &to_bigint(-1_500)[..]);
+        assert_eq!(
+            as_offset_date_time(&col_type(ColType::Timestamp), &bytes).unwrap(),
+            Some(timestamp)
+        );
+        assert!(as_offset_date_time(&col_type(ColType::Bigint), &bytes).is_err());
+    }
+
+    #[test]
+    fn converts_dates_from_epoch_days() {
+        let epoch = Date::from_calendar_date(1970, Month::January, 1).unwrap();
+        assert_eq!(try_encode(epoch).as_slice().unwrap(), &[0x80, 0, 0, 0]);
+
+        let day_before = Date::from_calendar_date(1969, Month::December, 31).unwrap();
+        assert_eq!(
+            try_encode(day_before).as_slice().unwrap(),
+            &[0x7F, 0xFF, 0xFF, 0xFF]
+        );
+
+        for date in &[
+            day_before,
+            Date::from_calendar_date(2021, Month::September, 20).unwrap(),
+        ] {
+            assert_eq!(
+                as_date(&col_type(ColType::Date), &try_encode(*date)).unwrap(),
+                Some(*date)
+            );
+        }
+        assert!(as_date(&col_type(ColType::Date), &CBytes::new(to_u(u32::MAX))).is_err());
+    }
+
+    #[test]
+    fn converts_times() {
+        let time = Time::from_hms_nano(23, 59, 59, 999_999_999).unwrap();
+
+        assert_eq!(
+            as_time(&col_type(ColType::Time), &encode(time)).unwrap(),
+            Some(time)
+        );
+        assert!(as_time(
+            &col_type(ColType::Time),
+            &CBytes::new(to_bigint(NANOS_PER_DAY))
+        )
+        .is_err());
+        assert_eq!(
+            as_time(&col_type(ColType::Time), &CBytes::new_empty()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn converts_durations() {
+        let duration_type = ColTypeOption {
+            id: ColType::Custom,
+            value: Some(ColTypeOptionValue::CString(CString::new(
+                DURATION_TYPE.to_string(),
+            ))),
+        };
+        let duration = -(Duration::days(3) + Duration::nanoseconds(42));
+
+        assert_eq!(
+            as_duration(&duration_type, &try_encode(duration)).unwrap(),
+            Some(duration)
+        );
+        assert!(as_duration(&duration_type, &CBytes::new(to_duration(1, 0, 0))).is_err());
+        assert!(Bytes::try_from(Duration::days(i32::MAX as i64 + 1)).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Generation and ordering of `timeuuid` values, i.e. version 1 UUIDs.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::sync::atomic::{self, AtomicU64};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Number of 100 nanosecond intervals between the start of the Gregorian calendar
+/// (1582-10-15), which timeuuids count from, and the unix epoch.
+const GREGORIAN_EPOCH_OFFSET: u64 = 0x01B2_1DD2_1381_4000;
+
+/// Timeuuids store times in 60 bits.
+const MAX_TICKS: u64 = 0x0FFF_FFFF_FFFF_FFFF;
+
+const TICKS_PER_MILLI: u64 = 10_000;
+
+/// Clock sequences and nodes of `min_timeuuid()` and `max_timeuuid()`, which are the lowest
+/// and highest ones in Cassandra's ordering.
+const MIN_CLOCK_SEQ_AND_NODE: [u8; 8] = [0x80; 8];
+const MAX_CLOCK_SEQ_AND_NODE: [u8; 8] = [0x7F; 8];
+
+/// Generator of timeuuids with a fixed node id and clock sequence. Timeuuids of a generator are
+/// unique and increasing, even if generated within the same 100 nanoseconds or if the system
+/// clock goes back.
+#[derive(Debug)]
+pub struct TimeUuidGenerator {
+    clock_seq_and_node: [u8; 8],
+    last: AtomicU64,
+}
+
+impl TimeUuidGenerator {
+    /// Creates a generator with a random node id, which has the multicast bit set, so it
+    /// doesn't collide with MAC addresses, and a random clock sequence.
+    pub fn new() -> Self {
+        let mut node = [0; 6];
+        node.copy_from_slice(&rand::random::<u64>().to_be_bytes()[2..]);
+        node[0] |= 0x01;
+
+        Self::with_node(node, rand::random())
+    }
+
+    /// Creates a generator with given node id, e.g. a MAC address, and clock sequence, of which
+    /// only the lower 14 bits are used.
+    pub fn with_node(node: [u8; 6], clock_seq: u16) -> Self {
+        let mut clock_seq_and_node = [0; 8];
+        clock_seq_and_node[0] = ((clock_seq >> 8) as u8 & 0x3F) | 0x80;
+        clock_seq_and_node[1] = clock_seq as u8;
+        clock_seq_and_node[2..].copy_from_slice(&node);
+
+        TimeUuidGenerator {
+            clock_seq_and_node,
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Generates a timeuuid of the current time.
+    pub fn generate(&self) -> Uuid {
+        let now = ticks(SystemTime::now());
+
+        // the closure always returns a value, so the update can't fail
+        let last = self
+            .last
+            .fetch_update(atomic::Ordering::SeqCst, atomic::Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_else(|last| last);
+
+        from_ticks(now.max(last + 1), self.clock_seq_and_node)
+    }
+}
+
+impl Default for TimeUuidGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the lowest timeuuid of given time, at millisecond precision, like Cassandra's
+/// `minTimeuuid()`. Selecting timeuuids greater or equal to it includes all timeuuids of the
+/// millisecond. Times before 1582-10-15, which timeuuids can't represent, give the lowest
+/// timeuuid.
+pub fn min_timeuuid(time: SystemTime) -> Uuid {
+    let ticks = ticks(time);
+    from_ticks(ticks - ticks % TICKS_PER_MILLI, MIN_CLOCK_SEQ_AND_NODE)
+}
+
+/// Returns the highest timeuuid of given time, at millisecond precision, like Cassandra's
+/// `maxTimeuuid()`. Selecting timeuuids less or equal to it includes all timeuuids of the
+/// millisecond. Times after the year 5236, which timeuuids can't represent, give the highest
+/// timeuuid.
+pub fn max_timeuuid(time: SystemTime) -> Uuid {
+    let ticks = ticks(time);
+    from_ticks(
+        (ticks - ticks % TICKS_PER_MILLI + TICKS_PER_MILLI - 1).min(MAX_TICKS),
+        MAX_CLOCK_SEQ_AND_NODE,
+    )
+}
+
+/// Returns the time of a timeuuid, or `None` if the UUID isn't a timeuuid.
+pub fn timeuuid_time(uuid: &Uuid) -> Option<SystemTime> {
+    if uuid.get_version_num() != 1 {
+        return None;
+    }
+
+    let ticks = reordered_msb(uuid) & 0x0FFF_FFFF_FFFF_FFFF;
+    let since_epoch = |ticks: u64| Duration::from_nanos(ticks * 100);
+    if ticks >= GREGORIAN_EPOCH_OFFSET {
+        Some(UNIX_EPOCH + since_epoch(ticks - GREGORIAN_EPOCH_OFFSET))
+    } else {
+        Some(UNIX_EPOCH - since_epoch(GREGORIAN_EPOCH_OFFSET - ticks))
+    }
+}
+
+/// Compares timeuuids the way Cassandra orders `timeuuid` columns: by time first, and then by
+/// clock sequence and node, compared as signed bytes. Unlike the ordering of `Uuid`, it sorts
+/// timeuuids chronologically.
+pub fn compare_timeuuids(left: &Uuid, right: &Uuid) -> Ordering {
+    (reordered_msb(left) as i64)
+        .cmp(&(reordered_msb(right) as i64))
+        .then_with(|| {
+            let left = left.as_bytes()[8..].iter().map(|byte| *byte as i8);
+            let right = right.as_bytes()[8..].iter().map(|byte| *byte as i8);
+            left.cmp(right)
+        })
+}
+
+/// Returns the number of 100 nanosecond intervals since the start of the Gregorian calendar,
+/// clamped to the range of timeuuids.
+fn ticks(time: SystemTime) -> u64 {
+    let ticks_of = |duration: Duration| u64::try_from(duration.as_nanos() / 100).ok();
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => ticks_of(elapsed)
+            .and_then(|ticks| ticks.checked_add(GREGORIAN_EPOCH_OFFSET))
+            .map_or(MAX_TICKS, |ticks| ticks.min(MAX_TICKS)),
+        Err(err) => ticks_of(err.duration())
+            .and_then(|ticks| GREGORIAN_EPOCH_OFFSET.checked_sub(ticks))
+            .unwrap_or(0),
+    }
+}
+
+fn from_ticks(ticks: u64, clock_seq_and_node: [u8; 8]) -> Uuid {
+    let mut bytes = [0; 16];
+    bytes[..4].copy_from_slice(&(ticks as u32).to_be_bytes());
+    bytes[4..6].copy_from_slice(&((ticks >> 32) as u16).to_be_bytes());
+    bytes[6..8].copy_from_slice(&(((ticks >> 48) as u16 & 0x0FFF) | 0x1000).to_be_bytes());
+    bytes[8..].copy_from_slice(&clock_seq_and_node);
+    Uuid::from_bytes(bytes)
+}
+
+/// Returns the version and time of a UUID, in this order.
+fn reordered_msb(uuid: &Uuid) -> u64 {
+    let bytes = uuid.as_bytes();
+    let time_low = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64;
+    let time_mid = u16::from_be_bytes([bytes[4], bytes[5]]) as u64;
+    let time_hi_and_version = u16::from_be_bytes([bytes[6], bytes[7]]) as u64;
+    (time_hi_and_version << 48) | (time_mid << 32) | time_low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_increasing_timeuuids() {
+        let generator = TimeUuidGenerator::with_node([1, 2, 3, 4, 5, 6], 0x1234);
+        let before = SystemTime::now();
+        let first = generator.generate();
+        let second = generator.generate();
+
+        assert_eq!(first.get_version_num(), 1);
+        assert_eq!(&first.as_bytes()[8..], &[0x92, 0x34, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(compare_timeuuids(&first, &second), Ordering::Less);
+        assert!(timeuuid_time(&first).unwrap() >= before - Duration::from_micros(1));
+    }
+
+    #[test]
+    fn reads_times_of_timeuuids() {
+        // 2013-01-01 00:05:00 UTC
+        let time = UNIX_EPOCH + Duration::from_millis(1_356_998_700_000);
+        let uuid = Uuid::parse_str("d2177dd0-eaa2-11de-a572-001b779c76e3").unwrap();
+
+        assert_eq!(
+            timeuuid_time(&uuid),
+            Some(UNIX_EPOCH + Duration::from_nanos(1_261_009_589_805_000_000))
+        );
+        assert_eq!(timeuuid_time(&min_timeuuid(time)), Some(time));
+        assert_eq!(
+            timeuuid_time(&max_timeuuid(time)),
+            Some(time + Duration::from_nanos(999_900))
+        );
+        let random = Uuid::parse_str("8ef2a5fc-1c4f-41ec-9621-0242ac130002").unwrap();
+        assert_eq!(timeuuid_time(&random), None);
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1_000);
+        assert_eq!(
+            timeuuid_time(&min_timeuuid(before_epoch)),
+            Some(before_epoch)
+        );
+
+        let gregorian_epoch = UNIX_EPOCH - Duration::from_nanos(GREGORIAN_EPOCH_OFFSET * 100);
+        let before_gregorian_epoch = gregorian_epoch - Duration::from_secs(86_400);
+        assert_eq!(
+            timeuuid_time(&min_timeuuid(before_gregorian_epoch)),
+            Some(gregorian_epoch)
+        );
+        let after_year_5236 = UNIX_EPOCH + Duration::from_secs(200_000_000_000);
+        assert_eq!(ticks(after_year_5236), MAX_TICKS);
+    }
+
+    #[test]
+    fn orders_timeuuids_like_cassandra() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_356_998_700_000);
+        let generator = TimeUuidGenerator::new();
+        let uuid = from_ticks(ticks(time) + 5, generator.clock_seq_and_node);
+
+        assert_eq!(
+            compare_timeuuids(&min_timeuuid(time), &uuid),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_timeuuids(&uuid, &max_timeuuid(time)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_timeuuids(
+                &max_timeuuid(time),
+                &min_timeuuid(time + Duration::from_millis(1))
+            ),
+            Ordering::Less
+        );
+
+        // clock sequences and nodes are compared as signed bytes
+        let low = from_ticks(ticks(time), [0x80, 0, 0, 0, 0, 0, 0, 0]);
+        let high = from_ticks(ticks(time), [0x7F, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(compare_timeuuids(&low, &high), Ordering::Less);
+        assert_eq!(low.cmp(&high), Ordering::Greater);
+    }
+}
@@ -1,16 +1,22 @@
+use std::collections::HashMap;
 use std::net::IpAddr;
 use time::PrimitiveDateTime;
 use uuid::Uuid;
 
-use crate::error::{column_is_empty_err, Error, Result};
+use crate::error::{column_not_found_err, Error, Result};
 use crate::frame::frame_result::{CTuple, ColType, ColTypeOption, ColTypeOptionValue};
+use crate::frame::{IntoBytes, TryFromTuple};
 use crate::types::blob::Blob;
 use crate::types::data_serialization_types::*;
 use crate::types::decimal::Decimal;
 use crate::types::list::List;
 use crate::types::map::Map;
+use crate::types::rows::Row;
 use crate::types::udt::UDT;
-use crate::types::{ByIndex, CBytes, IntoRustByIndex};
+use crate::types::value::{Bytes, Value};
+use crate::types::{
+    AsRustType, ByIndex, CBytes, FromCqlValue, FromElement, IntoRustByIndex, IntoRustByName,
+};
 
 use std::hash::{Hash, Hasher};
 
@@ -56,6 +62,89 @@ impl Tuple {
 
         Tuple { data: d }
     }
+
+    /// Returns the number of elements of the tuple.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the type of the element at given index, for reading tuples of unknown types.
+    pub fn element_type(&self, index: usize) -> Option<&ColTypeOption> {
+        self.data.get(index).map(|(col_type, _)| col_type)
+    }
+}
+
+/// Tuple value of any number and types of elements, for binding tuples whose types aren't
+/// known at compile time. Tuples of known types are bound as Rust tuples.
+///
+/// ```
+/// use cdrs_tokio::types::tuple::CqlTuple;
+/// use cdrs_tokio::types::value::Value;
+///
+/// let tuple = CqlTuple::new().with(1).with("text").with(None::<i64>);
+/// let value: Value = tuple.into();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CqlTuple {
+    elements: Vec<Value>,
+}
+
+impl CqlTuple {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends an element, which is null for `None`.
+    pub fn with<V: Into<Value>>(mut self, element: V) -> Self {
+        self.elements.push(element.into());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+impl From<Vec<Value>> for CqlTuple {
+    fn from(elements: Vec<Value>) -> Self {
+        CqlTuple { elements }
+    }
+}
+
+/// Copies elements of a read tuple, so it can be bound again.
+impl From<Tuple> for CqlTuple {
+    fn from(tuple: Tuple) -> Self {
+        let elements = tuple
+            .data
+            .into_iter()
+            .map(|(_, bytes)| match bytes.into_plain() {
+                Some(bytes) => Value::new_normal(Bytes::new(bytes)),
+                None => Value::new_null(),
+            })
+            .collect();
+
+        CqlTuple { elements }
+    }
+}
+
+impl From<CqlTuple> for Bytes {
+    fn from(tuple: CqlTuple) -> Bytes {
+        Bytes::new(
+            tuple
+                .elements
+                .iter()
+                .flat_map(IntoBytes::into_cbytes)
+                .collect(),
+        )
+    }
 }
 
 impl ByIndex for Tuple {}
@@ -63,6 +152,7 @@ impl ByIndex for Tuple {}
 into_rust_by_index!(Tuple, Blob);
 into_rust_by_index!(Tuple, String);
 into_rust_by_index!(Tuple, bool);
+into_rust_by_index!(Tuple, i128);
 into_rust_by_index!(Tuple, i64);
 into_rust_by_index!(Tuple, i32);
 into_rust_by_index!(Tuple, i16);
@@ -77,3 +167,260 @@ into_rust_by_index!(Tuple, UDT);
 into_rust_by_index!(Tuple, Tuple);
 into_rust_by_index!(Tuple, PrimitiveDateTime);
 into_rust_by_index!(Tuple, Decimal);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Tuple, chrono::DateTime<chrono::Utc>, super::chrono::as_date_time);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Tuple, chrono::NaiveDate, super::chrono::as_naive_date);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Tuple, chrono::NaiveTime, super::chrono::as_naive_time);
+#[cfg(feature = "chrono")]
+into_rust_by_index!(Tuple, chrono::Duration, super::chrono::as_duration);
+#[cfg(feature = "time")]
+into_rust_by_index!(Tuple, time03::OffsetDateTime, super::time03::as_offset_date_time);
+#[cfg(feature = "time")]
+into_rust_by_index!(Tuple, time03::Date, super::time03::as_date);
+#[cfg(feature = "time")]
+into_rust_by_index!(Tuple, time03::Time, super::time03::as_time);
+#[cfg(feature = "time")]
+into_rust_by_index!(Tuple, time03::Duration, super::time03::as_duration);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_index!(Tuple, bigdecimal::BigDecimal, super::bigdecimal::as_big_decimal);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_index!(Tuple, num_bigint::BigInt, super::bigdecimal::as_big_int);
+
+impl<T: FromCqlValue> IntoRustByIndex<T> for Tuple {
+    fn get_by_index(&self, index: usize) -> Result<Option<T>> {
+        let (col_type, value) = self
+            .data
+            .get(index)
+            .ok_or_else(|| column_not_found_err(index))?;
+        T::from_cql_value(col_type, value)
+    }
+}
+
+impl<T> IntoRustByIndex<Vec<T>> for Tuple
+where
+    List: AsRustType<Vec<T>>,
+{
+    fn get_by_index(&self, index: usize) -> Result<Option<Vec<T>>> {
+        match IntoRustByIndex::<List>::get_by_index(self, index)? {
+            Some(list) => list.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> IntoRustByIndex<HashMap<K, V>> for Tuple
+where
+    Map: AsRustType<HashMap<K, V>>,
+{
+    fn get_by_index(&self, index: usize) -> Result<Option<HashMap<K, V>>> {
+        match IntoRustByIndex::<Map>::get_by_index(self, index)? {
+            Some(map) => map.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Implements conversions between CQL tuples and Rust tuples with given element types and
+/// indexes. Elements are read by position and can't be null, while they're written as nulls
+/// for `None`. Rust tuples are read from rows, UDTs, tuples, lists, sets and map values.
+macro_rules! rust_tuple {
+    ($($element:ident $index:tt),+) => {
+        impl<$($element),+> TryFromTuple for ($($element,)+)
+        where
+            $(Tuple: IntoRustByIndex<$element>,)+
+        {
+            fn try_from_tuple(tuple: Tuple) -> Result<Self> {
+                let len = [$($index),+].len();
+                if tuple.len() != len {
+                    return Err(Error::General(format!(
+                        "Cannot convert tuple of {} elements into tuple of {} elements",
+                        tuple.len(),
+                        len
+                    )));
+                }
+
+                Ok(($(IntoRustByIndex::<$element>::get_r_by_index(&tuple, $index)?,)+))
+            }
+        }
+
+        impl<$($element: Into<Value>),+> From<($($element,)+)> for Bytes {
+            fn from(tuple: ($($element,)+)) -> Bytes {
+                let mut bytes = vec![];
+                $(bytes.extend(tuple.$index.into().into_cbytes());)+
+                Bytes::new(bytes)
+            }
+        }
+
+        impl<$($element),+> FromElement for ($($element,)+)
+        where
+            ($($element,)+): TryFromTuple,
+        {
+            fn from_element(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Self> {
+                TryFromTuple::try_from_tuple(Tuple::from_element(col_type, bytes)?)
+            }
+        }
+
+        impl<$($element),+> IntoRustByIndex<($($element,)+)> for Tuple
+        where
+            ($($element,)+): TryFromTuple,
+        {
+            fn get_by_index(&self, index: usize) -> Result<Option<($($element,)+)>> {
+                IntoRustByIndex::<Tuple>::get_by_index(self, index)?
+                    .map(TryFromTuple::try_from_tuple)
+                    .transpose()
+            }
+        }
+
+        impl<$($element),+> IntoRustByName<($($element,)+)> for Row
+        where
+            ($($element,)+): TryFromTuple,
+        {
+            fn get_by_name(&self, name: &str) -> Result<Option<($($element,)+)>> {
+                IntoRustByName::<Tuple>::get_by_name(self, name)?
+                    .map(TryFromTuple::try_from_tuple)
+                    .transpose()
+            }
+        }
+
+        impl<$($element),+> IntoRustByIndex<($($element,)+)> for Row
+        where
+            ($($element,)+): TryFromTuple,
+        {
+            fn get_by_index(&self, index: usize) -> Result<Option<($($element,)+)>> {
+                IntoRustByIndex::<Tuple>::get_by_index(self, index)?
+                    .map(TryFromTuple::try_from_tuple)
+                    .transpose()
+            }
+        }
+
+        impl<$($element),+> IntoRustByName<($($element,)+)> for UDT
+        where
+            ($($element,)+): TryFromTuple,
+        {
+            fn get_by_name(&self, name: &str) -> Result<Option<($($element,)+)>> {
+                IntoRustByName::<Tuple>::get_by_name(self, name)?
+                    .map(TryFromTuple::try_from_tuple)
+                    .transpose()
+            }
+        }
+
+        impl<$($element),+> AsRustType<Vec<($($element,)+)>> for List
+        where
+            ($($element,)+): TryFromTuple,
+        {
+            fn as_rust_type(&self) -> Result<Option<Vec<($($element,)+)>>> {
+                AsRustType::<Vec<Tuple>>::as_rust_type(self)?
+                    .map(|tuples| tuples.into_iter().map(TryFromTuple::try_from_tuple).collect())
+                    .transpose()
+            }
+        }
+
+        impl<Key: Eq + Hash, $($element),+> AsRustType<HashMap<Key, ($($element,)+)>> for Map
+        where
+            Map: AsRustType<HashMap<Key, Tuple>>,
+            ($($element,)+): TryFromTuple,
+        {
+            fn as_rust_type(&self) -> Result<Option<HashMap<Key, ($($element,)+)>>> {
+                AsRustType::<HashMap<Key, Tuple>>::as_rust_type(self)?
+                    .map(|tuples| {
+                        tuples
+                            .into_iter()
+                            .map(|(key, tuple)| {
+                                TryFromTuple::try_from_tuple(tuple).map(|value| (key, value))
+                            })
+                            .collect()
+                    })
+                    .transpose()
+            }
+        }
+    };
+}
+
+rust_tuple!(A 0);
+rust_tuple!(A 0, B 1);
+rust_tuple!(A 0, B 1, C 2);
+rust_tuple!(A 0, B 1, C 2, D 3);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+rust_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{col_type, encode};
+
+    fn tuple_type(types: Vec<ColTypeOption>) -> CTuple {
+        CTuple { types }
+    }
+
+    fn decode(value: CBytes, metadata: &CTuple) -> Tuple {
+        let bytes = value.as_slice().unwrap();
+        Tuple::new(decode_tuple(bytes, metadata.types.len()).unwrap(), metadata)
+    }
+
+    #[test]
+    fn converts_rust_tuples() {
+        let metadata = tuple_type(vec![col_type(ColType::Int), col_type(ColType::Varchar)]);
+        let tuple = decode(encode((1, "one")), &metadata);
+
+        assert_eq!(tuple.len(), 2);
+        assert!(matches!(
+            tuple.element_type(1).unwrap().id,
+            ColType::Varchar
+        ));
+        assert_eq!(
+            <(i32, String)>::try_from_tuple(tuple).unwrap(),
+            (1, "one".to_string())
+        );
+
+        let tuple = decode(encode((1, None::<&str>)), &metadata);
+        assert!(<(i32, String)>::try_from_tuple(tuple).is_err());
+
+        let tuple = decode(encode((1, "one")), &metadata);
+        assert!(<(i32,)>::try_from_tuple(tuple).is_err());
+    }
+
+    #[test]
+    fn converts_nested_tuples() {
+        let inner = tuple_type(vec![col_type(ColType::Bigint), col_type(ColType::Boolean)]);
+        let inner_type = ColTypeOption {
+            id: ColType::Tuple,
+            value: Some(ColTypeOptionValue::TupleType(inner)),
+        };
+        let metadata = tuple_type(vec![col_type(ColType::Int), inner_type.clone()]);
+
+        let value = (7, (8i64, true));
+        let tuple = decode(encode(value), &metadata);
+        assert_eq!(<(i32, (i64, bool))>::try_from_tuple(tuple).unwrap(), value);
+
+        let list = List::new(
+            vec![encode((1i64, false)), encode((2i64, true))],
+            ColTypeOption {
+                id: ColType::List,
+                value: Some(ColTypeOptionValue::CList(Box::new(inner_type))),
+            },
+        );
+        let list: Vec<(i64, bool)> = list.as_r_type().unwrap();
+        assert_eq!(list, vec![(1, false), (2, true)]);
+    }
+
+    #[test]
+    fn copies_read_tuples() {
+        let metadata = tuple_type(vec![col_type(ColType::Int), col_type(ColType::Varchar)]);
+        let tuple = decode(encode((1, None::<&str>)), &metadata);
+
+        let copy = CqlTuple::from(tuple);
+        assert_eq!(copy, CqlTuple::new().with(1).with(None::<&str>));
+        assert_eq!(
+            encode(copy).as_slice().unwrap(),
+            encode((1, None::<&str>)).as_slice().unwrap()
+        );
+    }
+}
@@ -1,18 +1,23 @@
 use std::collections::HashMap;
+use std::hash::Hash;
 use std::net::IpAddr;
 use std::num::{NonZeroI16, NonZeroI8, NonZeroI32, NonZeroI64};
 use time::PrimitiveDateTime;
 use uuid::Uuid;
 
-use crate::error::{column_is_empty_err, Error, Result};
+use crate::error::{column_not_found_err, Error, Result};
+use crate::frame::TryFromUDT;
 use crate::frame::frame_result::{CUdt, ColType, ColTypeOption, ColTypeOptionValue};
+use crate::frame::IntoBytes;
 use crate::types::blob::Blob;
+use crate::types::cql_value::CqlValue;
 use crate::types::data_serialization_types::*;
 use crate::types::decimal::Decimal;
 use crate::types::list::List;
 use crate::types::map::Map;
 use crate::types::tuple::Tuple;
-use crate::types::{ByName, CBytes, IntoRustByName};
+use crate::types::value::{Bytes, MaybeEmpty, Value};
+use crate::types::{AsRustType, ByName, CBytes, FromCqlValue, IntoRustByName};
 
 #[derive(Clone, Debug)]
 pub struct UDT {
@@ -35,6 +40,91 @@ impl UDT {
 
         UDT { data: d }
     }
+
+    /// Checks if the UDT has a field with given name.
+    pub fn contains_field(&self, name: &str) -> bool {
+        self.data.contains_key(name)
+    }
+}
+
+/// UDT value built field by field and checked against the type of the UDT, for binding UDTs
+/// without deriving `IntoCDRSValue`, e.g. when they aren't known at compile time. The type is
+/// e.g. the type of a column read with rows. Fields which aren't set are null.
+///
+/// ```
+/// use cdrs_tokio::error::Result;
+/// use cdrs_tokio::frame::frame_result::CUdt;
+/// use cdrs_tokio::types::udt::UdtBuilder;
+/// use cdrs_tokio::types::value::Value;
+///
+/// fn address(address_type: &CUdt) -> Result<Value> {
+///     let address = UdtBuilder::new(address_type)
+///         .field("street", "Main St")?
+///         .field("number", 12)?
+///         .build();
+///     Ok(address.into())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UdtBuilder {
+    udt_type: CUdt,
+    fields: Vec<Value>,
+}
+
+impl UdtBuilder {
+    pub fn new(udt_type: &CUdt) -> Self {
+        UdtBuilder {
+            udt_type: udt_type.clone(),
+            fields: vec![Value::new_null(); udt_type.descriptions.len()],
+        }
+    }
+
+    /// Sets a field, which is null for `None`. Values carry their CQL type, e.g. `i32` is an
+    /// `int`, and it fails if the UDT has no field of given name or if the value isn't a value of
+    /// the field's type (see `CqlValue::encode()`).
+    pub fn field<V: Into<CqlValue>>(mut self, name: &str, value: V) -> Result<Self> {
+        let udt_name = self.udt_type.udt_name.as_str();
+        let (index, col_type) = self
+            .udt_type
+            .descriptions
+            .iter()
+            .enumerate()
+            .find(|(_, (field, _))| field.as_str() == name)
+            .map(|(index, (_, col_type))| (index, col_type))
+            .ok_or_else(|| {
+                let fields: Vec<&str> = self
+                    .udt_type
+                    .descriptions
+                    .iter()
+                    .map(|(field, _)| field.as_str())
+                    .collect();
+                Error::General(format!(
+                    "UDT {} has no field {} (fields: {})",
+                    udt_name,
+                    name,
+                    fields.join(", ")
+                ))
+            })?;
+
+        self.fields[index] = value.into().encode(col_type).map_err(|err| {
+            Error::General(format!(
+                "Invalid value of field {} of UDT {}: {}",
+                name, udt_name, err
+            ))
+        })?;
+        Ok(self)
+    }
+
+    /// Returns the UDT value, to be bound to queries.
+    pub fn build(self) -> Bytes {
+        self.into()
+    }
+}
+
+impl From<UdtBuilder> for Bytes {
+    fn from(udt: UdtBuilder) -> Bytes {
+        Bytes::new(udt.fields.iter().flat_map(IntoBytes::into_cbytes).collect())
+    }
 }
 
 impl ByName for UDT {}
@@ -42,6 +132,7 @@ impl ByName for UDT {}
 into_rust_by_name!(UDT, Blob);
 into_rust_by_name!(UDT, String);
 into_rust_by_name!(UDT, bool);
+into_rust_by_name!(UDT, i128);
 into_rust_by_name!(UDT, i64);
 into_rust_by_name!(UDT, i32);
 into_rust_by_name!(UDT, i16);
@@ -60,3 +151,133 @@ into_rust_by_name!(UDT, NonZeroI8);
 into_rust_by_name!(UDT, NonZeroI16);
 into_rust_by_name!(UDT, NonZeroI32);
 into_rust_by_name!(UDT, NonZeroI64);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(UDT, chrono::DateTime<chrono::Utc>, super::chrono::as_date_time);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(UDT, chrono::NaiveDate, super::chrono::as_naive_date);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(UDT, chrono::NaiveTime, super::chrono::as_naive_time);
+#[cfg(feature = "chrono")]
+into_rust_by_name!(UDT, chrono::Duration, super::chrono::as_duration);
+#[cfg(feature = "time")]
+into_rust_by_name!(UDT, time03::OffsetDateTime, super::time03::as_offset_date_time);
+#[cfg(feature = "time")]
+into_rust_by_name!(UDT, time03::Date, super::time03::as_date);
+#[cfg(feature = "time")]
+into_rust_by_name!(UDT, time03::Time, super::time03::as_time);
+#[cfg(feature = "time")]
+into_rust_by_name!(UDT, time03::Duration, super::time03::as_duration);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_name!(UDT, bigdecimal::BigDecimal, super::bigdecimal::as_big_decimal);
+#[cfg(feature = "bigdecimal")]
+into_rust_by_name!(UDT, num_bigint::BigInt, super::bigdecimal::as_big_int);
+
+impl<T: TryFromUDT> FromCqlValue for T {
+    fn from_cql_value(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<Self>> {
+        as_rust_type!(col_type, bytes, UDT)?
+            .map(T::try_from_udt)
+            .transpose()
+    }
+}
+
+impl<T: FromCqlValue> IntoRustByName<T> for UDT {
+    fn get_by_name(&self, name: &str) -> Result<Option<T>> {
+        let (col_type, value) = self
+            .data
+            .get(name)
+            .ok_or_else(|| column_not_found_err(name))?;
+        T::from_cql_value(col_type, value)
+    }
+}
+
+impl<T> IntoRustByName<MaybeEmpty<T>> for UDT
+where
+    UDT: IntoRustByName<T>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<MaybeEmpty<T>>> {
+        match self.data.get(name) {
+            Some((_, value)) if value.is_empty() => Ok(Some(MaybeEmpty::Empty)),
+            _ => IntoRustByName::<T>::get_by_name(self, name).map(|v| v.map(MaybeEmpty::Value)),
+        }
+    }
+}
+
+impl<T> IntoRustByName<Vec<T>> for UDT
+where
+    List: AsRustType<Vec<T>>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<Vec<T>>> {
+        match IntoRustByName::<List>::get_by_name(self, name)? {
+            Some(list) => list.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> IntoRustByName<HashMap<K, V>> for UDT
+where
+    Map: AsRustType<HashMap<K, V>>,
+{
+    fn get_by_name(&self, name: &str) -> Result<Option<HashMap<K, V>>> {
+        match IntoRustByName::<Map>::get_by_name(self, name)? {
+            Some(map) => map.as_rust_type(),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CString;
+
+    fn udt_type() -> CUdt {
+        let field = |name: &str, id| {
+            (
+                CString::new(name.to_string()),
+                ColTypeOption { id, value: None },
+            )
+        };
+        CUdt {
+            ks: CString::new("ks".to_string()),
+            udt_name: CString::new("address".to_string()),
+            descriptions: vec![
+                field("street", ColType::Varchar),
+                field("number", ColType::Int),
+                field("flat", ColType::Smallint),
+            ],
+        }
+    }
+
+    #[test]
+    fn builds_udt_values() {
+        let udt_type = udt_type();
+        let value = UdtBuilder::new(&udt_type)
+            .field("number", 12)
+            .unwrap()
+            .field("street", "Main St")
+            .unwrap()
+            .build();
+
+        let fields = decode_udt(&Value::new_normal(value).body, 3).unwrap();
+        let udt = UDT::new(fields, &udt_type);
+        assert_eq!(udt.r_by_name::<String>("street").unwrap(), "Main St");
+        assert_eq!(udt.r_by_name::<i32>("number").unwrap(), 12);
+        assert_eq!(udt.by_name::<i16>("flat").unwrap(), None);
+    }
+
+    #[test]
+    fn checks_fields_against_udt_type() {
+        let udt_type = udt_type();
+        let builder = UdtBuilder::new(&udt_type);
+
+        assert!(builder.clone().field("floor", 1).is_err());
+        assert!(builder.clone().field("number", "12").is_err());
+        assert!(builder.clone().field("number", 12i64).is_err());
+        assert!(builder.clone().field("number", 12.0f32).is_err());
+        assert!(builder.clone().field("flat", 12).is_err());
+        assert!(builder.clone().field("street", Blob::new(vec![0xFF])).is_err());
+        assert!(builder.clone().field("flat", None::<i16>).is_ok());
+        assert!(builder.field("flat", CqlValue::Empty).is_ok());
+    }
+}
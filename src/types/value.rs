@@ -1,5 +1,5 @@
 use std::cmp::Eq;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::Into;
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -68,6 +68,78 @@ impl Value {
             value_type: ValueType::NotSet,
         }
     }
+
+    /// Checks if the value is unset, i.e. leaves the bound column unchanged.
+    pub fn is_unset(&self) -> bool {
+        self.value_type == ValueType::NotSet
+    }
+}
+
+/// Unset bound value, which leaves the column unchanged instead of writing a null, so partial
+/// updates through prepared queries don't create tombstones. Converts into
+/// `Value::new_not_set()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Unset;
+
+impl From<Unset> for Value {
+    fn from(_: Unset) -> Value {
+        Value::new_not_set()
+    }
+}
+
+/// Bound value which is either set or left unset, e.g. for optional fields of partial updates.
+/// Unlike `Option`, which binds `None` as a null, `MaybeUnset::Unset` leaves the column
+/// unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaybeUnset<T> {
+    Set(T),
+    Unset,
+}
+
+impl<T> From<Option<T>> for MaybeUnset<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => MaybeUnset::Set(value),
+            None => MaybeUnset::Unset,
+        }
+    }
+}
+
+impl<T: Into<Value>> From<MaybeUnset<T>> for Value {
+    fn from(value: MaybeUnset<T>) -> Value {
+        match value {
+            MaybeUnset::Set(value) => value.into(),
+            MaybeUnset::Unset => Value::new_not_set(),
+        }
+    }
+}
+
+/// Value of a type without an empty representation in Rust, e.g. `int`, which can also be
+/// empty, i.e. of zero length, as opposed to null. Empty values of such types can only be read
+/// as `MaybeEmpty::Empty`, which is bound as an empty value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaybeEmpty<T> {
+    Empty,
+    Value(T),
+}
+
+impl<T> MaybeEmpty<T> {
+    /// Returns the value, or `None` if it's empty.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            MaybeEmpty::Empty => None,
+            MaybeEmpty::Value(value) => Some(value),
+        }
+    }
+}
+
+impl<T: Into<Bytes>> From<MaybeEmpty<T>> for Bytes {
+    fn from(value: MaybeEmpty<T>) -> Bytes {
+        match value {
+            MaybeEmpty::Empty => Bytes::new(vec![]),
+            MaybeEmpty::Value(value) => value.into(),
+        }
+    }
 }
 
 impl IntoBytes for Value {
@@ -103,6 +175,12 @@ impl Bytes {
     }
 }
 
+impl<T: ToCqlValue> From<T> for Bytes {
+    fn from(value: T) -> Bytes {
+        value.to_cql_value()
+    }
+}
+
 impl Into<Bytes> for String {
     fn into(self) -> Bytes {
         Bytes(self.into_bytes())
@@ -139,6 +217,13 @@ impl Into<Bytes> for i64 {
     }
 }
 
+/// `i128` values are bound as `varint`, as no other CQL integer holds them.
+impl From<i128> for Bytes {
+    fn from(value: i128) -> Bytes {
+        Bytes(to_varint_i128(value))
+    }
+}
+
 impl Into<Bytes> for u8 {
     fn into(self) -> Bytes {
         Bytes(vec![self])
@@ -247,16 +332,47 @@ impl Into<Bytes> for Decimal {
     }
 }
 
+/// Encodes elements of a list or set.
+fn encode_elements<T: Into<Bytes>>(len: usize, elements: impl Iterator<Item = T>) -> Bytes {
+    let mut bytes = to_int(len as i32);
+    for element in elements {
+        bytes.extend_from_slice(&Value::new_normal(element).into_cbytes());
+    }
+    Bytes(bytes)
+}
+
+/// Encodes entries of a map.
+fn encode_entries<K, V>(len: usize, entries: impl Iterator<Item = (K, V)>) -> Bytes
+where
+    K: Into<Bytes>,
+    V: Into<Bytes>,
+{
+    let mut bytes = to_int(len as i32);
+    for (key, value) in entries {
+        bytes.extend_from_slice(&Value::new_normal(key).into_cbytes());
+        bytes.extend_from_slice(&Value::new_normal(value).into_cbytes());
+    }
+    Bytes(bytes)
+}
+
 impl<T: Into<Bytes> + Clone + Debug> From<Vec<T>> for Bytes {
     fn from(vec: Vec<T>) -> Bytes {
-        let mut bytes: Vec<u8> = vec![];
-        bytes.extend_from_slice(to_int(vec.len() as i32).as_slice());
-        bytes = vec.iter().fold(bytes, |mut acc, v| {
-            let b: Bytes = v.clone().into();
-            acc.extend_from_slice(Value::new_normal(b).into_cbytes().as_slice());
-            acc
-        });
-        Bytes(bytes)
+        encode_elements(vec.len(), vec.into_iter())
+    }
+}
+
+/// Sets are bound as `set` or `list` values.
+impl<T: Into<Bytes>> From<HashSet<T>> for Bytes {
+    fn from(set: HashSet<T>) -> Bytes {
+        encode_elements(set.len(), set.into_iter())
+    }
+}
+
+/// Elements are bound in their order, which is the order of `set` values of types which
+/// Cassandra orders the same way, e.g. `int` or `text`.
+impl<T: Into<Bytes>> From<BTreeSet<T>> for Bytes {
+    fn from(set: BTreeSet<T>) -> Bytes {
+        encode_elements(set.len(), set.into_iter())
     }
 }
 
@@ -266,16 +382,13 @@ where
     V: Into<Bytes> + Clone + Debug,
 {
     fn from(map: HashMap<K, V>) -> Bytes {
-        let mut bytes: Vec<u8> = vec![];
-        bytes.extend_from_slice(to_int(map.len() as i32).as_slice());
-        bytes = map.iter().fold(bytes, |mut acc, (k, v)| {
-            let key_bytes: Bytes = k.clone().into();
-            let val_bytes: Bytes = v.clone().into();
-            acc.extend_from_slice(Value::new_normal(key_bytes).into_cbytes().as_slice());
-            acc.extend_from_slice(Value::new_normal(val_bytes).into_cbytes().as_slice());
-            acc
-        });
-        Bytes(bytes)
+        encode_entries(map.len(), map.into_iter())
+    }
+}
+
+impl<K: Into<Bytes>, V: Into<Bytes>> From<BTreeMap<K, V>> for Bytes {
+    fn from(map: BTreeMap<K, V>) -> Bytes {
+        encode_entries(map.len(), map.into_iter())
     }
 }
 
@@ -328,7 +441,7 @@ mod tests {
     #[test]
     fn test_new_null_value() {
         let null_value = Value::new_null();
-        assert_eq!(null_value.body, vec![]);
+        assert_eq!(null_value.body, Vec::<u8>::new());
         match null_value.value_type {
             ValueType::Null => assert!(true),
             _ => unreachable!(),
@@ -338,13 +451,52 @@ mod tests {
     #[test]
     fn test_new_not_set_value() {
         let not_set_value = Value::new_not_set();
-        assert_eq!(not_set_value.body, vec![]);
+        assert_eq!(not_set_value.body, Vec::<u8>::new());
         match not_set_value.value_type {
             ValueType::NotSet => assert!(true),
             _ => unreachable!(),
         }
     }
 
+    #[test]
+    fn test_unset_values() {
+        assert!(Value::from(Unset).is_unset());
+        assert!(Value::from(MaybeUnset::<i32>::Unset).is_unset());
+        assert!(Value::from(MaybeUnset::from(None::<i32>)).is_unset());
+        assert_eq!(Value::from(MaybeUnset::Set(1i32)), Value::from(1i32));
+        assert_eq!(
+            Value::from(MaybeUnset::Set(None::<i32>)).value_type,
+            ValueType::Null
+        );
+        assert_eq!(Value::from(Unset).into_cbytes(), vec![255, 255, 255, 254]);
+    }
+
+    #[test]
+    fn test_maybe_empty_values() {
+        assert_eq!(
+            Value::from(MaybeEmpty::<i32>::Empty).into_cbytes(),
+            vec![0, 0, 0, 0]
+        );
+        assert_eq!(Value::from(MaybeEmpty::Value(1i32)), Value::from(1i32));
+        assert_eq!(MaybeEmpty::Value(1).into_option(), Some(1));
+        assert_eq!(MaybeEmpty::<i32>::Empty.into_option(), None);
+    }
+
+    #[test]
+    fn test_collection_values() {
+        let list = Value::from(vec![1i32, 2]);
+        let set: BTreeSet<i32> = vec![2, 1].into_iter().collect();
+        assert_eq!(Value::from(set), list);
+        let set: HashSet<i32> = vec![1].into_iter().collect();
+        assert_eq!(Value::from(set), Value::from(vec![1i32]));
+
+        let map: BTreeMap<&str, i8> = vec![("a", 1)].into_iter().collect();
+        assert_eq!(
+            Value::from(map).into_cbytes(),
+            vec![0, 0, 0, 14, 0, 0, 0, 1, 0, 0, 0, 1, b'a', 0, 0, 0, 1, 1]
+        );
+    }
+
     #[test]
     fn test_value_into_cbytes() {
         let value = Value::new_normal(1 as u8);
@@ -0,0 +1,157 @@
+//! Cassandra 5 `vector<type, dimension>` values, which are sent as custom types. Vectors of
+//! fixed size elements, e.g. `vector<float, n>`, are read as lists, so they're converted into
+//! `Vec` and fixed-size arrays, and bound as `CqlVector`.
+
+use std::convert::TryInto;
+
+use crate::error::{Error, Result};
+use crate::frame::frame_result::{ColType, ColTypeOption, ColTypeOptionValue};
+use crate::types::list::List;
+use crate::types::value::Bytes;
+use crate::types::CBytes;
+
+const VECTOR_TYPE: &str = "org.apache.cassandra.db.marshal.VectorType";
+
+/// Vector value, bound as a `vector` of the same dimension. Vectors are read as `Vec`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CqlVector<T>(pub Vec<T>);
+
+/// Returns the type and size of elements and the dimension of a vector type, given by its
+/// class, or `None` if the class isn't a vector of supported elements.
+pub(crate) fn parse_vector_type(class: &str) -> Option<(ColType, usize, usize)> {
+    let (element, dimension) = class
+        .strip_prefix(VECTOR_TYPE)?
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .rsplit_once(',')?;
+
+    let (element, size) = match element.trim().rsplit('.').next()? {
+        "FloatType" => (ColType::Float, 4),
+        "DoubleType" => (ColType::Double, 8),
+        "Int32Type" => (ColType::Int, 4),
+        "LongType" => (ColType::Bigint, 8),
+        _ => return None,
+    };
+
+    Some((element, size, dimension.trim().parse().ok()?))
+}
+
+/// Reads a vector value as a list of its elements, checking its dimension.
+pub(crate) fn as_list(col_type: &ColTypeOption, bytes: &CBytes) -> Result<Option<List>> {
+    let class = match col_type.value {
+        Some(ColTypeOptionValue::CString(ref class)) => class.as_str(),
+        _ => "",
+    };
+    let (element, size, dimension) = parse_vector_type(class).ok_or_else(|| {
+        Error::General(format!(
+            "Invalid conversion. Cannot convert {} into List (valid types: List, Set, vector).",
+            class
+        ))
+    })?;
+
+    let bytes = match bytes.non_empty_slice()? {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+    if bytes.len() != size * dimension {
+        return Err(Error::General(format!(
+            "Vector of {} bytes doesn't have {} elements of {} bytes",
+            bytes.len(),
+            dimension,
+            size
+        )));
+    }
+
+    let data = bytes
+        .chunks(size)
+        .map(|element| CBytes::new(element.to_vec()))
+        .collect();
+    let metadata = ColTypeOption {
+        id: ColType::List,
+        value: Some(ColTypeOptionValue::CList(Box::new(ColTypeOption {
+            id: element,
+            value: None,
+        }))),
+    };
+
+    Ok(Some(List::new(data, metadata)))
+}
+
+/// Converts elements of a vector or list into an array of the same length.
+pub(crate) fn into_array<T, const N: usize>(elements: Vec<T>) -> Result<[T; N]> {
+    elements.try_into().map_err(|elements: Vec<T>| {
+        Error::General(format!(
+            "Cannot convert {} elements into array of {} elements",
+            elements.len(),
+            N
+        ))
+    })
+}
+
+macro_rules! vector_element {
+    ($element:ty) => {
+        impl From<CqlVector<$element>> for Bytes {
+            fn from(vector: CqlVector<$element>) -> Bytes {
+                Bytes::new(vector.0.iter().flat_map(|e| e.to_be_bytes()).collect())
+            }
+        }
+    };
+}
+
+vector_element!(f32);
+vector_element!(f64);
+vector_element!(i32);
+vector_element!(i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::value::Value;
+    use crate::types::{AsRustType, CString};
+
+    fn vector_class(element: &str, dimension: &str) -> String {
+        format!(
+            "{}(org.apache.cassandra.db.marshal.{},{})",
+            VECTOR_TYPE, element, dimension
+        )
+    }
+
+    #[test]
+    fn parses_vector_types() {
+        assert!(matches!(
+            parse_vector_type(&vector_class("FloatType", " 3")),
+            Some((ColType::Float, 4, 3))
+        ));
+        assert!(matches!(
+            parse_vector_type(&vector_class("LongType", "2")),
+            Some((ColType::Bigint, 8, 2))
+        ));
+        assert!(parse_vector_type(&vector_class("UTF8Type", "3")).is_none());
+        assert!(parse_vector_type("org.apache.cassandra.db.marshal.DurationType").is_none());
+    }
+
+    #[test]
+    fn converts_float_vectors() {
+        let col_type = ColTypeOption {
+            id: ColType::Custom,
+            value: Some(ColTypeOptionValue::CString(CString::new(vector_class(
+                "FloatType",
+                "3",
+            )))),
+        };
+        assert_eq!(col_type.to_string(), "vector<float, 3>");
+        let vector = [0.5f32, -1.0, 2.25];
+        let bytes = CBytes::new(Value::new_normal(CqlVector(vector.to_vec())).body);
+        assert_eq!(bytes.as_slice().unwrap().len(), 12);
+
+        let list = as_list(&col_type, &bytes).unwrap().unwrap();
+        let elements: Vec<f32> = list.as_r_type().unwrap();
+        assert_eq!(elements, vector.to_vec());
+        assert_eq!(into_array::<_, 3>(elements.clone()).unwrap(), vector);
+        assert!(into_array::<_, 2>(elements).is_err());
+
+        let short = CBytes::new(Value::new_normal(CqlVector(vec![1.0f32, 2.0])).body);
+        assert!(as_list(&col_type, &short).is_err());
+        assert!(as_list(&col_type, &CBytes::new_empty()).unwrap().is_none());
+    }
+}